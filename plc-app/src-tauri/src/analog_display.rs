@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::database::AnalogDisplay;
+
+// Mostradores analógicos do painel: a conversão WORD -> valor de engenharia
+// (escala, offset, sinal, faixa) e a formatação ("Nível: 3.2 m") ficam no
+// backend; o painel só exibe o que chega no evento plc-data.
+
+const NO_DATA: &str = "---";
+
+/// Valor calculado de um mostrador, pronto para exibição
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalogReading {
+    pub id: i64,
+    pub label: String,
+    pub value: Option<f64>,   // Valor limitado a min/max (None = WORD ainda não recebida)
+    pub raw: Option<f64>,     // WORD recebida do PLC
+    pub unit: String,
+    pub formatted: String,    // "Nível: 3.2 m"
+    pub percent: f64,         // Posição na faixa min/max (0-100), para barras/gauges
+    pub out_of_range: bool,   // Valor calculado estava fora de min/max
+}
+
+fn format_value(value: Option<f64>, decimals: i32) -> String {
+    match value {
+        Some(value) => format!("{:.*}", decimals.clamp(0, 6) as usize, value),
+        None => NO_DATA.to_string(),
+    }
+}
+
+/// Converte a WORD recebida no valor do mostrador
+pub fn evaluate(display: &AnalogDisplay, raw: Option<f64>) -> AnalogReading {
+    let (min, max) = (display.min_value.min(display.max_value), display.min_value.max(display.max_value));
+    let scaled = raw.map(|raw| {
+        let raw = raw as u16;
+        let word = if display.signed { raw as i16 as f64 } else { raw as f64 };
+        word * display.scale + display.offset
+    });
+    let out_of_range = scaled.is_some_and(|v| v < min || v > max);
+    let value = scaled.map(|v| v.clamp(min, max));
+    let percent = match value {
+        Some(v) if max > min => (v - min) / (max - min) * 100.0,
+        _ => 0.0,
+    };
+
+    let number = format_value(value, display.decimals);
+    let formatted = match (display.label.trim(), display.unit.trim()) {
+        ("", "") => number,
+        ("", unit) => format!("{} {}", number, unit),
+        (label, "") => format!("{}: {}", label, number),
+        (label, unit) => format!("{}: {} {}", label, number, unit),
+    };
+
+    AnalogReading {
+        id: display.id,
+        label: display.label.clone(),
+        value,
+        raw,
+        unit: display.unit.clone(),
+        formatted,
+        percent,
+        out_of_range,
+    }
+}
+
+/// Calcula todos os mostradores habilitados a partir das variáveis do PlcData
+pub fn evaluate_all(displays: &[AnalogDisplay], variables: &HashMap<String, f64>) -> Vec<AnalogReading> {
+    displays.iter()
+        .filter(|d| d.enabled)
+        .map(|d| evaluate(d, variables.get(&format!("Word[{}]", d.word_index)).copied()))
+        .collect()
+}
+
+pub fn validate(display: &AnalogDisplay) -> Result<(), String> {
+    if display.label.trim().is_empty() && display.unit.trim().is_empty() {
+        return Err("Informe o rótulo ou a unidade do mostrador".to_string());
+    }
+    if !(0..128).contains(&display.word_index) {
+        return Err(format!("WORD inválida: {} (0-127)", display.word_index));
+    }
+    if display.scale == 0.0 || !display.scale.is_finite() || !display.offset.is_finite() {
+        return Err("Escala/offset inválidos".to_string());
+    }
+    if !display.min_value.is_finite() || !display.max_value.is_finite() || display.min_value >= display.max_value {
+        return Err("Valor mínimo deve ser menor que o máximo".to_string());
+    }
+    if !(0..=6).contains(&display.decimals) {
+        return Err(format!("Casas decimais inválidas: {} (0-6)", display.decimals));
+    }
+    Ok(())
+}
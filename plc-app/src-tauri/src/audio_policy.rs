@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use crate::database::{Database, VideoConfig};
+
+// Áudio dos vídeos de publicidade. Cada vídeo tem volume (0-100) e mudo próprios;
+// o horário de silêncio global (ex: 22:00-07:00, turno da noite da eclusa)
+// limita todos os vídeos a `max_volume` (0 = mudo). O backend calcula o áudio
+// efetivo de cada vídeo e envia no evento "audio-policy" sempre que ele muda
+// (entrada/saída do horário ou alteração de configuração): o painel só aplica.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const FULL_VOLUME: i32 = 100;
+
+// Chaves em display_configs
+pub const KEY_QUIET_ENABLED: &str = "quiet_hours_enabled";
+pub const KEY_QUIET_START: &str = "quiet_hours_start";
+pub const KEY_QUIET_END: &str = "quiet_hours_end";
+pub const KEY_QUIET_MAX_VOLUME: &str = "quiet_hours_max_volume";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHoursConfig {
+    pub enabled: bool,
+    pub start: String,    // "HH:MM" (hora local)
+    pub end: String,      // "HH:MM"; antes de `start` = atravessa a meia-noite
+    pub max_volume: i32,  // Volume máximo durante o horário (0 = mudo)
+}
+
+impl QuietHoursConfig {
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            enabled: db.get_display_config(KEY_QUIET_ENABLED).await?.map(|v| v == "true").unwrap_or(false),
+            start: db.get_display_config(KEY_QUIET_START).await?.unwrap_or_else(|| "22:00".to_string()),
+            end: db.get_display_config(KEY_QUIET_END).await?.unwrap_or_else(|| "07:00".to_string()),
+            max_volume: db.get_display_config(KEY_QUIET_MAX_VOLUME).await?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+        })
+    }
+
+    pub async fn save(&self, db: &Database) -> Result<(), sqlx::Error> {
+        db.set_display_config(KEY_QUIET_ENABLED, if self.enabled { "true" } else { "false" }, "boolean").await?;
+        db.set_display_config(KEY_QUIET_START, self.start.trim(), "text").await?;
+        db.set_display_config(KEY_QUIET_END, self.end.trim(), "text").await?;
+        db.set_display_config(KEY_QUIET_MAX_VOLUME, &self.max_volume.to_string(), "number").await?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let start = parse_time(&self.start).ok_or_else(|| format!("Início inválido: '{}' (use HH:MM)", self.start))?;
+        let end = parse_time(&self.end).ok_or_else(|| format!("Fim inválido: '{}' (use HH:MM)", self.end))?;
+        if start == end {
+            return Err("Início e fim do horário de silêncio não podem ser iguais".to_string());
+        }
+        validate_volume(self.max_volume)
+    }
+
+    /// Horário de silêncio em vigor às `now` (hora local)
+    pub fn is_quiet_at(&self, now: NaiveTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start < end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+pub fn validate_volume(volume: i32) -> Result<(), String> {
+    if !(0..=FULL_VOLUME).contains(&volume) {
+        return Err(format!("Volume inválido: {} (0-100)", volume));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VideoAudio {
+    pub id: i64,
+    pub volume: i32,  // Volume efetivo (0-100)
+    pub muted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioPolicy {
+    pub quiet: bool,          // Horário de silêncio em vigor
+    pub max_volume: i32,      // Limite atual (100 fora do horário)
+    pub quiet_start: String,
+    pub quiet_end: String,
+    pub videos: Vec<VideoAudio>,
+}
+
+pub type AudioPolicyState = Arc<Mutex<Option<AudioPolicy>>>;
+
+/// Áudio efetivo de um vídeo sob o limite atual
+fn effective_audio(video: &VideoConfig, max_volume: i32) -> VideoAudio {
+    let volume = video.volume.clamp(0, FULL_VOLUME).min(max_volume);
+    VideoAudio { id: video.id, volume, muted: video.muted || volume == 0 }
+}
+
+pub async fn compute_policy(db: &Database) -> Result<AudioPolicy, sqlx::Error> {
+    let config = QuietHoursConfig::load(db).await?;
+    let quiet = config.is_quiet_at(Local::now().time());
+    let max_volume = if quiet { config.max_volume.clamp(0, FULL_VOLUME) } else { FULL_VOLUME };
+    let videos = db.get_all_videos().await?
+        .iter()
+        .map(|video| effective_audio(video, max_volume))
+        .collect();
+    Ok(AudioPolicy { quiet, max_volume, quiet_start: config.start, quiet_end: config.end, videos })
+}
+
+/// Recalcula a política e emite "audio-policy" se ela mudou
+pub async fn refresh_policy(app_handle: &AppHandle, db: &Database, state: &AudioPolicyState) -> Result<AudioPolicy, sqlx::Error> {
+    let policy = compute_policy(db).await?;
+    let mut current = state.lock().await;
+    if current.as_ref() != Some(&policy) {
+        if current.as_ref().map(|p| p.quiet) != Some(policy.quiet) {
+            if policy.quiet {
+                println!("🔇 Horário de silêncio ativo ({}-{}): volume máximo {}", policy.quiet_start, policy.quiet_end, policy.max_volume);
+            } else {
+                println!("🔊 Fora do horário de silêncio: volume de cada vídeo");
+            }
+        }
+        let _ = app_handle.emit("audio-policy", &policy);
+        *current = Some(policy.clone());
+    }
+    Ok(policy)
+}
+
+/// Confere o horário de silêncio periodicamente (entrada/saída sem ação do operador)
+pub fn start_audio_policy(app_handle: AppHandle, database: Arc<Mutex<Option<Arc<Database>>>>, state: AudioPolicyState) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(db) = database.lock().await.clone() else { continue };
+            if let Err(e) = refresh_policy(&app_handle, &db, &state).await {
+                eprintln!("⚠️ Erro ao calcular política de áudio: {:?}", e);
+            }
+        }
+    });
+}
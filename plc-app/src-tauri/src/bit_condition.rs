@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+// Condições de exibição das mensagens: combinações de bits das WORDs do PLC.
+//
+//   Word[3].2 AND Word[1].0
+//   (Word[0].3 OR Word[0].4) AND NOT Word[0].1
+//
+// Operadores: AND / OR / NOT (ou && / || / !), parênteses. Precedência: NOT > AND > OR.
+// Condição vazia = comportamento antigo (apenas o bit word_index.bit_index do BitConfig).
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Bit { word: usize, bit: u8 },
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Bit(usize, u8),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\r' | '\n' => i += 1,
+            '(' => { tokens.push(Token::Open); i += 1; }
+            ')' => { tokens.push(Token::Close); i += 1; }
+            '!' => { tokens.push(Token::Not); i += 1; }
+            '&' | '|' => {
+                if chars.get(i + 1) != Some(&c) {
+                    return Err(format!("Operador incompleto na posição {}: use {}{}", i + 1, c, c));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+                i += 2;
+            }
+            _ if c.is_ascii_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '[' | ']' | '.' | '_')) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                match word.to_ascii_uppercase().as_str() {
+                    "AND" | "E" => tokens.push(Token::And),
+                    "OR" | "OU" => tokens.push(Token::Or),
+                    "NOT" | "NAO" => tokens.push(Token::Not),
+                    _ => {
+                        let (word_index, bit_index) = parse_bit_ref(&word)
+                            .ok_or_else(|| format!("Referência inválida '{}' (use Word[N].B)", word))?;
+                        tokens.push(Token::Bit(word_index, bit_index));
+                    }
+                }
+            }
+            _ => return Err(format!("Caractere inesperado '{}' na posição {}", c, i + 1)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// "Word[3].2" -> (3, 2)
+pub fn parse_bit_ref(text: &str) -> Option<(usize, u8)> {
+    let (word_part, bit_part) = text.split_once("].")?;
+    let word_index = word_part.strip_prefix("Word[")?.parse().ok()?;
+    let bit_index: u8 = bit_part.parse().ok()?;
+    (bit_index < 16).then_some((word_index, bit_index))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Condition::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Condition::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Condition, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Condition::Not(Box::new(self.parse_unary()?))),
+            Some(Token::Open) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err("Parêntese não fechado".to_string()),
+                }
+            }
+            Some(Token::Bit(word, bit)) => Ok(Condition::Bit { word, bit }),
+            Some(token) => Err(format!("Operador inesperado: {:?}", token)),
+            None => Err("Expressão incompleta".to_string()),
+        }
+    }
+}
+
+impl Condition {
+    pub fn parse(expr: &str) -> Result<Condition, String> {
+        let tokens = tokenize(expr)?;
+        if tokens.is_empty() {
+            return Err("Expressão vazia".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let condition = parser.parse_or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("Conteúdo inesperado após a expressão: {:?}", parser.tokens[parser.pos]));
+        }
+        Ok(condition)
+    }
+
+    /// Avalia com as variáveis do PlcData (WORD ausente = 0)
+    pub fn evaluate(&self, variables: &HashMap<String, f64>) -> bool {
+        match self {
+            Condition::Bit { word, bit } => {
+                let value = variables.get(&format!("Word[{}]", word)).copied().unwrap_or(0.0) as u16;
+                (value >> bit) & 1 == 1
+            }
+            Condition::Not(inner) => !inner.evaluate(variables),
+            Condition::And(left, right) => left.evaluate(variables) && right.evaluate(variables),
+            Condition::Or(left, right) => left.evaluate(variables) || right.evaluate(variables),
+        }
+    }
+}
+
+/// Valida a expressão (vazia é válida: usa o bit do próprio BitConfig)
+pub fn validate(expr: &str) -> Result<(), String> {
+    if expr.trim().is_empty() {
+        return Ok(());
+    }
+    Condition::parse(expr).map(|_| ()).map_err(|e| format!("Condição inválida: {}", e))
+}
+
+/// Estado de exibição de um BitConfig: condição se houver, senão o bit configurado
+pub fn is_active(condition: &str, word_index: i32, bit_index: i32, variables: &HashMap<String, f64>) -> bool {
+    if !condition.trim().is_empty() {
+        if let Ok(parsed) = Condition::parse(condition) {
+            return parsed.evaluate(variables);
+        }
+    }
+    if word_index < 0 || !(0..16).contains(&bit_index) {
+        return false;
+    }
+    Condition::Bit { word: word_index as usize, bit: bit_index as u8 }.evaluate(variables)
+}
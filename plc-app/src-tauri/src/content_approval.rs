@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::database::{BitConfig, Database, PanelEvent};
+
+// Fluxo de aprovação de conteúdo: com `content_approval_required` ativo, edições
+// de textos, fases, mensagens de bits, da playlist de vídeos e dos eventos
+// programados viram rascunhos (content_drafts) e só entram no ar quando um
+// usuário com papel "approver" executa approve_changes.
+// Cada aprovação/rejeição fica registrada em system_logs (categoria "audit").
+
+pub const KEY_APPROVAL_REQUIRED: &str = "content_approval_required";
+pub const ROLE_EDITOR: &str = "editor";
+pub const ROLE_APPROVER: &str = "approver";
+
+/// Alteração de conteúdo pendente (ou aplicada diretamente sem o fluxo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "entity", rename_all = "snake_case")]
+pub enum ContentChange {
+    Text { key: String, text: String },
+    Phase { phase_number: i32, title: String, description: String, color: String },
+    BitConfig { config: BitConfig },
+    DeleteBitConfig { word_index: i32, bit_index: i32 },
+    // 🆕 Playlist pública de vídeos
+    AddVideo { name: String, file_path: String, duration: i32, enabled: bool, priority: i32, description: String },
+    UpdateVideo { id: i64, name: String, file_path: String, duration: i32, enabled: bool, priority: i32, description: String, display_order: i32 },
+    DeleteVideo { id: i64 },
+    ReorderVideo { id: i64, display_order: i32 },
+    ClearAllVideos,
+    // 🆕 Eventos programados (substituem textos e playlist no período)
+    AddPanelEvent { event: PanelEvent },
+    UpdatePanelEvent { event: PanelEvent },
+}
+
+impl ContentChange {
+    /// Identifica o item alterado; um novo rascunho do mesmo item substitui o anterior
+    pub fn entity_key(&self) -> String {
+        match self {
+            ContentChange::Text { key, .. } => format!("text:{}", key),
+            ContentChange::Phase { phase_number, .. } => format!("phase:{}", phase_number),
+            ContentChange::BitConfig { config } => format!("bit:{}.{}", config.word_index, config.bit_index),
+            ContentChange::DeleteBitConfig { word_index, bit_index } => format!("bit:{}.{}", word_index, bit_index),
+            // Cada vídeo novo é um item próprio (dois envios não se substituem)
+            ContentChange::AddVideo { file_path, .. } => format!("video:new:{}", file_path),
+            ContentChange::UpdateVideo { id, .. } | ContentChange::DeleteVideo { id } => format!("video:{}", id),
+            ContentChange::ReorderVideo { id, .. } => format!("video:{}:order", id),
+            ContentChange::ClearAllVideos => "video:all".to_string(),
+            ContentChange::AddPanelEvent { event } => format!("event:new:{}:{}", event.name, event.starts_at),
+            ContentChange::UpdatePanelEvent { event } => format!("event:{}", event.id),
+        }
+    }
+
+    /// Valor proposto, no mesmo formato do valor atual (para o diff). Campos
+    /// ausentes mantêm o valor atual (ex.: reordenar só muda display_order).
+    fn proposed_value(&self) -> Option<serde_json::Value> {
+        match self {
+            ContentChange::Text { key, text } => Some(serde_json::json!({ "key": key, "text": text })),
+            ContentChange::Phase { phase_number, title, description, color } => Some(serde_json::json!({
+                "phase_number": phase_number, "title": title, "description": description, "color": color
+            })),
+            ContentChange::BitConfig { config } => serde_json::to_value(config).ok(),
+            ContentChange::DeleteBitConfig { .. } => None,
+            ContentChange::AddVideo { name, file_path, duration, enabled, priority, description } => Some(serde_json::json!({
+                "name": name, "file_path": file_path, "duration": duration, "enabled": enabled,
+                "priority": priority, "description": description
+            })),
+            ContentChange::UpdateVideo { name, file_path, duration, enabled, priority, description, display_order, .. } => Some(serde_json::json!({
+                "name": name, "file_path": file_path, "duration": duration, "enabled": enabled,
+                "priority": priority, "description": description, "display_order": display_order
+            })),
+            ContentChange::ReorderVideo { display_order, .. } => Some(serde_json::json!({ "display_order": display_order })),
+            ContentChange::DeleteVideo { .. } | ContentChange::ClearAllVideos => None,
+            ContentChange::AddPanelEvent { event } | ContentChange::UpdatePanelEvent { event } => serde_json::to_value(event).ok(),
+        }
+    }
+
+    async fn current_value(&self, db: &Database) -> Result<Option<serde_json::Value>, sqlx::Error> {
+        Ok(match self {
+            ContentChange::Text { key, .. } => db.get_all_texts().await?
+                .into_iter()
+                .find(|t| &t.key == key)
+                .map(|t| serde_json::json!({ "key": t.key, "text": t.text })),
+            ContentChange::Phase { phase_number, .. } => db.get_phase(*phase_number).await?
+                .map(|p| serde_json::json!({
+                    "phase_number": p.phase_number, "title": p.title, "description": p.description, "color": p.color
+                })),
+            ContentChange::BitConfig { config } => db.get_bit_config(config.word_index, config.bit_index).await?
+                .and_then(|b| serde_json::to_value(b).ok()),
+            ContentChange::DeleteBitConfig { word_index, bit_index } => db.get_bit_config(*word_index, *bit_index).await?
+                .and_then(|b| serde_json::to_value(b).ok()),
+            ContentChange::AddVideo { .. } => None,
+            ContentChange::UpdateVideo { id, .. }
+            | ContentChange::DeleteVideo { id }
+            | ContentChange::ReorderVideo { id, .. } => db.get_video(*id).await?
+                .and_then(|v| serde_json::to_value(v).ok()),
+            ContentChange::ClearAllVideos => {
+                let names: Vec<String> = db.get_all_videos().await?.into_iter().map(|v| v.name).collect();
+                Some(serde_json::json!({ "videos": names }))
+            }
+            ContentChange::AddPanelEvent { .. } => None,
+            ContentChange::UpdatePanelEvent { event } => db.get_all_panel_events().await?
+                .into_iter()
+                .find(|e| e.id == event.id)
+                .and_then(|e| serde_json::to_value(e).ok()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingChange {
+    pub id: i64,
+    pub entity_key: String,
+    pub action: String, // "create", "update" ou "delete"
+    pub author: String,
+    pub created_at: String,
+    pub diff: Vec<FieldDiff>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentUser {
+    pub username: String,
+    pub role: String,
+}
+
+/// Diferença campo a campo (ignora `id` e campos iguais)
+fn diff_values(before: Option<&serde_json::Value>, after: Option<&serde_json::Value>) -> Vec<FieldDiff> {
+    let empty = serde_json::Map::new();
+    let before_map = before.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let after_map = after.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let mut fields: Vec<&String> = before_map.keys().chain(after_map.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    fields.into_iter()
+        .filter(|field| field.as_str() != "id")
+        .filter(|field| before_map.get(*field) != after_map.get(*field))
+        .map(|field| FieldDiff {
+            field: field.clone(),
+            before: before_map.get(field).cloned(),
+            after: after_map.get(field).cloned(),
+        })
+        .collect()
+}
+
+/// Sobrepõe os campos propostos ao valor atual
+fn merge_values(current: Option<&serde_json::Value>, proposed: serde_json::Value) -> serde_json::Value {
+    match (current.and_then(|v| v.as_object()), proposed) {
+        (Some(current), serde_json::Value::Object(fields)) => {
+            let mut merged = current.clone();
+            merged.extend(fields);
+            serde_json::Value::Object(merged)
+        }
+        (_, proposed) => proposed,
+    }
+}
+
+pub fn hash_pin(username: &str, pin: &str) -> String {
+    hex::encode(Sha256::digest(format!("plc-app:{}:{}", username, pin).as_bytes()))
+}
+
+pub async fn approval_required(db: &Database) -> bool {
+    db.get_display_config(KEY_APPROVAL_REQUIRED).await.ok().flatten().map(|v| v == "true").unwrap_or(false)
+}
+
+/// Valida usuário/PIN e exige o papel "approver"
+pub async fn authorize_approver(db: &Database, username: &str, pin: &str) -> Result<(), String> {
+    let user = db.get_content_user(username).await
+        .map_err(|e| format!("Erro ao buscar usuário: {:?}", e))?;
+    match user {
+        Some((role, pin_hash)) if pin_hash == hash_pin(username, pin) => {
+            if role == ROLE_APPROVER {
+                Ok(())
+            } else {
+                Err(format!("Usuário {} não tem o papel de aprovador", username))
+            }
+        }
+        _ => Err("Usuário ou PIN inválido".to_string()),
+    }
+}
+
+/// Aplica direto ou, com o fluxo ativo, guarda como rascunho. Retorna Some(id do rascunho) quando pendente.
+pub async fn submit_change(db: &Database, change: ContentChange, author: Option<String>) -> Result<Option<i64>, String> {
+    let author = author.filter(|a| !a.trim().is_empty()).unwrap_or_else(|| "desconhecido".to_string());
+    if approval_required(db).await {
+        let payload = serde_json::to_string(&change).map_err(|e| e.to_string())?;
+        let id = db.save_content_draft(&change.entity_key(), &payload, &author).await
+            .map_err(|e| format!("Erro ao salvar rascunho: {:?}", e))?;
+        println!("📝 Rascunho {} ({}) criado por {}", id, change.entity_key(), author);
+        Ok(Some(id))
+    } else {
+        db.apply_content_changes(std::slice::from_ref(&change)).await
+            .map_err(|e| format!("Erro ao aplicar alteração: {:?}", e))?;
+        Ok(None)
+    }
+}
+
+pub async fn list_pending(db: &Database) -> Result<Vec<PendingChange>, String> {
+    let drafts = db.get_content_drafts().await
+        .map_err(|e| format!("Erro ao buscar rascunhos: {:?}", e))?;
+    let mut pending = Vec::new();
+    for (id, entity_key, payload, author, created_at) in drafts {
+        let Ok(change) = serde_json::from_str::<ContentChange>(&payload) else { continue };
+        let current = change.current_value(db).await
+            .map_err(|e| format!("Erro ao buscar valor atual: {:?}", e))?;
+        let proposed = change.proposed_value().map(|proposed| merge_values(current.as_ref(), proposed));
+        let action = match (&current, &proposed) {
+            (_, None) => "delete",
+            (None, Some(_)) => "create",
+            (Some(_), Some(_)) => "update",
+        };
+        pending.push(PendingChange {
+            id,
+            entity_key,
+            action: action.to_string(),
+            author,
+            created_at,
+            diff: diff_values(current.as_ref(), proposed.as_ref()),
+        });
+    }
+    Ok(pending)
+}
+
+/// Aprova (aplica) ou rejeita rascunhos; registra cada item na auditoria
+pub async fn resolve_drafts(db: &Database, ids: &[i64], approver: &str, approve: bool) -> Result<usize, String> {
+    let drafts: Vec<(i64, String, String, String, String)> = db.get_content_drafts().await
+        .map_err(|e| format!("Erro ao buscar rascunhos: {:?}", e))?
+        .into_iter()
+        .filter(|(id, ..)| ids.is_empty() || ids.contains(id))
+        .collect();
+    if drafts.is_empty() {
+        return Ok(0);
+    }
+
+    let draft_ids: Vec<i64> = drafts.iter().map(|(id, ..)| *id).collect();
+    if approve {
+        let changes = drafts.iter()
+            .map(|(id, _, payload, ..)| serde_json::from_str::<ContentChange>(payload)
+                .map_err(|e| format!("Rascunho {} inválido: {}", id, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        db.apply_content_changes_and_clear_drafts(&changes, &draft_ids).await
+            .map_err(|e| format!("Erro ao aplicar alterações: {:?}", e))?;
+    } else {
+        db.delete_content_drafts(&draft_ids).await
+            .map_err(|e| format!("Erro ao rejeitar rascunhos: {:?}", e))?;
+    }
+
+    let verb = if approve { "aprovada" } else { "rejeitada" };
+    for (id, entity_key, payload, author, _) in &drafts {
+        let _ = db.add_system_log(
+            "info",
+            "audit",
+            &format!("Alteração {} {} por {}", entity_key, verb, approver),
+            &format!("Rascunho #{} de {}: {}", id, author, payload),
+        ).await;
+    }
+    println!("✅ {} alteração(ões) {}(s) por {}", drafts.len(), verb, approver);
+    Ok(drafts.len())
+}
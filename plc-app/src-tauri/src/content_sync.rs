@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use crate::database::{BitConfig, Database, VideoConfig};
+
+// Sincronização de conteúdo (vídeos, textos e mensagens de bits) a partir de um
+// servidor central: o painel baixa periodicamente `manifest.json`, confere o
+// SHA-256 de cada vídeo e só então aplica tudo em uma transação.
+//
+// Formato do manifesto (só conteúdo aprovado deve ser publicado):
+// {
+//   "version": "campanha-2024-06",
+//   "texts": [{"key": "welcome", "text": "..."}],
+//   "bit_configs": [{"word_index": 1, "bit_index": 0, "name": "...", "message": "...", ...}],
+//   "videos": [{"name": "...", "url": "videos/edp.mp4", "sha256": "...", "duration": 30, ...}]
+// }
+// URLs relativas são resolvidas a partir da URL do manifesto.
+
+const DEFAULT_INTERVAL_S: u64 = 300;
+const MIN_INTERVAL_S: u64 = 30;
+const DISABLED_POLL: Duration = Duration::from_secs(30);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(600);
+const SYNC_DIR: &str = "synced_videos";
+
+// Chaves em display_configs
+pub const KEY_ENABLED: &str = "content_sync_enabled";
+pub const KEY_URL: &str = "content_sync_url";
+pub const KEY_INTERVAL: &str = "content_sync_interval_s";
+pub const KEY_VERSION: &str = "content_sync_version";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentSyncConfig {
+    pub enabled: bool,
+    pub manifest_url: String, // Ex: http://192.168.1.10:8080/conteudo/manifest.json
+    pub interval_s: u64,
+    pub applied_version: Option<String>, // Última versão aplicada (somente leitura)
+}
+
+impl ContentSyncConfig {
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            enabled: db.get_display_config(KEY_ENABLED).await?.map(|v| v == "true").unwrap_or(false),
+            manifest_url: db.get_display_config(KEY_URL).await?.unwrap_or_default(),
+            interval_s: db.get_display_config(KEY_INTERVAL).await?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_INTERVAL_S),
+            applied_version: db.get_display_config(KEY_VERSION).await?.filter(|v| !v.is_empty()),
+        })
+    }
+
+    pub async fn save(&self, db: &Database) -> Result<(), sqlx::Error> {
+        db.set_display_config(KEY_ENABLED, if self.enabled { "true" } else { "false" }, "boolean").await?;
+        db.set_display_config(KEY_URL, &self.manifest_url, "string").await?;
+        db.set_display_config(KEY_INTERVAL, &self.interval_s.max(MIN_INTERVAL_S).to_string(), "number").await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentManifest {
+    version: String,
+    #[serde(default)]
+    texts: Vec<ManifestText>,
+    #[serde(default)]
+    bit_configs: Vec<ManifestBit>,
+    #[serde(default)]
+    videos: Vec<ManifestVideo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestText {
+    key: String,
+    text: String,
+}
+
+fn default_true() -> bool { true }
+fn default_color() -> String { "#ffffff".to_string() }
+fn default_font_size() -> i32 { 48 }
+fn default_position() -> String { "center".to_string() }
+fn default_font_family() -> String { "Arial Black".to_string() }
+fn default_font_weight() -> String { "bold".to_string() }
+fn default_letter_spacing() -> i32 { 2 }
+fn default_duration() -> i32 { 30 }
+fn default_volume() -> i32 { 100 }
+
+#[derive(Debug, Deserialize)]
+struct ManifestBit {
+    word_index: i32,
+    bit_index: i32,
+    name: String,
+    message: String,
+    #[serde(default)]
+    message_off: String,
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default = "default_color")]
+    color: String,
+    #[serde(default = "default_font_size")]
+    font_size: i32,
+    #[serde(default = "default_position")]
+    position: String,
+    #[serde(default = "default_font_family")]
+    font_family: String,
+    #[serde(default = "default_font_weight")]
+    font_weight: String,
+    #[serde(default = "default_true")]
+    text_shadow: bool,
+    #[serde(default = "default_letter_spacing")]
+    letter_spacing: i32,
+    #[serde(default)]
+    use_template: bool,
+    #[serde(default)]
+    message_template: String,
+    #[serde(default)]
+    condition: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestVideo {
+    name: String,
+    url: String,
+    sha256: String,
+    #[serde(default = "default_duration")]
+    duration: i32,
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    display_order: i32,
+    #[serde(default = "default_volume")]
+    volume: i32,
+    #[serde(default)]
+    muted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentSyncResult {
+    pub version: String,
+    pub applied: bool, // false = versão já aplicada, nada mudou
+    pub texts: usize,
+    pub bit_configs: usize,
+    pub videos: usize,
+    pub videos_downloaded: usize,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn validate_manifest(manifest: &ContentManifest) -> Result<(), String> {
+    if manifest.version.trim().is_empty() {
+        return Err("Manifesto sem versão".to_string());
+    }
+    for bit in &manifest.bit_configs {
+        if !(0..64).contains(&bit.word_index) || !(0..16).contains(&bit.bit_index) {
+            return Err(format!("Bit inválido no manifesto: Word[{}].{}", bit.word_index, bit.bit_index));
+        }
+    }
+    for video in &manifest.videos {
+        if video.sha256.len() != 64 || !video.sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("SHA-256 inválido para o vídeo '{}'", video.name));
+        }
+    }
+    Ok(())
+}
+
+/// Arquivo local endereçado pelo hash (mesmo vídeo em várias campanhas = 1 download)
+fn video_path(sync_dir: &Path, video: &ManifestVideo) -> PathBuf {
+    let extension = Path::new(video.url.split('?').next().unwrap_or(""))
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp4")
+        .to_lowercase();
+    sync_dir.join(format!("{}.{}", video.sha256.to_lowercase(), extension))
+}
+
+async fn file_matches(path: &Path, sha256: &str) -> bool {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => sha256_hex(&bytes).eq_ignore_ascii_case(sha256),
+        Err(_) => false,
+    }
+}
+
+/// Baixa para `.part`, confere o hash e só então renomeia para o nome final
+async fn download_video(client: &reqwest::Client, url: &reqwest::Url, target: &Path, sha256: &str) -> Result<(), String> {
+    let mut response = client.get(url.clone()).send().await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Erro ao baixar {}: {}", url, e))?;
+
+    let part = target.with_extension("part");
+    let mut file = tokio::fs::File::create(&part).await
+        .map_err(|e| format!("Erro ao criar {:?}: {}", part, e))?;
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Erro ao baixar {}: {}", url, e))? {
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(|e| format!("Erro ao gravar {:?}: {}", part, e))?;
+    }
+    file.flush().await.map_err(|e| format!("Erro ao gravar {:?}: {}", part, e))?;
+    drop(file);
+
+    let actual = hex::encode(hasher.finalize());
+    if !actual.eq_ignore_ascii_case(sha256) {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(format!("Hash divergente para {} (esperado {}, recebido {})", url, sha256, actual));
+    }
+    tokio::fs::rename(&part, target).await
+        .map_err(|e| format!("Erro ao mover {:?}: {}", part, e))
+}
+
+/// Uma rodada de sincronização. Nada é aplicado se qualquer vídeo falhar.
+pub async fn sync_once(db: &Database, app_data_dir: &Path, force: bool) -> Result<ContentSyncResult, String> {
+    let config = ContentSyncConfig::load(db).await
+        .map_err(|e| format!("Erro ao ler configuração de sincronização: {:?}", e))?;
+    let manifest_url = reqwest::Url::parse(config.manifest_url.trim())
+        .map_err(|e| format!("URL do manifesto inválida: {}", e))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Erro ao criar cliente HTTP: {}", e))?;
+    let manifest: ContentManifest = client.get(manifest_url.clone()).send().await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Erro ao buscar manifesto: {}", e))?
+        .json().await
+        .map_err(|e| format!("Manifesto inválido: {}", e))?;
+    validate_manifest(&manifest)?;
+
+    let mut result = ContentSyncResult {
+        version: manifest.version.clone(),
+        applied: false,
+        texts: manifest.texts.len(),
+        bit_configs: manifest.bit_configs.len(),
+        videos: manifest.videos.len(),
+        videos_downloaded: 0,
+    };
+    if !force && config.applied_version.as_deref() == Some(manifest.version.as_str()) {
+        return Ok(result);
+    }
+
+    // 1) Baixar e verificar todos os vídeos antes de tocar no banco
+    let sync_dir = app_data_dir.join(SYNC_DIR);
+    tokio::fs::create_dir_all(&sync_dir).await
+        .map_err(|e| format!("Erro ao criar {:?}: {}", sync_dir, e))?;
+    let mut videos = Vec::new();
+    for (index, video) in manifest.videos.iter().enumerate() {
+        let target = video_path(&sync_dir, video);
+        if !file_matches(&target, &video.sha256).await {
+            let url = manifest_url.join(&video.url)
+                .map_err(|e| format!("URL inválida para '{}': {}", video.name, e))?;
+            download_video(&client, &url, &target, &video.sha256).await?;
+            result.videos_downloaded += 1;
+        }
+        videos.push(VideoConfig {
+            id: 0,
+            name: video.name.clone(),
+            file_path: target.to_string_lossy().to_string(),
+            duration: video.duration,
+            enabled: video.enabled,
+            priority: video.priority,
+            description: video.description.clone(),
+            display_order: if video.display_order > 0 { video.display_order } else { index as i32 + 1 },
+            volume: video.volume.clamp(0, 100),
+            muted: video.muted,
+        });
+    }
+
+    // 2) Aplicar textos, bits e vídeos em uma transação
+    let texts: Vec<(String, String)> = manifest.texts.iter().map(|t| (t.key.clone(), t.text.clone())).collect();
+    let bits: Vec<BitConfig> = manifest.bit_configs.into_iter().map(|b| BitConfig {
+        id: 0,
+        word_index: b.word_index,
+        bit_index: b.bit_index,
+        name: b.name,
+        message: b.message,
+        message_off: b.message_off,
+        enabled: b.enabled,
+        priority: b.priority,
+        color: b.color,
+        font_size: b.font_size,
+        position: b.position,
+        font_family: b.font_family,
+        font_weight: b.font_weight,
+        text_shadow: b.text_shadow,
+        letter_spacing: b.letter_spacing,
+        use_template: b.use_template,
+        message_template: b.message_template,
+        condition: b.condition,
+    }).collect();
+    db.apply_content_bundle(&texts, &bits, &videos).await
+        .map_err(|e| format!("Erro ao aplicar conteúdo (nada foi alterado): {:?}", e))?;
+    db.set_display_config(KEY_VERSION, &manifest.version, "string").await
+        .map_err(|e| format!("Erro ao gravar versão aplicada: {:?}", e))?;
+    result.applied = true;
+
+    // 3) Remover vídeos sincronizados que saíram da campanha
+    let keep: HashSet<PathBuf> = videos.iter().map(|v| PathBuf::from(&v.file_path)).collect();
+    if let Ok(mut entries) = tokio::fs::read_dir(&sync_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if !keep.contains(&entry.path()) {
+                let _ = tokio::fs::remove_file(entry.path()).await;
+            }
+        }
+    }
+
+    let _ = db.add_system_log(
+        "info",
+        "sync",
+        "Conteúdo sincronizado do servidor central",
+        &format!("Versão: {} - {} textos, {} bits, {} vídeos ({} baixados)",
+            result.version, result.texts, result.bit_configs, result.videos, result.videos_downloaded)
+    ).await;
+    Ok(result)
+}
+
+/// Loop periódico; o intervalo e a URL são relidos a cada rodada
+pub fn start_content_sync(
+    app_handle: tauri::AppHandle,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    app_data_dir: PathBuf,
+) {
+    use tauri::Emitter;
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let Some(db) = database.lock().await.clone() else {
+                tokio::time::sleep(DISABLED_POLL).await;
+                continue;
+            };
+            let config = match ContentSyncConfig::load(&db).await {
+                Ok(config) if config.enabled && !config.manifest_url.trim().is_empty() => config,
+                _ => {
+                    tokio::time::sleep(DISABLED_POLL).await;
+                    continue;
+                }
+            };
+
+            match sync_once(&db, &app_data_dir, false).await {
+                Ok(result) if result.applied => {
+                    println!("✅ Conteúdo sincronizado: versão {}", result.version);
+                    let _ = app_handle.emit("content-synced", &result);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("⚠️ Sincronização de conteúdo: {}", e);
+                    let _ = db.add_system_log("warning", "sync", "Falha na sincronização de conteúdo", &e).await;
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(config.interval_s.max(MIN_INTERVAL_S))).await;
+        }
+    });
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{broadcast, Mutex};
+use crate::database::{CountdownTimer, Database};
+use crate::tcp_server::PlcData;
+
+// Contadores regressivos do painel ("Tempo restante de eclusagem: 12:30").
+// Origem "word": a WORD traz os segundos restantes calculados pelo PLC.
+// Origem "phase": a WORD traz a fase atual; a contagem de `duration_s` começa
+// quando ela passa a valer `phase_number` e some quando a fase termina.
+// Os valores são recalculados a cada segundo e enviados no evento "countdown-update".
+
+pub const SOURCE_WORD: &str = "word";
+pub const SOURCE_PHASE: &str = "phase";
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+const NO_DATA: &str = "--:--";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CountdownValue {
+    pub id: i64,
+    pub name: String,               // Tag usada nos templates: {Timer:nome}
+    pub label: String,
+    pub active: bool,               // Contagem em andamento (restante > 0)
+    pub remaining_s: Option<u64>,   // None = sem dados do PLC / fase não iniciada
+    pub formatted: String,          // "12:30" ou "1:05:00"
+    pub text: String,               // "Tempo restante de eclusagem: 12:30"
+}
+
+pub type CountdownState = Arc<Mutex<Vec<CountdownValue>>>;
+
+pub fn format_remaining(seconds: u64) -> String {
+    let (hours, minutes, secs) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}", minutes, secs)
+    }
+}
+
+pub fn validate(timer: &CountdownTimer) -> Result<(), String> {
+    let mut chars = timer.name.chars();
+    let valid_name = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Err(format!("Nome inválido: '{}' (use letras, números e _)", timer.name));
+    }
+    if !(0..128).contains(&timer.word_index) {
+        return Err(format!("WORD inválida: {} (0-127)", timer.word_index));
+    }
+    match timer.source.as_str() {
+        SOURCE_WORD => Ok(()),
+        SOURCE_PHASE if timer.duration_s <= 0 => Err("Duração da fase deve ser maior que zero".to_string()),
+        SOURCE_PHASE => Ok(()),
+        other => Err(format!("Origem inválida: {} (use word ou phase)", other)),
+    }
+}
+
+/// Calcula os segundos restantes; `phase_started` guarda o início de cada fase em andamento
+fn remaining_for(
+    timer: &CountdownTimer,
+    variables: &HashMap<String, f64>,
+    phase_started: &mut HashMap<i64, Instant>,
+) -> Option<u64> {
+    let word = variables.get(&format!("Word[{}]", timer.word_index)).copied()?;
+    match timer.source.as_str() {
+        SOURCE_PHASE => {
+            if word as i32 != timer.phase_number {
+                phase_started.remove(&timer.id);
+                return None;
+            }
+            // Se o app abrir no meio da fase, conta a partir de quando a fase foi vista
+            let started = *phase_started.entry(timer.id).or_insert_with(Instant::now);
+            Some((timer.duration_s as u64).saturating_sub(started.elapsed().as_secs()))
+        }
+        _ => Some(word.max(0.0) as u16 as u64),
+    }
+}
+
+fn to_value(timer: &CountdownTimer, remaining: Option<u64>) -> CountdownValue {
+    let formatted = remaining.map(format_remaining).unwrap_or_else(|| NO_DATA.to_string());
+    let text = if timer.label.trim().is_empty() {
+        formatted.clone()
+    } else {
+        format!("{}: {}", timer.label.trim(), formatted)
+    };
+    CountdownValue {
+        id: timer.id,
+        name: timer.name.clone(),
+        label: timer.label.clone(),
+        active: remaining.is_some_and(|r| r > 0),
+        remaining_s: remaining,
+        formatted,
+        text,
+    }
+}
+
+/// Acompanha as WORDs recebidas e publica os contadores a cada segundo
+pub fn start_countdowns(
+    app_handle: AppHandle,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    state: CountdownState,
+    mut rx: broadcast::Receiver<PlcData>,
+) {
+    let variables: Arc<std::sync::Mutex<HashMap<String, f64>>> = Arc::new(std::sync::Mutex::new(HashMap::new()));
+
+    let latest = variables.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(data) => {
+                    if let Ok(mut current) = latest.lock() {
+                        *current = data.variables;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        let mut phase_started: HashMap<i64, Instant> = HashMap::new();
+        loop {
+            interval.tick().await;
+            let Some(db) = database.lock().await.clone() else { continue };
+            let timers: Vec<CountdownTimer> = match db.get_all_countdown_timers().await {
+                Ok(timers) => timers.into_iter().filter(|t| t.enabled).collect(),
+                Err(_) => continue,
+            };
+            if timers.is_empty() {
+                state.lock().await.clear();
+                phase_started.clear();
+                continue;
+            }
+
+            let snapshot = variables.lock().map(|v| v.clone()).unwrap_or_default();
+            phase_started.retain(|id, _| timers.iter().any(|t| t.id == *id));
+            let values: Vec<CountdownValue> = timers.iter()
+                .map(|timer| to_value(timer, remaining_for(timer, &snapshot, &mut phase_started)))
+                .collect();
+
+            let _ = app_handle.emit("countdown-update", &values);
+            *state.lock().await = values;
+        }
+    });
+}
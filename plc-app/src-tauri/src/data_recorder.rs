@@ -0,0 +1,245 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast;
+use crate::tcp_server::PlcData;
+
+// Gravação contínua das words recebidas do PLC em arquivos por hora
+// (plc_recordings/plc_AAAAMMDD_HH.jsonl, UTC), mantendo as últimas 48h.
+// Serve de evidência do que o PLC realmente enviou ("o semáforo estava errado
+// ontem às 14:30") e pode ser exportado em CSV por intervalo de tempo.
+
+pub const RETENTION_HOURS: i64 = 48;
+const RECORDINGS_DIR: &str = "plc_recordings";
+const FILE_PREFIX: &str = "plc_";
+const FILE_EXTENSION: &str = "jsonl";
+// Frames idênticos ao anterior só são regravados a cada 10s (comprova que o PLC continuava enviando)
+const UNCHANGED_REWRITE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedFrame {
+    t: DateTime<Utc>,
+    w: Vec<f64>, // Word[0..n]
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub directory: String,
+    pub files: usize,
+    pub total_bytes: u64,
+    pub oldest: Option<String>, // Início da hora mais antiga gravada
+    pub newest: Option<String>,
+    pub retention_hours: i64,
+}
+
+pub fn recordings_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(RECORDINGS_DIR)
+}
+
+fn file_name_for(hour: DateTime<Utc>) -> String {
+    format!("{}{}.{}", FILE_PREFIX, hour.format("%Y%m%d_%H"), FILE_EXTENSION)
+}
+
+/// Hora (UTC) representada por um arquivo de gravação, pelo nome
+fn hour_of_file(path: &Path) -> Option<DateTime<Utc>> {
+    let stem = path.file_stem()?.to_str()?.strip_prefix(FILE_PREFIX)?;
+    if path.extension()?.to_str()? != FILE_EXTENSION {
+        return None;
+    }
+    let naive = NaiveDateTime::parse_from_str(&format!("{}0000", stem), "%Y%m%d_%H%M%S").ok()?;
+    Some(Utc.from_utc_datetime(&naive))
+}
+
+fn truncate_to_hour(t: DateTime<Utc>) -> DateTime<Utc> {
+    t.with_minute(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_nanosecond(0)).unwrap_or(t)
+}
+
+/// Extrai as words ordenadas por índice (Word[0], Word[1], ...)
+pub fn extract_words(data: &PlcData) -> Vec<f64> {
+    let mut indexed: Vec<(usize, f64)> = data.variables.iter()
+        .filter_map(|(name, value)| {
+            let index = name.strip_prefix("Word[")?.strip_suffix(']')?.parse().ok()?;
+            Some((index, *value))
+        })
+        .collect();
+    indexed.sort_by_key(|(index, _)| *index);
+    let len = indexed.last().map(|(index, _)| index + 1).unwrap_or(0);
+    let mut words = vec![0.0; len];
+    for (index, value) in indexed {
+        words[index] = value;
+    }
+    words
+}
+
+async fn list_recording_files(dir: &Path) -> Vec<(DateTime<Utc>, PathBuf)> {
+    let mut files = Vec::new();
+    if let Ok(mut entries) = fs::read_dir(dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if let Some(hour) = hour_of_file(&path) {
+                files.push((hour, path));
+            }
+        }
+    }
+    files.sort_by_key(|(hour, _)| *hour);
+    files
+}
+
+async fn prune_old_files(dir: &Path) {
+    let cutoff = truncate_to_hour(Utc::now()) - ChronoDuration::hours(RETENTION_HOURS);
+    for (hour, path) in list_recording_files(dir).await {
+        if hour < cutoff {
+            match fs::remove_file(&path).await {
+                Ok(_) => println!("🗑️ Gravação antiga removida: {}", path.display()),
+                Err(e) => eprintln!("⚠️ Erro ao remover gravação {}: {:?}", path.display(), e),
+            }
+        }
+    }
+}
+
+/// Inicia a gravação em segundo plano a partir do broadcast do servidor TCP
+pub fn start_plc_recorder(app_data_dir: PathBuf, mut rx: broadcast::Receiver<PlcData>) {
+    tauri::async_runtime::spawn(async move {
+        let dir = recordings_dir(&app_data_dir);
+        if let Err(e) = fs::create_dir_all(&dir).await {
+            eprintln!("❌ Erro ao criar diretório de gravações {}: {:?}", dir.display(), e);
+            return;
+        }
+        prune_old_files(&dir).await;
+        println!("⏺️ Gravando dados do PLC em {} (últimas {}h)", dir.display(), RETENTION_HOURS);
+
+        let mut current: Option<(DateTime<Utc>, File)> = None;
+        let mut last_words: Vec<f64> = Vec::new();
+        let mut last_written: Option<std::time::Instant> = None;
+
+        loop {
+            let data = match rx.recv().await {
+                Ok(data) => data,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("⚠️ Gravador do PLC atrasado, {} frames descartados", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let words = extract_words(&data);
+            if words.is_empty() {
+                continue;
+            }
+            if words == last_words && last_written.is_some_and(|t| t.elapsed() < UNCHANGED_REWRITE_INTERVAL) {
+                continue;
+            }
+
+            let t = DateTime::parse_from_rfc3339(&data.timestamp)
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now());
+            let hour = truncate_to_hour(t);
+
+            // Rotação por hora
+            if current.as_ref().map(|(h, _)| *h != hour).unwrap_or(true) {
+                let path = dir.join(file_name_for(hour));
+                match OpenOptions::new().create(true).append(true).open(&path).await {
+                    Ok(file) => current = Some((hour, file)),
+                    Err(e) => {
+                        eprintln!("❌ Erro ao abrir gravação {}: {:?}", path.display(), e);
+                        current = None;
+                        continue;
+                    }
+                }
+                prune_old_files(&dir).await;
+            }
+
+            let Some((_, file)) = current.as_mut() else { continue };
+            let mut line = match serde_json::to_string(&RecordedFrame { t, w: words.clone() }) {
+                Ok(line) => line,
+                Err(_) => continue,
+            };
+            line.push('\n');
+            // flush a cada frame: a evidência precisa estar no disco mesmo se o app cair
+            let written = match file.write_all(line.as_bytes()).await {
+                Ok(_) => file.flush().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = written {
+                eprintln!("❌ Erro ao gravar dados do PLC: {:?}", e);
+                current = None;
+                continue;
+            }
+            last_words = words;
+            last_written = Some(std::time::Instant::now());
+        }
+    });
+}
+
+pub async fn recording_info(app_data_dir: &Path) -> RecordingInfo {
+    let dir = recordings_dir(app_data_dir);
+    let files = list_recording_files(&dir).await;
+    let mut total_bytes = 0;
+    for (_, path) in &files {
+        if let Ok(metadata) = fs::metadata(path).await {
+            total_bytes += metadata.len();
+        }
+    }
+    RecordingInfo {
+        directory: dir.display().to_string(),
+        files: files.len(),
+        total_bytes,
+        oldest: files.first().map(|(hour, _)| hour.to_rfc3339()),
+        newest: files.last().map(|(hour, _)| (*hour + ChronoDuration::hours(1)).to_rfc3339()),
+        retention_hours: RETENTION_HOURS,
+    }
+}
+
+/// Exporta em CSV (timestamp;Word[0];Word[1];...) os frames entre `start` e `end` (RFC3339)
+pub async fn export_range(app_data_dir: &Path, start: &str, end: &str, output_path: &Path) -> Result<usize, String> {
+    let start = DateTime::parse_from_rfc3339(start)
+        .map_err(|e| format!("Data inicial inválida: {}", e))?.with_timezone(&Utc);
+    let end = DateTime::parse_from_rfc3339(end)
+        .map_err(|e| format!("Data final inválida: {}", e))?.with_timezone(&Utc);
+    if end <= start {
+        return Err("Data final deve ser posterior à inicial".to_string());
+    }
+
+    let dir = recordings_dir(app_data_dir);
+    let mut frames: Vec<RecordedFrame> = Vec::new();
+    for (hour, path) in list_recording_files(&dir).await {
+        if hour + ChronoDuration::hours(1) <= start || hour > end {
+            continue;
+        }
+        let file = File::open(&path).await
+            .map_err(|e| format!("Erro ao abrir {}: {}", path.display(), e))?;
+        let mut lines = BufReader::new(file).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            // Última linha pode estar incompleta se o app foi encerrado durante a escrita
+            let Ok(frame) = serde_json::from_str::<RecordedFrame>(&line) else { continue };
+            if frame.t >= start && frame.t <= end {
+                frames.push(frame);
+            }
+        }
+    }
+
+    let columns = frames.iter().map(|f| f.w.len()).max().unwrap_or(0);
+    let mut csv = String::from("timestamp");
+    for i in 0..columns {
+        csv.push_str(&format!(";Word[{}]", i));
+    }
+    csv.push('\n');
+    for frame in &frames {
+        csv.push_str(&frame.t.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+        for i in 0..columns {
+            match frame.w.get(i) {
+                Some(value) => csv.push_str(&format!(";{}", value)),
+                None => csv.push(';'),
+            }
+        }
+        csv.push('\n');
+    }
+
+    fs::write(output_path, csv).await
+        .map_err(|e| format!("Erro ao salvar {}: {}", output_path.display(), e))?;
+    println!("📤 {} frames do PLC exportados para {}", frames.len(), output_path.display());
+    Ok(frames.len())
+}
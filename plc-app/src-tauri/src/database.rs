@@ -1,5 +1,8 @@
 ﻿use sqlx::{Pool, Sqlite, SqlitePool, Row};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::bit_condition;
+use crate::content_approval::ContentChange;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextConfig {
@@ -46,6 +49,8 @@ pub struct BitConfig {
     pub letter_spacing: i32,  // Espaçamento entre letras (px)
     pub use_template: bool,   // Se true, usa message_template com variáveis
     pub message_template: String, // Template com tags {Word[N]}
+    #[serde(default)]
+    pub condition: String,    // Expressão de bits (ex: "Word[3].2 AND Word[1].0"); vazio = só este bit
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,7 +63,7 @@ pub struct SystemLog {
     pub details: String,      // Detalhes adicionais (JSON, stack trace, etc)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VideoConfig {
     pub id: i64,
     pub name: String,         // Nome do vÃ­deo
@@ -68,6 +73,111 @@ pub struct VideoConfig {
     pub priority: i32,        // Prioridade de exibiÃ§Ã£o
     pub description: String,  // DescriÃ§Ã£o do vÃ­deo
     pub display_order: i32,   // Ordem de exibiÃ§Ã£o
+    pub volume: i32,          // Volume do áudio (0-100)
+    pub muted: bool,          // Áudio desligado neste vídeo
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataMapping {
+    pub id: i64,
+    pub name: String,         // Variável gerada em PlcData (ex: "sistema_ativo")
+    pub word_index: i32,      // WORD de origem
+    pub mapping_type: String, // "bit" ou "analog"
+    pub bit_index: i32,       // 0-15, usado quando mapping_type = "bit"
+    pub scale: f64,           // "analog": valor = word * scale + offset
+    pub offset: f64,
+    pub signed: bool,         // "analog": interpreta a WORD como inteiro com sinal (i16)
+    pub enabled: bool,
+    pub description: String,
+}
+
+impl DataMapping {
+    /// Calcula o valor da variável a partir das words recebidas (None se a WORD não veio)
+    pub fn evaluate(&self, word: Option<f64>) -> Option<f64> {
+        let raw = word? as u16;
+        match self.mapping_type.as_str() {
+            "bit" => Some(if (raw >> (self.bit_index as u16 & 0x0F)) & 1 == 1 { 1.0 } else { 0.0 }),
+            _ => {
+                let value = if self.signed { raw as i16 as f64 } else { raw as f64 };
+                Some(value * self.scale + self.offset)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolConfig {
+    pub source: String,    // IP do PLC/origem, ou "*" para qualquer origem sem configuração própria
+    pub protocol: String,  // "auto" (detecta por pacote), "json" ou "binary"
+    pub handshake: String, // Se não vazio, a origem deve enviar esta string ao conectar
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelTheme {
+    pub id: i64,
+    pub name: String,
+    pub background_color: String,  // Cor de fundo do painel (#hex)
+    pub background_image: String,  // Caminho de imagem de fundo (vazio = sem imagem)
+    pub font_family: String,       // Fonte padrão das mensagens
+    pub font_weight: String,       // 'normal', 'bold', 'black'
+    pub text_color: String,        // Cor padrão de textos sem cor própria
+    pub override_bit_fonts: bool,  // Se true, a fonte do tema substitui a fonte de cada bit
+    pub logo_path: String,         // Caminho do logotipo (vazio = sem logo)
+    pub logo_position: String,     // 'top-left', 'top-right', 'bottom-left', 'bottom-right'
+}
+
+pub const ACTIVE_THEME_KEY: &str = "active_theme";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalogDisplay {
+    pub id: i64,
+    pub label: String,        // Texto exibido antes do valor (ex: "Nível")
+    pub word_index: i32,      // WORD de origem
+    pub scale: f64,           // valor = word * scale + offset
+    pub offset: f64,
+    pub signed: bool,         // Interpreta a WORD como inteiro com sinal (i16)
+    pub min_value: f64,       // Faixa válida; fora dela o valor é limitado e marcado
+    pub max_value: f64,
+    pub unit: String,         // Unidade exibida após o valor (ex: "m", "km/h")
+    pub decimals: i32,        // Casas decimais na formatação
+    pub enabled: bool,
+    pub display_order: i32,   // Ordem de exibição no painel
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountdownTimer {
+    pub id: i64,
+    pub name: String,         // Tag nos templates: {Timer:nome}
+    pub label: String,        // Ex: "Tempo restante de eclusagem"
+    pub source: String,       // "word" (WORD = segundos restantes) ou "phase" (contagem desde o início da fase)
+    pub word_index: i32,      // WORD com os segundos restantes ou com a fase atual
+    pub phase_number: i32,    // "phase": valor da WORD que indica a fase
+    pub duration_s: i32,      // "phase": duração prevista da fase em segundos
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionConfig {
+    pub id: i64,
+    pub name: String,         // Ex: "Publicidade", "Alarmes"
+    pub scope: String,        // "playlist" (entre vídeos e troca mensagens/vídeos) ou "message" (mensagens de bits)
+    pub min_priority: i32,    // "message": vale para mensagens com prioridade >= min_priority
+    pub effect: String,       // "fade", "cut" ou "slide"
+    pub duration_ms: i32,     // Duração do efeito (ignorada em "cut")
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelEvent {
+    pub id: i64,
+    pub name: String,           // Ex: "Regata fim de semana"
+    pub starts_at: String,      // Horário local "AAAA-MM-DDTHH:MM"
+    pub ends_at: String,
+    pub messages: Vec<String>,  // Textos exibidos quando não há mensagens de bits ativas
+    pub video_ids: Vec<i64>,    // Playlist do evento (vazia = mantém a playlist normal)
+    pub theme_id: Option<i64>,  // Tema durante o evento (None = tema ativo)
+    pub priority: i32,          // Eventos sobrepostos: vale o de maior prioridade
+    pub enabled: bool,
 }
 
 pub struct Database {
@@ -181,6 +291,12 @@ impl Database {
             .await
             .ok();
 
+        // Migration: condição combinando vários bits (AND/OR/NOT)
+        sqlx::query("ALTER TABLE bit_configs ADD COLUMN condition TEXT NOT NULL DEFAULT ''")
+            .execute(&pool)
+            .await
+            .ok();
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS video_configs (
@@ -217,6 +333,185 @@ impl Database {
         .execute(&pool)
         .await?;
 
+        // Mapeamento de dados (nome -> word/bit ou analógico escalado)
+        let data_mappings_existed = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'data_mappings'")
+            .fetch_optional(&pool)
+            .await?
+            .is_some();
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS data_mappings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                word_index INTEGER NOT NULL,
+                mapping_type TEXT NOT NULL DEFAULT 'bit',
+                bit_index INTEGER NOT NULL DEFAULT 0,
+                scale REAL NOT NULL DEFAULT 1.0,
+                value_offset REAL NOT NULL DEFAULT 0.0,
+                signed BOOLEAN NOT NULL DEFAULT 0,
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                description TEXT NOT NULL DEFAULT '',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Protocolo por origem (JSON/binário) e handshake opcional
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS protocol_configs (
+                source TEXT PRIMARY KEY,
+                protocol TEXT NOT NULL DEFAULT 'auto',
+                handshake TEXT NOT NULL DEFAULT '',
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Temas visuais do painel (marca sazonal / por cliente)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS themes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                background_color TEXT NOT NULL DEFAULT '#000000',
+                background_image TEXT NOT NULL DEFAULT '',
+                font_family TEXT NOT NULL DEFAULT 'Arial Black',
+                font_weight TEXT NOT NULL DEFAULT 'bold',
+                text_color TEXT NOT NULL DEFAULT '#ffffff',
+                override_bit_fonts BOOLEAN NOT NULL DEFAULT 0,
+                logo_path TEXT NOT NULL DEFAULT '',
+                logo_position TEXT NOT NULL DEFAULT 'top-right',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Fluxo de aprovação de conteúdo: rascunhos e usuários (editor/approver)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS content_drafts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entity_key TEXT NOT NULL UNIQUE,
+                payload TEXT NOT NULL,
+                author TEXT NOT NULL DEFAULT '',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS content_users (
+                username TEXT PRIMARY KEY,
+                role TEXT NOT NULL DEFAULT 'editor',
+                pin_hash TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Mostradores analógicos do painel (nível, velocidade...) calculados a partir das WORDs
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS analog_displays (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                word_index INTEGER NOT NULL,
+                scale REAL NOT NULL DEFAULT 1.0,
+                value_offset REAL NOT NULL DEFAULT 0.0,
+                signed BOOLEAN NOT NULL DEFAULT 0,
+                min_value REAL NOT NULL DEFAULT 0.0,
+                max_value REAL NOT NULL DEFAULT 100.0,
+                unit TEXT NOT NULL DEFAULT '',
+                decimals INTEGER NOT NULL DEFAULT 1,
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                display_order INTEGER NOT NULL DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Contadores regressivos (segundos na WORD ou duração desde o início da fase)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS countdown_timers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                label TEXT NOT NULL DEFAULT '',
+                source TEXT NOT NULL DEFAULT 'word',
+                word_index INTEGER NOT NULL,
+                phase_number INTEGER NOT NULL DEFAULT 0,
+                duration_s INTEGER NOT NULL DEFAULT 0,
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Transições do painel (playlist de vídeos e classes de prioridade das mensagens)
+        let transitions_existed = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'transition_configs'")
+            .fetch_optional(&pool)
+            .await?
+            .is_some();
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS transition_configs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                scope TEXT NOT NULL DEFAULT 'message',
+                min_priority INTEGER NOT NULL DEFAULT 0,
+                effect TEXT NOT NULL DEFAULT 'fade',
+                duration_ms INTEGER NOT NULL DEFAULT 500,
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Eventos programados (sobrepõem textos/vídeos/tema por um período)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS panel_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                starts_at TEXT NOT NULL,
+                ends_at TEXT NOT NULL,
+                messages TEXT NOT NULL DEFAULT '[]',
+                video_ids TEXT NOT NULL DEFAULT '[]',
+                theme_id INTEGER,
+                priority INTEGER NOT NULL DEFAULT 0,
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
         // Inserir dados padrão para as fases da eclusa
         let db = Database { pool };
         
@@ -237,10 +532,35 @@ impl Database {
             .await
             .ok(); // Ignora erro se coluna já existe
         
+        // Migração: áudio por vídeo (volume 0-100 e mudo)
+        sqlx::query("ALTER TABLE video_configs ADD COLUMN volume INTEGER NOT NULL DEFAULT 100")
+            .execute(&db.pool)
+            .await
+            .ok(); // Ignora erro se coluna já existe
+        
+        sqlx::query("ALTER TABLE video_configs ADD COLUMN muted BOOLEAN NOT NULL DEFAULT 0")
+            .execute(&db.pool)
+            .await
+            .ok(); // Ignora erro se coluna já existe
+        
+        // Migração: vídeos recebidos da sincronização central (substituídos a cada campanha)
+        sqlx::query("ALTER TABLE video_configs ADD COLUMN synced INTEGER NOT NULL DEFAULT 0")
+            .execute(&db.pool)
+            .await
+            .ok(); // Ignora erro se coluna já existe
+        
         db.insert_default_phases().await?;
         db.insert_default_texts().await?;
         db.insert_default_display_configs().await?;
         db.insert_default_bit_configs().await?;
+        db.insert_default_themes().await?;
+        // Só na criação da tabela: mapeamentos apagados pelo usuário não devem voltar
+        if !data_mappings_existed {
+            db.insert_default_data_mappings().await?;
+        }
+        if !transitions_existed {
+            db.insert_default_transitions().await?;
+        }
         // NÃO inserir vídeos de exemplo - usuário quer começar vazio
         // db.insert_default_video_configs().await?;
 
@@ -376,6 +696,68 @@ impl Database {
         Ok(())
     }
 
+    async fn insert_default_themes(&self) -> Result<(), sqlx::Error> {
+        // Visual atual do painel (fundo preto, texto branco)
+        sqlx::query("INSERT OR IGNORE INTO themes (name) VALUES ('Padrão')")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Equivalente ao que process_plc_data fazia fixo no código (WORD 0, bits 0-2)
+    async fn insert_default_data_mappings(&self) -> Result<(), sqlx::Error> {
+        let mappings = vec![
+            ("sistema_ativo", 0, 0, "Sistema ativo (WORD 0, bit 0)"),
+            ("emergencia", 0, 1, "Emergência (WORD 0, bit 1)"),
+            ("manutencao", 0, 2, "Manutenção (WORD 0, bit 2)"),
+        ];
+
+        for (name, word_index, bit_index, description) in mappings {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO data_mappings (name, word_index, mapping_type, bit_index, description)
+                VALUES (?, ?, 'bit', ?, ?)
+                "#,
+            )
+            .bind(name)
+            .bind(word_index)
+            .bind(bit_index)
+            .bind(description)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert_default_transitions(&self) -> Result<(), sqlx::Error> {
+        // Publicidade com fade cruzado; alarmes (prioridade alta) entram sem efeito
+        let transitions = vec![
+            ("Publicidade", "playlist", 0, "fade", 800),
+            ("Mensagens", "message", 0, "fade", 500),
+            ("Alarmes", "message", 80, "cut", 0),
+        ];
+
+        for (name, scope, min_priority, effect, duration_ms) in transitions {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO transition_configs (name, scope, min_priority, effect, duration_ms)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(name)
+            .bind(scope)
+            .bind(min_priority)
+            .bind(effect)
+            .bind(duration_ms)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
     async fn insert_default_video_configs(&self) -> Result<(), sqlx::Error> {
         let videos = vec![
             ("Publicidade EDP Verde", "videos/edp_verde.mp4", 30, true, 10, "Energia renovÃ¡vel e sustentÃ¡vel da EDP"),
@@ -484,6 +866,12 @@ impl Database {
     }
 
     // MÃ©todos para configuraÃ§Ãµes de display
+    /// Consulta trivial para o health check (banco respondendo)
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
     pub async fn get_display_config(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
         let result = sqlx::query("SELECT value FROM display_configs WHERE key = ?")
             .bind(key)
@@ -524,7 +912,7 @@ impl Database {
 
     // MÃ©todos para gerenciar configuraÃ§Ãµes de bits
     pub async fn get_all_bit_configs(&self) -> Result<Vec<BitConfig>, sqlx::Error> {
-        let rows = sqlx::query("SELECT id, word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, COALESCE(font_family, 'Arial Black') as font_family, COALESCE(font_weight, 'bold') as font_weight, COALESCE(text_shadow, 1) as text_shadow, COALESCE(letter_spacing, 2) as letter_spacing, COALESCE(use_template, 0) as use_template, COALESCE(message_template, '') as message_template FROM bit_configs ORDER BY word_index, bit_index")
+        let rows = sqlx::query("SELECT id, word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, COALESCE(font_family, 'Arial Black') as font_family, COALESCE(font_weight, 'bold') as font_weight, COALESCE(text_shadow, 1) as text_shadow, COALESCE(letter_spacing, 2) as letter_spacing, COALESCE(use_template, 0) as use_template, COALESCE(message_template, '') as message_template, COALESCE(condition, '') as condition FROM bit_configs ORDER BY word_index, bit_index")
             .fetch_all(&self.pool)
             .await?;
 
@@ -546,11 +934,12 @@ impl Database {
             letter_spacing: row.get("letter_spacing"),
             use_template: row.get::<i64, _>("use_template") != 0,
             message_template: row.get("message_template"),
+            condition: row.get("condition"),
         }).collect())
     }
 
     pub async fn get_bit_config(&self, word_index: i32, bit_index: i32) -> Result<Option<BitConfig>, sqlx::Error> {
-        let row = sqlx::query("SELECT id, word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, COALESCE(font_family, 'Arial Black') as font_family, COALESCE(font_weight, 'bold') as font_weight, COALESCE(text_shadow, 1) as text_shadow, COALESCE(letter_spacing, 2) as letter_spacing, COALESCE(use_template, 0) as use_template, COALESCE(message_template, '') as message_template FROM bit_configs WHERE word_index = ? AND bit_index = ?")
+        let row = sqlx::query("SELECT id, word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, COALESCE(font_family, 'Arial Black') as font_family, COALESCE(font_weight, 'bold') as font_weight, COALESCE(text_shadow, 1) as text_shadow, COALESCE(letter_spacing, 2) as letter_spacing, COALESCE(use_template, 0) as use_template, COALESCE(message_template, '') as message_template, COALESCE(condition, '') as condition FROM bit_configs WHERE word_index = ? AND bit_index = ?")
             .bind(word_index)
             .bind(bit_index)
             .fetch_optional(&self.pool)
@@ -574,14 +963,15 @@ impl Database {
             letter_spacing: r.get("letter_spacing"),
             use_template: r.get::<i64, _>("use_template") != 0,
             message_template: r.get("message_template"),
+            condition: r.get("condition"),
         }))
     }
 
-    pub async fn add_bit_config(&self, word_index: i32, bit_index: i32, name: &str, message: &str, message_off: &str, enabled: bool, priority: i32, color: &str, font_size: i32, position: &str, font_family: &str, font_weight: &str, text_shadow: bool, letter_spacing: i32, use_template: bool, message_template: &str) -> Result<i64, sqlx::Error> {
+    pub async fn add_bit_config(&self, word_index: i32, bit_index: i32, name: &str, message: &str, message_off: &str, enabled: bool, priority: i32, color: &str, font_size: i32, position: &str, font_family: &str, font_weight: &str, text_shadow: bool, letter_spacing: i32, use_template: bool, message_template: &str, condition: &str) -> Result<i64, sqlx::Error> {
         let result = sqlx::query(
             r#"
-            INSERT INTO bit_configs (word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, font_family, font_weight, text_shadow, letter_spacing, use_template, message_template)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO bit_configs (word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, font_family, font_weight, text_shadow, letter_spacing, use_template, message_template, condition)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(word_index)
@@ -600,17 +990,18 @@ impl Database {
         .bind(letter_spacing)
         .bind(use_template as i64)
         .bind(message_template)
+        .bind(condition)
         .execute(&self.pool)
         .await?;
         
         Ok(result.last_insert_rowid())
     }
 
-    pub async fn update_bit_config(&self, word_index: i32, bit_index: i32, name: &str, message: &str, message_off: &str, enabled: bool, priority: i32, color: &str, font_size: i32, position: &str, font_family: &str, font_weight: &str, text_shadow: bool, letter_spacing: i32, use_template: bool, message_template: &str) -> Result<(), sqlx::Error> {
+    pub async fn update_bit_config(&self, word_index: i32, bit_index: i32, name: &str, message: &str, message_off: &str, enabled: bool, priority: i32, color: &str, font_size: i32, position: &str, font_family: &str, font_weight: &str, text_shadow: bool, letter_spacing: i32, use_template: bool, message_template: &str, condition: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
             UPDATE bit_configs 
-            SET name = ?, message = ?, message_off = ?, enabled = ?, priority = ?, color = ?, font_size = ?, position = ?, font_family = ?, font_weight = ?, text_shadow = ?, letter_spacing = ?, use_template = ?, message_template = ?, updated_at = CURRENT_TIMESTAMP
+            SET name = ?, message = ?, message_off = ?, enabled = ?, priority = ?, color = ?, font_size = ?, position = ?, font_family = ?, font_weight = ?, text_shadow = ?, letter_spacing = ?, use_template = ?, message_template = ?, condition = ?, updated_at = CURRENT_TIMESTAMP
             WHERE word_index = ? AND bit_index = ?
             "#,
         )
@@ -628,6 +1019,7 @@ impl Database {
         .bind(letter_spacing)
         .bind(use_template as i64)
         .bind(message_template)
+        .bind(condition)
         .bind(word_index)
         .bind(bit_index)
         .execute(&self.pool)
@@ -647,17 +1039,17 @@ impl Database {
     }
 
     // MÃ©todo para processar dados PLC e retornar mensagens ativas baseadas nos bits
-    pub async fn process_plc_bits(&self, word_data: &[u16]) -> Result<Vec<(BitConfig, bool)>, sqlx::Error> {
+    pub async fn process_plc_bits(&self, variables: &HashMap<String, f64>) -> Result<Vec<(BitConfig, bool)>, sqlx::Error> {
         let bit_configs = self.get_all_bit_configs().await?;
         let mut active_bits = Vec::new();
 
         for bit_config in bit_configs {
-            if !bit_config.enabled || bit_config.word_index < 0 || bit_config.word_index >= word_data.len() as i32 {
+            if !bit_config.enabled {
                 continue;
             }
 
-            let word_value = word_data[bit_config.word_index as usize];
-            let bit_value = (word_value >> bit_config.bit_index) & 1 == 1;
+            // Condição combinada (AND/OR/NOT) ou apenas o bit configurado
+            let bit_value = bit_condition::is_active(&bit_config.condition, bit_config.word_index, bit_config.bit_index, variables);
             
             active_bits.push((bit_config, bit_value));
         }
@@ -668,88 +1060,65 @@ impl Database {
         Ok(active_bits)
     }
 
-    // MÃ©todos para gerenciar vÃ­deos
-    pub async fn get_all_videos(&self) -> Result<Vec<VideoConfig>, sqlx::Error> {
-        let rows = sqlx::query("SELECT id, name, file_path, duration, enabled, priority, description, COALESCE(display_order, 0) as display_order FROM video_configs ORDER BY display_order, priority DESC, name")
+    // Métodos para gerenciar o mapeamento de dados do PLC
+    pub async fn get_all_data_mappings(&self) -> Result<Vec<DataMapping>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, word_index, mapping_type, bit_index, scale, value_offset, signed, enabled, description FROM data_mappings ORDER BY word_index, bit_index, name")
             .fetch_all(&self.pool)
             .await?;
 
-        Ok(rows.into_iter().map(|row| VideoConfig {
+        Ok(rows.into_iter().map(|row| DataMapping {
             id: row.get("id"),
             name: row.get("name"),
-            file_path: row.get("file_path"),
-            duration: row.get("duration"),
+            word_index: row.get("word_index"),
+            mapping_type: row.get("mapping_type"),
+            bit_index: row.get("bit_index"),
+            scale: row.get("scale"),
+            offset: row.get("value_offset"),
+            signed: row.get::<i64, _>("signed") != 0,
             enabled: row.get::<i64, _>("enabled") != 0,
-            priority: row.get("priority"),
             description: row.get("description"),
-            display_order: row.get("display_order"),
         }).collect())
     }
 
-    pub async fn get_video(&self, id: i64) -> Result<Option<VideoConfig>, sqlx::Error> {
-        let row = sqlx::query("SELECT id, name, file_path, duration, enabled, priority, description, COALESCE(display_order, 0) as display_order FROM video_configs WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?;
-
-        Ok(row.map(|r| VideoConfig {
-            id: r.get("id"),
-            name: r.get("name"),
-            file_path: r.get("file_path"),
-            duration: r.get("duration"),
-            enabled: r.get::<i64, _>("enabled") != 0,
-            priority: r.get("priority"),
-            description: r.get("description"),
-            display_order: r.get("display_order"),
-        }))
-    }
-
-    pub async fn add_video(&self, name: &str, file_path: &str, duration: i32, enabled: bool, priority: i32, description: &str) -> Result<i64, sqlx::Error> {
-        println!("🗄️ [DB] add_video: name='{}', file_path='{}', duration={}, enabled={}, priority={}, description='{}'", 
-            name, file_path, duration, enabled, priority, description);
-        
-        // Obter o próximo display_order
-        let next_order = sqlx::query("SELECT COALESCE(MAX(display_order), 0) + 1 as next_order FROM video_configs")
-            .fetch_one(&self.pool)
-            .await?
-            .get::<i32, _>("next_order");
-        
+    pub async fn add_data_mapping(&self, name: &str, word_index: i32, mapping_type: &str, bit_index: i32, scale: f64, offset: f64, signed: bool, enabled: bool, description: &str) -> Result<i64, sqlx::Error> {
         let result = sqlx::query(
             r#"
-            INSERT INTO video_configs (name, file_path, duration, enabled, priority, description, display_order)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO data_mappings (name, word_index, mapping_type, bit_index, scale, value_offset, signed, enabled, description)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(name)
-        .bind(file_path)
-        .bind(duration)
+        .bind(word_index)
+        .bind(mapping_type)
+        .bind(bit_index)
+        .bind(scale)
+        .bind(offset)
+        .bind(signed as i64)
         .bind(enabled as i64)
-        .bind(priority)
         .bind(description)
-        .bind(next_order)
         .execute(&self.pool)
         .await?;
         
-        let id = result.last_insert_rowid();
-        println!("✅ [DB] Vídeo inserido com ID: {} e ordem: {}", id, next_order);
-        Ok(id)
+        Ok(result.last_insert_rowid())
     }
 
-    pub async fn update_video(&self, id: i64, name: &str, file_path: &str, duration: i32, enabled: bool, priority: i32, description: &str, display_order: i32) -> Result<(), sqlx::Error> {
+    pub async fn update_data_mapping(&self, id: i64, name: &str, word_index: i32, mapping_type: &str, bit_index: i32, scale: f64, offset: f64, signed: bool, enabled: bool, description: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            UPDATE video_configs 
-            SET name = ?, file_path = ?, duration = ?, enabled = ?, priority = ?, description = ?, display_order = ?, updated_at = CURRENT_TIMESTAMP 
+            UPDATE data_mappings 
+            SET name = ?, word_index = ?, mapping_type = ?, bit_index = ?, scale = ?, value_offset = ?, signed = ?, enabled = ?, description = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
         )
         .bind(name)
-        .bind(file_path)
-        .bind(duration)
+        .bind(word_index)
+        .bind(mapping_type)
+        .bind(bit_index)
+        .bind(scale)
+        .bind(offset)
+        .bind(signed as i64)
         .bind(enabled as i64)
-        .bind(priority)
         .bind(description)
-        .bind(display_order)
         .bind(id)
         .execute(&self.pool)
         .await?;
@@ -757,8 +1126,8 @@ impl Database {
         Ok(())
     }
 
-    pub async fn delete_video(&self, id: i64) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM video_configs WHERE id = ?")
+    pub async fn delete_data_mapping(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM data_mappings WHERE id = ?")
             .bind(id)
             .execute(&self.pool)
             .await?;
@@ -766,57 +1135,469 @@ impl Database {
         Ok(())
     }
 
-    pub async fn get_enabled_videos(&self) -> Result<Vec<VideoConfig>, sqlx::Error> {
-        println!("🎬 [DB] get_enabled_videos chamado");
-        let rows = sqlx::query("SELECT id, name, file_path, duration, enabled, priority, description, COALESCE(display_order, 0) as display_order FROM video_configs WHERE enabled = 1 ORDER BY display_order, priority DESC, name")
+    // Métodos para gerenciar o protocolo por origem
+    pub async fn get_all_protocol_configs(&self) -> Result<Vec<ProtocolConfig>, sqlx::Error> {
+        let rows = sqlx::query("SELECT source, protocol, handshake FROM protocol_configs ORDER BY source")
             .fetch_all(&self.pool)
             .await?;
 
-        let videos: Vec<VideoConfig> = rows.into_iter().map(|row| VideoConfig {
-            id: row.get("id"),
-            name: row.get("name"),
-            file_path: row.get("file_path"),
-            duration: row.get("duration"),
-            enabled: row.get::<i64, _>("enabled") != 0,
-            priority: row.get("priority"),
-            description: row.get("description"),
-            display_order: row.get("display_order"),
-        }).collect();
+        Ok(rows.into_iter().map(|row| ProtocolConfig {
+            source: row.get("source"),
+            protocol: row.get("protocol"),
+            handshake: row.get("handshake"),
+        }).collect())
+    }
+
+    pub async fn save_protocol_config(&self, source: &str, protocol: &str, handshake: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO protocol_configs (source, protocol, handshake, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(source) DO UPDATE SET protocol = excluded.protocol, handshake = excluded.handshake, updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(source)
+        .bind(protocol)
+        .bind(handshake)
+        .execute(&self.pool)
+        .await?;
         
-        println!("✅ [DB] get_enabled_videos retornando {} vídeos", videos.len());
-        for video in &videos {
-            println!("   📹 [DB] - {} ({}s) - enabled: {}", video.name, video.duration, video.enabled);
+        Ok(())
+    }
+
+    pub async fn delete_protocol_config(&self, source: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM protocol_configs WHERE source = ?")
+            .bind(source)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Métodos para gerenciar temas do painel
+    fn row_to_theme(row: &sqlx::sqlite::SqliteRow) -> PanelTheme {
+        PanelTheme {
+            id: row.get("id"),
+            name: row.get("name"),
+            background_color: row.get("background_color"),
+            background_image: row.get("background_image"),
+            font_family: row.get("font_family"),
+            font_weight: row.get("font_weight"),
+            text_color: row.get("text_color"),
+            override_bit_fonts: row.get::<i64, _>("override_bit_fonts") != 0,
+            logo_path: row.get("logo_path"),
+            logo_position: row.get("logo_position"),
         }
+    }
+
+    pub async fn get_all_themes(&self) -> Result<Vec<PanelTheme>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, background_color, background_image, font_family, font_weight, text_color, override_bit_fonts, logo_path, logo_position FROM themes ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(Self::row_to_theme).collect())
+    }
+
+    pub async fn get_theme(&self, id: i64) -> Result<Option<PanelTheme>, sqlx::Error> {
+        let row = sqlx::query("SELECT id, name, background_color, background_image, font_family, font_weight, text_color, override_bit_fonts, logo_path, logo_position FROM themes WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(Self::row_to_theme))
+    }
+
+    /// Tema indicado em display_configs.active_theme (ou o primeiro cadastrado)
+    pub async fn get_active_theme(&self) -> Result<Option<PanelTheme>, sqlx::Error> {
+        if let Some(id) = self.get_display_config(ACTIVE_THEME_KEY).await?.and_then(|v| v.parse::<i64>().ok()) {
+            if let Some(theme) = self.get_theme(id).await? {
+                return Ok(Some(theme));
+            }
+        }
+        let row = sqlx::query("SELECT id, name, background_color, background_image, font_family, font_weight, text_color, override_bit_fonts, logo_path, logo_position FROM themes ORDER BY id LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(Self::row_to_theme))
+    }
+
+    pub async fn add_theme(&self, theme: &PanelTheme) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO themes (name, background_color, background_image, font_family, font_weight, text_color, override_bit_fonts, logo_path, logo_position)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&theme.name)
+        .bind(&theme.background_color)
+        .bind(&theme.background_image)
+        .bind(&theme.font_family)
+        .bind(&theme.font_weight)
+        .bind(&theme.text_color)
+        .bind(theme.override_bit_fonts as i64)
+        .bind(&theme.logo_path)
+        .bind(&theme.logo_position)
+        .execute(&self.pool)
+        .await?;
         
-        Ok(videos)
+        Ok(result.last_insert_rowid())
     }
 
-    pub async fn clear_all_videos(&self) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM video_configs")
+    pub async fn update_theme(&self, theme: &PanelTheme) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE themes 
+            SET name = ?, background_color = ?, background_image = ?, font_family = ?, font_weight = ?, text_color = ?, override_bit_fonts = ?, logo_path = ?, logo_position = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(&theme.name)
+        .bind(&theme.background_color)
+        .bind(&theme.background_image)
+        .bind(&theme.font_family)
+        .bind(&theme.font_weight)
+        .bind(&theme.text_color)
+        .bind(theme.override_bit_fonts as i64)
+        .bind(&theme.logo_path)
+        .bind(&theme.logo_position)
+        .bind(theme.id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    pub async fn delete_theme(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM themes WHERE id = ?")
+            .bind(id)
             .execute(&self.pool)
             .await?;
+        
         Ok(())
     }
 
-    pub async fn reorder_video(&self, id: i64, new_order: i32) -> Result<(), sqlx::Error> {
-        println!("🔄 [DB] Reordenando vídeo ID={} para ordem={}", id, new_order);
+    // Métodos para gerenciar mostradores analógicos
+    pub async fn get_all_analog_displays(&self) -> Result<Vec<AnalogDisplay>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, label, word_index, scale, value_offset, signed, min_value, max_value, unit, decimals, enabled, display_order FROM analog_displays ORDER BY display_order, id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| AnalogDisplay {
+            id: row.get("id"),
+            label: row.get("label"),
+            word_index: row.get("word_index"),
+            scale: row.get("scale"),
+            offset: row.get("value_offset"),
+            signed: row.get::<i64, _>("signed") != 0,
+            min_value: row.get("min_value"),
+            max_value: row.get("max_value"),
+            unit: row.get("unit"),
+            decimals: row.get("decimals"),
+            enabled: row.get::<i64, _>("enabled") != 0,
+            display_order: row.get("display_order"),
+        }).collect())
+    }
+
+    pub async fn add_analog_display(&self, display: &AnalogDisplay) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO analog_displays (label, word_index, scale, value_offset, signed, min_value, max_value, unit, decimals, enabled, display_order)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&display.label)
+        .bind(display.word_index)
+        .bind(display.scale)
+        .bind(display.offset)
+        .bind(display.signed as i64)
+        .bind(display.min_value)
+        .bind(display.max_value)
+        .bind(&display.unit)
+        .bind(display.decimals)
+        .bind(display.enabled as i64)
+        .bind(display.display_order)
+        .execute(&self.pool)
+        .await?;
         
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn update_analog_display(&self, display: &AnalogDisplay) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            UPDATE video_configs 
-            SET display_order = ?, updated_at = CURRENT_TIMESTAMP 
+            UPDATE analog_displays 
+            SET label = ?, word_index = ?, scale = ?, value_offset = ?, signed = ?, min_value = ?, max_value = ?, unit = ?, decimals = ?, enabled = ?, display_order = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
         )
-        .bind(new_order)
-        .bind(id)
+        .bind(&display.label)
+        .bind(display.word_index)
+        .bind(display.scale)
+        .bind(display.offset)
+        .bind(display.signed as i64)
+        .bind(display.min_value)
+        .bind(display.max_value)
+        .bind(&display.unit)
+        .bind(display.decimals)
+        .bind(display.enabled as i64)
+        .bind(display.display_order)
+        .bind(display.id)
         .execute(&self.pool)
         .await?;
         
-        println!("✅ [DB] Vídeo reordenado com sucesso");
         Ok(())
     }
 
+    pub async fn delete_analog_display(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM analog_displays WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Métodos para gerenciar contadores regressivos
+    pub async fn get_all_countdown_timers(&self) -> Result<Vec<CountdownTimer>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, label, source, word_index, phase_number, duration_s, enabled FROM countdown_timers ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| CountdownTimer {
+            id: row.get("id"),
+            name: row.get("name"),
+            label: row.get("label"),
+            source: row.get("source"),
+            word_index: row.get("word_index"),
+            phase_number: row.get("phase_number"),
+            duration_s: row.get("duration_s"),
+            enabled: row.get::<i64, _>("enabled") != 0,
+        }).collect())
+    }
+
+    pub async fn add_countdown_timer(&self, timer: &CountdownTimer) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO countdown_timers (name, label, source, word_index, phase_number, duration_s, enabled)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&timer.name)
+        .bind(&timer.label)
+        .bind(&timer.source)
+        .bind(timer.word_index)
+        .bind(timer.phase_number)
+        .bind(timer.duration_s)
+        .bind(timer.enabled as i64)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn update_countdown_timer(&self, timer: &CountdownTimer) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE countdown_timers 
+            SET name = ?, label = ?, source = ?, word_index = ?, phase_number = ?, duration_s = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(&timer.name)
+        .bind(&timer.label)
+        .bind(&timer.source)
+        .bind(timer.word_index)
+        .bind(timer.phase_number)
+        .bind(timer.duration_s)
+        .bind(timer.enabled as i64)
+        .bind(timer.id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    pub async fn delete_countdown_timer(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM countdown_timers WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Métodos para gerenciar transições do painel
+    pub async fn get_all_transitions(&self) -> Result<Vec<TransitionConfig>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, scope, min_priority, effect, duration_ms, enabled FROM transition_configs ORDER BY scope, min_priority DESC, name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| TransitionConfig {
+            id: row.get("id"),
+            name: row.get("name"),
+            scope: row.get("scope"),
+            min_priority: row.get("min_priority"),
+            effect: row.get("effect"),
+            duration_ms: row.get("duration_ms"),
+            enabled: row.get::<i64, _>("enabled") != 0,
+        }).collect())
+    }
+
+    pub async fn add_transition(&self, transition: &TransitionConfig) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO transition_configs (name, scope, min_priority, effect, duration_ms, enabled)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&transition.name)
+        .bind(&transition.scope)
+        .bind(transition.min_priority)
+        .bind(&transition.effect)
+        .bind(transition.duration_ms)
+        .bind(transition.enabled as i64)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn update_transition(&self, transition: &TransitionConfig) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE transition_configs 
+            SET name = ?, scope = ?, min_priority = ?, effect = ?, duration_ms = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(&transition.name)
+        .bind(&transition.scope)
+        .bind(transition.min_priority)
+        .bind(&transition.effect)
+        .bind(transition.duration_ms)
+        .bind(transition.enabled as i64)
+        .bind(transition.id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    pub async fn delete_transition(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM transition_configs WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Métodos para gerenciar eventos programados do painel
+    pub async fn get_all_panel_events(&self) -> Result<Vec<PanelEvent>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, starts_at, ends_at, messages, video_ids, theme_id, priority, enabled FROM panel_events ORDER BY starts_at, priority DESC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| PanelEvent {
+            id: row.get("id"),
+            name: row.get("name"),
+            starts_at: row.get("starts_at"),
+            ends_at: row.get("ends_at"),
+            messages: serde_json::from_str(&row.get::<String, _>("messages")).unwrap_or_default(),
+            video_ids: serde_json::from_str(&row.get::<String, _>("video_ids")).unwrap_or_default(),
+            theme_id: row.get("theme_id"),
+            priority: row.get("priority"),
+            enabled: row.get::<i64, _>("enabled") != 0,
+        }).collect())
+    }
+
+    pub async fn delete_panel_event(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM panel_events WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // MÃ©todos para gerenciar vÃ­deos
+    pub async fn get_all_videos(&self) -> Result<Vec<VideoConfig>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, file_path, duration, enabled, priority, description, COALESCE(display_order, 0) as display_order, COALESCE(volume, 100) as volume, COALESCE(muted, 0) as muted FROM video_configs ORDER BY display_order, priority DESC, name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| VideoConfig {
+            id: row.get("id"),
+            name: row.get("name"),
+            file_path: row.get("file_path"),
+            duration: row.get("duration"),
+            enabled: row.get::<i64, _>("enabled") != 0,
+            priority: row.get("priority"),
+            description: row.get("description"),
+            display_order: row.get("display_order"),
+            volume: row.get("volume"),
+            muted: row.get::<i64, _>("muted") != 0,
+        }).collect())
+    }
+
+    pub async fn get_video(&self, id: i64) -> Result<Option<VideoConfig>, sqlx::Error> {
+        let row = sqlx::query("SELECT id, name, file_path, duration, enabled, priority, description, COALESCE(display_order, 0) as display_order, COALESCE(volume, 100) as volume, COALESCE(muted, 0) as muted FROM video_configs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| VideoConfig {
+            id: r.get("id"),
+            name: r.get("name"),
+            file_path: r.get("file_path"),
+            duration: r.get("duration"),
+            enabled: r.get::<i64, _>("enabled") != 0,
+            priority: r.get("priority"),
+            description: r.get("description"),
+            display_order: r.get("display_order"),
+            volume: r.get("volume"),
+            muted: r.get::<i64, _>("muted") != 0,
+        }))
+    }
+
+    pub async fn set_video_audio(&self, id: i64, volume: i32, muted: bool) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE video_configs SET volume = ?, muted = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(volume)
+            .bind(muted as i64)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    pub async fn get_enabled_videos(&self) -> Result<Vec<VideoConfig>, sqlx::Error> {
+        println!("🎬 [DB] get_enabled_videos chamado");
+        let rows = sqlx::query("SELECT id, name, file_path, duration, enabled, priority, description, COALESCE(display_order, 0) as display_order, COALESCE(volume, 100) as volume, COALESCE(muted, 0) as muted FROM video_configs WHERE enabled = 1 ORDER BY display_order, priority DESC, name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let videos: Vec<VideoConfig> = rows.into_iter().map(|row| VideoConfig {
+            id: row.get("id"),
+            name: row.get("name"),
+            file_path: row.get("file_path"),
+            duration: row.get("duration"),
+            enabled: row.get::<i64, _>("enabled") != 0,
+            priority: row.get("priority"),
+            description: row.get("description"),
+            display_order: row.get("display_order"),
+            volume: row.get("volume"),
+            muted: row.get::<i64, _>("muted") != 0,
+        }).collect();
+        
+        println!("✅ [DB] get_enabled_videos retornando {} vídeos", videos.len());
+        for video in &videos {
+            println!("   📹 [DB] - {} ({}s) - enabled: {}", video.name, video.duration, video.enabled);
+        }
+        
+        Ok(videos)
+    }
+
     // Função para verificar se os vídeos devem ser exibidos baseado no bit PLC
     pub async fn should_show_videos(&self, plc_data: &[u16]) -> Result<bool, sqlx::Error> {
         // Obter configurações do bit de controle
@@ -849,6 +1630,361 @@ impl Database {
         }
     }
 
+    // ===== SINCRONIZAÇÃO DE CONTEÚDO (campanhas centrais) =====
+    /// Aplica um pacote de conteúdo em uma única transação: textos e bits são
+    /// atualizados/inseridos; vídeos sincronizados anteriormente são substituídos.
+    /// `videos` já devem estar baixados e verificados (file_path local).
+    pub async fn apply_content_bundle(
+        &self,
+        texts: &[(String, String)],
+        bits: &[BitConfig],
+        videos: &[VideoConfig],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for (key, text) in texts {
+            sqlx::query(
+                r#"
+                INSERT INTO text_configs (key, text) VALUES (?, ?)
+                ON CONFLICT(key) DO UPDATE SET text = excluded.text, updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(key)
+            .bind(text)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for bit in bits {
+            sqlx::query(
+                r#"
+                INSERT INTO bit_configs (word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, font_family, font_weight, text_shadow, letter_spacing, use_template, message_template, condition)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(word_index, bit_index) DO UPDATE SET
+                    name = excluded.name, message = excluded.message, message_off = excluded.message_off,
+                    enabled = excluded.enabled, priority = excluded.priority, color = excluded.color,
+                    font_size = excluded.font_size, position = excluded.position, font_family = excluded.font_family,
+                    font_weight = excluded.font_weight, text_shadow = excluded.text_shadow,
+                    letter_spacing = excluded.letter_spacing, use_template = excluded.use_template,
+                    message_template = excluded.message_template, condition = excluded.condition, updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(bit.word_index)
+            .bind(bit.bit_index)
+            .bind(&bit.name)
+            .bind(&bit.message)
+            .bind(&bit.message_off)
+            .bind(bit.enabled as i64)
+            .bind(bit.priority)
+            .bind(&bit.color)
+            .bind(bit.font_size)
+            .bind(&bit.position)
+            .bind(&bit.font_family)
+            .bind(&bit.font_weight)
+            .bind(bit.text_shadow as i64)
+            .bind(bit.letter_spacing)
+            .bind(bit.use_template as i64)
+            .bind(&bit.message_template)
+            .bind(&bit.condition)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM video_configs WHERE synced = 1")
+            .execute(&mut *tx)
+            .await?;
+        for video in videos {
+            sqlx::query(
+                r#"
+                INSERT INTO video_configs (name, file_path, duration, enabled, priority, description, display_order, volume, muted, synced)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1)
+                "#,
+            )
+            .bind(&video.name)
+            .bind(&video.file_path)
+            .bind(video.duration)
+            .bind(video.enabled as i64)
+            .bind(video.priority)
+            .bind(&video.description)
+            .bind(video.display_order)
+            .bind(video.volume)
+            .bind(video.muted as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    // ===== APROVAÇÃO DE CONTEÚDO =====
+    pub async fn save_content_draft(&self, entity_key: &str, payload: &str, author: &str) -> Result<i64, sqlx::Error> {
+        // Novo rascunho do mesmo item substitui o anterior (e volta ao fim da fila)
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM content_drafts WHERE entity_key = ?")
+            .bind(entity_key)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("INSERT INTO content_drafts (entity_key, payload, author) VALUES (?, ?, ?)")
+            .bind(entity_key)
+            .bind(payload)
+            .bind(author)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        
+        Ok(result.last_insert_rowid())
+    }
+
+    /// (id, entity_key, payload, author, created_at) em ordem de criação
+    pub async fn get_content_drafts(&self) -> Result<Vec<(i64, String, String, String, String)>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, entity_key, payload, author, CAST(created_at AS TEXT) as created_at FROM content_drafts ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| (
+            row.get("id"),
+            row.get("entity_key"),
+            row.get("payload"),
+            row.get("author"),
+            row.get("created_at"),
+        )).collect())
+    }
+
+    pub async fn delete_content_drafts(&self, ids: &[i64]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for id in ids {
+            sqlx::query("DELETE FROM content_drafts WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    async fn apply_content_change(tx: &mut sqlx::Transaction<'_, Sqlite>, change: &ContentChange) -> Result<(), sqlx::Error> {
+        match change {
+            ContentChange::Text { key, text } => {
+                sqlx::query("UPDATE text_configs SET text = ?, updated_at = CURRENT_TIMESTAMP WHERE key = ?")
+                    .bind(text)
+                    .bind(key)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            ContentChange::Phase { phase_number, title, description, color } => {
+                sqlx::query("UPDATE phase_configs SET title = ?, description = ?, color = ?, updated_at = CURRENT_TIMESTAMP WHERE phase_number = ?")
+                    .bind(title)
+                    .bind(description)
+                    .bind(color)
+                    .bind(phase_number)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            ContentChange::BitConfig { config: bit } => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO bit_configs (word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, font_family, font_weight, text_shadow, letter_spacing, use_template, message_template, condition)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT(word_index, bit_index) DO UPDATE SET
+                        name = excluded.name, message = excluded.message, message_off = excluded.message_off,
+                        enabled = excluded.enabled, priority = excluded.priority, color = excluded.color,
+                        font_size = excluded.font_size, position = excluded.position, font_family = excluded.font_family,
+                        font_weight = excluded.font_weight, text_shadow = excluded.text_shadow,
+                        letter_spacing = excluded.letter_spacing, use_template = excluded.use_template,
+                        message_template = excluded.message_template, condition = excluded.condition, updated_at = CURRENT_TIMESTAMP
+                    "#,
+                )
+                .bind(bit.word_index)
+                .bind(bit.bit_index)
+                .bind(&bit.name)
+                .bind(&bit.message)
+                .bind(&bit.message_off)
+                .bind(bit.enabled as i64)
+                .bind(bit.priority)
+                .bind(&bit.color)
+                .bind(bit.font_size)
+                .bind(&bit.position)
+                .bind(&bit.font_family)
+                .bind(&bit.font_weight)
+                .bind(bit.text_shadow as i64)
+                .bind(bit.letter_spacing)
+                .bind(bit.use_template as i64)
+                .bind(&bit.message_template)
+                .bind(&bit.condition)
+                .execute(&mut **tx)
+                .await?;
+            }
+            ContentChange::DeleteBitConfig { word_index, bit_index } => {
+                sqlx::query("DELETE FROM bit_configs WHERE word_index = ? AND bit_index = ?")
+                    .bind(word_index)
+                    .bind(bit_index)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            ContentChange::AddVideo { name, file_path, duration, enabled, priority, description } => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO video_configs (name, file_path, duration, enabled, priority, description, display_order)
+                    VALUES (?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(display_order), 0) + 1 FROM video_configs))
+                    "#,
+                )
+                .bind(name)
+                .bind(file_path)
+                .bind(duration)
+                .bind(*enabled as i64)
+                .bind(priority)
+                .bind(description)
+                .execute(&mut **tx)
+                .await?;
+            }
+            ContentChange::UpdateVideo { id, name, file_path, duration, enabled, priority, description, display_order } => {
+                sqlx::query(
+                    r#"
+                    UPDATE video_configs
+                    SET name = ?, file_path = ?, duration = ?, enabled = ?, priority = ?, description = ?, display_order = ?, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = ?
+                    "#,
+                )
+                .bind(name)
+                .bind(file_path)
+                .bind(duration)
+                .bind(*enabled as i64)
+                .bind(priority)
+                .bind(description)
+                .bind(display_order)
+                .bind(id)
+                .execute(&mut **tx)
+                .await?;
+            }
+            ContentChange::DeleteVideo { id } => {
+                sqlx::query("DELETE FROM video_configs WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            ContentChange::ReorderVideo { id, display_order } => {
+                sqlx::query("UPDATE video_configs SET display_order = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+                    .bind(display_order)
+                    .bind(id)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            ContentChange::ClearAllVideos => {
+                sqlx::query("DELETE FROM video_configs")
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            ContentChange::AddPanelEvent { event } => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO panel_events (name, starts_at, ends_at, messages, video_ids, theme_id, priority, enabled)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(&event.name)
+                .bind(&event.starts_at)
+                .bind(&event.ends_at)
+                .bind(serde_json::to_string(&event.messages).unwrap_or_else(|_| "[]".to_string()))
+                .bind(serde_json::to_string(&event.video_ids).unwrap_or_else(|_| "[]".to_string()))
+                .bind(event.theme_id)
+                .bind(event.priority)
+                .bind(event.enabled as i64)
+                .execute(&mut **tx)
+                .await?;
+            }
+            ContentChange::UpdatePanelEvent { event } => {
+                sqlx::query(
+                    r#"
+                    UPDATE panel_events
+                    SET name = ?, starts_at = ?, ends_at = ?, messages = ?, video_ids = ?, theme_id = ?, priority = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = ?
+                    "#,
+                )
+                .bind(&event.name)
+                .bind(&event.starts_at)
+                .bind(&event.ends_at)
+                .bind(serde_json::to_string(&event.messages).unwrap_or_else(|_| "[]".to_string()))
+                .bind(serde_json::to_string(&event.video_ids).unwrap_or_else(|_| "[]".to_string()))
+                .bind(event.theme_id)
+                .bind(event.priority)
+                .bind(event.enabled as i64)
+                .bind(event.id)
+                .execute(&mut **tx)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn apply_content_changes(&self, changes: &[ContentChange]) -> Result<(), sqlx::Error> {
+        self.apply_content_changes_and_clear_drafts(changes, &[]).await
+    }
+
+    /// Aplica as alterações e remove os rascunhos correspondentes na mesma transação
+    pub async fn apply_content_changes_and_clear_drafts(&self, changes: &[ContentChange], draft_ids: &[i64]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for change in changes {
+            Self::apply_content_change(&mut tx, change).await?;
+        }
+        for id in draft_ids {
+            sqlx::query("DELETE FROM content_drafts WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// (role, pin_hash) do usuário
+    pub async fn get_content_user(&self, username: &str) -> Result<Option<(String, String)>, sqlx::Error> {
+        let row = sqlx::query("SELECT role, pin_hash FROM content_users WHERE username = ?")
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| (r.get("role"), r.get("pin_hash"))))
+    }
+
+    pub async fn list_content_users(&self) -> Result<Vec<(String, String)>, sqlx::Error> {
+        let rows = sqlx::query("SELECT username, role FROM content_users ORDER BY username")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| (row.get("username"), row.get("role"))).collect())
+    }
+
+    pub async fn count_content_approvers(&self) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM content_users WHERE role = 'approver'")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get("count"))
+    }
+
+    pub async fn save_content_user(&self, username: &str, role: &str, pin_hash: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_users (username, role, pin_hash) VALUES (?, ?, ?)
+            ON CONFLICT(username) DO UPDATE SET role = excluded.role, pin_hash = excluded.pin_hash
+            "#,
+        )
+        .bind(username)
+        .bind(role)
+        .bind(pin_hash)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    pub async fn delete_content_user(&self, username: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM content_users WHERE username = ?")
+            .bind(username)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
     // ===== SISTEMA DE LOGS =====
     pub async fn add_system_log(
         &self, 
@@ -916,6 +2052,29 @@ impl Database {
         Ok(logs)
     }
 
+    /// Logs com id > after_id em ordem crescente (encaminhamento ao plc-hmi)
+    pub async fn get_logs_after(&self, after_id: i64, limit: i32) -> Result<Vec<SystemLog>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM system_logs WHERE id > ? ORDER BY id ASC LIMIT ?")
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut logs = Vec::new();
+        for row in rows {
+            logs.push(SystemLog {
+                id: row.get("id"),
+                timestamp: row.get("timestamp"),
+                level: row.get("level"),
+                category: row.get("category"),
+                message: row.get("message"),
+                details: row.get("details"),
+            });
+        }
+
+        Ok(logs)
+    }
+
     pub async fn clear_old_logs(&self, days: i32) -> Result<(), sqlx::Error> {
         let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
         let cutoff_str = cutoff.to_rfc3339();
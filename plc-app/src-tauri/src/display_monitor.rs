@@ -0,0 +1,216 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Listener, Manager};
+use tokio::sync::Mutex;
+use crate::database::Database;
+
+// Monitor de saúde do display: o painel (webview) emite "display-metrics" a cada
+// poucos segundos com FPS, erros de decodificação e a posição do vídeo. Aqui
+// detectamos degradação e travamentos (vídeo parado / webview sem reportar),
+// registramos em system_logs e acionamos o watchdog configurado.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const REPORT_TIMEOUT: Duration = Duration::from_secs(30);   // Painel aberto sem métricas = webview travado
+const VIDEO_STALL_TIMEOUT: Duration = Duration::from_secs(20); // Vídeo "tocando" sem avançar
+const MIN_FPS: f64 = 15.0;
+const LOW_FPS_REPORTS: u32 = 3;        // Relatórios seguidos abaixo do mínimo
+const ESCALATION_WINDOW: Duration = Duration::from_secs(600);
+const ESCALATION_STALLS: usize = 3;    // Travamentos na janela para reiniciar o app
+const RECOVERY_GRACE: Duration = Duration::from_secs(30);
+
+pub const KEY_WATCHDOG_ACTION: &str = "display_watchdog_action"; // "log" | "reload_panel" | "restart_app"
+
+/// Payload do evento "display-metrics" enviado pelo painel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayMetrics {
+    pub fps: f64,
+    pub decode_errors: u64,   // Acumulado desde que o painel abriu
+    pub dropped_frames: u64,  // Acumulado (getVideoPlaybackQuality)
+    pub video_active: bool,   // Há vídeo em exibição que deveria estar tocando
+    pub video_time_s: f64,
+    #[serde(default)]
+    pub video_duration_s: f64, // 0 = sem vídeo ou duração desconhecida
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct DisplayHealthStatus {
+    pub last_metrics: Option<DisplayMetrics>,
+    pub seconds_since_report: Option<u64>,
+    pub monitors: usize,
+    pub degraded: bool,
+    pub stalled: bool,
+    pub stall_count: u64,
+    pub last_issue: Option<String>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    last_metrics: Option<DisplayMetrics>,
+    last_report: Option<Instant>,
+    panel_opened_at: Option<Instant>,
+    video_progress_at: Option<Instant>, // Última vez em que video_time_s avançou
+    low_fps_reports: u32,
+    reported_decode_errors: u64,
+    degraded: bool,
+    stalled: bool,
+    stall_count: u64,
+    recent_stalls: Vec<Instant>,
+    last_action: Option<Instant>,
+    monitors: Option<usize>,
+    last_issue: Option<String>,
+}
+
+pub type DisplayMonitorState = Arc<Mutex<DisplayHealthStatus>>;
+
+async fn log(database: &Arc<Mutex<Option<Arc<Database>>>>, level: &str, message: &str, details: &str) {
+    println!("🖥️ [Display] {} - {}", message, details);
+    if let Some(db) = database.lock().await.as_ref() {
+        let _ = db.add_system_log(level, "display", message, details).await;
+    }
+}
+
+pub fn start_display_monitor(
+    app_handle: AppHandle,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    status: DisplayMonitorState,
+) {
+    let state = Arc::new(Mutex::new(MonitorState::default()));
+
+    // Métricas do webview
+    let state_listener = state.clone();
+    app_handle.listen("display-metrics", move |event| {
+        let Ok(metrics) = serde_json::from_str::<DisplayMetrics>(event.payload()) else { return };
+        let state = state_listener.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut state = state.lock().await;
+            let now = Instant::now();
+            let advanced = state.last_metrics.as_ref()
+                .map(|prev| (metrics.video_time_s - prev.video_time_s).abs() > 0.05)
+                .unwrap_or(true);
+            if !metrics.video_active || advanced || state.video_progress_at.is_none() {
+                state.video_progress_at = Some(now);
+            }
+            state.low_fps_reports = if metrics.fps < MIN_FPS { state.low_fps_reports + 1 } else { 0 };
+            state.last_report = Some(now);
+            state.last_metrics = Some(metrics);
+        });
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let panel_open = app_handle.get_webview_window("panel").is_some();
+            let monitors = app_handle.available_monitors().map(|m| m.len()).unwrap_or(0);
+            let mut state = state.lock().await;
+            let now = Instant::now();
+
+            // Disponibilidade do display no SO
+            if state.monitors != Some(monitors) {
+                if monitors == 0 {
+                    log(&database, "error", "Nenhum monitor disponível", "O sistema operacional não reporta displays conectados").await;
+                } else if state.monitors == Some(0) {
+                    log(&database, "info", "Monitor disponível novamente", &format!("{} monitor(es)", monitors)).await;
+                }
+                state.monitors = Some(monitors);
+            }
+
+            // Degradação: FPS baixo sustentado e erros de decodificação
+            let degraded = state.low_fps_reports >= LOW_FPS_REPORTS;
+            if degraded != state.degraded {
+                state.degraded = degraded;
+                let fps = state.last_metrics.as_ref().map(|m| m.fps).unwrap_or(0.0);
+                if degraded {
+                    let issue = format!("FPS {:.1} abaixo de {:.0} por {} relatórios", fps, MIN_FPS, LOW_FPS_REPORTS);
+                    log(&database, "warning", "Desempenho do display degradado", &issue).await;
+                    state.last_issue = Some(issue);
+                } else {
+                    log(&database, "info", "Desempenho do display normalizado", &format!("FPS {:.1}", fps)).await;
+                }
+            }
+            let decode_errors = state.last_metrics.as_ref().map(|m| m.decode_errors).unwrap_or(0);
+            if decode_errors < state.reported_decode_errors {
+                state.reported_decode_errors = 0; // Painel recarregado: contador reiniciou
+            }
+            if decode_errors > state.reported_decode_errors {
+                let issue = format!("{} novo(s) erro(s) de decodificação", decode_errors - state.reported_decode_errors);
+                log(&database, "warning", "Erros de decodificação de vídeo", &issue).await;
+                state.reported_decode_errors = decode_errors;
+                state.last_issue = Some(issue);
+            }
+
+            // Painel fechado: descarta métricas antigas para não acusar travamento ao reabrir
+            if !panel_open {
+                state.panel_opened_at = None;
+                state.last_report = None;
+                state.last_metrics = None;
+                state.video_progress_at = None;
+                state.low_fps_reports = 0;
+            } else if state.panel_opened_at.is_none() {
+                state.panel_opened_at = Some(now);
+            }
+
+            // Travamento: webview sem reportar ou vídeo sem avançar
+            let in_grace = state.last_action.map(|t| now.duration_since(t) < RECOVERY_GRACE).unwrap_or(false);
+            let stall_reason = if !panel_open || in_grace {
+                None
+            } else if state.last_report.or(state.panel_opened_at).map(|t| now.duration_since(t) > REPORT_TIMEOUT).unwrap_or(false) {
+                Some(format!("Painel sem enviar métricas há mais de {}s", REPORT_TIMEOUT.as_secs()))
+            } else if state.last_metrics.as_ref().map(|m| m.video_active).unwrap_or(false)
+                && state.video_progress_at.map(|t| now.duration_since(t) > VIDEO_STALL_TIMEOUT).unwrap_or(false)
+            {
+                Some(format!("Vídeo parado há mais de {}s", VIDEO_STALL_TIMEOUT.as_secs()))
+            } else {
+                None
+            };
+
+            state.stalled = stall_reason.is_some();
+            if let Some(reason) = stall_reason {
+                state.stall_count += 1;
+                state.recent_stalls.retain(|t| now.duration_since(*t) < ESCALATION_WINDOW);
+                state.recent_stalls.push(now);
+                state.last_action = Some(now);
+                state.last_issue = Some(reason.clone());
+
+                let configured = match database.lock().await.as_ref() {
+                    Some(db) => db.get_display_config(KEY_WATCHDOG_ACTION).await.ok().flatten(),
+                    None => None,
+                };
+                let mut action = configured.unwrap_or_else(|| "reload_panel".to_string());
+                if action == "reload_panel" && state.recent_stalls.len() >= ESCALATION_STALLS {
+                    action = "restart_app".to_string();
+                }
+                log(&database, "error", "Display travado", &format!("{} - ação: {}", reason, action)).await;
+
+                match action.as_str() {
+                    "reload_panel" => {
+                        if let Some(panel) = app_handle.get_webview_window("panel") {
+                            let _ = panel.eval("window.location.reload()");
+                        }
+                        state.panel_opened_at = Some(now);
+                        state.last_report = None;
+                        state.video_progress_at = None;
+                    }
+                    "restart_app" => {
+                        log(&database, "critical", "Reiniciando aplicação pelo watchdog do display", &reason).await;
+                        app_handle.restart();
+                    }
+                    _ => {}
+                }
+            }
+
+            // Publicar estado para o comando get_display_health
+            let mut published = status.lock().await;
+            *published = DisplayHealthStatus {
+                last_metrics: state.last_metrics.clone(),
+                seconds_since_report: state.last_report.map(|t| now.duration_since(t).as_secs()),
+                monitors,
+                degraded: state.degraded,
+                stalled: state.stalled,
+                stall_count: state.stall_count,
+                last_issue: state.last_issue.clone(),
+            };
+        }
+    });
+}
@@ -0,0 +1,116 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+// ============================================================================
+// ERROS TIPADOS DOS COMANDOS
+// ============================================================================
+//
+// Mesmo contrato do plc-hmi (plc-hmi/src-tauri/src/error.rs): o erro chega ao
+// frontend como {"code": "DB_NOT_INITIALIZED", "message": "..."} e a UI decide
+// pelo código (src/utils/appError.ts). Funções auxiliares em String entram pelo
+// `?` como OTHER; os comandos classificam o erro na origem.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    NotRunning(String),        // Serviço parado (servidor TCP, monitor, ...)
+    AlreadyRunning(String),
+    DbNotInitialized(String),  // Banco ainda não aberto ou inacessível
+    PlcNotFound(String),       // PLC sem conexão ou desconhecido
+    NotFound(String),          // Vídeo, tema, evento, rascunho... inexistente
+    Timeout(String),
+    ConfigInvalid(String),     // Parâmetro ou configuração recusada
+    Io(String),                // Porta, arquivo, socket
+    Database(String),          // Falha de leitura/gravação no SQLite
+    Other(String),             // Erros ainda não classificados
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotRunning(_) => "NOT_RUNNING",
+            AppError::AlreadyRunning(_) => "ALREADY_RUNNING",
+            AppError::DbNotInitialized(_) => "DB_NOT_INITIALIZED",
+            AppError::PlcNotFound(_) => "PLC_NOT_FOUND",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Timeout(_) => "TIMEOUT",
+            AppError::ConfigInvalid(_) => "CONFIG_INVALID",
+            AppError::Io(_) => "IO",
+            AppError::Database(_) => "DATABASE",
+            AppError::Other(_) => "OTHER",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotRunning(message)
+            | AppError::AlreadyRunning(message)
+            | AppError::DbNotInitialized(message)
+            | AppError::PlcNotFound(message)
+            | AppError::NotFound(message)
+            | AppError::Timeout(message)
+            | AppError::ConfigInvalid(message)
+            | AppError::Io(message)
+            | AppError::Database(message)
+            | AppError::Other(message) => message,
+        }
+    }
+
+    /// "Banco de dados não inicializado"
+    pub fn db_not_initialized() -> Self {
+        AppError::DbNotInitialized("Banco de dados não inicializado".to_string())
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::RowNotFound => AppError::NotFound("Registro não encontrado".to_string()),
+            sqlx::Error::PoolTimedOut => AppError::Timeout(format!("Banco de dados não respondeu: {}", e)),
+            sqlx::Error::PoolClosed | sqlx::Error::Io(_) => AppError::DbNotInitialized(format!("Banco de dados inacessível: {}", e)),
+            _ => AppError::Database(format!("Erro no banco de dados: {:?}", e)),
+        }
+    }
+}
+
+/// Funções que ainda devolvem String podem usar `?` em chamadas com AppError
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.message().to_string()
+    }
+}
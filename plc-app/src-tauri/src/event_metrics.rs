@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Listener};
+use crate::database::Database;
+
+// Contadores dos eventos "plc-data" emitidos e do consumo informado pelas
+// janelas (evento "plc-data-consumed" com o último `seq` processado). A
+// diferença mostra quando a webview não acompanha o ciclo de 500 ms do PLC.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const LAG_WARNING_EVENTS: u64 = 20;                      // ~10s de atraso no ciclo de 500 ms
+const REPORT_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Relatório periódico enviado pela janela
+#[derive(Debug, Clone, Deserialize)]
+struct ConsumptionReport {
+    window: String,
+    last_seq: u64,
+    received: u64, // Eventos recebidos desde que a janela abriu
+}
+
+struct ConsumerState {
+    last_seq: u64,
+    received: u64,
+    reported_at: Instant,
+    lagging: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumerMetrics {
+    pub window: String,
+    pub last_seq: u64,
+    pub received: u64,
+    pub lag_events: u64,
+    pub seconds_since_report: u64,
+    pub lagging: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlcEventMetricsSnapshot {
+    pub emitted: u64,
+    pub consumers: Vec<ConsumerMetrics>,
+}
+
+#[derive(Default)]
+pub struct PlcEventMetrics {
+    emitted: AtomicU64,
+    last_emitted_at: Mutex<Option<Instant>>,
+    consumers: Mutex<HashMap<String, ConsumerState>>,
+}
+
+impl PlcEventMetrics {
+    /// Registra uma emissão de "plc-data" e retorna o `seq` a enviar no payload
+    pub fn next_seq(&self) -> u64 {
+        if let Ok(mut last) = self.last_emitted_at.lock() {
+            *last = Some(Instant::now());
+        }
+        self.emitted.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Tempo desde o último "plc-data" emitido (None = nenhum ainda)
+    pub fn last_emit_age(&self) -> Option<Duration> {
+        self.last_emitted_at.lock().ok()?.map(|at| at.elapsed())
+    }
+
+    fn record_report(&self, report: ConsumptionReport) {
+        if let Ok(mut consumers) = self.consumers.lock() {
+            let lagging = consumers.get(&report.window).map(|c| c.lagging).unwrap_or(false);
+            consumers.insert(report.window, ConsumerState {
+                last_seq: report.last_seq,
+                received: report.received,
+                reported_at: Instant::now(),
+                lagging,
+            });
+        }
+    }
+
+    pub fn snapshot(&self) -> PlcEventMetricsSnapshot {
+        let emitted = self.emitted.load(Ordering::SeqCst);
+        let consumers = match self.consumers.lock() {
+            Ok(consumers) => consumers.iter()
+                .map(|(window, c)| ConsumerMetrics {
+                    window: window.clone(),
+                    last_seq: c.last_seq,
+                    received: c.received,
+                    lag_events: emitted.saturating_sub(c.last_seq),
+                    seconds_since_report: c.reported_at.elapsed().as_secs(),
+                    lagging: c.lagging,
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        PlcEventMetricsSnapshot { emitted, consumers }
+    }
+}
+
+/// Escuta os relatórios de consumo e registra em system_logs quando uma janela fica para trás
+pub fn start_event_metrics(
+    app_handle: AppHandle,
+    metrics: Arc<PlcEventMetrics>,
+    database: Arc<tokio::sync::Mutex<Option<Arc<Database>>>>,
+) {
+    let metrics_listener = metrics.clone();
+    app_handle.listen("plc-data-consumed", move |event| {
+        if let Ok(report) = serde_json::from_str::<ConsumptionReport>(event.payload()) {
+            metrics_listener.record_report(report);
+        }
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let emitted = metrics.emitted.load(Ordering::SeqCst);
+
+            // Mudanças de estado (atrasada <-> em dia); janelas fechadas deixam de reportar e são descartadas
+            let mut changes = Vec::new();
+            if let Ok(mut consumers) = metrics.consumers.lock() {
+                consumers.retain(|_, c| c.reported_at.elapsed() < REPORT_STALE_AFTER);
+                for (window, consumer) in consumers.iter_mut() {
+                    let lag = emitted.saturating_sub(consumer.last_seq);
+                    let lagging = lag >= LAG_WARNING_EVENTS;
+                    if lagging != consumer.lagging {
+                        consumer.lagging = lagging;
+                        changes.push((window.clone(), lagging, lag));
+                    }
+                }
+            }
+
+            for (window, lagging, lag) in changes {
+                let (level, message) = if lagging {
+                    ("warning", format!("Janela '{}' não acompanha os dados do PLC", window))
+                } else {
+                    ("info", format!("Janela '{}' voltou a acompanhar os dados do PLC", window))
+                };
+                let details = format!("{} eventos emitidos, {} de atraso", emitted, lag);
+                println!("📊 {} - {}", message, details);
+                if let Some(db) = database.lock().await.as_ref() {
+                    let _ = db.add_system_log(level, "ui", &message, &details).await;
+                }
+            }
+        }
+    });
+}
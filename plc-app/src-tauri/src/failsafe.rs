@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use crate::database::Database;
+use crate::event_metrics::PlcEventMetrics;
+
+// Estado seguro do painel com dados do PLC velhos. Depois de `timeout_s` sem
+// plc-data (PLC desligado, cabo, servidor TCP parado) o backend força o painel
+// para o estado configurado (semáforo + mensagem, ex: vermelho e "INFORMAÇÃO
+// INDISPONÍVEL") e libera quando os dados voltam. Cada troca emite
+// "panel-failsafe" e fica em system_logs: o painel só exibe.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const MIN_TIMEOUT_S: u64 = 2;
+const MAX_TIMEOUT_S: u64 = 3600;
+pub const SEMAPHORES: &[&str] = &["vermelho", "amarelo", "verde", "apagado"];
+
+// Chaves em display_configs
+pub const KEY_FAILSAFE_ENABLED: &str = "failsafe_enabled";
+pub const KEY_FAILSAFE_TIMEOUT_S: &str = "failsafe_timeout_s";
+pub const KEY_FAILSAFE_MESSAGE: &str = "failsafe_message";
+pub const KEY_FAILSAFE_SEMAPHORE: &str = "failsafe_semaphore";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailsafeConfig {
+    pub enabled: bool,
+    pub timeout_s: u64,     // Segundos sem plc-data até entrar no estado seguro
+    pub message: String,
+    pub semaphore: String,  // "vermelho", "amarelo", "verde" ou "apagado"
+}
+
+impl FailsafeConfig {
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            enabled: db.get_display_config(KEY_FAILSAFE_ENABLED).await?.map(|v| v == "true").unwrap_or(true),
+            timeout_s: db.get_display_config(KEY_FAILSAFE_TIMEOUT_S).await?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+            message: db.get_display_config(KEY_FAILSAFE_MESSAGE).await?
+                .unwrap_or_else(|| "INFORMAÇÃO INDISPONÍVEL".to_string()),
+            semaphore: db.get_display_config(KEY_FAILSAFE_SEMAPHORE).await?
+                .unwrap_or_else(|| "vermelho".to_string()),
+        })
+    }
+
+    pub async fn save(&self, db: &Database) -> Result<(), sqlx::Error> {
+        db.set_display_config(KEY_FAILSAFE_ENABLED, if self.enabled { "true" } else { "false" }, "boolean").await?;
+        db.set_display_config(KEY_FAILSAFE_TIMEOUT_S, &self.timeout_s.to_string(), "number").await?;
+        db.set_display_config(KEY_FAILSAFE_MESSAGE, self.message.trim(), "text").await?;
+        db.set_display_config(KEY_FAILSAFE_SEMAPHORE, &self.semaphore, "text").await?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_TIMEOUT_S..=MAX_TIMEOUT_S).contains(&self.timeout_s) {
+            return Err(format!("Tempo sem dados inválido: {}s ({}-{})", self.timeout_s, MIN_TIMEOUT_S, MAX_TIMEOUT_S));
+        }
+        if self.message.trim().is_empty() {
+            return Err("Mensagem do estado seguro é obrigatória".to_string());
+        }
+        if !SEMAPHORES.contains(&self.semaphore.as_str()) {
+            return Err(format!("Semáforo inválido: {} (use {})", self.semaphore, SEMAPHORES.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+/// Payload do evento "panel-failsafe"
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FailsafeState {
+    pub active: bool,
+    pub since: Option<String>,         // Entrada no estado seguro (RFC 3339)
+    pub stale_seconds: Option<u64>,    // Idade dos dados do PLC ao entrar
+    pub message: String,
+    pub semaphore: String,
+}
+
+pub type FailsafeStateHandle = Arc<Mutex<FailsafeState>>;
+
+/// Compara a idade do último plc-data com a configuração e emite "panel-failsafe" na troca
+async fn evaluate(
+    app_handle: &AppHandle,
+    db: &Database,
+    config: &FailsafeConfig,
+    data_age: Duration,
+    state: &FailsafeStateHandle,
+) {
+    let stale = config.enabled && data_age.as_secs() >= config.timeout_s;
+    let mut current = state.lock().await;
+
+    let next = match (stale, current.active) {
+        (true, false) => {
+            let details = format!("{}s sem dados do PLC (limite {}s) - semáforo {}, \"{}\"",
+                data_age.as_secs(), config.timeout_s, config.semaphore, config.message);
+            println!("🚨 Painel em estado seguro: {}", details);
+            let _ = db.add_system_log("warning", "failsafe", "Painel em estado seguro", &details).await;
+            FailsafeState {
+                active: true,
+                since: Some(chrono::Local::now().to_rfc3339()),
+                stale_seconds: Some(data_age.as_secs()),
+                message: config.message.clone(),
+                semaphore: config.semaphore.clone(),
+            }
+        }
+        (false, true) => {
+            let details = match &current.since {
+                Some(since) => format!("Dados do PLC de volta (estado seguro desde {})", since),
+                None => "Dados do PLC de volta".to_string(),
+            };
+            println!("✅ Painel fora do estado seguro: {}", details);
+            let _ = db.add_system_log("info", "failsafe", "Painel fora do estado seguro", &details).await;
+            FailsafeState::default()
+        }
+        // Configuração alterada durante o estado seguro: painel passa a mostrar a nova
+        (true, true) => FailsafeState {
+            message: config.message.clone(),
+            semaphore: config.semaphore.clone(),
+            ..current.clone()
+        },
+        (false, false) => FailsafeState::default(),
+    };
+
+    if *current != next {
+        let _ = app_handle.emit("panel-failsafe", &next);
+        *current = next;
+    }
+}
+
+/// Confere a idade dos dados a cada segundo (entrada/saída sem ação do operador);
+/// configuração salva vale a partir da próxima conferência
+pub fn start_failsafe(
+    app_handle: AppHandle,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    event_metrics: Arc<PlcEventMetrics>,
+    state: FailsafeStateHandle,
+) {
+    let started = Instant::now();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(db) = database.lock().await.clone() else { continue };
+            match FailsafeConfig::load(&db).await {
+                Ok(config) => {
+                    // Sem nenhum plc-data ainda, conta desde o início do app
+                    let data_age = event_metrics.last_emit_age().unwrap_or_else(|| started.elapsed());
+                    evaluate(&app_handle, &db, &config, data_age, &state).await;
+                }
+                Err(e) => eprintln!("⚠️ Erro ao carregar estado seguro do painel: {:?}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,248 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use crate::database::Database;
+use crate::display_monitor::DisplayMonitorState;
+use crate::event_metrics::PlcEventMetrics;
+use crate::failsafe::FailsafeStateHandle;
+use crate::panel_status::PanelTrackerState;
+
+// Endpoint HTTP mínimo (sem framework) para o monitoramento da eclusa
+// (Zabbix/PRTG): GET/HEAD /healthz responde 200 quando o painel está aberto,
+// recebendo plc-data recente e com o banco respondendo; 503 caso contrário,
+// com os motivos em "failures". A configuração é relida a cada
+// CONFIG_INTERVAL: ligar/desligar ou trocar a porta não exige reiniciar o app.
+
+const CONFIG_INTERVAL: Duration = Duration::from_secs(5);
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Chaves em display_configs
+pub const KEY_HEALTH_ENABLED: &str = "health_endpoint_enabled";
+pub const KEY_HEALTH_HOST: &str = "health_endpoint_host";
+pub const KEY_HEALTH_PORT: &str = "health_endpoint_port";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthEndpointConfig {
+    pub enabled: bool,
+    pub bind_host: String,  // "0.0.0.0" para o servidor de monitoramento alcançar
+    pub port: u16,
+}
+
+impl HealthEndpointConfig {
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            enabled: db.get_display_config(KEY_HEALTH_ENABLED).await?.map(|v| v == "true").unwrap_or(false),
+            bind_host: db.get_display_config(KEY_HEALTH_HOST).await?
+                .unwrap_or_else(|| "0.0.0.0".to_string()),
+            port: db.get_display_config(KEY_HEALTH_PORT).await?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(8503),
+        })
+    }
+
+    pub async fn save(&self, db: &Database) -> Result<(), sqlx::Error> {
+        db.set_display_config(KEY_HEALTH_ENABLED, if self.enabled { "true" } else { "false" }, "boolean").await?;
+        db.set_display_config(KEY_HEALTH_HOST, self.bind_host.trim(), "text").await?;
+        db.set_display_config(KEY_HEALTH_PORT, &self.port.to_string(), "number").await?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bind_host.trim().parse::<IpAddr>().is_err() {
+            return Err(format!("Endereço inválido: {} (ex: 0.0.0.0 ou 127.0.0.1)", self.bind_host));
+        }
+        if self.port == 0 || self.port == 8502 {
+            return Err(format!("Porta inválida: {} (8502 é do servidor TCP)", self.port));
+        }
+        Ok(())
+    }
+
+    fn address(&self) -> String {
+        format!("{}:{}", self.bind_host.trim(), self.port)
+    }
+}
+
+/// Corpo da resposta de /healthz
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub status: String,                    // "ok" | "unhealthy"
+    pub timestamp: String,
+    pub panel_open: bool,
+    pub plc_data_age_ms: Option<u64>,      // None = nenhum plc-data emitido ainda
+    pub plc_data_fresh: bool,
+    pub database_ok: bool,
+    pub database_error: Option<String>,
+    pub failsafe_active: bool,
+    pub display_stalled: bool,
+    pub failures: Vec<String>,
+}
+
+/// Dependências da verificação (clonáveis para a task do servidor HTTP)
+#[derive(Clone)]
+pub struct HealthContext {
+    pub app_handle: AppHandle,
+    pub database: Arc<Mutex<Option<Arc<Database>>>>,
+    pub panel_tracker: PanelTrackerState,
+    pub display_health: DisplayMonitorState,
+    pub event_metrics: Arc<PlcEventMetrics>,
+    pub failsafe: FailsafeStateHandle,
+}
+
+pub async fn collect_health(context: &HealthContext) -> HealthReport {
+    let mut failures = Vec::new();
+    let panel = crate::panel_status::build_status(
+        &context.app_handle,
+        &context.panel_tracker,
+        &context.display_health,
+        &context.event_metrics,
+    ).await;
+
+    if !panel.panel_open {
+        failures.push("Painel público fechado".to_string());
+    }
+    if panel.plc_data_stale {
+        failures.push(match panel.last_plc_data_age_ms {
+            Some(age) => format!("Sem dados do PLC há {} ms", age),
+            None => "Nenhum dado do PLC recebido".to_string(),
+        });
+    }
+    if panel.panel_open && panel.stalled {
+        failures.push("Painel travado (sem relatório de métricas)".to_string());
+    }
+    let failsafe_active = context.failsafe.lock().await.active;
+    if failsafe_active {
+        failures.push("Painel em estado seguro".to_string());
+    }
+
+    let db = context.database.lock().await.clone();
+    let database_error = match db {
+        Some(db) => db.ping().await.err().map(|e| e.to_string()),
+        None => Some("Banco de dados não inicializado".to_string()),
+    };
+    if let Some(e) = &database_error {
+        failures.push(format!("Banco indisponível: {}", e));
+    }
+
+    let healthy = failures.is_empty();
+    HealthReport {
+        healthy,
+        status: if healthy { "ok" } else { "unhealthy" }.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        panel_open: panel.panel_open,
+        plc_data_age_ms: panel.last_plc_data_age_ms,
+        plc_data_fresh: !panel.plc_data_stale,
+        database_ok: database_error.is_none(),
+        database_error,
+        failsafe_active,
+        display_stalled: panel.stalled,
+        failures,
+    }
+}
+
+async fn handle_request(mut stream: TcpStream, context: &HealthContext) -> std::io::Result<()> {
+    // Só a linha de requisição importa: "GET /healthz HTTP/1.1"
+    let mut buffer = [0u8; 2048];
+    let n = match tokio::time::timeout(REQUEST_READ_TIMEOUT, stream.read(&mut buffer)).await {
+        Ok(result) => result?,
+        Err(_) => return Ok(()),
+    };
+    let request = String::from_utf8_lossy(&buffer[..n]);
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("");
+
+    let (status_line, body) = match (method, path) {
+        ("GET" | "HEAD", "/healthz") => {
+            let report = collect_health(context).await;
+            let status_line = if report.healthy { "200 OK" } else { "503 Service Unavailable" };
+            (status_line, serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string()))
+        }
+        ("GET" | "HEAD", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status_line,
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn serve(listener: tokio::net::TcpListener, context: HealthContext) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let context = context.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_request(stream, &context).await {
+                        eprintln!("⚠️ Health check: erro na requisição: {:?}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                eprintln!("❌ Health check: erro ao aceitar conexão: {:?}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+/// Mantém o listener de acordo com a configuração salva (liga, desliga, troca de porta)
+pub fn start_health_endpoint(context: HealthContext) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CONFIG_INTERVAL);
+        let mut running: Option<(HealthEndpointConfig, tokio::task::JoinHandle<()>)> = None;
+        let mut last_attempt: Option<HealthEndpointConfig> = None;
+        loop {
+            interval.tick().await;
+            let Some(db) = context.database.lock().await.clone() else { continue };
+            let config = match HealthEndpointConfig::load(&db).await {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("⚠️ Erro ao carregar configuração do health check: {:?}", e);
+                    continue;
+                }
+            };
+
+            if running.as_ref().is_some_and(|(current, _)| *current == config) {
+                continue;
+            }
+            if let Some((current, handle)) = running.take() {
+                handle.abort();
+                println!("🛑 Health check parado ({})", current.address());
+            }
+            if !config.enabled {
+                last_attempt = None;
+                continue;
+            }
+
+            // Porta ocupada: tenta de novo a cada conferência, mas só registra uma vez
+            let address = config.address();
+            match tokio::net::TcpListener::bind(&address).await {
+                Ok(listener) => {
+                    println!("🚀 Health check disponível em http://{}/healthz", address);
+                    let _ = db.add_system_log("info", "health", "Health check iniciado", &format!("http://{}/healthz", address)).await;
+                    let handle = tokio::spawn(serve(listener, context.clone()));
+                    running = Some((config.clone(), handle));
+                }
+                Err(e) if last_attempt.as_ref() != Some(&config) => {
+                    eprintln!("❌ Erro ao abrir porta do health check {}: {:?}", address, e);
+                    let _ = db.add_system_log("error", "health", "Erro ao iniciar health check", &format!("{}: {}", address, e)).await;
+                }
+                Err(_) => {}
+            }
+            last_attempt = Some(config);
+        }
+    });
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+use crate::database::Database;
+use crate::tcp_server::{PlcData, TcpServer};
+
+// Assina tags de um plc-hmi na mesma máquina pela API local do HMI (named pipe
+// no Windows, socket Unix nos demais - ver ipc_server.rs no plc-hmi), sem passar
+// por WebSocket/TCP. Os valores entram no mesmo canal dos pacotes do PLC, então
+// bits, displays analógicos, contadores e gravação funcionam igual.
+//
+// Cada mensagem publicada carrega TODOS os tags assinados (último valor de cada),
+// com o nome da assinatura: "nivel", "192.168.1.10:pressao" ou, para
+// "192.168.1.11:*", o nome do tag. BOOL vira 1/0; textos não numéricos são ignorados.
+//
+// Com token de API ativo no HMI, a 1ª mensagem é {"op":"auth","token":"wst_..."}
+// com o token configurado aqui (gerado no HMI em "Tokens de API").
+
+const DISABLED_POLL: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+const PONG_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[cfg(windows)]
+pub const DEFAULT_ENDPOINT: &str = r"\\.\pipe\plc-hmi-tags";
+
+/// Endpoint padrão do plc-hmi (mesmo cálculo de ipc_server.rs)
+#[cfg(windows)]
+pub fn default_endpoint() -> String {
+    DEFAULT_ENDPOINT.to_string()
+}
+
+/// Endpoint padrão do plc-hmi: socket na pasta do usuário ($XDG_RUNTIME_DIR ou
+/// <tmp>/plc-hmi-<uid>), mesmo cálculo de ipc_server.rs
+#[cfg(not(windows))]
+pub fn default_endpoint() -> String {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => std::path::PathBuf::from(dir),
+        // SAFETY: getuid não falha nem tem efeitos colaterais
+        None => std::env::temp_dir().join(format!("plc-hmi-{}", unsafe { libc::getuid() })),
+    };
+    dir.join("plc-hmi-tags.sock").to_string_lossy().to_string()
+}
+
+// Chaves em display_configs
+pub const KEY_ENABLED: &str = "hmi_ipc_enabled";
+pub const KEY_ENDPOINT: &str = "hmi_ipc_endpoint";
+pub const KEY_TAGS: &str = "hmi_ipc_tags";
+pub const KEY_TOKEN: &str = "hmi_ipc_token";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HmiIpcConfig {
+    pub enabled: bool,
+    pub endpoint: String,  // Vazio = endpoint padrão do plc-hmi
+    pub tags: Vec<String>, // Ex: ["nivel", "192.168.1.10:pressao", "192.168.1.11:*"]
+    #[serde(default)]
+    pub token: String,     // 🆕 Token de API do HMI (vazio = HMI sem autenticação)
+}
+
+impl HmiIpcConfig {
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            enabled: db.get_display_config(KEY_ENABLED).await?.map(|v| v == "true").unwrap_or(false),
+            endpoint: db.get_display_config(KEY_ENDPOINT).await?.unwrap_or_default(),
+            tags: db.get_display_config(KEY_TAGS).await?
+                .map(|v| v.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+                .unwrap_or_default(),
+            token: db.get_display_config(KEY_TOKEN).await?.unwrap_or_default(),
+        })
+    }
+
+    pub async fn save(&self, db: &Database) -> Result<(), sqlx::Error> {
+        db.set_display_config(KEY_ENABLED, if self.enabled { "true" } else { "false" }, "boolean").await?;
+        db.set_display_config(KEY_ENDPOINT, &self.endpoint, "string").await?;
+        db.set_display_config(KEY_TAGS, &self.tags.join(","), "string").await?;
+        db.set_display_config(KEY_TOKEN, &self.token, "string").await?;
+        Ok(())
+    }
+
+    fn endpoint(&self) -> String {
+        if self.endpoint.trim().is_empty() { default_endpoint() } else { self.endpoint.trim().to_string() }
+    }
+}
+
+/// Valor publicado pelo HMI (texto) → número usado pelos painéis
+fn to_number(value: &str) -> Option<f64> {
+    match value.trim() {
+        "true" | "TRUE" | "True" => Some(1.0),
+        "false" | "FALSE" | "False" => Some(0.0),
+        other => other.parse::<f64>().ok().filter(|v| v.is_finite()),
+    }
+}
+
+/// Inicia a assinatura em segundo plano (obedece a configuração a cada reconexão)
+pub fn start_hmi_ipc(
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    tcp_server: Arc<Mutex<Option<Arc<TcpServer>>>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut backoff = Duration::from_secs(1);
+
+        loop {
+            let Some(db) = database.lock().await.clone() else {
+                tokio::time::sleep(DISABLED_POLL).await;
+                continue;
+            };
+            let config = match HmiIpcConfig::load(&db).await {
+                Ok(config) if config.enabled && !config.tags.is_empty() => config,
+                Ok(_) => {
+                    tokio::time::sleep(DISABLED_POLL).await;
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("⚠️ IPC com o HMI: erro ao ler configuração: {:?}", e);
+                    tokio::time::sleep(DISABLED_POLL).await;
+                    continue;
+                }
+            };
+            let Some(server) = tcp_server.lock().await.clone() else {
+                tokio::time::sleep(DISABLED_POLL).await;
+                continue;
+            };
+
+            let result = match connect(&config.endpoint()).await {
+                Ok(stream) => {
+                    backoff = Duration::from_secs(1);
+                    println!("📡 Assinando {} tag(s) do HMI em {}", config.tags.len(), config.endpoint());
+                    run_session(stream, &db, &server, &config).await
+                }
+                Err(e) => Err(format!("erro na conexão: {}", e)),
+            };
+            match result {
+                Ok(()) => println!("🔄 IPC com o HMI: configuração alterada, reconectando"),
+                Err(e) => eprintln!("⚠️ IPC com o HMI em {}: {}", config.endpoint(), e),
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+#[cfg(windows)]
+async fn connect(endpoint: &str) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(endpoint)
+}
+
+#[cfg(not(windows))]
+async fn connect(endpoint: &str) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(endpoint).await
+}
+
+/// Uma conexão com o HMI. Ok(()) = configuração mudou; Err = falha de comunicação.
+async fn run_session<S>(stream: S, db: &Arc<Database>, server: &Arc<TcpServer>, config: &HmiIpcConfig) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    if !config.token.is_empty() {
+        let auth = serde_json::json!({"op": "auth", "token": config.token});
+        writer.write_all(format!("{}\n", auth).as_bytes()).await
+            .map_err(|e| format!("erro ao autenticar: {}", e))?;
+    }
+    let subscribe = serde_json::json!({"op": "sub", "tags": config.tags});
+    writer.write_all(format!("{}\n", subscribe).as_bytes()).await
+        .map_err(|e| format!("erro ao assinar: {}", e))?;
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    let mut last_message = tokio::time::Instant::now();
+    let mut values: HashMap<String, f64> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = line.map_err(|e| format!("erro ao receber: {}", e))?
+                    .ok_or_else(|| "conexão encerrada pelo HMI".to_string())?;
+                last_message = tokio::time::Instant::now();
+                let Ok(message) = serde_json::from_str::<serde_json::Value>(&line) else { continue };
+                match message.get("t").and_then(|t| t.as_str()).unwrap_or("") {
+                    "v" => {
+                        let Some(data) = message.get("d").and_then(|d| d.as_object()) else { continue };
+                        for (key, value) in data {
+                            if let Some(number) = value.as_str().and_then(to_number) {
+                                values.insert(key.clone(), number);
+                            }
+                        }
+                        server.publish(PlcData {
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                            variables: values.clone(),
+                        });
+                    }
+                    "q" => {
+                        // HMI sem dados ao vivo: não republicar valores velhos
+                        println!("⚠️ HMI sem dados ao vivo (qualidade {})", message.get("q").and_then(|q| q.as_str()).unwrap_or("?"));
+                        values.clear();
+                    }
+                    "hello" if config.token.is_empty() && message.get("auth").and_then(|a| a.as_bool()).unwrap_or(false) => {
+                        return Err("HMI exige token de API: configure o token da assinatura IPC".to_string());
+                    }
+                    "err" => eprintln!("⚠️ HMI recusou a mensagem: {}", message.get("m").and_then(|m| m.as_str()).unwrap_or("?")),
+                    _ => {}
+                }
+            }
+            _ = ping.tick() => {
+                if last_message.elapsed() > PONG_TIMEOUT {
+                    return Err("HMI não responde".to_string());
+                }
+                match HmiIpcConfig::load(db).await {
+                    Ok(current) if &current != config => return Ok(()),
+                    _ => {}
+                }
+                writer.write_all(b"{\"op\":\"ping\"}\n").await
+                    .map_err(|e| format!("erro ao enviar ping: {}", e))?;
+            }
+        }
+    }
+}
@@ -1,21 +1,84 @@
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State, Manager, WebviewWindowBuilder, WebviewUrl};
+use tauri::{AppHandle, Emitter, State, Manager};
 use tokio::sync::Mutex;
 
 mod tcp_server;
 mod database;
-use tcp_server::{TcpServer, PlcData};
-use database::{Database, BitConfig, VideoConfig, SystemLog};
+mod log_forwarder;
+mod hmi_ipc;
+mod content_sync;
+mod display_monitor;
+mod data_recorder;
+mod event_metrics;
+mod content_approval;
+mod bit_condition;
+mod analog_display;
+mod countdown;
+mod audio_policy;
+mod transitions;
+mod panel_status;
+mod failsafe;
+mod panel_window;
+mod health_endpoint;
+mod word_history;
+mod template_values;
+mod panel_events;
+mod error;
+use tcp_server::{TcpServer, PlcData, PlcProtocol, PlcWriteResult};
+use content_approval::ContentChange;
+use error::AppError;
+use database::{Database, BitConfig, VideoConfig, SystemLog, DataMapping, ProtocolConfig, PanelTheme, AnalogDisplay, CountdownTimer, TransitionConfig, PanelEvent};
 
 #[derive(Clone, serde::Serialize)]
 struct PlcDataPayload {
+    seq: u64, // Sequência do evento; a janela devolve o último processado em "plc-data-consumed"
     message: PlcData,
+    active_bits: Vec<i64>, // ids dos BitConfigs cuja condição está verdadeira (avaliada no backend)
+    analog_values: Vec<analog_display::AnalogReading>, // Mostradores analógicos já formatados
+    transitions: transitions::PanelTransitions, // Efeitos de troca (playlist e classe da mensagem mais prioritária)
+    template_values: std::collections::HashMap<String, String>, // Placeholders calculados ({time}, {phase_title}, {speed_kmh}...)
+}
+
+/// Monta o payload do evento plc-data, avaliando as condições das mensagens e os mostradores analógicos
+async fn build_plc_payload(
+    data: PlcData,
+    seq: u64,
+    database: &Arc<Mutex<Option<Arc<Database>>>>,
+) -> PlcDataPayload {
+    let db = database.lock().await.clone();
+    let (active_bits, analog_values, transitions, template_values) = match db {
+        Some(db) => {
+            let active: Vec<BitConfig> = db.process_plc_bits(&data.variables).await
+                .map(|bits| bits.into_iter().filter(|(_, active)| *active).map(|(config, _)| config).collect())
+                .unwrap_or_default();
+            let analog_values = db.get_all_analog_displays().await
+                .map(|displays| analog_display::evaluate_all(&displays, &data.variables))
+                .unwrap_or_default();
+            let top_priority = active.iter().map(|config| config.priority).max();
+            let transitions = db.get_all_transitions().await
+                .map(|configs| transitions::resolve(&configs, top_priority))
+                .unwrap_or_default();
+            let template_values = template_values::compute(&db, &data.variables).await;
+            (active.into_iter().map(|config| config.id).collect(), analog_values, transitions, template_values)
+        }
+        None => (Vec::new(), Vec::new(), Default::default(), Default::default()),
+    };
+    PlcDataPayload { seq, message: data, active_bits, analog_values, transitions, template_values }
 }
 
 #[derive(Clone)]
 struct AppState {
     tcp_server: Arc<Mutex<Option<Arc<TcpServer>>>>,
     database: Arc<Mutex<Option<Arc<Database>>>>,
+    display_health: display_monitor::DisplayMonitorState,
+    event_metrics: Arc<event_metrics::PlcEventMetrics>,
+    countdowns: countdown::CountdownState,
+    audio_policy: audio_policy::AudioPolicyState,
+    panel_tracker: panel_status::PanelTrackerState,
+    failsafe: failsafe::FailsafeStateHandle,
+    panel_window: Arc<panel_window::PanelWindowManager>,
+    word_history: word_history::WordHistoryState,
+    panel_event: panel_events::ActivePanelEventState,
 }
 
 #[tauri::command]
@@ -28,7 +91,7 @@ async fn start_tcp_server(
     port: u16, 
     app_handle: AppHandle,
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut server_guard = state.tcp_server.lock().await;
     
     if server_guard.is_some() {
@@ -40,6 +103,12 @@ async fn start_tcp_server(
     // Configurar database se disponível
     if let Some(db) = state.database.lock().await.as_ref() {
         server.set_database(Arc::downgrade(db));
+        if let Ok(mappings) = db.get_all_data_mappings().await {
+            server.set_data_mappings(mappings);
+        }
+        if let Ok(configs) = db.get_all_protocol_configs().await {
+            server.set_protocol_configs(configs);
+        }
     }
     
     let server = Arc::new(server);
@@ -51,10 +120,15 @@ async fn start_tcp_server(
         }
     });
     
+    word_history::start_word_history(state.word_history.clone(), state.database.clone(), server.subscribe());
+    
     let mut rx = server.subscribe();
+    let event_metrics = state.event_metrics.clone();
+    let database = state.database.clone();
     tokio::spawn(async move {
         while let Ok(data) = rx.recv().await {
-            let _ = app_handle.emit("plc-data", PlcDataPayload { message: data });
+            let payload = build_plc_payload(data, event_metrics.next_seq(), &database).await;
+            let _ = app_handle.emit("plc-data", payload);
         }
     });
     
@@ -78,7 +152,7 @@ async fn connect_to_plc(
     plc_ip: String, 
     plc_port: u16,
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let server_guard = state.tcp_server.lock().await;
     
     if let Some(server) = server_guard.as_ref() {
@@ -123,25 +197,96 @@ async fn connect_to_plc(
                 &format!("PLC: {}:{}", plc_ip, plc_port)
             ).await;
         }
-        Err("Servidor TCP não está rodando. Inicie o servidor primeiro.".to_string())
+        Err(AppError::NotRunning("Servidor TCP não está rodando. Inicie o servidor primeiro.".to_string()))
+    }
+}
+
+/// Envia a escrita pela conexão do PLC, aguarda a confirmação e emite
+/// "plc-write-confirmed" ou "plc-write-failed"
+async fn write_to_plc(
+    plc_ip: Option<String>,
+    variable_path: &str,
+    value: &str,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<PlcWriteResult, String> {
+    // Clonar o servidor para não segurar o lock enquanto aguarda o PLC
+    let server = state.tcp_server.lock().await.clone()
+        .ok_or("Servidor TCP não está rodando. Inicie o servidor primeiro.")?;
+    let plc_ip = match plc_ip {
+        Some(ip) => ip,
+        None => {
+            // Sem IP: só é possível escolher se houver exatamente um PLC conectado
+            let connected = server.writable_plcs();
+            match connected.as_slice() {
+                [ip] => ip.clone(),
+                [] => return Err("Nenhum PLC conectado".to_string()),
+                _ => return Err(format!("Vários PLCs conectados ({}) - informe o IP", connected.join(", "))),
+            }
+        }
+    };
+
+    let result = server.write_variable(&plc_ip, variable_path, value).await?;
+    let database = state.database.lock().await.clone();
+    if result.status == "confirmed" {
+        let _ = app_handle.emit("plc-write-confirmed", &result);
+        if let Some(db) = database {
+            let _ = db.add_system_log(
+                "info",
+                "plc",
+                "Escrita confirmada pelo PLC",
+                &format!("PLC: {} - {} = {} ({}ms)", result.plc_ip, result.variable_path, result.value, result.elapsed_ms)
+            ).await;
+        }
+        return Ok(result);
     }
+    let _ = app_handle.emit("plc-write-failed", &result);
+    Err(match result.error_code {
+        Some(code) => format!("PLC {} rejeitou a escrita de {} (código {})", result.plc_ip, result.variable_path, code),
+        None => format!("PLC {} não confirmou a escrita de {}", result.plc_ip, result.variable_path),
+    })
+}
+
+#[tauri::command]
+async fn write_plc_variable(
+    plc_ip: String,
+    variable_path: String,
+    value: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<PlcWriteResult, AppError> {
+    write_to_plc(Some(plc_ip), &variable_path, &value, &app_handle, &state).await.map_err(AppError::from)
 }
 
+/// Comando de texto: "Word[5]=100", "Word[5].3=1", "NomeDoMapeamento=12.5"
+/// ou com IP: "192.168.1.33 Word[5]=100" (sem IP, usa o único PLC conectado)
 #[tauri::command]
-async fn send_plc_command(_command: String) -> Result<String, String> {
-    Ok("Comando enviado com sucesso".to_string())
+async fn send_plc_command(
+    command: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let (target, value) = command.split_once('=')
+        .ok_or_else(|| AppError::ConfigInvalid(format!("Comando inválido: '{}' (use variável=valor)", command)))?;
+    let target = target.trim();
+    let (plc_ip, variable_path) = match target.split_once(char::is_whitespace) {
+        Some((ip, path)) => (Some(ip.to_string()), path.trim()),
+        None => (None, target),
+    };
+    let result = write_to_plc(plc_ip, variable_path, value.trim(), &app_handle, &state).await?;
+    Ok(format!("Escrita confirmada: {} = {} (PLC {})", result.variable_path, result.value, result.plc_ip))
 }
 
 #[tauri::command]
-async fn init_database(app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+async fn init_database(app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
     // Obter o diretório de dados do app
     let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Falha ao obter diretório de dados: {:?}", e))?;
+        .map_err(|e| AppError::Io(format!("Falha ao obter diretório de dados: {:?}", e)))?;
     
     // Criar diretório se não existir
     if !app_data_dir.exists() {
         std::fs::create_dir_all(&app_data_dir)
-            .map_err(|e| format!("Falha ao criar diretório: {:?}", e))?;
+            .map_err(|e| AppError::Io(format!("Falha ao criar diretório: {:?}", e)))?;
     }
     
     // Caminho completo do banco
@@ -150,7 +295,7 @@ async fn init_database(app_handle: AppHandle, state: State<'_, AppState>) -> Res
     // Criar arquivo vazio se não existir
     if !db_path.exists() {
         std::fs::File::create(&db_path)
-            .map_err(|e| format!("Falha ao criar arquivo: {:?}", e))?;
+            .map_err(|e| AppError::Io(format!("Falha ao criar arquivo: {:?}", e)))?;
     }
     
     let database_url = format!("sqlite://{}?mode=rwc", db_path.to_string_lossy().replace('\\', "/"));
@@ -160,56 +305,57 @@ async fn init_database(app_handle: AppHandle, state: State<'_, AppState>) -> Res
             *state.database.lock().await = Some(Arc::new(db));
             Ok(format!("Banco de dados inicializado: {}", db_path.display()))
         }
-        Err(e) => Err(format!("Erro ao inicializar banco: {:?}", e))
+        Err(e) => Err(AppError::DbNotInitialized(format!("Erro ao inicializar banco: {:?}", e)))
     }
 }
 
 #[tauri::command]
-async fn get_all_texts(state: State<'_, AppState>) -> Result<Vec<database::TextConfig>, String> {
+async fn get_all_texts(state: State<'_, AppState>) -> Result<Vec<database::TextConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_all_texts().await
-            .map_err(|e| format!("Erro ao buscar textos: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar textos: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn update_text(key: String, text: String, state: State<'_, AppState>) -> Result<String, String> {
+async fn update_text(key: String, text: String, author: Option<String>, state: State<'_, AppState>) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.update_text(&key, &text).await
-            .map_err(|e| format!("Erro ao atualizar texto: {:?}", e))?;
-        Ok("Texto atualizado com sucesso".to_string())
+        match content_approval::submit_change(db, ContentChange::Text { key, text }, author).await? {
+            Some(draft_id) => Ok(format!("Texto enviado para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Texto atualizado com sucesso".to_string()),
+        }
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_all_phases(state: State<'_, AppState>) -> Result<Vec<database::PhaseConfig>, String> {
+async fn get_all_phases(state: State<'_, AppState>) -> Result<Vec<database::PhaseConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_all_phases().await
-            .map_err(|e| format!("Erro ao buscar fases: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar fases: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_phase(phase_number: i32, state: State<'_, AppState>) -> Result<Option<database::PhaseConfig>, String> {
+async fn get_phase(phase_number: i32, state: State<'_, AppState>) -> Result<Option<database::PhaseConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_phase(phase_number).await
-            .map_err(|e| format!("Erro ao buscar fase: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar fase: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -219,63 +365,90 @@ async fn update_phase(
     title: String, 
     description: String, 
     color: String,
+    author: Option<String>,
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.update_phase(phase_number, &title, &description, &color).await
-            .map_err(|e| format!("Erro ao atualizar fase: {:?}", e))?;
-        Ok("Fase atualizada com sucesso".to_string())
+        let change = ContentChange::Phase { phase_number, title, description, color };
+        match content_approval::submit_change(db, change, author).await? {
+            Some(draft_id) => Ok(format!("Fase enviada para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Fase atualizada com sucesso".to_string()),
+        }
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
+/// WORD com o número da fase atual ({phase_number}/{phase_title} nos templates); None desliga
 #[tauri::command]
-async fn open_panel_window(app_handle: AppHandle) -> Result<String, String> {
-    let _panel_window = WebviewWindowBuilder::new(&app_handle, "panel", WebviewUrl::App("src/panel.html".into()))
-        .title("Painel da Eclusa")
-        .inner_size(1920.0, 1080.0)
-        .resizable(true)
-        .decorations(true)
-        .build()
-        .map_err(|e| format!("Erro ao criar janela do painel: {}", e))?;
-
-    Ok("Painel aberto".to_string())
+async fn get_phase_word_index(state: State<'_, AppState>) -> Result<Option<usize>, AppError> {
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    template_values::load_phase_word_index(&db).await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar WORD da fase: {:?}", e)))
 }
 
 #[tauri::command]
-async fn close_panel_window(app_handle: AppHandle) -> Result<String, String> {
-    if let Some(panel_window) = app_handle.get_webview_window("panel") {
-        panel_window.close().map_err(|e| format!("Erro ao fechar painel: {}", e))?;
-        Ok("Painel fechado".to_string())
-    } else {
-        Err("Painel não está aberto".to_string())
+async fn set_phase_word_index(word_index: Option<usize>, state: State<'_, AppState>) -> Result<String, AppError> {
+    if word_index.is_some_and(|index| index > 63) {
+        return Err(AppError::ConfigInvalid("WORD da fase inválida: use 0-63".to_string()));
     }
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    let value = word_index.map(|index| index.to_string()).unwrap_or_default();
+    db.set_display_config(template_values::KEY_PHASE_WORD_INDEX, &value, "number").await
+        .map_err(|e| AppError::Database(format!("Erro ao salvar WORD da fase: {:?}", e)))?;
+    Ok(match word_index {
+        Some(index) => format!("Fase atual lida de Word[{}]", index),
+        None => "Fase atual desativada nos templates".to_string(),
+    })
+}
+
+/// Abre o painel; se já estiver aberto, só traz para frente
+#[tauri::command]
+async fn open_panel_window(app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    state.panel_window.open(&app_handle).await.map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn close_panel_window(app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    state.panel_window.close(&app_handle).await.map_err(AppError::from)
 }
 
 #[tauri::command]
-async fn get_all_bit_configs(state: State<'_, AppState>) -> Result<Vec<BitConfig>, String> {
+async fn toggle_panel_window(app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    state.panel_window.toggle(&app_handle).await.map_err(AppError::from)
+}
+
+/// Estado da janela do painel (o mesmo enviado no evento "panel-window")
+#[tauri::command]
+async fn get_panel_window_state(state: State<'_, AppState>) -> Result<panel_window::PanelWindowInfo, AppError> {
+    Ok(state.panel_window.info())
+}
+
+#[tauri::command]
+async fn get_all_bit_configs(state: State<'_, AppState>) -> Result<Vec<BitConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_all_bit_configs().await
-            .map_err(|e| format!("Erro ao buscar configurações de bits: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar configurações de bits: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_bit_config(word_index: i32, bit_index: i32, state: State<'_, AppState>) -> Result<Option<BitConfig>, String> {
+async fn get_bit_config(word_index: i32, bit_index: i32, state: State<'_, AppState>) -> Result<Option<BitConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_bit_config(word_index, bit_index).await
-            .map_err(|e| format!("Erro ao buscar configuração de bit: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar configuração de bit: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -297,15 +470,25 @@ async fn add_bit_config(
     letter_spacing: i32,
     use_template: bool,
     message_template: String,
+    condition: Option<String>,
+    author: Option<String>,
     state: State<'_, AppState>
-) -> Result<i64, String> {
+) -> Result<i64, AppError> {
+    let condition = condition.unwrap_or_default().trim().to_string();
+    bit_condition::validate(&condition).map_err(AppError::ConfigInvalid)?;
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.add_bit_config(word_index, bit_index, &name, &message, &message_off, enabled, priority, &color, font_size, &position, &font_family, &font_weight, text_shadow, letter_spacing, use_template, &message_template).await
-            .map_err(|e| format!("Erro ao adicionar configuração de bit: {:?}", e))
+        // Com aprovação obrigatória, retorna o id do rascunho
+        if content_approval::approval_required(db).await {
+            let config = BitConfig { id: 0, word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, font_family, font_weight, text_shadow, letter_spacing, use_template, message_template, condition };
+            return content_approval::submit_change(db, ContentChange::BitConfig { config }, author).await
+                .map(|draft_id| draft_id.unwrap_or(0)).map_err(AppError::from);
+        }
+        db.add_bit_config(word_index, bit_index, &name, &message, &message_off, enabled, priority, &color, font_size, &position, &font_family, &font_weight, text_shadow, letter_spacing, use_template, &message_template, &condition).await
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar configuração de bit: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -327,53 +510,70 @@ async fn update_bit_config(
     letter_spacing: i32,
     use_template: bool,
     message_template: String,
+    condition: Option<String>,
+    author: Option<String>,
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.update_bit_config(word_index, bit_index, &name, &message, &message_off, enabled, priority, &color, font_size, &position, &font_family, &font_weight, text_shadow, letter_spacing, use_template, &message_template).await
-            .map_err(|e| format!("Erro ao atualizar configuração de bit: {:?}", e))?;
+        // Sem `condition` no invoke, mantém a condição já gravada
+        let condition = match condition {
+            Some(condition) => condition.trim().to_string(),
+            None => db.get_bit_config(word_index, bit_index).await
+                .map_err(|e| AppError::Database(format!("Erro ao buscar configuração de bit: {:?}", e)))?
+                .map(|b| b.condition)
+                .unwrap_or_default(),
+        };
+        bit_condition::validate(&condition).map_err(AppError::ConfigInvalid)?;
+        if content_approval::approval_required(db).await {
+            let config = BitConfig { id: 0, word_index, bit_index, name, message, message_off, enabled, priority, color, font_size, position, font_family, font_weight, text_shadow, letter_spacing, use_template, message_template, condition };
+            let draft_id = content_approval::submit_change(db, ContentChange::BitConfig { config }, author).await?;
+            return Ok(format!("Configuração de bit enviada para aprovação (rascunho #{})", draft_id.unwrap_or(0)));
+        }
+        db.update_bit_config(word_index, bit_index, &name, &message, &message_off, enabled, priority, &color, font_size, &position, &font_family, &font_weight, text_shadow, letter_spacing, use_template, &message_template, &condition).await
+            .map_err(|e| AppError::Database(format!("Erro ao atualizar configuração de bit: {:?}", e)))?;
         Ok("Configuração de bit atualizada com sucesso".to_string())
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn delete_bit_config(word_index: i32, bit_index: i32, state: State<'_, AppState>) -> Result<String, String> {
+async fn delete_bit_config(word_index: i32, bit_index: i32, author: Option<String>, state: State<'_, AppState>) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.delete_bit_config(word_index, bit_index).await
-            .map_err(|e| format!("Erro ao deletar configuração de bit: {:?}", e))?;
-        Ok("Configuração de bit deletada com sucesso".to_string())
+        match content_approval::submit_change(db, ContentChange::DeleteBitConfig { word_index, bit_index }, author).await? {
+            Some(draft_id) => Ok(format!("Exclusão enviada para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Configuração de bit deletada com sucesso".to_string()),
+        }
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_all_videos(state: State<'_, AppState>) -> Result<Vec<VideoConfig>, String> {
+async fn get_all_videos(state: State<'_, AppState>) -> Result<Vec<VideoConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_all_videos().await
-            .map_err(|e| format!("Erro ao buscar vídeos: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar vídeos: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_video(id: i64, state: State<'_, AppState>) -> Result<Option<VideoConfig>, String> {
+async fn get_video(id: i64, state: State<'_, AppState>) -> Result<Option<VideoConfig>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_video(id).await
-            .map_err(|e| format!("Erro ao buscar vídeo: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar vídeo: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -386,25 +586,21 @@ async fn add_video(
     enabled: bool,
     priority: i32,
     description: String,
+    author: Option<String>,
     state: State<'_, AppState>
-) -> Result<i64, String> {
+) -> Result<String, AppError> {
     println!("📹 add_video chamado: name={}, path={}, duration={}", name, filePath, duration);
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        match db.add_video(&name, &filePath, duration, enabled, priority, &description).await {
-            Ok(id) => {
-                println!("✅ Vídeo adicionado com ID: {}", id);
-                Ok(id)
-            }
-            Err(e) => {
-                eprintln!("❌ Erro ao adicionar vídeo: {:?}", e);
-                Err(format!("Erro ao adicionar vídeo: {:?}", e))
-            }
+        let change = ContentChange::AddVideo { name, file_path: filePath, duration, enabled, priority, description };
+        match content_approval::submit_change(db, change, author).await? {
+            Some(draft_id) => Ok(format!("Vídeo enviado para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Vídeo adicionado com sucesso".to_string()),
         }
     } else {
         eprintln!("❌ Banco de dados não inicializado!");
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -420,40 +616,44 @@ async fn update_video(
     description: String,
     #[allow(non_snake_case)]
     displayOrder: i32,
+    author: Option<String>,
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.update_video(id, &name, &filePath, duration, enabled, priority, &description, displayOrder).await
-            .map_err(|e| format!("Erro ao atualizar vídeo: {:?}", e))?;
-        Ok("Vídeo atualizado com sucesso".to_string())
+        let change = ContentChange::UpdateVideo { id, name, file_path: filePath, duration, enabled, priority, description, display_order: displayOrder };
+        match content_approval::submit_change(db, change, author).await? {
+            Some(draft_id) => Ok(format!("Vídeo enviado para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Vídeo atualizado com sucesso".to_string()),
+        }
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn delete_video(id: i64, state: State<'_, AppState>) -> Result<String, String> {
+async fn delete_video(id: i64, author: Option<String>, state: State<'_, AppState>) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.delete_video(id).await
-            .map_err(|e| format!("Erro ao deletar vídeo: {:?}", e))?;
-        Ok("Vídeo deletado com sucesso".to_string())
+        match content_approval::submit_change(db, ContentChange::DeleteVideo { id }, author).await? {
+            Some(draft_id) => Ok(format!("Remoção do vídeo enviada para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Vídeo deletado com sucesso".to_string()),
+        }
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_enabled_videos(state: State<'_, AppState>) -> Result<Vec<VideoConfig>, String> {
+async fn get_enabled_videos(state: State<'_, AppState>) -> Result<Vec<VideoConfig>, AppError> {
     println!("🎬 [COMMAND] get_enabled_videos chamado pelo frontend");
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         let result = db.get_enabled_videos().await
-            .map_err(|e| format!("Erro ao buscar vídeos ativos: {:?}", e));
+            .map_err(|e| AppError::Database(format!("Erro ao buscar vídeos ativos: {:?}", e)));
         
         match &result {
             Ok(videos) => println!("✅ [COMMAND] Retornando {} vídeos para o frontend", videos.len()),
@@ -463,7 +663,7 @@ async fn get_enabled_videos(state: State<'_, AppState>) -> Result<Vec<VideoConfi
         result
     } else {
         println!("❌ [COMMAND] Banco de dados não inicializado!");
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -472,59 +672,199 @@ async fn reorder_video(
     id: i64,
     #[allow(non_snake_case)]
     newOrder: i32,
+    author: Option<String>,
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.reorder_video(id, newOrder).await
-            .map_err(|e| format!("Erro ao reordenar vídeo: {:?}", e))?;
-        Ok("Vídeo reordenado com sucesso".to_string())
+        match content_approval::submit_change(db, ContentChange::ReorderVideo { id, display_order: newOrder }, author).await? {
+            Some(draft_id) => Ok(format!("Nova ordem enviada para aprovação (rascunho #{})", draft_id)),
+            None => Ok("Vídeo reordenado com sucesso".to_string()),
+        }
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn clear_all_videos(state: State<'_, AppState>) -> Result<String, String> {
+async fn clear_all_videos(author: Option<String>, state: State<'_, AppState>) -> Result<String, AppError> {
     println!("🗑️ Limpando todos os vídeos do banco...");
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
-        db.clear_all_videos().await
-            .map_err(|e| format!("Erro ao limpar vídeos: {:?}", e))?;
-        println!("✅ Todos os vídeos foram removidos");
-        Ok("Todos os vídeos foram removidos com sucesso".to_string())
+        match content_approval::submit_change(db, ContentChange::ClearAllVideos, author).await? {
+            Some(draft_id) => Ok(format!("Remoção de todos os vídeos enviada para aprovação (rascunho #{})", draft_id)),
+            None => {
+                println!("✅ Todos os vídeos foram removidos");
+                Ok("Todos os vídeos foram removidos com sucesso".to_string())
+            }
+        }
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Volume (0-100) e mudo de um vídeo; o painel recebe o áudio efetivo em "audio-policy"
+#[tauri::command]
+async fn set_video_audio(
+    id: i64,
+    volume: i32,
+    muted: bool,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    audio_policy::validate_volume(volume).map_err(AppError::ConfigInvalid)?;
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    db.set_video_audio(id, volume, muted).await
+        .map_err(|e| AppError::Database(format!("Erro ao salvar áudio do vídeo: {:?}", e)))?;
+    audio_policy::refresh_policy(&app_handle, &db, &state.audio_policy).await
+        .map_err(|e| AppError::Database(format!("Erro ao calcular política de áudio: {:?}", e)))?;
+    Ok("Áudio do vídeo atualizado".to_string())
+}
+
+#[tauri::command]
+async fn get_quiet_hours_config(state: State<'_, AppState>) -> Result<audio_policy::QuietHoursConfig, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        audio_policy::QuietHoursConfig::load(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar horário de silêncio: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn set_quiet_hours_config(
+    config: audio_policy::QuietHoursConfig,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    config.validate().map_err(AppError::ConfigInvalid)?;
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    config.save(&db).await
+        .map_err(|e| AppError::Database(format!("Erro ao salvar horário de silêncio: {:?}", e)))?;
+    audio_policy::refresh_policy(&app_handle, &db, &state.audio_policy).await
+        .map_err(|e| AppError::Database(format!("Erro ao calcular política de áudio: {:?}", e)))?;
+    let _ = db.add_system_log("info", "ui", "Horário de silêncio alterado",
+        &format!("ativo={} {}-{} volume máx={}", config.enabled, config.start, config.end, config.max_volume)).await;
+    Ok("Horário de silêncio salvo".to_string())
+}
+
+/// Áudio efetivo atual (o mesmo enviado no evento "audio-policy")
+#[tauri::command]
+async fn get_audio_policy(app_handle: AppHandle, state: State<'_, AppState>) -> Result<audio_policy::AudioPolicy, AppError> {
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    audio_policy::refresh_policy(&app_handle, &db, &state.audio_policy).await
+        .map_err(|e| AppError::Database(format!("Erro ao calcular política de áudio: {:?}", e)))
+}
+
+// ============================================================================
+// ESTADO SEGURO DO PAINEL (DADOS DO PLC VELHOS)
+// ============================================================================
+
+#[tauri::command]
+async fn get_failsafe_config(state: State<'_, AppState>) -> Result<failsafe::FailsafeConfig, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        failsafe::FailsafeConfig::load(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar estado seguro: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Vale a partir da próxima conferência do backend (1s)
+#[tauri::command]
+async fn set_failsafe_config(config: failsafe::FailsafeConfig, state: State<'_, AppState>) -> Result<String, AppError> {
+    config.validate().map_err(AppError::ConfigInvalid)?;
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    config.save(&db).await
+        .map_err(|e| AppError::Database(format!("Erro ao salvar estado seguro: {:?}", e)))?;
+    let _ = db.add_system_log("info", "ui", "Estado seguro do painel alterado",
+        &format!("ativo={} limite={}s semáforo={} \"{}\"", config.enabled, config.timeout_s, config.semaphore, config.message)).await;
+    Ok("Estado seguro salvo".to_string())
+}
+
+/// Estado atual (o mesmo enviado no evento "panel-failsafe")
+#[tauri::command]
+async fn get_failsafe_state(state: State<'_, AppState>) -> Result<failsafe::FailsafeState, AppError> {
+    Ok(state.failsafe.lock().await.clone())
+}
+
+// ============================================================================
+// HEALTH CHECK HTTP PARA O MONITORAMENTO (ZABBIX/PRTG)
+// ============================================================================
+
+fn health_context(app_handle: AppHandle, state: &AppState) -> health_endpoint::HealthContext {
+    health_endpoint::HealthContext {
+        app_handle,
+        database: state.database.clone(),
+        panel_tracker: state.panel_tracker.clone(),
+        display_health: state.display_health.clone(),
+        event_metrics: state.event_metrics.clone(),
+        failsafe: state.failsafe.clone(),
     }
 }
 
 #[tauri::command]
-fn get_file_path(file_name: String) -> Result<String, String> {
+async fn get_health_endpoint_config(state: State<'_, AppState>) -> Result<health_endpoint::HealthEndpointConfig, AppError> {
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    health_endpoint::HealthEndpointConfig::load(&db).await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar health check: {:?}", e)))
+}
+
+/// O listener acompanha a configuração em até 5s (sem reiniciar o app)
+#[tauri::command]
+async fn set_health_endpoint_config(config: health_endpoint::HealthEndpointConfig, state: State<'_, AppState>) -> Result<String, AppError> {
+    config.validate().map_err(AppError::ConfigInvalid)?;
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    config.save(&db).await
+        .map_err(|e| AppError::Database(format!("Erro ao salvar health check: {:?}", e)))?;
+    let _ = db.add_system_log("info", "ui", "Health check alterado",
+        &format!("ativo={} {}:{}", config.enabled, config.bind_host, config.port)).await;
+    Ok("Health check salvo".to_string())
+}
+
+/// O mesmo relatório servido em /healthz
+#[tauri::command]
+async fn get_health_report(app_handle: AppHandle, state: State<'_, AppState>) -> Result<health_endpoint::HealthReport, AppError> {
+    Ok(health_endpoint::collect_health(&health_context(app_handle, &state)).await)
+}
+
+#[tauri::command]
+fn get_file_path(file_name: String) -> Result<String, AppError> {
     // Este comando seria usado com drag & drop, mas no Tauri web o file.path não está disponível
     // Por enquanto, retorna o nome do arquivo como fallback
     Ok(file_name)
 }
 
 #[tauri::command]
-async fn get_video_control_config(state: State<'_, AppState>) -> Result<(i32, i32), String> {
+async fn get_video_control_config(state: State<'_, AppState>) -> Result<(i32, i32), AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         let word_index = db.get_display_config("video_control_word_index").await
-            .map_err(|e| format!("Erro ao buscar word_index: {:?}", e))?
+            .map_err(|e| AppError::Database(format!("Erro ao buscar word_index: {:?}", e)))?
             .and_then(|v| v.parse::<i32>().ok())
             .unwrap_or(3);
             
         let bit_index = db.get_display_config("video_control_bit_index").await
-            .map_err(|e| format!("Erro ao buscar bit_index: {:?}", e))?
+            .map_err(|e| AppError::Database(format!("Erro ao buscar bit_index: {:?}", e)))?
             .and_then(|v| v.parse::<i32>().ok())
             .unwrap_or(3);
             
         Ok((word_index, bit_index))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -533,31 +873,31 @@ async fn set_video_control_config(
     word_index: i32, 
     bit_index: i32, 
     state: State<'_, AppState>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.set_display_config("video_control_word_index", &word_index.to_string(), "number").await
-            .map_err(|e| format!("Erro ao definir word_index: {:?}", e))?;
+            .map_err(|e| AppError::Database(format!("Erro ao definir word_index: {:?}", e)))?;
             
         db.set_display_config("video_control_bit_index", &bit_index.to_string(), "number").await
-            .map_err(|e| format!("Erro ao definir bit_index: {:?}", e))?;
+            .map_err(|e| AppError::Database(format!("Erro ao definir bit_index: {:?}", e)))?;
             
         Ok("Configuração do bit de controle de vídeos atualizada com sucesso".to_string())
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn get_recent_logs(limit: i32, state: State<'_, AppState>) -> Result<Vec<SystemLog>, String> {
+async fn get_recent_logs(limit: i32, state: State<'_, AppState>) -> Result<Vec<SystemLog>, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.get_recent_logs(limit).await
-            .map_err(|e| format!("Erro ao buscar logs: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar logs: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
@@ -568,167 +908,1326 @@ async fn add_system_log(
     message: String, 
     details: String,
     state: State<'_, AppState>
-) -> Result<i64, String> {
+) -> Result<i64, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.add_system_log(&level, &category, &message, &details).await
-            .map_err(|e| format!("Erro ao adicionar log: {:?}", e))
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar log: {:?}", e)))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
 #[tauri::command]
-async fn clear_old_logs(days: i32, state: State<'_, AppState>) -> Result<String, String> {
+async fn clear_old_logs(days: i32, state: State<'_, AppState>) -> Result<String, AppError> {
     let db_guard = state.database.lock().await;
     
     if let Some(db) = db_guard.as_ref() {
         db.clear_old_logs(days).await
-            .map_err(|e| format!("Erro ao limpar logs: {:?}", e))?;
+            .map_err(|e| AppError::Database(format!("Erro ao limpar logs: {:?}", e)))?;
         Ok(format!("Logs antigos de {} dias foram removidos", days))
     } else {
-        Err("Banco de dados não inicializado".to_string())
+        Err(AppError::db_not_initialized())
     }
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_dialog::init())
-        .manage(AppState {
-            tcp_server: Arc::new(Mutex::new(None)),
-            database: Arc::new(Mutex::new(None)),
-        })
-        .invoke_handler(tauri::generate_handler![
-            greet, 
-            start_tcp_server, 
-            send_plc_command,
-            connect_to_plc,
-            init_database,
-            get_all_texts,
-            update_text,
-            get_all_phases,
-            get_phase,
-            update_phase,
-            open_panel_window,
-            close_panel_window,
-            get_all_bit_configs,
-            get_bit_config,
-            add_bit_config,
-            update_bit_config,
-            delete_bit_config,
-            get_all_videos,
-            get_video,
-            add_video,
-            update_video,
-            delete_video,
-            get_enabled_videos,
-            reorder_video,
-            clear_all_videos,
-            get_file_path,
-            get_video_control_config,
-            set_video_control_config,
-            get_recent_logs,
-            add_system_log,
-            clear_old_logs
-        ])
-        .setup(|app| {
-            let app_handle = app.handle().clone();
-            
-            // Inicializa o banco de dados ANTES de tudo
-            tauri::async_runtime::block_on(async {
-                if let Some(state) = app_handle.try_state::<AppState>() {
-                    println!("🔄 Iniciando banco de dados...");
-                    
-                    // Obter o diretório de dados do app
-                    let app_data_dir = app_handle.path().app_data_dir()
-                        .expect("Falha ao obter diretório de dados do app");
-                    
-                    println!("📁 Diretório base: {:?}", app_data_dir);
-                    println!("📁 Diretório existe? {}", app_data_dir.exists());
-                    
-                    // Criar diretório se não existir
-                    if !app_data_dir.exists() {
-                        match std::fs::create_dir_all(&app_data_dir) {
-                            Ok(_) => println!("✅ Diretório criado com sucesso"),
-                            Err(e) => {
-                                eprintln!("❌ Erro ao criar diretório: {:?}", e);
-                                return;
-                            }
-                        }
-                    }
-                    
-                    // Caminho completo do banco
-                    let db_path = app_data_dir.join("plc_config.db");
-                    println!("📁 Caminho do banco: {}", db_path.display());
-                    
-                    // Criar arquivo vazio se não existir (para SQLite conseguir abrir)
-                    if !db_path.exists() {
-                        match std::fs::File::create(&db_path) {
-                            Ok(_) => println!("✅ Arquivo do banco criado"),
-                            Err(e) => eprintln!("⚠️ Erro ao criar arquivo: {:?}", e)
-                        }
-                    }
-                    
-                    // URL do SQLite (precisa ser absoluta)
-                    let db_url = format!("sqlite://{}?mode=rwc", db_path.to_string_lossy().replace('\\', "/"));
-                    println!("🔗 URL do banco: {}", db_url);
-                    
-                    match Database::new(&db_url).await {
-                        Ok(db) => {
-                            let db_arc = Arc::new(db);
-                            *state.database.lock().await = Some(db_arc.clone());
-                            println!("✅ Banco de dados inicializado com sucesso!");
-                            
-                            // Log de inicialização do sistema
-                            let _ = db_arc.add_system_log(
-                                "info", 
-                                "database", 
-                                "Sistema inicializado com sucesso", 
-                                &format!("Banco: {}", db_path.display())
-                            ).await;
-                        }
-                        Err(e) => {
-                            eprintln!("❌ ERRO CRÍTICO ao inicializar banco: {:?}", e);
-                            eprintln!("   Detalhes: {}", e);
-                        }
-                    }
-                }
-            });
-            
-            {
-                let app_handle_clone = app_handle.clone();
-                tauri::async_runtime::spawn(async move {
-                    // Aguarda um pouco para garantir que o app está pronto
-                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                    
-                    // Inicia o servidor TCP na porta 8502
-                    if let Some(state) = app_handle_clone.try_state::<AppState>() {
-                        let mut server = TcpServer::new(8502);
-                        
-                        // Configurar database se já estiver inicializado
-                        if let Some(db) = state.database.lock().await.as_ref() {
-                            server.set_database(Arc::downgrade(db));
-                        }
-                        
-                        let server = Arc::new(server);
-                        let server_clone = server.clone();
-                        
-                        tokio::spawn(async move {
-                            if let Err(e) = server_clone.start().await {
-                                eprintln!("Erro ao iniciar servidor TCP: {:?}", e);
-                            }
-                        });
-                        
-                        let mut rx = server.subscribe();
-                        let app_handle_clone2 = app_handle_clone.clone();
-                        tokio::spawn(async move {
-                            while let Ok(data) = rx.recv().await {
-                                let _ = app_handle_clone2.emit("plc-data", PlcDataPayload { message: data });
-                            }
-                        });
+#[tauri::command]
+async fn get_log_forwarding_config(state: State<'_, AppState>) -> Result<log_forwarder::LogForwardingConfig, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        log_forwarder::LogForwardingConfig::load(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar configuração de encaminhamento: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn set_log_forwarding_config(
+    enabled: bool,
+    url: String,
+    panel_id: String,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let url = url.trim().to_string();
+    if enabled && !(url.starts_with("ws://") || url.starts_with("wss://")) {
+        return Err(AppError::ConfigInvalid("URL do HMI deve começar com ws:// ou wss://".to_string()));
+    }
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let config = log_forwarder::LogForwardingConfig {
+            enabled,
+            url,
+            panel_id: if panel_id.trim().is_empty() { log_forwarder::hostname() } else { panel_id.trim().to_string() },
+        };
+        config.save(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar configuração de encaminhamento: {:?}", e)))?;
+        
+        let _ = db.add_system_log(
+            "info",
+            "system",
+            "Encaminhamento de logs ao HMI atualizado",
+            &format!("Ativo: {} - URL: {} - Painel: {}", config.enabled, config.url, config.panel_id)
+        ).await;
+        
+        Ok("Configuração de encaminhamento salva".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn get_hmi_ipc_config(state: State<'_, AppState>) -> Result<hmi_ipc::HmiIpcConfig, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        hmi_ipc::HmiIpcConfig::load(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar configuração de IPC com o HMI: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn set_hmi_ipc_config(
+    enabled: bool,
+    endpoint: String,
+    tags: Vec<String>,
+    token: Option<String>,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let tags: Vec<String> = tags.iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+    if enabled && tags.is_empty() {
+        return Err(AppError::ConfigInvalid("Informe pelo menos um tag para assinar no HMI".to_string()));
+    }
+    if tags.iter().any(|t| t.contains(',')) {
+        return Err(AppError::ConfigInvalid("Nome de tag não pode conter vírgula".to_string()));
+    }
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        // Sem token informado mantém o atual
+        let token = match token {
+            Some(token) => token.trim().to_string(),
+            None => hmi_ipc::HmiIpcConfig::load(db).await.map(|c| c.token).unwrap_or_default(),
+        };
+        let config = hmi_ipc::HmiIpcConfig { enabled, endpoint: endpoint.trim().to_string(), tags, token };
+        config.save(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar configuração de IPC com o HMI: {:?}", e)))?;
+        
+        let _ = db.add_system_log(
+            "info",
+            "system",
+            "Assinatura de tags do HMI (IPC) atualizada",
+            &format!("Ativo: {} - Endpoint: {} - Tags: {}", config.enabled,
+                     if config.endpoint.is_empty() { hmi_ipc::default_endpoint() } else { config.endpoint.clone() }, config.tags.len())
+        ).await;
+        
+        Ok("Configuração de IPC com o HMI salva".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn get_content_sync_config(state: State<'_, AppState>) -> Result<content_sync::ContentSyncConfig, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        content_sync::ContentSyncConfig::load(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar configuração de sincronização: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn set_content_sync_config(
+    enabled: bool,
+    manifest_url: String,
+    interval_s: u64,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let manifest_url = manifest_url.trim().to_string();
+    if enabled && !(manifest_url.starts_with("http://") || manifest_url.starts_with("https://")) {
+        return Err(AppError::ConfigInvalid("URL do manifesto deve começar com http:// ou https://".to_string()));
+    }
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let config = content_sync::ContentSyncConfig { enabled, manifest_url, interval_s, applied_version: None };
+        config.save(db).await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar configuração de sincronização: {:?}", e)))?;
+        Ok("Configuração de sincronização salva".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Sincroniza agora, mesmo que a versão do manifesto já tenha sido aplicada
+#[tauri::command]
+async fn sync_content_now(app_handle: AppHandle, state: State<'_, AppState>) -> Result<content_sync::ContentSyncResult, AppError> {
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| AppError::Io(format!("Erro ao obter diretório de dados: {:?}", e)))?;
+    
+    let result = content_sync::sync_once(&db, &app_data_dir, true).await?;
+    let _ = app_handle.emit("content-synced", &result);
+    Ok(result)
+}
+
+#[tauri::command]
+async fn get_display_health(state: State<'_, AppState>) -> Result<display_monitor::DisplayHealthStatus, AppError> {
+    Ok(state.display_health.lock().await.clone())
+}
+
+/// Estado real do painel público: janela, conteúdo em exibição, vídeo e idade do plc-data
+#[tauri::command]
+async fn get_panel_status(app_handle: AppHandle, state: State<'_, AppState>) -> Result<panel_status::PanelStatus, AppError> {
+    Ok(panel_status::build_status(&app_handle, &state.panel_tracker, &state.display_health, &state.event_metrics).await)
+}
+
+#[tauri::command]
+async fn get_display_watchdog_action(state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_display_config(display_monitor::KEY_WATCHDOG_ACTION).await
+            .map(|action| action.unwrap_or_else(|| "reload_panel".to_string()))
+            .map_err(|e| AppError::Database(format!("Erro ao buscar ação do watchdog: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Ação ao detectar travamento do display: "log", "reload_panel" ou "restart_app"
+#[tauri::command]
+async fn set_display_watchdog_action(action: String, state: State<'_, AppState>) -> Result<String, AppError> {
+    if !matches!(action.as_str(), "log" | "reload_panel" | "restart_app") {
+        return Err(AppError::ConfigInvalid(format!("Ação inválida: {} (use log, reload_panel ou restart_app)", action)));
+    }
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.set_display_config(display_monitor::KEY_WATCHDOG_ACTION, &action, "string").await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar ação do watchdog: {:?}", e)))?;
+        Ok("Ação do watchdog salva".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn get_plc_recording_info(app_handle: AppHandle) -> Result<data_recorder::RecordingInfo, AppError> {
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| AppError::Io(format!("Erro ao obter diretório de dados: {:?}", e)))?;
+    Ok(data_recorder::recording_info(&app_data_dir).await)
+}
+
+/// Exporta para CSV as words gravadas entre `start` e `end` (RFC3339)
+#[tauri::command]
+async fn export_plc_recording(
+    start: String,
+    end: String,
+    output_path: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<usize, AppError> {
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| AppError::Io(format!("Erro ao obter diretório de dados: {:?}", e)))?;
+    let exported = data_recorder::export_range(&app_data_dir, &start, &end, std::path::Path::new(&output_path)).await?;
+    
+    if let Some(db) = state.database.lock().await.as_ref() {
+        let _ = db.add_system_log(
+            "info",
+            "recording",
+            "Gravação do PLC exportada",
+            &format!("{} frames de {} até {} -> {}", exported, start, end, output_path)
+        ).await;
+    }
+    Ok(exported)
+}
+
+// ============================================================================
+// HISTÓRICO RECENTE DAS WORDS (EM MEMÓRIA)
+// ============================================================================
+
+/// Última mudança de "Word[2].3" (bit) ou "Word[2]" dentro do histórico
+#[tauri::command]
+fn get_last_word_change(reference: String, state: State<'_, AppState>) -> Result<word_history::LastWordChange, AppError> {
+    let reference = word_history::WordRef::parse(&reference)?;
+    let history = state.word_history.lock().map_err(|_| "Histórico indisponível".to_string())?;
+    Ok(history.last_change(reference))
+}
+
+/// Mudanças (mais recente primeiro) nos últimos `minutes` minutos - ex: semáforo oscilando
+#[tauri::command]
+fn get_word_changes(reference: String, minutes: Option<u32>, limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<word_history::WordChange>, AppError> {
+    let reference = word_history::WordRef::parse(&reference)?;
+    let since = minutes.map(|m| chrono::Utc::now() - chrono::Duration::minutes(m as i64));
+    let history = state.word_history.lock().map_err(|_| "Histórico indisponível".to_string())?;
+    Ok(history.changes(reference, since, limit.unwrap_or(100)))
+}
+
+#[tauri::command]
+fn get_word_history_info(state: State<'_, AppState>) -> Result<word_history::WordHistoryInfo, AppError> {
+    let history = state.word_history.lock().map_err(|_| "Histórico indisponível".to_string())?;
+    Ok(history.info())
+}
+
+#[tauri::command]
+async fn set_word_history_minutes(minutes: u32, state: State<'_, AppState>) -> Result<String, AppError> {
+    if !(1..=word_history::MAX_MINUTES).contains(&minutes) {
+        return Err(AppError::ConfigInvalid(format!("Duração inválida: {} min (1-{})", minutes, word_history::MAX_MINUTES)));
+    }
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    db.set_display_config(word_history::KEY_WORD_HISTORY_MINUTES, &minutes.to_string(), "number").await
+        .map_err(|e| AppError::Database(format!("Erro ao salvar histórico: {:?}", e)))?;
+    if let Ok(mut history) = state.word_history.lock() {
+        history.set_minutes(minutes);
+    }
+    Ok(format!("Histórico das words: últimos {} min", minutes))
+}
+
+// ============================================================================
+// MAPEAMENTO DE DADOS (nome -> word/bit ou analógico escalado)
+// ============================================================================
+
+fn validate_data_mapping(name: &str, word_index: i32, mapping_type: &str, bit_index: i32) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Nome da variável não pode ser vazio".to_string());
+    }
+    if name.starts_with("Word[") {
+        return Err("Nome não pode sobrescrever uma WORD recebida do PLC".to_string());
+    }
+    if !(0..128).contains(&word_index) {
+        return Err(format!("WORD inválida: {} (0-127)", word_index));
+    }
+    match mapping_type {
+        "bit" if !(0..16).contains(&bit_index) => Err(format!("Bit inválido: {} (0-15)", bit_index)),
+        "bit" | "analog" => Ok(()),
+        _ => Err(format!("Tipo inválido: {} (use bit ou analog)", mapping_type)),
+    }
+}
+
+/// Aplica no servidor TCP o mapeamento atual do banco
+async fn reload_data_mappings(state: &AppState, db: &Database) -> Result<(), String> {
+    let mappings = db.get_all_data_mappings().await
+        .map_err(|e| format!("Erro ao buscar mapeamento de dados: {:?}", e))?;
+    if let Some(server) = state.tcp_server.lock().await.as_ref() {
+        server.set_data_mappings(mappings);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_all_data_mappings(state: State<'_, AppState>) -> Result<Vec<DataMapping>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_data_mappings().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar mapeamento de dados: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn add_data_mapping(
+    name: String,
+    word_index: i32,
+    mapping_type: String,
+    bit_index: i32,
+    scale: f64,
+    offset: f64,
+    signed: bool,
+    enabled: bool,
+    description: String,
+    state: State<'_, AppState>
+) -> Result<i64, AppError> {
+    validate_data_mapping(&name, word_index, &mapping_type, bit_index).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let id = db.add_data_mapping(name.trim(), word_index, &mapping_type, bit_index, scale, offset, signed, enabled, &description).await
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar mapeamento: {:?}", e)))?;
+        reload_data_mappings(&state, db).await?;
+        Ok(id)
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn update_data_mapping(
+    id: i64,
+    name: String,
+    word_index: i32,
+    mapping_type: String,
+    bit_index: i32,
+    scale: f64,
+    offset: f64,
+    signed: bool,
+    enabled: bool,
+    description: String,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    validate_data_mapping(&name, word_index, &mapping_type, bit_index).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.update_data_mapping(id, name.trim(), word_index, &mapping_type, bit_index, scale, offset, signed, enabled, &description).await
+            .map_err(|e| AppError::Database(format!("Erro ao atualizar mapeamento: {:?}", e)))?;
+        reload_data_mappings(&state, db).await?;
+        Ok("Mapeamento atualizado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_data_mapping(id: i64, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.delete_data_mapping(id).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar mapeamento: {:?}", e)))?;
+        reload_data_mappings(&state, db).await?;
+        Ok("Mapeamento deletado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+// ============================================================================
+// PROTOCOLO POR ORIGEM (JSON / BINÁRIO / HANDSHAKE)
+// ============================================================================
+
+#[tauri::command]
+async fn get_protocol_configs(state: State<'_, AppState>) -> Result<Vec<ProtocolConfig>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_protocol_configs().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar configurações de protocolo: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// `source` = IP da origem ou "*"; vale para as próximas conexões
+#[tauri::command]
+async fn save_protocol_config(
+    source: String,
+    protocol: String,
+    handshake: String,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let source = source.trim().to_string();
+    if source != "*" && source.parse::<std::net::IpAddr>().is_err() {
+        return Err(AppError::ConfigInvalid(format!("Origem inválida: {} (use um IP ou *)", source)));
+    }
+    if PlcProtocol::parse(&protocol).is_none() {
+        return Err(AppError::ConfigInvalid(format!("Protocolo inválido: {} (use auto, json ou binary)", protocol)));
+    }
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.save_protocol_config(&source, &protocol.trim().to_ascii_lowercase(), &handshake).await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar protocolo: {:?}", e)))?;
+        reload_protocol_configs(&state, db).await?;
+        Ok(format!("Protocolo de {} salvo", source))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_protocol_config(source: String, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.delete_protocol_config(&source).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar protocolo: {:?}", e)))?;
+        reload_protocol_configs(&state, db).await?;
+        Ok(format!("Protocolo de {} removido", source))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+async fn reload_protocol_configs(state: &AppState, db: &Database) -> Result<(), String> {
+    let configs = db.get_all_protocol_configs().await
+        .map_err(|e| format!("Erro ao buscar configurações de protocolo: {:?}", e))?;
+    if let Some(server) = state.tcp_server.lock().await.as_ref() {
+        server.set_protocol_configs(configs);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TEMAS DO PAINEL
+// ============================================================================
+
+fn validate_theme(theme: &PanelTheme) -> Result<(), String> {
+    if theme.name.trim().is_empty() {
+        return Err("Nome do tema não pode ser vazio".to_string());
+    }
+    if !matches!(theme.logo_position.as_str(), "top-left" | "top-right" | "bottom-left" | "bottom-right") {
+        return Err(format!("Posição do logo inválida: {}", theme.logo_position));
+    }
+    Ok(())
+}
+
+/// Envia o tema ativo para todas as janelas (painel aplica sem recarregar)
+async fn emit_active_theme(app_handle: &AppHandle, db: &Database) {
+    if let Ok(Some(theme)) = db.get_active_theme().await {
+        println!("🎨 Tema ativo: {}", theme.name);
+        let _ = app_handle.emit("theme-changed", &theme);
+    }
+}
+
+#[tauri::command]
+async fn get_all_themes(state: State<'_, AppState>) -> Result<Vec<PanelTheme>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_themes().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar temas: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn get_active_theme(state: State<'_, AppState>) -> Result<Option<PanelTheme>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_active_theme().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar tema ativo: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn add_theme(theme: PanelTheme, state: State<'_, AppState>) -> Result<i64, AppError> {
+    validate_theme(&theme).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.add_theme(&theme).await
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar tema: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn update_theme(theme: PanelTheme, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    validate_theme(&theme).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.update_theme(&theme).await
+            .map_err(|e| AppError::Database(format!("Erro ao atualizar tema: {:?}", e)))?;
+        // Alteração no tema em uso aparece nos painéis imediatamente
+        if db.get_active_theme().await.ok().flatten().map(|t| t.id) == Some(theme.id) {
+            emit_active_theme(&app_handle, db).await;
+        }
+        Ok("Tema atualizado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_theme(id: i64, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let active = db.get_active_theme().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar tema ativo: {:?}", e)))?;
+        if active.map(|t| t.id) == Some(id) {
+            return Err(AppError::ConfigInvalid("Não é possível deletar o tema ativo".to_string()));
+        }
+        db.delete_theme(id).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar tema: {:?}", e)))?;
+        Ok("Tema deletado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn set_active_theme(id: i64, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let theme = db.get_theme(id).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar tema: {:?}", e)))?
+            .ok_or_else(|| AppError::NotFound(format!("Tema {} não encontrado", id)))?;
+        db.set_display_config(database::ACTIVE_THEME_KEY, &id.to_string(), "number").await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar tema ativo: {:?}", e)))?;
+        emit_active_theme(&app_handle, db).await;
+        let _ = db.add_system_log("info", "ui", "Tema do painel alterado", &theme.name).await;
+        Ok(format!("Tema '{}' ativado", theme.name))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+// ============================================================================
+// APROVAÇÃO DE CONTEÚDO (rascunho -> approve_changes)
+// ============================================================================
+
+#[derive(serde::Serialize)]
+struct ContentApprovalStatus {
+    required: bool,
+    approvers: i64,
+    pending: usize,
+}
+
+#[tauri::command]
+async fn get_content_approval_status(state: State<'_, AppState>) -> Result<ContentApprovalStatus, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        Ok(ContentApprovalStatus {
+            required: content_approval::approval_required(db).await,
+            approvers: db.count_content_approvers().await
+                .map_err(|e| AppError::Database(format!("Erro ao contar aprovadores: {:?}", e)))?,
+            pending: db.get_content_drafts().await
+                .map_err(|e| AppError::Database(format!("Erro ao buscar rascunhos: {:?}", e)))?.len(),
+        })
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Ativar exige um aprovador cadastrado; desativar exige credenciais de aprovador
+#[tauri::command]
+async fn set_content_approval_required(
+    enabled: bool,
+    approver: String,
+    pin: String,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        if enabled {
+            let approvers = db.count_content_approvers().await
+                .map_err(|e| AppError::Database(format!("Erro ao contar aprovadores: {:?}", e)))?;
+            if approvers == 0 {
+                return Err(AppError::ConfigInvalid("Cadastre um usuário aprovador antes de ativar o fluxo de aprovação".to_string()));
+            }
+        } else {
+            content_approval::authorize_approver(db, &approver, &pin).await?;
+        }
+        db.set_display_config(content_approval::KEY_APPROVAL_REQUIRED, if enabled { "true" } else { "false" }, "boolean").await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar configuração: {:?}", e)))?;
+        let _ = db.add_system_log(
+            "info",
+            "audit",
+            &format!("Fluxo de aprovação de conteúdo {}", if enabled { "ativado" } else { "desativado" }),
+            &format!("Por: {}", if approver.is_empty() { "desconhecido" } else { &approver }),
+        ).await;
+        Ok(format!("Aprovação de conteúdo {}", if enabled { "ativada" } else { "desativada" }))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn list_content_users(state: State<'_, AppState>) -> Result<Vec<content_approval::ContentUser>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.list_content_users().await
+            .map(|users| users.into_iter().map(|(username, role)| content_approval::ContentUser { username, role }).collect())
+            .map_err(|e| AppError::Database(format!("Erro ao buscar usuários: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Cria/atualiza usuário. Sem nenhum aprovador cadastrado, o primeiro pode ser criado sem credenciais.
+#[tauri::command]
+async fn save_content_user(
+    username: String,
+    role: String,
+    pin: String,
+    approver: String,
+    approver_pin: String,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let username = username.trim().to_string();
+    if username.is_empty() || pin.len() < 4 {
+        return Err(AppError::ConfigInvalid("Informe o usuário e um PIN com pelo menos 4 caracteres".to_string()));
+    }
+    if role != content_approval::ROLE_EDITOR && role != content_approval::ROLE_APPROVER {
+        return Err(AppError::ConfigInvalid(format!("Papel inválido: {} (use editor ou approver)", role)));
+    }
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let approvers = db.count_content_approvers().await
+            .map_err(|e| AppError::Database(format!("Erro ao contar aprovadores: {:?}", e)))?;
+        if approvers > 0 {
+            content_approval::authorize_approver(db, &approver, &approver_pin).await?;
+        }
+        db.save_content_user(&username, &role, &content_approval::hash_pin(&username, &pin)).await
+            .map_err(|e| AppError::Database(format!("Erro ao salvar usuário: {:?}", e)))?;
+        let _ = db.add_system_log("info", "audit", &format!("Usuário {} salvo com papel {}", username, role),
+            &format!("Por: {}", if approvers > 0 { approver.as_str() } else { "configuração inicial" })).await;
+        Ok(format!("Usuário {} salvo", username))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_content_user(
+    username: String,
+    approver: String,
+    approver_pin: String,
+    state: State<'_, AppState>
+) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        content_approval::authorize_approver(db, &approver, &approver_pin).await?;
+        let is_approver = db.get_content_user(&username).await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar usuário: {:?}", e)))?
+            .map(|(role, _)| role == content_approval::ROLE_APPROVER)
+            .unwrap_or(false);
+        let approvers = db.count_content_approvers().await
+            .map_err(|e| AppError::Database(format!("Erro ao contar aprovadores: {:?}", e)))?;
+        if is_approver && approvers <= 1 && content_approval::approval_required(db).await {
+            return Err(AppError::ConfigInvalid("Não é possível remover o último aprovador com o fluxo de aprovação ativo".to_string()));
+        }
+        db.delete_content_user(&username).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar usuário: {:?}", e)))?;
+        let _ = db.add_system_log("info", "audit", &format!("Usuário {} removido", username), &format!("Por: {}", approver)).await;
+        Ok(format!("Usuário {} removido", username))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Rascunhos pendentes com diff campo a campo em relação ao conteúdo no ar
+#[tauri::command]
+async fn list_pending_changes(state: State<'_, AppState>) -> Result<Vec<content_approval::PendingChange>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        content_approval::list_pending(db).await.map_err(AppError::from)
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Coloca no ar os rascunhos indicados (lista vazia = todos)
+#[tauri::command]
+async fn approve_changes(
+    ids: Vec<i64>,
+    approver: String,
+    pin: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>
+) -> Result<usize, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        content_approval::authorize_approver(db, &approver, &pin).await?;
+        let applied = content_approval::resolve_drafts(db, &ids, &approver, true).await?;
+        if applied > 0 {
+            // Evento aprovado pode entrar em vigor agora
+            panel_events::refresh(&app_handle, db, &state.panel_event).await;
+            let _ = app_handle.emit("content-approved", applied);
+        }
+        Ok(applied)
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn reject_changes(
+    ids: Vec<i64>,
+    approver: String,
+    pin: String,
+    state: State<'_, AppState>
+) -> Result<usize, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        content_approval::authorize_approver(db, &approver, &pin).await?;
+        content_approval::resolve_drafts(db, &ids, &approver, false).await.map_err(AppError::from)
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+// ============================================================================
+// MOSTRADORES ANALÓGICOS (nível, velocidade...) CALCULADOS A PARTIR DAS WORDS
+// ============================================================================
+
+#[tauri::command]
+async fn get_all_analog_displays(state: State<'_, AppState>) -> Result<Vec<AnalogDisplay>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_analog_displays().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar mostradores analógicos: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn add_analog_display(display: AnalogDisplay, state: State<'_, AppState>) -> Result<i64, AppError> {
+    analog_display::validate(&display).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.add_analog_display(&display).await
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar mostrador analógico: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn update_analog_display(display: AnalogDisplay, state: State<'_, AppState>) -> Result<String, AppError> {
+    analog_display::validate(&display).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.update_analog_display(&display).await
+            .map_err(|e| AppError::Database(format!("Erro ao atualizar mostrador analógico: {:?}", e)))?;
+        Ok("Mostrador analógico atualizado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_analog_display(id: i64, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.delete_analog_display(id).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar mostrador analógico: {:?}", e)))?;
+        Ok("Mostrador analógico deletado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Pré-visualização na tela de configuração: resultado do mostrador para uma WORD informada
+#[tauri::command]
+fn preview_analog_display(display: AnalogDisplay, raw: f64) -> Result<analog_display::AnalogReading, AppError> {
+    analog_display::validate(&display).map_err(AppError::ConfigInvalid)?;
+    Ok(analog_display::evaluate(&display, Some(raw)))
+}
+
+// ============================================================================
+// TRANSIÇÕES DO PAINEL (playlist e classes de prioridade das mensagens)
+// ============================================================================
+
+async fn emit_transitions(app_handle: &AppHandle, db: &Database) {
+    if let Ok(configs) = db.get_all_transitions().await {
+        let _ = app_handle.emit("transitions-changed", &configs);
+    }
+}
+
+#[tauri::command]
+async fn get_all_transitions(state: State<'_, AppState>) -> Result<Vec<TransitionConfig>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_transitions().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar transições: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn add_transition(transition: TransitionConfig, app_handle: AppHandle, state: State<'_, AppState>) -> Result<i64, AppError> {
+    transitions::validate(&transition).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        let id = db.add_transition(&transition).await
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar transição: {:?}", e)))?;
+        emit_transitions(&app_handle, db).await;
+        Ok(id)
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn update_transition(transition: TransitionConfig, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    transitions::validate(&transition).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.update_transition(&transition).await
+            .map_err(|e| AppError::Database(format!("Erro ao atualizar transição: {:?}", e)))?;
+        emit_transitions(&app_handle, db).await;
+        Ok("Transição atualizada com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_transition(id: i64, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.delete_transition(id).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar transição: {:?}", e)))?;
+        emit_transitions(&app_handle, db).await;
+        Ok("Transição deletada com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+// ============================================================================
+// EVENTOS PROGRAMADOS DO PAINEL (textos/vídeos/tema por período)
+// ============================================================================
+
+#[tauri::command]
+async fn get_all_panel_events(state: State<'_, AppState>) -> Result<Vec<PanelEvent>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_panel_events().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar eventos: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn add_panel_event(event: PanelEvent, author: Option<String>, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    panel_events::validate(&event).map_err(AppError::ConfigInvalid)?;
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    let details = format!("{} ({} até {})", event.name, event.starts_at, event.ends_at);
+    // 🆕 Evento substitui conteúdo público: passa pelo fluxo de aprovação
+    if let Some(draft_id) = content_approval::submit_change(&db, ContentChange::AddPanelEvent { event }, author).await? {
+        return Ok(format!("Evento enviado para aprovação (rascunho #{})", draft_id));
+    }
+    let _ = db.add_system_log("info", "ui", "Evento do painel criado", &details).await;
+    panel_events::refresh(&app_handle, &db, &state.panel_event).await;
+    Ok("Evento criado com sucesso".to_string())
+}
+
+#[tauri::command]
+async fn update_panel_event(event: PanelEvent, author: Option<String>, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    panel_events::validate(&event).map_err(AppError::ConfigInvalid)?;
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    if let Some(draft_id) = content_approval::submit_change(&db, ContentChange::UpdatePanelEvent { event }, author).await? {
+        return Ok(format!("Evento enviado para aprovação (rascunho #{})", draft_id));
+    }
+    panel_events::refresh(&app_handle, &db, &state.panel_event).await;
+    Ok("Evento atualizado com sucesso".to_string())
+}
+
+#[tauri::command]
+async fn delete_panel_event(id: i64, app_handle: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    db.delete_panel_event(id).await
+        .map_err(|e| AppError::Database(format!("Erro ao deletar evento: {:?}", e)))?;
+    panel_events::refresh(&app_handle, &db, &state.panel_event).await;
+    Ok("Evento deletado com sucesso".to_string())
+}
+
+/// Evento em vigor (o mesmo enviado em "panel-event"); None = conteúdo normal
+#[tauri::command]
+async fn get_active_panel_event(state: State<'_, AppState>) -> Result<Option<panel_events::ActivePanelEvent>, AppError> {
+    Ok(state.panel_event.lock().await.clone())
+}
+
+/// Transições resolvidas para uma prioridade de mensagem (pré-visualização na configuração)
+#[tauri::command]
+async fn resolve_transitions(priority: Option<i32>, state: State<'_, AppState>) -> Result<transitions::PanelTransitions, AppError> {
+    let db = state.database.lock().await.clone()
+        .ok_or_else(AppError::db_not_initialized)?;
+    let configs = db.get_all_transitions().await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar transições: {:?}", e)))?;
+    Ok(transitions::resolve(&configs, priority))
+}
+
+// ============================================================================
+// CONTADORES REGRESSIVOS ("Tempo restante de eclusagem: 12:30")
+// ============================================================================
+
+#[tauri::command]
+async fn get_all_countdown_timers(state: State<'_, AppState>) -> Result<Vec<CountdownTimer>, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.get_all_countdown_timers().await
+            .map_err(|e| AppError::Database(format!("Erro ao buscar contadores: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn add_countdown_timer(timer: CountdownTimer, state: State<'_, AppState>) -> Result<i64, AppError> {
+    countdown::validate(&timer).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.add_countdown_timer(&timer).await
+            .map_err(|e| AppError::Database(format!("Erro ao adicionar contador: {:?}", e)))
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn update_countdown_timer(timer: CountdownTimer, state: State<'_, AppState>) -> Result<String, AppError> {
+    countdown::validate(&timer).map_err(AppError::ConfigInvalid)?;
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.update_countdown_timer(&timer).await
+            .map_err(|e| AppError::Database(format!("Erro ao atualizar contador: {:?}", e)))?;
+        Ok("Contador atualizado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+#[tauri::command]
+async fn delete_countdown_timer(id: i64, state: State<'_, AppState>) -> Result<String, AppError> {
+    let db_guard = state.database.lock().await;
+    
+    if let Some(db) = db_guard.as_ref() {
+        db.delete_countdown_timer(id).await
+            .map_err(|e| AppError::Database(format!("Erro ao deletar contador: {:?}", e)))?;
+        Ok("Contador deletado com sucesso".to_string())
+    } else {
+        Err(AppError::db_not_initialized())
+    }
+}
+
+/// Valores atuais dos contadores (os mesmos do evento "countdown-update")
+#[tauri::command]
+async fn get_countdown_values(state: State<'_, AppState>) -> Result<Vec<countdown::CountdownValue>, AppError> {
+    Ok(state.countdowns.lock().await.clone())
+}
+
+/// Eventos "plc-data" emitidos vs. consumidos por janela (atraso da webview)
+#[tauri::command]
+fn get_plc_event_metrics(state: State<'_, AppState>) -> event_metrics::PlcEventMetricsSnapshot {
+    state.event_metrics.snapshot()
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .manage(AppState {
+            tcp_server: Arc::new(Mutex::new(None)),
+            database: Arc::new(Mutex::new(None)),
+            display_health: Arc::new(Mutex::new(Default::default())),
+            event_metrics: Arc::new(Default::default()),
+            countdowns: Arc::new(Mutex::new(Vec::new())),
+            audio_policy: Arc::new(Mutex::new(None)),
+            panel_tracker: Arc::new(Mutex::new(Default::default())),
+            failsafe: Arc::new(Mutex::new(Default::default())),
+            panel_window: Arc::new(Default::default()),
+            word_history: Arc::new(std::sync::Mutex::new(Default::default())),
+            panel_event: Arc::new(Mutex::new(None)),
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet, 
+            start_tcp_server, 
+            send_plc_command,
+            write_plc_variable,
+            connect_to_plc,
+            init_database,
+            get_all_texts,
+            update_text,
+            get_all_phases,
+            get_phase,
+            update_phase,
+            get_phase_word_index,
+            set_phase_word_index,
+            open_panel_window,
+            close_panel_window,
+            toggle_panel_window,
+            get_panel_window_state,
+            get_all_bit_configs,
+            get_bit_config,
+            add_bit_config,
+            update_bit_config,
+            delete_bit_config,
+            get_all_videos,
+            get_video,
+            add_video,
+            update_video,
+            delete_video,
+            get_enabled_videos,
+            reorder_video,
+            clear_all_videos,
+            get_file_path,
+            get_video_control_config,
+            set_video_control_config,
+            get_recent_logs,
+            add_system_log,
+            clear_old_logs,
+            get_log_forwarding_config,
+            set_log_forwarding_config,
+            get_hmi_ipc_config,
+            set_hmi_ipc_config,
+            get_content_sync_config,
+            set_content_sync_config,
+            sync_content_now,
+            get_display_health,
+            get_panel_status,
+            get_display_watchdog_action,
+            set_display_watchdog_action,
+            get_plc_recording_info,
+            export_plc_recording,
+            get_last_word_change,
+            get_word_changes,
+            get_word_history_info,
+            set_word_history_minutes,
+            get_all_data_mappings,
+            add_data_mapping,
+            update_data_mapping,
+            delete_data_mapping,
+            get_protocol_configs,
+            save_protocol_config,
+            delete_protocol_config,
+            get_plc_event_metrics,
+            get_all_themes,
+            get_active_theme,
+            add_theme,
+            update_theme,
+            delete_theme,
+            set_active_theme,
+            get_content_approval_status,
+            set_content_approval_required,
+            list_content_users,
+            save_content_user,
+            delete_content_user,
+            list_pending_changes,
+            approve_changes,
+            reject_changes,
+            get_all_analog_displays,
+            add_analog_display,
+            update_analog_display,
+            delete_analog_display,
+            preview_analog_display,
+            get_all_countdown_timers,
+            add_countdown_timer,
+            update_countdown_timer,
+            delete_countdown_timer,
+            get_countdown_values,
+            set_video_audio,
+            get_quiet_hours_config,
+            set_quiet_hours_config,
+            get_audio_policy,
+            get_failsafe_config,
+            set_failsafe_config,
+            get_failsafe_state,
+            get_health_endpoint_config,
+            set_health_endpoint_config,
+            get_health_report,
+            get_all_transitions,
+            add_transition,
+            update_transition,
+            delete_transition,
+            get_all_panel_events,
+            add_panel_event,
+            update_panel_event,
+            delete_panel_event,
+            get_active_panel_event,
+            resolve_transitions
+        ])
+        .setup(|app| {
+            let app_handle = app.handle().clone();
+            
+            // Inicializa o banco de dados ANTES de tudo
+            tauri::async_runtime::block_on(async {
+                if let Some(state) = app_handle.try_state::<AppState>() {
+                    println!("🔄 Iniciando banco de dados...");
+                    
+                    // Obter o diretório de dados do app
+                    let app_data_dir = app_handle.path().app_data_dir()
+                        .expect("Falha ao obter diretório de dados do app");
+                    
+                    println!("📁 Diretório base: {:?}", app_data_dir);
+                    println!("📁 Diretório existe? {}", app_data_dir.exists());
+                    
+                    // Criar diretório se não existir
+                    if !app_data_dir.exists() {
+                        match std::fs::create_dir_all(&app_data_dir) {
+                            Ok(_) => println!("✅ Diretório criado com sucesso"),
+                            Err(e) => {
+                                eprintln!("❌ Erro ao criar diretório: {:?}", e);
+                                return;
+                            }
+                        }
+                    }
+                    
+                    // Caminho completo do banco
+                    let db_path = app_data_dir.join("plc_config.db");
+                    println!("📁 Caminho do banco: {}", db_path.display());
+                    
+                    // Criar arquivo vazio se não existir (para SQLite conseguir abrir)
+                    if !db_path.exists() {
+                        match std::fs::File::create(&db_path) {
+                            Ok(_) => println!("✅ Arquivo do banco criado"),
+                            Err(e) => eprintln!("⚠️ Erro ao criar arquivo: {:?}", e)
+                        }
+                    }
+                    
+                    // URL do SQLite (precisa ser absoluta)
+                    let db_url = format!("sqlite://{}?mode=rwc", db_path.to_string_lossy().replace('\\', "/"));
+                    println!("🔗 URL do banco: {}", db_url);
+                    
+                    match Database::new(&db_url).await {
+                        Ok(db) => {
+                            let db_arc = Arc::new(db);
+                            *state.database.lock().await = Some(db_arc.clone());
+                            println!("✅ Banco de dados inicializado com sucesso!");
+                            
+                            // Log de inicialização do sistema
+                            let _ = db_arc.add_system_log(
+                                "info", 
+                                "database", 
+                                "Sistema inicializado com sucesso", 
+                                &format!("Banco: {}", db_path.display())
+                            ).await;
+                        }
+                        Err(e) => {
+                            eprintln!("❌ ERRO CRÍTICO ao inicializar banco: {:?}", e);
+                            eprintln!("   Detalhes: {}", e);
+                        }
+                    }
+                }
+            });
+            
+            // Encaminhamento de logs/heartbeat para o plc-hmi central (se configurado)
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                log_forwarder::start_log_forwarder(state.database.clone(), state.tcp_server.clone());
+                
+                // Tags assinados direto do plc-hmi local (IPC), se configurado
+                hmi_ipc::start_hmi_ipc(state.database.clone(), state.tcp_server.clone());
+                
+                // Sincronização de conteúdo com o servidor central (se configurada)
+                if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+                    content_sync::start_content_sync(app_handle.clone(), state.database.clone(), app_data_dir);
+                }
+                
+                // Monitor de saúde do display (FPS/erros reportados pelo painel + monitores do SO)
+                display_monitor::start_display_monitor(app_handle.clone(), state.database.clone(), state.display_health.clone());
+                
+                // Estado do painel para a administração + heartbeat em system_logs
+                panel_status::start_panel_status(
+                    app_handle.clone(),
+                    state.database.clone(),
+                    state.panel_tracker.clone(),
+                    state.display_health.clone(),
+                    state.event_metrics.clone(),
+                );
+                
+                // Consumo dos eventos plc-data pelas janelas
+                event_metrics::start_event_metrics(app_handle.clone(), state.event_metrics.clone(), state.database.clone());
+                
+                // Áudio dos vídeos: horário de silêncio conferido periodicamente
+                audio_policy::start_audio_policy(app_handle.clone(), state.database.clone(), state.audio_policy.clone());
+                
+                // Estado seguro do painel quando os dados do PLC param de chegar
+                failsafe::start_failsafe(app_handle.clone(), state.database.clone(), state.event_metrics.clone(), state.failsafe.clone());
+                
+                // Eventos programados: ativação/expiração automática pelo período
+                panel_events::start_event_scheduler(app_handle.clone(), state.database.clone(), state.panel_event.clone());
+                
+                // Endpoint /healthz para o monitoramento do local (se habilitado)
+                health_endpoint::start_health_endpoint(health_context(app_handle.clone(), &state));
+            }
+            
+            {
+                let app_handle_clone = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    // Aguarda um pouco para garantir que o app está pronto
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                    
+                    // Inicia o servidor TCP na porta 8502
+                    if let Some(state) = app_handle_clone.try_state::<AppState>() {
+                        let mut server = TcpServer::new(8502);
+                        
+                        // Configurar database se já estiver inicializado
+                        if let Some(db) = state.database.lock().await.as_ref() {
+                            server.set_database(Arc::downgrade(db));
+                            
+                            // Mapeamento de dados (variáveis derivadas das WORDs)
+                            match db.get_all_data_mappings().await {
+                                Ok(mappings) => server.set_data_mappings(mappings),
+                                Err(e) => eprintln!("⚠️ Erro ao carregar mapeamento de dados: {:?}", e),
+                            }
+                            
+                            // Protocolo por origem (JSON/binário/handshake)
+                            match db.get_all_protocol_configs().await {
+                                Ok(configs) => server.set_protocol_configs(configs),
+                                Err(e) => eprintln!("⚠️ Erro ao carregar protocolos: {:?}", e),
+                            }
+                        }
+                        
+                        let server = Arc::new(server);
+                        let server_clone = server.clone();
+                        
+                        tokio::spawn(async move {
+                            if let Err(e) = server_clone.start().await {
+                                eprintln!("Erro ao iniciar servidor TCP: {:?}", e);
+                            }
+                        });
+                        
+                        let mut rx = server.subscribe();
+                        let app_handle_clone2 = app_handle_clone.clone();
+                        let event_metrics = state.event_metrics.clone();
+                        let database = state.database.clone();
+                        tokio::spawn(async move {
+                            while let Ok(data) = rx.recv().await {
+                                let payload = build_plc_payload(data, event_metrics.next_seq(), &database).await;
+                                let _ = app_handle_clone2.emit("plc-data", payload);
+                            }
+                        });
+                        
+                        // Gravação das words recebidas (últimas 48h) para análise posterior
+                        if let Ok(app_data_dir) = app_handle_clone.path().app_data_dir() {
+                            data_recorder::start_plc_recorder(app_data_dir, server.subscribe());
+                        }
+                        
+                        // Histórico em memória para "quando o Word[N].B mudou"
+                        word_history::start_word_history(state.word_history.clone(), state.database.clone(), server.subscribe());
+                        
+                        // Contadores regressivos ({Timer:nome}) recalculados a cada segundo
+                        countdown::start_countdowns(app_handle_clone.clone(), state.database.clone(), state.countdowns.clone(), server.subscribe());
                         
                         *state.tcp_server.lock().await = Some(server.clone());
                         
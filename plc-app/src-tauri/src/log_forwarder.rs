@@ -0,0 +1,205 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use crate::database::Database;
+use crate::tcp_server::TcpServer;
+
+// Encaminha system_logs e heartbeat deste painel para um plc-hmi central
+// (servidor WebSocket do HMI, comandos PANEL_HEARTBEAT / PANEL_LOGS).
+// O cursor de envio vem do próprio HMI (`last_log_id` nas respostas), então
+// nada é perdido nem duplicado se painel ou HMI reiniciarem.
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+const LOG_SEND_INTERVAL: Duration = Duration::from_secs(2);
+const LOG_BATCH_SIZE: i32 = 200;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+const DISABLED_POLL: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const PLC_STALE_AFTER_S: u64 = 30;
+
+// Chaves em display_configs
+pub const KEY_ENABLED: &str = "hmi_forward_enabled";
+pub const KEY_URL: &str = "hmi_forward_url";
+pub const KEY_PANEL_ID: &str = "hmi_forward_panel_id";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogForwardingConfig {
+    pub enabled: bool,
+    pub url: String,      // Ex: ws://192.168.1.10:8765
+    pub panel_id: String, // Identificação do painel no HMI (padrão: nome do computador)
+}
+
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "painel".to_string())
+}
+
+impl LogForwardingConfig {
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        let enabled = db.get_display_config(KEY_ENABLED).await?.map(|v| v == "true").unwrap_or(false);
+        let url = db.get_display_config(KEY_URL).await?.unwrap_or_default();
+        let panel_id = db.get_display_config(KEY_PANEL_ID).await?
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(hostname);
+        Ok(Self { enabled, url, panel_id })
+    }
+
+    pub async fn save(&self, db: &Database) -> Result<(), sqlx::Error> {
+        db.set_display_config(KEY_ENABLED, if self.enabled { "true" } else { "false" }, "boolean").await?;
+        db.set_display_config(KEY_URL, &self.url, "string").await?;
+        db.set_display_config(KEY_PANEL_ID, &self.panel_id, "string").await?;
+        Ok(())
+    }
+}
+
+/// Inicia o encaminhamento em segundo plano (obedece a configuração a cada reconexão)
+pub fn start_log_forwarder(
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    tcp_server: Arc<Mutex<Option<Arc<TcpServer>>>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let mut backoff = Duration::from_secs(1);
+
+        loop {
+            let Some(db) = database.lock().await.clone() else {
+                tokio::time::sleep(DISABLED_POLL).await;
+                continue;
+            };
+            let config = match LogForwardingConfig::load(&db).await {
+                Ok(config) if config.enabled && !config.url.trim().is_empty() => config,
+                Ok(_) => {
+                    tokio::time::sleep(DISABLED_POLL).await;
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("⚠️ Encaminhamento de logs: erro ao ler configuração: {:?}", e);
+                    tokio::time::sleep(DISABLED_POLL).await;
+                    continue;
+                }
+            };
+
+            let session_start = Instant::now();
+            match run_session(&db, &tcp_server, &config, started).await {
+                Ok(()) => println!("🔄 Encaminhamento de logs: configuração alterada, reconectando"),
+                Err(e) => eprintln!("⚠️ Encaminhamento de logs para {}: {}", config.url, e),
+            }
+
+            if session_start.elapsed() > MAX_BACKOFF {
+                backoff = Duration::from_secs(1);
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+async fn heartbeat_message(
+    tcp_server: &Arc<Mutex<Option<Arc<TcpServer>>>>,
+    config: &LogForwardingConfig,
+    started: Instant,
+) -> String {
+    let last_data_age_s = tcp_server.lock().await.as_ref().and_then(|s| s.last_data_age_s());
+    serde_json::json!({
+        "type": "PANEL_HEARTBEAT",
+        "panel": {
+            "panel_id": config.panel_id,
+            "hostname": hostname(),
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "plc_connected": last_data_age_s.map(|age| age <= PLC_STALE_AFTER_S).unwrap_or(false),
+            "last_data_age_s": last_data_age_s,
+            "uptime_s": started.elapsed().as_secs()
+        }
+    }).to_string()
+}
+
+/// Uma conexão com o HMI. Ok(()) = configuração mudou; Err = falha de comunicação.
+async fn run_session(
+    db: &Arc<Database>,
+    tcp_server: &Arc<Mutex<Option<Arc<TcpServer>>>>,
+    config: &LogForwardingConfig,
+    started: Instant,
+) -> Result<(), String> {
+    // Dados de tags em frames binários: mais leves e descartados sem parse (HMI antigo ignora o parâmetro)
+    let url = format!("{}{}format=binary", config.url, if config.url.contains('?') { '&' } else { '?' });
+    let (ws, _) = tokio::time::timeout(CONNECT_TIMEOUT, connect_async(url.as_str())).await
+        .map_err(|_| "timeout na conexão".to_string())?
+        .map_err(|e| format!("erro na conexão: {}", e))?;
+    let (mut sender, mut receiver) = ws.split();
+    println!("📡 Encaminhando logs para o HMI {} como '{}'", config.url, config.panel_id);
+
+    sender.send(Message::Text(heartbeat_message(tcp_server, config, started).await)).await
+        .map_err(|e| format!("erro ao enviar heartbeat: {}", e))?;
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut log_tick = tokio::time::interval(LOG_SEND_INTERVAL);
+    let mut cursor: Option<i64> = None;     // Definido pelo primeiro PANEL_ACK
+    let mut in_flight: Option<Instant> = None; // Lote aguardando PANEL_LOGS_ACK
+    let mut last_ack = Instant::now();
+
+    loop {
+        tokio::select! {
+            msg = receiver.next() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Err("conexão encerrada pelo HMI".to_string()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(format!("erro ao receber: {}", e)),
+                };
+                // Dados de tags (binários/JSON) chegam na mesma conexão e são ignorados
+                let Ok(response) = serde_json::from_str::<serde_json::Value>(&text) else { continue };
+                let response_type = response.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                if response_type != "PANEL_ACK" && response_type != "PANEL_LOGS_ACK" {
+                    continue;
+                }
+                last_ack = Instant::now();
+                if response_type == "PANEL_LOGS_ACK" {
+                    in_flight = None;
+                }
+                if response.get("success").and_then(|s| s.as_bool()) == Some(true) {
+                    if let Some(last_id) = response.get("last_log_id").and_then(|id| id.as_i64()) {
+                        cursor = Some(last_id);
+                    }
+                } else {
+                    let message = response.get("message").and_then(|m| m.as_str()).unwrap_or("?");
+                    eprintln!("⚠️ HMI recusou {}: {}", response_type, message);
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_ack.elapsed() > ACK_TIMEOUT {
+                    return Err("HMI não responde aos heartbeats".to_string());
+                }
+                match LogForwardingConfig::load(db).await {
+                    Ok(current) if &current != config => return Ok(()),
+                    _ => {}
+                }
+                sender.send(Message::Text(heartbeat_message(tcp_server, config, started).await)).await
+                    .map_err(|e| format!("erro ao enviar heartbeat: {}", e))?;
+            }
+            _ = log_tick.tick() => {
+                let Some(after_id) = cursor else { continue };
+                if in_flight.is_some_and(|sent| sent.elapsed() < ACK_TIMEOUT) {
+                    continue;
+                }
+                let logs = db.get_logs_after(after_id, LOG_BATCH_SIZE).await
+                    .map_err(|e| format!("erro ao ler system_logs: {:?}", e))?;
+                if logs.is_empty() {
+                    continue;
+                }
+                let message = serde_json::json!({
+                    "type": "PANEL_LOGS",
+                    "panel_id": config.panel_id,
+                    "logs": logs
+                });
+                sender.send(Message::Text(message.to_string())).await
+                    .map_err(|e| format!("erro ao enviar logs: {}", e))?;
+                in_flight = Some(Instant::now());
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use crate::database::{Database, PanelEvent, PanelTheme, VideoConfig};
+
+// Eventos programados do painel (ex: avisos do fim de semana da regata): entre
+// `starts_at` e `ends_at` os textos, a playlist e o tema do evento substituem o
+// conteúdo normal. O agendador do backend ativa e expira sozinho; eventos
+// sobrepostos resolvem pela maior prioridade. Mensagens de bits ativas (PLC)
+// continuam na frente dos textos do evento. Cada troca emite "panel-event"
+// (null = sem evento) e fica em system_logs.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M";
+const MAX_MESSAGES: usize = 5;
+
+/// Evento em vigor, já com vídeos e tema resolvidos para o painel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActivePanelEvent {
+    pub id: i64,
+    pub name: String,
+    pub ends_at: String,
+    pub messages: Vec<String>,
+    pub videos: Vec<VideoConfig>,
+    pub theme: Option<PanelTheme>,
+}
+
+pub type ActivePanelEventState = Arc<Mutex<Option<ActivePanelEvent>>>;
+
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text.trim(), DATETIME_FORMAT).ok()
+}
+
+pub fn validate(event: &PanelEvent) -> Result<(), String> {
+    if event.name.trim().is_empty() {
+        return Err("Nome do evento é obrigatório".to_string());
+    }
+    let starts_at = parse_datetime(&event.starts_at)
+        .ok_or_else(|| format!("Início inválido: {} (use AAAA-MM-DDTHH:MM)", event.starts_at))?;
+    let ends_at = parse_datetime(&event.ends_at)
+        .ok_or_else(|| format!("Fim inválido: {} (use AAAA-MM-DDTHH:MM)", event.ends_at))?;
+    if ends_at <= starts_at {
+        return Err("O fim do evento deve ser depois do início".to_string());
+    }
+    if event.messages.len() > MAX_MESSAGES {
+        return Err(format!("No máximo {} textos por evento", MAX_MESSAGES));
+    }
+    if event.messages.iter().all(|m| m.trim().is_empty()) && event.video_ids.is_empty() && event.theme_id.is_none() {
+        return Err("O evento precisa de textos, vídeos ou tema".to_string());
+    }
+    Ok(())
+}
+
+/// Evento em vigor agora: habilitado, dentro do período, maior prioridade
+pub fn current(events: &[PanelEvent], now: NaiveDateTime) -> Option<&PanelEvent> {
+    events.iter()
+        .filter(|e| e.enabled)
+        .filter(|e| match (parse_datetime(&e.starts_at), parse_datetime(&e.ends_at)) {
+            (Some(start), Some(end)) => start <= now && now < end,
+            _ => false,
+        })
+        .max_by_key(|e| e.priority)
+}
+
+async fn resolve(db: &Database, event: &PanelEvent) -> ActivePanelEvent {
+    // Mantém a ordem escolhida no evento; vídeos desabilitados ou apagados ficam de fora
+    let all_videos = db.get_all_videos().await.unwrap_or_default();
+    let videos = event.video_ids.iter()
+        .filter_map(|id| all_videos.iter().find(|v| v.id == *id && v.enabled).cloned())
+        .collect();
+    let theme = match event.theme_id {
+        Some(id) => db.get_theme(id).await.ok().flatten(),
+        None => None,
+    };
+    ActivePanelEvent {
+        id: event.id,
+        name: event.name.clone(),
+        ends_at: event.ends_at.clone(),
+        messages: event.messages.iter().map(|m| m.trim().to_string()).filter(|m| !m.is_empty()).collect(),
+        videos,
+        theme,
+    }
+}
+
+/// Recalcula o evento em vigor e emite "panel-event" se mudou (chamado pelo agendador e após alterações)
+pub async fn refresh(app_handle: &AppHandle, db: &Database, state: &ActivePanelEventState) {
+    let events = match db.get_all_panel_events().await {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("⚠️ Erro ao carregar eventos do painel: {:?}", e);
+            return;
+        }
+    };
+    let next = match current(&events, chrono::Local::now().naive_local()) {
+        Some(event) => Some(resolve(db, event).await),
+        None => None,
+    };
+
+    let mut active = state.lock().await;
+    if *active == next {
+        return;
+    }
+    match (active.as_ref(), next.as_ref()) {
+        (previous, Some(event)) if previous.map(|p| p.id) != Some(event.id) => {
+            println!("📅 Evento do painel ativado: {} (até {})", event.name, event.ends_at);
+            let _ = db.add_system_log("info", "event", "Evento do painel ativado",
+                &format!("{} até {} - {} texto(s), {} vídeo(s)", event.name, event.ends_at, event.messages.len(), event.videos.len())).await;
+        }
+        (Some(previous), None) => {
+            println!("📅 Evento do painel encerrado: {}", previous.name);
+            let _ = db.add_system_log("info", "event", "Evento do painel encerrado", &previous.name).await;
+        }
+        _ => {}
+    }
+    let _ = app_handle.emit("panel-event", &next);
+    *active = next;
+}
+
+pub fn start_event_scheduler(
+    app_handle: AppHandle,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    state: ActivePanelEventState,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(db) = database.lock().await.clone() else { continue };
+            refresh(&app_handle, &db, &state).await;
+        }
+    });
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+use tokio::sync::Mutex;
+use crate::database::Database;
+use crate::display_monitor::DisplayMonitorState;
+use crate::event_metrics::PlcEventMetrics;
+
+// Estado real do painel público para a tela de administração: janela aberta,
+// o que está sendo exibido (evento "panel-content" enviado pelo painel a cada
+// troca), posição do vídeo (métricas do monitor de display) e idade do último
+// plc-data. A cada CHECK_INTERVAL o estado vai no evento "panel-status"; a
+// cada HEARTBEAT_INTERVAL (e quando o painel abre/fecha) fica em system_logs.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(300);
+const PLC_DATA_STALE_MS: u64 = 10_000; // ~20 ciclos de 500 ms sem dados
+
+/// Payload do evento "panel-content" enviado pelo painel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelContent {
+    pub view: String,                // "plc" (mensagens), "video" ou "failsafe" (estado seguro)
+    pub video_id: Option<i64>,
+    pub video_title: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<String>,       // Mensagens em exibição (modo "plc")
+}
+
+#[derive(Default)]
+pub struct PanelTracker {
+    content: Option<PanelContent>,
+    content_since: Option<Instant>,
+}
+
+pub type PanelTrackerState = Arc<Mutex<PanelTracker>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PanelStatus {
+    pub panel_open: bool,
+    pub content: Option<PanelContent>,
+    pub content_seconds: Option<u64>,        // Há quanto tempo o conteúdo atual está na tela
+    pub video_playing: bool,
+    pub video_time_s: Option<f64>,
+    pub video_duration_s: Option<f64>,
+    pub seconds_since_report: Option<u64>,   // Último relatório de métricas do painel
+    pub last_plc_data_age_ms: Option<u64>,   // None = nenhum plc-data emitido ainda
+    pub plc_data_stale: bool,
+    pub degraded: bool,
+    pub stalled: bool,
+}
+
+impl PanelStatus {
+    /// Resumo de uma linha para system_logs
+    fn summary(&self) -> String {
+        let content = match &self.content {
+            Some(content) if content.view == "video" => format!(
+                "vídeo '{}' em {:.0}s/{:.0}s",
+                content.video_title.as_deref().unwrap_or("?"),
+                self.video_time_s.unwrap_or(0.0),
+                self.video_duration_s.unwrap_or(0.0),
+            ),
+            Some(content) if content.view == "failsafe" => "estado seguro (dados do PLC velhos)".to_string(),
+            Some(content) => format!("{} mensagem(ns) PLC", content.messages.len()),
+            None => "conteúdo não informado".to_string(),
+        };
+        let plc = match self.last_plc_data_age_ms {
+            Some(age) => format!("último plc-data há {} ms", age),
+            None => "sem plc-data".to_string(),
+        };
+        format!("{}; {}; degradado={} travado={}", content, plc, self.degraded, self.stalled)
+    }
+}
+
+pub async fn build_status(
+    app_handle: &AppHandle,
+    tracker: &PanelTrackerState,
+    display_health: &DisplayMonitorState,
+    event_metrics: &PlcEventMetrics,
+) -> PanelStatus {
+    let panel_open = app_handle.get_webview_window("panel").is_some();
+    let (content, content_seconds) = {
+        let tracker = tracker.lock().await;
+        match (panel_open, &tracker.content) {
+            (true, Some(content)) => (Some(content.clone()), tracker.content_since.map(|since| since.elapsed().as_secs())),
+            _ => (None, None),
+        }
+    };
+    let health = display_health.lock().await.clone();
+    let metrics = health.last_metrics.as_ref().filter(|_| panel_open);
+    let last_plc_data_age_ms = event_metrics.last_emit_age().map(|age| age.as_millis() as u64);
+
+    PanelStatus {
+        panel_open,
+        content,
+        content_seconds,
+        video_playing: metrics.is_some_and(|m| m.video_active),
+        video_time_s: metrics.filter(|m| m.video_active).map(|m| m.video_time_s),
+        video_duration_s: metrics.filter(|m| m.video_active && m.video_duration_s > 0.0).map(|m| m.video_duration_s),
+        seconds_since_report: health.seconds_since_report.filter(|_| panel_open),
+        last_plc_data_age_ms,
+        plc_data_stale: last_plc_data_age_ms.map_or(true, |age| age > PLC_DATA_STALE_MS),
+        degraded: health.degraded,
+        stalled: health.stalled,
+    }
+}
+
+async fn log(database: &Arc<Mutex<Option<Arc<Database>>>>, level: &str, message: &str, details: &str) {
+    println!("📺 [Painel] {} - {}", message, details);
+    if let Some(db) = database.lock().await.as_ref() {
+        let _ = db.add_system_log(level, "panel", message, details).await;
+    }
+}
+
+pub fn start_panel_status(
+    app_handle: AppHandle,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+    tracker: PanelTrackerState,
+    display_health: DisplayMonitorState,
+    event_metrics: Arc<PlcEventMetrics>,
+) {
+    // Conteúdo informado pelo painel a cada troca
+    let tracker_listener = tracker.clone();
+    app_handle.listen("panel-content", move |event| {
+        let Ok(content) = serde_json::from_str::<PanelContent>(event.payload()) else { return };
+        let tracker = tracker_listener.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut tracker = tracker.lock().await;
+            let changed = tracker.content.as_ref()
+                .map(|current| current.view != content.view || current.video_id != content.video_id || current.messages != content.messages)
+                .unwrap_or(true);
+            if changed {
+                tracker.content_since = Some(Instant::now());
+            }
+            tracker.content = Some(content);
+        });
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        let mut last_heartbeat: Option<Instant> = None;
+        let mut was_open: Option<bool> = None;
+        loop {
+            interval.tick().await;
+            let status = build_status(&app_handle, &tracker, &display_health, &event_metrics).await;
+
+            if was_open != Some(status.panel_open) {
+                if status.panel_open {
+                    log(&database, "info", "Painel aberto", "Janela do painel público em exibição").await;
+                } else if was_open.is_some() {
+                    log(&database, "warning", "Painel fechado", "Janela do painel público não está aberta").await;
+                    // Conteúdo antigo não vale para a próxima abertura
+                    *tracker.lock().await = PanelTracker::default();
+                }
+                was_open = Some(status.panel_open);
+            }
+
+            if last_heartbeat.map_or(true, |at| at.elapsed() >= HEARTBEAT_INTERVAL) {
+                let level = if status.panel_open && !status.plc_data_stale && !status.stalled { "info" } else { "warning" };
+                let details = if status.panel_open { status.summary() } else { "painel fechado".to_string() };
+                log(&database, level, "Heartbeat do painel", &details).await;
+                last_heartbeat = Some(Instant::now());
+            }
+
+            let _ = app_handle.emit("panel-status", &status);
+        }
+    });
+}
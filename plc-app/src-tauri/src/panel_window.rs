@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+
+// Uma única janela do painel público. Abrir de novo só traz a existente para
+// frente; abrir/fechar/alternar passam por `op_lock` para dois cliques rápidos
+// não criarem a janela duas vezes. O estado vem dos eventos da própria janela
+// (foco, fechamento pelo X do SO) e é publicado em "panel-window".
+
+pub const PANEL_LABEL: &str = "panel";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PanelWindowInfo {
+    pub open: bool,
+    pub focused: bool,
+    pub opened_at: Option<String>,   // RFC 3339
+    pub closed_at: Option<String>,
+    pub open_count: u64,             // Aberturas desde o início do app
+}
+
+#[derive(Default)]
+pub struct PanelWindowManager {
+    // std::sync: atualizado nos callbacks síncronos de evento da janela
+    info: std::sync::Mutex<PanelWindowInfo>,
+    op_lock: tokio::sync::Mutex<()>,
+}
+
+impl PanelWindowManager {
+    pub fn info(&self) -> PanelWindowInfo {
+        self.info.lock().map(|info| info.clone()).unwrap_or_default()
+    }
+
+    fn update(&self, app_handle: &AppHandle, change: impl FnOnce(&mut PanelWindowInfo)) {
+        let snapshot = match self.info.lock() {
+            Ok(mut info) => {
+                change(&mut info);
+                info.clone()
+            }
+            Err(_) => return,
+        };
+        let _ = app_handle.emit("panel-window", &snapshot);
+    }
+
+    /// Abre o painel ou traz para frente o que já está aberto
+    pub async fn open(self: &Arc<Self>, app_handle: &AppHandle) -> Result<String, String> {
+        let _guard = self.op_lock.lock().await;
+        if let Some(window) = app_handle.get_webview_window(PANEL_LABEL) {
+            bring_to_front(&window)?;
+            self.update(app_handle, |info| {
+                info.open = true;
+                info.focused = true;
+            });
+            return Ok("Painel já aberto - trazido para frente".to_string());
+        }
+
+        let window = WebviewWindowBuilder::new(app_handle, PANEL_LABEL, WebviewUrl::App("src/panel.html".into()))
+            .title("Painel da Eclusa")
+            .inner_size(1920.0, 1080.0)
+            .resizable(true)
+            .decorations(true)
+            .build()
+            .map_err(|e| format!("Erro ao criar janela do painel: {}", e))?;
+
+        let manager = self.clone();
+        let manager_handle = app_handle.clone();
+        window.on_window_event(move |event| {
+            match event {
+                WindowEvent::Focused(focused) => {
+                    let focused = *focused;
+                    manager.update(&manager_handle, |info| info.focused = focused);
+                }
+                WindowEvent::Destroyed => {
+                    println!("🪟 Janela do painel fechada");
+                    manager.update(&manager_handle, |info| {
+                        info.open = false;
+                        info.focused = false;
+                        info.closed_at = Some(chrono::Local::now().to_rfc3339());
+                    });
+                }
+                _ => {}
+            }
+        });
+
+        println!("🪟 Janela do painel aberta");
+        self.update(app_handle, |info| {
+            info.open = true;
+            info.focused = true;
+            info.opened_at = Some(chrono::Local::now().to_rfc3339());
+            info.open_count += 1;
+        });
+        Ok("Painel aberto".to_string())
+    }
+
+    /// Fecha o painel; já fechado não é erro
+    pub async fn close(&self, app_handle: &AppHandle) -> Result<String, String> {
+        let _guard = self.op_lock.lock().await;
+        match app_handle.get_webview_window(PANEL_LABEL) {
+            Some(window) => {
+                window.close().map_err(|e| format!("Erro ao fechar painel: {}", e))?;
+                Ok("Painel fechado".to_string())
+            }
+            None => {
+                // Estado pode ter ficado para trás (ex: webview caiu sem evento)
+                if self.info().open {
+                    self.update(app_handle, |info| {
+                        info.open = false;
+                        info.focused = false;
+                    });
+                }
+                Ok("Painel já estava fechado".to_string())
+            }
+        }
+    }
+
+    /// Abre se fechado, fecha se aberto
+    pub async fn toggle(self: &Arc<Self>, app_handle: &AppHandle) -> Result<String, String> {
+        if app_handle.get_webview_window(PANEL_LABEL).is_some() {
+            self.close(app_handle).await
+        } else {
+            self.open(app_handle).await
+        }
+    }
+}
+
+fn bring_to_front(window: &WebviewWindow) -> Result<(), String> {
+    if window.is_minimized().unwrap_or(false) {
+        window.unminimize().map_err(|e| format!("Erro ao restaurar painel: {}", e))?;
+    }
+    window.show().map_err(|e| format!("Erro ao mostrar painel: {}", e))?;
+    window.set_focus().map_err(|e| format!("Erro ao focar painel: {}", e))
+}
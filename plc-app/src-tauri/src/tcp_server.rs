@@ -1,14 +1,16 @@
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio::time::{sleep, timeout};
 use serde::{Deserialize, Serialize};
-use crate::database::Database;
+use crate::database::{Database, DataMapping, ProtocolConfig};
 use std::sync::Weak;
+use plc_core::connection::{read_or_write, FrameEvent, FrameReader, SocketEvent};
+use plc_core::ByteOrder;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlcData {
@@ -16,6 +18,126 @@ pub struct PlcData {
     pub variables: HashMap<String, f64>,
 }
 
+/// Formato dos pacotes de uma origem. `Auto` mantém a detecção por pacote (legado).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlcProtocol {
+    Auto,
+    Json,
+    Binary,
+}
+
+impl PlcProtocol {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(PlcProtocol::Auto),
+            "json" => Some(PlcProtocol::Json),
+            "binary" => Some(PlcProtocol::Binary),
+            _ => None,
+        }
+    }
+}
+
+// Origem com protocolo "auto" pode declarar o formato no primeiro pacote
+const PROTOCOL_DECLARATION_PREFIX: &[u8] = b"PROTO:";
+
+// ============================================================================
+// ESCRITA NO PLC (WRITE-BACK) PELA MESMA CONEXÃO
+// ============================================================================
+//
+// Frame "WRTE" / confirmação "WACK" no formato de plc_core::write_protocol
+// (o mesmo do plc-hmi). Aqui o offset é sempre de uma WORD (Word[i] → i * 2)
+// e o valor tem 2 bytes (ou 1 byte 0/1 para bit).
+
+const MAX_WRITE_WORDS: u16 = 128; // Mesmo limite de WORDs lidas por pacote
+const WRITE_ACK_TIMEOUT: Duration = Duration::from_secs(3);
+const WRITE_CHANNEL_CAPACITY: usize = 32;
+
+/// WORD (e bit) de destino de uma escrita
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteTarget {
+    pub word_index: u16,
+    pub bit: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlcWriteResult {
+    pub plc_ip: String,
+    pub variable_path: String,
+    pub value: String,
+    pub raw_value: u16,          // Valor gravado na WORD (ou 0/1 para bit)
+    pub seq: u16,
+    pub target: WriteTarget,
+    pub status: String,          // "confirmed", "rejected", "timeout"
+    pub error_code: Option<u8>,
+    pub elapsed_ms: u64,
+}
+
+/// "Word[5]", "Word[5].3" ou nome de um mapeamento de dados (bit ou analógico)
+fn resolve_write_target<'a>(variable_path: &str, mappings: &'a [DataMapping]) -> Result<(WriteTarget, Option<&'a DataMapping>), String> {
+    let path = variable_path.trim();
+    if let Some(mapping) = mappings.iter().find(|m| m.enabled && m.name == path) {
+        let word_index = u16::try_from(mapping.word_index).ok().filter(|i| *i < MAX_WRITE_WORDS)
+            .ok_or_else(|| format!("Mapeamento '{}' aponta para WORD inválida: {}", path, mapping.word_index))?;
+        let bit = (mapping.mapping_type == "bit").then_some((mapping.bit_index & 0x0F) as u8);
+        return Ok((WriteTarget { word_index, bit }, Some(mapping)));
+    }
+
+    let invalid = || format!("Variável inválida: '{}' (use Word[i], Word[i].bit ou o nome de um mapeamento)", variable_path);
+    let rest = path.strip_prefix("Word[").ok_or_else(invalid)?;
+    let (index, bit) = rest.split_once(']').ok_or_else(invalid)?;
+    let word_index: u16 = index.parse().map_err(|_| invalid())?;
+    if word_index >= MAX_WRITE_WORDS {
+        return Err(format!("WORD {} fora da faixa (0-{})", word_index, MAX_WRITE_WORDS - 1));
+    }
+    let bit = match bit.strip_prefix('.') {
+        Some(bit) => Some(bit.parse::<u8>().ok().filter(|b| *b < 16).ok_or_else(|| format!("Bit inválido: {} (0-15)", bit))?),
+        None if bit.is_empty() => None,
+        None => return Err(invalid()),
+    };
+    Ok((WriteTarget { word_index, bit }, None))
+}
+
+/// Valor bruto da escrita: bit → 0/1; WORD → 0..65535 (ou -32768..32767);
+/// mapeamento analógico → inverso de `word * scale + offset`
+fn encode_write_value(target: &WriteTarget, mapping: Option<&DataMapping>, value: &str) -> Result<u16, String> {
+    let value = value.trim();
+    if target.bit.is_some() {
+        return match value.to_ascii_uppercase().as_str() {
+            "1" | "TRUE" | "ON" => Ok(1),
+            "0" | "FALSE" | "OFF" => Ok(0),
+            _ => Err(format!("Valor de bit inválido: '{}' (use 1/0 ou TRUE/FALSE)", value)),
+        };
+    }
+    let number: f64 = value.parse().map_err(|_| format!("Valor numérico inválido: '{}'", value))?;
+    let (raw, signed) = match mapping {
+        Some(m) if m.scale != 0.0 => (((number - m.offset) / m.scale).round(), m.signed),
+        Some(m) => return Err(format!("Mapeamento '{}' com escala 0 não aceita escrita", m.name)),
+        None => (number, number < 0.0),
+    };
+    if !raw.is_finite() || raw.fract() != 0.0 {
+        return Err(format!("Valor '{}' não é inteiro para a WORD", value));
+    }
+    if signed {
+        if raw < i16::MIN as f64 || raw > i16::MAX as f64 {
+            return Err(format!("Valor '{}' fora da faixa de INT", value));
+        }
+        Ok(raw as i16 as u16)
+    } else {
+        if raw < 0.0 || raw > u16::MAX as f64 {
+            return Err(format!("Valor '{}' fora da faixa de WORD", value));
+        }
+        Ok(raw as u16)
+    }
+}
+
+fn encode_write_frame(seq: u16, target: &WriteTarget, raw_value: u16) -> Vec<u8> {
+    let data: Vec<u8> = match target.bit {
+        Some(_) => vec![raw_value as u8],
+        None => raw_value.to_be_bytes().to_vec(),
+    };
+    plc_core::write_protocol::encode_write_frame(seq, target.word_index as u32 * 2, target.bit, &data)
+}
+
 #[derive(Clone)]
 pub struct TcpServer {
     port: u16,
@@ -24,6 +146,12 @@ pub struct TcpServer {
     connection_count: Arc<AtomicU64>,
     last_data_time: Arc<AtomicU64>,
     database: Option<Weak<Database>>,
+    data_mappings: Arc<RwLock<Vec<DataMapping>>>,
+    protocol_configs: Arc<RwLock<HashMap<String, ProtocolConfig>>>,
+    // Escrita no PLC: frames a enviar por conexão (IP) + confirmações pendentes
+    write_channels: Arc<Mutex<HashMap<String, mpsc::Sender<Vec<u8>>>>>,
+    pending_writes: Arc<Mutex<HashMap<(String, u16), oneshot::Sender<u8>>>>,
+    next_write_seq: Arc<AtomicU16>,
 }
 
 impl TcpServer {
@@ -36,6 +164,11 @@ impl TcpServer {
             connection_count: Arc::new(AtomicU64::new(0)),
             last_data_time: Arc::new(AtomicU64::new(0)),
             database: None,
+            data_mappings: Arc::new(RwLock::new(Vec::new())),
+            protocol_configs: Arc::new(RwLock::new(HashMap::new())),
+            write_channels: Arc::new(Mutex::new(HashMap::new())),
+            pending_writes: Arc::new(Mutex::new(HashMap::new())),
+            next_write_seq: Arc::new(AtomicU16::new(1)),
         }
     }
     
@@ -43,6 +176,34 @@ impl TcpServer {
         self.database = Some(database);
     }
     
+    /// Substitui o mapeamento de dados usado para gerar as variáveis derivadas (só os habilitados)
+    pub fn set_data_mappings(&self, mappings: Vec<DataMapping>) {
+        let enabled: Vec<DataMapping> = mappings.into_iter().filter(|m| m.enabled).collect();
+        println!("🗺️ Mapeamento de dados do PLC: {} variáveis", enabled.len());
+        if let Ok(mut current) = self.data_mappings.write() {
+            *current = enabled;
+        }
+    }
+    
+    /// Substitui a configuração de protocolo por origem (IP ou "*")
+    pub fn set_protocol_configs(&self, configs: Vec<ProtocolConfig>) {
+        if let Ok(mut current) = self.protocol_configs.write() {
+            *current = configs.into_iter().map(|c| (c.source.clone(), c)).collect();
+        }
+    }
+    
+    /// Protocolo e handshake para uma origem (IP exato > "*" > auto sem handshake)
+    fn protocol_for(&self, peer_ip: &str) -> (PlcProtocol, String) {
+        let configs = match self.protocol_configs.read() {
+            Ok(configs) => configs,
+            Err(_) => return (PlcProtocol::Auto, String::new()),
+        };
+        match configs.get(peer_ip).or_else(|| configs.get("*")) {
+            Some(config) => (PlcProtocol::parse(&config.protocol).unwrap_or(PlcProtocol::Auto), config.handshake.clone()),
+            None => (PlcProtocol::Auto, String::new()),
+        }
+    }
+    
     async fn log_error(&self, category: &str, message: &str, details: &str) {
         if let Some(db_weak) = &self.database {
             if let Some(db) = db_weak.upgrade() {
@@ -91,9 +252,10 @@ impl TcpServer {
                     let tx = self.tx.clone();
                     let last_data_time = self.last_data_time.clone();
                     let server_clone = self.clone();
+                    let peer_ip = addr.ip().to_string();
                     
                     tokio::spawn(async move {
-                        if let Err(e) = handle_connection_robust(socket, tx, last_data_time, conn_id, server_clone).await {
+                        if let Err(e) = handle_connection_robust(socket, tx, last_data_time, conn_id, server_clone, peer_ip).await {
                             eprintln!("❌ Conexão #{} encerrada: {:?}", conn_id, e);
                         } else {
                             println!("✅ Conexão #{} encerrada normalmente", conn_id);
@@ -130,14 +292,103 @@ impl TcpServer {
         }
     }
 
+    /// Segundos desde o último pacote do PLC (None = nenhum dado ainda)
+    pub fn last_data_age_s(&self) -> Option<u64> {
+        let last_data = self.last_data_time.load(Ordering::SeqCst);
+        if last_data == 0 {
+            return None;
+        }
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        Some(now.saturating_sub(last_data))
+    }
+
     pub fn subscribe(&self) -> broadcast::Receiver<PlcData> {
         self.tx.subscribe()
     }
 
+    /// Publica dados vindos de outra origem (ex: tags do plc-hmi via IPC) no mesmo canal do PLC
+    pub fn publish(&self, data: PlcData) {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        self.last_data_time.store(now, Ordering::SeqCst);
+        let _ = self.tx.send(data);
+    }
+
+    /// IPs com conexão ativa que aceitam escrita
+    pub fn writable_plcs(&self) -> Vec<String> {
+        self.write_channels.lock().map(|c| c.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Envia uma escrita ao PLC e aguarda a confirmação (WACK). Rejeição e
+    /// timeout voltam como Ok com o status, para o chamador emitir o evento.
+    pub async fn write_variable(&self, plc_ip: &str, variable_path: &str, value: &str) -> Result<PlcWriteResult, String> {
+        let mappings = self.data_mappings.read().map(|m| m.clone()).unwrap_or_default();
+        let (target, mapping) = resolve_write_target(variable_path, &mappings)?;
+        let raw_value = encode_write_value(&target, mapping, value)?;
+        let sender = self.write_channels.lock().ok()
+            .and_then(|channels| channels.get(plc_ip).cloned())
+            .ok_or_else(|| format!("PLC {} não está conectado", plc_ip))?;
+
+        let seq = self.next_write_seq.fetch_add(1, Ordering::SeqCst);
+        let key = (plc_ip.to_string(), seq);
+        let (reply_tx, reply_rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending_writes.lock() {
+            pending.insert(key.clone(), reply_tx);
+        }
+        let started = Instant::now();
+        if sender.send(encode_write_frame(seq, &target, raw_value)).await.is_err() {
+            if let Ok(mut pending) = self.pending_writes.lock() {
+                pending.remove(&key);
+            }
+            return Err(format!("PLC {} não está conectado", plc_ip));
+        }
+        println!("✍️ Escrita #{} para {}: {} = {} (WORD {}, bit {:?})", seq, plc_ip, variable_path, value, target.word_index, target.bit);
+
+        let (status, error_code) = match timeout(WRITE_ACK_TIMEOUT, reply_rx).await {
+            Ok(Ok(0)) => ("confirmed", None),
+            Ok(Ok(code)) => ("rejected", Some(code)),
+            _ => {
+                if let Ok(mut pending) = self.pending_writes.lock() {
+                    pending.remove(&key);
+                }
+                ("timeout", None)
+            }
+        };
+        if status != "confirmed" {
+            self.log_warning("plc", &format!("Escrita em {} não confirmada ({})", variable_path, status),
+                &format!("PLC: {} - seq #{} - código {:?}", plc_ip, seq, error_code)).await;
+        }
+        Ok(PlcWriteResult {
+            plc_ip: plc_ip.to_string(),
+            variable_path: variable_path.to_string(),
+            value: value.to_string(),
+            raw_value,
+            seq,
+            target,
+            status: status.to_string(),
+            error_code,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Só com escrita aguardando confirmação o início do pacote pode ser um WACK
+    fn has_pending_write(&self, plc_ip: &str) -> bool {
+        self.pending_writes.lock().is_ok_and(|pending| pending.keys().any(|(ip, _)| ip == plc_ip))
+    }
+
+    /// Entrega a confirmação ao `write_variable` que aguarda este seq
+    fn resolve_write_ack(&self, plc_ip: &str, seq: u16, status: u8) {
+        let reply = self.pending_writes.lock().ok().and_then(|mut p| p.remove(&(plc_ip.to_string(), seq)));
+        match reply {
+            Some(reply) => { let _ = reply.send(status); }
+            None => println!("⚠️ Confirmação de escrita #{} de {} sem pedido pendente", seq, plc_ip),
+        }
+    }
+
     pub async fn connect_to_plc(&self, plc_ip: &str, plc_port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let tx = self.tx.clone();
         let last_data_time = self.last_data_time.clone();
         let plc_address = format!("{}:{}", plc_ip, plc_port);
+        let peer_ip = plc_ip.to_string();
         let server_clone = self.clone();
         
         println!("🔄 Iniciando conexão robusta com PLC em {}", plc_address);
@@ -153,7 +404,7 @@ impl TcpServer {
                         backoff_delay = Duration::from_secs(2);
                         println!("✅ Conectado ao PLC {}", plc_address);
                         
-                        if let Err(e) = handle_connection_robust(socket, tx.clone(), last_data_time.clone(), 0, server_clone.clone()).await {
+                        if let Err(e) = handle_connection_robust(socket, tx.clone(), last_data_time.clone(), 0, server_clone.clone(), peer_ip.clone()).await {
                             eprintln!("❌ Erro na comunicação com PLC: {:?}", e);
                             server_clone.log_error("plc", "Erro na comunicação com PLC", &format!("{:?}", e)).await;
                         }
@@ -198,6 +449,7 @@ async fn handle_connection_robust(
     last_data_time: Arc<AtomicU64>,
     conn_id: u64,
     server: TcpServer,
+    peer_ip: String,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Configure socket options
     socket.set_nodelay(true)?;
@@ -207,16 +459,42 @@ async fn handle_connection_robust(
     let mut packets_processed = 0u64;
     let connection_start = Instant::now();
     
-    println!("🔗 Conexão #{} estabelecida - configurando keepalive", conn_id);
+    // Protocolo configurado para esta origem (fixo durante a conexão)
+    let (configured_protocol, handshake) = server.protocol_for(&peer_ip);
+    let mut protocol = configured_protocol;
+    let mut awaiting_handshake = !handshake.is_empty();
+    let mut first_payload = true;
+    
+    println!("🔗 Conexão #{} estabelecida ({}) - protocolo {:?}{}", conn_id, peer_ip, protocol,
+        if awaiting_handshake { ", aguardando handshake" } else { "" });
+    
+    // Escritas para este PLC saem por este socket
+    let (write_tx, mut write_rx) = mpsc::channel::<Vec<u8>>(WRITE_CHANNEL_CAPACITY);
+    if let Ok(mut channels) = server.write_channels.lock() {
+        channels.insert(peer_ip.clone(), write_tx.clone());
+    }
+    let _write_guard = WriteChannelGuard { channels: server.write_channels.clone(), peer_ip: peer_ip.clone(), sender: write_tx };
+
+    // Sem estrutura de frame: cada leitura é um pacote (WACK separado na fronteira)
+    let mut reader = FrameReader::new(Vec::with_capacity(buffer.len()), buffer.len(), None);
 
     loop {
-        // Use timeout for reads to detect dead connections
-        match timeout(Duration::from_secs(30), socket.read(&mut buffer)).await {
-            Ok(Ok(0)) => {
+        // Escritas pendentes saem entre as leituras; timeout de leitura detecta conexões mortas (plc-core)
+        let event = match read_or_write(&mut socket, &mut buffer, &mut write_rx, Duration::from_secs(30)).await {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("❌ Erro na conexão #{}: {:?}", conn_id, e);
+                server.log_error("tcp", &format!("Erro na conexão #{}", conn_id), &e.to_string()).await;
+                break;
+            }
+        };
+        match event {
+            SocketEvent::WriteSent(_) => continue,
+            SocketEvent::Closed => {
                 println!("📡 Conexão #{} encerrada pelo peer", conn_id);
                 break;
             }
-            Ok(Ok(n)) => {
+            SocketEvent::Read(n) => {
                 total_bytes_received += n as u64;
                 packets_processed += 1;
                 
@@ -235,8 +513,66 @@ async fn handle_connection_robust(
                         conn_id, packets_processed, total_bytes_received, elapsed, rate);
                 }
                 
+                if let Err(reason) = reader.push(&buffer[..n]) {
+                    eprintln!("⚠️ Conexão #{}: {}", conn_id, reason);
+                    continue;
+                }
+                
+                // Confirmações de escrita (WACK) chegam antes dos dados no mesmo pacote;
+                // sem escrita pendente, "WACK..." é dado como outro qualquer
+                let mut acked = false;
+                let mut frame = Vec::new();
+                while let Some(event) = reader.next_event(None, server.has_pending_write(&peer_ip)) {
+                    match event {
+                        FrameEvent::WriteAck { seq, status } => {
+                            server.resolve_write_ack(&peer_ip, seq, status);
+                            acked = true;
+                        }
+                        FrameEvent::Frame(data) => frame = data,
+                        // Sem estrutura nem backfill nesta conexão
+                        FrameEvent::Backfill(_) | FrameEvent::Invalid(_) => {}
+                    }
+                }
+                let mut payload = if acked { trim_line_start(&frame) } else { &frame[..] };
+                if payload.is_empty() {
+                    continue;
+                }
+                
+                // Handshake: a origem deve começar a conexão com a string configurada
+                if awaiting_handshake {
+                    if !payload.starts_with(handshake.as_bytes()) {
+                        eprintln!("❌ Conexão #{} ({}) sem o handshake esperado - encerrando", conn_id, peer_ip);
+                        server.log_warning("tcp", &format!("Handshake inválido de {}", peer_ip), &format!("Conexão #{} encerrada", conn_id)).await;
+                        break;
+                    }
+                    awaiting_handshake = false;
+                    payload = trim_line_start(&payload[handshake.len()..]);
+                    timeout(Duration::from_secs(5), socket.write_all(b"HANDSHAKE:OK\r\n")).await??;
+                    println!("🤝 Conexão #{} ({}) handshake aceito", conn_id, peer_ip);
+                }
+                
+                // Negociação: com protocolo "auto", o primeiro pacote pode declarar PROTO:JSON / PROTO:BINARY
+                if first_payload && !payload.is_empty() {
+                    first_payload = false;
+                    if let Some((declared, rest)) = parse_protocol_declaration(payload) {
+                        if protocol == PlcProtocol::Auto {
+                            protocol = declared;
+                            println!("🤝 Conexão #{} ({}) negociou protocolo {:?}", conn_id, peer_ip, protocol);
+                        } else if declared != protocol {
+                            server.log_warning("tcp", &format!("{} declarou {:?}, mas está configurado como {:?}", peer_ip, declared, protocol), "Mantido o protocolo configurado").await;
+                        }
+                        timeout(Duration::from_secs(5), socket.write_all(format!("PROTO:{:?}\r\n", protocol).to_uppercase().as_bytes())).await??;
+                        payload = rest;
+                    }
+                }
+                
+                if payload.is_empty() {
+                    continue;
+                }
+                
                 // Process data with error handling
-                match process_plc_data(&buffer[..n], &tx).await {
+                let mappings = server.data_mappings.read().map(|m| m.clone()).unwrap_or_default();
+                match process_plc_data(payload, &tx, &mappings, protocol).await {
                     Ok(_) => {
                         // Send robust ACK with timestamp
                         let ack_response = format!("ACK:{}\r\n", now);
@@ -253,12 +589,7 @@ async fn handle_connection_robust(
                     }
                 }
             }
-            Ok(Err(e)) => {
-                eprintln!("❌ Erro de leitura na conexão #{}: {:?}", conn_id, e);
-                server.log_error("tcp", &format!("Erro de leitura na conexão #{}", conn_id), &format!("{:?}", e)).await;
-                break;
-            }
-            Err(_) => {
+            SocketEvent::ReadTimeout => {
                 // Send keepalive ping (silent, no log spam)
                 if let Err(_) = timeout(Duration::from_secs(5), socket.write_all(b"PING\r\n")).await {
                     println!("💔 Conexão #{} não responde ao PING após 30s - encerrando", conn_id);
@@ -276,16 +607,41 @@ async fn handle_connection_robust(
     Ok(())
 }
 
+/// Remove o canal de escrita ao encerrar a conexão (se uma reconexão não o substituiu)
+struct WriteChannelGuard {
+    channels: Arc<Mutex<HashMap<String, mpsc::Sender<Vec<u8>>>>>,
+    peer_ip: String,
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl Drop for WriteChannelGuard {
+    fn drop(&mut self) {
+        if let Ok(mut channels) = self.channels.lock() {
+            if channels.get(&self.peer_ip).is_some_and(|c| c.same_channel(&self.sender)) {
+                channels.remove(&self.peer_ip);
+            }
+        }
+    }
+}
+
 async fn process_plc_data(
     data: &[u8], 
-    tx: &broadcast::Sender<PlcData>
+    tx: &broadcast::Sender<PlcData>,
+    mappings: &[DataMapping],
+    protocol: PlcProtocol
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Try JSON first
-    let data_str = String::from_utf8_lossy(data);
-    
-    if let Ok(plc_data) = serde_json::from_str::<PlcData>(&data_str) {
-        tx.send(plc_data)?;
-        return Ok(());
+    // JSON: obrigatório no modo "json", tentativa no modo "auto", ignorado no modo "binary"
+    if protocol != PlcProtocol::Binary {
+        let data_str = String::from_utf8_lossy(data);
+        match serde_json::from_str::<PlcData>(&data_str) {
+            Ok(mut plc_data) => {
+                apply_data_mappings(&mut plc_data.variables, mappings);
+                tx.send(plc_data)?;
+                return Ok(());
+            }
+            Err(e) if protocol == PlcProtocol::Json => return Err(format!("JSON inválido: {}", e).into()),
+            Err(_) => {}
+        }
     }
     
     // Parse binary data as Words with validation
@@ -295,17 +651,10 @@ async fn process_plc_data(
     
     let mut variables = HashMap::new();
     let num_words = data.len() / 2;
-    
-    // Limit to reasonable number of words
-    for i in 0..num_words.min(128) {
-        let byte_index = i * 2;
-        if byte_index + 1 < data.len() {
-            let word_value = u16::from_be_bytes([
-                data[byte_index],
-                data[byte_index + 1]
-            ]);
-            variables.insert(format!("Word[{}]", i), word_value as f64);
-        }
+
+    // WORDs big-endian pela mesma leitura do plc-hmi (plc-core), limitadas a 128
+    for (i, word_value) in plc_core::decode_words(data, ByteOrder::Big, MAX_WRITE_WORDS as usize).into_iter().enumerate() {
+        variables.insert(format!("Word[{}]", i), word_value as f64);
     }
     
     // Add metadata
@@ -313,12 +662,8 @@ async fn process_plc_data(
     variables.insert("total_words".to_string(), num_words as f64);
     variables.insert("connection_quality".to_string(), 100.0);
     
-    // Add derived variables for common PLC patterns
-    if let Some(&status_word) = variables.get("Word[0]") {
-        variables.insert("sistema_ativo".to_string(), if (status_word as u16) & 0x0001 != 0 { 1.0 } else { 0.0 });
-        variables.insert("emergencia".to_string(), if (status_word as u16) & 0x0002 != 0 { 1.0 } else { 0.0 });
-        variables.insert("manutencao".to_string(), if (status_word as u16) & 0x0004 != 0 { 1.0 } else { 0.0 });
-    }
+    // Variáveis derivadas configuradas na tabela data_mappings
+    apply_data_mappings(&mut variables, mappings);
     
     let plc_data = PlcData {
         timestamp: chrono::Utc::now().to_rfc3339(),
@@ -328,3 +673,26 @@ async fn process_plc_data(
     tx.send(plc_data)?;
     Ok(())
 }
+
+fn apply_data_mappings(variables: &mut HashMap<String, f64>, mappings: &[DataMapping]) {
+    for mapping in mappings {
+        let word = variables.get(&format!("Word[{}]", mapping.word_index)).copied();
+        if let Some(value) = mapping.evaluate(word) {
+            variables.insert(mapping.name.clone(), value);
+        }
+    }
+}
+
+/// Remove \r/\n que sobram após o handshake
+fn trim_line_start(data: &[u8]) -> &[u8] {
+    let start = data.iter().position(|b| *b != b'\r' && *b != b'\n').unwrap_or(data.len());
+    &data[start..]
+}
+
+/// "PROTO:JSON\r\n..." -> (Json, resto do pacote)
+fn parse_protocol_declaration(data: &[u8]) -> Option<(PlcProtocol, &[u8])> {
+    let rest = data.strip_prefix(PROTOCOL_DECLARATION_PREFIX)?;
+    let end = rest.iter().position(|b| *b == b'\r' || *b == b'\n').unwrap_or(rest.len());
+    let protocol = std::str::from_utf8(&rest[..end]).ok().and_then(PlcProtocol::parse)?;
+    Some((protocol, trim_line_start(&rest[end..])))
+}
\ No newline at end of file
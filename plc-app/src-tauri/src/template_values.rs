@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+use crate::database::Database;
+
+// Placeholders calculados no backend para os templates das mensagens, além de
+// {Word[N]} e {Timer:nome}. Vão prontos (texto formatado) em cada plc-data:
+//   {time} / {date}                 - hora e data locais ("14:30", "16/10/2026")
+//   {nome}                          - variável de data_mappings (ex: {speed_kmh} escalada da WORD)
+//   {phase_number} / {phase_title}  - fase atual lida da WORD configurada em `phase_word_index`
+// Placeholder sem valor (ex: fase sem título) fica de fora: o painel mantém o texto original.
+
+pub const KEY_PHASE_WORD_INDEX: &str = "phase_word_index";
+
+/// Número com no máximo 2 casas, sem zeros à direita (85, 12.5, 3.14)
+fn format_number(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub async fn load_phase_word_index(db: &Database) -> Result<Option<usize>, sqlx::Error> {
+    Ok(db.get_display_config(KEY_PHASE_WORD_INDEX).await?.and_then(|v| v.parse().ok()))
+}
+
+pub async fn compute(db: &Database, variables: &HashMap<String, f64>) -> HashMap<String, String> {
+    let now = chrono::Local::now();
+    let mut values = HashMap::new();
+    values.insert("time".to_string(), now.format("%H:%M").to_string());
+    values.insert("date".to_string(), now.format("%d/%m/%Y").to_string());
+
+    // Variáveis derivadas (data_mappings) já calculadas no servidor TCP
+    for (name, value) in variables.iter().filter(|(name, _)| !name.starts_with("Word[")) {
+        values.insert(name.clone(), format_number(*value));
+    }
+
+    let phase_word = load_phase_word_index(db).await.ok().flatten()
+        .and_then(|index| variables.get(&format!("Word[{}]", index)).copied());
+    if let Some(word) = phase_word {
+        let phase_number = word as i32;
+        values.insert("phase_number".to_string(), phase_number.to_string());
+        if let Ok(Some(phase)) = db.get_phase(phase_number).await {
+            if phase.enabled {
+                values.insert("phase_title".to_string(), phase.title);
+            }
+        }
+    }
+    values
+}
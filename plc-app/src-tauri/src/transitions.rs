@@ -0,0 +1,77 @@
+use serde::Serialize;
+use crate::database::TransitionConfig;
+
+// Transições do painel configuradas no backend. Escopo "playlist": troca entre
+// vídeos e entre mensagens/vídeos. Escopo "message": por classe de prioridade das
+// mensagens de bits; vale a configuração de maior `min_priority` que a mensagem
+// alcança (ex: alarmes >= 80 entram em "cut", o resto com "fade").
+// O efeito resolvido vai em cada evento plc-data; o painel só aplica.
+
+pub const SCOPE_PLAYLIST: &str = "playlist";
+pub const SCOPE_MESSAGE: &str = "message";
+pub const EFFECT_CUT: &str = "cut";
+const EFFECTS: &[&str] = &["fade", EFFECT_CUT, "slide"];
+const MAX_DURATION_MS: i32 = 10_000;
+
+/// Efeito pronto para o painel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transition {
+    pub effect: String,     // "fade", "cut" ou "slide"
+    pub duration_ms: i32,   // 0 em "cut"
+}
+
+impl Default for Transition {
+    /// Sem configuração: o fade de 0,5 s que o painel sempre usou
+    fn default() -> Self {
+        Transition { effect: "fade".to_string(), duration_ms: 500 }
+    }
+}
+
+impl From<&TransitionConfig> for Transition {
+    fn from(config: &TransitionConfig) -> Self {
+        let duration_ms = if config.effect == EFFECT_CUT { 0 } else { config.duration_ms };
+        Transition { effect: config.effect.clone(), duration_ms }
+    }
+}
+
+/// Transições resolvidas para o estado atual do painel
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PanelTransitions {
+    pub playlist: Transition,  // Entre vídeos e na troca mensagens <-> vídeos
+    pub message: Transition,   // Classe da mensagem ativa de maior prioridade
+}
+
+pub fn validate(config: &TransitionConfig) -> Result<(), String> {
+    if config.name.trim().is_empty() {
+        return Err("Nome da transição é obrigatório".to_string());
+    }
+    if !matches!(config.scope.as_str(), SCOPE_PLAYLIST | SCOPE_MESSAGE) {
+        return Err(format!("Escopo inválido: {} (use playlist ou message)", config.scope));
+    }
+    if !EFFECTS.contains(&config.effect.as_str()) {
+        return Err(format!("Efeito inválido: {} (use fade, cut ou slide)", config.effect));
+    }
+    if config.effect != EFFECT_CUT && !(1..=MAX_DURATION_MS).contains(&config.duration_ms) {
+        return Err(format!("Duração inválida: {} ms (1-{})", config.duration_ms, MAX_DURATION_MS));
+    }
+    Ok(())
+}
+
+/// Transição da classe de prioridade que a mensagem alcança
+pub fn for_priority(configs: &[TransitionConfig], priority: i32) -> Transition {
+    configs.iter()
+        .filter(|c| c.enabled && c.scope == SCOPE_MESSAGE && c.min_priority <= priority)
+        .max_by_key(|c| c.min_priority)
+        .map(Transition::from)
+        .unwrap_or_default()
+}
+
+/// `top_priority`: prioridade da mensagem ativa mais importante (None = nenhuma ativa)
+pub fn resolve(configs: &[TransitionConfig], top_priority: Option<i32>) -> PanelTransitions {
+    let playlist = configs.iter()
+        .find(|c| c.enabled && c.scope == SCOPE_PLAYLIST)
+        .map(Transition::from)
+        .unwrap_or_default();
+    let message = for_priority(configs, top_priority.unwrap_or(0));
+    PanelTransitions { playlist, message }
+}
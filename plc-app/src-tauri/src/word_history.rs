@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use crate::database::Database;
+use crate::tcp_server::PlcData;
+
+// Histórico em memória das words recebidas nos últimos `minutes` minutos, para
+// responder "quando o Word[2].3 mudou pela última vez" e listar oscilações
+// recentes (semáforo piscando) sem abrir as gravações em disco. Guarda só os
+// frames diferentes do anterior: entre dois frames guardados o valor é o mesmo.
+
+pub const KEY_WORD_HISTORY_MINUTES: &str = "word_history_minutes";
+const DEFAULT_MINUTES: u32 = 30;
+pub const MAX_MINUTES: u32 = 240;
+const MAX_FRAMES: usize = 50_000;
+const MAX_CHANGES: usize = 500;
+
+struct Frame {
+    at: DateTime<Utc>,
+    words: Vec<f64>,
+}
+
+pub struct WordHistory {
+    frames: VecDeque<Frame>,
+    minutes: u32,
+    started_at: DateTime<Utc>,               // Início do histórico (app aberto ou servidor iniciado)
+    last_received: Option<DateTime<Utc>>,
+}
+
+impl Default for WordHistory {
+    fn default() -> Self {
+        WordHistory { frames: VecDeque::new(), minutes: DEFAULT_MINUTES, started_at: Utc::now(), last_received: None }
+    }
+}
+
+pub type WordHistoryState = Arc<Mutex<WordHistory>>;
+
+/// "Word[2].3" (bit) ou "Word[2]" (word inteira)
+#[derive(Debug, Clone, Copy)]
+pub struct WordRef {
+    pub word_index: usize,
+    pub bit_index: Option<u8>,
+}
+
+impl WordRef {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if let Some((word_index, bit_index)) = crate::bit_condition::parse_bit_ref(text) {
+            return Ok(WordRef { word_index, bit_index: Some(bit_index) });
+        }
+        text.strip_prefix("Word[")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|index| index.parse().ok())
+            .map(|word_index| WordRef { word_index, bit_index: None })
+            .ok_or_else(|| format!("Referência inválida '{}' (use Word[N] ou Word[N].B)", text))
+    }
+
+    fn value(&self, words: &[f64]) -> Option<f64> {
+        let word = *words.get(self.word_index)?;
+        Some(match self.bit_index {
+            Some(bit) => (((word as u16) >> bit) & 1) as f64,
+            None => word,
+        })
+    }
+
+    fn label(&self) -> String {
+        match self.bit_index {
+            Some(bit) => format!("Word[{}].{}", self.word_index, bit),
+            None => format!("Word[{}]", self.word_index),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WordChange {
+    pub reference: String,
+    pub changed_at: String,      // RFC 3339
+    pub seconds_ago: f64,
+    pub from_value: Option<f64>, // None = word ainda não existia no frame anterior
+    pub to_value: Option<f64>,
+}
+
+/// Resposta de "quando mudou pela última vez"
+#[derive(Debug, Clone, Serialize)]
+pub struct LastWordChange {
+    pub reference: String,
+    pub current_value: Option<f64>,
+    pub last_change: Option<WordChange>, // None = sem mudança dentro do histórico
+    pub history_start: String,           // Mudanças antes disso não são conhecidas
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WordHistoryInfo {
+    pub minutes: u32,
+    pub frames: usize,
+    pub oldest: Option<String>,
+    pub newest: Option<String>,
+    pub last_received: Option<String>,
+}
+
+impl WordHistory {
+    pub fn set_minutes(&mut self, minutes: u32) {
+        self.minutes = minutes.clamp(1, MAX_MINUTES);
+        self.prune(Utc::now());
+    }
+
+    pub fn push(&mut self, at: DateTime<Utc>, words: Vec<f64>) {
+        self.last_received = Some(at);
+        if self.frames.back().is_some_and(|last| last.words == words) {
+            return;
+        }
+        self.frames.push_back(Frame { at, words });
+        self.prune(at);
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - ChronoDuration::minutes(self.minutes as i64);
+        // O frame mais antigo dentro da janela precisa do anterior como base do valor
+        while self.frames.len() > 1 && self.frames[1].at < cutoff {
+            self.frames.pop_front();
+        }
+        while self.frames.len() > MAX_FRAMES {
+            self.frames.pop_front();
+        }
+    }
+
+    fn history_start(&self) -> DateTime<Utc> {
+        let window_start = Utc::now() - ChronoDuration::minutes(self.minutes as i64);
+        let oldest = self.frames.front().map(|f| f.at).unwrap_or(self.started_at);
+        oldest.max(window_start).max(self.started_at)
+    }
+
+    /// Transições do valor referenciado, da mais recente para a mais antiga
+    pub fn changes(&self, reference: WordRef, since: Option<DateTime<Utc>>, limit: usize) -> Vec<WordChange> {
+        let now = Utc::now();
+        let since = since.unwrap_or_else(|| self.history_start());
+        let mut changes: Vec<WordChange> = self.frames.iter().zip(self.frames.iter().skip(1))
+            .filter(|(_, current)| current.at >= since)
+            .filter_map(|(previous, current)| {
+                let from_value = reference.value(&previous.words);
+                let to_value = reference.value(&current.words);
+                (from_value != to_value).then(|| WordChange {
+                    reference: reference.label(),
+                    changed_at: current.at.to_rfc3339(),
+                    seconds_ago: (now - current.at).num_milliseconds() as f64 / 1000.0,
+                    from_value,
+                    to_value,
+                })
+            })
+            .collect();
+        changes.reverse();
+        changes.truncate(limit.min(MAX_CHANGES));
+        changes
+    }
+
+    pub fn last_change(&self, reference: WordRef) -> LastWordChange {
+        LastWordChange {
+            reference: reference.label(),
+            current_value: self.frames.back().and_then(|f| reference.value(&f.words)),
+            last_change: self.changes(reference, None, 1).into_iter().next(),
+            history_start: self.history_start().to_rfc3339(),
+        }
+    }
+
+    pub fn info(&self) -> WordHistoryInfo {
+        WordHistoryInfo {
+            minutes: self.minutes,
+            frames: self.frames.len(),
+            oldest: self.frames.front().map(|f| f.at.to_rfc3339()),
+            newest: self.frames.back().map(|f| f.at.to_rfc3339()),
+            last_received: self.last_received.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+pub async fn load_minutes(db: &Database) -> u32 {
+    db.get_display_config(KEY_WORD_HISTORY_MINUTES).await.ok().flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MINUTES)
+}
+
+/// Alimenta o histórico a partir do broadcast do servidor TCP
+pub fn start_word_history(
+    history: WordHistoryState,
+    database: Arc<tokio::sync::Mutex<Option<Arc<Database>>>>,
+    mut rx: broadcast::Receiver<PlcData>,
+) {
+    tauri::async_runtime::spawn(async move {
+        if let Some(db) = database.lock().await.clone() {
+            let minutes = load_minutes(&db).await;
+            if let Ok(mut history) = history.lock() {
+                history.set_minutes(minutes);
+            }
+        }
+        loop {
+            let data = match rx.recv().await {
+                Ok(data) => data,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let words = crate::data_recorder::extract_words(&data);
+            if words.is_empty() {
+                continue;
+            }
+            if let Ok(mut history) = history.lock() {
+                history.push(Utc::now(), words);
+            }
+        }
+    });
+}
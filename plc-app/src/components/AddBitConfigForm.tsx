@@ -21,6 +21,7 @@ interface AddBitConfigFormProps {
     letterSpacing: number;
     useTemplate: boolean;
     messageTemplate: string;
+    condition: string;
   }) => Promise<void>;
   onCancel: () => void;
   plcData?: { variables: { [key: string]: number } } | null;
@@ -43,7 +44,8 @@ export const AddBitConfigForm: React.FC<AddBitConfigFormProps> = ({ onAdd, onCan
     textShadow: true,
     letterSpacing: 5,
     useTemplate: false,
-    messageTemplate: ''
+    messageTemplate: '',
+    condition: ''
   });
 
   const [isSubmitting, setIsSubmitting] = useState(false);
@@ -172,6 +174,18 @@ export const AddBitConfigForm: React.FC<AddBitConfigFormProps> = ({ onAdd, onCan
           <p className="text-xs text-edp-slate mt-1">💡 Esta mensagem aparecerá no painel LED quando o bit estiver ativo</p>
         </div>
 
+        <div>
+          <label className="block text-xs font-medium text-edp-slate mb-1">Condição combinada (opcional)</label>
+          <input
+            type="text"
+            value={formData.condition}
+            onChange={(e) => setFormData({ ...formData, condition: e.target.value })}
+            className="w-full px-3 py-2 text-sm font-mono border border-edp-neutral-light rounded focus:ring-2 focus:ring-edp-marine focus:border-edp-marine"
+            placeholder="EX: Word[3].2 AND NOT Word[1].0"
+          />
+          <p className="text-xs text-edp-slate mt-1">💡 Vazio = apenas o bit selecionado. Operadores: AND, OR, NOT e parênteses</p>
+        </div>
+
         {/* TOGGLE: Usar Template com Variáveis */}
         <div className="border-2 border-edp-slate rounded-lg p-4 bg-edp-slate bg-opacity-10">
           <label className="flex items-start gap-3 cursor-pointer">
@@ -4,9 +4,11 @@
  * Cabeçalho geral da aplicação com título e ícones de usuário
  */
 
-import React from 'react';
+import React, { useEffect, useState } from 'react';
 import { Bell, User, Settings, LogOut, Monitor } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { PanelWindowInfo } from '../types';
 
 export type PropsHeader = {
   titulo?: string;
@@ -17,6 +19,21 @@ export const Header: React.FC<PropsHeader> = ({
   titulo = 'Dashboard',
   nomeUsuario = 'Usuário'
 }) => {
+  const [painelAberto, setPainelAberto] = useState(false);
+
+  // Estado real da janela do painel (inclusive fechada pelo X do sistema)
+  useEffect(() => {
+    invoke<PanelWindowInfo>('get_panel_window_state')
+      .then((info) => setPainelAberto(info.open))
+      .catch((error) => console.error('Erro ao consultar janela do painel:', error));
+
+    const unlisten = listen<PanelWindowInfo>('panel-window', (event) => setPainelAberto(event.payload.open));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Clique abre o painel ou traz para frente o que já está aberto
   const abrirPainelVisualizacao = async () => {
     try {
       await invoke('open_panel_window');
@@ -26,6 +43,15 @@ export const Header: React.FC<PropsHeader> = ({
     }
   };
 
+  const alternarPainelVisualizacao = async (event: React.MouseEvent) => {
+    event.preventDefault();
+    try {
+      await invoke('toggle_panel_window');
+    } catch (error) {
+      console.error('Erro ao alternar painel:', error);
+    }
+  };
+
   return (
     <header className="bg-edp-neutral-white-wash border-b border-edp-marine border-opacity-20">
       <div className="px-8 h-[76px] flex items-center justify-between">
@@ -41,10 +67,14 @@ export const Header: React.FC<PropsHeader> = ({
           {/* Botão Painel - sempre visível */}
           <button 
             onClick={abrirPainelVisualizacao}
-            className="p-2 text-white bg-edp-cobalt hover:bg-edp-cobalt-100 rounded-lg transition-edp transform hover:scale-110 active:scale-95 shadow-sm hover:shadow"
-            title="Abrir Painel de Visualização"
+            onContextMenu={alternarPainelVisualizacao}
+            className="relative p-2 text-white bg-edp-cobalt hover:bg-edp-cobalt-100 rounded-lg transition-edp transform hover:scale-110 active:scale-95 shadow-sm hover:shadow"
+            title={painelAberto ? 'Painel aberto - clique para trazer para frente, botão direito para fechar' : 'Abrir Painel de Visualização'}
           >
             <Monitor size={20} />
+            {painelAberto && (
+              <span className="absolute -top-1 -right-1 w-2.5 h-2.5 bg-edp-semantic-green rounded-full border border-white"></span>
+            )}
           </button>
 
           {/* Notificações - sempre visível */}
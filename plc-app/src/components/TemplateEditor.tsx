@@ -217,6 +217,7 @@ export const TemplateEditor: React.FC<TemplateEditorProps> = ({
         <ul className="text-xs text-gray-600 space-y-1">
           <li>• Use <code className="px-1 py-0.5 bg-white border rounded">{'{Word[N]}'}</code> para inserir valores do PLC</li>
           <li>• Exemplo: <code className="px-1 py-0.5 bg-white border rounded">Velocidade: {'{Word[5]}'} km/h</code></li>
+          <li>• Calculados: <code className="px-1 py-0.5 bg-white border rounded">{'{time}'}</code>, <code className="px-1 py-0.5 bg-white border rounded">{'{date}'}</code>, <code className="px-1 py-0.5 bg-white border rounded">{'{phase_title}'}</code> e variáveis mapeadas, ex: <code className="px-1 py-0.5 bg-white border rounded">{'{speed_kmh}'}</code></li>
           <li>• Pressione "Nova Linha" para texto em múltiplas linhas</li>
           <li>• Combine texto fixo com variáveis dinâmicas</li>
         </ul>
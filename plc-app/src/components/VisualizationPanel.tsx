@@ -1,23 +1,63 @@
 import React, { useState, useEffect, useMemo, useRef } from 'react';
 import { listen } from '@tauri-apps/api/event';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke, convertFileSrc } from '@tauri-apps/api/core';
 import { Activity, AlertTriangle, CheckCircle, Clock } from 'lucide-react';
-import type { PlcData, VideoConfig, BitConfig } from '../types';
-import { parseTemplate } from '../utils/templateParser';
+import type { PlcData, VideoConfig, BitConfig, PanelTheme, AnalogReading, CountdownValue, AudioPolicy, PanelTransitions, Transition, FailsafeState, ActivePanelEvent } from '../types';
+import { parseTemplate, type TimerValues, type TemplateValues } from '../utils/templateParser';
+import { useDisplayHealthReporter } from '../hooks/useDisplayHealthReporter';
+import { usePlcConsumptionReporter } from '../hooks/usePlcConsumptionReporter';
+import { usePanelContentReporter } from '../hooks/usePanelContentReporter';
+
+// Sem dados do backend: o fade de 0,5 s que o painel sempre usou
+const DEFAULT_TRANSITIONS: PanelTransitions = {
+  playlist: { effect: 'fade', duration_ms: 500 },
+  message: { effect: 'fade', duration_ms: 500 },
+};
+
+const FAILSAFE_SEMAPHORE_COLORS: Record<FailsafeState['semaphore'], string> = {
+  vermelho: '#ff0000',
+  amarelo: '#ffcc00',
+  verde: '#00ff00',
+  apagado: '#1f2937',
+};
+
+/** Animação CSS de entrada para a transição; "cut" troca na hora (sem atraso escalonado) */
+const transitionAnimation = (transition: Transition, keyframes: { fade: string; slide: string }, delayS = 0): string => {
+  if (transition.effect === 'cut') return 'none';
+  const name = transition.effect === 'slide' ? keyframes.slide : keyframes.fade;
+  return `${name} ${transition.duration_ms}ms ease-out ${delayS}s both`;
+};
 
 export const VisualizationPanel: React.FC = () => {
   const [plcData, setPlcData] = useState<PlcData | null>(null);
   const [isConnected, setIsConnected] = useState(false);
   const [lastUpdate, setLastUpdate] = useState<Date | null>(null);
   const [currentTime, setCurrentTime] = useState(new Date());
-  const [videos, setVideos] = useState<VideoConfig[]>([]);
+  const [playlistVideos, setVideos] = useState<VideoConfig[]>([]);
   const [bitConfigs, setBitConfigs] = useState<BitConfig[]>([]);
+  const [activeBits, setActiveBits] = useState<number[] | null>(null); // IDs ativos calculados no backend (condições)
+  const [analogValues, setAnalogValues] = useState<AnalogReading[]>([]); // Mostradores analógicos formatados no backend
+  const [timerValues, setTimerValues] = useState<TimerValues>({}); // Contadores regressivos ({Timer:nome})
+  const [templateValues, setTemplateValues] = useState<TemplateValues>({}); // Placeholders calculados no backend ({time}, {phase_title}...)
   const [currentView, setCurrentView] = useState<'plc' | 'video'>('plc');
   const [currentVideoIndex, setCurrentVideoIndex] = useState(0);
   const [viewStartTime, setViewStartTime] = useState(Date.now());
   const [videoControlConfig, setVideoControlConfig] = useState<{ wordIndex: number; bitIndex: number }>({ wordIndex: 3, bitIndex: 3 });
   const [videoSrc, setVideoSrc] = useState<string>('');
+  const [baseTheme, setTheme] = useState<PanelTheme | null>(null);
+  const [audioPolicy, setAudioPolicy] = useState<AudioPolicy | null>(null); // Volume/mudo efetivos calculados no backend
+  const [transitions, setTransitions] = useState<PanelTransitions>(DEFAULT_TRANSITIONS); // Efeitos resolvidos no backend
+  const [failsafe, setFailsafe] = useState<FailsafeState | null>(null); // Estado seguro forçado pelo backend (dados do PLC velhos)
+  const [panelEvent, setPanelEvent] = useState<ActivePanelEvent | null>(null); // Evento programado em vigor (ativado/expirado no backend)
+  // Evento em vigor substitui a playlist (se tiver vídeos) e o tema (se tiver tema)
+  const videos = useMemo(
+    () => (panelEvent && panelEvent.videos.length > 0 ? panelEvent.videos : playlistVideos),
+    [panelEvent, playlistVideos]
+  );
+  const theme = panelEvent?.theme ?? baseTheme;
   const videoRef = useRef<HTMLVideoElement>(null);
+  const { reportDecodeError } = useDisplayHealthReporter(videoRef);
+  const { markConsumed } = usePlcConsumptionReporter('panel');
   
   // Refs para valores atualizados no intervalo (stale closure fix)
   const currentViewRef = useRef(currentView);
@@ -57,14 +97,19 @@ export const VisualizationPanel: React.FC = () => {
     console.log('🎧 [Panel] Configurando listener PLC...');
     const setupListener = async () => {
       try {
-        const unlisten = await listen<{ message: PlcData }>('plc-data', (event) => {
+        const unlisten = await listen<{ seq?: number; message: PlcData; active_bits?: number[]; analog_values?: AnalogReading[]; transitions?: PanelTransitions; template_values?: TemplateValues }>('plc-data', (event) => {
           console.log('📡 [Panel] Dados PLC recebidos!', {
             timestamp: event.payload.message.timestamp,
             variablesCount: Object.keys(event.payload.message.variables).length
           });
           setPlcData(event.payload.message);
+          setActiveBits(event.payload.active_bits ?? null);
+          setAnalogValues(event.payload.analog_values ?? []);
+          setTransitions(event.payload.transitions ?? DEFAULT_TRANSITIONS);
+          setTemplateValues(event.payload.template_values ?? {});
           setIsConnected(true);
           setLastUpdate(new Date());
+          markConsumed(event.payload.seq);
         });
         console.log('✅ [Panel] Listener PLC configurado com sucesso');
         return unlisten;
@@ -120,6 +165,122 @@ export const VisualizationPanel: React.FC = () => {
     loadConfigs();
   }, []); // Executa apenas uma vez
 
+  // Tema ativo - carregado uma vez e atualizado pelo evento theme-changed
+  useEffect(() => {
+    invoke<PanelTheme | null>('get_active_theme')
+      .then(setTheme)
+      .catch(error => console.error('❌ [Panel] Erro ao carregar tema:', error));
+
+    let unlistenFn: (() => void) | undefined;
+    listen<PanelTheme>('theme-changed', (event) => {
+      console.log('🎨 [Panel] Tema alterado:', event.payload.name);
+      setTheme(event.payload);
+    }).then(fn => { unlistenFn = fn; });
+
+    return () => {
+      if (unlistenFn) unlistenFn();
+    };
+  }, []);
+
+  // Contadores regressivos - backend envia os valores formatados a cada segundo
+  useEffect(() => {
+    const applyCountdowns = (values: CountdownValue[]) => {
+      setTimerValues(Object.fromEntries(values.map(v => [v.name, v.formatted])));
+    };
+
+    invoke<CountdownValue[]>('get_countdown_values')
+      .then(applyCountdowns)
+      .catch(error => console.error('❌ [Panel] Erro ao carregar contadores:', error));
+
+    let unlistenFn: (() => void) | undefined;
+    listen<CountdownValue[]>('countdown-update', (event) => {
+      applyCountdowns(event.payload);
+    }).then(fn => { unlistenFn = fn; });
+
+    return () => {
+      if (unlistenFn) unlistenFn();
+    };
+  }, []);
+
+  // Áudio dos vídeos - backend envia volume/mudo efetivos (horário de silêncio incluído)
+  useEffect(() => {
+    invoke<AudioPolicy>('get_audio_policy')
+      .then(setAudioPolicy)
+      .catch(error => console.error('❌ [Panel] Erro ao carregar política de áudio:', error));
+
+    let unlistenFn: (() => void) | undefined;
+    listen<AudioPolicy>('audio-policy', (event) => {
+      console.log(event.payload.quiet ? '🔇 [Panel] Horário de silêncio ativo' : '🔊 [Panel] Política de áudio atualizada');
+      setAudioPolicy(event.payload);
+    }).then(fn => { unlistenFn = fn; });
+
+    return () => {
+      if (unlistenFn) unlistenFn();
+    };
+  }, []);
+
+  // Estado seguro - backend decide entrada/saída pela idade dos dados do PLC
+  useEffect(() => {
+    invoke<FailsafeState>('get_failsafe_state')
+      .then(setFailsafe)
+      .catch(error => console.error('❌ [Panel] Erro ao carregar estado seguro:', error));
+
+    let unlistenFn: (() => void) | undefined;
+    listen<FailsafeState>('panel-failsafe', (event) => {
+      console.log(event.payload.active ? '🚨 [Panel] Estado seguro ativo' : '✅ [Panel] Estado seguro encerrado');
+      setFailsafe(event.payload);
+    }).then(fn => { unlistenFn = fn; });
+
+    return () => {
+      if (unlistenFn) unlistenFn();
+    };
+  }, []);
+
+  // Evento programado (regata, avisos) - ativação/expiração decididas no backend
+  useEffect(() => {
+    invoke<ActivePanelEvent | null>('get_active_panel_event')
+      .then(setPanelEvent)
+      .catch(error => console.error('❌ [Panel] Erro ao carregar evento programado:', error));
+
+    let unlistenFn: (() => void) | undefined;
+    listen<ActivePanelEvent | null>('panel-event', (event) => {
+      console.log(event.payload ? `📅 [Panel] Evento ativo: ${event.payload.name}` : '📅 [Panel] Evento encerrado');
+      setPanelEvent(event.payload);
+    }).then(fn => { unlistenFn = fn; });
+
+    return () => {
+      if (unlistenFn) unlistenFn();
+    };
+  }, []);
+
+  // Troca de playlist (início/fim do evento) recomeça do primeiro vídeo
+  useEffect(() => {
+    setCurrentVideoIndex(0);
+  }, [panelEvent?.id]);
+
+  // Vídeo não toca (nem com som) por baixo do estado seguro
+  useEffect(() => {
+    const video = videoRef.current;
+    if (!video) return;
+    if (failsafe?.active) {
+      video.pause();
+    } else if (video.paused && currentView === 'video') {
+      video.play().catch(() => undefined);
+    }
+  }, [failsafe?.active, currentView]);
+
+  // Aplica o áudio do vídeo atual; sem política carregada o vídeo fica mudo
+  const applyVideoAudio = (element: HTMLVideoElement | null, videoId?: number) => {
+    if (!element) return;
+    const audio = audioPolicy?.videos.find(v => v.id === videoId);
+    element.muted = audio ? audio.muted : true;
+    element.volume = audio ? audio.volume / 100 : 0;
+  };
+
+  useEffect(() => {
+    applyVideoAudio(videoRef.current, videos[currentVideoIndex]?.id);
+  }, [audioPolicy, currentVideoIndex, videos, videoSrc]);
+
   // Atualizar relógio - independente
   useEffect(() => {
     const timeInterval = setInterval(() => {
@@ -377,7 +538,10 @@ export const VisualizationPanel: React.FC = () => {
       
       const wordKey = `Word[${bitConfig.word_index}]`;
       const wordValue = plcData.variables[wordKey] || 0;
-      const bitValue = ((wordValue >> bitConfig.bit_index) & 1) === 1;
+      // Backend avalia as condições (AND/OR/NOT); cálculo local só como fallback
+      const bitValue = activeBits
+        ? activeBits.includes(bitConfig.id)
+        : ((wordValue >> bitConfig.bit_index) & 1) === 1;
       
       console.log(`🔍 [Bit Check] ${wordKey}.${bitConfig.bit_index} (${bitConfig.name}):`, {
         wordValue,
@@ -393,7 +557,7 @@ export const VisualizationPanel: React.FC = () => {
         
         if (bitConfig.use_template && bitConfig.message_template) {
           // Usar template e substituir {Word[N]} por valores reais
-          finalMessage = parseTemplate(bitConfig.message_template, plcData.variables);
+          finalMessage = parseTemplate(bitConfig.message_template, plcData.variables, timerValues, templateValues);
           console.log(`✅ [Template Processado] ${bitConfig.name}:`, {
             template: bitConfig.message_template,
             resultado: finalMessage
@@ -407,12 +571,12 @@ export const VisualizationPanel: React.FC = () => {
         if (finalMessage.trim()) {
           messages.push({
             message: finalMessage,
-            color: bitConfig.color,
+            color: bitConfig.color || theme?.text_color || '#ffffff',
             priority: bitConfig.priority,
             fontSize: bitConfig.font_size,
             position: bitConfig.position,
-            fontFamily: bitConfig.font_family || 'Arial Black',
-            fontWeight: bitConfig.font_weight || 'bold',
+            fontFamily: (theme?.override_bit_fonts ? theme.font_family : bitConfig.font_family) || theme?.font_family || 'Arial Black',
+            fontWeight: (theme?.override_bit_fonts ? theme.font_weight : bitConfig.font_weight) || theme?.font_weight || 'bold',
             textShadow: bitConfig.text_shadow !== undefined ? bitConfig.text_shadow : true,
             letterSpacing: bitConfig.letter_spacing || 2
           });
@@ -424,10 +588,20 @@ export const VisualizationPanel: React.FC = () => {
     
     // Ordenar por prioridade (maior primeiro)
     return messages.sort((a, b) => b.priority - a.priority);
-  }, [plcData, bitConfigs]); // Recalcula quando plcData ou bitConfigs mudam
+  }, [plcData, activeBits, bitConfigs, theme, timerValues, templateValues]); // Recalcula quando plcData, bitConfigs ou tema mudam
 
   const currentVideo = videos[currentVideoIndex];
 
+  const shownView = failsafe?.active ? 'failsafe' : currentView;
+  usePanelContentReporter({
+    view: shownView,
+    video_id: shownView === 'video' ? currentVideo?.id ?? null : null,
+    video_title: shownView === 'video' ? currentVideo?.name ?? null : null,
+    messages: shownView !== 'plc' ? []
+      : activeMessages.length > 0 ? activeMessages.map((m) => m.message)
+      : panelEvent?.messages ?? [],
+  });
+
   // LOG GIGANTE PARA DEBUG
   console.log('🚨🚨🚨 [ESTADO COMPLETO DO PAINEL] 🚨🚨🚨', {
     currentView,
@@ -441,7 +615,25 @@ export const VisualizationPanel: React.FC = () => {
   });
 
   return (
-    <div className="w-full h-screen bg-black flex items-center justify-center overflow-hidden text-white">
+    <div
+      className="relative w-full h-screen bg-black flex items-center justify-center overflow-hidden text-white"
+      style={theme ? {
+        backgroundColor: theme.background_color,
+        backgroundImage: theme.background_image ? `url("${convertFileSrc(theme.background_image)}")` : undefined,
+        backgroundSize: 'cover',
+        backgroundPosition: 'center',
+        color: theme.text_color,
+      } : undefined}
+    >
+      {theme?.logo_path && currentView === 'plc' ? (
+        <img
+          src={convertFileSrc(theme.logo_path)}
+          alt={theme.name}
+          className={`absolute max-h-24 max-w-xs object-contain z-10 ${
+            { 'top-left': 'top-6 left-6', 'top-right': 'top-6 right-6', 'bottom-left': 'bottom-6 left-6', 'bottom-right': 'bottom-6 right-6' }[theme.logo_position]
+          }`}
+        />
+      ) : null}
       {/* FULL SCREEN - Alternância entre PLC e Vídeos */}
       {currentView === 'plc' ? (
           // Modo PLC - Mensagens dos Bits Ativos
@@ -453,7 +645,7 @@ export const VisualizationPanel: React.FC = () => {
                     key={index}
                     className="w-full flex items-center justify-center transform transition-all duration-500"
                     style={{ 
-                      animation: `fadeIn 0.5s ease-in ${index * 0.1}s both`,
+                      animation: transitionAnimation(transitions.message, { fade: 'fadeIn', slide: 'panelSlide' }, index * 0.1),
                       flex: 1,
                       maxHeight: `${100 / Math.min(activeMessages.length, 5)}%`
                     }}
@@ -483,11 +675,59 @@ export const VisualizationPanel: React.FC = () => {
                   </div>
                 ))}
               </div>
+            ) : panelEvent && panelEvent.messages.length > 0 ? (
+              // Sem mensagens de bits: textos do evento programado
+              <div className="w-full h-full flex flex-col justify-center items-center space-y-4">
+                {panelEvent.messages.map((text, index) => (
+                  <p
+                    key={`${panelEvent.id}-${index}`}
+                    className="w-full text-center uppercase"
+                    style={{
+                      animation: transitionAnimation(transitions.message, { fade: 'fadeIn', slide: 'panelSlide' }, index * 0.1),
+                      color: theme?.text_color || '#ffffff',
+                      fontFamily: theme?.font_family || 'Arial Black',
+                      fontWeight: theme?.font_weight || 'bold',
+                      fontSize: index === 0 ? '72px' : '48px',
+                      letterSpacing: '2px',
+                      lineHeight: 1.1,
+                    }}
+                  >
+                    {parseTemplate(text, plcData?.variables ?? {}, timerValues, templateValues)}
+                  </p>
+                ))}
+              </div>
+            ) : null}
+            {analogValues.length > 0 ? (
+              <div className="absolute bottom-6 left-0 right-0 flex justify-center gap-12 px-8">
+                {analogValues.map((reading) => (
+                  <div key={reading.id} className="flex flex-col items-center min-w-[12rem]">
+                    <p
+                      className="text-5xl font-bold tracking-wide"
+                      style={{
+                        fontFamily: theme?.font_family,
+                        color: reading.out_of_range ? '#ff3b3b' : undefined,
+                      }}
+                    >
+                      {reading.formatted}
+                    </p>
+                    <div className="w-full h-2 mt-2 bg-white bg-opacity-20 rounded-full overflow-hidden">
+                      <div
+                        className="h-full bg-white rounded-full transition-all duration-500"
+                        style={{ width: `${reading.percent}%` }}
+                      />
+                    </div>
+                  </div>
+                ))}
+              </div>
             ) : null}
           </div>
         ) : (
           // Modo Vídeo - FULL SCREEN
-          <div className="w-full h-full flex items-center justify-center">
+          <div
+            key={videoSrc}
+            className="w-full h-full flex items-center justify-center"
+            style={{ animation: transitionAnimation(transitions.playlist, { fade: 'panelFade', slide: 'panelSlide' }) }}
+          >
             {(() => {
               console.log('🎥 [Panel RENDER] Modo VIDEO ativo!', {
                 currentVideoIndex,
@@ -504,19 +744,26 @@ export const VisualizationPanel: React.FC = () => {
                 src={videoSrc}
                 autoPlay
                 playsInline
-                muted
                 className="w-full h-full object-cover"
                 onError={() => {
                   console.error('❌ [Panel] Erro ao carregar vídeo:', currentVideo.file_path);
                   console.error('❌ [Panel] URL:', videoSrc);
+                  reportDecodeError();
                 }}
                 onLoadedData={() => {
                   console.log('✅ [Panel] Vídeo carregado:', currentVideo.name);
                   if (videoRef.current) {
-                    videoRef.current.play().then(() => {
+                    const element = videoRef.current;
+                    applyVideoAudio(element, currentVideo.id);
+                    element.play().then(() => {
                       console.log('▶️ [Panel] Vídeo reproduzindo!');
                     }).catch(err => {
                       console.error('❌ [Panel] Erro ao dar play:', err);
+                      // Autoplay com som bloqueado: reproduz mudo
+                      if (!element.muted) {
+                        element.muted = true;
+                        element.play().catch(() => {});
+                      }
                     });
                   }
                 }}
@@ -526,6 +773,22 @@ export const VisualizationPanel: React.FC = () => {
             ) : null}
           </div>
         )}
+
+        {/* Estado seguro: cobre todo o conteúdo até os dados do PLC voltarem */}
+        {failsafe?.active && (
+          <div className="absolute inset-0 z-50 bg-black flex flex-col items-center justify-center gap-12">
+            <div
+              className="w-64 h-64 rounded-full border-8 border-gray-700"
+              style={{
+                backgroundColor: FAILSAFE_SEMAPHORE_COLORS[failsafe.semaphore],
+                boxShadow: failsafe.semaphore === 'apagado' ? 'none' : `0 0 80px ${FAILSAFE_SEMAPHORE_COLORS[failsafe.semaphore]}`,
+              }}
+            />
+            <div className="text-7xl font-bold text-white text-center px-12 uppercase tracking-wide">
+              {failsafe.message}
+            </div>
+          </div>
+        )}
         
         <style>{`
           @keyframes fadeIn {
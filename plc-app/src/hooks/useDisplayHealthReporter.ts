@@ -0,0 +1,54 @@
+import { useEffect, useRef, useCallback, type RefObject } from 'react';
+import { emit } from '@tauri-apps/api/event';
+
+const REPORT_INTERVAL_MS = 5000;
+
+// Envia ao backend (evento "display-metrics") o FPS real da webview, erros de
+// decodificação e a posição do vídeo atual, para o monitor de display detectar
+// degradação e travamentos.
+export const useDisplayHealthReporter = (videoRef: RefObject<HTMLVideoElement>) => {
+  const decodeErrorsRef = useRef(0);
+
+  const reportDecodeError = useCallback(() => {
+    decodeErrorsRef.current += 1;
+  }, []);
+
+  useEffect(() => {
+    let frames = 0;
+    let rafId = 0;
+    let windowStart = performance.now();
+
+    const countFrame = () => {
+      frames += 1;
+      rafId = requestAnimationFrame(countFrame);
+    };
+    rafId = requestAnimationFrame(countFrame);
+
+    const interval = setInterval(() => {
+      const now = performance.now();
+      const fps = (frames * 1000) / (now - windowStart);
+      frames = 0;
+      windowStart = now;
+
+      const video = videoRef.current;
+      const quality = video?.getVideoPlaybackQuality?.();
+      emit('display-metrics', {
+        fps,
+        decode_errors: decodeErrorsRef.current,
+        dropped_frames: quality?.droppedVideoFrames ?? 0,
+        video_active: !!video && !video.paused && !video.ended,
+        video_time_s: video?.currentTime ?? 0,
+        video_duration_s: video && Number.isFinite(video.duration) ? video.duration : 0,
+      }).catch((error) => {
+        console.error('❌ [Panel] Erro ao enviar métricas do display:', error);
+      });
+    }, REPORT_INTERVAL_MS);
+
+    return () => {
+      cancelAnimationFrame(rafId);
+      clearInterval(interval);
+    };
+  }, [videoRef]);
+
+  return { reportDecodeError };
+};
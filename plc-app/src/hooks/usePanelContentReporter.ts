@@ -0,0 +1,23 @@
+import { useEffect } from 'react';
+import { emit } from '@tauri-apps/api/event';
+import type { PanelContent } from '../types';
+
+const RESEND_INTERVAL_MS = 30000;
+
+// Informa ao backend (evento "panel-content") o que o painel está exibindo,
+// a cada troca de conteúdo e periodicamente (o backend pode ter reiniciado),
+// para get_panel_status e o heartbeat refletirem o estado real do display.
+export const usePanelContentReporter = (content: PanelContent) => {
+  const key = JSON.stringify(content);
+
+  useEffect(() => {
+    const send = () => {
+      emit('panel-content', JSON.parse(key)).catch((error) => {
+        console.error('❌ [Panel] Erro ao enviar conteúdo em exibição:', error);
+      });
+    };
+    send();
+    const interval = setInterval(send, RESEND_INTERVAL_MS);
+    return () => clearInterval(interval);
+  }, [key]);
+};
@@ -0,0 +1,35 @@
+import { useEffect, useRef, useCallback } from 'react';
+import { emit } from '@tauri-apps/api/event';
+
+const REPORT_INTERVAL_MS = 2000;
+
+// Informa ao backend (evento "plc-data-consumed") o último `seq` de plc-data
+// processado por esta janela, para medir o atraso em relação ao que foi emitido.
+export const usePlcConsumptionReporter = (windowName: string) => {
+  const lastSeqRef = useRef(0);
+  const receivedRef = useRef(0);
+
+  const markConsumed = useCallback((seq: number | undefined) => {
+    receivedRef.current += 1;
+    if (seq !== undefined && seq > lastSeqRef.current) {
+      lastSeqRef.current = seq;
+    }
+  }, []);
+
+  useEffect(() => {
+    const interval = setInterval(() => {
+      if (receivedRef.current === 0) return;
+      emit('plc-data-consumed', {
+        window: windowName,
+        last_seq: lastSeqRef.current,
+        received: receivedRef.current,
+      }).catch((error) => {
+        console.error('❌ Erro ao enviar relatório de consumo:', error);
+      });
+    }, REPORT_INTERVAL_MS);
+
+    return () => clearInterval(interval);
+  }, [windowName]);
+
+  return { markConsumed };
+};
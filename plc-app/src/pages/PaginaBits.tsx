@@ -5,6 +5,7 @@ import type { PlcData, BitConfig } from '../types';
 import { AddBitConfigForm } from '../components/AddBitConfigForm';
 import { LEDPreview } from '../components/LEDPreview';
 import { TemplateEditor } from '../components/TemplateEditor';
+import { errorMessage } from '../utils/appError';
 
 interface PaginaBitsProps {
   isConnected: boolean;
@@ -61,6 +62,7 @@ export const PaginaBits: React.FC<PaginaBitsProps> = ({
     letterSpacing: number;
     useTemplate: boolean;
     messageTemplate: string;
+    condition: string;
   }) => {
     try {
       // Já vem em camelCase do formulário, apenas envia para o backend
@@ -81,13 +83,14 @@ export const PaginaBits: React.FC<PaginaBitsProps> = ({
         letterSpacing: bitConfig.letterSpacing,
         useTemplate: bitConfig.useTemplate,
         messageTemplate: bitConfig.messageTemplate,
+        condition: bitConfig.condition,
       });
       setShowAddBitForm(false);
       await loadBitConfigs();
       alert('Configuração de bit adicionada com sucesso!');
     } catch (error) {
       console.error('Erro ao adicionar configuração de bit:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
     }
   };
 
@@ -116,6 +119,7 @@ export const PaginaBits: React.FC<PaginaBitsProps> = ({
     letterSpacing: number;
     useTemplate: boolean;
     messageTemplate: string;
+    condition: string;
   }) => {
     if (!editingBit) return;
     
@@ -137,6 +141,7 @@ export const PaginaBits: React.FC<PaginaBitsProps> = ({
         letterSpacing: updatedBit.letterSpacing,
         useTemplate: updatedBit.useTemplate,
         messageTemplate: updatedBit.messageTemplate,
+        condition: updatedBit.condition,
       });
       
       handleCloseEditModal();
@@ -144,7 +149,7 @@ export const PaginaBits: React.FC<PaginaBitsProps> = ({
       alert('Configuração atualizada com sucesso!');
     } catch (error) {
       console.error('Erro ao atualizar configuração:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
     }
   };
 
@@ -518,7 +523,7 @@ const BitConfigTableRow: React.FC<{
                   onUpdate();
                   alert('Configuração deletada com sucesso!');
                 } catch (error) {
-                  alert(`Erro: ${error}`);
+                  alert(`Erro: ${errorMessage(error)}`);
                 }
               }
             }}
@@ -550,6 +555,7 @@ const EditBitModal: React.FC<{
     letterSpacing: number;
     useTemplate: boolean;
     messageTemplate: string;
+    condition: string;
   }) => void;
   onClose: () => void;
   plcData?: PlcData | null;
@@ -568,6 +574,7 @@ const EditBitModal: React.FC<{
     letterSpacing: bitConfig.letter_spacing || 3,
     useTemplate: bitConfig.use_template || false,
     messageTemplate: bitConfig.message_template || '',
+    condition: bitConfig.condition || '',
   });
 
   // Calcular estado atual do bit
@@ -674,6 +681,21 @@ const EditBitModal: React.FC<{
               />
             </div>
 
+            {/* Condição combinada */}
+            <div className="space-y-1">
+              <label className="block text-sm font-medium text-edp-marine">
+                Condição (opcional)
+              </label>
+              <input
+                type="text"
+                value={formData.condition}
+                onChange={(e) => setFormData({ ...formData, condition: e.target.value })}
+                className="w-full px-3 py-2 text-sm font-mono border border-edp-neutral-lighter rounded-lg focus:ring-2 focus:ring-edp-marine focus:border-edp-marine transition-edp bg-edp-neutral-white-wash focus:bg-white"
+                placeholder={`Ex: Word[${bitConfig.word_index}].${bitConfig.bit_index} AND NOT Word[1].0`}
+              />
+              <p className="text-xs text-edp-slate">Vazio = apenas este bit. Operadores: AND, OR, NOT e parênteses.</p>
+            </div>
+
             {/* Template Toggle */}
             <div className="border border-edp-violet border-opacity-30 rounded-lg p-2 bg-edp-violet bg-opacity-10">
               <label className="flex items-center gap-2 cursor-pointer">
@@ -1,9 +1,10 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
-import { Video, Upload, Trash2, Play, Edit, X, Check, Clock, RefreshCw, Folder, Zap, AlertCircle, Settings, MemoryStick, ChevronUp, ChevronDown } from 'lucide-react';
-import type { VideoConfig } from '../types';
+import { Video, Upload, Trash2, Play, Edit, X, Check, Clock, RefreshCw, Folder, Zap, AlertCircle, Settings, MemoryStick, ChevronUp, ChevronDown, Moon } from 'lucide-react';
+import type { VideoConfig, QuietHoursConfig } from '../types';
 import { AddVideoForm } from '../components/AddVideoForm';
+import { errorMessage } from '../utils/appError';
 
 export const PaginaPublicidade: React.FC = () => {
   const [videos, setVideos] = useState<VideoConfig[]>([]);
@@ -17,6 +18,11 @@ export const PaginaPublicidade: React.FC = () => {
   const [videoControlConfig, setVideoControlConfig] = useState({ wordIndex: 5, bitIndex: 3 });
   const [isUpdatingConfig, setIsUpdatingConfig] = useState(false);
   
+  // Horário de silêncio (áudio limitado no turno da noite)
+  const [showQuietHours, setShowQuietHours] = useState(false);
+  const [quietHours, setQuietHours] = useState<QuietHoursConfig>({ enabled: false, start: '22:00', end: '07:00', max_volume: 0 });
+  const [isSavingQuietHours, setIsSavingQuietHours] = useState(false);
+  
   // Paginação
   const [paginaAtual, setPaginaAtual] = useState(1);
   const itensPorPagina = 10;
@@ -24,8 +30,31 @@ export const PaginaPublicidade: React.FC = () => {
   useEffect(() => {
     loadVideos();
     loadVideoControlConfig();
+    loadQuietHours();
   }, []);
 
+  const loadQuietHours = async () => {
+    try {
+      setQuietHours(await invoke<QuietHoursConfig>('get_quiet_hours_config'));
+    } catch (error) {
+      console.error('Erro ao carregar horário de silêncio:', error);
+    }
+  };
+
+  const handleSaveQuietHours = async () => {
+    setIsSavingQuietHours(true);
+    try {
+      await invoke('set_quiet_hours_config', { config: quietHours });
+      setShowQuietHours(false);
+      alert('✅ Horário de silêncio salvo!');
+    } catch (error) {
+      console.error('Erro ao salvar horário de silêncio:', error);
+      alert(`❌ Erro: ${errorMessage(error)}`);
+    } finally {
+      setIsSavingQuietHours(false);
+    }
+  };
+
   const loadVideoControlConfig = async () => {
     try {
       const [wordIndex, bitIndex] = await invoke<[number, number]>('get_video_control_config');
@@ -60,6 +89,7 @@ export const PaginaPublicidade: React.FC = () => {
       if (!selected) return;
 
       const files = Array.isArray(selected) ? selected : [selected];
+      let message = '';
       
       for (const filePath of files) {
         const fileName = filePath.split('\\').pop() || filePath.split('/').pop() || 'video';
@@ -72,14 +102,14 @@ export const PaginaPublicidade: React.FC = () => {
           description: ''
         };
 
-        await invoke('add_video', videoData);
+        message = await invoke<string>('add_video', videoData);
       }
 
       await loadVideos();
-      alert(`✅ ${files.length} vídeo(s) adicionado(s) com sucesso!`);
+      alert(`✅ ${files.length} vídeo(s): ${message}`);
     } catch (error) {
       console.error('Erro ao selecionar vídeos:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
     }
   };
 
@@ -92,24 +122,24 @@ export const PaginaPublicidade: React.FC = () => {
     description: string;
   }) => {
     try {
-      await invoke('add_video', videoData);
+      const message = await invoke<string>('add_video', videoData);
       setShowAddVideoForm(false);
       await loadVideos();
-      alert('✅ Vídeo adicionado com sucesso!');
+      alert(`✅ ${message}`);
     } catch (error) {
       console.error('Erro ao adicionar vídeo:', error);
-      alert(`❌ Erro: ${error}`);
+      alert(`❌ Erro: ${errorMessage(error)}`);
     }
   };
 
   const clearAllVideos = async () => {
     if (window.confirm('⚠️ ATENÇÃO: Isso vai APAGAR TODOS OS VÍDEOS. Tem certeza?')) {
       try {
-        await invoke('clear_all_videos');
+        const message = await invoke<string>('clear_all_videos');
         await loadVideos();
-        alert('✅ Todos os vídeos foram removidos!');
+        alert(`✅ ${message}`);
       } catch (error) {
-        alert(`Erro: ${error}`);
+        alert(`Erro: ${errorMessage(error)}`);
       }
     }
   };
@@ -124,7 +154,7 @@ export const PaginaPublicidade: React.FC = () => {
       await loadVideos();
     } catch (error) {
       console.error('Erro ao mover vídeo para cima:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
     }
   };
 
@@ -138,7 +168,7 @@ export const PaginaPublicidade: React.FC = () => {
       await loadVideos();
     } catch (error) {
       console.error('Erro ao mover vídeo para baixo:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
     }
   };
 
@@ -168,7 +198,7 @@ export const PaginaPublicidade: React.FC = () => {
       alert('✅ Configuração do controle de vídeos atualizada!');
     } catch (error) {
       console.error('Erro ao atualizar configuração:', error);
-      alert(`❌ Erro: ${error}`);
+      alert(`❌ Erro: ${errorMessage(error)}`);
     } finally {
       setIsUpdatingConfig(false);
     }
@@ -290,6 +320,15 @@ export const PaginaPublicidade: React.FC = () => {
                 <MemoryStick size={16} />
               </button>
 
+              {/* Botão Horário de Silêncio */}
+              <button
+                onClick={() => setShowQuietHours(!showQuietHours)}
+                className={`p-2.5 hover:text-edp-marine hover:bg-edp-neutral-white-tint rounded-lg transition-colors border border-edp-neutral-lighter hover:border-edp-marine ${quietHours.enabled ? 'text-edp-marine' : 'text-edp-slate'}`}
+                title={quietHours.enabled ? `Silêncio: ${quietHours.start}-${quietHours.end} (máx. ${quietHours.max_volume}%)` : 'Horário de silêncio desativado'}
+              >
+                <Moon size={16} />
+              </button>
+
               {/* Botão Recarregar */}
               <button
                 onClick={loadVideos}
@@ -412,6 +451,64 @@ export const PaginaPublicidade: React.FC = () => {
               </div>
             </div>
           )}
+
+          {/* Horário de Silêncio */}
+          {showQuietHours && (
+            <div className="mt-3 pt-3 border-t border-edp-neutral-lighter bg-edp-marine bg-opacity-5 rounded-lg p-3">
+              <div className="flex items-center justify-between">
+                <div className="flex items-center gap-3 text-sm">
+                  <label className="flex items-center gap-2 font-medium text-edp-marine cursor-pointer">
+                    <input
+                      type="checkbox"
+                      checked={quietHours.enabled}
+                      onChange={(e) => setQuietHours({ ...quietHours, enabled: e.target.checked })}
+                      className="w-4 h-4 text-edp-marine border-gray-300 rounded focus:ring-edp-marine"
+                    />
+                    Silêncio
+                  </label>
+                  <input
+                    type="time"
+                    value={quietHours.start}
+                    onChange={(e) => setQuietHours({ ...quietHours, start: e.target.value })}
+                    className="px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white font-tabular"
+                  />
+                  <span className="text-edp-marine">até</span>
+                  <input
+                    type="time"
+                    value={quietHours.end}
+                    onChange={(e) => setQuietHours({ ...quietHours, end: e.target.value })}
+                    className="px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white font-tabular"
+                  />
+                  <span className="text-edp-marine">Volume máx.</span>
+                  <input
+                    type="number"
+                    min="0"
+                    max="100"
+                    value={quietHours.max_volume}
+                    onChange={(e) => setQuietHours({ ...quietHours, max_volume: parseInt(e.target.value) || 0 })}
+                    className="w-16 px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white font-tabular"
+                    title="0 = mudo"
+                  />
+                  <span className="text-xs text-edp-slate">% (0 = mudo)</span>
+                </div>
+                <div className="flex gap-2">
+                  <button
+                    onClick={handleSaveQuietHours}
+                    disabled={isSavingQuietHours}
+                    className="px-3 py-1 text-xs bg-edp-marine text-white rounded hover:bg-edp-marine-100 disabled:opacity-50 transition-colors"
+                  >
+                    {isSavingQuietHours ? '...' : 'Salvar'}
+                  </button>
+                  <button
+                    onClick={() => setShowQuietHours(false)}
+                    className="px-3 py-1 text-xs bg-edp-slate text-white rounded hover:bg-edp-slate-100 transition-colors"
+                  >
+                    Fechar
+                  </button>
+                </div>
+              </div>
+            </div>
+          )}
         </div>
 
         {/* Add Video Form Integrado */}
@@ -564,7 +661,9 @@ const VideoTableRow: React.FC<{
     duration: video.duration,
     enabled: video.enabled,
     priority: video.priority,
-    description: video.description
+    description: video.description,
+    volume: video.volume,
+    muted: video.muted
   });
 
   // Helper para converter caminho do arquivo em URL válida
@@ -614,11 +713,14 @@ const VideoTableRow: React.FC<{
         description: formData.description,
         displayOrder: video.display_order
       });
+      if (formData.volume !== video.volume || formData.muted !== video.muted) {
+        await invoke('set_video_audio', { id: video.id, volume: formData.volume, muted: formData.muted });
+      }
       setIsEditing(false);
       onUpdate();
     } catch (error) {
       console.error('Erro ao atualizar vídeo:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
     }
   };
 
@@ -629,7 +731,7 @@ const VideoTableRow: React.FC<{
         onUpdate();
       } catch (error) {
         console.error('Erro ao deletar vídeo:', error);
-        alert(`Erro: ${error}`);
+        alert(`Erro: ${errorMessage(error)}`);
       }
     }
   };
@@ -641,7 +743,9 @@ const VideoTableRow: React.FC<{
       duration: video.duration,
       enabled: video.enabled,
       priority: video.priority,
-      description: video.description
+      description: video.description,
+      volume: video.volume,
+      muted: video.muted
     });
     setIsEditing(false);
   };
@@ -765,6 +869,34 @@ const VideoTableRow: React.FC<{
                 </div>
               </div>
 
+              {/* Áudio */}
+              <div className="space-y-2">
+                <label className="block text-sm font-semibold text-gray-700">
+                  Volume do Áudio: {formData.muted ? 'mudo' : `${formData.volume}%`}
+                </label>
+                <div className="flex items-center gap-4">
+                  <input
+                    type="range"
+                    min="0"
+                    max="100"
+                    value={formData.volume}
+                    disabled={formData.muted}
+                    onChange={(e) => setFormData({ ...formData, volume: parseInt(e.target.value) || 0 })}
+                    className="flex-1 accent-edp-marine disabled:opacity-50"
+                  />
+                  <label className="flex items-center gap-2 text-sm text-gray-700 cursor-pointer">
+                    <input
+                      type="checkbox"
+                      checked={formData.muted}
+                      onChange={(e) => setFormData({ ...formData, muted: e.target.checked })}
+                      className="w-4 h-4 text-edp-marine border-gray-300 rounded focus:ring-edp-marine"
+                    />
+                    Mudo
+                  </label>
+                </div>
+                <p className="text-xs text-gray-500">No horário de silêncio o volume é limitado ao máximo configurado</p>
+              </div>
+
               {/* Toggle Ativo */}
               <div className="border-2 border-edp-electric rounded-lg p-4 bg-green-50">
                 <label className="flex items-center cursor-pointer">
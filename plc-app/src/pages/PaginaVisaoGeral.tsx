@@ -1,8 +1,15 @@
 import React, { useState, useEffect } from 'react';
-import { Activity, Database, Zap, Cpu, AlertTriangle, Info, AlertCircle, XCircle, ChevronLeft, ChevronRight } from 'lucide-react';
-import type { PlcData, SystemLog } from '../types';
+import { Activity, Database, Zap, Cpu, AlertTriangle, Info, AlertCircle, XCircle, ChevronLeft, ChevronRight, Monitor, Film, Radio } from 'lucide-react';
+import type { PlcData, SystemLog, PanelStatus, FailsafeConfig, FailsafeState, FailsafeSemaphore, HealthEndpointConfig, HealthReport } from '../types';
 import { CardModerno } from '../components/CardModerno';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { errorMessage } from '../utils/appError';
+
+const formatSeconds = (seconds: number) => {
+  const total = Math.floor(seconds);
+  return `${Math.floor(total / 60)}:${String(total % 60).padStart(2, '0')}`;
+};
 
 interface PaginaVisaoGeralProps {
   isConnected: boolean;
@@ -19,6 +26,103 @@ export const PaginaVisaoGeral: React.FC<PaginaVisaoGeralProps> = ({
   const [loadingLogs, setLoadingLogs] = useState(true);
   const [currentPage, setCurrentPage] = useState(1);
   const itemsPerPage = 8;
+  const [panelStatus, setPanelStatus] = useState<PanelStatus | null>(null);
+  const [failsafeConfig, setFailsafeConfig] = useState<FailsafeConfig>({ enabled: true, timeout_s: 10, message: 'INFORMAÇÃO INDISPONÍVEL', semaphore: 'vermelho' });
+  const [failsafeState, setFailsafeState] = useState<FailsafeState | null>(null);
+  const [isSavingFailsafe, setIsSavingFailsafe] = useState(false);
+  const [healthConfig, setHealthConfig] = useState<HealthEndpointConfig>({ enabled: false, bind_host: '0.0.0.0', port: 8503 });
+  const [healthReport, setHealthReport] = useState<HealthReport | null>(null);
+  const [isSavingHealth, setIsSavingHealth] = useState(false);
+
+  // Estado seguro do painel (dados do PLC velhos) - entrada/saída decidida no backend
+  useEffect(() => {
+    invoke<FailsafeConfig>('get_failsafe_config')
+      .then(setFailsafeConfig)
+      .catch((error) => console.error('Erro ao carregar estado seguro:', error));
+    invoke<FailsafeState>('get_failsafe_state')
+      .then(setFailsafeState)
+      .catch((error) => console.error('Erro ao carregar estado seguro:', error));
+
+    const unlisten = listen<FailsafeState>('panel-failsafe', (event) => setFailsafeState(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleSaveFailsafe = async () => {
+    setIsSavingFailsafe(true);
+    try {
+      await invoke('set_failsafe_config', { config: failsafeConfig });
+      alert('✅ Estado seguro salvo!');
+    } catch (error) {
+      console.error('Erro ao salvar estado seguro:', error);
+      alert(`❌ Erro: ${errorMessage(error)}`);
+    } finally {
+      setIsSavingFailsafe(false);
+    }
+  };
+
+  // Endpoint /healthz para o monitoramento do local (mesmo relatório do HTTP)
+  const loadHealthReport = () => {
+    invoke<HealthReport>('get_health_report')
+      .then(setHealthReport)
+      .catch((error) => console.error('Erro ao carregar health check:', error));
+  };
+
+  useEffect(() => {
+    invoke<HealthEndpointConfig>('get_health_endpoint_config')
+      .then(setHealthConfig)
+      .catch((error) => console.error('Erro ao carregar health check:', error));
+    loadHealthReport();
+    const interval = setInterval(loadHealthReport, 10000);
+    return () => clearInterval(interval);
+  }, []);
+
+  const handleSaveHealth = async () => {
+    setIsSavingHealth(true);
+    try {
+      await invoke('set_health_endpoint_config', { config: healthConfig });
+      alert('✅ Health check salvo!');
+    } catch (error) {
+      console.error('Erro ao salvar health check:', error);
+      alert(`❌ Erro: ${errorMessage(error)}`);
+    } finally {
+      setIsSavingHealth(false);
+    }
+  };
+
+  // Estado real do painel público (atualizado pelo backend a cada 10s)
+  useEffect(() => {
+    invoke<PanelStatus>('get_panel_status')
+      .then(setPanelStatus)
+      .catch((error) => console.error('Erro ao carregar estado do painel:', error));
+
+    const unlisten = listen<PanelStatus>('panel-status', (event) => setPanelStatus(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const panelContentLabel = () => {
+    const content = panelStatus?.content;
+    if (!panelStatus?.panel_open) return 'Fechado';
+    if (!content) return 'Aguardando painel';
+    if (content.view === 'failsafe') return 'Estado seguro';
+    if (content.view === 'video') return content.video_title ?? 'Vídeo';
+    return content.messages.length > 0 ? `${content.messages.length} mensagem(ns)` : 'Sem mensagens';
+  };
+
+  const videoPositionLabel = () => {
+    if (!panelStatus?.video_playing || panelStatus.video_time_s === null) return '--:--';
+    const position = formatSeconds(panelStatus.video_time_s);
+    return panelStatus.video_duration_s ? `${position} / ${formatSeconds(panelStatus.video_duration_s)}` : position;
+  };
+
+  const plcDataAgeLabel = () => {
+    const age = panelStatus?.last_plc_data_age_ms;
+    if (age === null || age === undefined) return 'Sem dados';
+    return age < 1000 ? `${age} ms` : `${(age / 1000).toFixed(1)} s`;
+  };
 
   useEffect(() => {
     loadLogs();
@@ -94,6 +198,147 @@ export const PaginaVisaoGeral: React.FC<PaginaVisaoGeralProps> = ({
         />
       </div>
 
+      {/* Painel Público - estado real reportado pelo backend */}
+      <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-4">
+        <CardModerno
+          titulo="Painel Público"
+          valor={panelStatus?.panel_open ? 'Aberto' : 'Fechado'}
+          descricao={panelStatus?.stalled ? 'Travado' : panelStatus?.degraded ? 'Desempenho degradado' : undefined}
+          icone={Monitor}
+          cor={!panelStatus?.panel_open || panelStatus.stalled ? 'vermelho' : panelStatus.degraded ? 'amarelo' : 'verde'}
+        />
+
+        <CardModerno
+          titulo="Em Exibição"
+          valor={panelContentLabel()}
+          descricao={panelStatus?.content_seconds != null ? `há ${formatSeconds(panelStatus.content_seconds)}` : undefined}
+          icone={Film}
+          cor="azul"
+        />
+
+        <CardModerno
+          titulo="Posição do Vídeo"
+          valor={videoPositionLabel()}
+          icone={Activity}
+          cor="neutro"
+        />
+
+        <CardModerno
+          titulo="Últimos Dados PLC"
+          valor={plcDataAgeLabel()}
+          icone={Radio}
+          cor={panelStatus?.plc_data_stale ? 'amarelo' : 'verde'}
+        />
+      </div>
+
+      {/* Estado seguro do painel quando os dados do PLC param */}
+      <div className="bg-white rounded-lg shadow-sm border border-edp-neutral-lighter px-6 py-4">
+        <div className="flex flex-wrap items-center justify-between gap-3">
+          <div className="flex flex-wrap items-center gap-3 text-sm">
+            <label className="flex items-center gap-2 font-medium text-edp-marine cursor-pointer">
+              <input
+                type="checkbox"
+                checked={failsafeConfig.enabled}
+                onChange={(e) => setFailsafeConfig({ ...failsafeConfig, enabled: e.target.checked })}
+                className="w-4 h-4 text-edp-marine border-gray-300 rounded focus:ring-edp-marine"
+              />
+              Estado seguro após
+            </label>
+            <input
+              type="number"
+              min="2"
+              max="3600"
+              value={failsafeConfig.timeout_s}
+              onChange={(e) => setFailsafeConfig({ ...failsafeConfig, timeout_s: parseInt(e.target.value) || 0 })}
+              className="w-20 px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white font-tabular"
+            />
+            <span className="text-edp-marine">s sem dados do PLC:</span>
+            <select
+              value={failsafeConfig.semaphore}
+              onChange={(e) => setFailsafeConfig({ ...failsafeConfig, semaphore: e.target.value as FailsafeSemaphore })}
+              className="px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white"
+            >
+              <option value="vermelho">Semáforo vermelho</option>
+              <option value="amarelo">Semáforo amarelo</option>
+              <option value="verde">Semáforo verde</option>
+              <option value="apagado">Semáforo apagado</option>
+            </select>
+            <input
+              type="text"
+              value={failsafeConfig.message}
+              onChange={(e) => setFailsafeConfig({ ...failsafeConfig, message: e.target.value })}
+              className="w-64 px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white"
+              placeholder="INFORMAÇÃO INDISPONÍVEL"
+            />
+            {failsafeState?.active && (
+              <span className="px-2 py-0.5 text-xs font-medium rounded bg-edp-semantic-light-red text-edp-semantic-red">
+                Ativo no painel
+              </span>
+            )}
+          </div>
+          <button
+            onClick={handleSaveFailsafe}
+            disabled={isSavingFailsafe}
+            className="px-3 py-1 text-xs bg-edp-marine text-white rounded hover:bg-edp-marine-100 disabled:opacity-50 transition-colors"
+          >
+            {isSavingFailsafe ? '...' : 'Salvar'}
+          </button>
+        </div>
+      </div>
+
+      {/* Health check HTTP para o monitoramento do local (Zabbix/PRTG) */}
+      <div className="bg-white rounded-lg shadow-sm border border-edp-neutral-lighter px-6 py-4">
+        <div className="flex flex-wrap items-center justify-between gap-3">
+          <div className="flex flex-wrap items-center gap-3 text-sm">
+            <label className="flex items-center gap-2 font-medium text-edp-marine cursor-pointer">
+              <input
+                type="checkbox"
+                checked={healthConfig.enabled}
+                onChange={(e) => setHealthConfig({ ...healthConfig, enabled: e.target.checked })}
+                className="w-4 h-4 text-edp-marine border-gray-300 rounded focus:ring-edp-marine"
+              />
+              Health check HTTP em
+            </label>
+            <input
+              type="text"
+              value={healthConfig.bind_host}
+              onChange={(e) => setHealthConfig({ ...healthConfig, bind_host: e.target.value })}
+              className="w-28 px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white font-tabular"
+              placeholder="0.0.0.0"
+            />
+            <span className="text-edp-marine">:</span>
+            <input
+              type="number"
+              min="1"
+              max="65535"
+              value={healthConfig.port}
+              onChange={(e) => setHealthConfig({ ...healthConfig, port: parseInt(e.target.value) || 0 })}
+              className="w-20 px-2 py-1 text-xs border border-edp-neutral-lighter rounded focus:ring-1 focus:ring-edp-marine bg-white font-tabular"
+            />
+            <span className="text-xs text-edp-neutral-medium font-tabular">/healthz</span>
+            {healthReport && (
+              <span
+                className={`px-2 py-0.5 text-xs font-medium rounded ${
+                  healthReport.healthy
+                    ? 'bg-edp-semantic-light-green text-edp-semantic-green'
+                    : 'bg-edp-semantic-light-red text-edp-semantic-red'
+                }`}
+                title={healthReport.failures.join('\n')}
+              >
+                {healthReport.healthy ? '200 OK' : `503 - ${healthReport.failures.length} falha(s)`}
+              </span>
+            )}
+          </div>
+          <button
+            onClick={handleSaveHealth}
+            disabled={isSavingHealth}
+            className="px-3 py-1 text-xs bg-edp-marine text-white rounded hover:bg-edp-marine-100 disabled:opacity-50 transition-colors"
+          >
+            {isSavingHealth ? '...' : 'Salvar'}
+          </button>
+        </div>
+      </div>
+
       {/* Logs do Sistema com Controles Integrados */}
       <div className="bg-white rounded-lg shadow-sm border border-edp-neutral-lighter overflow-hidden">
         <div className="px-6 py-4 border-b border-edp-neutral-lighter bg-edp-neutral-white-wash">
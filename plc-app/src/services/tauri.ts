@@ -1,6 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { PlcData, TextConfig, PhaseConfig } from '../types';
+import type { PlcData, TextConfig, PhaseConfig, PanelWindowInfo, PanelEvent, ActivePanelEvent } from '../types';
 
 export class TauriService {
   static async listenToPlcData(callback: (data: PlcData) => void) {
@@ -17,6 +17,14 @@ export class TauriService {
     return await invoke('close_panel_window');
   }
 
+  static async togglePanelWindow(): Promise<string> {
+    return await invoke('toggle_panel_window');
+  }
+
+  static async getPanelWindowState(): Promise<PanelWindowInfo> {
+    return await invoke('get_panel_window_state');
+  }
+
   static async getAllTexts(): Promise<TextConfig[]> {
     return await invoke('get_all_texts');
   }
@@ -32,4 +40,34 @@ export class TauriService {
   static async updatePhase(phaseNumber: number, title: string, description: string, color: string): Promise<string> {
     return await invoke('update_phase', { phaseNumber, title, description, color });
   }
+
+  // Eventos programados (textos/vídeos/tema por período)
+  static async getAllPanelEvents(): Promise<PanelEvent[]> {
+    return await invoke('get_all_panel_events');
+  }
+
+  static async addPanelEvent(event: PanelEvent): Promise<string> {
+    return await invoke('add_panel_event', { event });
+  }
+
+  static async updatePanelEvent(event: PanelEvent): Promise<string> {
+    return await invoke('update_panel_event', { event });
+  }
+
+  static async deletePanelEvent(id: number): Promise<string> {
+    return await invoke('delete_panel_event', { id });
+  }
+
+  static async getActivePanelEvent(): Promise<ActivePanelEvent | null> {
+    return await invoke('get_active_panel_event');
+  }
+
+  // WORD com o número da fase atual ({phase_number}/{phase_title} nos templates)
+  static async getPhaseWordIndex(): Promise<number | null> {
+    return await invoke('get_phase_word_index');
+  }
+
+  static async setPhaseWordIndex(wordIndex: number | null): Promise<string> {
+    return await invoke('set_phase_word_index', { wordIndex });
+  }
 }
\ No newline at end of file
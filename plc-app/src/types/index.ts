@@ -49,6 +49,7 @@ export interface BitConfig {
   letter_spacing: number;  // Espaçamento entre letras (px)
   use_template: boolean;   // Se true, usa message_template com variáveis
   message_template: string; // Template com tags {Word[N]}, ex: "Velocidade: {Word[10]} km/h"
+  condition: string;       // Combinação de bits, ex: "Word[3].2 AND NOT Word[1].0" (vazio = só este bit)
 }
 
 export interface BitStatus {
@@ -66,6 +67,25 @@ export interface VideoConfig {
   priority: number;        // Prioridade de exibição
   description: string;     // Descrição do vídeo
   display_order: number;   // Ordem de exibição
+  volume: number;          // Volume do áudio (0-100)
+  muted: boolean;          // Áudio desligado neste vídeo
+}
+
+// Horário de silêncio global (display_configs quiet_hours_*)
+export interface QuietHoursConfig {
+  enabled: boolean;
+  start: string;           // "HH:MM"
+  end: string;             // "HH:MM" (antes do início = atravessa a meia-noite)
+  max_volume: number;      // Volume máximo no horário (0 = mudo)
+}
+
+// Áudio efetivo calculado no backend (evento audio-policy)
+export interface AudioPolicy {
+  quiet: boolean;
+  max_volume: number;
+  quiet_start: string;
+  quiet_end: string;
+  videos: { id: number; volume: number; muted: boolean }[];
 }
 
 export interface SystemLog {
@@ -75,4 +95,182 @@ export interface SystemLog {
   category: string;        // "plc", "tcp", "database", "ui"
   message: string;         // Mensagem de log
   details: string;         // Detalhes adicionais
-}
\ No newline at end of file
+}
+export interface PanelTheme {
+  id: number;
+  name: string;
+  background_color: string;    // Cor de fundo do painel
+  background_image: string;    // Caminho da imagem de fundo (vazio = sem imagem)
+  font_family: string;         // Fonte padrão das mensagens
+  font_weight: string;         // 'normal', 'bold', 'black'
+  text_color: string;          // Cor padrão de textos
+  override_bit_fonts: boolean; // Fonte do tema substitui a de cada bit
+  logo_path: string;           // Caminho do logotipo (vazio = sem logo)
+  logo_position: 'top-left' | 'top-right' | 'bottom-left' | 'bottom-right';
+}
+
+export interface AnalogDisplay {
+  id: number;
+  label: string;           // Rótulo exibido (ex: "Nível")
+  word_index: number;      // WORD de origem
+  scale: number;           // valor = word * scale + offset
+  offset: number;
+  signed: boolean;         // WORD como inteiro com sinal (i16)
+  min_value: number;       // Faixa válida do valor
+  max_value: number;
+  unit: string;            // Unidade (ex: "m", "km/h")
+  decimals: number;        // Casas decimais
+  enabled: boolean;
+  display_order: number;
+}
+
+// Eventos programados do painel (tabela panel_events)
+export interface PanelEvent {
+  id: number;
+  name: string;
+  starts_at: string;      // Horário local "AAAA-MM-DDTHH:MM" (input datetime-local)
+  ends_at: string;
+  messages: string[];     // Exibidos quando não há mensagens de bits ativas
+  video_ids: number[];    // Playlist do evento (vazia = playlist normal)
+  theme_id: number | null;
+  priority: number;       // Eventos sobrepostos: vale o de maior prioridade
+  enabled: boolean;
+}
+
+// Evento em vigor resolvido no backend (evento panel-event; null = conteúdo normal)
+export interface ActivePanelEvent {
+  id: number;
+  name: string;
+  ends_at: string;
+  messages: string[];
+  videos: VideoConfig[];
+  theme: PanelTheme | null;
+}
+
+// Transições do painel (tabela transition_configs)
+export interface TransitionConfig {
+  id: number;
+  name: string;
+  scope: 'playlist' | 'message'; // Entre vídeos/troca de tela ou por classe de prioridade das mensagens
+  min_priority: number;          // "message": mensagens com prioridade >= min_priority
+  effect: 'fade' | 'cut' | 'slide';
+  duration_ms: number;           // Ignorada em "cut"
+  enabled: boolean;
+}
+
+// Efeitos resolvidos no backend (evento plc-data -> transitions)
+export interface Transition {
+  effect: 'fade' | 'cut' | 'slide';
+  duration_ms: number;
+}
+
+export interface PanelTransitions {
+  playlist: Transition;
+  message: Transition;     // Classe da mensagem ativa de maior prioridade
+}
+
+// Valor calculado no backend (evento plc-data -> analog_values)
+export interface AnalogReading {
+  id: number;
+  label: string;
+  value: number | null;    // null = WORD ainda não recebida
+  raw: number | null;
+  unit: string;
+  formatted: string;       // Texto pronto, ex: "Nível: 3.2 m"
+  percent: number;         // 0-100 dentro de min/max
+  out_of_range: boolean;
+}
+
+export interface CountdownTimer {
+  id: number;
+  name: string;            // Tag nos templates: {Timer:nome}
+  label: string;           // Ex: "Tempo restante de eclusagem"
+  source: 'word' | 'phase'; // WORD com segundos restantes ou contagem desde o início da fase
+  word_index: number;
+  phase_number: number;    // "phase": valor da WORD que indica a fase
+  duration_s: number;      // "phase": duração da fase em segundos
+  enabled: boolean;
+}
+
+// Janela do painel público (get_panel_window_state / evento panel-window)
+export interface PanelWindowInfo {
+  open: boolean;
+  focused: boolean;
+  opened_at: string | null;
+  closed_at: string | null;
+  open_count: number;
+}
+
+// Estado seguro do painel com dados do PLC velhos (display_configs failsafe_*)
+export type FailsafeSemaphore = 'vermelho' | 'amarelo' | 'verde' | 'apagado';
+
+export interface FailsafeConfig {
+  enabled: boolean;
+  timeout_s: number;          // Segundos sem plc-data até entrar no estado seguro
+  message: string;
+  semaphore: FailsafeSemaphore;
+}
+
+// Enviado pelo backend na entrada/saída (evento panel-failsafe)
+export interface FailsafeState {
+  active: boolean;
+  since: string | null;
+  stale_seconds: number | null;
+  message: string;
+  semaphore: FailsafeSemaphore;
+}
+
+// Endpoint /healthz para o monitoramento do local (Zabbix/PRTG)
+export interface HealthEndpointConfig {
+  enabled: boolean;
+  bind_host: string;
+  port: number;
+}
+
+export interface HealthReport {
+  healthy: boolean;
+  status: 'ok' | 'unhealthy';
+  timestamp: string;
+  panel_open: boolean;
+  plc_data_age_ms: number | null;
+  plc_data_fresh: boolean;
+  database_ok: boolean;
+  database_error: string | null;
+  failsafe_active: boolean;
+  display_stalled: boolean;
+  failures: string[];
+}
+
+// Conteúdo em exibição informado pelo painel (evento panel-content)
+export interface PanelContent {
+  view: 'plc' | 'video' | 'failsafe';
+  video_id: number | null;
+  video_title: string | null;
+  messages: string[];
+}
+
+// Estado real do painel público (get_panel_status / evento panel-status)
+export interface PanelStatus {
+  panel_open: boolean;
+  content: PanelContent | null;
+  content_seconds: number | null;
+  video_playing: boolean;
+  video_time_s: number | null;
+  video_duration_s: number | null;
+  seconds_since_report: number | null;
+  last_plc_data_age_ms: number | null;
+  plc_data_stale: boolean;
+  degraded: boolean;
+  stalled: boolean;
+}
+
+// Enviado pelo backend a cada segundo (evento countdown-update)
+export interface CountdownValue {
+  id: number;
+  name: string;
+  label: string;
+  active: boolean;
+  remaining_s: number | null;
+  formatted: string;       // "12:30"
+  text: string;            // "Tempo restante de eclusagem: 12:30"
+}
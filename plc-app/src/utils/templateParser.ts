@@ -5,16 +5,39 @@
  * - Template: "Velocidade: {Word[10]} km/h - Distância: {Word[11]} m"
  * - PlcData: { variables: { "Word[10]": 85, "Word[11]": 120 } }
  * - Resultado: "Velocidade: 85 km/h - Distância: 120 m"
+ *
+ * Contadores regressivos do backend usam {Timer:nome}, ex: "Tempo restante: {Timer:eclusagem}"
+ *
+ * Placeholders calculados no backend (template_values do evento plc-data):
+ * {time}, {date}, {phase_number}, {phase_title} e variáveis de data_mappings, ex: {speed_kmh}
  */
 
 export interface PlcVariables {
   [key: string]: number;
 }
 
+// Nome do contador -> valor formatado ("12:30")
+export interface TimerValues {
+  [name: string]: string;
+}
+
+// Placeholder calculado -> texto pronto ("14:30", "Enchimento", "12.5")
+export interface TemplateValues {
+  [name: string]: string;
+}
+
+// Exemplos para o preview do editor
+const PREVIEW_VALUES: TemplateValues = {
+  time: '14:30',
+  date: '16/10/2026',
+  phase_number: '2',
+  phase_title: 'Enchimento',
+};
+
 /**
- * Substitui tags {Word[N]} no template pelos valores reais
+ * Substitui tags {Word[N]}, {Timer:nome} e placeholders calculados ({time}, {phase_title}...) pelos valores reais
  */
-export function parseTemplate(template: string, variables: PlcVariables): string {
+export function parseTemplate(template: string, variables: PlcVariables, timers: TimerValues = {}, computed: TemplateValues = {}): string {
   if (!template) return '';
   
   // Regex para encontrar tags {Word[N]} onde N é um número
@@ -27,7 +50,8 @@ export function parseTemplate(template: string, variables: PlcVariables): string
     // Se a variável existe no PLC, substitui pelo valor
     // Senão, mantém a tag para indicar que está aguardando dados
     return value !== undefined ? String(value) : match;
-  });
+  }).replace(/\{Timer:(\w+)\}/g, (match, name) => timers[name] ?? match)
+    .replace(/\{(\w+)\}/g, (match, name) => computed[name] ?? match);
 }
 
 /**
@@ -78,9 +102,9 @@ export function validateTemplate(template: string): string[] {
   }
   
   // Verifica se há { sem fechar corretamente
-  const invalidTags = template.match(/\{(?!Word\[\d+\]\})[^}]*\}/g);
+  const invalidTags = template.match(/\{(?!Word\[\d+\]\}|Timer:\w+\}|\w+\})[^}]*\}/g);
   if (invalidTags) {
-    errors.push(`Tags inválidas encontradas: ${invalidTags.join(', ')} - Use formato {Word[N]}`);
+    errors.push(`Tags inválidas encontradas: ${invalidTags.join(', ')} - Use formato {Word[N]}, {Timer:nome} ou {nome}`);
   }
   
   return errors;
@@ -98,5 +122,6 @@ export function previewTemplate(template: string): string {
     // Valores simulados para preview
     const simulatedValue = Math.floor(Math.random() * 100);
     return String(simulatedValue);
-  });
+  }).replace(/\{Timer:\w+\}/g, '12:30')
+    .replace(/\{(\w+)\}/g, (match, name) => PREVIEW_VALUES[name] ?? String(Math.floor(Math.random() * 100)));
 }
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Ordem dos bytes dos valores numéricos no frame (exemplo com DWORD 0xAABBCCDD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ByteOrder {
+    #[default]
+    Big,          // AA BB CC DD (Siemens S7)
+    Little,       // DD CC BB AA (Beckhoff, PCs)
+    ByteSwapped,  // BB AA DD CC (bytes trocados dentro de cada WORD)
+    WordSwapped,  // CC DD AA BB (WORDs em ordem inversa, comum em gateways Modbus)
+}
+
+impl ByteOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ByteOrder::Big => "big",
+            ByteOrder::Little => "little",
+            ByteOrder::ByteSwapped => "byte-swapped",
+            ByteOrder::WordSwapped => "word-swapped",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "big" => Some(ByteOrder::Big),
+            "little" => Some(ByteOrder::Little),
+            "byte-swapped" => Some(ByteOrder::ByteSwapped),
+            "word-swapped" => Some(ByteOrder::WordSwapped),
+            _ => None,
+        }
+    }
+}
+
+/// Converte um valor entre a ordem do PLC e big-endian (a conversão é a
+/// mesma nos dois sentidos). Valores de 1 byte não mudam.
+pub fn to_big_endian(order: ByteOrder, bytes: &mut [u8]) {
+    match order {
+        ByteOrder::Big => {}
+        ByteOrder::Little => bytes.reverse(),
+        ByteOrder::ByteSwapped => bytes.chunks_exact_mut(2).for_each(|word| word.swap(0, 1)),
+        ByteOrder::WordSwapped => {
+            let words = bytes.len() / 2;
+            for i in 0..words / 2 {
+                let j = words - 1 - i;
+                bytes.swap(i * 2, j * 2);
+                bytes.swap(i * 2 + 1, j * 2 + 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::framing::{split_frame, FrameMode, FrameSplit};
+use crate::parser::{backfill_frame_len, is_backfill_frame, split_fixed_frame};
+use crate::structure::PlcStructureConfig;
+use crate::write_protocol::{is_partial_write_ack, parse_write_ack, WRITE_ACK_SIZE};
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+// ============================================================================
+// LEITURA DA CONEXÃO COM O PLC - SOCKET, ESCRITAS E CORTE DOS FRAMES
+// ============================================================================
+//
+// O laço de cada conexão é o mesmo nos dois apps: `read_or_write` espera a
+// próxima leitura ou envia a escrita que chegar antes pelo mesmo socket, e o
+// `FrameReader` acumula os bytes e devolve, a cada fronteira de frame:
+//   WACK     → só com escrita pendente e "WACK" + seq + status completos
+//   backfill → frame "BKFL" inteiro (quando habilitado)
+//   dados    → cortado pelo framing da estrutura; sem estrutura, a leitura inteira
+// O accept continua em cada app: bloqueio e IDs de reconexão no plc-hmi; o
+// plc-app também conecta como cliente e negocia handshake/protocolo.
+
+/// Tempo máximo para enviar um frame de escrita ao PLC
+pub const WRITE_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SocketEvent {
+    Read(usize),       // Bytes lidos em buffer[..n]
+    WriteSent(usize),  // Frame de escrita enviado (bytes)
+    Closed,            // PLC encerrou a conexão
+    ReadTimeout,       // Nada recebido dentro do timeout de leitura
+}
+
+/// Espera a próxima leitura do PLC ou envia pelo mesmo socket a escrita que chegar antes
+pub async fn read_or_write<S>(
+    socket: &mut S,
+    buffer: &mut [u8],
+    writes: &mut mpsc::Receiver<Vec<u8>>,
+    read_timeout: Duration,
+) -> io::Result<SocketEvent>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    tokio::select! {
+        Some(frame) = writes.recv() => {
+            match tokio::time::timeout(WRITE_SEND_TIMEOUT, socket.write_all(&frame)).await {
+                Ok(Ok(())) => Ok(SocketEvent::WriteSent(frame.len())),
+                Ok(Err(e)) => Err(io::Error::new(e.kind(), format!("Erro ao enviar escrita: {}", e))),
+                Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut,
+                    format!("Escrita não enviada em {}s", WRITE_SEND_TIMEOUT.as_secs()))),
+            }
+        }
+        result = tokio::time::timeout(read_timeout, socket.read(buffer)) => match result {
+            Ok(Ok(0)) => Ok(SocketEvent::Closed),
+            Ok(Ok(n)) => Ok(SocketEvent::Read(n)),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Ok(SocketEvent::ReadTimeout),
+        },
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameEvent {
+    WriteAck { seq: u16, status: u8 },  // Confirmação de escrita (WACK)
+    Backfill(Vec<u8>),                  // Frame "BKFL" completo
+    Frame(Vec<u8>),                     // Payload de dados
+    Invalid(String),                    // Framing impossível: acumulador descartado
+}
+
+/// Acumulador de uma conexão: recebe as leituras e devolve os itens completos
+pub struct FrameReader {
+    accumulator: Vec<u8>,
+    max_frame: usize,
+    max_backfill: Option<usize>, // None = conexão sem backfill ("BKFL" é dado)
+}
+
+impl FrameReader {
+    /// `accumulator` pode vir de um pool (devolvido por `into_buffer`)
+    pub fn new(mut accumulator: Vec<u8>, max_frame: usize, max_backfill: Option<usize>) -> Self {
+        accumulator.clear();
+        Self { accumulator, max_frame, max_backfill }
+    }
+
+    pub fn len(&self) -> usize {
+        self.accumulator.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accumulator.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.accumulator.clear();
+    }
+
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.accumulator
+    }
+
+    /// Acrescenta uma leitura; acima do limite o acumulador é descartado e volta Err
+    pub fn push(&mut self, data: &[u8]) -> Result<(), String> {
+        // Frames de backfill podem ser bem maiores que os frames normais
+        let pending = if self.accumulator.is_empty() { data } else { &self.accumulator[..] };
+        let limit = match self.max_backfill {
+            Some(max_backfill) if is_backfill_frame(pending) => max_backfill,
+            _ => self.max_frame,
+        };
+        let total = self.accumulator.len() + data.len();
+        if total > limit {
+            self.accumulator.clear();
+            return Err(format!("acumulador estourou ({} > {} bytes)", total, limit));
+        }
+        self.accumulator.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Próximo item completo no início do acumulador (sempre uma fronteira de
+    /// frame); None = esperar a próxima leitura. `write_pending` diz se há
+    /// escrita aguardando WACK nesta conexão: sem ela, "WACK..." é dado.
+    pub fn next_event(&mut self, config: Option<&PlcStructureConfig>, write_pending: bool) -> Option<FrameEvent> {
+        if self.accumulator.is_empty() {
+            return None;
+        }
+
+        if write_pending {
+            if let Some((seq, status)) = parse_write_ack(&self.accumulator) {
+                self.accumulator.drain(..WRITE_ACK_SIZE);
+                return Some(FrameEvent::WriteAck { seq, status });
+            }
+            if is_partial_write_ack(&self.accumulator) {
+                return None;
+            }
+        }
+
+        if self.max_backfill.is_some() && is_backfill_frame(&self.accumulator) {
+            let frame_len = backfill_frame_len(&self.accumulator)?;
+            return Some(FrameEvent::Backfill(self.accumulator.drain(..frame_len).collect()));
+        }
+
+        let (framing, byte_order) = config.map(|c| (c.framing, c.byte_order)).unwrap_or_default();
+        let split = match (framing, config) {
+            // Fixo: byte de tipo do perfil ou tamanho exato, só esse frame sai do acumulador
+            (FrameMode::Fixed, Some(config)) => split_fixed_frame(config, &mut self.accumulator),
+            // Sem estrutura: a leitura inteira vira um frame
+            (FrameMode::Fixed, None) => FrameSplit::Frame(self.accumulator.drain(..).collect()),
+            _ => split_frame(framing, byte_order, &mut self.accumulator, self.max_frame),
+        };
+        match split {
+            FrameSplit::Frame(frame) => Some(FrameEvent::Frame(frame)),
+            FrameSplit::Incomplete => None,
+            FrameSplit::Invalid(reason) => {
+                self.accumulator.clear();
+                Some(FrameEvent::Invalid(reason))
+            }
+        }
+    }
+}
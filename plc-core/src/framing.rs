@@ -0,0 +1,109 @@
+use crate::byte_order::{to_big_endian, ByteOrder};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// SEPARAÇÃO DE FRAMES POR CONEXÃO (FRAMING)
+// ============================================================================
+//
+// Como o fluxo TCP de cada PLC é cortado em frames:
+//   fixed            → tamanho conhecido da estrutura/perfis (`parser::split_fixed_frame`)
+//   length-prefix-2  → tamanho do payload (u16) | payload
+//   length-prefix-4  → tamanho do payload (u32) | payload
+//   newline          → payload | '\n'  ("\r\n" também aceito)
+//   stx-etx          → 0x02 | payload | 0x03 (bytes antes do STX são descartados)
+// O prefixo de tamanho segue a ordem de bytes da estrutura. Escritas (WACK) e
+// backfill são reconhecidos antes, na fronteira de frame (`connection`).
+
+const STX: u8 = 0x02;
+const ETX: u8 = 0x03;
+
+/// Como o servidor TCP separa os frames de dados de uma conexão
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrameMode {
+    #[default]
+    Fixed,          // Frame = total_size (ou tamanho de um perfil), sem cabeçalho
+    LengthPrefix2,  // 2 bytes de tamanho do payload antes de cada frame
+    LengthPrefix4,  // 4 bytes de tamanho do payload antes de cada frame
+    Newline,        // Payload terminado em '\n' (um '\r' antes dele é descartado)
+    StxEtx,         // STX (0x02) + payload + ETX (0x03)
+}
+
+impl FrameMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameMode::Fixed => "fixed",
+            FrameMode::LengthPrefix2 => "length-prefix-2",
+            FrameMode::LengthPrefix4 => "length-prefix-4",
+            FrameMode::Newline => "newline",
+            FrameMode::StxEtx => "stx-etx",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fixed" => Some(FrameMode::Fixed),
+            "length-prefix-2" => Some(FrameMode::LengthPrefix2),
+            "length-prefix-4" => Some(FrameMode::LengthPrefix4),
+            "newline" => Some(FrameMode::Newline),
+            "stx-etx" => Some(FrameMode::StxEtx),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameSplit {
+    Frame(Vec<u8>),   // Payload completo, já removido do acumulador
+    Incomplete,       // Faltam bytes: esperar a próxima leitura
+    Invalid(String),  // Cabeçalho impossível: o acumulador deve ser descartado
+}
+
+/// Retira do início de `accumulator` o próximo payload delimitado (modos diferentes de `fixed`)
+pub fn split_frame(mode: FrameMode, byte_order: ByteOrder, accumulator: &mut Vec<u8>, max_payload: usize) -> FrameSplit {
+    let prefix_len = match mode {
+        FrameMode::LengthPrefix2 => 2,
+        FrameMode::LengthPrefix4 => 4,
+        FrameMode::Newline => {
+            let Some(end) = accumulator.iter().position(|&b| b == b'\n') else {
+                return FrameSplit::Incomplete;
+            };
+            let mut frame: Vec<u8> = accumulator.drain(..=end).collect();
+            frame.pop();
+            if frame.last() == Some(&b'\r') {
+                frame.pop();
+            }
+            return FrameSplit::Frame(frame);
+        }
+        FrameMode::StxEtx => {
+            let Some(start) = accumulator.iter().position(|&b| b == STX) else {
+                accumulator.clear(); // Lixo sem início de frame
+                return FrameSplit::Incomplete;
+            };
+            accumulator.drain(..start);
+            let Some(end) = accumulator.iter().position(|&b| b == ETX) else {
+                return FrameSplit::Incomplete;
+            };
+            let frame = accumulator[1..end].to_vec();
+            accumulator.drain(..=end);
+            return FrameSplit::Frame(frame);
+        }
+        FrameMode::Fixed => return FrameSplit::Invalid("Framing fixo não usa delimitadores".to_string()),
+    };
+
+    if accumulator.len() < prefix_len {
+        return FrameSplit::Incomplete;
+    }
+    let mut prefix = accumulator[..prefix_len].to_vec();
+    to_big_endian(byte_order, &mut prefix);
+    let payload_len = prefix.iter().fold(0usize, |len, &b| (len << 8) | b as usize);
+    if payload_len == 0 || payload_len > max_payload {
+        return FrameSplit::Invalid(format!("Prefixo de tamanho inválido: {} bytes (máx {})", payload_len, max_payload));
+    }
+    if accumulator.len() < prefix_len + payload_len {
+        return FrameSplit::Incomplete;
+    }
+    let frame = accumulator[prefix_len..prefix_len + payload_len].to_vec();
+    accumulator.drain(..prefix_len + payload_len);
+    FrameSplit::Frame(frame)
+}
@@ -0,0 +1,30 @@
+// ============================================================================
+// PLC-CORE - PROTOCOLO DE FIO COMPARTILHADO ENTRE PLC-HMI E PLC-APP
+// ============================================================================
+//
+// Os dois aplicativos recebem dados do PLC por TCP e escrevem de volta pelo
+// mesmo socket. O formato no fio e a leitura da conexão moram aqui para uma
+// correção valer nos dois:
+// `byte_order`: ordens de bytes dos PLCs e conversão para big-endian.
+// `framing`: modos de separação de frames no fluxo TCP e o corte delimitado.
+// `structure`: blocos e perfis da estrutura do frame.
+// `parser`: decodificação do frame, corte fixo, backfill e seletores de variable_path.
+// `packet`: leitura do frame como WORDs (sem estrutura configurada).
+// `write_protocol`: frames de escrita "WRTE" e confirmações "WACK".
+// `connection`: laço de leitura/escrita do socket e corte dos frames por conexão.
+// O accept continua em cada app: o plc-hmi bloqueia IPs e mantém IDs de
+// reconexão; o plc-app também conecta como cliente e negocia handshake/protocolo.
+
+pub mod byte_order;
+pub mod connection;
+pub mod framing;
+pub mod packet;
+pub mod parser;
+pub mod structure;
+pub mod write_protocol;
+
+pub use byte_order::{to_big_endian, ByteOrder};
+pub use connection::{read_or_write, FrameEvent, FrameReader, SocketEvent};
+pub use framing::{split_frame, FrameMode, FrameSplit};
+pub use packet::decode_words;
+pub use structure::{DataBlockConfig, FrameProfile, PlcStructureConfig};
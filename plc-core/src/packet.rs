@@ -0,0 +1,21 @@
+use crate::byte_order::{to_big_endian, ByteOrder};
+
+// ============================================================================
+// PACOTE DE DADOS DO PLC - WORDS
+// ============================================================================
+//
+// Sem estrutura configurada os dois apps leem o frame como uma sequência de
+// WORDs (plc-hmi na detecção automática, plc-app no modo binário). A leitura
+// mora aqui para a ordem de bytes ser aplicada do mesmo jeito nos dois.
+
+/// WORDs do frame na ordem de bytes do PLC (byte final ímpar é ignorado)
+pub fn decode_words(data: &[u8], order: ByteOrder, max_words: usize) -> Vec<u16> {
+    data.chunks_exact(2)
+        .take(max_words)
+        .map(|pair| {
+            let mut word = [pair[0], pair[1]];
+            to_big_endian(order, &mut word);
+            u16::from_be_bytes(word)
+        })
+        .collect()
+}
@@ -0,0 +1,935 @@
+use crate::byte_order::ByteOrder;
+use crate::framing::FrameMode;
+use crate::structure::{DataBlockConfig, PlcStructureConfig};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// ============================================================================
+// PARSER DO FRAME DO PLC - ESTRUTURA, PERFIS, BACKFILL E variable_path
+// ============================================================================
+//
+// Decodifica o frame com a estrutura configurada (ou por detecção automática),
+// corta frames de tamanho fixo e resolve os seletores de variable_path. Mora
+// no plc-core para o plc-hmi e o plc-app lerem os frames do mesmo jeito.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlcVariable {
+    pub name: String,
+    pub value: String,
+    pub data_type: String,
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlcDataPacket {
+    pub ip: String,
+    pub timestamp: u64,
+    // Bytes brutos não vão para a UI em cada pacote - usar `get_latest_raw_frame`
+    #[serde(skip_serializing, default)]
+    pub raw_data: Vec<u8>,
+    pub size: usize,
+    pub variables: Vec<PlcVariable>,
+}
+
+/// Converte bytes para WORD (16-bit unsigned)
+fn bytes_to_word(high_byte: u8, low_byte: u8) -> u16 {
+    // PLCs geralmente usam big-endian (high byte primeiro)
+    ((high_byte as u16) << 8) | (low_byte as u16)
+}
+
+/// 🆕 Conversão entre a ordem do PLC e big-endian (igual no plc-app)
+pub use crate::byte_order::to_big_endian;
+
+/// Tamanho em bytes de cada tipo suportado em `DataBlockConfig`
+pub fn data_type_size(data_type: &str) -> Option<usize> {
+    match data_type {
+        "BYTE" => Some(1),
+        "WORD" | "INT" => Some(2),
+        "DWORD" | "DINT" | "REAL" => Some(4),
+        "LWORD" | "LINT" | "LREAL" => Some(8),
+        _ => match time_type(data_type) {
+            Some(time) => Some(time.size()),
+            None => text_type(data_type).map(|text| text.size()),
+        },
+    }
+}
+
+/// Soma o tamanho de uma lista de blocos (erro se houver tipo inválido)
+pub fn blocks_total_size(blocks: &[DataBlockConfig]) -> Result<usize, String> {
+    let mut total_size = 0;
+    for block in blocks {
+        let type_size = data_type_size(&block.data_type)
+            .ok_or_else(|| format!("Tipo inválido: {}", block.data_type))?;
+        total_size += type_size * block.count as usize;
+    }
+    Ok(total_size)
+}
+
+/// Seleciona a estrutura a usar para este frame:
+/// 1. Perfil cujo byte de tipo confere (e cabe no frame)
+/// 2. Perfil cujo tamanho é exatamente o do frame
+/// 3. Estrutura principal se o tamanho confere
+pub fn select_frame_layout<'a>(config: &'a PlcStructureConfig, raw_data: &[u8]) -> Option<(&'a str, &'a [DataBlockConfig])> {
+    let data_len = raw_data.len();
+    
+    for profile in &config.profiles {
+        if let (Some(offset), Some(value)) = (profile.type_byte_offset, profile.type_byte_value) {
+            if offset < data_len && raw_data[offset] == value && profile.total_size == data_len {
+                return Some((profile.name.as_str(), &profile.blocks));
+            }
+        }
+    }
+    
+    for profile in &config.profiles {
+        if profile.type_byte_offset.is_none() && profile.total_size == data_len {
+            return Some((profile.name.as_str(), &profile.blocks));
+        }
+    }
+    
+    if config.total_size == data_len {
+        return Some(("default", &config.blocks));
+    }
+    
+    None
+}
+
+/// Tamanhos de frame válidos para um PLC (estrutura principal + perfis)
+pub fn known_frame_sizes(config: &PlcStructureConfig) -> Vec<usize> {
+    let mut sizes: Vec<usize> = std::iter::once(config.total_size)
+        .chain(config.profiles.iter().map(|p| p.total_size))
+        .filter(|s| *s > 0)
+        .collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes
+}
+
+// ============================================================================
+// FRAME DE BACKFILL (DADOS BUFERIZADOS NO PLC DURANTE QUEDA DO HMI)
+// ============================================================================
+//
+// Após reconectar, o PLC pode enviar as amostras que guardou enquanto o HMI
+// estava fora, cada uma com o seu timestamp original:
+//
+//   "BKFL" (4 bytes) | quantidade de registros (u16)
+//   por registro: ts_ms Unix (u64) | tamanho do frame (u16) | frame (mesma estrutura dos frames normais)
+//
+// Tudo big-endian, como os frames normais.
+
+pub const BACKFILL_MAGIC: &[u8; 4] = b"BKFL";
+const BACKFILL_HEADER_SIZE: usize = 6;
+const BACKFILL_RECORD_HEADER_SIZE: usize = 10;
+
+/// Registro de backfill já parseado
+#[derive(Debug, Clone)]
+pub struct BackfillRecord {
+    pub ts_ms: i64,
+    pub variables: Vec<PlcVariable>,
+}
+
+/// Indica se os bytes acumulados são (o início de) um frame de backfill
+pub fn is_backfill_frame(raw_data: &[u8]) -> bool {
+    let len = raw_data.len().min(BACKFILL_MAGIC.len());
+    len > 0 && raw_data[..len] == BACKFILL_MAGIC[..len]
+}
+
+/// Tamanho total do frame de backfill, ou None se ainda faltam bytes
+pub fn backfill_frame_len(raw_data: &[u8]) -> Option<usize> {
+    if raw_data.len() < BACKFILL_HEADER_SIZE || !raw_data.starts_with(BACKFILL_MAGIC) {
+        return None;
+    }
+    let count = u16::from_be_bytes([raw_data[4], raw_data[5]]) as usize;
+    let mut offset = BACKFILL_HEADER_SIZE;
+    for _ in 0..count {
+        if offset + BACKFILL_RECORD_HEADER_SIZE > raw_data.len() {
+            return None;
+        }
+        let frame_len = u16::from_be_bytes([raw_data[offset + 8], raw_data[offset + 9]]) as usize;
+        offset += BACKFILL_RECORD_HEADER_SIZE + frame_len;
+    }
+    (offset <= raw_data.len()).then_some(offset)
+}
+
+/// Parseia um frame de backfill completo. Registros cujo frame não bate com
+/// nenhum layout da estrutura são contados em `rejected` e ignorados.
+pub fn parse_backfill_frame(raw_data: &[u8], config: &PlcStructureConfig) -> Result<(Vec<BackfillRecord>, usize), String> {
+    let total_len = backfill_frame_len(raw_data)
+        .ok_or_else(|| "Frame de backfill incompleto".to_string())?;
+    let count = u16::from_be_bytes([raw_data[4], raw_data[5]]) as usize;
+
+    let mut records = Vec::with_capacity(count);
+    let mut rejected = 0;
+    let mut offset = BACKFILL_HEADER_SIZE;
+    while offset < total_len {
+        let mut ts_bytes = [0u8; 8];
+        ts_bytes.copy_from_slice(&raw_data[offset..offset + 8]);
+        let ts_ms = u64::from_be_bytes(ts_bytes) as i64;
+        let frame_len = u16::from_be_bytes([raw_data[offset + 8], raw_data[offset + 9]]) as usize;
+        let frame = &raw_data[offset + BACKFILL_RECORD_HEADER_SIZE..offset + BACKFILL_RECORD_HEADER_SIZE + frame_len];
+        offset += BACKFILL_RECORD_HEADER_SIZE + frame_len;
+
+        match select_frame_layout(config, frame) {
+            Some((_, blocks)) => records.push(BackfillRecord {
+                ts_ms,
+                variables: parse_with_config(frame, blocks, config.byte_order),
+            }),
+            None => rejected += 1,
+        }
+    }
+    Ok((records, rejected))
+}
+
+// 🆕 Separação de frames por conexão (framing): `PlcStructureConfig.framing`
+// diz como o fluxo TCP de cada PLC é cortado. Os modos delimitados ficam em `framing`.
+pub use crate::framing::{split_frame, FrameSplit};
+
+/// Tamanhos de frame sem byte de tipo (estrutura principal + perfis só por tamanho)
+pub fn untyped_frame_sizes(config: &PlcStructureConfig) -> Vec<usize> {
+    let mut sizes: Vec<usize> = std::iter::once(config.total_size)
+        .chain(config.profiles.iter().filter(|p| p.type_byte_offset.is_none()).map(|p| p.total_size))
+        .filter(|s| *s > 0)
+        .collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes
+}
+
+/// Framing fixo: o fluxo só é cortado sem ambiguidade se no máximo um tamanho dispensa o byte de tipo
+pub fn validate_fixed_framing(config: &PlcStructureConfig) -> Result<(), String> {
+    let untyped = untyped_frame_sizes(config);
+    if config.framing == FrameMode::Fixed && untyped.len() > 1 {
+        return Err(format!(
+            "Framing fixo com frames de {:?} bytes sem byte de tipo: informe o byte de tipo dos perfis ou use um framing delimitado",
+            untyped));
+    }
+    Ok(())
+}
+
+fn take_fixed(accumulator: &mut Vec<u8>, size: usize) -> FrameSplit {
+    if accumulator.len() < size {
+        return FrameSplit::Incomplete;
+    }
+    FrameSplit::Frame(accumulator.drain(..size).collect())
+}
+
+/// 🆕 Retira do início de `accumulator` o próximo frame do modo `fixed`, na
+/// mesma ordem de `select_frame_layout`:
+/// 1. Perfil cujo byte de tipo confere → exatamente o tamanho desse perfil
+/// 2. Um único tamanho sem byte de tipo → esse tamanho
+/// 3. Vários tamanhos sem byte de tipo (estruturas antigas; o save exige byte
+///    de tipo): o conteúdo não separa os frames, vale o fim da leitura
+pub fn split_fixed_frame(config: &PlcStructureConfig, accumulator: &mut Vec<u8>) -> FrameSplit {
+    let pending = accumulator.len();
+    if pending == 0 {
+        return FrameSplit::Incomplete;
+    }
+
+    let mut waiting_type_byte = false;
+    for profile in config.profiles.iter().filter(|p| p.total_size > 0) {
+        if let (Some(offset), Some(value)) = (profile.type_byte_offset, profile.type_byte_value) {
+            if offset >= pending {
+                waiting_type_byte = true;
+            } else if accumulator[offset] == value {
+                return take_fixed(accumulator, profile.total_size);
+            }
+        }
+    }
+
+    match untyped_frame_sizes(config).as_slice() {
+        [] if waiting_type_byte => FrameSplit::Incomplete,
+        [] => FrameSplit::Invalid(format!("Byte de tipo não corresponde a nenhum perfil ({} bytes)", pending)),
+        [size] => take_fixed(accumulator, *size),
+        sizes => {
+            let max_size = sizes[sizes.len() - 1];
+            if sizes.contains(&pending) || pending > max_size {
+                take_fixed(accumulator, pending.min(max_size))
+            } else {
+                FrameSplit::Incomplete
+            }
+        }
+    }
+}
+
+// ============================================================================
+// 🆕 TAGS DE FORMA DE ONDA (BLOCOS ARRAY)
+// ============================================================================
+//
+// Bloco com `waveform: true` vira uma única variável com o nome do bloco (sem
+// índice), tipo "<TIPO>[]" (ex: "REAL[]") e valor em texto JSON com todos os
+// elementos ("[0.125000,-0.031000,...]"). Não entra nos broadcasts periódicos:
+// o cliente pede com GET_WAVEFORM e o historian grava em tag_waveforms (blob).
+
+pub const WAVEFORM_TYPE_SUFFIX: &str = "[]";
+
+/// Tipo de variável de forma de onda (ex: "REAL[]")
+pub fn is_waveform_type(data_type: &str) -> bool {
+    data_type.ends_with(WAVEFORM_TYPE_SUFFIX)
+}
+
+/// Pontos de uma forma de onda a partir do valor publicado (null = NaN)
+pub fn waveform_points(value: &str) -> Option<Vec<f64>> {
+    serde_json::from_str::<Vec<Option<f64>>>(value).ok()
+        .map(|points| points.into_iter().map(|p| p.unwrap_or(f64::NAN)).collect())
+}
+
+// ============================================================================
+// 🆕 TIPOS TEXTO (STRING, WSTRING, CHAR)
+// ============================================================================
+//
+// Layout S7 (big-endian), n = comprimento máximo declarado:
+//   STRING[n]  → 1 byte tamanho máximo + 1 byte tamanho atual + n bytes
+//   WSTRING[n] → 2 bytes tamanho máximo + 2 bytes tamanho atual + n palavras UTF-16
+//   CHAR[n]    → n bytes fixos (array de CHAR, NULs no final são descartados)
+// Sem "[n]" vale o padrão do TIA Portal: STRING = 254, WSTRING = 254, CHAR = 1.
+// O valor publicado é o próprio texto; não há extração de bits nem escrita.
+
+const DEFAULT_TEXT_LEN: usize = 254;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextType {
+    String(usize),
+    WString(usize),
+    Char(usize),
+}
+
+impl TextType {
+    /// Bytes ocupados no frame (cabeçalho incluído)
+    pub fn size(&self) -> usize {
+        match self {
+            TextType::String(len) => len + 2,
+            TextType::WString(len) => len * 2 + 4,
+            TextType::Char(len) => *len,
+        }
+    }
+
+    /// Decodifica um elemento; `bytes` tem exatamente `size()` bytes
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            TextType::String(len) => {
+                let actual = (bytes[1] as usize).min(bytes[0] as usize).min(*len);
+                latin1(&bytes[2..2 + actual])
+            }
+            TextType::WString(len) => {
+                let max = bytes_to_word(bytes[0], bytes[1]) as usize;
+                let actual = (bytes_to_word(bytes[2], bytes[3]) as usize).min(max).min(*len);
+                let units: Vec<u16> = bytes[4..4 + actual * 2]
+                    .chunks_exact(2)
+                    .map(|pair| bytes_to_word(pair[0], pair[1]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            TextType::Char(_) => {
+                let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+                latin1(&bytes[..end])
+            }
+        }
+    }
+}
+
+/// CHAR do S7 é 8 bits (página de código do Windows ≈ Latin-1)
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// "STRING[20]", "WSTRING", "CHAR[8]"... → tipo texto (None para tipos numéricos/inválidos)
+pub fn text_type(data_type: &str) -> Option<TextType> {
+    let (base, len) = match data_type.split_once('[') {
+        Some((base, rest)) => {
+            let len: usize = rest.strip_suffix(']')?.trim().parse().ok()?;
+            (base, Some(len))
+        }
+        None => (data_type, None),
+    };
+    let text = match base {
+        "STRING" => TextType::String(len.unwrap_or(DEFAULT_TEXT_LEN)),
+        "WSTRING" => TextType::WString(len.unwrap_or(DEFAULT_TEXT_LEN)),
+        "CHAR" => TextType::Char(len.unwrap_or(1)),
+        _ => return None,
+    };
+    // Limites do S7: STRING até 254, WSTRING até 16382
+    let valid = match text {
+        TextType::String(len) => (1..=254).contains(&len),
+        TextType::WString(len) => (1..=16382).contains(&len),
+        TextType::Char(len) => len >= 1,
+    };
+    valid.then_some(text)
+}
+
+/// Tipo cujo valor é texto (sem bits, sem escala, sem escrita)
+pub fn is_text_type(data_type: &str) -> bool {
+    text_type(data_type).is_some()
+}
+
+// ============================================================================
+// 🆕 TIPOS DATA/HORA IEC 61131 (TIME, DATE, TIME_OF_DAY, DATE_AND_TIME)
+// ============================================================================
+//
+// Layout S7 (big-endian depois da ordem de bytes da estrutura):
+//   TIME          → DINT, duração em ms              → "PT1H2M3.004S" (ISO 8601)
+//   DATE          → UINT, dias desde 1990-01-01      → "2024-05-17"
+//   TIME_OF_DAY   → UDINT, ms desde a meia-noite     → "13:45:10.250"
+//   DATE_AND_TIME → 8 bytes BCD (ano, mês, dia, hora, min, seg, ms + dia da semana)
+//                                                    → "2024-05-17T13:45:10.250"
+// DT é estrutura byte a byte: a ordem de bytes não se aplica.
+// Valor fora da faixa (ou BCD inválido) vira "?". Só leitura, sem bits.
+
+const MS_PER_DAY: u32 = 86_400_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeType {
+    Time,
+    Date,
+    TimeOfDay,
+    DateAndTime,
+}
+
+impl TimeType {
+    pub fn size(&self) -> usize {
+        match self {
+            TimeType::Date => 2,
+            TimeType::Time | TimeType::TimeOfDay => 4,
+            TimeType::DateAndTime => 8,
+        }
+    }
+
+    /// Tipos numéricos passam pela ordem de bytes; DT (BCD) não
+    pub fn uses_byte_order(&self) -> bool {
+        *self != TimeType::DateAndTime
+    }
+
+    /// Decodifica um elemento já em big-endian; `bytes` tem exatamente `size()` bytes
+    fn decode(&self, bytes: &[u8]) -> Option<String> {
+        match self {
+            TimeType::Time => Some(format_duration_ms(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))),
+            TimeType::Date => Some(s7_date(bytes_to_word(bytes[0], bytes[1]))?.format("%Y-%m-%d").to_string()),
+            TimeType::TimeOfDay => format_time_of_day(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+            TimeType::DateAndTime => decode_date_and_time(bytes),
+        }
+    }
+}
+
+/// "TIME", "DATE", "TOD"/"TIME_OF_DAY", "DT"/"DATE_AND_TIME" → tipo data/hora
+pub fn time_type(data_type: &str) -> Option<TimeType> {
+    match data_type {
+        "TIME" => Some(TimeType::Time),
+        "DATE" => Some(TimeType::Date),
+        "TOD" | "TIME_OF_DAY" => Some(TimeType::TimeOfDay),
+        "DT" | "DATE_AND_TIME" => Some(TimeType::DateAndTime),
+        _ => None,
+    }
+}
+
+/// Tipo cujo valor é data/hora em texto ISO (sem bits, sem escala, sem escrita)
+pub fn is_time_type(data_type: &str) -> bool {
+    time_type(data_type).is_some()
+}
+
+/// Dias desde 1990-01-01 → data (DATE do S7)
+pub fn s7_date(days: u16) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::from_ymd_opt(1990, 1, 1)?.checked_add_days(chrono::Days::new(days as u64))
+}
+
+/// Duração ISO 8601: 3723004 → "PT1H2M3.004S", -1500 → "-PT1.500S", 0 → "PT0S"
+pub fn format_duration_ms(ms: i32) -> String {
+    let sign = if ms < 0 { "-" } else { "" };
+    let total = ms.unsigned_abs();
+    let (hours, minutes) = (total / 3_600_000, total / 60_000 % 60);
+    let (seconds, millis) = (total / 1000 % 60, total % 1000);
+
+    let mut text = format!("{}PT", sign);
+    if hours > 0 {
+        text.push_str(&format!("{}H", hours));
+    }
+    if minutes > 0 {
+        text.push_str(&format!("{}M", minutes));
+    }
+    if millis > 0 {
+        text.push_str(&format!("{}.{:03}S", seconds, millis));
+    } else if seconds > 0 || total == 0 {
+        text.push_str(&format!("{}S", seconds));
+    }
+    text
+}
+
+/// ms desde a meia-noite → "HH:MM:SS.mmm" (None se passar de 24h)
+pub fn format_time_of_day(ms: u32) -> Option<String> {
+    if ms >= MS_PER_DAY {
+        return None;
+    }
+    Some(format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000))
+}
+
+fn bcd(byte: u8) -> Option<u32> {
+    let (high, low) = (byte >> 4, byte & 0x0F);
+    (high <= 9 && low <= 9).then_some((high * 10 + low) as u32)
+}
+
+/// DATE_AND_TIME: ano 90-99 = 1990-1999, 00-89 = 2000-2089
+fn decode_date_and_time(bytes: &[u8]) -> Option<String> {
+    let year = bcd(bytes[0])?;
+    let year = if year >= 90 { 1900 + year } else { 2000 + year };
+    let date = chrono::NaiveDate::from_ymd_opt(year as i32, bcd(bytes[1])?, bcd(bytes[2])?)?;
+    // Milissegundos: 2 dígitos BCD no byte 6 + dígito alto do byte 7 (o baixo é o dia da semana)
+    let millis_digit = (bytes[7] >> 4) as u32;
+    if millis_digit > 9 {
+        return None;
+    }
+    let millis = bcd(bytes[6])? * 10 + millis_digit;
+    let time = chrono::NaiveTime::from_hms_milli_opt(bcd(bytes[3])?, bcd(bytes[4])?, bcd(bytes[5])?, millis)?;
+    Some(date.and_time(time).format("%Y-%m-%dT%H:%M:%S%.3f").to_string())
+}
+
+// ============================================================================
+// 🆕 EXTRAÇÃO POR variable_path (BITS, FAIXAS DE BITS, BYTES, REAL COM WORDS TROCADAS)
+// ============================================================================
+//
+// Sufixo depois do último '.' da variável (endereços "DB..." não têm sufixo):
+//   Word[3].5          bit 5                          → BOOL ("TRUE"/"FALSE")
+//   Word[3].4-7        bits 4 a 7 como inteiro        → UINT (0..15)
+//   DWord[1].B0        byte 0 (mais significativo,    → BYTE
+//                      primeiro na memória, como no S7)
+//   DWord[1].REAL_SWAP REAL com as duas words trocadas → REAL (gateways Modbus)
+// Usado pelo SmartCache e pelos comandos de leitura em tempo real.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSelector {
+    Bit(u8),
+    BitRange(u8, u8), // (menor, maior), inclusivo
+    Byte(u8),
+    SwappedReal,
+}
+
+const REAL_SWAP_SUFFIX: &str = "REAL_SWAP";
+
+impl PathSelector {
+    /// Sufixo com cara de seletor (dígito, "B<n>" ou REAL_SWAP); outros ("Motor.Speed") fazem parte do nome
+    fn looks_like(suffix: &str) -> bool {
+        let suffix = suffix.trim();
+        suffix.starts_with(|c: char| c.is_ascii_digit())
+            || suffix.eq_ignore_ascii_case(REAL_SWAP_SUFFIX)
+            || suffix.strip_prefix(['B', 'b']).is_some_and(|i| !i.is_empty() && i.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    fn parse(suffix: &str) -> Result<Self, String> {
+        let suffix = suffix.trim();
+        if suffix.eq_ignore_ascii_case(REAL_SWAP_SUFFIX) {
+            return Ok(PathSelector::SwappedReal);
+        }
+        if let Some(index) = suffix.strip_prefix(['B', 'b']) {
+            return index.parse::<u8>().ok().filter(|i| *i < 8).map(PathSelector::Byte)
+                .ok_or_else(|| format!("Byte inválido '{}' (use B0..B7)", suffix));
+        }
+        if let Some((low, high)) = suffix.split_once('-') {
+            return match (low.trim().parse::<u8>(), high.trim().parse::<u8>()) {
+                (Ok(low), Ok(high)) if low <= high && high < 64 => Ok(PathSelector::BitRange(low, high)),
+                _ => Err(format!("Faixa de bits inválida '{}' (ex: 4-7)", suffix)),
+            };
+        }
+        suffix.parse::<u8>().ok().filter(|b| *b < 64).map(PathSelector::Bit)
+            .ok_or_else(|| format!("Seletor inválido '{}'", suffix))
+    }
+
+    /// Tipo publicado do valor extraído
+    pub fn data_type(&self) -> &'static str {
+        match self {
+            PathSelector::Bit(_) => "BOOL",
+            PathSelector::BitRange(_, _) => "UINT",
+            PathSelector::Byte(_) => "BYTE",
+            PathSelector::SwappedReal => "REAL",
+        }
+    }
+
+    /// Extrai o valor da variável de origem; None se o valor/tipo não permitir
+    /// (ex: byte 5 de uma WORD). O chamador usa o valor bruto nesse caso.
+    pub fn extract(&self, value: &str, data_type: &str) -> Option<String> {
+        let size = data_type_size(data_type)?;
+        let raw = raw_bits(value, data_type, size)?;
+        let bits = size as u8 * 8;
+        match *self {
+            PathSelector::Bit(bit) if bit < bits => {
+                Some(if (raw >> bit) & 1 == 1 { "TRUE".to_string() } else { "FALSE".to_string() })
+            }
+            PathSelector::BitRange(low, high) if high < bits => {
+                let width = high - low + 1;
+                let mask = if width >= 64 { u64::MAX } else { (1u64 << width) - 1 };
+                Some(((raw >> low) & mask).to_string())
+            }
+            PathSelector::Byte(index) if (index as usize) < size => {
+                Some(((raw >> ((size - 1 - index as usize) * 8)) & 0xFF).to_string())
+            }
+            PathSelector::SwappedReal if size == 4 => {
+                let swapped = (raw as u32).rotate_right(16);
+                Some(format!("{:.6}", f32::from_bits(swapped)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Bits do valor como está na memória do PLC (inteiros negativos em complemento de 2).
+/// REAL vem do texto com 6 casas: aproximado, prefira DWORD como origem do REAL_SWAP.
+fn raw_bits(value: &str, data_type: &str, size: usize) -> Option<u64> {
+    let value = value.trim();
+    let raw = match data_type {
+        "REAL" => value.parse::<f32>().ok()?.to_bits() as u64,
+        "LREAL" => value.parse::<f64>().ok()?.to_bits(),
+        _ => match value.parse::<u64>() {
+            Ok(v) => v,
+            Err(_) => value.parse::<i64>().ok()? as u64,
+        },
+    };
+    Some(if size >= 8 { raw } else { raw & ((1u64 << (size * 8)) - 1) })
+}
+
+/// Separa "Word[3].4-7" em ("Word[3]", Some(BitRange(4, 7))). Sem sufixo (ou
+/// endereço "DB...") o caminho inteiro é o nome da variável.
+pub fn split_variable_path(variable_path: &str) -> Result<(&str, Option<PathSelector>), String> {
+    if variable_path.starts_with("DB") {
+        return Ok((variable_path, None));
+    }
+    match variable_path.rsplit_once('.') {
+        Some((base, suffix)) if PathSelector::looks_like(suffix) => Ok((base, Some(PathSelector::parse(suffix)?))),
+        _ => Ok((variable_path, None)),
+    }
+}
+
+/// Separa "Word[5]" em ("Word", 5); None se a variável não for Bloco[i]
+pub fn split_array_index(variable: &str) -> Option<(&str, u32)> {
+    let (name, rest) = variable.split_once('[')?;
+    let index = rest.strip_suffix(']')?.parse::<u32>().ok()?;
+    Some((name, index))
+}
+
+/// Valor e tipo publicados de um tag a partir da variável de origem
+pub fn extract_tag_value(selector: Option<PathSelector>, variable: &PlcVariable) -> (String, String) {
+    match selector.and_then(|s| s.extract(&variable.value, &variable.data_type).map(|v| (v, s.data_type()))) {
+        Some((value, data_type)) => (value, data_type.to_string()),
+        None => (variable.value.clone(), variable.data_type.clone()),
+    }
+}
+
+/// Parseia dados usando configuração estruturada do banco de dados
+pub fn parse_with_config(raw_data: &[u8], blocks: &[DataBlockConfig], byte_order: ByteOrder) -> Vec<PlcVariable> {
+    let mut variables = Vec::new();
+    let mut offset = 0;
+    
+    for block in blocks {
+        let mut waveform_points: Vec<String> = Vec::new();
+        let text = text_type(&block.data_type);
+        let time = time_type(&block.data_type);
+        let Some(type_size) = data_type_size(&block.data_type) else { continue };
+        let order = block.byte_order.unwrap_or(byte_order);
+        
+        for i in 0..block.count {
+            if offset + type_size > raw_data.len() {
+                break;
+            }
+            
+            // 🆕 Valor numérico normalizado para big-endian antes de decodificar
+            let mut element = [0u8; 8];
+            let b = &mut element[..type_size.min(8)];
+            if text.is_none() {
+                b.copy_from_slice(&raw_data[offset..offset + type_size]);
+                if time.map(|time| time.uses_byte_order()).unwrap_or(true) {
+                    to_big_endian(order, b);
+                }
+            }
+            
+            let value_str = match block.data_type.as_str() {
+                "BYTE" => {
+                    let val = b[0];
+                    format!("{}", val)
+                }
+                "WORD" => {
+                    let val = bytes_to_word(b[0], b[1]);
+                    format!("{}", val)
+                }
+                "INT" => {
+                    let val = bytes_to_word(b[0], b[1]) as i16;
+                    format!("{}", val)
+                }
+                "DWORD" => {
+                    let val = ((b[0] as u32) << 24) |
+                             ((b[1] as u32) << 16) |
+                             ((b[2] as u32) << 8) |
+                             (b[3] as u32);
+                    format!("{}", val)
+                }
+                "DINT" => {
+                    let bytes = [b[0], b[1], b[2], b[3]];
+                    let val = i32::from_be_bytes(bytes);
+                    format!("{}", val)
+                }
+                "REAL" => {
+                    let bytes = [b[0], b[1], b[2], b[3]];
+                    let val = f32::from_be_bytes(bytes);
+                    format!("{:.6}", val)
+                }
+                "LWORD" => {
+                    let val = ((b[0] as u64) << 56) |
+                             ((b[1] as u64) << 48) |
+                             ((b[2] as u64) << 40) |
+                             ((b[3] as u64) << 32) |
+                             ((b[4] as u64) << 24) |
+                             ((b[5] as u64) << 16) |
+                             ((b[6] as u64) << 8) |
+                             (b[7] as u64);
+                    format!("{}", val)
+                }
+                "LINT" => {
+                    let bytes = [b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]];
+                    let val = i64::from_be_bytes(bytes);
+                    format!("{}", val)
+                }
+                "LREAL" => {
+                    let bytes = [b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]];
+                    let val = f64::from_be_bytes(bytes);
+                    format!("{:.6}", val)
+                }
+                // 🆕 STRING / WSTRING / CHAR[n] e TIME / DATE / TOD / DT
+                _ => match (text, time) {
+                    (Some(text), _) => text.decode(&raw_data[offset..offset + type_size]),
+                    (None, Some(time)) => time.decode(b).unwrap_or_else(|| String::from("?")),
+                    (None, None) => String::from("?"),
+                },
+            };
+            
+            if block.waveform && text.is_none() && time.is_none() {
+                // NaN/infinito não existem em JSON: ponto sem valor
+                let finite = value_str.parse::<f64>().is_ok_and(|v| v.is_finite());
+                waveform_points.push(if finite { value_str } else { "null".to_string() });
+            } else {
+                variables.push(PlcVariable {
+                    name: format!("{}[{}]", block.name, i),
+                    value: value_str,
+                    data_type: block.data_type.clone(),
+                    unit: None,
+                });
+            }
+            
+            offset += type_size;
+        }
+        
+        if !waveform_points.is_empty() {
+            variables.push(PlcVariable {
+                name: block.name.clone(),
+                value: format!("[{}]", waveform_points.join(",")),
+                data_type: format!("{}{}", block.data_type, WAVEFORM_TYPE_SUFFIX),
+                unit: None,
+            });
+        }
+    }
+    
+    variables
+}
+
+/// Detecta o formato real dos dados baseado no conteúdo
+fn detect_data_format(raw_data: &[u8]) -> &'static str {
+    let data_len = raw_data.len();
+    
+    // Se é exatamente 130 bytes e múltiplo de 2, provavelmente são WORDs
+    if data_len == 130 && data_len % 2 == 0 {
+        return "word";
+    }
+    
+    // Se é exatamente 520 bytes, é a estrutura mista: 65 WORDs + 65 INTs + 65 REALs
+    if data_len == 520 {
+        return "mixed";
+    }
+    
+    // Analisar padrões nos dados para detectar formato
+    if data_len >= 4 {
+        // Verificar se há padrões de REAL (float) válidos
+        let mut valid_floats = 0;
+        for i in (0..data_len - 3).step_by(4) {
+            let bytes = [raw_data[i], raw_data[i + 1], raw_data[i + 2], raw_data[i + 3]];
+            let float_val = f32::from_be_bytes(bytes);
+            if float_val.is_finite() && float_val.abs() < 1e6 && float_val.abs() > 1e-6 {
+                valid_floats += 1;
+            }
+        }
+        
+        // Se mais de 30% são floats válidos, são REALs
+        if valid_floats > (data_len / 4) * 3 / 10 {
+            return "real";
+        }
+    }
+    
+    // Se múltiplo de 4, pode ser DWORDs
+    if data_len % 4 == 0 {
+        return "dword";
+    }
+    
+    // Se múltiplo de 2, são WORDs
+    if data_len % 2 == 0 {
+        return "word";
+    }
+    
+    // Senão, são bytes
+    "byte"
+}
+
+/// 🚀 NOVA FUNÇÃO: Parse com cache - ZERO DATABASE CALLS!
+pub fn parse_plc_data_cached(raw_data: &[u8], ip: &str, cached_config: Option<PlcStructureConfig>) -> PlcDataPacket {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs();
+    
+    let data_len = raw_data.len();
+    
+    // 🚀 USAR CONFIG DO CACHE - ZERO LOCKS!
+    let variables = if let Some(config) = cached_config {
+        println!("⚡ PLC {}: Usando config CACHEADA ({} blocos, {} bytes) - PERFORMANCE MÁXIMA!", 
+                 ip, config.blocks.len(), config.total_size);
+        
+        if let Some((profile_name, blocks)) = select_frame_layout(&config, raw_data) {
+            if !config.profiles.is_empty() {
+                println!("🧩 PLC {}: Frame de {} bytes → perfil '{}'", ip, data_len, profile_name);
+            }
+            parse_with_config(raw_data, blocks, config.byte_order)
+        } else {
+            println!("⚠️ PLC {}: Tamanho diferente! Esperado {:?} bytes, recebido {} bytes. Usando detecção automática.",
+                     ip, known_frame_sizes(&config), data_len);
+            parse_auto_detect(raw_data)
+        }
+    } else {
+        println!("📊 PLC {}: Sem config cacheada. Usando detecção automática em {} bytes", ip, data_len);
+        parse_auto_detect(raw_data)
+    };
+    
+    println!("📊 PLC {}: Parseados {} variáveis", ip, variables.len());
+    
+    PlcDataPacket {
+        ip: ip.to_string(),
+        timestamp,
+        raw_data: raw_data.to_vec(),
+        size: data_len,
+        variables,
+    }
+}
+
+/// Detecção automática quando não tem configuração
+fn parse_auto_detect(raw_data: &[u8]) -> Vec<PlcVariable> {
+    let mut variables = Vec::new();
+    let data_len = raw_data.len();
+    
+    // Detectar formato real dos dados
+    let format = detect_data_format(raw_data);
+    
+    match format {
+        "word" => {
+            // APENAS WORDs - formato detectado (leitura do plc-core, igual ao plc-app)
+            for (i, word_value) in crate::packet::decode_words(raw_data, ByteOrder::Big, usize::MAX).into_iter().enumerate() {
+                variables.push(PlcVariable {
+                    name: format!("W{}", i),
+                    value: word_value.to_string(),
+                    data_type: "WORD".to_string(),
+                    unit: None,
+                });
+            }
+        }
+        
+        "dword" => {
+            // DWORDs detectados
+            let dword_count = data_len / 4;
+            for i in 0..dword_count {
+                let offset = i * 4;
+                if offset + 3 < data_len {
+                    let dword_value = ((raw_data[offset] as u32) << 24) |
+                                     ((raw_data[offset + 1] as u32) << 16) |
+                                     ((raw_data[offset + 2] as u32) << 8) |
+                                     (raw_data[offset + 3] as u32);
+                    variables.push(PlcVariable {
+                        name: format!("DW{}", i),
+                        value: dword_value.to_string(),
+                        data_type: "DWORD".to_string(),
+                        unit: None,
+                    });
+                }
+            }
+        }
+        
+        "real" => {
+            // REALs detectados
+            let real_count = data_len / 4;
+            for i in 0..real_count {
+                let offset = i * 4;
+                if offset + 3 < data_len {
+                    let bytes = [raw_data[offset], raw_data[offset + 1], raw_data[offset + 2], raw_data[offset + 3]];
+                    let float_value = f32::from_be_bytes(bytes);
+                    variables.push(PlcVariable {
+                        name: format!("R{}", i),
+                        value: format!("{:.6}", float_value),
+                        data_type: "REAL".to_string(),
+                        unit: None,
+                    });
+                }
+            }
+        }
+        
+        "mixed" => {
+            // Estrutura mista: 65 WORDs + 65 INTs + 65 REALs (520 bytes)
+            // Array[0..64] of Word = 130 bytes (bytes 0-129)
+            // Array[0..64] of Int = 130 bytes (bytes 130-259) 
+            // Array[0..64] of Real = 260 bytes (bytes 260-519)
+            
+            // WORDs (primeiros 130 bytes)
+            for i in 0..65 {
+                let offset = i * 2;
+                if offset + 1 < 130 {
+                    let word_value = bytes_to_word(raw_data[offset], raw_data[offset + 1]);
+                    variables.push(PlcVariable {
+                        name: format!("W{}", i),
+                        value: word_value.to_string(),
+                        data_type: "WORD".to_string(),
+                        unit: None,
+                    });
+                }
+            }
+            
+            // INTs (próximos 130 bytes, offset 130-259)
+            for i in 0..65 {
+                let offset = 130 + (i * 2);
+                if offset + 1 < 260 {
+                    let int_value = bytes_to_word(raw_data[offset], raw_data[offset + 1]) as i16;
+                    variables.push(PlcVariable {
+                        name: format!("I{}", i),
+                        value: int_value.to_string(),
+                        data_type: "INT".to_string(),
+                        unit: None,
+                    });
+                }
+            }
+            
+            // REALs (últimos 260 bytes, offset 260-519)
+            for i in 0..65 {
+                let offset = 260 + (i * 4);
+                if offset + 3 < data_len {
+                    let bytes = [raw_data[offset], raw_data[offset + 1], raw_data[offset + 2], raw_data[offset + 3]];
+                    let float_value = f32::from_be_bytes(bytes);
+                    variables.push(PlcVariable {
+                        name: format!("R{}", i),
+                        value: format!("{:.6}", float_value),
+                        data_type: "REAL".to_string(),
+                        unit: None,
+                    });
+                }
+            }
+        }
+        
+        _ => {
+            // BYTEs como fallback
+            for (i, byte) in raw_data.iter().enumerate() {
+                variables.push(PlcVariable {
+                    name: format!("B{}", i),
+                    value: byte.to_string(),
+                    data_type: "BYTE".to_string(),
+                    unit: None,
+                });
+            }
+        }
+    }
+    
+    variables
+}
@@ -0,0 +1,50 @@
+use crate::byte_order::ByteOrder;
+use crate::framing::FrameMode;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// ESTRUTURA DO FRAME DO PLC (BLOCOS E PERFIS)
+// ============================================================================
+//
+// Layout dos dados que o PLC envia: blocos tipados em sequência, com perfis
+// alternativos escolhidos por tamanho ou byte de tipo. O plc-hmi grava no
+// SQLite; o corte dos frames e o parser usam a mesma definição.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataBlockConfig {
+    pub data_type: String,  // "WORD", "INT", "DWORD", "REAL", etc
+    pub count: u32,         // Número de elementos
+    pub name: String,       // Nome do array (ex: "Word", "Real2")
+    #[serde(default)]
+    pub waveform: bool,     // 🆕 Bloco inteiro = um único tag array (ex: 100 pontos de vibração)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_order: Option<ByteOrder>, // 🆕 Sobrescreve a ordem da estrutura só neste bloco
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlcStructureConfig {
+    pub plc_ip: String,
+    pub blocks: Vec<DataBlockConfig>,
+    pub total_size: usize,
+    pub last_updated: i64,
+    // 🆕 Perfis alternativos de frame (ex: frame "rápido" pequeno + frame "lento" grande)
+    #[serde(default)]
+    pub profiles: Vec<FrameProfile>,
+    // 🆕 Ordem dos bytes padrão de todos os blocos (estrutura principal e perfis)
+    #[serde(default)]
+    pub byte_order: ByteOrder,
+    // 🆕 Separação dos frames no TCP (firmwares diferentes no mesmo servidor)
+    #[serde(default)]
+    pub framing: FrameMode,
+}
+
+/// Estrutura alternativa de frame para o mesmo PLC, selecionada por pacote
+/// pelo tamanho do frame ou por um byte de tipo em posição fixa.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameProfile {
+    pub name: String,                      // Ex: "fast", "slow"
+    pub blocks: Vec<DataBlockConfig>,
+    pub total_size: usize,
+    pub type_byte_offset: Option<usize>,   // Posição do byte de tipo no frame (se houver)
+    pub type_byte_value: Option<u8>,       // Valor esperado do byte de tipo
+}
@@ -0,0 +1,52 @@
+// ============================================================================
+// ESCRITA NO PLC PELA MESMA CONEXÃO (WRITE-BACK)
+// ============================================================================
+//
+// Frame enviado ao PLC (big-endian):
+//
+//   "WRTE" (4 bytes) | seq (u16) | offset em bytes (u32) | bit (u8, 0xFF = valor inteiro)
+//   | tamanho do valor (u16) | valor
+//
+// O offset é a posição da variável no frame de dados que o PLC envia (no
+// plc-app, sempre uma WORD: Word[i] → i * 2). O PLC confirma cada escrita no
+// fluxo de dados, entre frames:
+//
+//   "WACK" (4 bytes) | seq (u16) | status (u8, 0 = aplicado, outro = código de erro)
+
+pub const WRITE_MAGIC: &[u8; 4] = b"WRTE";
+pub const WRITE_ACK_MAGIC: &[u8; 4] = b"WACK";
+pub const WRITE_ACK_SIZE: usize = 7;
+pub const WHOLE_VALUE_BIT: u8 = 0xFF;
+const WRITE_HEADER_SIZE: usize = 13;
+
+/// Monta o frame de escrita; `bit` None grava o valor inteiro
+pub fn encode_write_frame(seq: u16, byte_offset: u32, bit: Option<u8>, data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(WRITE_HEADER_SIZE + data.len());
+    frame.extend_from_slice(WRITE_MAGIC);
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&byte_offset.to_be_bytes());
+    frame.push(bit.unwrap_or(WHOLE_VALUE_BIT));
+    frame.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    frame.extend_from_slice(data);
+    frame
+}
+
+/// Confirmação completa ("WACK" + seq + status) no início dos bytes. Só vale na
+/// fronteira de frame e com escrita pendente: fora disso os bytes são dados.
+pub fn is_write_ack(raw_data: &[u8]) -> bool {
+    raw_data.len() >= WRITE_ACK_SIZE && raw_data.starts_with(WRITE_ACK_MAGIC)
+}
+
+/// Início de uma confirmação que ainda não chegou inteira
+pub fn is_partial_write_ack(raw_data: &[u8]) -> bool {
+    let len = raw_data.len().min(WRITE_ACK_MAGIC.len());
+    len > 0 && raw_data.len() < WRITE_ACK_SIZE && raw_data[..len] == WRITE_ACK_MAGIC[..len]
+}
+
+/// (seq, status) de uma confirmação completa no início dos bytes
+pub fn parse_write_ack(raw_data: &[u8]) -> Option<(u16, u8)> {
+    if !is_write_ack(raw_data) {
+        return None;
+    }
+    Some((u16::from_be_bytes([raw_data[4], raw_data[5]]), raw_data[6]))
+}
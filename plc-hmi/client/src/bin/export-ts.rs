@@ -0,0 +1,21 @@
+// ============================================================================
+// EXPORTA OS TIPOS DO PROTOCOLO PARA TYPESCRIPT
+// ============================================================================
+//
+// cargo run --features ts --bin export-ts [-- <diretório>]
+// Padrão: ../src/types/protocol (frontend do plc-hmi). Um arquivo .ts por tipo,
+// com as dependências (Instance, ServerStatus, TagInfo) incluídas.
+
+use plc_hmi_client::protocol::{ClientMessage, ServerMessage};
+use ts_rs::TS;
+
+const DEFAULT_OUT_DIR: &str = "../src/types/protocol";
+
+fn main() {
+    let out_dir = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_OUT_DIR.to_string());
+    if let Err(e) = ServerMessage::export_all_to(&out_dir).and_then(|_| ClientMessage::export_all_to(&out_dir)) {
+        eprintln!("❌ Erro ao exportar tipos TypeScript: {}", e);
+        std::process::exit(1);
+    }
+    println!("✅ Tipos TypeScript exportados em {}", out_dir);
+}
@@ -0,0 +1,232 @@
+// ============================================================================
+// CLIENTE ASSÍNCRONO DO WEBSOCKET (feature "client")
+// ============================================================================
+//
+// `HmiClient::connect` faz o handshake completo:
+//   1. token de API (?token=wst_...) e/ou chave pública (?public_key=...) na
+//      query quando informados - com chave pública os dados vêm mascarados;
+//   2. WELCOME (ou erro se o servidor exigir credencial e nenhuma for aceita);
+//   3. HELLO v2 com os recursos pedidos e espera do HELLO_ACK.
+// Depois disso as mensagens chegam tipadas em `Subscription::next()`.
+// Frames binários (MessagePack) não são pedidos: tudo chega em JSON.
+
+use crate::protocol::{
+    ClientMessage, ServerMessage, TagValue, FEATURE_TAG_TIMESTAMPS, FEATURE_TYPED_VALUES, PROTOCOL_VERSION,
+};
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Opções de conexão
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    pub token: Option<String>,      // Token de API (wst_...) se o servidor exigir
+    pub public_key: Option<String>, // Chave pública (dados mascarados pelas regras da chave)
+    pub typed_values: bool,         // Números/booleanos em vez de texto
+    pub tag_timestamps: bool,       // {"v", "ts"} por tag
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self { token: None, public_key: None, typed_values: true, tag_timestamps: true }
+    }
+}
+
+/// Conexão ativa: envia comandos ao servidor
+pub struct HmiClient {
+    sink: Mutex<SplitSink<WsStream, Message>>,
+    reader: JoinHandle<()>,
+    pub welcome: ServerMessage,
+    pub hello_ack: ServerMessage,
+}
+
+/// Mensagens recebidas do servidor, na ordem de chegada
+pub struct Subscription {
+    rx: mpsc::UnboundedReceiver<ServerMessage>,
+}
+
+impl Subscription {
+    /// Próxima mensagem; None quando a conexão fecha
+    pub async fn next(&mut self) -> Option<ServerMessage> {
+        self.rx.recv().await
+    }
+
+    /// Próximo TAG_DATA (demais mensagens são descartadas), já convertido em `TagValue`
+    pub async fn next_tags(&mut self) -> Option<HashMap<String, TagValue>> {
+        loop {
+            if let ServerMessage::TagData { tags, .. } = self.next().await? {
+                return Some(tags.iter().map(|(name, raw)| (name.clone(), TagValue::from_json(raw))).collect());
+            }
+        }
+    }
+}
+
+impl HmiClient {
+    pub async fn connect(url: &str, options: ClientOptions) -> Result<(Self, Subscription), String> {
+        let mut url = url.to_string();
+        let credentials = [("token", &options.token), ("public_key", &options.public_key)];
+        for (name, value) in credentials {
+            if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                url = format!("{}{}{}={}", url, separator, name, value);
+            }
+        }
+        let (stream, _) = tokio::time::timeout(HANDSHAKE_TIMEOUT, connect_async(url.as_str()))
+            .await
+            .map_err(|_| "Tempo esgotado ao conectar no WebSocket".to_string())?
+            .map_err(|e| format!("Erro ao conectar no WebSocket: {}", e))?;
+        let (mut sink, mut stream) = stream.split();
+
+        // Mensagens recebidas antes do HELLO_ACK (SERVER_STATUS...) vão para a fila
+        let mut pending = Vec::new();
+        let welcome = loop {
+            match next_message(&mut stream).await? {
+                message @ ServerMessage::Welcome { .. } => break message,
+                ServerMessage::AuthRequired { .. } => {
+                    return Err("Servidor exige token de API ou chave pública (ClientOptions::token/public_key)".to_string());
+                }
+                ServerMessage::AuthResult { success: false, message, .. } => {
+                    return Err(format!("Credencial rejeitada: {}", message.unwrap_or_default()));
+                }
+                other => pending.push(other),
+            }
+        };
+
+        let mut features = Vec::new();
+        if options.typed_values {
+            features.push(FEATURE_TYPED_VALUES.to_string());
+        }
+        if options.tag_timestamps {
+            features.push(FEATURE_TAG_TIMESTAMPS.to_string());
+        }
+        let hello = ClientMessage::Hello { protocol_version: PROTOCOL_VERSION, features, public_key: options.public_key.clone() };
+        send_message(&mut sink, &hello).await?;
+
+        let hello_ack = loop {
+            match next_message(&mut stream).await? {
+                ServerMessage::HelloAck { success: false, message, .. } => {
+                    return Err(format!("HELLO recusado: {}", message.unwrap_or_default()));
+                }
+                message @ ServerMessage::HelloAck { .. } => break message,
+                other => pending.push(other),
+            }
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        for message in pending {
+            let _ = tx.send(message);
+        }
+        let reader = tokio::spawn(async move {
+            while let Some(Ok(frame)) = stream.next().await {
+                let Message::Text(text) = frame else { continue };
+                match serde_json::from_str::<ServerMessage>(&text) {
+                    Ok(message) => {
+                        if tx.send(message).is_err() {
+                            break; // Subscription descartada
+                        }
+                    }
+                    Err(e) => eprintln!("⚠️ plc-hmi-client: mensagem inválida ignorada: {}", e),
+                }
+            }
+        });
+
+        let client = Self { sink: Mutex::new(sink), reader, welcome, hello_ack };
+        Ok((client, Subscription { rx }))
+    }
+
+    pub async fn send(&self, message: &ClientMessage) -> Result<(), String> {
+        send_message(&mut *self.sink.lock().await, message).await
+    }
+
+    pub async fn list_plcs(&self) -> Result<(), String> {
+        self.send(&ClientMessage::ListPlcs).await
+    }
+
+    pub async fn list_tags(&self, plc_ips: Vec<String>) -> Result<(), String> {
+        self.send(&ClientMessage::ListTags { plc_ips }).await
+    }
+
+    /// Filtra os dados recebidos por PLC, área e categoria (listas vazias = tudo)
+    pub async fn subscribe(&self, plc_ips: Vec<String>, areas: Vec<String>, categories: Vec<String>) -> Result<(), String> {
+        self.send(&ClientMessage::Subscribe { plc_ips, areas, categories, include_all_faults: true, min_priority: 0 }).await
+    }
+
+    pub async fn get_waveform(&self, tag: &str, plc_ip: Option<&str>) -> Result<(), String> {
+        self.send(&ClientMessage::GetWaveform { tag: tag.to_string(), plc_ip: plc_ip.map(str::to_string) }).await
+    }
+
+    pub async fn close(self) {
+        let _ = self.sink.lock().await.close().await;
+        self.reader.abort();
+    }
+}
+
+async fn next_message(stream: &mut futures_util::stream::SplitStream<WsStream>) -> Result<ServerMessage, String> {
+    loop {
+        let frame = tokio::time::timeout(HANDSHAKE_TIMEOUT, stream.next())
+            .await
+            .map_err(|_| "Tempo esgotado no handshake do WebSocket".to_string())?
+            .ok_or_else(|| "Conexão fechada pelo servidor".to_string())?
+            .map_err(|e| format!("Erro no WebSocket: {}", e))?;
+        match frame {
+            Message::Text(text) => {
+                return serde_json::from_str(&text).map_err(|e| format!("Mensagem inválida do servidor: {}", e));
+            }
+            Message::Close(_) => return Err("Conexão fechada pelo servidor".to_string()),
+            _ => {}
+        }
+    }
+}
+
+async fn send_message(sink: &mut SplitSink<WsStream, Message>, message: &ClientMessage) -> Result<(), String> {
+    let text = serde_json::to_string(message).map_err(|e| format!("Erro ao serializar mensagem: {}", e))?;
+    sink.send(Message::Text(text)).await.map_err(|e| format!("Erro ao enviar mensagem: {}", e))
+}
+
+// ============================================================================
+// REST: HEALTH CHECK (/healthz)
+// ============================================================================
+
+/// Resposta do /healthz: 200 = saudável, 503 = alguma verificação falhou
+#[derive(Debug, Clone)]
+pub struct HealthResponse {
+    pub http_status: u16,
+    pub healthy: bool,
+    pub report: Value,
+}
+
+pub async fn health(host: &str, port: u16) -> Result<HealthResponse, String> {
+    let request = async {
+        let mut stream = TcpStream::connect((host, port)).await
+            .map_err(|e| format!("Erro ao conectar em {}:{}: {}", host, port, e))?;
+        let request = format!("GET /healthz HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\n\r\n", host, port);
+        stream.write_all(request.as_bytes()).await.map_err(|e| format!("Erro ao enviar requisição: {}", e))?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.map_err(|e| format!("Erro ao ler resposta: {}", e))?;
+        Ok::<_, String>(response)
+    };
+    let response = tokio::time::timeout(HEALTH_TIMEOUT, request).await
+        .map_err(|_| "Tempo esgotado no /healthz".to_string())??;
+
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| "Resposta HTTP inválida".to_string())?;
+    let http_status = head.split_whitespace().nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| "Status HTTP inválido".to_string())?;
+    let report: Value = serde_json::from_str(body.trim()).unwrap_or(Value::Null);
+    let healthy = report.get("healthy").and_then(Value::as_bool).unwrap_or(http_status == 200);
+    Ok(HealthResponse { http_status, healthy, report })
+}
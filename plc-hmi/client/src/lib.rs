@@ -0,0 +1,18 @@
+// ============================================================================
+// PLC-HMI-CLIENT - CLIENTE TIPADO DO WEBSOCKET/REST DO PLC HMI
+// ============================================================================
+//
+// `protocol`: mensagens do WebSocket (as mesmas structs que o servidor usa
+// para montar WELCOME, HELLO_ACK, AUTH_*, SERVER_STATUS).
+// `client` (feature "client", padrão): conexão assíncrona com autenticação,
+// negociação HELLO v2 e fila de mensagens tipadas.
+// Tipos TypeScript: `cargo run -p plc-hmi-client --features ts --bin export-ts`.
+
+pub mod protocol;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "client")]
+pub use client::{health, ClientOptions, HealthResponse, HmiClient, Subscription};
+pub use protocol::{ClientMessage, ServerMessage, ServerStatus, TagValue};
@@ -0,0 +1,263 @@
+// ============================================================================
+// MENSAGENS DO PROTOCOLO WEBSOCKET (v2, negociado via HELLO)
+// ============================================================================
+//
+// Mesmos tipos usados pelo servidor (plc-hmi/src-tauri) para montar todas as
+// mensagens (WELCOME, HELLO_ACK, AUTH_*, TAG_DATA, CRITICAL, listas, WAVEFORM...)
+// - e pelo cliente para ler tudo que chega.
+// Cada mensagem é um objeto JSON com o campo "type"; tipos que esta versão
+// não conhece viram `ServerMessage::Unknown` em vez de erro.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[cfg(feature = "ts")]
+use ts_rs::TS;
+
+pub const PROTOCOL_VERSION: u64 = 2;
+pub const LEGACY_PROTOCOL_VERSION: u64 = 1;
+pub const FEATURE_BINARY: &str = "binary";                 // MessagePack em frames binários
+pub const FEATURE_TYPED_VALUES: &str = "typed_values";     // Números/booleanos em vez de texto
+pub const FEATURE_TAG_TIMESTAMPS: &str = "tag_timestamps"; // {"v": valor, "ts": epoch ms} por tag
+pub const SUPPORTED_FEATURES: &[&str] = &[FEATURE_BINARY, FEATURE_TYPED_VALUES, FEATURE_TAG_TIMESTAMPS];
+
+/// Identidade do servidor (nome, site, UUID)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS))]
+pub struct Instance {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub site: String,
+    #[serde(default)]
+    pub uuid: String,
+}
+
+/// Status do servidor: SERVER_STATUS no WebSocket e payload do tópico MQTT retido
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS))]
+pub struct ServerStatus {
+    pub state: String, // "online" | "offline"
+    pub version: String,
+    pub uptime_s: u64,
+    pub connected_plcs: u64,
+    #[serde(default)]
+    pub instance: Instance,
+    pub timestamp_ms: i64,
+}
+
+/// Metadados de um tag (TAG_LIST)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS))]
+pub struct TagInfo {
+    pub plc_ip: String,
+    pub tag_name: String,
+    #[serde(default)]
+    pub data_type: String,
+    pub unit: Option<String>,
+    pub area: Option<String>,
+    pub category: Option<String>,
+}
+
+/// Mensagens enviadas pelo cliente
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS))]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientMessage {
+    Hello {
+        protocol_version: u64,
+        features: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public_key: Option<String>,
+    },
+    Auth {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        token: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public_key: Option<String>, // Chave pública no lugar do token (dados sempre mascarados)
+    },
+    ListPlcs,
+    ListTags {
+        #[serde(default)]
+        plc_ips: Vec<String>,
+    },
+    SubscribePlcs {
+        plc_ips: Vec<String>,
+    },
+    Subscribe {
+        #[serde(default)]
+        plc_ips: Vec<String>,
+        #[serde(default)]
+        areas: Vec<String>,
+        #[serde(default)]
+        categories: Vec<String>,
+        #[serde(default)]
+        include_all_faults: bool,
+        #[serde(default)]
+        min_priority: u8,
+    },
+    GetWaveform {
+        tag: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        plc_ip: Option<String>,
+    },
+    /// Liga/desliga o playback histórico só para esta conexão (TAG_DATA com "playback": true)
+    Playback {
+        enabled: bool,
+    },
+}
+
+/// Mensagens enviadas pelo servidor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(TS))]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServerMessage {
+    Welcome {
+        protocol_version: u64,
+        min_protocol_version: u64,
+        features: Vec<String>,
+        client_id: u64,
+        server_version: String,
+        #[serde(default)]
+        instance: Instance,
+    },
+    HelloAck {
+        success: bool,
+        protocol_version: u64,
+        features: Vec<String>,
+        #[serde(default)]
+        rejected_features: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public: Option<String>,
+        #[serde(default)]
+        instance: Instance,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    AuthRequired {
+        timeout_ms: u64,
+    },
+    AuthResult {
+        success: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public: Option<String>, // Nome da chave pública aceita
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    ServerStatus {
+        status: ServerStatus,
+    },
+    /// Valores: texto, número/booleano (typed_values) ou {"v", "ts"} (tag_timestamps) - ver `TagValue`
+    TagData {
+        protocol_version: u64,
+        tags: HashMap<String, Value>,
+        /// Valores reproduzidos do historian (cliente em playback), nunca estado ao vivo
+        #[serde(default, skip_serializing_if = "is_false")]
+        playback: bool,
+    },
+    Critical {
+        plc_ip: String,
+        tag: String,
+        value: Value,
+        ts: Option<u64>,
+    },
+    PlcList {
+        plcs: Vec<String>,
+        timestamp: u64,
+    },
+    TagList {
+        tags: Vec<TagInfo>,
+        timestamp: u64,
+    },
+    SubscribeAck {
+        success: bool,
+        #[serde(default)]
+        plcs: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        areas: Option<Vec<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        categories: Option<Vec<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        include_all_faults: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_priority: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    PlaybackAck {
+        success: bool,
+        enabled: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position_ms: Option<i64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    Waveform {
+        success: bool,
+        tag: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        plc_ip: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ts_ms: Option<u64>,
+        #[serde(default)]
+        count: usize,
+        #[serde(default)]
+        points: Vec<Option<f64>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// Tipo não conhecido por esta versão do cliente
+    #[serde(other)]
+    Unknown,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl ServerMessage {
+    /// Valor JSON pronto para enviar (o servidor monta as mensagens por aqui)
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+/// Valor de um tag do TAG_DATA, qualquer que seja o formato negociado
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagValue {
+    pub value: Value,
+    pub ts_ms: Option<u64>,
+}
+
+impl TagValue {
+    pub fn from_json(raw: &Value) -> Self {
+        match raw {
+            Value::Object(obj) if obj.contains_key("v") => TagValue {
+                value: obj.get("v").cloned().unwrap_or(Value::Null),
+                ts_ms: obj.get("ts").and_then(Value::as_u64),
+            },
+            other => TagValue { value: other.clone(), ts_ms: None },
+        }
+    }
+
+    /// Número (texto "12.5", número, ou booleano como 0/1)
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self.value {
+            Value::Number(n) => n.as_f64(),
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            Value::String(s) => match s.as_str() {
+                "TRUE" | "true" => Some(1.0),
+                "FALSE" | "false" => Some(0.0),
+                _ => s.trim().parse().ok(),
+            },
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::commands::WebSocketServerState;
+use crate::database::{AlarmDefinition, AlarmOccurrence, Database};
+use crate::i18n::{msg, LocalizedMessage};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// MOTOR DE ALARMES - CONDIÇÕES CONFIGURÁVEIS SOBRE OS VALORES DO SMARTCACHE
+// ============================================================================
+//
+// A cada EVALUATE_INTERVAL_MS compara o valor atual de cada tag com as
+// definições da tabela `alarm_definitions`:
+//   - dispara quando a condição dura `on_delay_ms` (ocorrência em alarm_history,
+//     evento "alarm-raised");
+//   - normaliza quando a condição, com a histerese `deadband`, fica falsa por
+//     `off_delay_ms` (cleared_at_ms, evento "alarm-cleared").
+// O estado fica no banco: ao reiniciar, ocorrências sem cleared_at_ms continuam
+// ativas e não disparam de novo. O reconhecimento (ack_alarm) é independente
+// do estado - um alarme normalizado fica em aberto até ser reconhecido.
+
+const EVALUATE_INTERVAL_MS: u64 = 250;
+const RELOAD_DEFINITIONS_INTERVAL: Duration = Duration::from_secs(10);
+
+pub const COMPARISONS: &[&str] = &[">", ">=", "<", "<=", "==", "!="];
+pub const SEVERITIES: &[&str] = &["info", "warning", "critical"];
+
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Recarrega as definições na próxima avaliação (após salvar/excluir/renomear)
+pub fn request_reload() {
+    RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Valida uma definição antes de salvar
+pub fn validate_definition(definition: &AlarmDefinition) -> Result<(), String> {
+    if definition.plc_ip.trim().is_empty() || definition.tag_name.trim().is_empty() {
+        return Err("Alarme precisa de PLC e tag".to_string());
+    }
+    if definition.name.trim().is_empty() {
+        return Err("Alarme precisa de um nome".to_string());
+    }
+    if !COMPARISONS.contains(&definition.comparison.as_str()) {
+        return Err(format!("Comparação inválida: '{}' (use {})", definition.comparison, COMPARISONS.join(" ")));
+    }
+    if !SEVERITIES.contains(&definition.severity.as_str()) {
+        return Err(format!("Severidade inválida: '{}' (use {})", definition.severity, SEVERITIES.join(", ")));
+    }
+    if !definition.threshold.is_finite() {
+        return Err("Limite do alarme deve ser um número".to_string());
+    }
+    if !definition.deadband.is_finite() || definition.deadband < 0.0 {
+        return Err("Histerese (deadband) não pode ser negativa".to_string());
+    }
+    Ok(())
+}
+
+/// Valor do cache como número (bits: TRUE = 1, FALSE = 0)
+fn numeric_value(value: &str) -> Option<f64> {
+    match value {
+        "TRUE" | "true" => Some(1.0),
+        "FALSE" | "false" => Some(0.0),
+        _ => value.trim().parse::<f64>().ok().filter(|v| v.is_finite()),
+    }
+}
+
+/// Condição do alarme; ativo, só normaliza depois de passar da histerese
+fn condition_met(definition: &AlarmDefinition, value: f64, active: bool) -> bool {
+    let threshold = definition.threshold;
+    let band = if active { definition.deadband } else { 0.0 };
+    match definition.comparison.as_str() {
+        ">" => value > threshold - band,
+        ">=" => value >= threshold - band,
+        "<" => value < threshold + band,
+        "<=" => value <= threshold + band,
+        "==" => value == threshold,
+        "!=" => value != threshold,
+        _ => false,
+    }
+}
+
+/// Texto da ocorrência: mensagem configurada ou a condição
+fn describe(definition: &AlarmDefinition, value: &str) -> LocalizedMessage {
+    match definition.message.as_deref().filter(|m| !m.trim().is_empty()) {
+        Some(message) => msg("common.raw", &[("message", message.to_string())]),
+        None => msg("alarm.condition", &[
+            ("tag", definition.tag_name.clone()),
+            ("comparison", definition.comparison.clone()),
+            ("threshold", definition.threshold.to_string()),
+            ("value", value.to_string()),
+        ]),
+    }
+}
+
+/// Estado em memória de uma definição
+#[derive(Default)]
+struct AlarmState {
+    occurrence_id: Option<i64>,       // Ocorrência ativa (sem cleared_at_ms)
+    transition_since: Option<Instant>, // Início da condição pendente (on/off delay)
+}
+
+pub fn start_alarm_engine(app_handle: AppHandle, database: Arc<Database>, websocket_state: WebSocketServerState) {
+    tauri::async_runtime::spawn(async move {
+        let mut definitions: Vec<AlarmDefinition> = Vec::new();
+        let mut states: HashMap<i64, AlarmState> = HashMap::new();
+        let mut loaded_at: Option<Instant> = None;
+
+        // Ocorrências ativas antes do reinício continuam ativas
+        match database.list_open_alarm_occurrences() {
+            Ok(open) => {
+                for occurrence in &open {
+                    states.entry(occurrence.alarm_id).or_default().occurrence_id = Some(occurrence.id);
+                }
+                println!("🚨 Motor de alarmes iniciado ({} alarmes ativos retomados)", open.len());
+            }
+            Err(e) => println!("⚠️ Alarmes: erro ao carregar ocorrências ativas: {}", e),
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_millis(EVALUATE_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+
+            if RELOAD_REQUESTED.swap(false, Ordering::SeqCst) || loaded_at.map_or(true, |at| at.elapsed() >= RELOAD_DEFINITIONS_INTERVAL) {
+                match database.list_alarm_definitions(None) {
+                    Ok(list) => {
+                        definitions = list.into_iter().filter(|d| d.enabled).collect();
+                        // Alarme excluído/desativado: ocorrência ativa normaliza sem valor
+                        let removed: Vec<i64> = states.keys().copied().filter(|id| !definitions.iter().any(|d| d.id == *id)).collect();
+                        for alarm_id in removed {
+                            if let Some(occurrence_id) = states.remove(&alarm_id).and_then(|s| s.occurrence_id) {
+                                clear(&app_handle, &database, occurrence_id, alarm_id, None);
+                            }
+                        }
+                    }
+                    Err(e) => println!("⚠️ Alarmes: erro ao carregar definições: {}", e),
+                }
+                loaded_at = Some(Instant::now());
+            }
+            if definitions.is_empty() {
+                continue;
+            }
+
+            let Some(smart_cache) = websocket_state.read().await.as_ref().map(|s| s.smart_cache()) else {
+                continue; // Sem WebSocket rodando não há valores ao vivo
+            };
+            let values: HashMap<(String, String), String> = smart_cache.snapshot(None).into_iter()
+                .map(|c| ((c.plc_ip, c.tag_name), c.value))
+                .collect();
+
+            let now = Instant::now();
+            for definition in &definitions {
+                let Some(raw) = values.get(&(definition.plc_ip.clone(), definition.tag_name.clone())) else { continue };
+                let Some(value) = numeric_value(raw) else { continue };
+                let state = states.entry(definition.id).or_default();
+                let active = state.occurrence_id.is_some();
+
+                // Sem transição pendente: condição igual ao estado atual
+                if condition_met(definition, value, active) == active {
+                    state.transition_since = None;
+                    continue;
+                }
+                let since = *state.transition_since.get_or_insert(now);
+                let delay_ms = if active { definition.off_delay_ms } else { definition.on_delay_ms };
+                if now.duration_since(since) < Duration::from_millis(delay_ms) {
+                    continue;
+                }
+                state.transition_since = None;
+
+                state.occurrence_id = match state.occurrence_id {
+                    // Falha ao gravar mantém o estado: tenta de novo na próxima avaliação
+                    Some(occurrence_id) => (!clear(&app_handle, &database, occurrence_id, definition.id, Some(raw))).then_some(occurrence_id),
+                    None => raise(&app_handle, &database, definition, raw),
+                };
+            }
+        }
+    });
+}
+
+fn raise(app_handle: &AppHandle, database: &Arc<Database>, definition: &AlarmDefinition, value: &str) -> Option<i64> {
+    let message = describe(definition, value);
+    let mut occurrence = AlarmOccurrence {
+        id: 0,
+        alarm_id: definition.id,
+        plc_ip: definition.plc_ip.clone(),
+        tag_name: definition.tag_name.clone(),
+        name: definition.name.clone(),
+        severity: definition.severity.clone(),
+        message: message.text.clone(),
+        raised_value: value.to_string(),
+        raised_at_ms: chrono::Utc::now().timestamp_millis(),
+        cleared_value: None,
+        cleared_at_ms: None,
+        acked_at_ms: None,
+        acked_by: None,
+    };
+    occurrence.id = match database.insert_alarm_occurrence(&occurrence) {
+        Ok(id) => id,
+        Err(e) => {
+            println!("❌ Alarmes: erro ao gravar '{}': {}", definition.name, e);
+            return None;
+        }
+    };
+
+    println!("🚨 ALARME [{}] {} - {} ({} = {})", definition.severity, definition.name, message.text, definition.tag_name, value);
+    let payload = serde_json::json!({
+        "occurrence": occurrence,
+        "plc_ip": occurrence.plc_ip,
+        "message": message.text,
+        "message_key": message.key,
+        "message_args": message.args,
+    });
+    let _ = app_handle.emit("alarm-raised", &payload);
+    // Alarme crítico congela logs e frames do PLC em um incidente
+    crate::incident_capture::capture_for_event(app_handle, database.clone(), "alarm-raised", &occurrence.severity, &payload, None);
+    Some(occurrence.id)
+}
+
+fn clear(app_handle: &AppHandle, database: &Database, occurrence_id: i64, alarm_id: i64, value: Option<&str>) -> bool {
+    let cleared_at_ms = chrono::Utc::now().timestamp_millis();
+    match database.clear_alarm_occurrence(occurrence_id, value, cleared_at_ms) {
+        Ok(_) => {
+            println!("✅ Alarme #{} normalizado (ocorrência #{})", alarm_id, occurrence_id);
+            let _ = app_handle.emit("alarm-cleared", serde_json::json!({
+                "occurrence_id": occurrence_id,
+                "alarm_id": alarm_id,
+                "cleared_value": value,
+                "cleared_at_ms": cleared_at_ms,
+            }));
+            true
+        }
+        Err(e) => {
+            println!("❌ Alarmes: erro ao normalizar ocorrência #{}: {}", occurrence_id, e);
+            false
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::database::{AlarmOccurrence, Database, Notification};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// ============================================================================
+// KPIs DE ALARME (REVISÃO DE RACIONALIZAÇÃO ESTILO ISA-18.2)
+// ============================================================================
+//
+// Calculados sobre o histórico da central de notificações (cada notificação é
+// uma ocorrência de alarme e "lida" equivale a reconhecida, read_at) somado às
+// ocorrências do motor de alarmes (alarm_history, reconhecidas em acked_at_ms). Metas de
+// referência da ISA-18.2: ~6 alarmes/hora por operador é aceitável, 12 é o
+// máximo gerenciável; mais de 10 alarmes em 10 minutos caracteriza inundação.
+
+const FLOOD_WINDOW_S: i64 = 600;
+const FLOOD_THRESHOLD: usize = 10;
+const ACCEPTABLE_PER_HOUR: f64 = 6.0;
+const MANAGEABLE_PER_HOUR: f64 = 12.0;
+const TOP_FREQUENT_LIMIT: usize = 10;
+const STANDING_LIMIT: u32 = 500;
+
+/// Períodos pré-definidos aceitos por `period_bounds` (em segundos)
+pub const PERIODS: &[(&str, i64)] = &[
+    ("1h", 3_600),
+    ("8h", 8 * 3_600),   // Um turno
+    ("24h", 24 * 3_600),
+    ("7d", 7 * 24 * 3_600),
+    ("30d", 30 * 24 * 3_600),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FrequentAlarm {
+    pub title: String,
+    pub source_event: Option<String>,
+    pub severity: String,
+    pub count: usize,
+    pub share_pct: f64, // Participação no total do período
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlarmKpis {
+    pub from_s: i64,
+    pub to_s: i64,
+    pub total_alarms: usize,
+    pub alarms_per_hour: f64,
+    pub peak_alarms_10min: usize,
+    pub flood_periods: usize,                 // Janelas de 10 min com mais de 10 alarmes
+    pub time_in_flood_pct: f64,
+    pub rating: String,                       // "acceptable", "manageable", "overloaded"
+    pub by_severity: HashMap<String, usize>,
+    pub top_frequent: Vec<FrequentAlarm>,
+    pub top_frequent_share_pct: f64,          // Quanto do total os mais frequentes representam
+    pub acknowledged: usize,
+    pub mean_time_to_ack_s: Option<f64>,
+    pub max_time_to_ack_s: Option<i64>,
+    pub standing_threshold_s: i64,
+    pub standing_alarms: Vec<Notification>,   // Não reconhecidos há mais que o limite (qualquer período)
+    pub standing_process_alarms: Vec<AlarmOccurrence>, // 🆕 Idem, do motor de alarmes
+}
+
+/// Resolve o período: "1h", "8h", "24h", "7d", "30d" ou intervalo explícito (segundos Unix)
+pub fn period_bounds(period: Option<&str>, from_s: Option<i64>, to_s: Option<i64>, now_s: i64) -> Result<(i64, i64), String> {
+    match (from_s, to_s) {
+        (Some(from_s), Some(to_s)) if to_s > from_s => Ok((from_s, to_s)),
+        (Some(_), Some(_)) => Err("Período inválido: fim deve ser maior que início".to_string()),
+        _ => {
+            let period = period.unwrap_or("24h");
+            let (_, length) = PERIODS.iter().find(|(name, _)| *name == period)
+                .ok_or_else(|| format!("Período desconhecido: {} (use {})",
+                    period, PERIODS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")))?;
+            Ok((now_s - length, now_s))
+        }
+    }
+}
+
+/// Calcula os KPIs de alarme do período
+pub fn compute(db: &Database, from_s: i64, to_s: i64, standing_threshold_s: i64, now_s: i64) -> Result<AlarmKpis, String> {
+    let mut occurrences = db.list_notification_occurrences(from_s, to_s)
+        .map_err(|e| format!("Erro ao ler histórico de alarmes: {}", e))?;
+    let standing_alarms = db.list_standing_notifications(now_s - standing_threshold_s, STANDING_LIMIT)
+        .map_err(|e| format!("Erro ao ler alarmes em aberto: {}", e))?;
+
+    // Motor de alarmes: mesma tupla (severidade, título, origem, disparo, reconhecimento) em segundos
+    let process_alarms = db.list_alarm_history(from_s * 1_000, to_s * 1_000, None, u32::MAX)
+        .map_err(|e| format!("Erro ao ler histórico do motor de alarmes: {}", e))?;
+    occurrences.extend(process_alarms.into_iter().map(|a| {
+        (a.severity, a.name, Some("alarm-raised".to_string()), a.raised_at_ms / 1_000, a.acked_at_ms.map(|ms| ms / 1_000))
+    }));
+    let standing_process_alarms = db.list_standing_alarm_occurrences((now_s - standing_threshold_s) * 1_000, STANDING_LIMIT)
+        .map_err(|e| format!("Erro ao ler alarmes do motor em aberto: {}", e))?;
+
+    let total_alarms = occurrences.len();
+    let hours = (to_s - from_s) as f64 / 3_600.0;
+    let alarms_per_hour = if hours > 0.0 { total_alarms as f64 / hours } else { 0.0 };
+
+    // Janelas fixas de 10 minutos a partir do início do período
+    let window_count = ((to_s - from_s + FLOOD_WINDOW_S - 1) / FLOOD_WINDOW_S).max(1) as usize;
+    let mut windows = vec![0usize; window_count];
+    for (_, _, _, created_at, _) in &occurrences {
+        let index = ((created_at - from_s) / FLOOD_WINDOW_S) as usize;
+        windows[index.min(window_count - 1)] += 1;
+    }
+    let peak_alarms_10min = windows.iter().copied().max().unwrap_or(0);
+    let flood_periods = windows.iter().filter(|count| **count > FLOOD_THRESHOLD).count();
+
+    let mut by_severity: HashMap<String, usize> = HashMap::new();
+    let mut frequency: HashMap<(String, Option<String>), (String, usize)> = HashMap::new();
+    let mut ack_times = Vec::new();
+    for (severity, title, source_event, created_at, read_at) in occurrences {
+        *by_severity.entry(severity.clone()).or_insert(0) += 1;
+        frequency.entry((title, source_event)).or_insert((severity, 0)).1 += 1;
+        if let Some(read_at) = read_at {
+            ack_times.push((read_at - created_at).max(0));
+        }
+    }
+
+    let mut top_frequent: Vec<FrequentAlarm> = frequency.into_iter()
+        .map(|((title, source_event), (severity, count))| FrequentAlarm {
+            title,
+            source_event,
+            severity,
+            count,
+            share_pct: count as f64 * 100.0 / total_alarms as f64,
+        })
+        .collect();
+    top_frequent.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.title.cmp(&b.title)));
+    top_frequent.truncate(TOP_FREQUENT_LIMIT);
+    let top_frequent_share_pct = top_frequent.iter().map(|a| a.share_pct).sum();
+
+    let rating = if alarms_per_hour <= ACCEPTABLE_PER_HOUR {
+        "acceptable"
+    } else if alarms_per_hour <= MANAGEABLE_PER_HOUR {
+        "manageable"
+    } else {
+        "overloaded"
+    };
+
+    Ok(AlarmKpis {
+        from_s,
+        to_s,
+        total_alarms,
+        alarms_per_hour,
+        peak_alarms_10min,
+        flood_periods,
+        time_in_flood_pct: flood_periods as f64 * 100.0 / window_count as f64,
+        rating: rating.to_string(),
+        by_severity,
+        top_frequent,
+        top_frequent_share_pct,
+        acknowledged: ack_times.len(),
+        mean_time_to_ack_s: (!ack_times.is_empty()).then(|| ack_times.iter().sum::<i64>() as f64 / ack_times.len() as f64),
+        max_time_to_ack_s: ack_times.iter().copied().max(),
+        standing_threshold_s,
+        standing_alarms,
+        standing_process_alarms,
+    })
+}
@@ -0,0 +1,103 @@
+use crate::commands::{TcpServerState, WebSocketServerState};
+use crate::database::{Database, RuntimeState};
+use crate::tcp_server::TcpServer;
+use crate::websocket_server::{WebSocketConfig, WebSocketServer};
+use std::sync::Arc;
+use tauri::AppHandle;
+
+// ============================================================================
+// RETOMADA AUTOMÁTICA DOS SERVIDORES APÓS REINÍCIO
+// ============================================================================
+//
+// start/stop dos servidores TCP e WebSocket gravam em `runtime_state` se o
+// servidor ficou rodando e com qual configuração (porta TCP, WebSocketConfig).
+// No setup, o que estava rodando quando o app fechou (ou caiu) sobe de novo,
+// TCP antes do WebSocket. Cada componente pode ser excluído da retomada
+// (tcp_autostart / websocket_autostart) sem perder o estado gravado.
+
+fn update(db: &Database, apply: impl FnOnce(&mut RuntimeState)) {
+    let result = db.load_runtime_state().and_then(|mut state| {
+        apply(&mut state);
+        state.updated_at = chrono::Utc::now().timestamp();
+        db.save_runtime_state(&state)
+    });
+    if let Err(e) = result {
+        println!("⚠️ Erro ao gravar estado de execução dos servidores: {}", e);
+    }
+}
+
+/// Servidor TCP iniciado (Some(porta)) ou parado (None)
+pub fn remember_tcp(db: &Database, port: Option<u16>) {
+    update(db, |state| {
+        state.tcp_running = port.is_some();
+        if let Some(port) = port {
+            state.tcp_port = port;
+        }
+    });
+}
+
+/// WebSocket iniciado (Some(config)) ou parado (None)
+pub fn remember_websocket(db: &Database, config: Option<&WebSocketConfig>) {
+    update(db, |state| {
+        state.websocket_running = config.is_some();
+        if let Some(config) = config {
+            state.websocket_config = Some(config.clone());
+        }
+    });
+}
+
+/// Opções por componente; o estado de execução gravado não muda
+pub fn set_autostart(db: &Database, tcp: bool, websocket: bool) -> Result<(), String> {
+    let mut state = db.load_runtime_state()
+        .map_err(|e| format!("Erro ao carregar estado de execução: {}", e))?;
+    state.tcp_autostart = tcp;
+    state.websocket_autostart = websocket;
+    state.updated_at = chrono::Utc::now().timestamp();
+    db.save_runtime_state(&state)
+        .map_err(|e| format!("Erro ao salvar retomada automática: {}", e))
+}
+
+/// Chamado no setup: sobe o que estava rodando no último encerramento
+pub fn restore_servers(app_handle: AppHandle, db: Arc<Database>, tcp_state: TcpServerState, websocket_state: WebSocketServerState) {
+    let state = match db.load_runtime_state() {
+        Ok(state) => state,
+        Err(e) => {
+            println!("⚠️ Estado de execução não carregado, servidores não retomados: {}", e);
+            return;
+        }
+    };
+    let restore_tcp = state.tcp_autostart && state.tcp_running;
+    let restore_websocket = state.websocket_autostart && state.websocket_running;
+    if !restore_tcp && !restore_websocket {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        if restore_tcp {
+            let mut server = TcpServer::new(state.tcp_port, app_handle.clone(), Some(db.clone()));
+            match server.start_server().await {
+                Ok(msg) => {
+                    println!("♻️ Servidor TCP retomado: {}", msg);
+                    *tcp_state.write().await = Some(server);
+                }
+                // Mantém tcp_running: a próxima abertura tenta de novo (ex: porta ocupada)
+                Err(e) => println!("⚠️ Servidor TCP não retomado na porta {}: {}", state.tcp_port, e),
+            }
+        }
+
+        if restore_websocket {
+            let Some(config) = state.websocket_config else {
+                println!("⚠️ WebSocket não retomado: última configuração não gravada");
+                return;
+            };
+            let mut server = WebSocketServer::new(config, app_handle, db, Some(tcp_state));
+            match server.start().await {
+                Ok(msg) => {
+                    println!("♻️ WebSocket retomado: {}", msg);
+                    *websocket_state.write().await = Some(server);
+                }
+                Err(e) => println!("⚠️ WebSocket não retomado: {}", e),
+            }
+        }
+    });
+}
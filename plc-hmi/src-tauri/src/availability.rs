@@ -0,0 +1,323 @@
+use crate::database::{Database, PlcAvailabilityDay, PlcConnectionEvent};
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Listener};
+
+// ============================================================================
+// DISPONIBILIDADE DOS PLCs (RELATÓRIO MENSAL DE CONFIABILIDADE DA MANUTENÇÃO)
+// ============================================================================
+//
+// Cada conexão/queda de PLC e cada início/parada do servidor TCP vira uma linha
+// em plc_connection_events. Um job consolida os eventos por PLC e por dia local
+// em plc_availability_daily (up/down em ms, quedas e reconexões); semanas e
+// meses são somas dos dias. Regras:
+//   - só contam PLCs com estrutura configurada
+//   - com o servidor parado o estado do PLC é desconhecido: não conta up nem down
+//   - após o servidor iniciar, o PLC está "down" até conectar
+//   - app fechado sem "tcp-server-stopped" (queda de energia): na abertura é gravada
+//     uma parada no instante do último evento/consolidação conhecido
+// MTBF = tempo up / quedas; MTTR = tempo down / reconexões.
+
+const ROLLUP_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+pub const EVENT_CONNECTED: &str = "connected";
+pub const EVENT_DISCONNECTED: &str = "disconnected";
+pub const EVENT_SERVER_STARTED: &str = "server_started";
+pub const EVENT_SERVER_STOPPED: &str = "server_stopped";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailabilityKpis {
+    pub plc_ip: String,
+    pub period: String,           // "2026-10-16", "2026-W42", "2026-10" ou "total"
+    pub from_day: String,
+    pub to_day: String,
+    pub observed_ms: i64,         // up + down (servidor rodando)
+    pub up_ms: i64,
+    pub down_ms: i64,
+    pub uptime_pct: Option<f64>,  // None = nada observado
+    pub disconnections: u32,
+    pub mtbf_s: Option<f64>,      // None = sem quedas no período
+    pub mttr_s: Option<f64>,      // None = sem reconexões no período
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum LinkState {
+    Up,
+    Down,
+    Unknown,
+}
+
+#[derive(Default)]
+struct Totals {
+    up_ms: i64,
+    down_ms: i64,
+    disconnections: u32,
+    repairs: u32,
+}
+
+/// Estado dos PLCs durante a reprodução dos eventos
+struct Replay<'a> {
+    tracked: &'a HashSet<String>,
+    server_running: bool,
+    states: HashMap<String, LinkState>,
+    totals: HashMap<String, Totals>,
+    cursor_ms: i64,
+}
+
+impl<'a> Replay<'a> {
+    fn new(tracked: &'a HashSet<String>, initial: &[PlcConnectionEvent], from_ms: i64) -> Self {
+        let server_running = initial.iter()
+            .rev()
+            .find(|e| e.plc_ip.is_none())
+            .map_or(false, |e| e.event == EVENT_SERVER_STARTED);
+        let states = tracked.iter().map(|ip| {
+            let last = initial.iter().rev().find(|e| e.plc_ip.as_deref() == Some(ip.as_str()));
+            let state = match last.map(|e| e.event.as_str()) {
+                _ if !server_running => LinkState::Unknown,
+                Some(EVENT_CONNECTED) => LinkState::Up,
+                _ => LinkState::Down,
+            };
+            (ip.clone(), state)
+        }).collect();
+        Replay { tracked, server_running, states, totals: HashMap::new(), cursor_ms: from_ms }
+    }
+
+    fn advance(&mut self, to_ms: i64) {
+        let elapsed = to_ms - self.cursor_ms;
+        if elapsed <= 0 {
+            return;
+        }
+        for (ip, state) in &self.states {
+            let totals = self.totals.entry(ip.clone()).or_default();
+            match state {
+                LinkState::Up => totals.up_ms += elapsed,
+                LinkState::Down => totals.down_ms += elapsed,
+                LinkState::Unknown => {}
+            }
+        }
+        self.cursor_ms = to_ms;
+    }
+
+    fn apply(&mut self, event: &PlcConnectionEvent) {
+        self.advance(event.ts_ms);
+        match (event.plc_ip.as_deref(), event.event.as_str()) {
+            (None, EVENT_SERVER_STARTED) => {
+                self.server_running = true;
+                self.states.values_mut().for_each(|s| *s = LinkState::Down);
+            }
+            (None, EVENT_SERVER_STOPPED) => {
+                self.server_running = false;
+                self.states.values_mut().for_each(|s| *s = LinkState::Unknown);
+            }
+            (Some(ip), event_name) if self.server_running && self.tracked.contains(ip) => {
+                let state = self.states.entry(ip.to_string()).or_insert(LinkState::Down);
+                let totals = self.totals.entry(ip.to_string()).or_default();
+                match (event_name, *state) {
+                    (EVENT_CONNECTED, LinkState::Down) => {
+                        totals.repairs += 1;
+                        *state = LinkState::Up;
+                    }
+                    (EVENT_DISCONNECTED, LinkState::Up) => {
+                        totals.disconnections += 1;
+                        *state = LinkState::Down;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn local_midnight_ms(day: NaiveDate) -> i64 {
+    Local.from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .map(|d| d.timestamp_millis())
+        .unwrap_or(0)
+}
+
+/// Consolida um dia local para os PLCs configurados (dia atual: até agora)
+fn rollup_day(db: &Database, tracked: &HashSet<String>, day: NaiveDate, now_ms: i64) -> Result<Vec<PlcAvailabilityDay>, String> {
+    let from_ms = local_midnight_ms(day);
+    let to_ms = local_midnight_ms(day + ChronoDuration::days(1)).min(now_ms);
+    let initial = db.last_connection_events_before(from_ms)
+        .map_err(|e| format!("Erro ao carregar estado inicial de {}: {}", day, e))?;
+    let events = db.list_connection_events(None, from_ms, to_ms)
+        .map_err(|e| format!("Erro ao carregar eventos de {}: {}", day, e))?;
+
+    let mut replay = Replay::new(tracked, &initial, from_ms);
+    for event in &events {
+        replay.apply(event);
+    }
+    replay.advance(to_ms);
+
+    let updated_at = chrono::Utc::now().timestamp();
+    Ok(tracked.iter().map(|ip| {
+        let totals = replay.totals.remove(ip).unwrap_or_default();
+        PlcAvailabilityDay {
+            plc_ip: ip.clone(),
+            day: day.format(DAY_FORMAT).to_string(),
+            up_ms: totals.up_ms,
+            down_ms: totals.down_ms,
+            disconnections: totals.disconnections,
+            repairs: totals.repairs,
+            updated_at,
+        }
+    }).collect())
+}
+
+/// Reconsolida do último dia consolidado (ou do primeiro evento) até hoje
+pub fn rollup(db: &Database) -> Result<usize, String> {
+    let Some((first_ms, _)) = db.connection_events_range().map_err(|e| format!("Erro ao consultar eventos: {}", e))? else {
+        return Ok(0);
+    };
+    let tracked: HashSet<String> = db.list_configured_plcs()
+        .map_err(|e| format!("Erro ao carregar PLCs configurados: {}", e))?
+        .into_iter()
+        .collect();
+    let today = Local::now().date_naive();
+    let start = db.last_availability_day().ok().flatten()
+        .and_then(|(day, _)| NaiveDate::parse_from_str(&day, DAY_FORMAT).ok())
+        .or_else(|| Local.timestamp_millis_opt(first_ms).single().map(|d| d.date_naive()))
+        .unwrap_or(today)
+        .min(today);
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let mut rows = Vec::new();
+    let mut day = start;
+    while day <= today {
+        rows.extend(rollup_day(db, &tracked, day, now_ms)?);
+        day += ChronoDuration::days(1);
+    }
+    db.upsert_availability_days(&rows)
+        .map_err(|e| format!("Erro ao gravar disponibilidade: {}", e))?;
+    Ok(rows.len())
+}
+
+/// Chave do período de um dia na granularidade pedida
+fn period_key(day: &str, granularity: &str) -> String {
+    let Ok(date) = NaiveDate::parse_from_str(day, DAY_FORMAT) else {
+        return day.to_string();
+    };
+    match granularity {
+        "week" => {
+            let week = date.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+        "month" => date.format("%Y-%m").to_string(),
+        "total" => "total".to_string(),
+        _ => day.to_string(),
+    }
+}
+
+pub fn validate_granularity(granularity: &str) -> Result<(), String> {
+    match granularity {
+        "day" | "week" | "month" | "total" => Ok(()),
+        _ => Err(format!("Granularidade inválida: {} (use day, week, month ou total)", granularity)),
+    }
+}
+
+/// Soma os dias consolidados por PLC e período
+pub fn aggregate(days: &[PlcAvailabilityDay], granularity: &str) -> Vec<AvailabilityKpis> {
+    let mut groups: BTreeMap<(String, String), Vec<&PlcAvailabilityDay>> = BTreeMap::new();
+    for day in days {
+        groups.entry((day.plc_ip.clone(), period_key(&day.day, granularity))).or_default().push(day);
+    }
+
+    groups.into_iter().map(|((plc_ip, period), days)| {
+        let up_ms: i64 = days.iter().map(|d| d.up_ms).sum();
+        let down_ms: i64 = days.iter().map(|d| d.down_ms).sum();
+        let disconnections: u32 = days.iter().map(|d| d.disconnections).sum();
+        let repairs: u32 = days.iter().map(|d| d.repairs).sum();
+        let observed_ms = up_ms + down_ms;
+        AvailabilityKpis {
+            plc_ip,
+            period,
+            from_day: days.first().map(|d| d.day.clone()).unwrap_or_default(),
+            to_day: days.last().map(|d| d.day.clone()).unwrap_or_default(),
+            observed_ms,
+            up_ms,
+            down_ms,
+            uptime_pct: (observed_ms > 0).then(|| up_ms as f64 * 100.0 / observed_ms as f64),
+            disconnections,
+            mtbf_s: (disconnections > 0).then(|| up_ms as f64 / 1000.0 / disconnections as f64),
+            mttr_s: (repairs > 0).then(|| down_ms as f64 / 1000.0 / repairs as f64),
+        }
+    }).collect()
+}
+
+fn record(database: &Database, plc_ip: Option<&str>, event: &str, reason: Option<&str>) {
+    if let Err(e) = database.add_connection_event(plc_ip, event, reason, chrono::Utc::now().timestamp_millis()) {
+        println!("⚠️ Disponibilidade: erro ao gravar evento '{}': {}", event, e);
+    }
+}
+
+fn payload_field(payload: &str, key: &str) -> Option<String> {
+    let payload: serde_json::Value = serde_json::from_str(payload).ok()?;
+    payload.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// App fechado sem parar o servidor: fecha o período no último instante conhecido
+fn close_unclean_shutdown(database: &Database) {
+    let Ok(last) = database.last_connection_events_before(i64::MAX) else { return };
+    let server_running = last.iter().rev().find(|e| e.plc_ip.is_none())
+        .map_or(false, |e| e.event == EVENT_SERVER_STARTED);
+    if !server_running {
+        return;
+    }
+    let last_event_ms = last.iter().map(|e| e.ts_ms).max().unwrap_or(0);
+    let last_rollup_ms = database.last_availability_day().ok().flatten()
+        .map_or(0, |(_, updated_at)| updated_at * 1000);
+    let ts_ms = last_event_ms.max(last_rollup_ms);
+    match database.add_connection_event(None, EVENT_SERVER_STOPPED, Some("app encerrado sem parar o servidor"), ts_ms) {
+        Ok(_) => println!("📉 Disponibilidade: parada do servidor não registrada - fechada em {}", ts_ms),
+        Err(e) => println!("⚠️ Disponibilidade: erro ao fechar período anterior: {}", e),
+    }
+}
+
+/// Grava os eventos de conexão e consolida a disponibilidade periodicamente
+pub fn start_availability_tracker(app_handle: AppHandle, database: Arc<Database>) {
+    close_unclean_shutdown(&database);
+
+    let db = database.clone();
+    app_handle.listen("plc-connected", move |event| {
+        if let Some(ip) = payload_field(event.payload(), "ip") {
+            record(&db, Some(&ip), EVENT_CONNECTED, None);
+        }
+    });
+    let db = database.clone();
+    app_handle.listen("plc-disconnected", move |event| {
+        if let Some(ip) = payload_field(event.payload(), "ip") {
+            record(&db, Some(&ip), EVENT_DISCONNECTED, None);
+        }
+    });
+    // Watchdog mata a conexão sem emitir plc-disconnected
+    let db = database.clone();
+    app_handle.listen("tcp-connection-dead", move |event| {
+        if let Some(ip) = payload_field(event.payload(), "ip") {
+            record(&db, Some(&ip), EVENT_DISCONNECTED, Some("watchdog"));
+        }
+    });
+    let db = database.clone();
+    app_handle.listen("tcp-server-started", move |_| record(&db, None, EVENT_SERVER_STARTED, None));
+    let db = database.clone();
+    app_handle.listen("tcp-server-stopped", move |_| record(&db, None, EVENT_SERVER_STOPPED, None));
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(ROLLUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let db = database.clone();
+            let result = tokio::task::spawn_blocking(move || rollup(&db)).await
+                .unwrap_or_else(|e| Err(format!("Task de disponibilidade falhou: {}", e)));
+            if let Err(e) = result {
+                println!("⚠️ Disponibilidade: {}", e);
+            }
+        }
+    });
+    println!("📈 Disponibilidade dos PLCs: eventos de conexão sendo registrados");
+}
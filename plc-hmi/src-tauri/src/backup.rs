@@ -0,0 +1,208 @@
+use crate::database::{fnv1a_64, BackupConfig, Database};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// BACKUP AUTOMÁTICO DIÁRIO DO BANCO DE CONFIGURAÇÃO
+// ============================================================================
+//
+// Os backups ficam em `<pasta do banco>/backups/plc_hmi_AAAAMMDD_HHMMSS.db`,
+// cada um com um arquivo `.fnv` ao lado contendo o hash FNV-1a do conteúdo.
+// Ficam os `retention` mais recentes (backup_config, padrão 7).
+// A listagem só lê o `.fnv`; o conteúdo é re-hasheado ao restaurar ou em
+// `verify_backup` (pedido explícito).
+
+const MIN_RETENTION: u32 = 1;
+const MAX_RETENTION: u32 = 365;
+const BACKUP_MAX_AGE_SECS: i64 = 24 * 3600;
+const BACKUP_CHECK_INTERVAL_SECS: u64 = 3600;
+const BACKUP_PREFIX: &str = "plc_hmi_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub created_at: i64,
+    pub checksum: Option<String>,
+    pub valid: Option<bool>, // Checksum do arquivo confere com o registrado (None = não verificado)
+}
+
+fn backups_dir(db: &Database) -> PathBuf {
+    db.db_path()
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default()
+        .join("backups")
+}
+
+fn checksum_path(backup: &Path) -> PathBuf {
+    backup.with_extension("db.fnv")
+}
+
+fn file_checksum(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Erro ao ler {:?}: {}", path, e))?;
+    Ok(format!("{:016x}", fnv1a_64(&bytes)))
+}
+
+/// Dados do backup a partir do `.fnv`; `verify` re-hasheia o arquivo
+fn backup_info(path: &Path, verify: bool) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let metadata = fs::metadata(path).ok()?;
+    let created_at = metadata.modified().ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let checksum = fs::read_to_string(checksum_path(path)).ok().map(|c| c.trim().to_string());
+    let valid = verify.then(|| match &checksum {
+        Some(expected) => file_checksum(path).map(|actual| &actual == expected).unwrap_or(false),
+        None => false,
+    });
+
+    Some(BackupInfo { file_name, size_bytes: metadata.len(), created_at, checksum, valid })
+}
+
+/// Lista os backups (mais recentes primeiro) com o checksum registrado, sem verificar
+pub fn list_backups(db: &Database) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(backups_dir(db)) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.starts_with(BACKUP_PREFIX) && name.ends_with(".db")
+        })
+        .filter_map(|p| backup_info(&p, false))
+        .collect();
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    backups
+}
+
+pub fn validate_config(config: &BackupConfig) -> Result<(), String> {
+    if !(MIN_RETENTION..=MAX_RETENTION).contains(&config.retention) {
+        return Err(format!("Quantidade de backups mantidos deve estar entre {} e {}", MIN_RETENTION, MAX_RETENTION));
+    }
+    Ok(())
+}
+
+/// Quantos backups manter (configuração salva ou padrão)
+fn retention(db: &Database) -> usize {
+    db.load_backup_config()
+        .unwrap_or_else(|e| {
+            println!("⚠️ Erro ao carregar configuração de backup, usando padrão: {}", e);
+            BackupConfig::default()
+        })
+        .retention
+        .max(MIN_RETENTION) as usize
+}
+
+/// Aplica a retenção configurada agora (ex: após reduzir a quantidade)
+pub fn apply_retention(db: &Database) {
+    enforce_retention(db, retention(db));
+}
+
+/// Remove os backups mais antigos além de `retention`
+fn enforce_retention(db: &Database, retention: usize) {
+    let dir = backups_dir(db);
+    for old in list_backups(db).into_iter().skip(retention) {
+        let path = dir.join(&old.file_name);
+        let _ = fs::remove_file(checksum_path(&path));
+        match fs::remove_file(&path) {
+            Ok(_) => println!("🗑️ Backup antigo removido: {}", old.file_name),
+            Err(e) => println!("⚠️ Erro ao remover backup {}: {}", old.file_name, e),
+        }
+    }
+}
+
+/// Cria um backup agora, grava o checksum e aplica a retenção
+pub fn create_backup(db: &Database) -> Result<BackupInfo, String> {
+    let info = write_backup(db)?;
+    apply_retention(db);
+    Ok(info)
+}
+
+fn write_backup(db: &Database) -> Result<BackupInfo, String> {
+    let dir = backups_dir(db);
+    fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar pasta de backups: {}", e))?;
+
+    let file_name = format!("{}{}.db", BACKUP_PREFIX, chrono::Local::now().format("%Y%m%d_%H%M%S"));
+    let path = dir.join(&file_name);
+
+    db.backup_into(&path).map_err(|e| format!("Erro ao gerar backup: {}", e))?;
+    let checksum = file_checksum(&path)?;
+    fs::write(checksum_path(&path), &checksum)
+        .map_err(|e| format!("Erro ao gravar checksum do backup: {}", e))?;
+
+    println!("💾 Backup de configuração criado: {} ({})", file_name, checksum);
+    backup_info(&path, false).ok_or_else(|| "Backup criado mas não encontrado".to_string())
+}
+
+fn backup_path(db: &Database, file_name: &str) -> Result<PathBuf, String> {
+    if file_name.contains('/') || file_name.contains('\\') || !file_name.starts_with(BACKUP_PREFIX) {
+        return Err(format!("Nome de backup inválido: {}", file_name));
+    }
+    Ok(backups_dir(db).join(file_name))
+}
+
+/// Re-hasheia um backup e compara com o checksum registrado
+pub fn verify_backup(db: &Database, file_name: &str) -> Result<BackupInfo, String> {
+    let path = backup_path(db, file_name)?;
+    backup_info(&path, true).ok_or_else(|| format!("Backup não encontrado: {}", file_name))
+}
+
+/// Restaura as tabelas de configuração a partir de um backup verificado
+pub fn restore_backup(db: &Database, file_name: &str) -> Result<usize, String> {
+    let path = backup_path(db, file_name)?;
+    let info = backup_info(&path, true).ok_or_else(|| format!("Backup não encontrado: {}", file_name))?;
+    if info.valid != Some(true) {
+        return Err(format!("Checksum do backup {} não confere - arquivo corrompido ou alterado", file_name));
+    }
+
+    // Backup de segurança do estado atual antes de sobrescrever
+    // (retenção só depois, para não apagar o backup que está sendo restaurado)
+    write_backup(db)?;
+
+    let rows = db.restore_config_from(&path).map_err(|e| format!("Erro ao restaurar backup: {}", e))?;
+    apply_retention(db);
+    println!("♻️ Configuração restaurada de {} ({} linhas)", file_name, rows);
+    Ok(rows)
+}
+
+/// Job em background: garante um backup a cada 24h (verifica de hora em hora)
+pub fn start_daily_backup(app_handle: AppHandle, database: Arc<Database>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(BACKUP_CHECK_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let now = chrono::Utc::now().timestamp();
+            let latest = list_backups(&database).first().map(|b| b.created_at).unwrap_or(0);
+            if now - latest < BACKUP_MAX_AGE_SECS {
+                continue;
+            }
+
+            let db = database.clone();
+            let result = tokio::task::spawn_blocking(move || create_backup(&db)).await
+                .unwrap_or_else(|e| Err(format!("Task de backup falhou: {}", e)));
+            match result {
+                Ok(info) => {
+                    let _ = app_handle.emit("config-backup-created", &info);
+                }
+                Err(message) => {
+                    println!("❌ Backup automático falhou: {}", message);
+                    let _ = app_handle.emit("config-backup-failed", serde_json::json!({
+                        "message": message,
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }));
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,259 @@
+use crate::database::Database;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{Emitter, Manager, Wry};
+
+// ============================================================================
+// AUDITORIA DE TAXA DE COMANDOS SENSÍVEIS
+// ============================================================================
+//
+// Todo comando Tauri passa por `audited` antes de executar. Os comandos
+// sensíveis (exclusões, alterações de configuração, desconexões, escrita de
+// arquivos) são contados por sessão (rótulo da janela/webview) em uma janela
+// deslizante. Passar do limite da categoria - ex: exclusão em massa por
+// script - gera o evento "security-anomaly" e uma entrada na auditoria.
+// Operações em lote contam pelo número de itens enviados.
+
+/// Comando → categoria
+pub const SENSITIVE_COMMANDS: &[(&str, &str)] = &[
+    ("disconnect_plc", "disconnect"),
+    ("run_connection_batch", "disconnect"),
+    ("stop_mqtt_status", "disconnect"),
+    ("delete_plc_structure", "delete"),
+    ("delete_tag_mapping", "delete"),
+    ("delete_tag_mappings_bulk", "delete"),
+    ("delete_tag_group_priority", "delete"),
+    ("delete_panel", "delete"),
+    ("delete_plc_rate_expectation", "delete"),
+    ("delete_public_stream_key", "delete"),
+    ("revoke_ws_token", "delete"),
+    ("clear_notifications", "delete"),
+    ("delete_alarm_definition", "delete"),
+    ("save_plc_structure", "config"),
+    ("save_plc_frame_profiles", "config"),
+    ("clone_plc_config", "config"),
+    ("migrate_plc_identity", "config"),
+    ("save_tag_mapping", "config"),
+    ("save_tag_mappings_bulk", "config"),
+    ("rename_tag", "config"),
+    ("save_tag_group_priority", "config"),
+    ("set_group_interval_override", "config"),
+    ("clear_group_interval_override", "config"),
+    ("save_websocket_config", "config"),
+    ("save_postgres_config", "config"),
+    ("restore_config_backup", "config"),
+    ("save_csv_logger_config", "config"),
+    ("save_health_config", "config"),
+    ("save_ws_session_config", "config"),
+    ("save_remote_tunnel_config", "config"),
+    ("save_plc_rate_expectation", "config"),
+    ("save_public_stream_key", "config"),
+    ("create_ws_token", "config"),
+    ("save_historian_targets", "config"),
+    ("save_historian_writer_config", "config"),
+    ("set_historian_tag_logging", "config"),
+    ("set_instance_identity", "config"),
+    ("set_backend_language", "config"),
+    ("start_mqtt_status", "config"),
+    ("save_alarm_definition", "config"),
+    ("save_flatline_config", "config"),
+    ("save_autostart_config", "config"),
+    ("save_parse_quarantine_config", "config"),
+    ("save_plc_alias", "config"),
+    ("delete_plc_alias", "delete"),
+    ("release_plc_quarantine", "config"),
+    ("write_file", "write"),
+    ("write_plc_variable", "write"),
+    ("approve_remote_command", "write"),
+];
+
+/// Categoria → (máximo de operações, janela em segundos)
+pub const RATE_LIMITS: &[(&str, u32, u64)] = &[
+    ("delete", 20, 60),
+    ("disconnect", 10, 60),
+    ("config", 60, 60),
+    ("write", 30, 60),
+];
+
+const MAX_RECENT_ANOMALIES: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityAnomaly {
+    pub session: String,
+    pub category: String,
+    pub count: u32,              // Operações na janela
+    pub limit: u32,
+    pub window_s: u64,
+    pub commands: Vec<String>,   // Comandos distintos na janela
+    pub timestamp: i64,          // ms Unix
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandRateEntry {
+    pub session: String,
+    pub command: String,
+    pub category: String,
+    pub total: u64,              // Invocações desde o início do backend
+    pub last_invoked_at: i64,    // ms Unix
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryRate {
+    pub session: String,
+    pub category: String,
+    pub in_window: u32,
+    pub limit: u32,
+    pub window_s: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandAuditReport {
+    pub commands: Vec<CommandRateEntry>,
+    pub rates: Vec<CategoryRate>,
+    pub anomalies: Vec<SecurityAnomaly>, // Mais recentes primeiro
+}
+
+#[derive(Default)]
+struct AuditorState {
+    // (sessão, categoria) → operações recentes (instante, comando, peso)
+    recent: HashMap<(String, &'static str), VecDeque<(Instant, &'static str, u32)>>,
+    // (sessão, comando) → (total, último ms)
+    totals: HashMap<(String, &'static str), (u64, i64)>,
+    // Não repetir o alerta da mesma sessão/categoria dentro da mesma janela
+    alerted_until: HashMap<(String, &'static str), Instant>,
+    anomalies: VecDeque<SecurityAnomaly>,
+}
+
+#[derive(Default)]
+pub struct CommandRateAuditor {
+    state: Mutex<AuditorState>,
+}
+
+impl CommandRateAuditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma invocação. Retorna a anomalia se o limite da categoria foi ultrapassado agora.
+    pub fn record(&self, session: &str, command: &str, weight: u32) -> Option<SecurityAnomaly> {
+        let (command, category) = SENSITIVE_COMMANDS.iter().copied().find(|(name, _)| *name == command)?;
+        let (_, limit, window_s) = RATE_LIMITS.iter().copied().find(|(name, _, _)| *name == category)?;
+        let now = Instant::now();
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let window = Duration::from_secs(window_s);
+        let key = (session.to_string(), category);
+
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        let total = state.totals.entry((session.to_string(), command)).or_insert((0, now_ms));
+        total.0 += 1;
+        total.1 = now_ms;
+
+        let recent = state.recent.entry(key.clone()).or_default();
+        recent.push_back((now, command, weight));
+        while recent.front().is_some_and(|(at, _, _)| now.duration_since(*at) > window) {
+            recent.pop_front();
+        }
+        let count: u32 = recent.iter().map(|(_, _, weight)| weight).sum();
+        if count <= limit || state.alerted_until.get(&key).is_some_and(|until| *until > now) {
+            return None;
+        }
+
+        let mut commands: Vec<String> = recent.iter().map(|(_, command, _)| command.to_string()).collect();
+        commands.sort();
+        commands.dedup();
+        let anomaly = SecurityAnomaly {
+            session: session.to_string(),
+            category: category.to_string(),
+            count,
+            limit,
+            window_s,
+            commands,
+            timestamp: now_ms,
+        };
+        state.alerted_until.insert(key, now + window);
+        state.anomalies.push_front(anomaly.clone());
+        state.anomalies.truncate(MAX_RECENT_ANOMALIES);
+        Some(anomaly)
+    }
+
+    pub fn report(&self, session: Option<&str>) -> CommandAuditReport {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        let matches = |s: &str| session.map_or(true, |wanted| wanted == s);
+
+        let mut commands: Vec<CommandRateEntry> = state.totals.iter()
+            .filter(|((s, _), _)| matches(s))
+            .map(|((s, command), (total, last))| CommandRateEntry {
+                session: s.clone(),
+                command: command.to_string(),
+                category: SENSITIVE_COMMANDS.iter().find(|(name, _)| name == command).map(|(_, c)| c.to_string()).unwrap_or_default(),
+                total: *total,
+                last_invoked_at: *last,
+            })
+            .collect();
+        commands.sort_by(|a, b| b.last_invoked_at.cmp(&a.last_invoked_at));
+
+        let rates = state.recent.iter()
+            .filter(|((s, _), _)| matches(s))
+            .filter_map(|((s, category), recent)| {
+                let (_, limit, window_s) = RATE_LIMITS.iter().copied().find(|(name, _, _)| name == category)?;
+                let in_window = recent.iter()
+                    .filter(|(at, _, _)| now.duration_since(*at) <= Duration::from_secs(window_s))
+                    .map(|(_, _, weight)| weight)
+                    .sum();
+                Some(CategoryRate { session: s.clone(), category: category.to_string(), in_window, limit, window_s })
+            })
+            .collect();
+
+        CommandAuditReport {
+            commands,
+            rates,
+            anomalies: state.anomalies.iter().filter(|a| matches(&a.session)).cloned().collect(),
+        }
+    }
+}
+
+/// Peso da invocação: itens do maior array do payload (operações em lote), mínimo 1
+fn invoke_weight(body: &InvokeBody) -> u32 {
+    match body {
+        InvokeBody::Json(serde_json::Value::Object(args)) => args.values()
+            .filter_map(|v| v.as_array().map(|a| a.len() as u32))
+            .max()
+            .unwrap_or(1)
+            .max(1),
+        _ => 1,
+    }
+}
+
+/// Envolve o handler de comandos gerado pelo Tauri com a auditoria de taxa
+pub fn audited<F>(handler: F) -> impl Fn(Invoke<Wry>) -> bool + Send + Sync + 'static
+where
+    F: Fn(Invoke<Wry>) -> bool + Send + Sync + 'static,
+{
+    move |invoke: Invoke<Wry>| {
+        let command = invoke.message.command();
+        if SENSITIVE_COMMANDS.iter().any(|(name, _)| *name == command) {
+            let webview = invoke.message.webview();
+            if let Some(auditor) = webview.try_state::<Arc<CommandRateAuditor>>() {
+                let weight = invoke_weight(invoke.message.payload());
+                if let Some(anomaly) = auditor.record(webview.label(), command, weight) {
+                    println!("🚨 Taxa anormal de comandos '{}' na sessão {}: {} em {}s (limite {})",
+                             anomaly.category, anomaly.session, anomaly.count, anomaly.window_s, anomaly.limit);
+                    if let Some(db) = webview.try_state::<Arc<Database>>() {
+                        let details = format!("{} operações '{}' em {}s (limite {}): {}",
+                                              anomaly.count, anomaly.category, anomaly.window_s, anomaly.limit, anomaly.commands.join(", "));
+                        if let Err(e) = db.add_audit_entry("security_anomaly", &anomaly.session, "alert", &details) {
+                            println!("⚠️ Falha ao registrar auditoria de security_anomaly: {}", e);
+                        }
+                    }
+                    let _ = webview.app_handle().emit("security-anomaly", &anomaly);
+                }
+            }
+        }
+        handler(invoke)
+    }
+}
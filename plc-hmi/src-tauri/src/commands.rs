@@ -1,20 +1,21 @@
 #[tauri::command]
 pub async fn reload_websocket_tag_groups(
     websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut ws_guard = websocket_state.write().await;
     match ws_guard.as_mut() {
         Some(server) => {
             server.reload_tag_groups().await?;
             Ok("WebSocket tag groups reloaded".to_string())
         }
-        None => Err("WebSocket server não está rodando".to_string())
+        None => Err(AppError::NotRunning("WebSocket server não está rodando".to_string()))
     }
 }
 use tauri::Emitter;
-use crate::tcp_server::{TcpServer, ConnectionStats};
-use crate::database::{Database, PlcStructureConfig, DataBlockConfig, TagMapping};
-use crate::websocket_server::{WebSocketServer, WebSocketConfig, WebSocketStats, NetworkInterface};
+use crate::tcp_server::{TcpServer, ConnectionStats, ConnectionBatchResult};
+use crate::error::AppError;
+use crate::database::{ByteOrder, Database, FrameMode, PlcStructureConfig, DataBlockConfig, TagMapping, FrameProfile, Notification, CsvLoggerConfig, TagBatchResult, TagItemResult, TagGroupPriority, HealthConfig, PanelStatus, PanelLog, AuditEntry, PlcRateExpectation, PublicStreamKey, HistorianTarget, HistorianWriterConfig, IncidentRecord, AlarmDefinition, AlarmOccurrence};
+use crate::websocket_server::{WebSocketServer, WebSocketConfig, WebSocketStats, NetworkInterface, parse_edge_path};
 
 // ✅ OTIMIZAÇÃO: Estruturas para monitoramento de memória
 #[derive(Debug, Clone, serde::Serialize)]
@@ -43,9 +44,31 @@ pub struct MemoryHealthReport {
     pub memory_stats: SystemMemoryStats,
     pub recommendations: Vec<String>,
     pub auto_cleanup_enabled: bool,
+    pub instance: InstanceIdentity, // 🆕 Servidor que gerou o relatório
 }
 use crate::database::WebSocketDbConfig;
-use crate::config::{ConfigManager, AppConfig};
+use crate::config::{ConfigManager, AppConfig, ConfigSecurityStatus, InstanceIdentity};
+use crate::historian::{self, HistoryGap, SnapshotComparison, WaveformSample};
+use crate::postgres::PgDatabase;
+use crate::redundancy::ConfigDriftReport;
+use crate::validation::{ConfigIssue, MappingCoverageReport};
+use crate::impact::{DependencyGraph, ImpactReport};
+use crate::incident_capture::{self, IncidentDetail};
+use crate::i18n::{t, LocalizedMessage};
+use crate::backup::BackupInfo;
+use crate::playback::{PlaybackController, PlaybackStatus, MAX_PLAYBACK_SPEED};
+use crate::graphql::{GraphqlContext, GraphqlServer, DEFAULT_GRAPHQL_PORT};
+use crate::rest_api::{RestApiContext, RestApiServer, DEFAULT_REST_PORT};
+use crate::csv_logger::{CsvLogger, CsvLoggerStatus};
+use crate::historian_writer::{HistorianWriter, HistorianWriterStats};
+use crate::opc_bridge::{OpcBridge, OpcBridgeConfig, OpcBridgeStatus};
+use crate::ipc_server::{IpcServer, IpcServerStatus};
+use crate::health::{HealthContext, HealthReport, HealthServer};
+use crate::packet_rate::PlcRateStatus;
+use crate::ws_protocol;
+use crate::frame_generator::{self, TestFrameSet};
+use crate::alarm_kpis::{self, AlarmKpis};
+use crate::command_audit::{CommandAuditReport, CommandRateAuditor};
 use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 use std::sync::Arc;
@@ -54,6 +77,18 @@ use sqlx::Connection;
 
 pub type TcpServerState = Arc<RwLock<Option<TcpServer>>>;
 pub type WebSocketServerState = Arc<RwLock<Option<WebSocketServer>>>;
+pub type WebSocketWorkersState = Arc<RwLock<Vec<WebSocketServer>>>; // 🆕 Instâncias extras do cluster
+pub type PlaybackState = Arc<RwLock<Option<PlaybackController>>>;
+pub type GraphqlServerState = Arc<RwLock<Option<GraphqlServer>>>;
+pub type RestApiServerState = Arc<RwLock<Option<RestApiServer>>>;
+pub type MqttStatusState = Arc<RwLock<Option<crate::mqtt_status::MqttStatusPublisher>>>;
+pub type CsvLoggerState = Arc<RwLock<Option<CsvLogger>>>;
+pub type OpcBridgeState = Arc<RwLock<Option<OpcBridge>>>;
+pub type HealthServerState = Arc<RwLock<Option<HealthServer>>>;
+pub type IpcServerState = Arc<RwLock<Option<IpcServer>>>;
+pub type HistorianWriterState = Arc<RwLock<Option<HistorianWriter>>>;
+pub type SimulatorState = Arc<RwLock<Option<crate::simulator::Simulator>>>;
+pub type PacketReplayState = Arc<RwLock<Option<crate::packet_recorder::PacketReplay>>>;
 
 #[tauri::command]
 pub async fn start_tcp_server(
@@ -61,11 +96,11 @@ pub async fn start_tcp_server(
     app_handle: AppHandle,
     server_state: State<'_, TcpServerState>,
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut server_guard = server_state.write().await;
     
     if server_guard.is_some() {
-        return Err("Servidor TCP já está rodando".to_string());
+        return Err(AppError::AlreadyRunning("Servidor TCP já está rodando".to_string()));
     }
     
     let mut server = TcpServer::new(port, app_handle, Some(db.inner().clone()));
@@ -73,6 +108,7 @@ pub async fn start_tcp_server(
     match server.start_server().await {
         Ok(msg) => {
             *server_guard = Some(server);
+            crate::autostart::remember_tcp(&db, Some(port)); // 🆕 Retomar ao reiniciar
             Ok(msg)
         }
         Err(e) => Err(e)
@@ -82,23 +118,32 @@ pub async fn start_tcp_server(
 #[tauri::command]
 pub async fn stop_tcp_server(
     server_state: State<'_, TcpServerState>,
-) -> Result<String, String> {
+    simulator_state: State<'_, SimulatorState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    // 🆕 Simulação depende do servidor: parar antes
+    let simulator = simulator_state.write().await.take();
+    if let Some(simulator) = simulator {
+        simulator.stop(&server_state).await;
+    }
+    
     let mut server_guard = server_state.write().await;
     
     match server_guard.as_mut() {
         Some(server) => {
             let result = server.stop_server().await;
             *server_guard = None;
+            crate::autostart::remember_tcp(&db, None);
             result
         }
-        None => Err("Servidor TCP não está rodando".to_string())
+        None => Err(AppError::tcp_not_running())
     }
 }
 
 // Comando para obter interfaces de rede disponíveis
 #[tauri::command]
-pub async fn get_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
-    WebSocketServer::get_available_network_interfaces()
+pub async fn get_network_interfaces() -> Result<Vec<NetworkInterface>, AppError> {
+    WebSocketServer::get_available_network_interfaces().map_err(AppError::from)
 }
 
 // Comando para configurar e salvar interfaces WebSocket
@@ -107,22 +152,22 @@ pub async fn save_websocket_config(
     config: WebSocketConfig,
     websocket_state: State<'_, WebSocketServerState>,
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let ws_guard = websocket_state.read().await;
     
     // Verificar se o servidor está rodando
     if ws_guard.is_some() {
-        return Err("Pare o WebSocket server antes de alterar a configuração".to_string());
+        return Err(AppError::ConfigInvalid("Pare o WebSocket server antes de alterar a configuração".to_string()));
     }
     
     // Validar interfaces
     if config.bind_interfaces.is_empty() {
-        return Err("Pelo menos uma interface deve ser selecionada".to_string());
+        return Err(AppError::ConfigInvalid("Pelo menos uma interface deve ser selecionada".to_string()));
     }
     
     for interface in &config.bind_interfaces {
         if interface.is_empty() {
-            return Err("Interface vazia não é permitida".to_string());
+            return Err(AppError::ConfigInvalid("Interface vazia não é permitida".to_string()));
         }
     }
     
@@ -139,7 +184,7 @@ pub async fn save_websocket_config(
     
     // Salvar no banco
     db.save_websocket_config(&db_config)
-        .map_err(|e| format!("Erro ao salvar configuração: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração: {}", e)))?;
     
     Ok(format!("Configuração WebSocket salva: {} interfaces na porta {}", 
               config.bind_interfaces.len(), config.port))
@@ -149,57 +194,153 @@ pub async fn save_websocket_config(
 #[tauri::command]
 pub async fn load_websocket_config(
     db: State<'_, Arc<Database>>,
-) -> Result<WebSocketDbConfig, String> {
+) -> Result<WebSocketDbConfig, AppError> {
     db.load_websocket_config()
-        .map_err(|e| format!("Erro ao carregar configuração: {}", e))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração: {}", e)))
 }
 
 #[tauri::command]
 pub async fn disconnect_plc(
     client_ip: String,
+    block_minutes: Option<u64>, // None/0 = bloqueado até desbloqueio manual
     server_state: State<'_, TcpServerState>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
         Some(server) => {
-            server.disconnect_client(client_ip).await
+            server.disconnect_client(client_ip, block_duration(block_minutes)).await
         }
-        None => Err("Servidor TCP não está rodando".to_string())
+        None => Err(AppError::tcp_not_running())
+    }
+}
+
+/// 🆕 Escreve um valor no PLC pela conexão TCP aceita, aguarda a confirmação (WACK)
+/// e a leitura de volta; o resultado vai para a auditoria
+#[tauri::command]
+pub async fn write_plc_variable(
+    plc_ip: String,
+    variable_path: String,
+    value: String,
+    server_state: State<'_, TcpServerState>,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<crate::plc_write::PlcWriteResult, AppError> {
+    let pending = {
+        let server_guard = server_state.read().await;
+        let server = server_guard.as_ref().ok_or_else(AppError::tcp_not_running)?;
+        server.send_write(&plc_ip, &variable_path, &value)?
+    };
+    let outcome = pending.wait(&app_handle).await;
+
+    let target = format!("{} {}", plc_ip, variable_path);
+    let (status, details) = match &outcome {
+        Ok(result) => ("ok", format!("{} = {} {}", variable_path, result.value, result.audit_summary())),
+        Err(e) => ("error", e.to_string()),
+    };
+    if let Err(e) = db.add_audit_entry("plc_write", &target, status, &details) {
+        println!("⚠️ Erro ao registrar escrita na auditoria: {}", e);
     }
+    outcome
 }
 
 #[tauri::command]
 pub async fn allow_plc_reconnect(
     client_ip: String,
     server_state: State<'_, TcpServerState>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
         Some(server) => {
             server.allow_reconnect(client_ip).await
         }
-        None => Err("Servidor TCP não está rodando".to_string())
+        None => Err(AppError::tcp_not_running())
+    }
+}
+
+// ============================================================================
+// OPERAÇÕES EM LOTE NAS CONEXÕES (paleta de comandos)
+// ============================================================================
+
+/// Minutos informados pela UI -> duração do bloqueio temporário
+fn block_duration(block_minutes: Option<u64>) -> Option<std::time::Duration> {
+    block_minutes.filter(|m| *m > 0).map(|m| std::time::Duration::from_secs(m * 60))
+}
+
+pub const CONNECTION_BATCH_OPERATIONS: &[&str] = &["disconnect_all", "allow_reconnect_all", "clear_health", "reset_statistics"];
+
+#[tauri::command]
+pub async fn run_connection_batch(
+    operation: String,
+    block_minutes: Option<u64>, // "disconnect_all": duração do bloqueio (None/0 = sem prazo)
+    server_state: State<'_, TcpServerState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<ConnectionBatchResult, AppError> {
+    let server_guard = server_state.read().await;
+    let server = server_guard.as_ref().ok_or_else(|| AppError::NotRunning("Servidor TCP não está rodando".to_string()))?;
+    
+    let result = match operation.as_str() {
+        "disconnect_all" => server.disconnect_all_clients(block_duration(block_minutes)).await,
+        "allow_reconnect_all" => server.allow_reconnect_all().await,
+        "clear_health" => server.clear_health().await,
+        "reset_statistics" => server.reset_statistics().await,
+        other => return Err(AppError::ConfigInvalid(format!("Operação desconhecida: {} (use {})", other, CONNECTION_BATCH_OPERATIONS.join(", ")))),
+    };
+    
+    let status = match (result.affected.is_empty(), result.failed.is_empty()) {
+        (_, true) => "ok",
+        (false, false) => "partial",
+        (true, false) => "failed",
+    };
+    let target = if result.affected.is_empty() { "-".to_string() } else { result.affected.join(",") };
+    let details = if result.failed.is_empty() {
+        result.summary.clone()
+    } else {
+        let failures: Vec<String> = result.failed.iter().map(|f| format!("{}: {}", f.ip, f.error)).collect();
+        format!("{} - {}", result.summary, failures.join("; "))
+    };
+    if let Err(e) = db.add_audit_entry(&result.operation, &target, status, &details) {
+        println!("⚠️ Falha ao registrar auditoria de {}: {}", result.operation, e);
     }
+    
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_audit_log(
+    limit: Option<u32>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<AuditEntry>, AppError> {
+    db.list_audit_log(limit.unwrap_or(200))
+        .map_err(|e| AppError::Database(format!("Erro ao listar auditoria: {}", e)))
+}
+
+/// Taxa de comandos sensíveis por sessão e anomalias recentes (ver command_audit.rs)
+#[tauri::command]
+pub async fn get_command_audit(
+    session: Option<String>,
+    auditor: State<'_, Arc<CommandRateAuditor>>,
+) -> Result<CommandAuditReport, AppError> {
+    Ok(auditor.report(session.as_deref()))
 }
 
 #[tauri::command]
 pub async fn get_connection_stats(
     server_state: State<'_, TcpServerState>,
-) -> Result<ConnectionStats, String> {
+) -> Result<ConnectionStats, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
         Some(server) => Ok(server.get_connection_stats().await),
-        None => Err("Servidor TCP não está rodando".to_string())
+        None => Err(AppError::NotRunning("Servidor TCP não está rodando".to_string()))
     }
 }
 
 #[tauri::command]
 pub async fn get_connected_clients(
     server_state: State<'_, TcpServerState>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
@@ -211,7 +352,7 @@ pub async fn get_connected_clients(
 #[tauri::command]
 pub async fn get_all_known_plcs(
     server_state: State<'_, TcpServerState>,
-) -> Result<Vec<(String, String)>, String> {
+) -> Result<Vec<(String, String, Option<u64>)>, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
@@ -223,7 +364,7 @@ pub async fn get_all_known_plcs(
 #[tauri::command]
 pub async fn get_all_plc_bytes(
     server_state: State<'_, TcpServerState>,
-) -> Result<std::collections::HashMap<String, u64>, String> {
+) -> Result<std::collections::HashMap<String, u64>, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
@@ -236,7 +377,7 @@ pub async fn get_all_plc_bytes(
 pub async fn get_plc_data(
     client_ip: String,
     server_state: State<'_, TcpServerState>,
-) -> Result<Option<crate::tcp_server::PlcDataPacket>, String> {
+) -> Result<Option<crate::tcp_server::PlcDataPacket>, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
@@ -248,7 +389,7 @@ pub async fn get_plc_data(
 #[tauri::command]
 pub async fn get_all_plc_data(
     server_state: State<'_, TcpServerState>,
-) -> Result<std::collections::HashMap<String, crate::tcp_server::PlcDataPacket>, String> {
+) -> Result<std::collections::HashMap<String, crate::tcp_server::PlcDataPacket>, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
@@ -262,27 +403,27 @@ pub async fn get_all_plc_data(
 pub async fn connect_to_plc(
     _plc_ip: String,
     _plc_port: u16,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     Ok("O PLC deve conectar no servidor, não o contrário".to_string())
 }
 
 #[tauri::command]
-pub async fn scan_network_for_plcs() -> Result<Vec<String>, String> {
+pub async fn scan_network_for_plcs() -> Result<Vec<String>, AppError> {
     Ok(vec!["Configure seu PLC para conectar no servidor".to_string()])
 }
 
 #[tauri::command]
-pub async fn auto_discover_plc() -> Result<Vec<String>, String> {
+pub async fn auto_discover_plc() -> Result<Vec<String>, AppError> {
     Ok(vec![])
 }
 
 #[tauri::command]
-pub async fn test_plc_connection(_ip: String, _port: u16) -> Result<bool, String> {
+pub async fn test_plc_connection(_ip: String, _port: u16) -> Result<bool, AppError> {
     Ok(false)
 }
 
 #[tauri::command]
-pub async fn get_latest_plc_data() -> Result<Option<String>, String> {
+pub async fn get_latest_plc_data() -> Result<Option<String>, AppError> {
     Ok(None)
 }
 
@@ -291,7 +432,7 @@ pub async fn get_plc_variable(
     plc_ip: String,
     variable_name: String,
     server_state: State<'_, TcpServerState>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let server_guard = server_state.read().await;
     
     match server_guard.as_ref() {
@@ -302,13 +443,70 @@ pub async fn get_plc_variable(
                     .find(|v| v.name == variable_name) {
                     Ok(variable.value.clone())
                 } else {
-                    Err(format!("Variável '{}' não encontrada no PLC {}", variable_name, plc_ip))
+                    Err(AppError::PlcNotFound(format!("Variável '{}' não encontrada no PLC {}", variable_name, plc_ip)))
                 }
             } else {
-                Err(format!("Nenhum dado disponível para PLC {}", plc_ip))
+                Err(AppError::PlcNotFound(format!("Nenhum dado disponível para PLC {}", plc_ip)))
             }
         }
-        None => Err("Servidor TCP não está rodando".to_string())
+        None => Err(AppError::NotRunning("Servidor TCP não está rodando".to_string()))
+    }
+}
+
+/// Retorna o último frame bruto de um PLC (hex dump sob demanda)
+#[tauri::command]
+pub async fn get_latest_raw_frame(
+    plc_ip: String,
+    server_state: State<'_, TcpServerState>,
+) -> Result<serde_json::Value, AppError> {
+    let server_guard = server_state.read().await;
+    let server = server_guard.as_ref().ok_or_else(|| AppError::NotRunning("Servidor TCP não está rodando".to_string()))?;
+    
+    let (timestamp, raw_data) = server.get_latest_raw_frame(&plc_ip)
+        .ok_or_else(|| AppError::PlcNotFound(format!("Nenhum dado disponível para PLC {}", plc_ip)))?;
+    
+    let hex = raw_data.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    
+    Ok(serde_json::json!({
+        "ip": plc_ip,
+        "timestamp": timestamp,
+        "size": raw_data.len(),
+        "raw_data": raw_data,
+        "hex": hex
+    }))
+}
+
+// ============================================================================
+// CANAIS DE DADOS EM TEMPO REAL (ALTA FREQUÊNCIA)
+// ============================================================================
+
+/// Assina os pacotes do servidor TCP via Tauri Channel (substitui o evento `plc-data-received`)
+#[tauri::command]
+pub async fn subscribe_plc_data_channel(
+    plc_ips: Option<Vec<String>>,
+    decimation: Option<u32>,
+    on_data: tauri::ipc::Channel<serde_json::Value>,
+    server_state: State<'_, TcpServerState>,
+) -> Result<u32, AppError> {
+    let server_guard = server_state.read().await;
+    match server_guard.as_ref() {
+        Some(server) => Ok(server.subscribe_data_channel(plc_ips, decimation.unwrap_or(1), on_data)),
+        None => Err(AppError::NotRunning("Servidor TCP não está rodando".to_string()))
+    }
+}
+
+#[tauri::command]
+pub async fn unsubscribe_plc_data_channel(
+    subscription_id: u32,
+    server_state: State<'_, TcpServerState>,
+) -> Result<bool, AppError> {
+    let server_guard = server_state.read().await;
+    match server_guard.as_ref() {
+        Some(server) => Ok(server.unsubscribe_data_channel(subscription_id)),
+        None => Ok(false)
     }
 }
 
@@ -320,60 +518,235 @@ pub async fn get_plc_variable(
 pub async fn save_plc_structure(
     plc_ip: String,
     blocks: Vec<DataBlockConfig>,
+    byte_order: Option<ByteOrder>,
+    framing: Option<FrameMode>,
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     // Calcular tamanho total
-    let mut total_size = 0;
-    for block in &blocks {
-        let type_size = match block.data_type.as_str() {
-            "BYTE" => 1,
-            "WORD" | "INT" => 2,
-            "DWORD" | "DINT" | "REAL" => 4,
-            "LWORD" | "LINT" | "LREAL" => 8,
-            _ => return Err(format!("Tipo inválido: {}", block.data_type)),
-        };
-        total_size += type_size * block.count as usize;
-    }
+    let total_size = crate::plc_parser::blocks_total_size(&blocks)?;
+    
+    // Preservar perfis de frame alternativos (e ordem dos bytes/framing, se não informados) já configurados
+    let existing = db.load_plc_structure(&plc_ip).ok().flatten();
+    let byte_order = byte_order
+        .or_else(|| existing.as_ref().map(|e| e.byte_order))
+        .unwrap_or_default();
+    let framing = framing
+        .or_else(|| existing.as_ref().map(|e| e.framing))
+        .unwrap_or_default();
+    let profiles = existing.map(|e| e.profiles).unwrap_or_default();
     
     let config = PlcStructureConfig {
         plc_ip: plc_ip.clone(),
         blocks,
         total_size,
         last_updated: chrono::Utc::now().timestamp(),
+        profiles,
+        byte_order,
+        framing,
     };
+    crate::plc_parser::validate_fixed_framing(&config).map_err(AppError::ConfigInvalid)?;
     
     db.save_plc_structure(&config)
-        .map_err(|e| format!("Erro ao salvar configuração: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração: {}", e)))?;
     
-    Ok(format!("Configuração salva para PLC {}: {} bytes", plc_ip, total_size))
+    Ok(t("plc.structure_saved", &[("ip", plc_ip), ("bytes", total_size.to_string())]))
 }
 
 #[tauri::command]
 pub async fn load_plc_structure(
     plc_ip: String,
     db: State<'_, Arc<Database>>,
-) -> Result<Option<PlcStructureConfig>, String> {
+) -> Result<Option<PlcStructureConfig>, AppError> {
     db.load_plc_structure(&plc_ip)
-        .map_err(|e| format!("Erro ao carregar configuração: {}", e))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração: {}", e)))
 }
 
 #[tauri::command]
 pub async fn list_configured_plcs(
     db: State<'_, Arc<Database>>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, AppError> {
     db.list_configured_plcs()
-        .map_err(|e| format!("Erro ao listar PLCs: {}", e))
+        .map_err(|e| AppError::Database(format!("Erro ao listar PLCs: {}", e)))
 }
 
 #[tauri::command]
 pub async fn delete_plc_structure(
     plc_ip: String,
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     db.delete_plc_structure(&plc_ip)
-        .map_err(|e| format!("Erro ao deletar configuração: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Erro ao deletar configuração: {}", e)))?;
+    
+    Ok(t("plc.structure_deleted", &[("ip", plc_ip)]))
+}
+
+/// 🆕 Salva os perfis de frame alternativos de um PLC (selecionados por tamanho ou byte de tipo)
+#[tauri::command]
+pub async fn save_plc_frame_profiles(
+    plc_ip: String,
+    profiles: Vec<FrameProfile>,
+    db: State<'_, Arc<Database>>,
+    tcp_state: State<'_, TcpServerState>,
+) -> Result<String, AppError> {
+    let mut config = db.load_plc_structure(&plc_ip)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração: {}", e)))?
+        .ok_or_else(|| AppError::PlcNotFound(format!("PLC {} não possui estrutura salva", plc_ip)))?;
+    
+    let mut validated = Vec::with_capacity(profiles.len());
+    for mut profile in profiles {
+        profile.total_size = crate::plc_parser::blocks_total_size(&profile.blocks)?;
+        if profile.type_byte_offset.is_some() != profile.type_byte_value.is_some() {
+            return Err(AppError::ConfigInvalid(format!("Perfil '{}': offset e valor do byte de tipo devem ser informados juntos", profile.name)));
+        }
+        if let Some(offset) = profile.type_byte_offset {
+            if offset >= profile.total_size {
+                return Err(AppError::ConfigInvalid(format!("Perfil '{}': byte de tipo ({}) fora do frame ({} bytes)", profile.name, offset, profile.total_size)));
+            }
+        }
+        validated.push(profile);
+    }
+    
+    config.profiles = validated;
+    crate::plc_parser::validate_fixed_framing(&config).map_err(AppError::ConfigInvalid)?;
+    config.last_updated = chrono::Utc::now().timestamp();
+    db.save_plc_structure(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar perfis: {}", e)))?;
+    
+    // Forçar recarga da configuração no servidor TCP
+    if let Some(server) = tcp_state.read().await.as_ref() {
+        server.reload_plc_config(&plc_ip);
+    }
     
-    Ok(format!("Configuração removida para PLC {}", plc_ip))
+    Ok(format!("{} perfis de frame salvos para PLC {}", config.profiles.len(), plc_ip))
+}
+
+/// Duplica a configuração de um PLC (estrutura, perfis e tags) para outro IP
+#[tauri::command]
+pub async fn clone_plc_config(
+    source_ip: String,
+    target_ip: String,
+    overwrite: Option<bool>,
+    db: State<'_, Arc<Database>>,
+    tcp_state: State<'_, TcpServerState>,
+    websocket_state: State<'_, WebSocketServerState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let target_ip = target_ip.trim().to_string();
+    if target_ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(AppError::ConfigInvalid(format!("IP de destino inválido: '{}'", target_ip)));
+    }
+    if source_ip == target_ip {
+        return Err(AppError::ConfigInvalid("PLC de origem e destino são o mesmo".to_string()));
+    }
+
+    let overwrite = overwrite.unwrap_or(false);
+    if !overwrite {
+        let has_structure = db.load_plc_structure(&target_ip)
+            .map_err(|e| AppError::Database(format!("Erro ao verificar PLC de destino: {}", e)))?
+            .is_some();
+        let has_tags = !db.load_tag_mappings(&target_ip)
+            .map_err(|e| AppError::Database(format!("Erro ao verificar PLC de destino: {}", e)))?
+            .is_empty();
+        if has_structure || has_tags {
+            return Err(AppError::ConfigInvalid(format!("PLC {} já possui configuração (use overwrite para substituir)", target_ip)));
+        }
+    }
+
+    let tags = db.clone_plc_config(&source_ip, &target_ip, overwrite)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => format!("PLC de origem {} não possui estrutura salva", source_ip),
+            e => format!("Erro ao clonar configuração: {}", e),
+        })?;
+    crate::alarm_engine::request_reload();
+
+    if let Some(server) = tcp_state.read().await.as_ref() {
+        server.reload_plc_config(&target_ip);
+    }
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+
+    let _ = app_handle.emit("plc-config-cloned", serde_json::json!({
+        "source_ip": source_ip,
+        "target_ip": target_ip,
+        "tags": tags
+    }));
+    Ok(format!("Configuração de {} copiada para {} ({} tags)", source_ip, target_ip, tags))
+}
+
+/// 🆕 Move estrutura, tags, historian e acompanhamento de saúde de um PLC para o
+/// novo IP. `dry_run` devolve só a prévia. O histórico no PostgreSQL só é
+/// confirmado se a migração local der certo.
+#[tauri::command]
+pub async fn migrate_plc_identity(
+    old_ip: String,
+    new_ip: String,
+    dry_run: bool,
+    db: State<'_, Arc<Database>>,
+    tcp_state: State<'_, TcpServerState>,
+    websocket_state: State<'_, WebSocketServerState>,
+    app_handle: AppHandle,
+) -> Result<crate::database::PlcIdentityMigration, AppError> {
+    let new_ip = new_ip.trim().to_string();
+    if new_ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(AppError::ConfigInvalid(format!("IP de destino inválido: '{}'", new_ip)));
+    }
+    if old_ip == new_ip {
+        return Err(AppError::ConfigInvalid("IP antigo e novo são o mesmo".to_string()));
+    }
+
+    // Histórico (se o PostgreSQL estiver configurado): transação aberta até o SQLite confirmar
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?;
+    let pg = match pg_config {
+        Some(config) => Some(PgDatabase::connect(&historian::postgres_url(&config)).await
+            .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?),
+        None => None,
+    };
+    let mut pg_tx = match &pg {
+        Some(pg) if !dry_run => Some(pg.pool.begin().await
+            .map_err(|e| AppError::Database(format!("Erro ao iniciar transação no historian: {}", e)))?),
+        _ => None,
+    };
+    let (history_rows, waveform_rows) = match (&pg, pg_tx.as_mut()) {
+        (_, Some(tx)) => historian::migrate_plc_history(tx, &old_ip, &new_ip).await
+            .map_err(|e| AppError::Database(format!("Erro ao migrar histórico: {}", e)))?,
+        (Some(pg), None) => historian::count_plc_history(&pg.pool, &old_ip).await
+            .map_err(|e| AppError::Database(format!("Erro ao contar histórico: {}", e)))?,
+        (None, None) => (0, 0),
+    };
+
+    // Se falhar aqui, pg_tx é descartado e o PostgreSQL faz rollback
+    let mut report = db.migrate_plc_identity(&old_ip, &new_ip, dry_run)
+        .map_err(|e| AppError::Database(format!("Erro ao migrar identidade do PLC: {}", e)))?;
+    report.history_rows = history_rows;
+    report.waveform_rows = waveform_rows;
+    if report.local_rows() == 0 && history_rows == 0 && waveform_rows == 0 {
+        return Err(AppError::PlcNotFound(format!("PLC {} não possui configuração nem histórico", old_ip)));
+    }
+    if dry_run {
+        return Ok(report);
+    }
+
+    if let Some(tx) = pg_tx {
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Identidade migrada, mas falhou ao confirmar histórico: {}", e)))?;
+    }
+
+    crate::alarm_engine::request_reload();
+    if let Some(server) = tcp_state.read().await.as_ref() {
+        server.reload_plc_config(&old_ip);
+        server.reload_plc_config(&new_ip);
+        if let Err(e) = server.reload_rate_expectations() {
+            println!("⚠️ Erro ao recarregar taxas esperadas após migrar {}: {}", old_ip, e);
+        }
+    }
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+
+    let details = serde_json::to_string(&report).unwrap_or_default();
+    if let Err(e) = db.add_audit_entry("plc_identity_migrate", &format!("{} → {}", old_ip, new_ip), "ok", &details) {
+        println!("⚠️ Erro ao registrar auditoria: {}", e);
+    }
+    let _ = app_handle.emit("plc-identity-migrated", &report);
+    Ok(report)
 }
 
 /// 🔍 DEBUG: Mostra o que está salvo no banco
@@ -381,32 +754,62 @@ pub async fn delete_plc_structure(
 pub async fn debug_show_plc_structure(
     plc_ip: String,
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     db.debug_show_saved_structure(&plc_ip)
-        .map_err(|e| format!("Erro ao ler banco: {}", e))
+        .map_err(|e| AppError::Database(format!("Erro ao ler banco: {}", e)))
 }
 
 // ============================================================================
 // COMANDOS DE CONFIGURAÇÃO DE TAG MAPPINGS
 // ============================================================================
 
+/// Validações comuns ao salvar um tag (individual ou em lote)
+fn validate_tag_for_save(tag: &TagMapping, tag_exists: impl Fn(&str) -> bool) -> Result<(), String> {
+    // 🆕 CONVERSÃO DE UNIDADE: verificar compatibilidade
+    if let Some(display_unit) = tag.display_unit.as_deref().filter(|u| !u.is_empty()) {
+        let unit = tag.unit.as_deref().unwrap_or("");
+        if crate::units::convert(1.0, unit, display_unit).is_none() {
+            return Err(format!("Conversão de '{}' para '{}' não suportada", unit, display_unit));
+        }
+    }
+    crate::units::validate_tag_scaling(tag)?;
+    if tag.display_decimals.map_or(false, |d| d > crate::units::MAX_DISPLAY_DECIMALS) {
+        return Err(format!("Casas decimais do tag '{}' devem estar entre 0 e {}", tag.tag_name, crate::units::MAX_DISPLAY_DECIMALS));
+    }
+    
+    // 🆕 SELETOR NO variable_path (bit, faixa de bits, byte, REAL_SWAP)
+    if parse_edge_path(&tag.variable_path).is_none() {
+        crate::plc_parser::split_variable_path(&tag.variable_path)
+            .map_err(|e| format!("variable_path '{}' inválido: {}", tag.variable_path, e))?;
+    }
+    
+    // 🆕 EDGE TAG: o tag de origem precisa existir no mesmo PLC
+    if let Some((_, source)) = parse_edge_path(&tag.variable_path) {
+        if !tag_exists(source) {
+            return Err(format!("Tag de origem '{}' não encontrado para edge tag '{}'", source, tag.tag_name));
+        }
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn save_tag_mapping(
     tag: TagMapping,
     db: State<'_, Arc<Database>>,
     websocket_state: State<'_, WebSocketServerState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut tag_to_save = tag;
     tag_to_save.created_at = chrono::Utc::now().timestamp();
     
     // Debug: verificar dados que chegaram do frontend
     println!("🔍 Backend: Tag recebido do frontend - enabled: {}", tag_to_save.enabled);
     
+    let existing_tags = db.load_tag_mappings(&tag_to_save.plc_ip).unwrap_or_default();
+    validate_tag_for_save(&tag_to_save, |name| existing_tags.iter().any(|t| t.tag_name == name)).map_err(AppError::ConfigInvalid)?;
+    
     // Verificar se o tag já existe (por plc_ip + variable_path)
-    let tag_exists = db.load_tag_mappings(&tag_to_save.plc_ip)
-        .map(|tags| tags.iter().any(|t| t.variable_path == tag_to_save.variable_path))
-        .unwrap_or(false);
+    let tag_exists = existing_tags.iter().any(|t| t.variable_path == tag_to_save.variable_path);
     match db.save_tag_mapping(&tag_to_save) {
         Ok(tag_id) => {
             // Sempre emitir status-changed
@@ -441,1335 +844,3819 @@ pub async fn save_tag_mapping(
             if tag_to_save.enabled {
                 println!("🔄 Tag '{}' ativado, WebSocket será notificado automaticamente no próximo ciclo", tag_to_save.tag_name);
             }
-            Ok(format!("Tag '{}' salvo com ID {} - {}", 
-                tag_to_save.tag_name, 
-                tag_id,
-                if tag_to_save.enabled { "Ativado para WebSocket" } else { "Inativo" }
-            ))
+            let state_key = if tag_to_save.enabled { "tag.state_enabled" } else { "tag.state_disabled" };
+            Ok(t("tag.saved", &[
+                ("tag", tag_to_save.tag_name.clone()),
+                ("id", tag_id.to_string()),
+                ("state", t(state_key, &[])),
+            ]))
         },
-        Err(e) => Err(format!("Erro ao salvar tag: {}", e))
+        Err(e) => Err(AppError::Database(format!("Erro ao salvar tag: {}", e)))
     }
 }
 
+/// Salva vários tags em uma transação: ou todos são gravados ou nenhum.
+/// Retorna o resultado de cada item; os grupos do WebSocket são recarregados uma única vez.
 #[tauri::command]
 pub async fn save_tag_mappings_bulk(
     tags: Vec<TagMapping>,
     db: State<'_, Arc<Database>>,
     websocket_state: State<'_, WebSocketServerState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+) -> Result<TagBatchResult, AppError> {
     if tags.is_empty() {
-        return Err("Lista de tags vazia".to_string());
+        return Err(AppError::ConfigInvalid("Lista de tags vazia".to_string()));
     }
 
     let plc_ip = tags[0].plc_ip.clone(); // Assumir que todos são do mesmo PLC
     let timestamp = chrono::Utc::now().timestamp();
-    
-    // Preparar tags com timestamp
-    let tags_to_save: Vec<TagMapping> = tags
-        .into_iter()
-        .map(|mut tag| {
-            tag.created_at = timestamp;
-            tag
-        })
-        .collect();
 
     // Verificar tags existentes de uma vez só
     let existing_tags = db.load_tag_mappings(&plc_ip)
-        .map_err(|e| format!("Erro ao verificar tags existentes: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Erro ao verificar tags existentes: {}", e)))?;
     
     let existing_paths: std::collections::HashSet<String> = existing_tags
         .iter()
         .map(|t| t.variable_path.clone())
         .collect();
-
-    // Filtrar duplicatas
-    let new_tags_only: Vec<TagMapping> = tags_to_save
-        .into_iter()
-        .filter(|tag| !existing_paths.contains(&tag.variable_path))
+    let known_names: std::collections::HashSet<&str> = existing_tags.iter()
+        .map(|t| t.tag_name.as_str())
+        .chain(tags.iter().map(|t| t.tag_name.as_str()))
         .collect();
 
-    if new_tags_only.is_empty() {
-        return Err("Todas as variáveis selecionadas já foram mapeadas".to_string());
+    // Validar cada item antes de tocar no banco; variáveis já mapeadas são ignoradas
+    let mut pending = Vec::new();
+    let mut new_tags_only: Vec<(usize, TagMapping)> = Vec::new();
+    for (index, mut tag) in tags.iter().cloned().enumerate() {
+        tag.created_at = timestamp;
+        if tag.plc_ip != plc_ip {
+            pending.push(TagItemResult::for_tag(index, &tag, "failed", None,
+                Some(format!("Tag de outro PLC ({}) no lote de {}", tag.plc_ip, plc_ip))));
+        } else if existing_paths.contains(&tag.variable_path) {
+            pending.push(TagItemResult::for_tag(index, &tag, "skipped", None,
+                Some("Variável já mapeada".to_string())));
+        } else if let Err(e) = validate_tag_for_save(&tag, |name| known_names.contains(name)) {
+            pending.push(TagItemResult::for_tag(index, &tag, "failed", None, Some(e)));
+        } else {
+            new_tags_only.push((index, tag));
+        }
+    }
+
+    if new_tags_only.is_empty() && pending.iter().all(|r| r.status == "skipped") {
+        return Err(AppError::ConfigInvalid("Todas as variáveis selecionadas já foram mapeadas".to_string()));
     }
 
     println!("🔍 Backend: Salvando {} tags em lote (filtrados {} duplicatas)", 
-             new_tags_only.len(), existing_paths.len());
+             new_tags_only.len(), pending.iter().filter(|r| r.status == "skipped").count());
 
-    // Salvar em lote usando transação
-    match db.save_tag_mappings_bulk(&new_tags_only) {
-        Ok(tag_ids) => {
-            let successful_count = tag_ids.iter().filter(|&&id| id > 0).count();
-            
-            // Emitir eventos para tags criados com sucesso
-            for (tag, &tag_id) in new_tags_only.iter().zip(tag_ids.iter()) {
-                if tag_id > 0 {
-                    let _ = app_handle.emit(
-                        "tag-created",
-                        serde_json::json!({
-                            "id": tag_id,
-                            "plc_ip": tag.plc_ip,
-                            "variable_path": tag.variable_path,
-                            "tag_name": tag.tag_name,
-                            "description": tag.description,
-                            "unit": tag.unit,
-                            "enabled": tag.enabled,
-                            "created_at": tag.created_at,
-                            "collect_mode": tag.collect_mode,
-                            "collect_interval_s": tag.collect_interval_s
-                        })
-                    );
-                }
-            }
+    let result = db.save_tag_mappings_bulk(&new_tags_only, pending)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar tags em lote: {}", e)))?;
 
-            // ✅ CORREÇÃO: Só recarregar WebSocket UMA VEZ ao final
-            let _ = reload_websocket_tag_groups(websocket_state).await;
-            
-            println!("🔄 Tags em lote ativados, WebSocket recarregado UMA VEZ");
+    if !result.committed {
+        println!("⚠️ Lote de tags revertido: {} itens com erro", result.failed);
+        return Ok(result);
+    }
 
-            Ok(format!("{} tags criados com sucesso em lote", successful_count))
-        },
-        Err(e) => Err(format!("Erro ao salvar tags em lote: {}", e))
+    // Emitir eventos para tags criados
+    for item in result.results.iter().filter(|r| r.status == "saved") {
+        let tag = &tags[item.index];
+        let _ = app_handle.emit(
+            "tag-created",
+            serde_json::json!({
+                "id": item.id,
+                "plc_ip": tag.plc_ip,
+                "variable_path": tag.variable_path,
+                "tag_name": tag.tag_name,
+                "description": tag.description,
+                "unit": tag.unit,
+                "enabled": tag.enabled,
+                "created_at": timestamp,
+                "collect_mode": tag.collect_mode,
+                "collect_interval_s": tag.collect_interval_s
+            })
+        );
     }
+
+    // ✅ CORREÇÃO: Só recarregar WebSocket UMA VEZ ao final
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+    
+    println!("🔄 {} tags criados em lote, WebSocket recarregado UMA VEZ", result.applied);
+    Ok(result)
 }
 
+/// Renomeia um tag mantendo histórico e referências (edge tags, logger CSV).
+/// O histórico no PostgreSQL só é confirmado se a renomeação local der certo.
 #[tauri::command]
-pub async fn load_tag_mappings(
+pub async fn rename_tag(
     plc_ip: String,
+    old_name: String,
+    new_name: String,
     db: State<'_, Arc<Database>>,
-) -> Result<Vec<TagMapping>, String> {
-    db.load_tag_mappings(&plc_ip)
-        .map_err(|e| format!("Erro ao carregar tags: {}", e))
+    websocket_state: State<'_, WebSocketServerState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, AppError> {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() || new_name.contains(':') || new_name.contains('(') || new_name.contains(')') {
+        return Err(AppError::ConfigInvalid(format!("Nome de tag inválido: '{}'", new_name)));
+    }
+    if new_name == old_name {
+        return Err(AppError::ConfigInvalid("O novo nome é igual ao atual".to_string()));
+    }
+
+    let existing_tags = db.load_tag_mappings(&plc_ip)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar tags: {}", e)))?;
+    if !existing_tags.iter().any(|t| t.tag_name == old_name) {
+        return Err(AppError::NotFound(format!("Tag '{}' não encontrado em {}", old_name, plc_ip)));
+    }
+    if existing_tags.iter().any(|t| t.tag_name == new_name) {
+        return Err(AppError::ConfigInvalid(format!("Já existe um tag '{}' em {}", new_name, plc_ip)));
+    }
+
+    // Histórico (se o PostgreSQL estiver configurado): transação aberta até o SQLite confirmar
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?;
+    let pg = match pg_config {
+        Some(config) => Some(PgDatabase::connect(&historian::postgres_url(&config)).await
+            .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?),
+        None => None,
+    };
+    let mut pg_tx = match &pg {
+        Some(pg) => Some(pg.pool.begin().await
+            .map_err(|e| AppError::Database(format!("Erro ao iniciar transação no historian: {}", e)))?),
+        None => None,
+    };
+    let history_rows = match pg_tx.as_mut() {
+        Some(tx) => historian::rename_tag_history(tx, &plc_ip, &old_name, &new_name).await
+            .map_err(|e| AppError::Database(format!("Erro ao renomear histórico: {}", e)))?,
+        None => 0,
+    };
+
+    // Se falhar aqui, pg_tx é descartado e o PostgreSQL faz rollback
+    let references = db.rename_tag(&plc_ip, &old_name, &new_name)
+        .map_err(|e| AppError::Database(format!("Erro ao renomear tag: {}", e)))?;
+    crate::alarm_engine::request_reload();
+
+    if let Some(tx) = pg_tx {
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("Tag renomeado, mas falhou ao confirmar histórico: {}", e)))?;
+    }
+
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+    let _ = app_handle.emit("tag-renamed", serde_json::json!({
+        "plc_ip": plc_ip,
+        "old_name": old_name,
+        "new_name": new_name,
+        "history_rows": history_rows,
+        "references": references
+    }));
+
+    Ok(t("tag.renamed", &[
+        ("old", old_name),
+        ("new", new_name),
+        ("rows", history_rows.to_string()),
+        ("references", references.to_string()),
+    ]))
 }
 
+// 🆕 PRIORIDADE DOS GRUPOS DE TAGS (clientes podem pedir só prioridade >= N)
+
 #[tauri::command]
-pub async fn delete_tag_mapping(
-    plc_ip: String,
-    variable_path: String,
+pub async fn list_tag_group_priorities(
     db: State<'_, Arc<Database>>,
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
-    db.delete_tag_mapping(&plc_ip, &variable_path)
-        .map_err(|e| format!("Erro ao deletar tag: {}", e))?;
-    // Sempre recarregar grupos de tags do WebSocket
-    let _ = reload_websocket_tag_groups(websocket_state).await;
-    Ok(format!("Tag {} removido", variable_path))
+) -> Result<Vec<TagGroupPriority>, AppError> {
+    db.list_tag_group_priorities()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar prioridades: {}", e)))
 }
 
 #[tauri::command]
-pub async fn delete_tag_mappings_bulk(
-    ids: Vec<i64>,
+pub async fn save_tag_group_priority(
+    priority: TagGroupPriority,
     db: State<'_, Arc<Database>>,
     websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
-    let count = ids.len();
-    db.delete_tag_mappings_bulk(ids)
-        .map_err(|e| format!("Erro ao deletar tags: {}", e))?;
-    // Sempre recarregar grupos de tags do WebSocket
+) -> Result<String, AppError> {
+    if priority.group_type != "area" && priority.group_type != "category" {
+        return Err(AppError::ConfigInvalid(format!("Tipo de grupo inválido: '{}' (use 'area' ou 'category')", priority.group_type)));
+    }
+    if priority.group_name.trim().is_empty() {
+        return Err(AppError::ConfigInvalid("Nome do grupo não informado".to_string()));
+    }
+    db.save_tag_group_priority(&priority)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar prioridade: {}", e)))?;
     let _ = reload_websocket_tag_groups(websocket_state).await;
-    Ok(format!("{} tags removidos com sucesso", count))
+    Ok(format!("Prioridade de {} '{}' definida como {}", priority.group_type, priority.group_name, priority.priority))
 }
 
 #[tauri::command]
-pub async fn get_active_tags(
-    plc_ip: String,
+pub async fn delete_tag_group_priority(
+    group_type: String,
+    group_name: String,
     db: State<'_, Arc<Database>>,
-) -> Result<Vec<TagMapping>, String> {
-    db.get_active_tags(&plc_ip)
-        .map_err(|e| format!("Erro ao buscar tags ativos: {}", e))
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<String, AppError> {
+    db.delete_tag_group_priority(&group_type, &group_name)
+        .map_err(|e| AppError::Database(format!("Erro ao remover prioridade: {}", e)))?;
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+    Ok(format!("Prioridade de {} '{}' removida", group_type, group_name))
 }
 
-#[tauri::command]
-pub async fn get_plc_variables_for_mapping(
-    plc_ip: String,
-    server_state: State<'_, TcpServerState>,
-) -> Result<Vec<String>, String> {
-    let server_guard = server_state.read().await;
-    
-    match server_guard.as_ref() {
-        Some(server) => {
-            if let Some(data_packet) = server.get_plc_data(&plc_ip).await {
-                let variable_names: Vec<String> = data_packet.variables
-                    .iter()
-                    .map(|v| v.name.clone())
-                    .collect();
-                Ok(variable_names)
-            } else {
-                Err("PLC não encontrado ou sem dados".to_string())
-            }
+// 🆕 OVERRIDE TEMPORÁRIO DO INTERVALO DE UM GRUPO (ex: "modo rápido" em teste de comporta)
+// Fica só em memória no SmartCache; ao vencer reverte sozinho e a reversão vai para a auditoria.
+
+async fn current_smart_cache(websocket_state: &WebSocketServerState) -> Result<Arc<crate::websocket_server::SmartCache>, String> {
+    websocket_state.read().await.as_ref()
+        .map(|server| server.smart_cache())
+        .ok_or_else(|| "WebSocket server não está rodando".to_string())
+}
+
+/// Remove os overrides vencidos, registra a reversão e avisa a UI
+fn revert_expired_interval_overrides(smart_cache: &crate::websocket_server::SmartCache, db: &Database, app_handle: &AppHandle) {
+    for entry in smart_cache.expire_interval_overrides() {
+        let target = format!("{}:{}", entry.group_type, entry.group_name);
+        println!("⏱️ Override de intervalo de {} expirou - voltando ao configurado", target);
+        if let Err(e) = db.add_audit_entry("broadcast_interval_revert", &target, "ok", &format!("{}s expirado", entry.interval_s)) {
+            println!("⚠️ Falha ao registrar auditoria de broadcast_interval_revert: {}", e);
         }
-        None => Err("Servidor TCP não está rodando".to_string())
+        let _ = app_handle.emit("broadcast-interval-reverted", &entry);
     }
 }
 
-// ============================================================================
-// COMANDOS WEBSOCKET SERVER
-// ============================================================================
+#[tauri::command]
+pub async fn list_group_interval_overrides(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<Vec<crate::websocket_server::GroupIntervalOverride>, AppError> {
+    Ok(current_smart_cache(&websocket_state).await?.list_interval_overrides())
+}
 
 #[tauri::command]
-pub async fn start_websocket_server(
-    config: WebSocketConfig,
+pub async fn set_group_interval_override(
+    group_type: String,
+    group_name: String,
+    interval_s: u64,
+    duration_s: u64,
+    reason: Option<String>,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
     app_handle: AppHandle,
+) -> Result<crate::websocket_server::GroupIntervalOverride, AppError> {
+    use crate::websocket_server::{GroupIntervalOverride, MAX_OVERRIDE_DURATION_S, MAX_OVERRIDE_INTERVAL_S};
+    if group_type != "area" && group_type != "category" {
+        return Err(AppError::ConfigInvalid(format!("Tipo de grupo inválido: '{}' (use 'area' ou 'category')", group_type)));
+    }
+    if group_name.trim().is_empty() {
+        return Err(AppError::ConfigInvalid("Nome do grupo não informado".to_string()));
+    }
+    if !(1..=MAX_OVERRIDE_INTERVAL_S).contains(&interval_s) {
+        return Err(AppError::ConfigInvalid(format!("Intervalo deve estar entre 1 e {}s", MAX_OVERRIDE_INTERVAL_S)));
+    }
+    if !(1..=MAX_OVERRIDE_DURATION_S).contains(&duration_s) {
+        return Err(AppError::ConfigInvalid(format!("Duração deve estar entre 1s e {}h", MAX_OVERRIDE_DURATION_S / 3600)));
+    }
+    let smart_cache = current_smart_cache(&websocket_state).await?;
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let entry = GroupIntervalOverride {
+        group_type,
+        group_name,
+        interval_s,
+        set_at_ms: now_ms,
+        expires_at_ms: now_ms + (duration_s * 1000) as i64,
+        reason: reason.filter(|r| !r.trim().is_empty()),
+    };
+    let previous = smart_cache.set_interval_override(entry.clone());
+
+    let target = format!("{}:{}", entry.group_type, entry.group_name);
+    let mut details = format!("{}s por {}s", interval_s, duration_s);
+    if let Some(previous) = previous {
+        details.push_str(&format!(" (substitui {}s)", previous.interval_s));
+    }
+    if let Some(ref reason) = entry.reason {
+        details.push_str(&format!(" - {}", reason));
+    }
+    println!("⏱️ Override de intervalo: {} -> {}", target, details);
+    if let Err(e) = db.add_audit_entry("broadcast_interval_override", &target, "ok", &details) {
+        println!("⚠️ Falha ao registrar auditoria de broadcast_interval_override: {}", e);
+    }
+    let _ = app_handle.emit("broadcast-interval-override", &entry);
+
+    // Reversão automática (um override substituído antes do prazo não é afetado)
+    let database = db.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(duration_s)).await;
+        revert_expired_interval_overrides(&smart_cache, &database, &app_handle);
+    });
+
+    Ok(entry)
+}
+
+#[tauri::command]
+pub async fn clear_group_interval_override(
+    group_type: String,
+    group_name: String,
+    db: State<'_, Arc<Database>>,
     websocket_state: State<'_, WebSocketServerState>,
-    tcp_server_state: State<'_, TcpServerState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let smart_cache = current_smart_cache(&websocket_state).await?;
+    let target = format!("{}:{}", group_type, group_name);
+    let entry = smart_cache.clear_interval_override(&group_type, &group_name)
+        .ok_or_else(|| AppError::NotFound(format!("Nenhum override ativo para {}", target)))?;
+
+    if let Err(e) = db.add_audit_entry("broadcast_interval_revert", &target, "ok", &format!("{}s removido manualmente", entry.interval_s)) {
+        println!("⚠️ Falha ao registrar auditoria de broadcast_interval_revert: {}", e);
+    }
+    let _ = app_handle.emit("broadcast-interval-reverted", &entry);
+    Ok(format!("Intervalo de {} voltou ao configurado", target))
+}
+
+// 🆕 TOKENS DE API DO WEBSOCKET (ver ws_auth.rs)
+// Com pelo menos um token ativo, o WebSocket passa a exigir autenticação.
+
+#[tauri::command]
+pub async fn list_ws_tokens(
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
-    println!("🔵 Iniciando WebSocket server com config: {:?}", config);
-    
-    // ⚠️ NÃO BLOQUEAR! Tentar lock com timeout
-    println!("🔵 Tentando adquirir lock do WebSocket state...");
-    let ws_guard_result = tokio::time::timeout(
-        tokio::time::Duration::from_millis(500),
-        websocket_state.write()
-    ).await;
-    
-    let mut ws_guard = match ws_guard_result {
-        Ok(guard) => {
-            println!("✅ Lock do WebSocket adquirido!");
-            guard
-        }
-        Err(_) => {
-            println!("❌ TIMEOUT ao tentar lock do WebSocket state!");
-            return Err("Timeout ao acessar estado do WebSocket".to_string());
-        }
-    };
-    
-    if ws_guard.is_some() {
-        return Err("WebSocket server já está rodando".to_string());
+) -> Result<Vec<crate::database::WsToken>, AppError> {
+    db.list_ws_tokens()
+        .map_err(|e| AppError::Database(format!("Erro ao listar tokens: {}", e)))
+}
+
+#[tauri::command]
+pub async fn create_ws_token(
+    name: String,
+    remote_support: Option<bool>,
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::ws_auth::CreatedWsToken, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::ConfigInvalid("Nome do token não informado".to_string()));
     }
-    
-    println!("🔵 Criando instância do WebSocket server...");
-    let mut websocket_server = WebSocketServer::new(
-        config,
-        app_handle,
-        db.inner().clone(),
-        Some(tcp_server_state.inner().clone()),
-    );
-    
-    println!("🔵 Iniciando WebSocket server...");
-    match websocket_server.start().await {
-        Ok(msg) => {
-            println!("✅ WebSocket server iniciado com sucesso: {}", msg);
-            *ws_guard = Some(websocket_server);
-            drop(ws_guard); // 🔓 LIBERAR LOCK IMEDIATAMENTE!
-            println!("🔓 Lock do WebSocket liberado!");
-            Ok(msg)
-        }
-        Err(e) => {
-            println!("❌ Erro ao iniciar WebSocket server: {}", e);
-            Err(e)
-        }
+    // 🆕 Token de suporte remoto: escrita só por /api/remote/write (aprovação do operador)
+    let remote_support = remote_support.unwrap_or(false);
+    let first_token = !crate::ws_auth::auth_required(&db);
+    let (token_hash, prefix, secret) = crate::ws_auth::generate_token();
+    let token = db.create_ws_token(&name, &token_hash, &prefix, remote_support)
+        .map_err(|e| AppError::Database(format!("Erro ao criar token: {}", e)))?;
+    let kind = if remote_support { ", suporte remoto" } else { "" };
+    if let Err(e) = db.add_audit_entry("ws_token_create", &name, "ok", &format!("{}… (id {}{})", prefix, token.id, kind)) {
+        println!("⚠️ Falha ao registrar auditoria de ws_token_create: {}", e);
+    }
+    if first_token {
+        println!("🔐 Primeiro token ativo: novas conexões WebSocket passam a exigir autenticação");
     }
+    Ok(crate::ws_auth::CreatedWsToken { token, secret })
 }
 
 #[tauri::command]
-pub async fn stop_websocket_server(
+pub async fn revoke_ws_token(
+    id: i64,
+    db: State<'_, Arc<Database>>,
     websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
-    let mut ws_guard = websocket_state.write().await;
-    
-    match ws_guard.as_mut() {
-        Some(server) => {
-            let result = server.stop().await;
-            *ws_guard = None;
-            result
-        }
-        None => Err("WebSocket server não está rodando".to_string())
+    workers_state: State<'_, WebSocketWorkersState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let token = db.revoke_ws_token(id)
+        .map_err(|e| AppError::Database(format!("Erro ao revogar token: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Token #{} não encontrado ou já revogado", id)))?;
+    let disconnected = websocket_state.read().await.as_ref()
+        .map(|server| server.disconnect_token_clients(id))
+        .unwrap_or(0)
+        + workers_state.read().await.iter()
+            .map(|worker| worker.disconnect_token_clients(id))
+            .sum::<usize>();
+
+    if let Err(e) = db.add_audit_entry("ws_token_revoke", &token.name, "ok", &format!("{}… (id {}), {} cliente(s) desconectado(s)", token.prefix, id, disconnected)) {
+        println!("⚠️ Falha ao registrar auditoria de ws_token_revoke: {}", e);
     }
+    let _ = app_handle.emit("ws-token-revoked", serde_json::json!({
+        "token": token,
+        "disconnected_clients": disconnected
+    }));
+    Ok(format!("Token '{}' revogado ({} cliente(s) desconectado(s))", token.name, disconnected))
 }
 
+// 🆕 SESSÕES DE CLIENTES WEBSOCKET (quem esteve conectado, quando e quanto recebeu)
+
 #[tauri::command]
-pub async fn get_websocket_stats(
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<WebSocketStats, String> {
-    let ws_guard = websocket_state.read().await;
-    
-    match ws_guard.as_ref() {
-        Some(server) => Ok(server.get_stats()),
-        None => {
-            // Retornar stats vazios se servidor não estiver rodando
-            Ok(WebSocketStats {
-                active_connections: 0,
-                total_connections: 0,
-                messages_sent: 0,
-                bytes_sent: 0,
-                uptime_seconds: 0,
-                server_status: "Parado".to_string(),
-                broadcast_rate_hz: 0.0,
-            })
-        }
+pub async fn get_ws_session_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::database::WsSessionConfig, AppError> {
+    db.load_ws_session_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração das sessões: {}", e)))
+}
+
+/// Salva e aplica a retenção na hora; `enabled` vale para as próximas conexões
+#[tauri::command]
+pub async fn save_ws_session_config(
+    mut config: crate::database::WsSessionConfig,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    if config.retention_days == 0 {
+        return Err(AppError::ConfigInvalid("Retenção deve ser de pelo menos 1 dia".to_string()));
     }
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_ws_session_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração das sessões: {}", e)))?;
+    let pruned = db.prune_ws_sessions(config.retention_days)
+        .map_err(|e| AppError::Database(format!("Erro ao aplicar retenção: {}", e)))?;
+    Ok(format!("Configuração salva ({} sessões antigas removidas)", pruned))
 }
 
+// 🆕 TÚNEL DE COMANDOS REMOTOS (aprovação única do operador local, ver remote_commands.rs)
+
 #[tauri::command]
-pub async fn get_websocket_clients(
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<Vec<serde_json::Value>, String> {
-    let ws_guard = websocket_state.read().await;
-    
-    match ws_guard.as_ref() {
-        Some(server) => Ok(server.get_connected_clients()),
-        None => Ok(Vec::new())
+pub async fn get_remote_tunnel_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::database::RemoteTunnelConfig, AppError> {
+    db.load_remote_tunnel_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do túnel remoto: {}", e)))
+}
+
+/// Desligar o túnel nega na hora os comandos que ainda aguardam decisão
+#[tauri::command]
+pub async fn save_remote_tunnel_config(
+    mut config: crate::database::RemoteTunnelConfig,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    crate::remote_commands::validate_timeout(config.approval_timeout_s).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_remote_tunnel_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do túnel remoto: {}", e)))?;
+    if let Err(e) = db.add_audit_entry("remote_tunnel_config", "remote_tunnel", "ok",
+        &format!("enabled={} prazo={}s", config.enabled, config.approval_timeout_s)) {
+        println!("⚠️ Erro ao registrar configuração do túnel na auditoria: {}", e);
     }
+    if config.enabled {
+        return Ok("Túnel de comandos remotos habilitado".to_string());
+    }
+    let denied = crate::remote_commands::deny_all(&app_handle, &db, "túnel remoto desabilitado");
+    Ok(format!("Túnel de comandos remotos desabilitado ({} pendente(s) negado(s))", denied))
 }
 
 #[tauri::command]
-pub async fn update_websocket_config(
-    config: WebSocketConfig,
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
-    let mut ws_guard = websocket_state.write().await;
-    
-    match ws_guard.as_mut() {
-        Some(server) => {
-            server.update_config(config);
-            Ok("Configuração do WebSocket atualizada".to_string())
-        }
-        None => Err("WebSocket server não está rodando".to_string())
+pub async fn list_pending_remote_commands() -> Result<Vec<crate::remote_commands::PendingRemoteCommand>, AppError> {
+    Ok(crate::remote_commands::list_pending())
+}
+
+#[tauri::command]
+pub async fn approve_remote_command(
+    id: String,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<crate::remote_commands::PendingRemoteCommand, AppError> {
+    crate::remote_commands::decide(&app_handle, &db, &id, true, None).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn deny_remote_command(
+    id: String,
+    reason: Option<String>,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<crate::remote_commands::PendingRemoteCommand, AppError> {
+    crate::remote_commands::decide(&app_handle, &db, &id, false, reason).map_err(AppError::from)
+}
+
+// 🆕 REGISTRO GLOBAL DE TAGS (nomes qualificados apelido.tag, ver tag_registry.rs)
+
+#[tauri::command]
+pub async fn list_all_tags(
+    filter: Option<crate::tag_registry::TagFilter>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::tag_registry::RegisteredTag>, AppError> {
+    crate::tag_registry::list(&db, &filter.unwrap_or_default()).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn find_duplicate_tag_names(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::tag_registry::DuplicateTagName>, AppError> {
+    crate::tag_registry::build(&db).map(|(_, duplicates)| duplicates).map_err(AppError::from)
+}
+
+/// "forno1.temperatura" → PLC e tag (para quem só tem o nome qualificado)
+#[tauri::command]
+pub async fn resolve_tag(
+    fqn: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::tag_registry::RegisteredTag, AppError> {
+    crate::tag_registry::resolve(&db, &fqn).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn list_plc_aliases(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::database::PlcAlias>, AppError> {
+    crate::tag_registry::list_aliases(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn save_plc_alias(
+    plc_ip: String,
+    alias: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    let alias = alias.trim().to_string();
+    crate::tag_registry::check_alias_available(&db, &plc_ip, &alias)?;
+    db.save_plc_alias(&crate::database::PlcAlias {
+        plc_ip: plc_ip.clone(),
+        alias: alias.clone(),
+        updated_at: chrono::Utc::now().timestamp(),
+    }).map_err(|e| AppError::Database(format!("Erro ao salvar apelido do PLC: {}", e)))?;
+    if let Err(e) = db.add_audit_entry("plc_alias", &plc_ip, "ok", &alias) {
+        println!("⚠️ Erro ao registrar apelido do PLC na auditoria: {}", e);
     }
+    Ok(format!("PLC {} agora é '{}' (tags: {}.<tag>)", plc_ip, alias, alias))
 }
 
+/// Volta ao apelido derivado do IP
 #[tauri::command]
-pub async fn get_websocket_config(
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<WebSocketConfig, String> {
-    let ws_guard = websocket_state.read().await;
-    
-    match ws_guard.as_ref() {
-        Some(server) => Ok(server.get_config().clone()),
-        None => Ok(WebSocketConfig::default())
+pub async fn delete_plc_alias(
+    plc_ip: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    let default_alias = crate::tag_registry::default_alias(&plc_ip);
+    crate::tag_registry::check_alias_available(&db, &plc_ip, &default_alias)?;
+    if !db.delete_plc_alias(&plc_ip).map_err(|e| AppError::Database(format!("Erro ao remover apelido do PLC: {}", e)))? {
+        return Err(AppError::ConfigInvalid(format!("PLC {} não tem apelido definido", plc_ip)));
     }
+    Ok(format!("PLC {} voltou ao apelido '{}'", plc_ip, default_alias))
 }
 
-// ============================================
-// COMANDOS DE CONFIGURAÇÃO INICIAL
-// ============================================
+// 🆕 ERROS DE PARSE POR PLC E QUARENTENA (ver parse_quarantine.rs)
 
 #[tauri::command]
-pub fn check_first_run(app_handle: AppHandle) -> Result<bool, String> {
-    let config_manager = ConfigManager::new(&app_handle)?;
-    Ok(config_manager.is_first_run())
+pub async fn get_parse_quarantine_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::database::ParseQuarantineConfig, AppError> {
+    db.load_parse_quarantine_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração da quarentena: {}", e)))
 }
 
 #[tauri::command]
-pub fn get_default_db_path(app_handle: AppHandle) -> Result<String, String> {
-    let path = ConfigManager::get_default_database_path(&app_handle)?;
-    Ok(path.to_string_lossy().to_string())
+pub async fn save_parse_quarantine_config(
+    mut config: crate::database::ParseQuarantineConfig,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    crate::parse_quarantine::validate_config(&config).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_parse_quarantine_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração da quarentena: {}", e)))?;
+    crate::parse_quarantine::set_config(config.clone());
+    Ok(format!("Quarentena: {} erros de parse em {}s", config.error_threshold, config.window_s))
 }
 
 #[tauri::command]
-pub fn validate_db_path(path: String) -> Result<(), String> {
-    ConfigManager::validate_database_path(&path)
+pub async fn get_plc_parse_stats() -> Result<Vec<crate::parse_quarantine::PlcParseStats>, AppError> {
+    Ok(crate::parse_quarantine::stats())
 }
 
+/// Libera um PLC em quarentena; a estrutura é recarregada do banco antes de voltar a parsear
 #[tauri::command]
-pub fn save_initial_config(
+pub async fn release_plc_quarantine(
+    plc_ip: String,
+    server_state: State<'_, TcpServerState>,
+    db: State<'_, Arc<Database>>,
     app_handle: AppHandle,
-    database_path: String,
-    tcp_port: u16,
-    websocket_port: u16,
-) -> Result<String, String> {
-    let config_manager = ConfigManager::new(&app_handle)?;
-    
-    // Validar caminho do banco
-    ConfigManager::validate_database_path(&database_path)?;
-    
-    let config = AppConfig {
-        database_path,
-        first_run_completed: true,
-        tcp_port,
-        websocket_port,
-        created_at: chrono::Utc::now().timestamp(),
-        updated_at: chrono::Utc::now().timestamp(),
-    };
-    
-    config_manager.save_config(&config)?;
-    
-    Ok("Configuração salva com sucesso!".to_string())
+) -> Result<crate::parse_quarantine::PlcParseStats, AppError> {
+    if let Some(server) = server_state.read().await.as_ref() {
+        server.reload_plc_config(&plc_ip);
+    }
+    crate::parse_quarantine::release(&app_handle, &db, &plc_ip).map_err(AppError::from)
 }
 
+// 🆕 RETOMADA AUTOMÁTICA DOS SERVIDORES (ver autostart.rs)
+
 #[tauri::command]
-pub fn get_app_config(app_handle: AppHandle) -> Result<AppConfig, String> {
-    let config_manager = ConfigManager::new(&app_handle)?;
-    config_manager.load_config()
+pub async fn get_runtime_state(
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::database::RuntimeState, AppError> {
+    db.load_runtime_state()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar estado de execução: {}", e)))
 }
 
-/// URGENTE: Corrige broadcast_interval_ms para valor seguro (1000ms mínimo)
+/// Liga/desliga a retomada de cada servidor no próximo início do app
 #[tauri::command]
-pub async fn fix_websocket_broadcast_interval(
+pub async fn save_autostart_config(
+    tcp: bool,
+    websocket: bool,
     db: State<'_, Arc<Database>>,
-) -> Result<String, String> {
-    // Carregar config atual
-    let current_config = db.load_websocket_config()
-        .map_err(|e| format!("Erro ao carregar config: {}", e))?;
-    
-    let old_interval = current_config.broadcast_interval_ms;
-    
-    // Se já está >= 1000ms, não precisa fazer nada
-    if old_interval >= 1000 {
-        return Ok(format!("✅ Broadcast interval já está seguro: {}ms", old_interval));
-    }
-    
-    // Corrigir para 1000ms (mínimo seguro)
-    let mut fixed_config = current_config.clone();
-    fixed_config.broadcast_interval_ms = 1000;
-    fixed_config.updated_at = chrono::Utc::now().timestamp();
-    
-    // Salvar no banco
-    db.save_websocket_config(&fixed_config)
-        .map_err(|e| format!("Erro ao salvar config corrigida: {}", e))?;
-    
-    println!("🔧 Broadcast interval CORRIGIDO: {}ms → 1000ms", old_interval);
-    Ok(format!("✅ Broadcast interval corrigido: {}ms → 1000ms (sistema agora estável)", old_interval))
+) -> Result<String, AppError> {
+    crate::autostart::set_autostart(&db, tcp, websocket)?;
+    let label = |enabled: bool| if enabled { "retomado" } else { "manual" };
+    Ok(format!("Ao reiniciar: TCP {}, WebSocket {}", label(tcp), label(websocket)))
 }
 
-use crate::database::PostgresConfig;
+// 🆕 SENSOR CONGELADO (tags analógicos sem variação, ver flatline.rs)
 
 #[tauri::command]
-pub async fn save_postgres_config(
-    config: PostgresConfig,
+pub async fn get_flatline_config(
     db: State<'_, Arc<Database>>,
-    app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    match db.save_postgres_config(&config) {
-        Ok(_) => {
-            // Emitir evento de configuração salva
-            let _ = app_handle.emit(
-                "postgres-config-saved",
-                serde_json::json!({
-                    "host": config.host,
-                    "port": config.port,
-                    "user": config.user,
-                    "database": config.database,
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                })
-            );
-            Ok("Configuração PostgreSQL salva com sucesso".to_string())
-        },
-        Err(e) => {
-            // Emitir evento de erro
-            let _ = app_handle.emit(
-                "postgres-config-error",
-                serde_json::json!({
-                    "operation": "save_config",
-                    "error": format!("{}", e),
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                })
-            );
-            Err(format!("Erro ao salvar configuração: {}", e))
-        }
-    }
+) -> Result<crate::database::FlatlineConfig, AppError> {
+    db.load_flatline_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração de sensor congelado: {}", e)))
 }
 
 #[tauri::command]
-pub async fn load_postgres_config(
+pub async fn save_flatline_config(
+    mut config: crate::database::FlatlineConfig,
     db: State<'_, Arc<Database>>,
-) -> Result<Option<PostgresConfig>, String> {
-    db.load_postgres_config()
-        .map_err(|e| format!("Erro ao carregar configuração: {}", e))
+) -> Result<String, AppError> {
+    crate::flatline::validate_config(&config).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_flatline_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração de sensor congelado: {}", e)))?;
+    crate::flatline::request_reload();
+    Ok(match config.enabled {
+        true => format!("Sensor congelado: tags analógicos sem variação por {}s serão marcados", config.flatline_after_s),
+        false => "Detecção de sensor congelado desabilitada".to_string(),
+    })
 }
 
-#[derive(Deserialize)]
-pub struct PostgresTestConfig {
-    pub host: String,
-    pub port: u16,
-    pub user: String,
-    pub password: String,
-    pub database: String,
+#[tauri::command]
+pub async fn get_flatlined_tags() -> Result<Vec<crate::flatline::FlatlinedTag>, AppError> {
+    Ok(crate::flatline::list_flatlined())
 }
 
+/// Sessões conectadas em algum momento do intervalo (ex: durante a parada de ontem à noite)
 #[tauri::command]
-pub async fn test_postgres_connection(
-    config: PostgresTestConfig,
-    app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    use tokio_postgres::{NoTls, Config};
-    
-    println!("🔍 Tentando conectar no PostgreSQL com tokio-postgres: {}:{}@{}/{}", 
-             config.user, config.port, config.host, config.database);
+pub async fn query_ws_client_sessions(
+    from_ms: i64,
+    to_ms: i64,
+    address: Option<String>,
+    token_name: Option<String>,
+    limit: Option<i64>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::database::WsClientSession>, AppError> {
+    if from_ms > to_ms {
+        return Err(AppError::ConfigInvalid("Início do intervalo depois do fim".to_string()));
+    }
+    db.query_ws_sessions(from_ms, to_ms, address.as_deref(), token_name.as_deref(), limit.unwrap_or(500).clamp(1, 10_000))
+        .map_err(|e| AppError::Database(format!("Erro ao consultar sessões: {}", e)))
+}
+
+// 🆕 CHAVES PÚBLICAS DO WEBSOCKET (mascaramento por grupo, ver ws_masking.rs)
+// A chave é credencial própria: com uma chave habilitada o WebSocket e as APIs
+// exigem token ou chave. Alterações valem para as próximas conexões.
+#[tauri::command]
+pub async fn list_public_stream_keys(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<PublicStreamKey>, AppError> {
+    db.load_public_stream_keys()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar chaves públicas: {}", e)))
+}
+
+#[tauri::command]
+pub async fn save_public_stream_key(
+    key: PublicStreamKey,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    if key.key.trim().len() < 16 {
+        return Err(AppError::ConfigInvalid("A chave pública deve ter pelo menos 16 caracteres".to_string()));
+    }
+    if key.name.trim().is_empty() {
+        return Err(AppError::ConfigInvalid("Informe um nome para a chave pública".to_string()));
+    }
+    crate::ws_masking::validate_rules(&key.rules).map_err(AppError::ConfigInvalid)?;
+    let first_credential = key.enabled && !crate::ws_auth::auth_required(&db);
+    db.save_public_stream_key(&key)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar chave pública: {}", e)))?;
+    if first_credential {
+        println!("🔐 Primeira chave pública habilitada: novas conexões passam a exigir token ou chave");
+    }
+    Ok(format!("Chave pública '{}' salva", key.name))
+}
+
+#[tauri::command]
+pub async fn delete_public_stream_key(
+    key: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<usize, AppError> {
+    db.delete_public_stream_key(&key)
+        .map_err(|e| AppError::Database(format!("Erro ao remover chave pública: {}", e)))
+}
+
+// ============================================================================
+// 🆕 DESTINOS DO HISTORIAN (FAILOVER)
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_historian_targets(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<HistorianTarget>, AppError> {
+    db.load_historian_targets()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar destinos do historian: {}", e)))
+}
+
+#[tauri::command]
+pub async fn save_historian_targets(
+    targets: Vec<HistorianTarget>,
+    db: State<'_, Arc<Database>>,
+    failover: State<'_, Arc<crate::historian_failover::HistorianFailover>>,
+) -> Result<String, AppError> {
+    crate::historian_failover::validate_targets(&targets).map_err(AppError::ConfigInvalid)?;
+    db.save_historian_targets(&targets)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar destinos do historian: {}", e)))?;
+    failover.reset().await;
+    Ok(format!("{} destino(s) do historian salvos", targets.len()))
+}
+
+#[tauri::command]
+pub async fn get_historian_failover_status(
+    failover: State<'_, Arc<crate::historian_failover::HistorianFailover>>,
+) -> Result<crate::historian_failover::FailoverStatus, AppError> {
+    Ok(failover.status())
+}
+
+#[tauri::command]
+pub async fn run_historian_catchup(
+    failover: State<'_, Arc<crate::historian_failover::HistorianFailover>>,
+) -> Result<crate::historian_failover::CatchupReport, AppError> {
+    failover.catch_up().await.map_err(AppError::from)
+}
+
+// ============================================================================
+// 🆕 GRAVAÇÃO CONTÍNUA NO HISTORIAN (ver historian_writer.rs)
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_historian_writer_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<HistorianWriterConfig, AppError> {
+    db.load_historian_writer_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do historian: {}", e)))
+}
+
+/// Salva a configuração; se a gravação estiver rodando, reinicia com a nova configuração
+#[tauri::command]
+pub async fn save_historian_writer_config(
+    mut config: HistorianWriterConfig,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    failover: State<'_, Arc<crate::historian_failover::HistorianFailover>>,
+    writer_state: State<'_, HistorianWriterState>,
+) -> Result<String, AppError> {
+    crate::historian_writer::validate_config(&config).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+
+    let mut writer_guard = writer_state.write().await;
+    if let Some(writer) = writer_guard.take() {
+        writer.stop().await;
+        *writer_guard = Some(HistorianWriter::start(db.inner().clone(), websocket_state.inner().clone(), failover.inner().clone(), config.clone())?);
+    }
+    config.enabled = writer_guard.is_some(); // "enabled" reflete se a gravação está ativa
+
+    db.save_historian_writer_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do historian: {}", e)))?;
+    Ok("Configuração do historian salva".to_string())
+}
+
+#[tauri::command]
+pub async fn start_historian_writer(
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    failover: State<'_, Arc<crate::historian_failover::HistorianFailover>>,
+    writer_state: State<'_, HistorianWriterState>,
+) -> Result<String, AppError> {
+    let mut writer_guard = writer_state.write().await;
+    if writer_guard.is_some() {
+        return Err(AppError::AlreadyRunning("Gravação do historian já está rodando".to_string()));
+    }
+
+    let mut config = db.load_historian_writer_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do historian: {}", e)))?;
+    *writer_guard = Some(HistorianWriter::start(db.inner().clone(), websocket_state.inner().clone(), failover.inner().clone(), config.clone())?);
+
+    // Lembrar que estava ativo para retomar ao reiniciar o app
+    config.enabled = true;
+    db.save_historian_writer_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do historian: {}", e)))?;
+    Ok("Gravação do historian iniciada".to_string())
+}
+
+#[tauri::command]
+pub async fn stop_historian_writer(
+    db: State<'_, Arc<Database>>,
+    writer_state: State<'_, HistorianWriterState>,
+) -> Result<String, AppError> {
+    let writer = writer_state.write().await.take()
+        .ok_or_else(|| AppError::NotRunning("Gravação do historian não está rodando".to_string()))?;
+    writer.stop().await;
+
+    let mut config = db.load_historian_writer_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do historian: {}", e)))?;
+    config.enabled = false;
+    db.save_historian_writer_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do historian: {}", e)))?;
+    Ok("Gravação do historian parada".to_string())
+}
+
+#[tauri::command]
+pub async fn get_historian_writer_stats(
+    db: State<'_, Arc<Database>>,
+    writer_state: State<'_, HistorianWriterState>,
+) -> Result<HistorianWriterStats, AppError> {
+    if let Some(writer) = writer_state.read().await.as_ref() {
+        return Ok(writer.stats());
+    }
+    let tags_logged = db.load_historian_tags()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar tags gravados: {}", e)))?
+        .len();
+    Ok(HistorianWriterStats { tags_logged, ..Default::default() })
+}
+
+// ============================================================================
+// 🆕 SIMULADOR DE PLC (ver simulator.rs)
+// ============================================================================
+
+/// Gera tráfego sintético para um PLC a partir da estrutura salva (requer servidor TCP rodando)
+#[tauri::command]
+pub async fn start_plc_simulator(
+    plc_ip: String,
+    interval_ms: Option<u64>,
+    seed: Option<u64>,
+    db: State<'_, Arc<Database>>,
+    server_state: State<'_, TcpServerState>,
+    simulator_state: State<'_, SimulatorState>,
+) -> Result<String, AppError> {
+    let mut simulator_guard = simulator_state.write().await;
+    if let Some(running) = simulator_guard.as_ref() {
+        return Err(AppError::AlreadyRunning(format!("Simulador já está rodando para {}", running.stats().plc_ip)));
+    }
+
+    let structure = db.load_plc_structure(&plc_ip)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar estrutura de {}: {}", plc_ip, e)))?
+        .ok_or_else(|| AppError::PlcNotFound(format!("PLC {} sem estrutura configurada", plc_ip)))?;
+    let config = crate::simulator::SimulatorConfig { plc_ip: plc_ip.clone(), interval_ms: interval_ms.unwrap_or(100), seed };
+    let simulator = crate::simulator::Simulator::start(server_state.inner().clone(), config, structure).await?;
+    let frame_size = simulator.stats().frame_size;
+    *simulator_guard = Some(simulator);
+    Ok(format!("Simulador iniciado para {} ({} bytes por frame)", plc_ip, frame_size))
+}
+
+#[tauri::command]
+pub async fn stop_plc_simulator(
+    server_state: State<'_, TcpServerState>,
+    simulator_state: State<'_, SimulatorState>,
+) -> Result<String, AppError> {
+    let simulator = simulator_state.write().await.take()
+        .ok_or_else(|| AppError::NotRunning("Simulador não está rodando".to_string()))?;
+    simulator.stop(&server_state).await;
+    Ok("Simulador parado".to_string())
+}
+
+#[tauri::command]
+pub async fn get_plc_simulator_status(
+    simulator_state: State<'_, SimulatorState>,
+) -> Result<crate::simulator::SimulatorStats, AppError> {
+    Ok(simulator_state.read().await.as_ref().map(|s| s.stats()).unwrap_or_default())
+}
+
+// ============================================================================
+// 🆕 GRAVAÇÃO E REPRODUÇÃO DE PACOTES BRUTOS (ver packet_recorder.rs)
+// ============================================================================
+
+/// Grava os frames brutos de um PLC em `file` (nome simples = pasta recordings/)
+#[tauri::command]
+pub async fn start_packet_recording(
+    plc_ip: String,
+    file: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::packet_recorder::RecordingInfo, AppError> {
+    let structure = db.load_plc_structure(&plc_ip)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar estrutura de {}: {}", plc_ip, e)))?;
+    let path = crate::packet_recorder::resolve_path(&db, &file);
+    crate::packet_recorder::start_recording(&path, &plc_ip, structure).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn stop_packet_recording(plc_ip: String) -> Result<crate::packet_recorder::RecordingInfo, AppError> {
+    crate::packet_recorder::stop_recording(&plc_ip).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn list_packet_recordings() -> Result<Vec<crate::packet_recorder::RecordingInfo>, AppError> {
+    Ok(crate::packet_recorder::active_recordings())
+}
+
+/// Reproduz uma gravação pelo parser e pelo WebSocket (`speed` 1.0 = tempo real)
+#[tauri::command]
+pub async fn replay_packet_file(
+    file: String,
+    speed: Option<f64>,
+    plc_ip: Option<String>,
+    db: State<'_, Arc<Database>>,
+    server_state: State<'_, TcpServerState>,
+    replay_state: State<'_, PacketReplayState>,
+) -> Result<crate::packet_recorder::ReplayStats, AppError> {
+    let mut replay_guard = replay_state.write().await;
+    if replay_guard.as_ref().map_or(false, |r| r.stats().running) {
+        return Err(AppError::AlreadyRunning("Já existe uma reprodução em andamento".to_string()));
+    }
+    let path = crate::packet_recorder::resolve_path(&db, &file);
+    let saved_structure = match &plc_ip {
+        Some(ip) => db.load_plc_structure(ip).map_err(|e| AppError::Database(format!("Erro ao carregar estrutura de {}: {}", ip, e)))?,
+        None => None,
+    };
+    let replay = crate::packet_recorder::PacketReplay::start(server_state.inner().clone(), &path, speed.unwrap_or(1.0), plc_ip, saved_structure).await?;
+    let stats = replay.stats();
+    *replay_guard = Some(replay);
+    Ok(stats)
+}
+
+#[tauri::command]
+pub async fn stop_packet_replay(
+    replay_state: State<'_, PacketReplayState>,
+) -> Result<crate::packet_recorder::ReplayStats, AppError> {
+    let replay = replay_state.write().await.take()
+        .ok_or_else(|| AppError::NotRunning("Nenhuma reprodução em andamento".to_string()))?;
+    Ok(replay.stop().await)
+}
+
+#[tauri::command]
+pub async fn get_packet_replay_status(
+    replay_state: State<'_, PacketReplayState>,
+) -> Result<crate::packet_recorder::ReplayStats, AppError> {
+    Ok(replay_state.read().await.as_ref().map(|r| r.stats()).unwrap_or_default())
+}
+
+/// Liga/desliga a gravação de tags no historian (vale em até 10s se estiver rodando)
+#[tauri::command]
+pub async fn set_historian_tag_logging(
+    plc_ip: String,
+    tag_names: Vec<String>,
+    enabled: bool,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    if tag_names.is_empty() {
+        return Err(AppError::ConfigInvalid("Nenhum tag informado".to_string()));
+    }
+    let changed = db.set_historian_tags(&plc_ip, &tag_names, enabled)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar tags gravados: {}", e)))?;
+    Ok(format!("{} tag(s) de {} {} no historian", changed, plc_ip, if enabled { "gravando" } else { "sem gravação" }))
+}
+
+/// Tags com gravação ligada: [{plc_ip, tag_name}]
+#[tauri::command]
+pub async fn list_historian_tags(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let tags = db.load_historian_tags()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar tags gravados: {}", e)))?;
+    Ok(tags.into_iter()
+        .map(|(plc_ip, tag_name)| serde_json::json!({ "plc_ip": plc_ip, "tag_name": tag_name }))
+        .collect())
+}
+
+/// Amostras gravadas de um tag em uma janela (ms Unix, mais antigas primeiro),
+/// convertidas da unidade vigente em cada instante para a unidade atual
+#[tauri::command]
+pub async fn query_tag_history(
+    plc_ip: String,
+    tag_name: String,
+    from_ms: i64,
+    to_ms: i64,
+    limit: Option<i64>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<historian::SnapshotValue>, AppError> {
+    if to_ms <= from_ms {
+        return Err(AppError::ConfigInvalid("Janela inválida: fim deve ser maior que início".to_string()));
+    }
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?
+        .ok_or_else(|| AppError::DbNotInitialized("PostgreSQL não configurado".to_string()))?;
+    let pg = PgDatabase::connect(&historian::postgres_url(&pg_config)).await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    let mut samples = historian::fetch_tag_history(&pg.pool, &plc_ip, &tag_name, from_ms, to_ms, limit.unwrap_or(10_000).clamp(1, 100_000)).await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar histórico de '{}': {}", tag_name, e)))?;
+    let versions = db.list_tag_unit_versions(Some(&plc_ip), Some(&tag_name))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar versões da unidade de '{}': {}", tag_name, e)))?;
+    crate::units::apply_unit_versions(&mut samples, &versions);
+    Ok(samples)
+}
+
+/// 🆕 Valor de um tag em um instante passado (step ou linear, ver historian::interpolate),
+/// na unidade atual do tag
+#[tauri::command]
+pub async fn read_tag_at(
+    plc_ip: String,
+    tag: String,
+    timestamp_ms: i64,
+    interpolation: Option<String>,
+    db: State<'_, Arc<Database>>,
+) -> Result<historian::InterpolatedValue, AppError> {
+    let mode = match interpolation.as_deref() {
+        None => historian::Interpolation::Step,
+        Some(value) => historian::Interpolation::parse(value)
+            .ok_or_else(|| AppError::ConfigInvalid(format!("Interpolação inválida: '{}' (use step ou linear)", value)))?,
+    };
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?
+        .ok_or_else(|| AppError::DbNotInitialized("PostgreSQL não configurado".to_string()))?;
+    let pg = PgDatabase::connect(&historian::postgres_url(&pg_config)).await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    let (before, after) = historian::fetch_neighbors(&pg.pool, &plc_ip, &tag, timestamp_ms).await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar histórico de '{}': {}", tag, e)))?;
+    // Converter as duas amostras para a unidade atual antes de interpolar
+    let mut samples: Vec<historian::SnapshotValue> = before.into_iter().chain(after).collect();
+    let versions = db.list_tag_unit_versions(Some(&plc_ip), Some(&tag))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar versões da unidade de '{}': {}", tag, e)))?;
+    crate::units::apply_unit_versions(&mut samples, &versions);
+
+    let before = samples.iter().find(|s| s.ts_ms <= timestamp_ms);
+    let after = samples.iter().find(|s| s.ts_ms > timestamp_ms);
+    Ok(historian::interpolate(&plc_ip, &tag, timestamp_ms, before, after, mode))
+}
+
+// ============================================================================
+// 🆕 ARQUIVO MORTO DO HISTORIAN (ver historian_archive.rs)
+// ============================================================================
+
+/// Move os meses mais antigos que `older_than_months` para arquivos compactados
+#[tauri::command]
+pub async fn archive_historian_months(
+    older_than_months: u32,
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::historian_archive::ArchiveReport, AppError> {
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?
+        .ok_or_else(|| AppError::DbNotInitialized("PostgreSQL não configurado".to_string()))?;
+    let pg = PgDatabase::connect(&historian::postgres_url(&pg_config)).await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    let report = crate::historian_archive::archive_older_than(&db, &pg.pool, older_than_months).await?;
+    if !report.archived.is_empty() {
+        let months: Vec<&str> = report.archived.iter().map(|a| a.month.as_str()).collect();
+        if let Err(e) = db.add_audit_entry("historian_archive", &months.join(", "), "ok", &format!("{} linhas arquivadas", report.rows_removed)) {
+            println!("⚠️ Erro ao registrar auditoria: {}", e);
+        }
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn list_historian_archives(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::historian_archive::ArchiveInfo>, AppError> {
+    Ok(crate::historian_archive::list_archives(&db))
+}
+
+#[tauri::command]
+pub async fn attach_historian_archive(
+    month: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::historian_archive::ArchiveInfo, AppError> {
+    let db = db.inner().clone();
+    tokio::task::spawn_blocking(move || crate::historian_archive::attach_archive(&db, &month)).await
+        .map_err(|e| format!("Task de anexação falhou: {}", e))?
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn detach_historian_archive(
+    month: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    crate::historian_archive::detach_archive(&db, &month)?;
+    Ok(format!("Arquivo de {} desanexado", month))
+}
+
+/// Amostras de um tag nos arquivos anexados (mesmo formato de query_tag_history)
+#[tauri::command]
+pub async fn query_archived_tag_history(
+    plc_ip: String,
+    tag_name: String,
+    from_ms: i64,
+    to_ms: i64,
+    limit: Option<i64>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<historian::SnapshotValue>, AppError> {
+    if to_ms <= from_ms {
+        return Err(AppError::ConfigInvalid("Janela inválida: fim deve ser maior que início".to_string()));
+    }
+    let limit = limit.unwrap_or(10_000).clamp(1, 100_000) as usize;
+    let database = db.inner().clone();
+    let (ip, name) = (plc_ip.clone(), tag_name.clone());
+    let mut samples = tokio::task::spawn_blocking(move || crate::historian_archive::query_attached(&database, &ip, &name, from_ms, to_ms, limit)).await
+        .map_err(|e| format!("Task de consulta falhou: {}", e))??;
+    let versions = db.list_tag_unit_versions(Some(&plc_ip), Some(&tag_name))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar versões da unidade de '{}': {}", tag_name, e)))?;
+    crate::units::apply_unit_versions(&mut samples, &versions);
+    Ok(samples)
+}
+
+/// 🆕 Versões da unidade dos tags (quando unit/display_unit mudou)
+#[tauri::command]
+pub async fn list_tag_unit_versions(
+    plc_ip: Option<String>,
+    tag_name: Option<String>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::database::TagUnitVersion>, AppError> {
+    db.list_tag_unit_versions(plc_ip.as_deref(), tag_name.as_deref())
+        .map_err(|e| AppError::Database(format!("Erro ao carregar versões da unidade: {}", e)))
+}
+
+#[tauri::command]
+pub async fn load_tag_mappings(
+    plc_ip: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<TagMapping>, AppError> {
+    db.load_tag_mappings(&plc_ip)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar tags: {}", e)))
+}
+
+#[tauri::command]
+pub async fn delete_tag_mapping(
+    plc_ip: String,
+    variable_path: String,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<String, AppError> {
+    db.delete_tag_mapping(&plc_ip, &variable_path)
+        .map_err(|e| AppError::Database(format!("Erro ao deletar tag: {}", e)))?;
+    // Sempre recarregar grupos de tags do WebSocket
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+    Ok(t("tag.deleted", &[("path", variable_path)]))
+}
+
+/// Remove vários tags em uma transação (tudo ou nada), com resultado por item
+#[tauri::command]
+pub async fn delete_tag_mappings_bulk(
+    ids: Vec<i64>,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<TagBatchResult, AppError> {
+    let result = db.delete_tag_mappings_bulk(ids)
+        .map_err(|e| AppError::Database(format!("Erro ao deletar tags: {}", e)))?;
+    // Recarregar grupos de tags do WebSocket uma vez, só se algo mudou
+    if result.committed {
+        let _ = reload_websocket_tag_groups(websocket_state).await;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_active_tags(
+    plc_ip: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<TagMapping>, AppError> {
+    db.get_active_tags(&plc_ip)
+        .map_err(|e| AppError::Database(format!("Erro ao buscar tags ativos: {}", e)))
+}
+
+#[tauri::command]
+pub async fn get_plc_variables_for_mapping(
+    plc_ip: String,
+    server_state: State<'_, TcpServerState>,
+) -> Result<Vec<String>, AppError> {
+    let server_guard = server_state.read().await;
+    
+    match server_guard.as_ref() {
+        Some(server) => {
+            if let Some(data_packet) = server.get_plc_data(&plc_ip).await {
+                let variable_names: Vec<String> = data_packet.variables
+                    .iter()
+                    .map(|v| v.name.clone())
+                    .collect();
+                Ok(variable_names)
+            } else {
+                Err(AppError::PlcNotFound("PLC não encontrado ou sem dados".to_string()))
+            }
+        }
+        None => Err(AppError::NotRunning("Servidor TCP não está rodando".to_string()))
+    }
+}
+
+// ============================================================================
+// COMANDOS WEBSOCKET SERVER
+// ============================================================================
+
+#[tauri::command]
+pub async fn start_websocket_server(
+    config: WebSocketConfig,
+    app_handle: AppHandle,
+    websocket_state: State<'_, WebSocketServerState>,
+    tcp_server_state: State<'_, TcpServerState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    println!("🔵 Iniciando WebSocket server com config: {:?}", config);
+    
+    // ⚠️ NÃO BLOQUEAR! Tentar lock com timeout
+    println!("🔵 Tentando adquirir lock do WebSocket state...");
+    let ws_guard_result = tokio::time::timeout(
+        tokio::time::Duration::from_millis(500),
+        websocket_state.write()
+    ).await;
+    
+    let mut ws_guard = match ws_guard_result {
+        Ok(guard) => {
+            println!("✅ Lock do WebSocket adquirido!");
+            guard
+        }
+        Err(_) => {
+            println!("❌ TIMEOUT ao tentar lock do WebSocket state!");
+            return Err(AppError::Timeout("Timeout ao acessar estado do WebSocket".to_string()));
+        }
+    };
+    
+    if ws_guard.is_some() {
+        return Err(AppError::AlreadyRunning("WebSocket server já está rodando".to_string()));
+    }
+    
+    println!("🔵 Criando instância do WebSocket server...");
+    let started_config = config.clone();
+    let mut websocket_server = WebSocketServer::new(
+        config,
+        app_handle,
+        db.inner().clone(),
+        Some(tcp_server_state.inner().clone()),
+    );
+    
+    println!("🔵 Iniciando WebSocket server...");
+    match websocket_server.start().await {
+        Ok(msg) => {
+            println!("✅ WebSocket server iniciado com sucesso: {}", msg);
+            *ws_guard = Some(websocket_server);
+            drop(ws_guard); // 🔓 LIBERAR LOCK IMEDIATAMENTE!
+            println!("🔓 Lock do WebSocket liberado!");
+            crate::autostart::remember_websocket(&db, Some(&started_config));
+            Ok(msg)
+        }
+        Err(e) => {
+            println!("❌ Erro ao iniciar WebSocket server: {}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn stop_websocket_server(
+    websocket_state: State<'_, WebSocketServerState>,
+    workers_state: State<'_, WebSocketWorkersState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    // 🆕 Workers leem o cache da principal: são parados antes dela
+    stop_all_websocket_workers(&workers_state).await;
+    let mut ws_guard = websocket_state.write().await;
+    
+    match ws_guard.as_mut() {
+        Some(server) => {
+            let result = server.stop().await;
+            *ws_guard = None;
+            crate::autostart::remember_websocket(&db, None);
+            result
+        }
+        None => Err(AppError::websocket_not_running())
+    }
+}
+
+// 🆕 CLUSTER DE BROADCASTERS (instâncias extras em outras portas, mesmo SmartCache)
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WebSocketWorkerInfo {
+    pub port: u16,
+    pub stats: WebSocketStats,
+}
+
+async fn stop_all_websocket_workers(workers_state: &WebSocketWorkersState) -> usize {
+    let mut workers = workers_state.write().await;
+    let count = workers.len();
+    for mut worker in workers.drain(..) {
+        if let Err(e) = worker.stop().await {
+            println!("⚠️ Erro ao parar worker WebSocket {:?}: {}", worker.worker_port(), e);
+        }
+    }
+    count
+}
+
+/// Sobe workers nas portas pedidas com a configuração da instância principal
+/// (hosts, limite de clientes). Porta já usada ou falha no bind: os workers
+/// desta chamada são parados e nada muda.
+#[tauri::command]
+pub async fn start_websocket_workers(
+    ports: Vec<u16>,
+    app_handle: AppHandle,
+    websocket_state: State<'_, WebSocketServerState>,
+    workers_state: State<'_, WebSocketWorkersState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    let ws_guard = websocket_state.read().await;
+    let primary = ws_guard.as_ref().ok_or_else(|| AppError::NotRunning("WebSocket server principal não está rodando".to_string()))?;
+    let mut workers = workers_state.write().await;
+
+    let mut taken: std::collections::HashSet<u16> = workers.iter().filter_map(|w| w.worker_port()).collect();
+    taken.insert(primary.get_config().port);
+    for port in &ports {
+        if !taken.insert(*port) {
+            return Err(AppError::ConfigInvalid(format!("Porta {} já está em uso pelo WebSocket", port)));
+        }
+    }
+    if workers.len() + ports.len() > crate::websocket_server::MAX_CLUSTER_WORKERS {
+        return Err(AppError::ConfigInvalid(format!("No máximo {} workers WebSocket", crate::websocket_server::MAX_CLUSTER_WORKERS)));
+    }
+
+    let mut started: Vec<WebSocketServer> = Vec::new();
+    for port in ports {
+        let mut config = primary.get_config().clone();
+        config.port = port;
+        let mut worker = WebSocketServer::new_worker(config, app_handle.clone(), db.inner().clone(), primary.smart_cache());
+        if let Err(e) = worker.start().await {
+            for mut worker in started {
+                let _ = worker.stop().await;
+            }
+            return Err(AppError::Other(format!("Worker na porta {}: {}", port, e)));
+        }
+        started.push(worker);
+    }
+
+    let count = started.len();
+    workers.extend(started);
+    println!("🧩 Cluster WebSocket: {} worker(s) ativo(s)", workers.len());
+    Ok(format!("{} worker(s) WebSocket iniciado(s) ({} no total)", count, workers.len()))
+}
+
+#[tauri::command]
+pub async fn stop_websocket_workers(
+    workers_state: State<'_, WebSocketWorkersState>,
+) -> Result<String, AppError> {
+    let stopped = stop_all_websocket_workers(&workers_state).await;
+    Ok(format!("{} worker(s) WebSocket parado(s)", stopped))
+}
+
+#[tauri::command]
+pub async fn get_websocket_workers(
+    workers_state: State<'_, WebSocketWorkersState>,
+) -> Result<Vec<WebSocketWorkerInfo>, AppError> {
+    Ok(workers_state.read().await.iter()
+        .filter_map(|worker| Some(WebSocketWorkerInfo { port: worker.worker_port()?, stats: worker.get_stats() }))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_websocket_stats(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<WebSocketStats, AppError> {
+    let ws_guard = websocket_state.read().await;
+    
+    match ws_guard.as_ref() {
+        Some(server) => Ok(server.get_stats()),
+        None => {
+            // Retornar stats vazios se servidor não estiver rodando
+            Ok(WebSocketStats {
+                active_connections: 0,
+                total_connections: 0,
+                messages_sent: 0,
+                bytes_sent: 0,
+                uptime_seconds: 0,
+                server_status: "Parado".to_string(),
+                broadcast_rate_hz: 0.0,
+                suppressed_events: 0,
+                critical_updates_sent: 0,
+                critical_latency_last_ms: 0.0,
+                critical_latency_avg_ms: 0.0,
+                critical_latency_max_ms: 0.0,
+                db_degraded: false,
+            })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_websocket_suppressed_events(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let ws_guard = websocket_state.read().await;
+    
+    match ws_guard.as_ref() {
+        Some(server) => Ok(server.get_suppressed_events()),
+        None => Ok(Vec::new())
+    }
+}
+
+/// 🆕 Circuit breaker do SQLite no cache do WebSocket (None = servidor parado)
+#[tauri::command]
+pub async fn get_websocket_db_breaker_status(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<Option<crate::db_breaker::DbBreakerStatus>, AppError> {
+    let ws_guard = websocket_state.read().await;
+    Ok(ws_guard.as_ref().map(|server| server.db_breaker_status()))
+}
+
+#[tauri::command]
+pub async fn get_websocket_clients(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let ws_guard = websocket_state.read().await;
+    
+    match ws_guard.as_ref() {
+        Some(server) => Ok(server.get_connected_clients()),
+        None => Ok(Vec::new())
+    }
+}
+
+#[tauri::command]
+pub async fn update_websocket_config(
+    config: WebSocketConfig,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<String, AppError> {
+    let mut ws_guard = websocket_state.write().await;
+    
+    match ws_guard.as_mut() {
+        Some(server) => {
+            server.update_config(config);
+            Ok("Configuração do WebSocket atualizada".to_string())
+        }
+        None => Err(AppError::NotRunning("WebSocket server não está rodando".to_string()))
+    }
+}
+
+#[tauri::command]
+pub async fn get_websocket_config(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<WebSocketConfig, AppError> {
+    let ws_guard = websocket_state.read().await;
+    
+    match ws_guard.as_ref() {
+        Some(server) => Ok(server.get_config().clone()),
+        None => Ok(WebSocketConfig::default())
+    }
+}
+
+// ============================================
+// COMANDOS DE CONFIGURAÇÃO INICIAL
+// ============================================
+
+#[tauri::command]
+pub fn check_first_run(app_handle: AppHandle) -> Result<bool, AppError> {
+    let config_manager = ConfigManager::new(&app_handle)?;
+    Ok(config_manager.is_first_run())
+}
+
+#[tauri::command]
+pub fn get_default_db_path(app_handle: AppHandle) -> Result<String, AppError> {
+    let path = ConfigManager::get_default_database_path(&app_handle)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn validate_db_path(path: String) -> Result<(), AppError> {
+    ConfigManager::validate_database_path(&path).map_err(AppError::ConfigInvalid)
+}
+
+#[tauri::command]
+pub fn save_initial_config(
+    app_handle: AppHandle,
+    database_path: String,
+    tcp_port: u16,
+    websocket_port: u16,
+) -> Result<String, AppError> {
+    let config_manager = ConfigManager::new(&app_handle)?;
+    
+    // Validar caminho do banco
+    ConfigManager::validate_database_path(&database_path).map_err(AppError::ConfigInvalid)?;
+    
+    // Manter a identidade já anunciada nesta sessão (UUID gerado no startup)
+    let current = config_manager.load_instance()?;
+    let config = AppConfig {
+        database_path,
+        first_run_completed: true,
+        tcp_port,
+        websocket_port,
+        created_at: chrono::Utc::now().timestamp(),
+        updated_at: chrono::Utc::now().timestamp(),
+        instance: current.instance,
+        startup_banner: current.startup_banner,
+        language: Some(crate::i18n::current_language().to_string()),
+    };
+    
+    config_manager.save_config(&config)?;
+    
+    Ok(t("config.saved", &[]))
+}
+
+#[tauri::command]
+pub fn get_app_config(app_handle: AppHandle) -> Result<AppConfig, AppError> {
+    let config_manager = ConfigManager::new(&app_handle)?;
+    config_manager.load_config().map_err(AppError::from)
+}
+
+/// Identidade desta instância (nome, site, UUID)
+#[tauri::command]
+pub fn get_instance_identity() -> Result<InstanceIdentity, AppError> {
+    Ok(crate::config::current_instance())
+}
+
+/// Altera nome/site e o banner de startup. O UUID não muda.
+#[tauri::command]
+pub fn set_instance_identity(
+    app_handle: AppHandle,
+    name: String,
+    site: String,
+    startup_banner: Option<String>,
+) -> Result<InstanceIdentity, AppError> {
+    let config_manager = ConfigManager::new(&app_handle)?;
+    let mut config = config_manager.load_instance()?;
+    config.instance.name = name.trim().to_string();
+    config.instance.site = site.trim().to_string();
+    config.instance.validate().map_err(AppError::ConfigInvalid)?;
+    config.startup_banner = startup_banner.filter(|b| !b.trim().is_empty());
+    
+    // Primeira execução: só em memória até save_initial_config criar o arquivo
+    if !config_manager.is_first_run() {
+        config_manager.save_config(&config)?;
+    }
+    crate::config::set_current_instance(config.instance.clone());
+    println!("🆔 Instância: {} @ {} ({})", config.instance.name, config.instance.site, config.instance.uuid);
+    Ok(config.instance)
+}
+
+// 🆕 IDIOMA DAS MENSAGENS DO BACKEND (ver i18n.rs)
+
+#[tauri::command]
+pub fn get_backend_language() -> Result<String, AppError> {
+    Ok(crate::i18n::current_language().to_string())
+}
+
+/// Troca o idioma das mensagens do backend e grava no AppConfig
+#[tauri::command]
+pub fn set_backend_language(app_handle: AppHandle, language: String) -> Result<LocalizedMessage, AppError> {
+    let language = crate::i18n::set_language(&language)?;
+    let config_manager = ConfigManager::new(&app_handle)?;
+    if !config_manager.is_first_run() {
+        let mut config = config_manager.load_config()?;
+        config.language = Some(language.to_string());
+        config_manager.save_config(&config)?;
+    }
+    let message = crate::i18n::msg("language.changed", &[("language", language.to_string())]);
+    let _ = app_handle.emit("backend-language-changed", &message);
+    println!("🌐 {}", message.text);
+    Ok(message)
+}
+
+/// Catálogo de mensagens (chave → texto) de um idioma; sem idioma, o atual
+#[tauri::command]
+pub fn get_message_catalog(language: Option<String>) -> Result<std::collections::BTreeMap<&'static str, &'static str>, AppError> {
+    let language = language.unwrap_or_else(|| crate::i18n::current_language().to_string());
+    if !crate::i18n::LANGUAGES.contains(&language.as_str()) {
+        return Err(AppError::ConfigInvalid(t("language.unsupported", &[
+            ("language", language),
+            ("supported", crate::i18n::LANGUAGES.join(", ")),
+        ])));
+    }
+    Ok(crate::i18n::catalog(&language))
+}
+
+/// Estado da criptografia/integridade do arquivo de configuração
+#[tauri::command]
+pub fn get_config_security_status(app_handle: AppHandle) -> Result<ConfigSecurityStatus, AppError> {
+    let config_manager = ConfigManager::new(&app_handle)?;
+    Ok(config_manager.check_security())
+}
+
+/// Ativa/desativa a criptografia em repouso do arquivo de configuração
+#[tauri::command]
+pub fn set_config_encryption(app_handle: AppHandle, enabled: bool) -> Result<ConfigSecurityStatus, AppError> {
+    let config_manager = ConfigManager::new(&app_handle)?;
+    config_manager.set_encryption(enabled)?;
+    Ok(config_manager.check_security())
+}
+
+/// URGENTE: Corrige broadcast_interval_ms para valor seguro (1000ms mínimo)
+#[tauri::command]
+pub async fn fix_websocket_broadcast_interval(
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    // Carregar config atual
+    let current_config = db.load_websocket_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar config: {}", e)))?;
+    
+    let old_interval = current_config.broadcast_interval_ms;
+    
+    // Se já está >= 1000ms, não precisa fazer nada
+    if old_interval >= 1000 {
+        return Ok(format!("✅ Broadcast interval já está seguro: {}ms", old_interval));
+    }
+    
+    // Corrigir para 1000ms (mínimo seguro)
+    let mut fixed_config = current_config.clone();
+    fixed_config.broadcast_interval_ms = 1000;
+    fixed_config.updated_at = chrono::Utc::now().timestamp();
+    
+    // Salvar no banco
+    db.save_websocket_config(&fixed_config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar config corrigida: {}", e)))?;
+    
+    println!("🔧 Broadcast interval CORRIGIDO: {}ms → 1000ms", old_interval);
+    Ok(format!("✅ Broadcast interval corrigido: {}ms → 1000ms (sistema agora estável)", old_interval))
+}
+
+use crate::database::PostgresConfig;
+
+#[tauri::command]
+pub async fn save_postgres_config(
+    config: PostgresConfig,
+    db: State<'_, Arc<Database>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, AppError> {
+    match db.save_postgres_config(&config) {
+        Ok(_) => {
+            // Emitir evento de configuração salva
+            let _ = app_handle.emit(
+                "postgres-config-saved",
+                serde_json::json!({
+                    "host": config.host,
+                    "port": config.port,
+                    "user": config.user,
+                    "database": config.database,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                })
+            );
+            Ok("Configuração PostgreSQL salva com sucesso".to_string())
+        },
+        Err(e) => {
+            // Emitir evento de erro
+            let _ = app_handle.emit(
+                "postgres-config-error",
+                serde_json::json!({
+                    "operation": "save_config",
+                    "error": format!("{}", e),
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                })
+            );
+            Err(AppError::Database(format!("Erro ao salvar configuração: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn load_postgres_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<Option<PostgresConfig>, AppError> {
+    db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração: {}", e)))
+}
+
+#[derive(Deserialize)]
+pub struct PostgresTestConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+#[tauri::command]
+pub async fn test_postgres_connection(
+    config: PostgresTestConfig,
+    app_handle: tauri::AppHandle,
+) -> Result<String, AppError> {
+    use tokio_postgres::{NoTls, Config};
+    
+    println!("🔍 Tentando conectar no PostgreSQL com tokio-postgres: {}:{}@{}/{}", 
+             config.user, config.port, config.host, config.database);
+    
+    // Usar tokio-postgres diretamente para evitar problemas de encoding do sqlx
+    let mut pg_config = Config::new();
+    pg_config
+        .host(&config.host)
+        .port(config.port)
+        .user(&config.user)
+        .password(&config.password)
+        .dbname(&config.database)
+        .application_name("plc-hmi");
+    
+    match pg_config.connect(NoTls).await {
+        Ok((client, connection)) => {
+            println!("✅ Conexão tokio-postgres estabelecida!");
+            
+            // Spawnar a conexão em background
+            let handle = tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    eprintln!("connection error: {}", e);
+                }
+            });
+            
+            // Testar uma query simples
+            match client.query("SELECT 1 as test", &[]).await {
+                Ok(rows) => {
+                    println!("✅ Query executada! Resultado: {} linhas", rows.len());
+                    handle.abort(); // Limpar conexão
+                    
+                    // Emitir evento de teste bem-sucedido
+                    let _ = app_handle.emit(
+                        "postgres-connection-success",
+                        serde_json::json!({
+                            "host": config.host,
+                            "port": config.port,
+                            "user": config.user,
+                            "database": config.database,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        })
+                    );
+                    
+                    Ok("✅ Conexão PostgreSQL funcionando perfeitamente!".to_string())
+                },
+                Err(e) => {
+                    println!("❌ Erro na query: {}", e);
+                    handle.abort();
+                    
+                    // Emitir evento de erro na query
+                    let _ = app_handle.emit(
+                        "postgres-connection-error",
+                        serde_json::json!({
+                            "host": config.host,
+                            "port": config.port,
+                            "operation": "test_query",
+                            "error": format!("{}", e),
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        })
+                    );
+                    
+                    Err(AppError::Database(format!("❌ Conexão OK mas erro na query: {}", e)))
+                }
+            }
+        },
+        Err(e) => {
+            let error_msg = e.to_string();
+            println!("❌ Erro de conexão tokio-postgres: {}", error_msg);
+            
+            // Fallback para sqlx se tokio-postgres também falhar
+            println!("🔄 Tentando fallback com sqlx...");
+            
+            let url = format!(
+                "postgresql://{}:{}@{}:{}/{}",
+                config.user, config.password, config.host, config.port, config.database
+            );
+            
+            match sqlx::postgres::PgConnection::connect(&url).await {
+                Ok(mut conn) => {
+                    match sqlx::query("SELECT 1").fetch_one(&mut conn).await {
+                        Ok(_) => Ok("✅ Conexão PostgreSQL (sqlx fallback) funcionando!".to_string()),
+                        Err(e) => Err(AppError::DbNotInitialized(format!("❌ Erro no fallback: {}", e)))
+                    }
+                },
+                Err(sqlx_error) => {
+                    // Mensagens amigáveis baseadas nos dois erros
+                    if error_msg.contains("password") || sqlx_error.to_string().contains("password") {
+                        Err(AppError::DbNotInitialized("❌ Falha na autenticação: Verifique usuário e senha".to_string()))
+                    } else if error_msg.contains("database") || sqlx_error.to_string().contains("database") {
+                        Err(AppError::DbNotInitialized(format!("❌ Database '{}' não encontrada", config.database)))
+                    } else if error_msg.contains("Connection refused") || sqlx_error.to_string().contains("Connection refused") {
+                        Err(AppError::NotRunning("❌ PostgreSQL não está rodando na porta especificada".to_string()))
+                    } else if error_msg.contains("role") || sqlx_error.to_string().contains("role") {
+                        Err(AppError::DbNotInitialized(format!("❌ Usuário '{}' não existe", config.user)))
+                    } else {
+                        Err(AppError::DbNotInitialized(format!("❌ Erro de conexão: {} | Fallback: {}", error_msg, sqlx_error)))
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Validar nome de banco (segurança)
+fn validate_database_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Nome do banco não pode estar vazio".to_string());
+    }
+    
+    if name.len() > 63 {
+        return Err("Nome do banco não pode ter mais de 63 caracteres".to_string());
+    }
+    
+    // Apenas letras, números e underscore
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err("Nome do banco pode conter apenas letras, números e underscore".to_string());
+    }
+    
+    // Não pode começar com número
+    if name.chars().next().unwrap().is_ascii_digit() {
+        return Err("Nome do banco não pode começar com número".to_string());
+    }
+    
+    // Palavras reservadas do PostgreSQL
+    let reserved = ["postgres", "template0", "template1", "user", "admin", "root", "system"];
+    if reserved.contains(&name.to_lowercase().as_str()) {
+        return Err("Nome do banco não pode ser uma palavra reservada".to_string());
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_postgres_database(
+    config: PostgresTestConfig,
+    database_name: String,
+    app_handle: tauri::AppHandle,
+) -> Result<String, AppError> {
+    use tokio_postgres::{NoTls, Config};
+    
+    // Validar nome do banco
+    validate_database_name(&database_name).map_err(AppError::ConfigInvalid)?;
+    
+    println!("🔧 Criando banco de dados '{}' no PostgreSQL...", database_name);
+    
+    // Conectar na database padrão 'postgres' para criar nova database
+    let mut pg_config = Config::new();
+    pg_config
+        .host(&config.host)
+        .port(config.port)
+        .user(&config.user)
+        .password(&config.password)
+        .dbname("postgres") // Conecta na DB padrão para criar nova
+        .application_name("plc-hmi");
+    
+    match pg_config.connect(NoTls).await {
+        Ok((client, connection)) => {
+            println!("✅ Conectado ao PostgreSQL para criar banco");
+            
+            let handle = tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    eprintln!("connection error: {}", e);
+                }
+            });
+            
+            // Usar query preparada para segurança (evitar SQL injection)
+            let create_query = format!("CREATE DATABASE \"{}\"", database_name);
+            
+            match client.batch_execute(&create_query).await {
+                Ok(_) => {
+                    println!("✅ Banco '{}' criado com sucesso!", database_name);
+                    handle.abort();
+                    
+                    // Emitir evento de sucesso
+                    let _ = app_handle.emit(
+                        "postgres-database-created",
+                        serde_json::json!({
+                            "host": config.host,
+                            "port": config.port,
+                            "database": database_name,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        })
+                    );
+                    
+                    Ok(format!("Banco de dados '{}' criado com sucesso!", database_name))
+                },
+                Err(e) => {
+                    println!("❌ Erro ao criar banco: {}", e);
+                    handle.abort();
+                    
+                    let error_msg = e.to_string();
+                    if error_msg.contains("already exists") {
+                        Err(AppError::ConfigInvalid(format!("O banco '{}' já existe", database_name)))
+                    } else if error_msg.contains("permission denied") {
+                        Err(AppError::ConfigInvalid("Usuário não tem permissão para criar bancos".to_string()))
+                    } else {
+                        // Emitir evento de erro
+                        let _ = app_handle.emit(
+                            "postgres-database-error",
+                            serde_json::json!({
+                                "operation": "create_database",
+                                "database": database_name,
+                                "error": error_msg,
+                                "timestamp": chrono::Utc::now().to_rfc3339()
+                            })
+                        );
+                        
+                        Err(AppError::Database(format!("Erro ao criar banco: {}", error_msg)))
+                    }
+                }
+            }
+        },
+        Err(e) => {
+            println!("❌ Erro de conexão: {}", e);
+            Err(AppError::DbNotInitialized(format!("Não foi possível conectar ao PostgreSQL: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn list_postgres_databases(
+    config: PostgresTestConfig,
+    _app_handle: tauri::AppHandle,
+) -> Result<Vec<String>, AppError> {
+    use tokio_postgres::{NoTls, Config};
+    
+    println!("📋 Listando bancos de dados no PostgreSQL...");
+    
+    let mut pg_config = Config::new();
+    pg_config
+        .host(&config.host)
+        .port(config.port)
+        .user(&config.user)
+        .password(&config.password)
+        .dbname("postgres")
+        .application_name("plc-hmi");
+    
+    match pg_config.connect(NoTls).await {
+        Ok((client, connection)) => {
+            let handle = tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    eprintln!("connection error: {}", e);
+                }
+            });
+            
+            // Query para listar bancos (excluindo templates)
+            let query = "SELECT datname FROM pg_database WHERE datistemplate = false ORDER BY datname";
+            
+            match client.query(query, &[]).await {
+                Ok(rows) => {
+                    let databases: Vec<String> = rows
+                        .iter()
+                        .map(|row| row.get::<_, String>(0))
+                        .collect();
+                    
+                    println!("✅ Encontrados {} bancos", databases.len());
+                    handle.abort();
+                    
+                    Ok(databases)
+                },
+                Err(e) => {
+                    println!("❌ Erro ao listar bancos: {}", e);
+                    handle.abort();
+                    Err(AppError::Database(format!("Erro ao listar bancos: {}", e)))
+                }
+            }
+        },
+        Err(e) => {
+            println!("❌ Erro de conexão: {}", e);
+            Err(AppError::DbNotInitialized(format!("Não foi possível conectar ao PostgreSQL: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn drop_postgres_database(
+    config: PostgresTestConfig,
+    database_name: String,
+    app_handle: tauri::AppHandle,
+) -> Result<String, AppError> {
+    use tokio_postgres::{NoTls, Config};
+    
+    // Validações de segurança
+    validate_database_name(&database_name).map_err(AppError::ConfigInvalid)?;
+    
+    // Não permitir excluir bancos críticos
+    let protected_dbs = ["postgres", "template0", "template1"];
+    if protected_dbs.contains(&database_name.as_str()) {
+        return Err(AppError::ConfigInvalid("Não é possível excluir bancos do sistema".to_string()));
+    }
+    
+    println!("🗑️ Excluindo banco de dados '{}'...", database_name);
+    
+    let mut pg_config = Config::new();
+    pg_config
+        .host(&config.host)
+        .port(config.port)
+        .user(&config.user)
+        .password(&config.password)
+        .dbname("postgres")
+        .application_name("plc-hmi");
+    
+    match pg_config.connect(NoTls).await {
+        Ok((client, connection)) => {
+            let handle = tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    eprintln!("connection error: {}", e);
+                }
+            });
+            
+            let drop_query = format!("DROP DATABASE \"{}\"", database_name);
+            
+            match client.batch_execute(&drop_query).await {
+                Ok(_) => {
+                    println!("✅ Banco '{}' excluído com sucesso!", database_name);
+                    handle.abort();
+                    
+                    // Emitir evento de sucesso
+                    let _ = app_handle.emit(
+                        "postgres-database-dropped",
+                        serde_json::json!({
+                            "database": database_name,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        })
+                    );
+                    
+                    Ok(format!("Banco de dados '{}' excluído com sucesso!", database_name))
+                },
+                Err(e) => {
+                    println!("❌ Erro ao excluir banco: {}", e);
+                    handle.abort();
+                    
+                    let error_msg = e.to_string();
+                    if error_msg.contains("does not exist") {
+                        Err(AppError::NotFound(format!("O banco '{}' não existe", database_name)))
+                    } else if error_msg.contains("being accessed") {
+                        Err(AppError::ConfigInvalid(format!("O banco '{}' está sendo usado por outras conexões", database_name)))
+                    } else {
+                        Err(AppError::Database(format!("Erro ao excluir banco: {}", error_msg)))
+                    }
+                }
+            }
+        },
+        Err(e) => {
+            println!("❌ Erro de conexão: {}", e);
+            Err(AppError::DbNotInitialized(format!("Não foi possível conectar ao PostgreSQL: {}", e)))
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct DatabaseTable {
+    pub name: String,
+    pub row_count: Option<u64>,
+    pub columns: Vec<DatabaseColumn>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct DatabaseColumn {
+    pub name: String,
+    pub data_type: String,
+    pub is_nullable: bool,
+    pub is_primary_key: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct DatabaseInspection {
+    pub database_name: String,
+    pub tables: Vec<DatabaseTable>,
+    pub total_tables: usize,
+}
+
+#[tauri::command]
+pub async fn inspect_postgres_database(
+    config: PostgresTestConfig,
+    database_name: String,
+    app_handle: tauri::AppHandle,
+) -> Result<DatabaseInspection, AppError> {
+    use tokio_postgres::{NoTls, Config};
+    
+    // Validações de segurança
+    validate_database_name(&database_name).map_err(AppError::ConfigInvalid)?;
+    
+    println!("🔍 Inspecionando estrutura do banco '{}'...", database_name);
+    
+    let mut pg_config = Config::new();
+    pg_config
+        .host(&config.host)
+        .port(config.port)
+        .user(&config.user)
+        .password(&config.password)
+        .dbname(&database_name)
+        .application_name("plc-hmi");
+    
+    match pg_config.connect(NoTls).await {
+        Ok((client, connection)) => {
+            let handle = tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    eprintln!("connection error: {}", e);
+                }
+            });
+            
+            // Query para obter lista de tabelas
+            let tables_query = "
+                SELECT 
+                    table_name
+                FROM information_schema.tables 
+                WHERE table_schema = 'public' 
+                AND table_type = 'BASE TABLE'
+                ORDER BY table_name
+            ";
+            
+            match client.query(tables_query, &[]).await {
+                Ok(table_rows) => {
+                    let mut tables: Vec<DatabaseTable> = Vec::new();
+                    
+                    for table_row in table_rows {
+                        let table_name: String = table_row.get(0);
+                        
+                        // Obter colunas da tabela
+                        let columns_query = "
+                            SELECT 
+                                c.column_name,
+                                c.data_type,
+                                c.is_nullable,
+                                COALESCE(pk.is_primary, false) as is_primary_key
+                            FROM information_schema.columns c
+                            LEFT JOIN (
+                                SELECT 
+                                    kc.column_name,
+                                    true as is_primary
+                                FROM information_schema.table_constraints tc
+                                JOIN information_schema.key_column_usage kc 
+                                    ON tc.constraint_name = kc.constraint_name
+                                    AND tc.table_schema = kc.table_schema
+                                WHERE tc.constraint_type = 'PRIMARY KEY'
+                                AND tc.table_name = $1
+                                AND tc.table_schema = 'public'
+                            ) pk ON c.column_name = pk.column_name
+                            WHERE c.table_name = $1
+                            AND c.table_schema = 'public'
+                            ORDER BY c.ordinal_position
+                        ";
+                        
+                        let mut columns: Vec<DatabaseColumn> = Vec::new();
+                        match client.query(columns_query, &[&table_name]).await {
+                            Ok(column_rows) => {
+                                println!("📊 Tabela '{}': {} colunas encontradas", table_name, column_rows.len());
+                                for column_row in column_rows {
+                                    let column = DatabaseColumn {
+                                        name: column_row.get(0),
+                                        data_type: column_row.get(1),
+                                        is_nullable: column_row.get::<_, String>(2) == "YES",
+                                        is_primary_key: column_row.get(3),
+                                    };
+                                    println!("  📝 Coluna: {} ({}) - PK: {} - NULL: {}", 
+                                        column.name, column.data_type, column.is_primary_key, column.is_nullable);
+                                    columns.push(column);
+                                }
+                            },
+                            Err(e) => {
+                                println!("⚠️ Erro ao obter colunas da tabela {}: {}", table_name, e);
+                            }
+                        }
+                        
+                        // Contar linhas da tabela (com limite para performance)
+                        let count_query = format!("SELECT COUNT(*) FROM \"{}\" LIMIT 1000000", table_name);
+                        let row_count = match client.query(&count_query, &[]).await {
+                            Ok(count_rows) => {
+                                if let Some(row) = count_rows.get(0) {
+                                    Some(row.get::<_, i64>(0) as u64)
+                                } else {
+                                    None
+                                }
+                            },
+                            Err(_) => None,
+                        };
+                        
+                        tables.push(DatabaseTable {
+                            name: table_name,
+                            row_count,
+                            columns,
+                        });
+                    }
+                    
+                    let inspection = DatabaseInspection {
+                        database_name: database_name.clone(),
+                        tables: tables.clone(),
+                        total_tables: tables.len(),
+                    };
+                    
+                    println!("✅ Estrutura do banco '{}' inspecionada: {} tabelas encontradas", database_name, tables.len());
+                    handle.abort();
+                    
+                    // Emitir evento de sucesso
+                    let _ = app_handle.emit(
+                        "postgres-database-inspected",
+                        serde_json::json!({
+                            "database": database_name,
+                            "tables_count": tables.len(),
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        })
+                    );
+                    
+                    Ok(inspection)
+                },
+                Err(e) => {
+                    println!("❌ Erro ao inspecionar banco: {}", e);
+                    handle.abort();
+                    
+                    let error_msg = e.to_string();
+                    if error_msg.contains("does not exist") {
+                        Err(AppError::NotFound(format!("O banco '{}' não existe", database_name)))
+                    } else {
+                        Err(AppError::Database(format!("Erro ao inspecionar banco: {}", error_msg)))
+                    }
+                }
+            }
+        },
+        Err(e) => {
+            println!("❌ Erro de conexão: {}", e);
+            Err(AppError::DbNotInitialized(format!("Não foi possível conectar ao banco '{}': {}", database_name, e)))
+        }
+    }
+}
+
+// ============================================================================
+// COMANDOS PARA PARSER DE LÓGICA - ACESSO DIRETO AO CACHE
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_real_time_tag_values(
+    plc_ip: String,
+    tcp_state: State<'_, TcpServerState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<std::collections::HashMap<String, String>, AppError> {
+    let mut result = std::collections::HashMap::new();
+    
+    // 1. Buscar dados brutos do cache TCP
+    let server_guard = tcp_state.read().await;
+    
+    if let Some(server) = server_guard.as_ref() {
+        // Acessar cache interno do TCP server
+        let latest_data = server.get_plc_data(&plc_ip);
+        
+        if let Some(plc_data) = latest_data.await {
+            println!("📊 Dados TCP para {}: {} variáveis", plc_ip, plc_data.variables.len());
+            
+            // 2. Buscar mapeamentos do banco
+            match db.load_tag_mappings(&plc_ip) {
+                Ok(mappings) => {
+                    println!("🗂️ Mapeamentos carregados: {}", mappings.len());
+                    
+                    // 3. Processar tags ativos
+                    for mapping in mappings.iter().filter(|m| m.enabled) {
+                        // Buscar variável TCP correspondente (Word[0].1, Word[0].4-7... -> procurar Word[0])
+                        let (search_name, selector) = crate::plc_parser::split_variable_path(&mapping.variable_path)
+                            .unwrap_or((mapping.variable_path.as_str(), None));
+                        if let Some(tcp_var) = plc_data.variables.iter().find(|v| v.name == search_name) {
+                            let (final_value, _) = crate::plc_parser::extract_tag_value(selector, tcp_var);
+                            
+                            // 🆕 Escalonamento e conversão de unidade (não para bits)
+                            let final_value = if matches!(selector, Some(crate::plc_parser::PathSelector::Bit(_))) {
+                                final_value
+                            } else {
+                                let scaled = crate::units::apply_tag_scaling(mapping, &final_value);
+                                crate::units::apply_tag_conversion(mapping, &scaled).0
+                            };
+                            
+                            result.insert(mapping.tag_name.clone(), final_value);
+                            println!("✅ Tag processado: {} = {}", mapping.tag_name, result.get(&mapping.tag_name).unwrap());
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("❌ Erro ao carregar mapeamentos: {}", e);
+                    return Err(AppError::Database(format!("Erro ao carregar mapeamentos: {}", e)));
+                }
+            }
+        } else {
+            return Err(AppError::PlcNotFound(format!("Nenhum dado disponível para PLC {}", plc_ip)));
+        }
+    } else {
+        return Err(AppError::NotRunning("Servidor TCP não está rodando".to_string()));
+    }
+    
+    println!("🎯 Total de tags processados: {}", result.len());
+    Ok(result)
+}
+
+// ============================================================================
+// COMANDOS PARA SCL ANALYSIS
+// ============================================================================
+
+// Estrutura para retornar tag com tipo de dado
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SclTagInfo {
+    pub tag_name: String,
+    pub value: String,
+    pub data_type: String,  // "BOOL", "INT", "WORD", "REAL", "DINT", "DWORD"
+    pub variable_path: String,
+}
+
+/// Comando para obter tags com tipo de dado para análise SCL
+/// Retorna informações completas incluindo o data_type inferido
+/// ✅ OTIMIZADO: Usa cache do WebSocket quando disponível, evitando consultas ao banco
+// ✅ OTIMIZAÇÃO: Comando para monitoramento de memória do sistema
+#[tauri::command]
+pub async fn get_system_memory_stats(
+    tcp_state: State<'_, TcpServerState>,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<SystemMemoryStats, AppError> {
+    // Coletar estatísticas do TCP Server
+    let (tcp_buffer_active, tcp_clients, tcp_cache_size) = {
+        let tcp_server_guard = tcp_state.read().await;
+        if let Some(tcp_server) = tcp_server_guard.as_ref() {
+            let stats = tcp_server.get_memory_stats();
+            (stats.0, tcp_server.get_connected_clients_count(), stats.1)
+        } else {
+            (0, 0, 0)
+        }
+    };
+
+    // Coletar estatísticas do WebSocket Server
+    let (ws_cache_size, ws_cache_pct, ws_mappings, ws_tracking, ws_clients) = {
+        let ws_server_guard = websocket_state.read().await;
+        if let Some(ws_server) = ws_server_guard.as_ref() {
+            let cache_stats = ws_server.get_cache_memory_stats();
+            let ws_stats = ws_server.get_stats();
+            (cache_stats.0, cache_stats.3, cache_stats.1, cache_stats.2, ws_stats.active_connections as usize)
+        } else {
+            (0, 0.0, 0, 0, 0)
+        }
+    };
+
+    // Calcular estimativa de memória total (aproximada)
+    let total_memory_kb = (tcp_buffer_active * 8) + // ~8KB por buffer médio
+                         (tcp_cache_size / 1000) +  // Cache TCP ~1KB por entrada
+                         (ws_cache_size / 5) +      // ~200 bytes por tag cached
+                         (ws_mappings * 2) +        // ~2KB por mapping
+                         (ws_tracking / 10);        // ~100 bytes por tracking
+
+    // Determinar status de saúde
+    let health_status = if ws_cache_pct > 90.0 || total_memory_kb > 10240 {
+        "critical".to_string()
+    } else if ws_cache_pct > 75.0 || total_memory_kb > 5120 {
+        "warning".to_string()
+    } else {
+        "healthy".to_string()
+    };
+
+    Ok(SystemMemoryStats {
+        tcp_buffer_pool_active: tcp_buffer_active,
+        tcp_connected_clients: tcp_clients,
+        tcp_data_cache_size: tcp_cache_size,
+        ws_tag_cache_size: ws_cache_size,
+        ws_tag_cache_usage_pct: ws_cache_pct,
+        ws_mappings_cache_size: ws_mappings,
+        ws_change_tracking_size: ws_tracking,
+        ws_connected_clients: ws_clients,
+        total_estimated_memory_kb: total_memory_kb,
+        memory_health_status: health_status,
+        last_cleanup_seconds_ago: 0, // Implementar se necessário
+    })
+}
+
+#[tauri::command]
+pub async fn get_memory_health_report(
+    tcp_state: State<'_, TcpServerState>,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<MemoryHealthReport, AppError> {
+    let memory_stats = get_system_memory_stats(tcp_state, websocket_state).await?;
     
-    // Usar tokio-postgres diretamente para evitar problemas de encoding do sqlx
-    let mut pg_config = Config::new();
-    pg_config
-        .host(&config.host)
-        .port(config.port)
-        .user(&config.user)
-        .password(&config.password)
-        .dbname(&config.database)
-        .application_name("plc-hmi");
+    let mut recommendations = Vec::new();
+    let status = memory_stats.memory_health_status.clone();
     
-    match pg_config.connect(NoTls).await {
-        Ok((client, connection)) => {
-            println!("✅ Conexão tokio-postgres estabelecida!");
+    match status.as_str() {
+        "critical" => {
+            recommendations.push("Memória crítica! Reinicie o sistema se possível".to_string());
+            recommendations.push("Reduza o número de tags monitorados".to_string());
+            recommendations.push("Verifique se há vazamentos de memória".to_string());
+        },
+        "warning" => {
+            recommendations.push("Uso de memória alto. Monitore frequentemente".to_string());
+            recommendations.push("Considere reduzir intervalos de cache".to_string());
+        },
+        _ => {
+            recommendations.push("Sistema operando dentro dos parâmetros normais".to_string());
+        }
+    }
+
+    if memory_stats.ws_tag_cache_usage_pct > 80.0 {
+        recommendations.push("Cache de tags próximo do limite. Limpeza automática ativa".to_string());
+    }
+
+    Ok(MemoryHealthReport {
+        status,
+        memory_stats,
+        recommendations,
+        auto_cleanup_enabled: true, // Sempre ativo com as otimizações
+        instance: crate::config::current_instance(),
+    })
+}
+
+/// 🆕 Última medição de recursos do próprio servidor (mesmos valores dos tags HMI_SELF)
+#[tauri::command]
+pub async fn get_resource_usage() -> Result<crate::self_monitor::ResourceUsage, AppError> {
+    crate::self_monitor::latest()
+        .ok_or_else(|| AppError::NotRunning("Automonitoramento ainda sem medição".to_string()))
+}
+
+#[tauri::command]
+pub async fn force_memory_cleanup(
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<String, AppError> {
+    let ws_server_guard = websocket_state.read().await;
+    if let Some(ws_server) = ws_server_guard.as_ref() {
+        let cleaned = ws_server.force_cache_cleanup().await;
+        if cleaned {
+            Ok("Limpeza de memória executada com sucesso".to_string())
+        } else {
+            Ok("Limpeza não necessária - memória dentro dos limites".to_string())
+        }
+    } else {
+        Err(AppError::NotRunning("WebSocket server não está ativo".to_string()))
+    }
+}
+
+// ✅ MELHORIA: Comando para cliente se inscrever em PLCs específicos
+#[tauri::command]
+pub async fn subscribe_client_to_plcs(
+    client_id: u64,
+    plc_ips: Vec<String>,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<String, AppError> {
+    let ws_server_guard = websocket_state.read().await;
+    if let Some(ws_server) = ws_server_guard.as_ref() {
+        ws_server.subscribe_to_plcs(client_id, plc_ips.clone()).await?;
+        Ok(format!("Cliente {} inscrito em PLCs: {:?}", client_id, plc_ips))
+    } else {
+        Err(AppError::NotRunning("WebSocket server não está ativo".to_string()))
+    }
+}
+
+// ✅ MELHORIA: Comando para listar PLCs disponíveis
+#[tauri::command]
+pub async fn get_available_plcs(
+    tcp_state: State<'_, TcpServerState>,
+) -> Result<Vec<String>, AppError> {
+    let tcp_server_guard = tcp_state.read().await;
+    if let Some(tcp_server) = tcp_server_guard.as_ref() {
+        let connected_plcs = tcp_server.get_all_plc_data().await;
+        Ok(connected_plcs.keys().cloned().collect())
+    } else {
+        Ok(vec![]) // Retorna lista vazia se TCP não ativo
+    }
+}
+
+#[tauri::command]
+pub async fn get_scl_tags(
+    plc_ip: String,
+    tcp_state: State<'_, TcpServerState>,
+    websocket_state: State<'_, WebSocketServerState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<SclTagInfo>, AppError> {
+    let mut result = Vec::new();
+    
+    // 1. Buscar dados brutos do cache TCP
+    let server_guard = tcp_state.read().await;
+    
+    if let Some(server) = server_guard.as_ref() {
+        let latest_data = server.get_plc_data(&plc_ip);
+        
+        if let Some(plc_data) = latest_data.await {
+            println!("🔍 SCL: Dados TCP para {}: {} variáveis", plc_ip, plc_data.variables.len());
             
-            // Spawnar a conexão em background
-            let handle = tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("connection error: {}", e);
+            // 2. Tentar buscar mapeamentos do CACHE do WebSocket primeiro
+            let mappings = {
+                let ws_guard = websocket_state.read().await;
+                if let Some(ws_server) = ws_guard.as_ref() {
+                    // Tentar obter do cache interno do WebSocket
+                    ws_server.get_cached_tag_mappings(&plc_ip).await
+                } else {
+                    None
                 }
-            });
+            };
             
-            // Testar uma query simples
-            match client.query("SELECT 1 as test", &[]).await {
-                Ok(rows) => {
-                    println!("✅ Query executada! Resultado: {} linhas", rows.len());
-                    handle.abort(); // Limpar conexão
-                    
-                    // Emitir evento de teste bem-sucedido
-                    let _ = app_handle.emit(
-                        "postgres-connection-success",
-                        serde_json::json!({
-                            "host": config.host,
-                            "port": config.port,
-                            "user": config.user,
-                            "database": config.database,
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        })
-                    );
-                    
-                    Ok("✅ Conexão PostgreSQL funcionando perfeitamente!".to_string())
-                },
-                Err(e) => {
-                    println!("❌ Erro na query: {}", e);
-                    handle.abort();
-                    
-                    // Emitir evento de erro na query
-                    let _ = app_handle.emit(
-                        "postgres-connection-error",
-                        serde_json::json!({
-                            "host": config.host,
-                            "port": config.port,
-                            "operation": "test_query",
-                            "error": format!("{}", e),
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        })
-                    );
-                    
-                    Err(format!("❌ Conexão OK mas erro na query: {}", e))
+            // Se cache não disponível, buscar do banco (fallback)
+            let mappings = match mappings {
+                Some(cached) => {
+                    println!("⚡ SCL: {} mapeamentos do CACHE (zero I/O!)", cached.len());
+                    cached
                 }
-            }
-        },
-        Err(e) => {
-            let error_msg = e.to_string();
-            println!("❌ Erro de conexão tokio-postgres: {}", error_msg);
-            
-            // Fallback para sqlx se tokio-postgres também falhar
-            println!("🔄 Tentando fallback com sqlx...");
-            
-            let url = format!(
-                "postgresql://{}:{}@{}:{}/{}",
-                config.user, config.password, config.host, config.port, config.database
-            );
-            
-            match sqlx::postgres::PgConnection::connect(&url).await {
-                Ok(mut conn) => {
-                    match sqlx::query("SELECT 1").fetch_one(&mut conn).await {
-                        Ok(_) => Ok("✅ Conexão PostgreSQL (sqlx fallback) funcionando!".to_string()),
-                        Err(e) => Err(format!("❌ Erro no fallback: {}", e))
-                    }
-                },
-                Err(sqlx_error) => {
-                    // Mensagens amigáveis baseadas nos dois erros
-                    if error_msg.contains("password") || sqlx_error.to_string().contains("password") {
-                        Err("❌ Falha na autenticação: Verifique usuário e senha".to_string())
-                    } else if error_msg.contains("database") || sqlx_error.to_string().contains("database") {
-                        Err(format!("❌ Database '{}' não encontrada", config.database))
-                    } else if error_msg.contains("Connection refused") || sqlx_error.to_string().contains("Connection refused") {
-                        Err("❌ PostgreSQL não está rodando na porta especificada".to_string())
-                    } else if error_msg.contains("role") || sqlx_error.to_string().contains("role") {
-                        Err(format!("❌ Usuário '{}' não existe", config.user))
-                    } else {
-                        Err(format!("❌ Erro de conexão: {} | Fallback: {}", error_msg, sqlx_error))
+                None => {
+                    println!("⚠️ SCL: Cache não disponível, buscando do banco...");
+                    match db.load_tag_mappings(&plc_ip) {
+                        Ok(m) => {
+                            println!("📂 SCL: {} mapeamentos carregados do banco", m.len());
+                            m
+                        }
+                        Err(e) => {
+                            return Err(AppError::Database(format!("Erro ao carregar mapeamentos: {}", e)));
+                        }
                     }
                 }
+            };
+            
+            // 3. Processar tags ativos
+            for mapping in mappings.iter().filter(|m| m.enabled) {
+                // Nome base para buscar no TCP (Word[0].3, Word[0].4-7, DWord[1].B0...)
+                let (search_name, selector) = crate::plc_parser::split_variable_path(&mapping.variable_path)
+                    .unwrap_or((mapping.variable_path.as_str(), None));
+                
+                // Buscar variável TCP correspondente
+                if let Some(tcp_var) = plc_data.variables.iter().find(|v| v.name == search_name) {
+                    // Determinar valor e tipo (bit → BOOL, faixa → UINT, byte → BYTE...)
+                    let (final_value, data_type) = crate::plc_parser::extract_tag_value(selector, tcp_var);
+                    
+                    result.push(SclTagInfo {
+                        tag_name: mapping.tag_name.clone(),
+                        value: final_value,
+                        data_type,
+                        variable_path: mapping.variable_path.clone(),
+                    });
+                }
+            }
+        } else {
+            return Err(AppError::PlcNotFound(format!("Nenhum dado disponível para PLC {}", plc_ip)));
+        }
+    } else {
+        return Err(AppError::NotRunning("Servidor TCP não está rodando".to_string()));
+    }
+    
+    println!("🎯 SCL: Total de {} tags processados", result.len());
+    Ok(result)
+}
+
+// ============================================================================
+// COMANDOS DE LEITURA/ESCRITA DE ARQUIVOS
+// ============================================================================
+
+#[tauri::command]
+pub async fn write_file(path: String, content: String) -> Result<(), AppError> {
+    std::fs::write(&path, &content)
+        .map_err(|e| AppError::Io(format!("Erro ao escrever arquivo: {}", e)))
+}
+
+#[tauri::command]
+pub async fn read_file(path: String) -> Result<String, AppError> {
+    std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Io(format!("Erro ao ler arquivo: {}", e)))
+}
+
+// ============================================================================
+// CONVERSÃO DE UNIDADES
+// ============================================================================
+
+/// Unidades para as quais a unidade informada pode ser convertida
+#[tauri::command]
+pub async fn list_unit_conversions(unit: String) -> Result<Vec<String>, AppError> {
+    Ok(crate::units::compatible_units(&unit))
+}
+
+/// 🆕 Valor atual de um tag como é publicado (convertido, com as casas decimais do tag)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FormattedTagValue {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub value: String,
+    pub unit: Option<String>,
+    pub formatted: String,      // Ex: "3.10 bar"
+    pub timestamp_ms: i64,
+}
+
+#[tauri::command]
+pub async fn get_formatted_tag(
+    plc_ip: String,
+    tag: String,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<FormattedTagValue, AppError> {
+    let cache = websocket_state.read().await.as_ref().map(|s| s.smart_cache())
+        .ok_or_else(|| AppError::NotRunning("WebSocket server não está rodando".to_string()))?;
+    let cached = cache.snapshot(Some(&plc_ip)).into_iter()
+        .find(|c| c.tag_name == tag)
+        .ok_or_else(|| AppError::NotFound(format!("Tag {} sem valor no PLC {}", tag, plc_ip)))?;
+    Ok(FormattedTagValue {
+        formatted: crate::units::format_with_unit(&cached.value, cached.unit.as_deref()),
+        plc_ip: cached.plc_ip,
+        tag_name: cached.tag_name,
+        value: cached.value,
+        unit: cached.unit,
+        timestamp_ms: (cached.timestamp_ns / 1_000_000) as i64,
+    })
+}
+
+// ============================================================================
+// VALIDAÇÃO DA CONFIGURAÇÃO
+// ============================================================================
+
+/// Verifica tags contra as estruturas dos PLCs e retorna a lista de problemas
+#[tauri::command]
+pub async fn validate_configuration(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<ConfigIssue>, AppError> {
+    crate::validation::validate_configuration(&db).map_err(AppError::from)
+}
+
+/// Relatório de quais variáveis/bytes do frame são usados por tags (layout padrão ou perfil)
+#[tauri::command]
+pub async fn get_mapping_coverage(
+    plc_ip: String,
+    profile: Option<String>,
+    db: State<'_, Arc<Database>>,
+) -> Result<MappingCoverageReport, AppError> {
+    crate::validation::mapping_coverage(&db, &plc_ip, profile.as_deref()).map_err(AppError::from)
+}
+
+// ============================================================================
+// 🆕 DEPENDÊNCIAS E IMPACTO DE EXCLUSÃO
+// ============================================================================
+
+/// Grafo de dependências de toda a configuração (blocos, tags, derivados, logger, chaves, prioridades)
+#[tauri::command]
+pub async fn get_tag_dependency_graph(
+    db: State<'_, Arc<Database>>,
+) -> Result<DependencyGraph, AppError> {
+    crate::impact::build_graph(&db).map_err(AppError::from)
+}
+
+/// O que depende de um tag antes de excluí-lo (`include_history` consulta o PostgreSQL)
+#[tauri::command]
+pub async fn analyze_tag_delete_impact(
+    plc_ip: String,
+    tag_name: String,
+    include_history: Option<bool>,
+    db: State<'_, Arc<Database>>,
+) -> Result<ImpactReport, AppError> {
+    let mut report = crate::impact::analyze_tag(&db, &plc_ip, &tag_name)?;
+    if include_history.unwrap_or(true) {
+        add_history_impact(&db, &mut report).await?;
+    }
+    Ok(report)
+}
+
+/// O que depende de um bloco da estrutura antes de removê-lo
+#[tauri::command]
+pub async fn analyze_block_delete_impact(
+    plc_ip: String,
+    block_name: String,
+    include_history: Option<bool>,
+    db: State<'_, Arc<Database>>,
+) -> Result<ImpactReport, AppError> {
+    let mut report = crate::impact::analyze_block(&db, &plc_ip, &block_name)?;
+    if include_history.unwrap_or(true) {
+        add_history_impact(&db, &mut report).await?;
+    }
+    Ok(report)
+}
+
+/// Conta o histórico dos tags afetados; historian fora do ar vira aviso, não erro
+async fn add_history_impact(db: &Database, report: &mut ImpactReport) -> Result<(), String> {
+    let Some(pg_config) = db.load_postgres_config()
+        .map_err(|e| format!("Erro ao carregar configuração PostgreSQL: {}", e))? else {
+        return Ok(());
+    };
+    if report.affected_tags.is_empty() {
+        report.history_rows = Some(0);
+        return Ok(());
+    }
+    let counts = match PgDatabase::connect(&historian::postgres_url(&pg_config)).await {
+        Ok(pg) => historian::count_tag_history(&pg.pool, &report.plc_ip, &report.affected_tags).await
+            .map_err(|e| format!("Erro ao consultar histórico: {}", e)),
+        Err(e) => Err(format!("Erro ao conectar no historian: {}", e)),
+    };
+    match counts {
+        Ok(counts) => {
+            let total: i64 = counts.values().sum();
+            report.history_rows = Some(total);
+            report.history_by_tag = counts.into_iter().collect();
+            if total > 0 {
+                report.safe_to_delete = false;
             }
         }
+        Err(e) => report.history_error = Some(e),
+    }
+    Ok(())
+}
+
+// ============================================================================
+// COMANDOS DO HISTORIAN
+// ============================================================================
+
+/// Compara os valores históricos de todos os tags em dois instantes (ms Unix)
+#[tauri::command]
+pub async fn compare_snapshots(
+    t1: i64,
+    t2: i64,
+    db: State<'_, Arc<Database>>,
+    failover: State<'_, Arc<crate::historian_failover::HistorianFailover>>,
+) -> Result<SnapshotComparison, AppError> {
+    // 🆕 Mesmo pool da gravação do historian (sem abrir conexões a cada comparação)
+    let pool = failover.primary_pool().await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    let mut before = historian::fetch_snapshot(&pool, t1).await
+        .map_err(|e| AppError::Database(format!("Erro ao ler snapshot t1: {}", e)))?;
+    let mut after = historian::fetch_snapshot(&pool, t2).await
+        .map_err(|e| AppError::Database(format!("Erro ao ler snapshot t2: {}", e)))?;
+    // 🆕 Mudança de unidade entre t1 e t2 não conta como mudança de valor
+    let versions = db.list_tag_unit_versions(None, None)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar versões da unidade: {}", e)))?;
+    crate::units::apply_unit_versions(&mut before, &versions);
+    crate::units::apply_unit_versions(&mut after, &versions);
+
+    let comparison = historian::diff_snapshots(t1, t2, before, after);
+    println!("📊 Snapshot diff {} → {}: {}/{} tags alterados",
+             t1, t2, comparison.changed_count, comparison.total_tags);
+    Ok(comparison)
+}
+
+/// Lacunas no historian (ex: HMI fora do ar) - candidatas a backfill pelo PLC
+#[tauri::command]
+pub async fn detect_history_gaps(
+    from_ms: i64,
+    to_ms: i64,
+    plc_ip: Option<String>,
+    min_gap_ms: Option<i64>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<HistoryGap>, AppError> {
+    if to_ms <= from_ms {
+        return Err(AppError::ConfigInvalid("Janela inválida: fim deve ser maior que início".to_string()));
+    }
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?
+        .ok_or_else(|| AppError::DbNotInitialized("PostgreSQL não configurado".to_string()))?;
+    let pg = PgDatabase::connect(&historian::postgres_url(&pg_config)).await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    let gaps = historian::detect_gaps(&pg.pool, plc_ip.as_deref(), from_ms, to_ms, min_gap_ms.unwrap_or(60_000)).await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar lacunas: {}", e)))?;
+    println!("🕳️ Historian: {} lacuna(s) entre {} e {}", gaps.len(), from_ms, to_ms);
+    Ok(gaps)
+}
+
+/// 🆕 Formas de onda gravadas de um tag (sem `with_points` só os metadados)
+#[tauri::command]
+pub async fn get_waveform_history(
+    plc_ip: String,
+    tag_name: String,
+    from_ms: i64,
+    to_ms: i64,
+    limit: Option<i64>,
+    with_points: Option<bool>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<WaveformSample>, AppError> {
+    if to_ms <= from_ms {
+        return Err(AppError::ConfigInvalid("Janela inválida: fim deve ser maior que início".to_string()));
+    }
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?
+        .ok_or_else(|| AppError::DbNotInitialized("PostgreSQL não configurado".to_string()))?;
+    let pg = PgDatabase::connect(&historian::postgres_url(&pg_config)).await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    historian::fetch_waveforms(&pg.pool, &plc_ip, &tag_name, from_ms, to_ms, limit.unwrap_or(100).clamp(1, 1_000), with_points.unwrap_or(true)).await
+        .map_err(|e| AppError::Database(format!("Erro ao buscar formas de onda: {}", e)))
+}
+
+/// 🆕 Última forma de onda recebida de um tag (cache do WebSocket)
+#[tauri::command]
+pub async fn get_live_waveform(
+    plc_ip: Option<String>,
+    tag_name: String,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<WaveformSample, AppError> {
+    let smart_cache = websocket_state.read().await.as_ref().map(|s| s.smart_cache())
+        .ok_or_else(|| AppError::NotRunning("WebSocket não está rodando".to_string()))?;
+    let cached = smart_cache.get_waveform(plc_ip.as_deref(), &tag_name)
+        .ok_or_else(|| AppError::NotFound(format!("Forma de onda '{}' não encontrada", tag_name)))?;
+    let points = crate::plc_parser::waveform_points(&cached.value)
+        .ok_or_else(|| AppError::ConfigInvalid(format!("Valor inválido na forma de onda '{}'", tag_name)))?;
+    Ok(WaveformSample {
+        plc_ip: cached.plc_ip,
+        tag_name: cached.tag_name,
+        data_type: cached.data_type.trim_end_matches(crate::plc_parser::WAVEFORM_TYPE_SUFFIX).to_string(),
+        unit: cached.unit,
+        ts_ms: (cached.timestamp_ns / 1_000_000) as i64,
+        point_count: points.len() as i32,
+        points,
+    })
+}
+
+// ============================================================================
+// COMANDOS DE PLAYBACK HISTÓRICO
+// ============================================================================
+
+fn validate_playback_speed(speed: f64) -> Result<f64, String> {
+    if speed > 0.0 && speed <= MAX_PLAYBACK_SPEED {
+        Ok(speed)
+    } else {
+        Err(format!("Velocidade inválida: {} (deve estar entre 0 e {})", speed, MAX_PLAYBACK_SPEED))
+    }
+}
+
+/// Inicia o playback (pausado) de uma janela do historian pelo WebSocket
+#[tauri::command]
+pub async fn start_playback(
+    from_ms: i64,
+    to_ms: i64,
+    speed: Option<f64>,
+    plc_ip: Option<String>,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    playback_state: State<'_, PlaybackState>,
+    app_handle: AppHandle,
+) -> Result<PlaybackStatus, AppError> {
+    if to_ms <= from_ms {
+        return Err(AppError::ConfigInvalid("Janela de playback inválida: fim deve ser maior que início".to_string()));
+    }
+    let speed = validate_playback_speed(speed.unwrap_or(1.0)).map_err(AppError::ConfigInvalid)?;
+
+    let smart_cache = {
+        let ws_guard = websocket_state.read().await;
+        match ws_guard.as_ref() {
+            Some(server) => server.smart_cache(),
+            None => return Err(AppError::NotRunning("WebSocket server não está rodando".to_string())),
+        }
+    };
+
+    let pg_config = db.load_postgres_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração PostgreSQL: {}", e)))?
+        .ok_or_else(|| AppError::DbNotInitialized("PostgreSQL não configurado".to_string()))?;
+    let pg = PgDatabase::connect(&historian::postgres_url(&pg_config)).await
+        .map_err(|e| AppError::DbNotInitialized(format!("Erro ao conectar no historian: {}", e)))?;
+
+    let mut guard = playback_state.write().await;
+    if let Some(previous) = guard.take() {
+        previous.stop().await;
+    }
+
+    let controller = PlaybackController::start(app_handle, pg.pool, smart_cache, from_ms, to_ms, speed, plc_ip);
+    let status = controller.status().await;
+    *guard = Some(controller);
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn playback_play(
+    playback_state: State<'_, PlaybackState>,
+) -> Result<(), AppError> {
+    match playback_state.read().await.as_ref() {
+        Some(controller) => controller.play().map_err(AppError::from),
+        None => Err(AppError::NotRunning("Nenhum playback ativo".to_string())),
+    }
+}
+
+#[tauri::command]
+pub async fn playback_pause(
+    playback_state: State<'_, PlaybackState>,
+) -> Result<(), AppError> {
+    match playback_state.read().await.as_ref() {
+        Some(controller) => controller.pause().map_err(AppError::from),
+        None => Err(AppError::NotRunning("Nenhum playback ativo".to_string())),
+    }
+}
+
+#[tauri::command]
+pub async fn playback_seek(
+    position_ms: i64,
+    playback_state: State<'_, PlaybackState>,
+) -> Result<(), AppError> {
+    match playback_state.read().await.as_ref() {
+        Some(controller) => controller.seek(position_ms).map_err(AppError::from),
+        None => Err(AppError::NotRunning("Nenhum playback ativo".to_string())),
+    }
+}
+
+#[tauri::command]
+pub async fn playback_set_speed(
+    speed: f64,
+    playback_state: State<'_, PlaybackState>,
+) -> Result<(), AppError> {
+    let speed = validate_playback_speed(speed).map_err(AppError::ConfigInvalid)?;
+    match playback_state.read().await.as_ref() {
+        Some(controller) => controller.set_speed(speed).map_err(AppError::from),
+        None => Err(AppError::NotRunning("Nenhum playback ativo".to_string())),
+    }
+}
+
+#[tauri::command]
+pub async fn get_playback_status(
+    playback_state: State<'_, PlaybackState>,
+) -> Result<Option<PlaybackStatus>, AppError> {
+    match playback_state.read().await.as_ref() {
+        Some(controller) => Ok(Some(controller.status().await)),
+        None => Ok(None),
+    }
+}
+
+/// Encerra o playback e volta aos dados ao vivo
+#[tauri::command]
+pub async fn stop_playback(
+    playback_state: State<'_, PlaybackState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    match playback_state.write().await.take() {
+        Some(controller) => {
+            controller.stop().await;
+            let _ = app_handle.emit("playback-stopped", serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            Ok("Playback encerrado".to_string())
+        }
+        None => Err(AppError::NotRunning("Nenhum playback ativo".to_string())),
+    }
+}
+
+// ============================================================================
+// CENTRAL DE NOTIFICAÇÕES
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_notifications(
+    unread_only: Option<bool>,
+    limit: Option<u32>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<Notification>, AppError> {
+    db.list_notifications(unread_only.unwrap_or(false), limit.unwrap_or(200))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar notificações: {}", e)))
+}
+
+#[tauri::command]
+pub async fn count_unread_notifications(
+    db: State<'_, Arc<Database>>,
+) -> Result<i64, AppError> {
+    db.count_unread_notifications()
+        .map_err(|e| AppError::Database(format!("Erro ao contar notificações: {}", e)))
+}
+
+/// Marca como lidas as notificações informadas (sem `ids` marca todas)
+#[tauri::command]
+pub async fn mark_notifications_read(
+    ids: Option<Vec<i64>>,
+    db: State<'_, Arc<Database>>,
+) -> Result<usize, AppError> {
+    db.mark_notifications_read(ids)
+        .map_err(|e| AppError::Database(format!("Erro ao marcar notificações: {}", e)))
+}
+
+#[tauri::command]
+pub async fn clear_notifications(
+    only_read: Option<bool>,
+    db: State<'_, Arc<Database>>,
+) -> Result<usize, AppError> {
+    db.clear_notifications(only_read.unwrap_or(false))
+        .map_err(|e| AppError::Database(format!("Erro ao limpar notificações: {}", e)))
+}
+
+/// KPIs de alarme do período ("1h", "8h", "24h", "7d", "30d" ou from_s/to_s em segundos Unix)
+#[tauri::command]
+pub async fn get_alarm_kpis(
+    period: Option<String>,
+    from_s: Option<i64>,
+    to_s: Option<i64>,
+    standing_threshold_s: Option<i64>,
+    db: State<'_, Arc<Database>>,
+) -> Result<AlarmKpis, AppError> {
+    let now_s = chrono::Utc::now().timestamp();
+    let (from_s, to_s) = alarm_kpis::period_bounds(period.as_deref(), from_s, to_s, now_s)?;
+    let kpis = alarm_kpis::compute(&db, from_s, to_s, standing_threshold_s.unwrap_or(24 * 3_600), now_s)?;
+    println!("📊 KPIs de alarme: {} alarmes ({:.1}/h, {}), {} em aberto",
+             kpis.total_alarms, kpis.alarms_per_hour, kpis.rating, kpis.standing_alarms.len());
+    Ok(kpis)
+}
+
+// ============================================================================
+// 🆕 DISPONIBILIDADE DOS PLCs (ver availability.rs)
+// ============================================================================
+
+/// KPIs de disponibilidade entre `from_day` e `to_day` ("AAAA-MM-DD", inclusive),
+/// agrupados por "day", "week", "month" ou "total"
+#[tauri::command]
+pub async fn get_plc_availability(
+    plc_ip: Option<String>,
+    from_day: String,
+    to_day: String,
+    granularity: Option<String>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::availability::AvailabilityKpis>, AppError> {
+    let granularity = granularity.unwrap_or_else(|| "day".to_string());
+    crate::availability::validate_granularity(&granularity).map_err(AppError::ConfigInvalid)?;
+    if to_day < from_day {
+        return Err(AppError::ConfigInvalid("Período inválido: fim antes do início".to_string()));
+    }
+    // Dia atual sempre atualizado na consulta (o job consolida a cada 15 min)
+    let database = db.inner().clone();
+    tokio::task::spawn_blocking(move || crate::availability::rollup(&database)).await
+        .map_err(|e| format!("Task de disponibilidade falhou: {}", e))??;
+
+    let days = db.list_availability_days(plc_ip.as_deref(), &from_day, &to_day)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar disponibilidade: {}", e)))?;
+    Ok(crate::availability::aggregate(&days, &granularity))
+}
+
+/// Eventos de conexão brutos (quedas, reconexões, início/parada do servidor)
+#[tauri::command]
+pub async fn list_plc_connection_events(
+    plc_ip: Option<String>,
+    from_ms: i64,
+    to_ms: i64,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<crate::database::PlcConnectionEvent>, AppError> {
+    db.list_connection_events(plc_ip.as_deref(), from_ms, to_ms)
+        .map_err(|e| AppError::Database(format!("Erro ao carregar eventos de conexão: {}", e)))
+}
+
+// ============================================================================
+// MOTOR DE ALARMES
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_alarm_definitions(
+    plc_ip: Option<String>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<AlarmDefinition>, AppError> {
+    db.list_alarm_definitions(plc_ip.as_deref())
+        .map_err(|e| AppError::Database(format!("Erro ao carregar alarmes: {}", e)))
+}
+
+/// Cria (id = 0) ou atualiza uma definição de alarme
+#[tauri::command]
+pub async fn save_alarm_definition(
+    definition: AlarmDefinition,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    crate::alarm_engine::validate_definition(&definition).map_err(AppError::ConfigInvalid)?;
+    let id = db.save_alarm_definition(&definition)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => format!("Alarme #{} não encontrado", definition.id),
+            e => format!("Erro ao salvar alarme: {}", e),
+        })?;
+    crate::alarm_engine::request_reload();
+    println!("🚨 Alarme '{}' salvo (#{}): {} {} {} [{}]", definition.name, id, definition.tag_name, definition.comparison, definition.threshold, definition.severity);
+    Ok(t("alarm.saved", &[("name", definition.name), ("id", id.to_string())]))
+}
+
+/// Remove a definição; ocorrência ativa normaliza na próxima avaliação
+#[tauri::command]
+pub async fn delete_alarm_definition(
+    id: i64,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    let deleted = db.delete_alarm_definition(id)
+        .map_err(|e| AppError::Database(format!("Erro ao remover alarme: {}", e)))?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Alarme #{} não encontrado", id)));
+    }
+    crate::alarm_engine::request_reload();
+    Ok(t("alarm.deleted", &[("id", id.to_string())]))
+}
+
+/// Alarmes em aberto: ativos ou normalizados sem reconhecimento
+#[tauri::command]
+pub async fn get_active_alarms(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<AlarmOccurrence>, AppError> {
+    db.list_active_alarms()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar alarmes ativos: {}", e)))
+}
+
+#[tauri::command]
+pub async fn ack_alarm(
+    occurrence_id: i64,
+    user: Option<String>,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let occurrence = db.ack_alarm_occurrence(occurrence_id, user.as_deref())
+        .map_err(|e| AppError::Database(format!("Erro ao reconhecer alarme: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Alarme #{} não encontrado ou já reconhecido", occurrence_id)))?;
+    println!("👍 Alarme '{}' reconhecido (ocorrência #{}{})", occurrence.name, occurrence.id,
+        occurrence.acked_by.as_deref().map(|u| format!(" por {}", u)).unwrap_or_default());
+    let _ = app_handle.emit("alarm-acknowledged", &occurrence);
+    Ok(t("alarm.acked", &[("name", occurrence.name)]))
+}
+
+/// Ocorrências disparadas no intervalo (padrão: últimas 24h)
+#[tauri::command]
+pub async fn get_alarm_history(
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+    plc_ip: Option<String>,
+    limit: Option<u32>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<AlarmOccurrence>, AppError> {
+    let to_ms = to_ms.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let from_ms = from_ms.unwrap_or(to_ms - 24 * 3_600_000);
+    if to_ms <= from_ms {
+        return Err(AppError::ConfigInvalid("Período inválido: fim deve ser maior que início".to_string()));
+    }
+    db.list_alarm_history(from_ms, to_ms, plc_ip.as_deref(), limit.unwrap_or(1_000))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar histórico de alarmes: {}", e)))
+}
+
+// ============================================================================
+// INCIDENTES CAPTURADOS (logs + frames ao redor de quedas e alarmes críticos)
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_incidents(
+    limit: Option<u32>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<IncidentRecord>, AppError> {
+    db.list_incidents(limit.unwrap_or(100))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar incidentes: {}", e)))
+}
+
+/// Incidente com o log e o dump dos frames gravados em arquivo
+#[tauri::command]
+pub async fn get_incident(
+    id: i64,
+    db: State<'_, Arc<Database>>,
+) -> Result<IncidentDetail, AppError> {
+    incident_capture::load_incident(&db, id).map_err(AppError::from)
+}
+
+// ============================================================================
+// BACKUPS AUTOMÁTICOS DA CONFIGURAÇÃO
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_config_backups(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<BackupInfo>, AppError> {
+    Ok(crate::backup::list_backups(&db))
+}
+
+#[tauri::command]
+pub async fn get_backup_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::database::BackupConfig, AppError> {
+    db.load_backup_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração de backup: {}", e)))
+}
+
+/// Salva quantos backups manter e já remove os excedentes
+#[tauri::command]
+pub async fn save_backup_config(
+    mut config: crate::database::BackupConfig,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    crate::backup::validate_config(&config).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_backup_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração de backup: {}", e)))?;
+    crate::backup::apply_retention(&db);
+    Ok(format!("Serão mantidos os {} backups mais recentes", config.retention))
+}
+
+/// Confere o checksum de um backup (a listagem não re-hasheia os arquivos)
+#[tauri::command]
+pub async fn verify_config_backup(
+    file_name: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<BackupInfo, AppError> {
+    crate::backup::verify_backup(&db, &file_name).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn create_config_backup(
+    db: State<'_, Arc<Database>>,
+) -> Result<BackupInfo, AppError> {
+    crate::backup::create_backup(&db).map_err(AppError::from)
+}
+
+/// Restaura a configuração de um backup e recarrega TCP/WebSocket com os novos dados
+#[tauri::command]
+pub async fn restore_config_backup(
+    file_name: String,
+    db: State<'_, Arc<Database>>,
+    tcp_state: State<'_, TcpServerState>,
+    websocket_state: State<'_, WebSocketServerState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let rows = crate::backup::restore_backup(&db, &file_name)?;
+    crate::alarm_engine::request_reload();
+
+    if let Some(server) = tcp_state.read().await.as_ref() {
+        for plc_ip in db.list_configured_plcs().unwrap_or_default() {
+            server.reload_plc_config(&plc_ip);
+        }
+    }
+    let _ = reload_websocket_tag_groups(websocket_state).await;
+
+    let _ = app_handle.emit("config-restored", serde_json::json!({
+        "file_name": file_name,
+        "rows": rows,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    Ok(format!("Configuração restaurada de {} ({} registros)", file_name, rows))
+}
+
+// ============================================================================
+// COMANDOS DE REDUNDÂNCIA (CHECKSUM DE CONFIGURAÇÃO)
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_config_checksum(
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    db.compute_config_checksum()
+        .map_err(|e| AppError::Database(format!("Erro ao calcular checksum: {}", e)))
+}
+
+/// Recebe o heartbeat do HMI par e compara os checksums de configuração
+#[tauri::command]
+pub async fn report_peer_heartbeat(
+    peer_id: String,
+    peer_checksum: String,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<ConfigDriftReport, AppError> {
+    crate::redundancy::check_peer(&app_handle, &db, &peer_id, &peer_checksum).map_err(AppError::from)
+}
+
+// 🆕 Par primário/standby: heartbeat por TCP e failover do standby
+
+#[tauri::command]
+pub async fn get_redundancy_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<crate::database::RedundancyConfig, AppError> {
+    db.load_redundancy_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração de redundância: {}", e)))
+}
+
+/// Aplicada no próximo ciclo do heartbeat (até 5s)
+#[tauri::command]
+pub async fn save_redundancy_config(
+    mut config: crate::database::RedundancyConfig,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    config.peer_address = config.peer_address.trim().to_string();
+    crate::redundancy::validate_config(&config).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_redundancy_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração de redundância: {}", e)))?;
+    Ok(match config.enabled {
+        true => format!("Redundância ativa: {} com o par {} (timeout {}s)", config.role, config.peer_address, config.peer_timeout_s),
+        false => "Redundância desabilitada".to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_redundancy_status(
+    status: State<'_, crate::redundancy::RedundancyState>,
+) -> Result<crate::redundancy::RedundancyStatus, AppError> {
+    Ok(status.read().await.clone())
+}
+
+
+// ============================================================================
+// API GRAPHQL (OPCIONAL)
+// ============================================================================
+
+/// Inicia o endpoint GraphQL (requer build com a feature "graphql")
+#[tauri::command]
+pub async fn start_graphql_server(
+    host: Option<String>,
+    port: Option<u16>,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    graphql_state: State<'_, GraphqlServerState>,
+) -> Result<String, AppError> {
+    let mut graphql_guard = graphql_state.write().await;
+    if let Some(server) = graphql_guard.as_ref() {
+        return Err(AppError::AlreadyRunning(format!("Servidor GraphQL já está rodando em {}", server.address)));
     }
+
+    let context = GraphqlContext {
+        database: db.inner().clone(),
+        websocket_state: websocket_state.inner().clone(),
+    };
+    // Só local por padrão: expor na rede é escolha explícita (host "0.0.0.0")
+    let host = host.unwrap_or_else(|| "127.0.0.1".to_string());
+    let server = GraphqlServer::start(context, &host, port.unwrap_or(DEFAULT_GRAPHQL_PORT)).await?;
+    let address = server.address.clone();
+    *graphql_guard = Some(server);
+
+    Ok(format!("Servidor GraphQL iniciado em http://{}/graphql", address))
 }
 
-// Validar nome de banco (segurança)
-fn validate_database_name(name: &str) -> Result<(), String> {
-    if name.is_empty() {
-        return Err("Nome do banco não pode estar vazio".to_string());
-    }
-    
-    if name.len() > 63 {
-        return Err("Nome do banco não pode ter mais de 63 caracteres".to_string());
-    }
-    
-    // Apenas letras, números e underscore
-    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
-        return Err("Nome do banco pode conter apenas letras, números e underscore".to_string());
-    }
-    
-    // Não pode começar com número
-    if name.chars().next().unwrap().is_ascii_digit() {
-        return Err("Nome do banco não pode começar com número".to_string());
-    }
-    
-    // Palavras reservadas do PostgreSQL
-    let reserved = ["postgres", "template0", "template1", "user", "admin", "root", "system"];
-    if reserved.contains(&name.to_lowercase().as_str()) {
-        return Err("Nome do banco não pode ser uma palavra reservada".to_string());
+#[tauri::command]
+pub async fn stop_graphql_server(
+    graphql_state: State<'_, GraphqlServerState>,
+) -> Result<String, AppError> {
+    match graphql_state.write().await.take() {
+        Some(server) => {
+            server.stop();
+            Ok("Servidor GraphQL parado".to_string())
+        }
+        None => Err(AppError::NotRunning("Servidor GraphQL não está rodando".to_string()))
     }
-    
-    Ok(())
 }
 
+/// Endereço do servidor GraphQL, se estiver rodando
 #[tauri::command]
-pub async fn create_postgres_database(
-    config: PostgresTestConfig,
-    database_name: String,
-    app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    use tokio_postgres::{NoTls, Config};
-    
-    // Validar nome do banco
-    validate_database_name(&database_name)?;
-    
-    println!("🔧 Criando banco de dados '{}' no PostgreSQL...", database_name);
-    
-    // Conectar na database padrão 'postgres' para criar nova database
-    let mut pg_config = Config::new();
-    pg_config
-        .host(&config.host)
-        .port(config.port)
-        .user(&config.user)
-        .password(&config.password)
-        .dbname("postgres") // Conecta na DB padrão para criar nova
-        .application_name("plc-hmi");
-    
-    match pg_config.connect(NoTls).await {
-        Ok((client, connection)) => {
-            println!("✅ Conectado ao PostgreSQL para criar banco");
-            
-            let handle = tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("connection error: {}", e);
-                }
-            });
-            
-            // Usar query preparada para segurança (evitar SQL injection)
-            let create_query = format!("CREATE DATABASE \"{}\"", database_name);
-            
-            match client.batch_execute(&create_query).await {
-                Ok(_) => {
-                    println!("✅ Banco '{}' criado com sucesso!", database_name);
-                    handle.abort();
-                    
-                    // Emitir evento de sucesso
-                    let _ = app_handle.emit(
-                        "postgres-database-created",
-                        serde_json::json!({
-                            "host": config.host,
-                            "port": config.port,
-                            "database": database_name,
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        })
-                    );
-                    
-                    Ok(format!("Banco de dados '{}' criado com sucesso!", database_name))
-                },
-                Err(e) => {
-                    println!("❌ Erro ao criar banco: {}", e);
-                    handle.abort();
-                    
-                    let error_msg = e.to_string();
-                    if error_msg.contains("already exists") {
-                        Err(format!("O banco '{}' já existe", database_name))
-                    } else if error_msg.contains("permission denied") {
-                        Err("Usuário não tem permissão para criar bancos".to_string())
-                    } else {
-                        // Emitir evento de erro
-                        let _ = app_handle.emit(
-                            "postgres-database-error",
-                            serde_json::json!({
-                                "operation": "create_database",
-                                "database": database_name,
-                                "error": error_msg,
-                                "timestamp": chrono::Utc::now().to_rfc3339()
-                            })
-                        );
-                        
-                        Err(format!("Erro ao criar banco: {}", error_msg))
-                    }
-                }
-            }
-        },
-        Err(e) => {
-            println!("❌ Erro de conexão: {}", e);
-            Err(format!("Não foi possível conectar ao PostgreSQL: {}", e))
-        }
+pub async fn get_graphql_status(
+    graphql_state: State<'_, GraphqlServerState>,
+) -> Result<Option<String>, AppError> {
+    Ok(graphql_state.read().await.as_ref().map(|s| s.address.clone()))
+}
+
+// ============================================================================
+// 🆕 API REST (OPCIONAL)
+// ============================================================================
+
+/// Inicia a API REST (requer build com a feature "rest")
+#[tauri::command]
+pub async fn start_rest_api(
+    host: Option<String>,
+    port: Option<u16>,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    server_state: State<'_, TcpServerState>,
+    rest_state: State<'_, RestApiServerState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let mut rest_guard = rest_state.write().await;
+    if let Some(server) = rest_guard.as_ref() {
+        return Err(AppError::AlreadyRunning(format!("API REST já está rodando em {}", server.address)));
     }
+
+    let context = RestApiContext {
+        database: db.inner().clone(),
+        websocket_state: websocket_state.inner().clone(),
+        tcp_state: server_state.inner().clone(),
+        app_handle,
+    };
+    let host = host.unwrap_or_else(|| "0.0.0.0".to_string());
+    let server = RestApiServer::start(context, &host, port.unwrap_or(DEFAULT_REST_PORT)).await?;
+    let address = server.address.clone();
+    *rest_guard = Some(server);
+
+    Ok(format!("API REST iniciada em http://{}/api", address))
 }
 
 #[tauri::command]
-pub async fn list_postgres_databases(
-    config: PostgresTestConfig,
-    _app_handle: tauri::AppHandle,
-) -> Result<Vec<String>, String> {
-    use tokio_postgres::{NoTls, Config};
-    
-    println!("📋 Listando bancos de dados no PostgreSQL...");
-    
-    let mut pg_config = Config::new();
-    pg_config
-        .host(&config.host)
-        .port(config.port)
-        .user(&config.user)
-        .password(&config.password)
-        .dbname("postgres")
-        .application_name("plc-hmi");
-    
-    match pg_config.connect(NoTls).await {
-        Ok((client, connection)) => {
-            let handle = tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("connection error: {}", e);
-                }
-            });
-            
-            // Query para listar bancos (excluindo templates)
-            let query = "SELECT datname FROM pg_database WHERE datistemplate = false ORDER BY datname";
-            
-            match client.query(query, &[]).await {
-                Ok(rows) => {
-                    let databases: Vec<String> = rows
-                        .iter()
-                        .map(|row| row.get::<_, String>(0))
-                        .collect();
-                    
-                    println!("✅ Encontrados {} bancos", databases.len());
-                    handle.abort();
-                    
-                    Ok(databases)
-                },
-                Err(e) => {
-                    println!("❌ Erro ao listar bancos: {}", e);
-                    handle.abort();
-                    Err(format!("Erro ao listar bancos: {}", e))
-                }
-            }
-        },
-        Err(e) => {
-            println!("❌ Erro de conexão: {}", e);
-            Err(format!("Não foi possível conectar ao PostgreSQL: {}", e))
+pub async fn stop_rest_api(
+    rest_state: State<'_, RestApiServerState>,
+) -> Result<String, AppError> {
+    match rest_state.write().await.take() {
+        Some(server) => {
+            server.stop();
+            Ok("API REST parada".to_string())
         }
+        None => Err(AppError::NotRunning("API REST não está rodando".to_string()))
     }
 }
 
+/// Endereço da API REST, se estiver rodando
 #[tauri::command]
-pub async fn drop_postgres_database(
-    config: PostgresTestConfig,
-    database_name: String,
-    app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    use tokio_postgres::{NoTls, Config};
-    
-    // Validações de segurança
-    validate_database_name(&database_name)?;
-    
-    // Não permitir excluir bancos críticos
-    let protected_dbs = ["postgres", "template0", "template1"];
-    if protected_dbs.contains(&database_name.as_str()) {
-        return Err("Não é possível excluir bancos do sistema".to_string());
+pub async fn get_rest_api_status(
+    rest_state: State<'_, RestApiServerState>,
+) -> Result<Option<String>, AppError> {
+    Ok(rest_state.read().await.as_ref().map(|s| s.address.clone()))
+}
+
+// ============================================================================
+// 🆕 STATUS DO SERVIDOR (WEBSOCKET SERVER_STATUS + TÓPICO MQTT RETIDO)
+// ============================================================================
+
+/// Status atual (o mesmo payload do SERVER_STATUS e do tópico MQTT)
+#[tauri::command]
+pub async fn get_server_status() -> Result<crate::server_status::ServerStatus, AppError> {
+    Ok(crate::server_status::current().await)
+}
+
+/// Inicia o publicador de status MQTT (requer build com a feature "mqtt")
+#[tauri::command]
+pub async fn start_mqtt_status(
+    config: crate::mqtt_status::MqttStatusConfig,
+    mqtt_state: State<'_, MqttStatusState>,
+    tcp_state: State<'_, TcpServerState>,
+    db: State<'_, Arc<Database>>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let mut mqtt_guard = mqtt_state.write().await;
+    if let Some(publisher) = mqtt_guard.as_ref() {
+        return Err(AppError::AlreadyRunning(format!("Status MQTT já está publicando em {} ('{}')", publisher.broker, publisher.topic)));
     }
-    
-    println!("🗑️ Excluindo banco de dados '{}'...", database_name);
-    
-    let mut pg_config = Config::new();
-    pg_config
-        .host(&config.host)
-        .port(config.port)
-        .user(&config.user)
-        .password(&config.password)
-        .dbname("postgres")
-        .application_name("plc-hmi");
-    
-    match pg_config.connect(NoTls).await {
-        Ok((client, connection)) => {
-            let handle = tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("connection error: {}", e);
-                }
-            });
-            
-            let drop_query = format!("DROP DATABASE \"{}\"", database_name);
-            
-            match client.batch_execute(&drop_query).await {
-                Ok(_) => {
-                    println!("✅ Banco '{}' excluído com sucesso!", database_name);
-                    handle.abort();
-                    
-                    // Emitir evento de sucesso
-                    let _ = app_handle.emit(
-                        "postgres-database-dropped",
-                        serde_json::json!({
-                            "database": database_name,
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        })
-                    );
-                    
-                    Ok(format!("Banco de dados '{}' excluído com sucesso!", database_name))
-                },
-                Err(e) => {
-                    println!("❌ Erro ao excluir banco: {}", e);
-                    handle.abort();
-                    
-                    let error_msg = e.to_string();
-                    if error_msg.contains("does not exist") {
-                        Err(format!("O banco '{}' não existe", database_name))
-                    } else if error_msg.contains("being accessed") {
-                        Err(format!("O banco '{}' está sendo usado por outras conexões", database_name))
-                    } else {
-                        Err(format!("Erro ao excluir banco: {}", error_msg))
-                    }
-                }
-            }
-        },
-        Err(e) => {
-            println!("❌ Erro de conexão: {}", e);
-            Err(format!("Não foi possível conectar ao PostgreSQL: {}", e))
+    let remote = crate::mqtt_status::RemoteCommandContext {
+        app_handle,
+        database: db.inner().clone(),
+        tcp_state: tcp_state.inner().clone(),
+    };
+    let publisher = crate::mqtt_status::MqttStatusPublisher::start(config, remote).await?;
+    let message = format!("Status MQTT publicando em {} ('{}')", publisher.broker, publisher.topic);
+    *mqtt_guard = Some(publisher);
+    Ok(message)
+}
+
+#[tauri::command]
+pub async fn stop_mqtt_status(
+    mqtt_state: State<'_, MqttStatusState>,
+) -> Result<String, AppError> {
+    match mqtt_state.write().await.take() {
+        Some(publisher) => {
+            publisher.stop().await;
+            Ok("Status MQTT parado (offline publicado)".to_string())
         }
+        None => Err(AppError::NotRunning("Status MQTT não está rodando".to_string()))
+    }
+}
+
+/// Broker e tópico do publicador MQTT, se estiver rodando
+#[tauri::command]
+pub async fn get_mqtt_status_info(
+    mqtt_state: State<'_, MqttStatusState>,
+) -> Result<Option<(String, String)>, AppError> {
+    Ok(mqtt_state.read().await.as_ref().map(|p| (p.broker.clone(), p.topic.clone())))
+}
+
+// ============================================================================
+// LOGGER CSV CONTÍNUO
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_csv_logger_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<CsvLoggerConfig, AppError> {
+    db.load_csv_logger_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do logger CSV: {}", e)))
+}
+
+/// Salva a configuração; se o logger estiver rodando, reinicia com a nova configuração
+#[tauri::command]
+pub async fn save_csv_logger_config(
+    mut config: CsvLoggerConfig,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    csv_logger_state: State<'_, CsvLoggerState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    crate::csv_logger::validate_config(&config).map_err(AppError::ConfigInvalid)?;
+    config.updated_at = chrono::Utc::now().timestamp();
+
+    let mut logger_guard = csv_logger_state.write().await;
+    if let Some(logger) = logger_guard.take() {
+        tokio::task::spawn_blocking(move || logger.stop()).await
+            .map_err(|e| format!("Erro ao parar logger CSV: {}", e))?;
+        *logger_guard = Some(CsvLogger::start(app_handle, websocket_state.inner().clone(), config.clone())?);
     }
+    config.enabled = logger_guard.is_some(); // "enabled" reflete se o logger está ativo
+
+    db.save_csv_logger_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do logger CSV: {}", e)))?;
+    Ok("Configuração do logger CSV salva".to_string())
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct DatabaseTable {
-    pub name: String,
-    pub row_count: Option<u64>,
-    pub columns: Vec<DatabaseColumn>,
+#[tauri::command]
+pub async fn start_csv_logger(
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+    csv_logger_state: State<'_, CsvLoggerState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let mut logger_guard = csv_logger_state.write().await;
+    if logger_guard.is_some() {
+        return Err(AppError::AlreadyRunning("Logger CSV já está rodando".to_string()));
+    }
+
+    let mut config = db.load_csv_logger_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do logger CSV: {}", e)))?;
+    *logger_guard = Some(CsvLogger::start(app_handle, websocket_state.inner().clone(), config.clone())?);
+
+    // Lembrar que estava ativo para retomar ao reiniciar o app
+    config.enabled = true;
+    db.save_csv_logger_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do logger CSV: {}", e)))?;
+    Ok(format!("Logger CSV iniciado em {}", config.output_dir))
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct DatabaseColumn {
-    pub name: String,
-    pub data_type: String,
-    pub is_nullable: bool,
-    pub is_primary_key: bool,
+#[tauri::command]
+pub async fn stop_csv_logger(
+    db: State<'_, Arc<Database>>,
+    csv_logger_state: State<'_, CsvLoggerState>,
+) -> Result<String, AppError> {
+    let logger = csv_logger_state.write().await.take()
+        .ok_or_else(|| AppError::NotRunning("Logger CSV não está rodando".to_string()))?;
+    tokio::task::spawn_blocking(move || logger.stop()).await
+        .map_err(|e| format!("Erro ao parar logger CSV: {}", e))?;
+
+    let mut config = db.load_csv_logger_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do logger CSV: {}", e)))?;
+    config.enabled = false;
+    db.save_csv_logger_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do logger CSV: {}", e)))?;
+    Ok("Logger CSV parado".to_string())
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct DatabaseInspection {
-    pub database_name: String,
-    pub tables: Vec<DatabaseTable>,
-    pub total_tables: usize,
+#[tauri::command]
+pub async fn get_csv_logger_status(
+    csv_logger_state: State<'_, CsvLoggerState>,
+) -> Result<CsvLoggerStatus, AppError> {
+    Ok(csv_logger_state.read().await.as_ref()
+        .map(|logger| logger.status())
+        .unwrap_or_default())
 }
 
+// ============================================================================
+// PONTE OPC DA (WINDOWS)
+// ============================================================================
+
+/// Inicia o executável da ponte OPC DA e passa a alimentá-lo com os valores do cache
 #[tauri::command]
-pub async fn inspect_postgres_database(
-    config: PostgresTestConfig,
-    database_name: String,
-    app_handle: tauri::AppHandle,
-) -> Result<DatabaseInspection, String> {
-    use tokio_postgres::{NoTls, Config};
-    
-    // Validações de segurança
-    validate_database_name(&database_name)?;
-    
-    println!("🔍 Inspecionando estrutura do banco '{}'...", database_name);
-    
-    let mut pg_config = Config::new();
-    pg_config
-        .host(&config.host)
-        .port(config.port)
-        .user(&config.user)
-        .password(&config.password)
-        .dbname(&database_name)
-        .application_name("plc-hmi");
-    
-    match pg_config.connect(NoTls).await {
-        Ok((client, connection)) => {
-            let handle = tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("connection error: {}", e);
-                }
-            });
-            
-            // Query para obter lista de tabelas
-            let tables_query = "
-                SELECT 
-                    table_name
-                FROM information_schema.tables 
-                WHERE table_schema = 'public' 
-                AND table_type = 'BASE TABLE'
-                ORDER BY table_name
-            ";
-            
-            match client.query(tables_query, &[]).await {
-                Ok(table_rows) => {
-                    let mut tables: Vec<DatabaseTable> = Vec::new();
-                    
-                    for table_row in table_rows {
-                        let table_name: String = table_row.get(0);
-                        
-                        // Obter colunas da tabela
-                        let columns_query = "
-                            SELECT 
-                                c.column_name,
-                                c.data_type,
-                                c.is_nullable,
-                                COALESCE(pk.is_primary, false) as is_primary_key
-                            FROM information_schema.columns c
-                            LEFT JOIN (
-                                SELECT 
-                                    kc.column_name,
-                                    true as is_primary
-                                FROM information_schema.table_constraints tc
-                                JOIN information_schema.key_column_usage kc 
-                                    ON tc.constraint_name = kc.constraint_name
-                                    AND tc.table_schema = kc.table_schema
-                                WHERE tc.constraint_type = 'PRIMARY KEY'
-                                AND tc.table_name = $1
-                                AND tc.table_schema = 'public'
-                            ) pk ON c.column_name = pk.column_name
-                            WHERE c.table_name = $1
-                            AND c.table_schema = 'public'
-                            ORDER BY c.ordinal_position
-                        ";
-                        
-                        let mut columns: Vec<DatabaseColumn> = Vec::new();
-                        match client.query(columns_query, &[&table_name]).await {
-                            Ok(column_rows) => {
-                                println!("📊 Tabela '{}': {} colunas encontradas", table_name, column_rows.len());
-                                for column_row in column_rows {
-                                    let column = DatabaseColumn {
-                                        name: column_row.get(0),
-                                        data_type: column_row.get(1),
-                                        is_nullable: column_row.get::<_, String>(2) == "YES",
-                                        is_primary_key: column_row.get(3),
-                                    };
-                                    println!("  📝 Coluna: {} ({}) - PK: {} - NULL: {}", 
-                                        column.name, column.data_type, column.is_primary_key, column.is_nullable);
-                                    columns.push(column);
-                                }
-                            },
-                            Err(e) => {
-                                println!("⚠️ Erro ao obter colunas da tabela {}: {}", table_name, e);
-                            }
-                        }
-                        
-                        // Contar linhas da tabela (com limite para performance)
-                        let count_query = format!("SELECT COUNT(*) FROM \"{}\" LIMIT 1000000", table_name);
-                        let row_count = match client.query(&count_query, &[]).await {
-                            Ok(count_rows) => {
-                                if let Some(row) = count_rows.get(0) {
-                                    Some(row.get::<_, i64>(0) as u64)
-                                } else {
-                                    None
-                                }
-                            },
-                            Err(_) => None,
-                        };
-                        
-                        tables.push(DatabaseTable {
-                            name: table_name,
-                            row_count,
-                            columns,
-                        });
-                    }
-                    
-                    let inspection = DatabaseInspection {
-                        database_name: database_name.clone(),
-                        tables: tables.clone(),
-                        total_tables: tables.len(),
-                    };
-                    
-                    println!("✅ Estrutura do banco '{}' inspecionada: {} tabelas encontradas", database_name, tables.len());
-                    handle.abort();
-                    
-                    // Emitir evento de sucesso
-                    let _ = app_handle.emit(
-                        "postgres-database-inspected",
-                        serde_json::json!({
-                            "database": database_name,
-                            "tables_count": tables.len(),
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        })
-                    );
-                    
-                    Ok(inspection)
-                },
-                Err(e) => {
-                    println!("❌ Erro ao inspecionar banco: {}", e);
-                    handle.abort();
-                    
-                    let error_msg = e.to_string();
-                    if error_msg.contains("does not exist") {
-                        Err(format!("O banco '{}' não existe", database_name))
-                    } else {
-                        Err(format!("Erro ao inspecionar banco: {}", error_msg))
-                    }
-                }
-            }
-        },
-        Err(e) => {
-            println!("❌ Erro de conexão: {}", e);
-            Err(format!("Não foi possível conectar ao banco '{}': {}", database_name, e))
+pub async fn start_opc_bridge(
+    config: OpcBridgeConfig,
+    websocket_state: State<'_, WebSocketServerState>,
+    opc_bridge_state: State<'_, OpcBridgeState>,
+    app_handle: AppHandle,
+) -> Result<String, AppError> {
+    let mut bridge_guard = opc_bridge_state.write().await;
+    if let Some(bridge) = bridge_guard.as_ref() {
+        if bridge.status().await.running {
+            return Err(AppError::AlreadyRunning("Ponte OPC DA já está rodando".to_string()));
         }
     }
+
+    let prog_id = config.prog_id.clone();
+    *bridge_guard = Some(OpcBridge::start(app_handle, websocket_state.inner().clone(), config).await?);
+    Ok(format!("Ponte OPC DA iniciada ({})", prog_id))
+}
+
+#[tauri::command]
+pub async fn stop_opc_bridge(
+    opc_bridge_state: State<'_, OpcBridgeState>,
+) -> Result<String, AppError> {
+    match opc_bridge_state.write().await.take() {
+        Some(bridge) => {
+            bridge.stop();
+            Ok("Ponte OPC DA parada".to_string())
+        }
+        None => Err(AppError::NotRunning("Ponte OPC DA não está rodando".to_string()))
+    }
+}
+
+#[tauri::command]
+pub async fn get_opc_bridge_status(
+    opc_bridge_state: State<'_, OpcBridgeState>,
+) -> Result<OpcBridgeStatus, AppError> {
+    match opc_bridge_state.read().await.as_ref() {
+        Some(bridge) => Ok(bridge.status().await),
+        None => Ok(OpcBridgeStatus::default()),
+    }
 }
 
 // ============================================================================
-// COMANDOS PARA PARSER DE LÓGICA - ACESSO DIRETO AO CACHE
+// 🆕 API LOCAL (IPC) PARA O PLC-APP
 // ============================================================================
 
+/// Abre o named pipe / socket Unix em que painéis plc-app locais assinam tags
 #[tauri::command]
-pub async fn get_real_time_tag_values(
-    plc_ip: String,
-    tcp_state: State<'_, TcpServerState>,
+pub async fn start_ipc_server(
+    endpoint: Option<String>,
+    websocket_state: State<'_, WebSocketServerState>,
+    ipc_state: State<'_, IpcServerState>,
     db: State<'_, Arc<Database>>,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    let mut result = std::collections::HashMap::new();
-    
-    // 1. Buscar dados brutos do cache TCP
-    let server_guard = tcp_state.read().await;
-    
-    if let Some(server) = server_guard.as_ref() {
-        // Acessar cache interno do TCP server
-        let latest_data = server.get_plc_data(&plc_ip);
-        
-        if let Some(plc_data) = latest_data.await {
-            println!("📊 Dados TCP para {}: {} variáveis", plc_ip, plc_data.variables.len());
-            
-            // 2. Buscar mapeamentos do banco
-            match db.load_tag_mappings(&plc_ip) {
-                Ok(mappings) => {
-                    println!("🗂️ Mapeamentos carregados: {}", mappings.len());
-                    
-                    // 3. Processar tags ativos
-                    for mapping in mappings.iter().filter(|m| m.enabled) {
-                        // Buscar variável TCP correspondente
-                        if let Some(tcp_var) = plc_data.variables.iter().find(|v| {
-                            // Para bits: Word[0].1 -> procurar Word[0]
-                            if mapping.variable_path.contains('.') {
-                                let base_name = mapping.variable_path.split('.').next().unwrap_or("");
-                                v.name == base_name
-                            } else {
-                                v.name == mapping.variable_path
-                            }
-                        }) {
-                            let final_value = if mapping.variable_path.contains('.') {
-                                // Extrair bit
-                                let parts: Vec<&str> = mapping.variable_path.split('.').collect();
-                                if parts.len() == 2 {
-                                    if let Ok(bit_index) = parts[1].parse::<u8>() {
-                                        if let Ok(int_val) = tcp_var.value.parse::<u64>() {
-                                            let bit_val = (int_val >> bit_index) & 1;
-                                            if bit_val == 1 { "TRUE".to_string() } else { "FALSE".to_string() }
-                                        } else {
-                                            tcp_var.value.clone()
-                                        }
-                                    } else {
-                                        tcp_var.value.clone()
-                                    }
-                                } else {
-                                    tcp_var.value.clone()
-                                }
-                            } else {
-                                tcp_var.value.clone()
-                            };
-                            
-                            result.insert(mapping.tag_name.clone(), final_value);
-                            println!("✅ Tag processado: {} = {}", mapping.tag_name, result.get(&mapping.tag_name).unwrap());
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("❌ Erro ao carregar mapeamentos: {}", e);
-                    return Err(format!("Erro ao carregar mapeamentos: {}", e));
-                }
-            }
-        } else {
-            return Err(format!("Nenhum dado disponível para PLC {}", plc_ip));
+) -> Result<String, AppError> {
+    let mut ipc_guard = ipc_state.write().await;
+    if let Some(server) = ipc_guard.as_ref() {
+        if server.status().await.running {
+            return Err(AppError::AlreadyRunning("API IPC já está rodando".to_string()));
         }
-    } else {
-        return Err("Servidor TCP não está rodando".to_string());
     }
-    
-    println!("🎯 Total de tags processados: {}", result.len());
-    Ok(result)
+
+    let server = IpcServer::start(websocket_state.inner().clone(), db.inner().clone(), endpoint).await?;
+    let endpoint = server.status().await.endpoint;
+    *ipc_guard = Some(server);
+    Ok(format!("API IPC iniciada em {}", endpoint))
+}
+
+#[tauri::command]
+pub async fn stop_ipc_server(
+    ipc_state: State<'_, IpcServerState>,
+) -> Result<String, AppError> {
+    match ipc_state.write().await.take() {
+        Some(server) => {
+            server.stop();
+            Ok("API IPC parada".to_string())
+        }
+        None => Err(AppError::NotRunning("API IPC não está rodando".to_string()))
+    }
+}
+
+#[tauri::command]
+pub async fn get_ipc_server_status(
+    ipc_state: State<'_, IpcServerState>,
+) -> Result<IpcServerStatus, AppError> {
+    match ipc_state.read().await.as_ref() {
+        Some(server) => Ok(server.status().await),
+        None => Ok(IpcServerStatus::default()),
+    }
 }
 
 // ============================================================================
-// COMANDOS PARA SCL ANALYSIS
+// HEALTH CHECK (/healthz)
 // ============================================================================
 
-// Estrutura para retornar tag com tipo de dado
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct SclTagInfo {
-    pub tag_name: String,
-    pub value: String,
-    pub data_type: String,  // "BOOL", "INT", "WORD", "REAL", "DINT", "DWORD"
-    pub variable_path: String,
-}
-
-/// Comando para obter tags com tipo de dado para análise SCL
-/// Retorna informações completas incluindo o data_type inferido
-/// ✅ OTIMIZADO: Usa cache do WebSocket quando disponível, evitando consultas ao banco
-// ✅ OTIMIZAÇÃO: Comando para monitoramento de memória do sistema
+/// Mesmo relatório servido pelo endpoint /healthz
 #[tauri::command]
-pub async fn get_system_memory_stats(
+pub async fn get_health_status(
+    db: State<'_, Arc<Database>>,
     tcp_state: State<'_, TcpServerState>,
     websocket_state: State<'_, WebSocketServerState>,
-) -> Result<SystemMemoryStats, String> {
-    // Coletar estatísticas do TCP Server
-    let (tcp_buffer_active, tcp_clients, tcp_cache_size) = {
-        let tcp_server_guard = tcp_state.read().await;
-        if let Some(tcp_server) = tcp_server_guard.as_ref() {
-            let stats = tcp_server.get_memory_stats();
-            (stats.0, tcp_server.get_connected_clients_count(), stats.1)
-        } else {
-            (0, 0, 0)
-        }
-    };
-
-    // Coletar estatísticas do WebSocket Server
-    let (ws_cache_size, ws_cache_pct, ws_mappings, ws_tracking, ws_clients) = {
-        let ws_server_guard = websocket_state.read().await;
-        if let Some(ws_server) = ws_server_guard.as_ref() {
-            let cache_stats = ws_server.get_cache_memory_stats();
-            let ws_stats = ws_server.get_stats();
-            (cache_stats.0, cache_stats.3, cache_stats.1, cache_stats.2, ws_stats.active_connections as usize)
-        } else {
-            (0, 0.0, 0, 0, 0)
-        }
-    };
-
-    // Calcular estimativa de memória total (aproximada)
-    let total_memory_kb = (tcp_buffer_active * 8) + // ~8KB por buffer médio
-                         (tcp_cache_size / 1000) +  // Cache TCP ~1KB por entrada
-                         (ws_cache_size / 5) +      // ~200 bytes por tag cached
-                         (ws_mappings * 2) +        // ~2KB por mapping
-                         (ws_tracking / 10);        // ~100 bytes por tracking
-
-    // Determinar status de saúde
-    let health_status = if ws_cache_pct > 90.0 || total_memory_kb > 10240 {
-        "critical".to_string()
-    } else if ws_cache_pct > 75.0 || total_memory_kb > 5120 {
-        "warning".to_string()
-    } else {
-        "healthy".to_string()
+) -> Result<HealthReport, AppError> {
+    let config = db.load_health_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do health check: {}", e)))?;
+    let context = HealthContext {
+        database: db.inner().clone(),
+        tcp_state: tcp_state.inner().clone(),
+        websocket_state: websocket_state.inner().clone(),
     };
+    Ok(crate::health::collect_health(&context, &config).await)
+}
 
-    Ok(SystemMemoryStats {
-        tcp_buffer_pool_active: tcp_buffer_active,
-        tcp_connected_clients: tcp_clients,
-        tcp_data_cache_size: tcp_cache_size,
-        ws_tag_cache_size: ws_cache_size,
-        ws_tag_cache_usage_pct: ws_cache_pct,
-        ws_mappings_cache_size: ws_mappings,
-        ws_change_tracking_size: ws_tracking,
-        ws_connected_clients: ws_clients,
-        total_estimated_memory_kb: total_memory_kb,
-        memory_health_status: health_status,
-        last_cleanup_seconds_ago: 0, // Implementar se necessário
-    })
+#[tauri::command]
+pub async fn get_health_config(
+    db: State<'_, Arc<Database>>,
+) -> Result<HealthConfig, AppError> {
+    db.load_health_config()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar configuração do health check: {}", e)))
 }
 
+/// Salva a configuração e (re)inicia ou para o endpoint HTTP conforme `enabled`
 #[tauri::command]
-pub async fn get_memory_health_report(
+pub async fn save_health_config(
+    mut config: HealthConfig,
+    db: State<'_, Arc<Database>>,
     tcp_state: State<'_, TcpServerState>,
     websocket_state: State<'_, WebSocketServerState>,
-) -> Result<MemoryHealthReport, String> {
-    let memory_stats = get_system_memory_stats(tcp_state, websocket_state).await?;
-    
-    let mut recommendations = Vec::new();
-    let status = memory_stats.memory_health_status.clone();
-    
-    match status.as_str() {
-        "critical" => {
-            recommendations.push("Memória crítica! Reinicie o sistema se possível".to_string());
-            recommendations.push("Reduza o número de tags monitorados".to_string());
-            recommendations.push("Verifique se há vazamentos de memória".to_string());
-        },
-        "warning" => {
-            recommendations.push("Uso de memória alto. Monitore frequentemente".to_string());
-            recommendations.push("Considere reduzir intervalos de cache".to_string());
-        },
-        _ => {
-            recommendations.push("Sistema operando dentro dos parâmetros normais".to_string());
-        }
+    health_state: State<'_, HealthServerState>,
+) -> Result<String, AppError> {
+    if config.port == 0 {
+        return Err(AppError::ConfigInvalid("Porta do health check inválida".to_string()));
+    }
+    if config.max_data_age_ms == 0 {
+        return Err(AppError::ConfigInvalid("Idade máxima dos dados deve ser maior que zero".to_string()));
     }
+    config.updated_at = chrono::Utc::now().timestamp();
+    db.save_health_config(&config)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar configuração do health check: {}", e)))?;
 
-    if memory_stats.ws_tag_cache_usage_pct > 80.0 {
-        recommendations.push("Cache de tags próximo do limite. Limpeza automática ativa".to_string());
+    let mut health_guard = health_state.write().await;
+    if let Some(server) = health_guard.take() {
+        server.stop();
+    }
+    if !config.enabled {
+        return Ok("Configuração salva (endpoint /healthz desativado)".to_string());
     }
 
-    Ok(MemoryHealthReport {
-        status,
-        memory_stats,
-        recommendations,
-        auto_cleanup_enabled: true, // Sempre ativo com as otimizações
-    })
+    let context = HealthContext {
+        database: db.inner().clone(),
+        tcp_state: tcp_state.inner().clone(),
+        websocket_state: websocket_state.inner().clone(),
+    };
+    let server = HealthServer::start(context, config).await?;
+    let address = server.address.clone();
+    *health_guard = Some(server);
+    Ok(format!("Health check disponível em http://{}/healthz", address))
 }
 
+// ============================================================================
+// PAINÉIS REMOTOS (plc-app)
+// ============================================================================
+
 #[tauri::command]
-pub async fn force_memory_cleanup(
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
-    let ws_server_guard = websocket_state.read().await;
-    if let Some(ws_server) = ws_server_guard.as_ref() {
-        let cleaned = ws_server.force_cache_cleanup().await;
-        if cleaned {
-            Ok("Limpeza de memória executada com sucesso".to_string())
-        } else {
-            Ok("Limpeza não necessária - memória dentro dos limites".to_string())
-        }
-    } else {
-        Err("WebSocket server não está ativo".to_string())
-    }
+pub async fn list_panels(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<PanelStatus>, AppError> {
+    db.list_panels(crate::panels::PANEL_OFFLINE_AFTER_MS)
+        .map_err(|e| AppError::Database(format!("Erro ao listar painéis: {}", e)))
 }
 
-// ✅ MELHORIA: Comando para cliente se inscrever em PLCs específicos
 #[tauri::command]
-pub async fn subscribe_client_to_plcs(
-    client_id: u64,
-    plc_ips: Vec<String>,
-    websocket_state: State<'_, WebSocketServerState>,
-) -> Result<String, String> {
-    let ws_server_guard = websocket_state.read().await;
-    if let Some(ws_server) = ws_server_guard.as_ref() {
-        ws_server.subscribe_to_plcs(client_id, plc_ips.clone()).await?;
-        Ok(format!("Cliente {} inscrito em PLCs: {:?}", client_id, plc_ips))
-    } else {
-        Err("WebSocket server não está ativo".to_string())
+pub async fn get_panel_logs(
+    panel_id: Option<String>,
+    level: Option<String>,
+    limit: Option<u32>,
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<PanelLog>, AppError> {
+    db.get_panel_logs(panel_id.as_deref(), level.as_deref(), limit.unwrap_or(200).min(5000))
+        .map_err(|e| AppError::Database(format!("Erro ao carregar logs dos painéis: {}", e)))
+}
+
+/// Remove um painel desativado (e seus logs) da supervisão
+#[tauri::command]
+pub async fn delete_panel(
+    panel_id: String,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, AppError> {
+    match db.delete_panel(&panel_id) {
+        Ok(0) => Err(AppError::NotFound(format!("Painel '{}' não encontrado", panel_id))),
+        Ok(_) => Ok(format!("Painel '{}' removido", panel_id)),
+        Err(e) => Err(AppError::Database(format!("Erro ao remover painel: {}", e))),
     }
 }
+// ============================================================================
+// TAXA DE PACOTES ESPERADA POR PLC
+// ============================================================================
 
-// ✅ MELHORIA: Comando para listar PLCs disponíveis
 #[tauri::command]
-pub async fn get_available_plcs(
-    tcp_state: State<'_, TcpServerState>,
-) -> Result<Vec<String>, String> {
-    let tcp_server_guard = tcp_state.read().await;
-    if let Some(tcp_server) = tcp_server_guard.as_ref() {
-        let connected_plcs = tcp_server.get_all_plc_data().await;
-        Ok(connected_plcs.keys().cloned().collect())
-    } else {
-        Ok(vec![]) // Retorna lista vazia se TCP não ativo
+pub async fn get_plc_rate_expectations(
+    db: State<'_, Arc<Database>>,
+) -> Result<Vec<PlcRateExpectation>, AppError> {
+    db.load_plc_rate_expectations()
+        .map_err(|e| AppError::Database(format!("Erro ao carregar taxas esperadas: {}", e)))
+}
+
+/// Salva o intervalo esperado de um PLC e aplica no servidor TCP em execução
+#[tauri::command]
+pub async fn save_plc_rate_expectation(
+    mut expectation: PlcRateExpectation,
+    db: State<'_, Arc<Database>>,
+    server_state: State<'_, TcpServerState>,
+) -> Result<String, AppError> {
+    if expectation.plc_ip.trim().is_empty() {
+        return Err(AppError::ConfigInvalid("IP do PLC é obrigatório".to_string()));
+    }
+    if expectation.expected_interval_ms == 0 {
+        return Err(AppError::ConfigInvalid("Intervalo esperado deve ser maior que zero".to_string()));
+    }
+    if !(expectation.warning_deviation_pct > 0.0 && expectation.warning_deviation_pct < expectation.alarm_deviation_pct) {
+        return Err(AppError::ConfigInvalid("Desvio de aviso deve ser maior que zero e menor que o de alarme".to_string()));
     }
+    expectation.plc_ip = expectation.plc_ip.trim().to_string();
+    expectation.updated_at = chrono::Utc::now().timestamp();
+    db.save_plc_rate_expectation(&expectation)
+        .map_err(|e| AppError::Database(format!("Erro ao salvar taxa esperada: {}", e)))?;
+
+    if let Some(server) = server_state.read().await.as_ref() {
+        server.reload_rate_expectations()?;
+    }
+    Ok(format!("Taxa esperada de {} salva: {} ms", expectation.plc_ip, expectation.expected_interval_ms))
 }
 
 #[tauri::command]
-pub async fn get_scl_tags(
+pub async fn delete_plc_rate_expectation(
     plc_ip: String,
-    tcp_state: State<'_, TcpServerState>,
-    websocket_state: State<'_, WebSocketServerState>,
     db: State<'_, Arc<Database>>,
-) -> Result<Vec<SclTagInfo>, String> {
-    let mut result = Vec::new();
-    
-    // 1. Buscar dados brutos do cache TCP
-    let server_guard = tcp_state.read().await;
-    
-    if let Some(server) = server_guard.as_ref() {
-        let latest_data = server.get_plc_data(&plc_ip);
-        
-        if let Some(plc_data) = latest_data.await {
-            println!("🔍 SCL: Dados TCP para {}: {} variáveis", plc_ip, plc_data.variables.len());
-            
-            // 2. Tentar buscar mapeamentos do CACHE do WebSocket primeiro
-            let mappings = {
-                let ws_guard = websocket_state.read().await;
-                if let Some(ws_server) = ws_guard.as_ref() {
-                    // Tentar obter do cache interno do WebSocket
-                    ws_server.get_cached_tag_mappings(&plc_ip).await
-                } else {
-                    None
-                }
-            };
-            
-            // Se cache não disponível, buscar do banco (fallback)
-            let mappings = match mappings {
-                Some(cached) => {
-                    println!("⚡ SCL: {} mapeamentos do CACHE (zero I/O!)", cached.len());
-                    cached
-                }
-                None => {
-                    println!("⚠️ SCL: Cache não disponível, buscando do banco...");
-                    match db.load_tag_mappings(&plc_ip) {
-                        Ok(m) => {
-                            println!("📂 SCL: {} mapeamentos carregados do banco", m.len());
-                            m
-                        }
-                        Err(e) => {
-                            return Err(format!("Erro ao carregar mapeamentos: {}", e));
-                        }
-                    }
-                }
-            };
-            
-            // 3. Processar tags ativos
-            for mapping in mappings.iter().filter(|m| m.enabled) {
-                // Determinar se é extração de bit (Word[0].3)
-                let is_bit_extraction = mapping.variable_path.contains('.') 
-                    && !mapping.variable_path.starts_with("DB");
-                
-                // Nome base para buscar no TCP
-                let search_name = if is_bit_extraction {
-                    mapping.variable_path.split('.').next().unwrap_or("")
-                } else {
-                    &mapping.variable_path
-                };
-                
-                // Buscar variável TCP correspondente
-                if let Some(tcp_var) = plc_data.variables.iter().find(|v| v.name == search_name) {
-                    // Determinar valor e tipo
-                    let (final_value, data_type) = if is_bit_extraction {
-                        // Extrair bit da Word
-                        let parts: Vec<&str> = mapping.variable_path.split('.').collect();
-                        if parts.len() == 2 {
-                            if let Ok(bit_index) = parts[1].parse::<u8>() {
-                                if let Ok(int_val) = tcp_var.value.parse::<u64>() {
-                                    let bit_val = (int_val >> bit_index) & 1;
-                                    let value = if bit_val == 1 { "TRUE".to_string() } else { "FALSE".to_string() };
-                                    (value, "BOOL".to_string())
-                                } else {
-                                    (tcp_var.value.clone(), tcp_var.data_type.clone())
-                                }
-                            } else {
-                                (tcp_var.value.clone(), tcp_var.data_type.clone())
-                            }
-                        } else {
-                            (tcp_var.value.clone(), tcp_var.data_type.clone())
-                        }
-                    } else {
-                        // Valor inteiro (WORD, INT, REAL, etc.)
-                        (tcp_var.value.clone(), tcp_var.data_type.clone())
-                    };
-                    
-                    result.push(SclTagInfo {
-                        tag_name: mapping.tag_name.clone(),
-                        value: final_value,
-                        data_type,
-                        variable_path: mapping.variable_path.clone(),
-                    });
-                }
-            }
-        } else {
-            return Err(format!("Nenhum dado disponível para PLC {}", plc_ip));
-        }
-    } else {
-        return Err("Servidor TCP não está rodando".to_string());
+    server_state: State<'_, TcpServerState>,
+) -> Result<String, AppError> {
+    match db.delete_plc_rate_expectation(&plc_ip) {
+        Ok(0) => return Err(AppError::NotFound(format!("Nenhuma taxa esperada para {}", plc_ip))),
+        Ok(_) => {}
+        Err(e) => return Err(AppError::Database(format!("Erro ao remover taxa esperada: {}", e))),
     }
-    
-    println!("🎯 SCL: Total de {} tags processados", result.len());
-    Ok(result)
+    if let Some(server) = server_state.read().await.as_ref() {
+        server.reload_rate_expectations()?;
+    }
+    Ok(format!("Taxa esperada de {} removida", plc_ip))
+}
+
+/// Taxa real x esperada de cada PLC conectado com expectativa configurada
+#[tauri::command]
+pub async fn get_plc_rate_status(
+    server_state: State<'_, TcpServerState>,
+) -> Result<Vec<PlcRateStatus>, AppError> {
+    Ok(server_state.read().await.as_ref().map(|s| s.get_rate_status()).unwrap_or_default())
 }
 
 // ============================================================================
-// COMANDOS DE LEITURA/ESCRITA DE ARQUIVOS
+// DOCUMENTAÇÃO DO PROTOCOLO WEBSOCKET
 // ============================================================================
 
+/// Gera a documentação do protocolo WebSocket desta instalação ("asyncapi" ou "json_schema")
 #[tauri::command]
-pub async fn write_file(path: String, content: String) -> Result<(), String> {
-    std::fs::write(&path, &content)
-        .map_err(|e| format!("Erro ao escrever arquivo: {}", e))
+pub async fn export_websocket_protocol(
+    format: Option<String>,
+    db: State<'_, Arc<Database>>,
+    websocket_state: State<'_, WebSocketServerState>,
+) -> Result<String, AppError> {
+    let format = format.unwrap_or_else(|| ws_protocol::FORMAT_ASYNCAPI.to_string());
+
+    let mut mappings = Vec::new();
+    for plc_ip in db.list_configured_plcs().map_err(|e| AppError::Database(format!("Erro ao listar PLCs: {}", e)))? {
+        mappings.extend(db.load_tag_mappings(&plc_ip)
+            .map_err(|e| AppError::Database(format!("Erro ao carregar tags de {}: {}", plc_ip, e)))?);
+    }
+
+    // Configuração e tipos observados vêm do servidor em execução; sem ele, do banco
+    let ws_guard = websocket_state.read().await;
+    let (config, cached_types) = match ws_guard.as_ref() {
+        Some(server) => {
+            let cached_types: std::collections::HashMap<String, String> = server.smart_cache().snapshot(None).into_iter()
+                .map(|cached| (format!("{}:{}", cached.plc_ip, cached.tag_name), cached.data_type))
+                .collect();
+            (server.get_config().clone(), cached_types)
+        }
+        None => {
+            let config = db.load_websocket_config()
+                .map(|c| WebSocketConfig {
+                    host: c.host,
+                    port: c.port,
+                    max_clients: c.max_clients,
+                    broadcast_interval_ms: c.broadcast_interval_ms,
+                    enabled: c.enabled,
+                    bind_interfaces: c.bind_interfaces,
+                })
+                .unwrap_or_default();
+            (config, std::collections::HashMap::new())
+        }
+    };
+    drop(ws_guard);
+
+    let tags = ws_protocol::collect_tags(mappings, &cached_types);
+    let mut document = match format.as_str() {
+        ws_protocol::FORMAT_ASYNCAPI => ws_protocol::build_asyncapi(&config, &tags),
+        ws_protocol::FORMAT_JSON_SCHEMA => ws_protocol::build_json_schema(&tags),
+        other => return Err(AppError::ConfigInvalid(format!("Formato desconhecido: {} (use asyncapi ou json_schema)", other))),
+    };
+    // Extensão "x-": identifica o servidor que gerou o documento
+    document["x-instance"] = serde_json::json!(crate::config::current_instance());
+    println!("📄 Documentação do protocolo WebSocket gerada ({}): {} tags", format, tags.len());
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| AppError::Other(format!("Erro ao serializar documentação: {}", e)))
 }
 
+// ============================================================================
+// FRAMES DE TESTE SINTÉTICOS
+// ============================================================================
+
+/// Gera um frame válido e frames mutados para uma estrutura (ainda não salva ou a salva do PLC)
 #[tauri::command]
-pub async fn read_file(path: String) -> Result<String, String> {
-    std::fs::read_to_string(&path)
-        .map_err(|e| format!("Erro ao ler arquivo: {}", e))
-}
\ No newline at end of file
+pub async fn generate_test_frames(
+    plc_ip: Option<String>,
+    config: Option<PlcStructureConfig>,
+    seed: Option<u64>,
+    db: State<'_, Arc<Database>>,
+) -> Result<TestFrameSet, AppError> {
+    let config = match (config, plc_ip) {
+        (Some(config), _) => config,
+        (None, Some(plc_ip)) => db.load_plc_structure(&plc_ip)
+            .map_err(|e| AppError::Database(format!("Erro ao carregar estrutura: {}", e)))?
+            .ok_or_else(|| AppError::PlcNotFound(format!("Nenhuma estrutura salva para {}", plc_ip)))?,
+        (None, None) => return Err(AppError::ConfigInvalid("Informe plc_ip ou config".to_string())),
+    };
+    frame_generator::generate_test_frames(&config, seed).map_err(AppError::from)
+}
@@ -1,8 +1,78 @@
 use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::io::Write;
+use std::sync::RwLock;
 use tauri::{AppHandle, Manager};
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{AeadCore, Aes256Gcm, Key, Nonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 🆕 CRIPTOGRAFIA EM REPOUSO (opcional)
+// Com a chave presente, app_config.json é gravado como envelope AES-256-GCM
+// + HMAC-SHA256 (chaves derivadas da chave mestra). A chave fica fora da pasta
+// de configuração (app_local_data_dir/config.key) ou vem de PLC_HMI_CONFIG_KEY.
+const ENCRYPTED_FORMAT: &str = "plc-hmi-enc-v1";
+const CONFIG_KEY_ENV: &str = "PLC_HMI_CONFIG_KEY";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedEnvelope {
+    format: String,
+    nonce: String,      // hex
+    ciphertext: String, // hex
+    hmac: String,       // hex, sobre nonce || ciphertext
+}
+
+/// Estado de segurança do arquivo de configuração (verificado no startup)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSecurityStatus {
+    pub encryption_enabled: bool, // Chave mestra disponível
+    pub file_encrypted: bool,
+    pub integrity_ok: bool,
+    pub message: String,
+}
+
+/// Grava via arquivo temporário + fsync + rename: uma queda no meio não deixa o
+/// arquivo pela metade. `owner_only`: permissão 0600 no Unix (no Windows a pasta
+/// de dados local já é só do usuário)
+fn write_synced(path: &Path, content: &str, owner_only: bool) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let _ = fs::remove_file(&tmp); // Sobra antiga com outras permissões
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if owner_only {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = owner_only;
+    let mut file = options.open(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)?;
+    // Sincronizar a pasta para o rename também sobreviver a uma queda
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Deriva (chave de cifra, chave de HMAC) a partir da chave mestra
+fn derive_keys(master: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let derive = |label: &[u8]| -> [u8; 32] {
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(master).expect("HMAC aceita chave de qualquer tamanho");
+        mac.update(label);
+        mac.finalize().into_bytes().into()
+    };
+    (derive(b"plc-hmi-config-enc"), derive(b"plc-hmi-config-mac"))
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -12,6 +82,88 @@ pub struct AppConfig {
     pub websocket_port: u16,
     pub created_at: i64,
     pub updated_at: i64,
+    #[serde(default)]
+    pub instance: InstanceIdentity,        // 🆕 Identificação deste servidor
+    #[serde(default)]
+    pub startup_banner: Option<String>,    // 🆕 Modelo do banner (None = padrão)
+    #[serde(default)]
+    pub language: Option<String>,          // 🆕 Idioma das mensagens do backend ("pt", "en")
+}
+
+// ============================================================================
+// IDENTIDADE DA INSTÂNCIA
+// ============================================================================
+//
+// Em operações com vários sites, cada servidor se identifica por nome, site e
+// UUID (gerado na primeira execução). A identidade vai no WELCOME/HELLO_ACK do
+// WebSocket, nos cabeçalhos X-Instance-* das respostas HTTP e nos relatórios
+// exportados. Fica em memória (INSTANCE) para quem não tem acesso ao AppHandle.
+
+pub const DEFAULT_BANNER: &str = "🏭 PLC HMI v{version} | {name} @ {site} | {uuid}";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceIdentity {
+    pub name: String,
+    pub site: String,
+    pub uuid: String,
+}
+
+static INSTANCE: RwLock<Option<InstanceIdentity>> = RwLock::new(None);
+
+impl InstanceIdentity {
+    /// Valida nome/site antes de salvar
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Nome da instância não pode ser vazio".to_string());
+        }
+        if self.name.len() > 64 || self.site.len() > 64 {
+            return Err("Nome e site da instância: máximo 64 caracteres".to_string());
+        }
+        if self.name.chars().chain(self.site.chars()).any(|c| c.is_control()) {
+            return Err("Nome e site da instância não podem ter caracteres de controle".to_string());
+        }
+        Ok(())
+    }
+
+    /// Cabeçalhos HTTP da identidade (valores fora do ASCII visível vão em %XX)
+    pub fn http_headers(&self) -> Vec<(&'static str, String)> {
+        let encode = |value: &str| -> String {
+            value.bytes().map(|b| match b {
+                b' '..=b'~' if b != b'%' => (b as char).to_string(),
+                _ => format!("%{:02X}", b),
+            }).collect()
+        };
+        vec![
+            ("X-Instance-Id", encode(&self.uuid)),
+            ("X-Instance-Name", encode(&self.name)),
+            ("X-Instance-Site", encode(&self.site)),
+        ]
+    }
+}
+
+/// Identidade no formato do protocolo WebSocket (plc-hmi-client)
+impl From<InstanceIdentity> for plc_hmi_client::protocol::Instance {
+    fn from(identity: InstanceIdentity) -> Self {
+        Self { name: identity.name, site: identity.site, uuid: identity.uuid }
+    }
+}
+
+/// Identidade em uso (vazia até o setup carregar a configuração)
+pub fn current_instance() -> InstanceIdentity {
+    INSTANCE.read().unwrap().clone().unwrap_or_default()
+}
+
+pub fn set_current_instance(identity: InstanceIdentity) {
+    *INSTANCE.write().unwrap() = Some(identity);
+}
+
+/// Texto do banner de startup com {name}, {site}, {uuid} e {version} substituídos
+pub fn render_banner(template: Option<&str>, identity: &InstanceIdentity) -> String {
+    template.filter(|t| !t.trim().is_empty()).unwrap_or(DEFAULT_BANNER)
+        .replace("{name}", &identity.name)
+        .replace("{site}", &identity.site)
+        .replace("{uuid}", &identity.uuid)
+        .replace("{version}", env!("CARGO_PKG_VERSION"))
 }
 
 impl Default for AppConfig {
@@ -23,12 +175,16 @@ impl Default for AppConfig {
             websocket_port: 8765,
             created_at: chrono::Utc::now().timestamp(),
             updated_at: chrono::Utc::now().timestamp(),
+            instance: InstanceIdentity::default(),
+            startup_banner: None,
+            language: None,
         }
     }
 }
 
 pub struct ConfigManager {
     config_path: PathBuf,
+    key_path: PathBuf,
 }
 
 impl ConfigManager {
@@ -44,7 +200,16 @@ impl ConfigManager {
         
         let config_path = app_dir.join("app_config.json");
         
-        Ok(Self { config_path })
+        // Chave em pasta diferente da configuração
+        let key_dir = app_handle
+            .path()
+            .app_local_data_dir()
+            .map_err(|e| format!("Falha ao obter diretório local: {}", e))?;
+        fs::create_dir_all(&key_dir)
+            .map_err(|e| format!("Falha ao criar diretório: {}", e))?;
+        let key_path = key_dir.join("config.key");
+        
+        Ok(Self { config_path, key_path })
     }
     
     pub fn load_config(&self) -> Result<AppConfig, String> {
@@ -56,7 +221,12 @@ impl ConfigManager {
         let content = fs::read_to_string(&self.config_path)
             .map_err(|e| format!("Erro ao ler configuração: {}", e))?;
         
-        let config: AppConfig = serde_json::from_str(&content)
+        let json = match serde_json::from_str::<EncryptedEnvelope>(&content) {
+            Ok(envelope) if envelope.format == ENCRYPTED_FORMAT => self.decrypt(&envelope)?,
+            _ => content,
+        };
+        
+        let config: AppConfig = serde_json::from_str(&json)
             .map_err(|e| format!("Erro ao parsear configuração: {}", e))?;
         
         println!("✅ Configuração carregada: {:?}", self.config_path);
@@ -64,19 +234,194 @@ impl ConfigManager {
     }
     
     pub fn save_config(&self, config: &AppConfig) -> Result<(), String> {
+        let master = self.master_key()?;
+        self.write_config(config, master.as_deref())
+    }
+    
+    /// Grava a configuração com a chave informada (None = texto puro)
+    fn write_config(&self, config: &AppConfig, master: Option<&[u8]>) -> Result<(), String> {
         let mut config = config.clone();
         config.updated_at = chrono::Utc::now().timestamp();
         
         let json = serde_json::to_string_pretty(&config)
             .map_err(|e| format!("Erro ao serializar configuração: {}", e))?;
         
-        fs::write(&self.config_path, json)
+        let content = match master {
+            Some(master) => self.encrypt(master, &json)?,
+            None => json,
+        };
+        
+        write_synced(&self.config_path, &content, false)
             .map_err(|e| format!("Erro ao salvar configuração: {}", e))?;
         
         println!("💾 Configuração salva: {:?}", self.config_path);
         Ok(())
     }
     
+    // ============================================================================
+    // CRIPTOGRAFIA DO ARQUIVO DE CONFIGURAÇÃO
+    // ============================================================================
+    
+    /// Chave mestra: variável de ambiente ou arquivo de chave (None = criptografia desativada)
+    fn master_key(&self) -> Result<Option<Vec<u8>>, String> {
+        if let Ok(hex_key) = std::env::var(CONFIG_KEY_ENV) {
+            let key = hex::decode(hex_key.trim())
+                .map_err(|e| format!("{} inválida: {}", CONFIG_KEY_ENV, e))?;
+            return Ok(Some(key));
+        }
+        if !self.key_path.exists() {
+            return Ok(None);
+        }
+        let hex_key = fs::read_to_string(&self.key_path)
+            .map_err(|e| format!("Erro ao ler chave de configuração: {}", e))?;
+        hex::decode(hex_key.trim())
+            .map(Some)
+            .map_err(|e| format!("Chave de configuração corrompida: {}", e))
+    }
+    
+    fn encrypt(&self, master: &[u8], plaintext: &str) -> Result<String, String> {
+        let (enc_key, mac_key) = derive_keys(master);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&enc_key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "Erro ao criptografar configuração".to_string())?;
+        
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&mac_key).expect("HMAC aceita chave de qualquer tamanho");
+        mac.update(&nonce);
+        mac.update(&ciphertext);
+        
+        let envelope = EncryptedEnvelope {
+            format: ENCRYPTED_FORMAT.to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(&ciphertext),
+            hmac: hex::encode(mac.finalize().into_bytes()),
+        };
+        serde_json::to_string_pretty(&envelope)
+            .map_err(|e| format!("Erro ao serializar configuração criptografada: {}", e))
+    }
+    
+    fn decrypt(&self, envelope: &EncryptedEnvelope) -> Result<String, String> {
+        let master = self.master_key()?
+            .ok_or_else(|| "Configuração criptografada, mas a chave não foi encontrada".to_string())?;
+        let (enc_key, mac_key) = derive_keys(&master);
+        
+        let nonce = hex::decode(&envelope.nonce).map_err(|_| "Nonce inválido na configuração".to_string())?;
+        let ciphertext = hex::decode(&envelope.ciphertext).map_err(|_| "Conteúdo inválido na configuração".to_string())?;
+        let expected = hex::decode(&envelope.hmac).map_err(|_| "HMAC inválido na configuração".to_string())?;
+        if nonce.len() != 12 {
+            return Err("Nonce inválido na configuração".to_string());
+        }
+        
+        // Verificação de integridade antes de decifrar (comparação em tempo constante)
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&mac_key).expect("HMAC aceita chave de qualquer tamanho");
+        mac.update(&nonce);
+        mac.update(&ciphertext);
+        mac.verify_slice(&expected)
+            .map_err(|_| "Arquivo de configuração adulterado: HMAC não confere".to_string())?;
+        
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&enc_key));
+        let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| "Arquivo de configuração adulterado: falha na autenticação".to_string())?;
+        String::from_utf8(plaintext).map_err(|_| "Configuração decifrada não é UTF-8".to_string())
+    }
+    
+    /// Ativa/desativa a criptografia, regravando o arquivo no novo formato
+    /// (a chave só some depois do texto puro estar no disco)
+    pub fn set_encryption(&self, enabled: bool) -> Result<(), String> {
+        let existing = if self.config_path.exists() { Some(self.load_config()?) } else { None };
+        
+        if enabled {
+            if self.master_key()?.is_none() {
+                let key = Aes256Gcm::generate_key(&mut OsRng);
+                write_synced(&self.key_path, &hex::encode(key), true)
+                    .map_err(|e| format!("Erro ao gravar chave de configuração: {}", e))?;
+                println!("🔐 Chave de criptografia da configuração criada: {:?}", self.key_path);
+            }
+            // Migração: texto puro → criptografado
+            if let Some(config) = existing {
+                self.save_config(&config)?;
+                println!("[MIGRATION] ✅ Configuração regravada criptografada");
+            }
+        } else {
+            if std::env::var(CONFIG_KEY_ENV).is_ok() {
+                return Err(format!("Criptografia definida por {}; remova a variável para desativar", CONFIG_KEY_ENV));
+            }
+            // Migração: criptografado → texto puro, gravado e sincronizado antes de apagar a chave
+            if let Some(config) = existing {
+                self.write_config(&config, None)?;
+                println!("[MIGRATION] ✅ Configuração regravada em texto puro");
+            }
+            if self.key_path.exists() {
+                fs::remove_file(&self.key_path)
+                    .map_err(|e| format!("Erro ao remover chave de configuração: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Verifica o arquivo no startup: integridade, formato e downgrade para texto puro
+    pub fn check_security(&self) -> ConfigSecurityStatus {
+        let encryption_enabled = matches!(self.master_key(), Ok(Some(_)));
+        let mut status = ConfigSecurityStatus {
+            encryption_enabled,
+            file_encrypted: false,
+            integrity_ok: true,
+            message: "Configuração íntegra".to_string(),
+        };
+        
+        let Ok(content) = fs::read_to_string(&self.config_path) else {
+            status.message = "Arquivo de configuração ainda não existe".to_string();
+            return status;
+        };
+        
+        match serde_json::from_str::<EncryptedEnvelope>(&content) {
+            Ok(envelope) if envelope.format == ENCRYPTED_FORMAT => {
+                status.file_encrypted = true;
+                if let Err(e) = self.decrypt(&envelope) {
+                    status.integrity_ok = false;
+                    status.message = e;
+                }
+            }
+            _ if encryption_enabled => {
+                // Arquivo em texto puro com criptografia ativa: possível substituição
+                status.integrity_ok = false;
+                status.message = "Configuração em texto puro com criptografia ativa (arquivo substituído?)".to_string();
+            }
+            _ => {}
+        }
+        status
+    }
+    
+    /// Carrega a configuração e completa a identidade (UUID novo, nome = hostname).
+    /// Na primeira execução nada é gravado: save_initial_config persiste depois.
+    pub fn load_instance(&self) -> Result<AppConfig, String> {
+        let mut config = self.load_config()?;
+        if self.is_first_run() {
+            // Sem arquivo ainda: a identidade desta sessão fica só em memória
+            let current = current_instance();
+            if !current.uuid.is_empty() {
+                config.instance = current;
+            }
+        }
+        let mut changed = false;
+        if config.instance.uuid.trim().is_empty() {
+            config.instance.uuid = uuid::Uuid::new_v4().to_string();
+            changed = true;
+        }
+        if config.instance.name.trim().is_empty() {
+            config.instance.name = std::env::var("COMPUTERNAME")
+                .or_else(|_| std::env::var("HOSTNAME"))
+                .unwrap_or_else(|_| "plc-hmi".to_string());
+            changed = true;
+        }
+        if changed && !self.is_first_run() {
+            self.save_config(&config)?;
+            println!("🆔 Identidade da instância gerada: {}", config.instance.uuid);
+        }
+        set_current_instance(config.instance.clone());
+        Ok(config)
+    }
+    
     pub fn is_first_run(&self) -> bool {
         !self.config_path.exists()
     }
@@ -0,0 +1,306 @@
+use crate::commands::WebSocketServerState;
+use crate::database::CsvLoggerConfig;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// LOGGER CSV CONTÍNUO - GRAVA TAGS SELECIONADOS EM ARQUIVOS ROTATIVOS
+// ============================================================================
+//
+// Algumas plantas ainda importam CSV em sistemas legados. A cada `interval_ms`
+// uma linha com o valor atual de cada tag (lido do SmartCache) é anexada ao
+// arquivo corrente, que pode estar numa pasta local ou num compartilhamento UNC.
+//
+// Template do nome do arquivo:
+//   {date} = AAAAMMDD   {year} {month} {day} {hour}   {seq} = contador de rotação por tamanho
+// Quando o nome renderizado muda (ex: virou a hora com {hour}) um arquivo novo é aberto.
+// Se o compartilhamento cair, as linhas são descartadas e o arquivo é reaberto no próximo ciclo.
+
+pub const MIN_INTERVAL_MS: u64 = 100;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvLoggerStatus {
+    pub running: bool,
+    pub current_file: Option<String>,
+    pub rows_written: u64,
+    pub rows_dropped: u64,   // Linhas perdidas por erro de escrita (ex: compartilhamento offline)
+    pub files_created: u64,
+    pub last_write_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Valida a configuração antes de salvar/iniciar
+pub fn validate_config(config: &CsvLoggerConfig) -> Result<(), String> {
+    if config.output_dir.trim().is_empty() {
+        return Err("Pasta de saída do CSV não configurada".to_string());
+    }
+    let template = config.file_name_template.trim();
+    if template.is_empty() || template.contains('/') || template.contains('\\') {
+        return Err(format!("Template de nome de arquivo inválido: '{}'", config.file_name_template));
+    }
+    if config.interval_ms < MIN_INTERVAL_MS {
+        return Err(format!("Intervalo mínimo do logger CSV é {}ms", MIN_INTERVAL_MS));
+    }
+    if config.delimiter.chars().count() != 1 {
+        return Err(format!("Delimitador deve ter exatamente um caractere: '{}'", config.delimiter));
+    }
+    if config.tags.is_empty() {
+        return Err("Nenhum tag selecionado para o logger CSV".to_string());
+    }
+    if let Some(invalid) = config.tags.iter().find(|t| t.split_once(':').is_none()) {
+        return Err(format!("Tag inválido '{}' (formato esperado: plc_ip:tag_name)", invalid));
+    }
+    Ok(())
+}
+
+/// Renderiza o template para o instante atual; sem {seq} no template, a rotação
+/// por tamanho acrescenta "_N" antes da extensão
+fn render_file_name(template: &str, now: &chrono::DateTime<chrono::Local>, seq: u32) -> String {
+    let name = template
+        .replace("{date}", &now.format("%Y%m%d").to_string())
+        .replace("{year}", &now.format("%Y").to_string())
+        .replace("{month}", &now.format("%m").to_string())
+        .replace("{day}", &now.format("%d").to_string())
+        .replace("{hour}", &now.format("%H").to_string());
+
+    if template.contains("{seq}") {
+        return name.replace("{seq}", &seq.to_string());
+    }
+    if seq == 0 {
+        return name;
+    }
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}_{}.{}", stem, seq, ext),
+        None => format!("{}_{}", name, seq),
+    }
+}
+
+/// Coloca aspas no valor se ele contiver o delimitador, aspas ou quebra de linha
+fn escape_field(value: &str, delimiter: char) -> String {
+    if value.contains(delimiter) || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Arquivo CSV aberto no momento
+struct OpenFile {
+    base_name: String, // Nome renderizado com seq = 0 (detecta troca de período)
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,
+    pending_rows: u32,
+}
+
+struct CsvWriter {
+    config: CsvLoggerConfig,
+    delimiter: char,
+    current: Option<OpenFile>,
+    seq: u32,
+}
+
+impl CsvWriter {
+    fn new(config: CsvLoggerConfig) -> Self {
+        let delimiter = config.delimiter.chars().next().unwrap_or(';');
+        Self { config, delimiter, current: None, seq: 0 }
+    }
+
+    fn header(&self) -> String {
+        let mut columns = vec!["timestamp".to_string()];
+        columns.extend(self.config.tags.iter().map(|t| escape_field(t, self.delimiter)));
+        columns.join(&self.delimiter.to_string())
+    }
+
+    /// Abre (ou reaproveita) o arquivo do período atual, rotacionando por tamanho
+    fn ensure_file(&mut self, now: &chrono::DateTime<chrono::Local>, status: &Mutex<CsvLoggerStatus>) -> Result<(), String> {
+        let base_name = render_file_name(&self.config.file_name_template, now, 0);
+        let max_bytes = self.config.rotate_max_mb * 1024 * 1024;
+
+        let needs_new = match &self.current {
+            None => true,
+            Some(file) if file.base_name != base_name => {
+                self.seq = 0;
+                true
+            }
+            Some(file) if max_bytes > 0 && file.size >= max_bytes => {
+                self.seq += 1;
+                true
+            }
+            Some(_) => false,
+        };
+        if !needs_new {
+            return Ok(());
+        }
+
+        self.close();
+        let dir = PathBuf::from(&self.config.output_dir);
+        fs::create_dir_all(&dir).map_err(|e| format!("Erro ao acessar pasta {:?}: {}", dir, e))?;
+
+        // Continuar numeração existente (ex: após reiniciar o app no mesmo período)
+        let mut path = dir.join(render_file_name(&self.config.file_name_template, now, self.seq));
+        while max_bytes > 0 && fs::metadata(&path).map(|m| m.len() >= max_bytes).unwrap_or(false) {
+            self.seq += 1;
+            path = dir.join(render_file_name(&self.config.file_name_template, now, self.seq));
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)
+            .map_err(|e| format!("Erro ao abrir {:?}: {}", path, e))?;
+        let mut size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let mut writer = BufWriter::new(file);
+
+        let is_new_file = size == 0;
+        if is_new_file {
+            let header = format!("{}\n", self.header());
+            writer.write_all(header.as_bytes())
+                .map_err(|e| format!("Erro ao gravar cabeçalho em {:?}: {}", path, e))?;
+            size += header.len() as u64;
+        }
+
+        println!("📝 Logger CSV gravando em {:?}", path);
+        let mut status = status.lock().unwrap();
+        status.current_file = Some(path.to_string_lossy().to_string());
+        if is_new_file {
+            status.files_created += 1;
+        }
+        drop(status);
+
+        self.current = Some(OpenFile { base_name, path, writer, size, pending_rows: 0 });
+        Ok(())
+    }
+
+    fn write_row(&mut self, values: &[Option<String>], status: &Mutex<CsvLoggerStatus>) -> Result<(), String> {
+        let now = chrono::Local::now();
+        self.ensure_file(&now, status)?;
+
+        let mut fields = vec![now.format("%Y-%m-%d %H:%M:%S%.3f").to_string()];
+        fields.extend(values.iter().map(|v| escape_field(v.as_deref().unwrap_or(""), self.delimiter)));
+        let line = format!("{}\n", fields.join(&self.delimiter.to_string()));
+
+        let flush_every = self.config.flush_every_rows.max(1);
+        let file = self.current.as_mut().expect("arquivo aberto por ensure_file");
+        file.writer.write_all(line.as_bytes())
+            .map_err(|e| format!("Erro ao gravar em {:?}: {}", file.path, e))?;
+        file.size += line.len() as u64;
+        file.pending_rows += 1;
+
+        if file.pending_rows >= flush_every {
+            file.writer.flush().map_err(|e| format!("Erro no flush de {:?}: {}", file.path, e))?;
+            file.pending_rows = 0;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        if let Some(mut file) = self.current.take() {
+            if let Err(e) = file.writer.flush() {
+                println!("⚠️ Logger CSV: erro no flush final de {:?}: {}", file.path, e);
+            }
+        }
+    }
+}
+
+/// Logger em execução (thread dedicada: escrita em compartilhamento de rede pode bloquear)
+pub struct CsvLogger {
+    status: Arc<Mutex<CsvLoggerStatus>>,
+    stop_tx: mpsc::Sender<()>,
+    handle: std::thread::JoinHandle<()>,
+}
+
+impl CsvLogger {
+    pub fn start(app_handle: AppHandle, websocket_state: WebSocketServerState, config: CsvLoggerConfig) -> Result<Self, String> {
+        validate_config(&config)?;
+
+        let status = Arc::new(Mutex::new(CsvLoggerStatus { running: true, ..Default::default() }));
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let thread_status = status.clone();
+
+        println!("🚀 Logger CSV iniciado: {} tags a cada {}ms em {}",
+                 config.tags.len(), config.interval_ms, config.output_dir);
+
+        let handle = std::thread::Builder::new()
+            .name("csv-logger".to_string())
+            .spawn(move || run_logger(app_handle, websocket_state, config, thread_status, stop_rx))
+            .map_err(|e| format!("Erro ao iniciar thread do logger CSV: {}", e))?;
+
+        Ok(Self { status, stop_tx, handle })
+    }
+
+    pub fn status(&self) -> CsvLoggerStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Para o logger, gravando o que estiver no buffer
+    pub fn stop(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.handle.join();
+        println!("🛑 Logger CSV parado");
+    }
+}
+
+fn run_logger(
+    app_handle: AppHandle,
+    websocket_state: WebSocketServerState,
+    config: CsvLoggerConfig,
+    status: Arc<Mutex<CsvLoggerStatus>>,
+    stop_rx: mpsc::Receiver<()>,
+) {
+    let interval = Duration::from_millis(config.interval_ms);
+    let tags: Vec<(String, String)> = config.tags.iter()
+        .filter_map(|t| t.split_once(':'))
+        .map(|(ip, name)| (ip.to_string(), name.to_string()))
+        .collect();
+    let mut writer = CsvWriter::new(config);
+    let mut failing = false;
+
+    // recv_timeout funciona como o "tick" e como sinal de parada ao mesmo tempo
+    while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+        let smart_cache = websocket_state.blocking_read().as_ref().map(|s| s.smart_cache());
+        let Some(smart_cache) = smart_cache else {
+            continue; // Sem WebSocket rodando não há valores ao vivo
+        };
+
+        let values: Vec<Option<String>> = tags.iter()
+            .map(|(ip, name)| smart_cache.get_value(ip, name))
+            .collect();
+        if values.iter().all(|v| v.is_none()) {
+            continue; // Nenhum dado recebido ainda
+        }
+
+        match writer.write_row(&values, &status) {
+            Ok(()) => {
+                let mut status = status.lock().unwrap();
+                status.rows_written += 1;
+                status.last_write_at = Some(chrono::Utc::now().timestamp_millis());
+                if failing {
+                    failing = false;
+                    status.last_error = None;
+                    println!("✅ Logger CSV recuperado - gravação retomada");
+                }
+            }
+            Err(message) => {
+                // Descartar o arquivo atual: reabre no próximo ciclo (ex: compartilhamento voltou)
+                writer.current = None;
+                let mut status = status.lock().unwrap();
+                status.rows_dropped += 1;
+                status.last_error = Some(message.clone());
+                if !failing {
+                    failing = true;
+                    println!("❌ Logger CSV: {}", message);
+                    let _ = app_handle.emit("csv-logger-error", serde_json::json!({
+                        "message": message,
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }));
+                }
+            }
+        }
+    }
+
+    writer.close();
+    status.lock().unwrap().running = false;
+}
@@ -0,0 +1,155 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// VERSIONAMENTO DO DIRETÓRIO DE DADOS
+// ============================================================================
+//
+// A versão do layout fica em dois lugares:
+//   - `PRAGMA user_version` do plc_hmi.db (acompanha o arquivo se for copiado)
+//   - `data_version.json` ao lado do banco (legível sem abrir o SQLite)
+// Antes de abrir o banco: recusar manifesto mais novo que o binário (downgrade).
+// Depois de Database::new: aplicar os passos pendentes e gravar o manifesto.
+
+/// Versão do layout que este binário entende
+pub const DATA_SCHEMA_VERSION: u32 = 2;
+
+const MANIFEST_FILE: &str = "data_version.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataManifest {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub updated_at: String,
+}
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+/// Passos numerados. Tabelas e colunas idempotentes continuam em Database::new;
+/// aqui ficam as mudanças que dependem de saber de qual versão o banco veio.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Layout versionado inicial (manifesto + user_version)",
+        apply: |_| Ok(()),
+    },
+    Migration {
+        version: 2,
+        description: "Versão inicial da unidade dos tags existentes (vigente desde sempre)",
+        apply: |conn| {
+            conn.execute(
+                "INSERT INTO tag_unit_versions (plc_ip, tag_name, unit, display_unit, effective_from_ms, created_at)
+                 SELECT plc_ip, tag_name, NULLIF(TRIM(unit), ''), NULLIF(TRIM(display_unit), ''), 0, strftime('%s', 'now')
+                 FROM tag_mappings
+                 WHERE NOT EXISTS (SELECT 1 FROM tag_unit_versions v WHERE v.plc_ip = tag_mappings.plc_ip AND v.tag_name = tag_mappings.tag_name)
+                 GROUP BY plc_ip, tag_name",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+];
+
+fn manifest_path(db_path: &Path) -> PathBuf {
+    db_path.parent().map(|p| p.to_path_buf()).unwrap_or_default().join(MANIFEST_FILE)
+}
+
+fn read_manifest(db_path: &Path) -> Option<DataManifest> {
+    let content = fs::read_to_string(manifest_path(db_path)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            println!("⚠️ Manifesto de versão ilegível ({}): {}", MANIFEST_FILE, e);
+            None
+        }
+    }
+}
+
+fn user_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get::<usize, i64>(0)).map(|v| v.max(0) as u32)
+}
+
+/// Versão gravada no diretório de dados (0 = layout anterior ao versionamento)
+fn stored_version(db_path: &Path) -> Result<(u32, Option<DataManifest>), String> {
+    let manifest = read_manifest(db_path);
+    let db_version = if db_path.exists() {
+        let conn = Connection::open(db_path).map_err(|e| format!("Erro ao abrir {:?}: {}", db_path, e))?;
+        user_version(&conn).map_err(|e| format!("Erro ao ler versão do banco: {}", e))?
+    } else {
+        0
+    };
+    let version = db_version.max(manifest.as_ref().map(|m| m.schema_version).unwrap_or(0));
+    Ok((version, manifest))
+}
+
+/// Chamar ANTES de abrir o banco: bloqueia downgrade e faz cópia de segurança
+/// quando há migração pendente. Retorna a versão encontrada.
+pub fn check_before_open(db_path: &Path) -> Result<u32, String> {
+    let (version, manifest) = stored_version(db_path)?;
+
+    if version > DATA_SCHEMA_VERSION {
+        let written_by = manifest.map(|m| m.app_version).unwrap_or_else(|| "desconhecida".to_string());
+        return Err(format!(
+            "Os dados em {:?} estão na versão {} (gravados pelo HMI {}), mas este executável ({}) só suporta até a versão {}. \
+             Atualize o HMI ou restaure um backup compatível - o banco NÃO foi aberto para evitar corrupção.",
+            db_path.parent().unwrap_or(db_path),
+            version,
+            written_by,
+            env!("CARGO_PKG_VERSION"),
+            DATA_SCHEMA_VERSION
+        ));
+    }
+
+    if db_path.exists() && version < DATA_SCHEMA_VERSION {
+        println!("[MIGRATION] Diretório de dados na versão {} → {}", version, DATA_SCHEMA_VERSION);
+        backup_before_migration(db_path, version)?;
+    }
+    Ok(version)
+}
+
+/// Snapshot consistente (inclui o WAL) antes de migrar
+fn backup_before_migration(db_path: &Path, from_version: u32) -> Result<(), String> {
+    let dir = db_path.parent().map(|p| p.to_path_buf()).unwrap_or_default().join("backups");
+    fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar pasta de backups: {}", e))?;
+    let target = dir.join(format!(
+        "pre_migration_v{}_to_v{}_{}.db",
+        from_version,
+        DATA_SCHEMA_VERSION,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ));
+
+    let conn = Connection::open(db_path).map_err(|e| format!("Erro ao abrir banco para backup: {}", e))?;
+    conn.execute("VACUUM INTO ?1", [target.to_string_lossy().to_string()])
+        .map_err(|e| format!("Erro ao gerar backup antes da migração: {}", e))?;
+    println!("[MIGRATION] 💾 Backup antes da migração: {:?}", target);
+    Ok(())
+}
+
+/// Chamar DEPOIS de Database::new (tabelas/colunas já criadas): aplica os passos
+/// pendentes em uma transação, grava user_version e o manifesto.
+pub fn apply_migrations(conn: &mut Connection, db_path: &Path, from_version: u32) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| format!("Erro ao iniciar migração: {}", e))?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from_version && m.version <= DATA_SCHEMA_VERSION) {
+        (migration.apply)(&tx).map_err(|e| format!("Migração v{} falhou: {}", migration.version, e))?;
+        println!("[MIGRATION] ✅ v{}: {}", migration.version, migration.description);
+    }
+    tx.pragma_update(None, "user_version", DATA_SCHEMA_VERSION as i64)
+        .map_err(|e| format!("Erro ao gravar versão do banco: {}", e))?;
+    tx.commit().map_err(|e| format!("Erro ao concluir migração: {}", e))?;
+
+    let manifest = DataManifest {
+        schema_version: DATA_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let content = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(manifest_path(db_path), content)
+        .map_err(|e| format!("Erro ao gravar {}: {}", MANIFEST_FILE, e))?;
+    Ok(())
+}
@@ -2,21 +2,16 @@ use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter};
+use crate::websocket_server::WebSocketConfig;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DataBlockConfig {
-    pub data_type: String,  // "WORD", "INT", "DWORD", "REAL", etc
-    pub count: u32,         // Número de elementos
-    pub name: String,       // Nome do array (ex: "Word", "Real2")
-}
+/// 🆕 Estrutura do frame do PLC (definida no plc-core, usada pelo parser e pelo framing)
+pub use plc_core::structure::{DataBlockConfig, FrameProfile, PlcStructureConfig};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlcStructureConfig {
-    pub plc_ip: String,
-    pub blocks: Vec<DataBlockConfig>,
-    pub total_size: usize,
-    pub last_updated: i64,
-}
+/// 🆕 Ordem dos bytes dos valores numéricos no frame (definida no plc-core)
+pub use plc_core::ByteOrder;
+
+/// 🆕 Como o servidor TCP separa os frames de dados desta conexão (definido no plc-core)
+pub use plc_core::FrameMode;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagMapping {
@@ -33,6 +28,34 @@ pub struct TagMapping {
     // 🆕 CAMPOS PARA SUBSCRIBE INTELIGENTE
     pub area: Option<String>,     // ENH, ESV, PJU, PMO, SCO, EDR, GER (equipamento)
     pub category: Option<String>, // PROC, FAULT, EVENT, ALARM, CMD (tipo de tag)
+    // 🆕 COALESCÊNCIA PARA TAGS EM MODO "change"
+    #[serde(default)]
+    pub min_resend_ms: Option<i64>, // Intervalo mínimo entre reenvios do mesmo tag
+    #[serde(default)]
+    pub debounce_ms: Option<i64>,   // Valor precisa ficar estável por este tempo antes de contar como mudança
+    // 🆕 CONVERSÃO DE UNIDADE (ex: unit "bar" → display_unit "kPa")
+    #[serde(default)]
+    pub display_unit: Option<String>,
+    // 🆕 TAG CRÍTICO (segurança): enviado na hora, fora dos lotes de broadcast
+    #[serde(default)]
+    pub critical: bool,
+    // 🆕 ESCALONAMENTO LINEAR (bruto raw_min..raw_max → engenharia eng_min..eng_max) + scale_offset
+    #[serde(default)]
+    pub raw_min: Option<f64>,
+    #[serde(default)]
+    pub raw_max: Option<f64>,
+    #[serde(default)]
+    pub eng_min: Option<f64>,
+    #[serde(default)]
+    pub eng_max: Option<f64>,
+    #[serde(default)]
+    pub scale_offset: Option<f64>,
+    // 🆕 BANDA MORTA (% da faixa de engenharia): variações menores não contam como mudança
+    #[serde(default)]
+    pub deadband_pct: Option<f64>,
+    // 🆕 CASAS DECIMAIS PUBLICADAS (após escalonamento/conversão; None = valor como veio)
+    #[serde(default)]
+    pub display_decimals: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,12 +69,612 @@ pub struct WebSocketDbConfig {
     pub updated_at: i64,
 }
 
+// 🆕 RESULTADO DE OPERAÇÕES EM LOTE NOS TAGS (tudo ou nada)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagItemResult {
+    pub index: usize,                  // Posição do item na lista enviada
+    pub tag_name: Option<String>,
+    pub variable_path: Option<String>,
+    pub id: Option<i64>,
+    pub status: String,                // "saved", "deleted", "skipped", "failed", "rolled_back"
+    pub error: Option<String>,
+}
+
+impl TagItemResult {
+    pub fn for_tag(index: usize, tag: &TagMapping, status: &str, id: Option<i64>, error: Option<String>) -> Self {
+        Self {
+            index,
+            tag_name: Some(tag.tag_name.clone()),
+            variable_path: Some(tag.variable_path.clone()),
+            id,
+            status: status.to_string(),
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagBatchResult {
+    pub committed: bool,
+    pub applied: usize,
+    pub failed: usize,
+    pub results: Vec<TagItemResult>,
+}
+
+impl TagBatchResult {
+    /// Ordena pelos índices de entrada; sem commit, itens aplicados viram "rolled_back"
+    pub fn finish(committed: bool, mut results: Vec<TagItemResult>) -> Self {
+        if !committed {
+            for item in results.iter_mut().filter(|r| r.status == "saved" || r.status == "deleted") {
+                if item.status == "saved" {
+                    item.id = None; // ID de insert revertido não existe
+                }
+                item.status = "rolled_back".to_string();
+            }
+        }
+        results.sort_by_key(|r| r.index);
+        let applied = results.iter().filter(|r| r.status == "saved" || r.status == "deleted").count();
+        let failed = results.iter().filter(|r| r.status == "failed").count();
+        Self { committed, applied, failed, results }
+    }
+}
+
+// 🆕 APELIDO DO PLC: prefixo dos nomes qualificados dos tags ("forno1.temperatura")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlcAlias {
+    pub plc_ip: String,
+    pub alias: String,
+    pub updated_at: i64,
+}
+
+// 🆕 MIGRAÇÃO DA IDENTIDADE DE UM PLC (IP trocado): linhas movidas por tabela.
+// Em dry_run as mesmas atualizações rodam e são revertidas, então a prévia é exata.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlcIdentityMigration {
+    pub old_ip: String,
+    pub new_ip: String,
+    pub dry_run: bool,
+    pub structures: usize,
+    pub tag_mappings: usize,
+    pub tag_unit_versions: usize,
+    pub historian_tags: usize,
+    pub alarm_definitions: usize,
+    pub alarm_history: usize,
+    pub rate_expectations: usize,
+    pub incidents: usize,
+    pub availability: usize,    // 🆕 Eventos de conexão + dias de disponibilidade
+    pub csv_logger_columns: usize,
+    pub replaced_on_new: usize, // Configuração já existente no novo IP substituída pela do antigo
+    pub history_rows: u64,      // PostgreSQL: tag_history (preenchido pelo comando)
+    pub waveform_rows: u64,     // PostgreSQL: tag_waveforms
+}
+
+impl PlcIdentityMigration {
+    pub fn local_rows(&self) -> usize {
+        self.structures + self.tag_mappings + self.tag_unit_versions + self.historian_tags + self.alarm_definitions
+            + self.alarm_history + self.rate_expectations + self.incidents + self.availability + self.csv_logger_columns
+    }
+}
+
+// 🆕 PRIORIDADE DE GRUPOS DE TAGS (filtro "prioridade >= N" por cliente WebSocket)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagGroupPriority {
+    pub group_type: String, // "area" ou "category"
+    pub group_name: String, // Ex: "ENH", "FAULT"
+    pub priority: u8,       // 0 (padrão) a 255 - maior = mais crítico
+}
+
+// 🆕 LOGGER CSV CONTÍNUO (integração com sistemas legados via pasta/compartilhamento)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvLoggerConfig {
+    pub enabled: bool,
+    pub output_dir: String,          // Pasta local ou compartilhamento UNC (\\servidor\pasta)
+    pub file_name_template: String,  // Ex: "plc_{date}_{hour}.csv" (ver csv_logger.rs)
+    pub interval_ms: u64,            // Intervalo entre linhas
+    pub rotate_max_mb: u64,          // Rotação por tamanho (0 = só pelo template)
+    pub flush_every_rows: u32,       // Flush a cada N linhas (0/1 = toda linha)
+    pub delimiter: String,           // "," ou ";"
+    pub tags: Vec<String>,           // "plc_ip:tag_name", na ordem das colunas
+    pub updated_at: i64,
+}
+
+impl Default for CsvLoggerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            output_dir: String::new(),
+            file_name_template: "plc_hmi_{date}.csv".to_string(),
+            interval_ms: 1000,
+            rotate_max_mb: 0,
+            flush_every_rows: 1,
+            delimiter: ";".to_string(),
+            tags: Vec::new(),
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 HEALTH CHECK (/healthz para watchdogs e balanceadores)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    pub enabled: bool,              // Endpoint HTTP ativo
+    pub bind_host: String,
+    pub port: u16,
+    pub max_data_age_ms: u64,       // Último dado de um PLC conectado mais velho que isso = falha
+    pub require_tcp_server: bool,   // Servidor TCP parado = falha
+    pub require_websocket: bool,    // Servidor WebSocket parado = falha
+    pub require_plc_data: bool,     // Nenhum PLC conectado = falha
+    pub check_postgres: bool,       // Testar conexão com o PostgreSQL configurado
+    pub updated_at: i64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_host: "0.0.0.0".to_string(),
+            port: 8090,
+            max_data_age_ms: 10_000,
+            require_tcp_server: true,
+            require_websocket: true,
+            require_plc_data: false,
+            check_postgres: false,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 PAINÉIS REMOTOS (plc-app) SUPERVISIONADOS PELO HMI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelHeartbeat {
+    pub panel_id: String,
+    pub hostname: String,
+    pub app_version: String,
+    pub plc_connected: bool,
+    pub last_data_age_s: Option<u64>, // None = nenhum dado do PLC desde o início
+    pub uptime_s: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelStatus {
+    pub panel_id: String,
+    pub hostname: String,
+    pub address: String,
+    pub app_version: String,
+    pub plc_connected: bool,
+    pub last_data_age_s: Option<u64>,
+    pub uptime_s: u64,
+    pub last_heartbeat: i64, // ms
+    pub first_seen: i64,     // ms
+    pub online: bool,
+    pub last_log_id: i64,    // Último system_logs.id recebido do painel
+}
+
+/// Linha de system_logs como enviada pelo painel (mesmo formato do SystemLog do plc-app)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelLogEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub level: String,
+    pub category: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelLog {
+    pub id: i64,
+    pub panel_id: String,
+    pub remote_id: i64,
+    pub timestamp: String,
+    pub level: String,
+    pub category: String,
+    pub message: String,
+    pub details: String,
+    pub received_at: i64,
+}
+
+// 🆕 CENTRAL DE NOTIFICAÇÕES (eventos críticos persistidos)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: i64,
+    pub severity: String, // "info", "warning", "critical"
+    pub title: String,
+    pub body: String,
+    pub source_event: Option<String>,
+    pub read: bool,
+    pub created_at: i64,
+}
+
+// 🆕 AUDITORIA DE AÇÕES DO OPERADOR (operações em lote nas conexões etc.)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub action: String,   // Ex: "disconnect_all", "reset_statistics"
+    pub target: String,   // IPs afetados ou "*"
+    pub result: String,   // "ok", "partial", "failed"
+    pub details: String,
+    pub created_at: i64,
+}
+
+// 🆕 CHAVES PÚBLICAS DO WEBSOCKET (ex: painel de turismo) COM MASCARAMENTO POR GRUPO
+/// Regra aplicada aos tags de um grupo ("area", "category", "tag" ou "*" = todos).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskingRule {
+    pub group_type: String,            // "area", "category", "tag" ou "*"
+    pub group_name: String,            // Ex: "ENH", "FAULT", "nivel_montante" ("" para "*")
+    #[serde(default)]
+    pub round_decimals: Option<u32>,   // Arredondar valores numéricos
+    #[serde(default)]
+    pub delay_s: Option<u64>,          // Atrasar os valores em N segundos
+    #[serde(default)]
+    pub hide: bool,                    // Não enviar o tag
+}
+
+// 🆕 TOKENS DE API DO WEBSOCKET (autenticação no handshake, ver ws_auth.rs)
+// Só o hash SHA-256 fica no banco; o token em texto é mostrado uma vez na criação.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsToken {
+    pub id: i64,
+    pub name: String,                  // Ex: "SCADA sala de controle"
+    pub prefix: String,                // Início do token, para reconhecer sem expor
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    #[serde(default)]
+    pub remote_support: bool,          // 🆕 Suporte remoto: escreve só com aprovação do operador (/api/remote/write)
+}
+
+// 🆕 SESSÕES DE CLIENTES WEBSOCKET ("quais telas estavam conectadas durante a parada?")
+// Aberta após a autenticação, atualizada a cada minuto (last_seen_ms) e fechada na
+// desconexão. Sessões que ficaram abertas por queda da HMI são fechadas no próximo
+// start com o último last_seen_ms ("interrupted").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsClientSession {
+    pub id: i64,
+    pub client_id: i64,
+    pub address: String,
+    pub token_id: Option<i64>,
+    pub token_name: Option<String>,
+    pub public_key: Option<String>,          // Chave pública da conexão (dados mascarados)
+    pub connected_at_ms: i64,
+    pub last_seen_ms: i64,
+    pub disconnected_at_ms: Option<i64>,
+    pub disconnect_reason: Option<String>,   // "closed", "revoked", "server_stopped", "interrupted"
+    pub bytes_sent: i64,
+    pub messages_sent: i64,
+    pub messages_received: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsSessionConfig {
+    pub enabled: bool,
+    pub retention_days: u32,         // Sessões encerradas há mais tempo são apagadas
+    pub updated_at: i64,
+}
+
+impl Default for WsSessionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 30,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 TÚNEL DE COMANDOS REMOTOS (suporte remoto com aprovação do operador local)
+// Desligado por padrão: sem ele, nenhum comando remoto chega a ser enfileirado.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteTunnelConfig {
+    pub enabled: bool,
+    pub approval_timeout_s: u32,     // Sem decisão nesse prazo o comando é negado
+    pub updated_at: i64,
+}
+
+impl Default for RemoteTunnelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            approval_timeout_s: 120,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 DETECÇÃO DE SENSOR CONGELADO (ver flatline.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlatlineConfig {
+    pub enabled: bool,
+    pub flatline_after_s: u32,       // Tempo sem nenhuma variação para marcar o tag
+    pub updated_at: i64,
+}
+
+impl Default for FlatlineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            flatline_after_s: 900,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 BACKUP AUTOMÁTICO DA CONFIGURAÇÃO (ver backup.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    pub retention: u32,              // Backups mantidos na pasta (os mais recentes)
+    pub updated_at: i64,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            retention: 7,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 REDUNDÂNCIA PRIMÁRIO/STANDBY (heartbeat entre os HMIs, ver redundancy.rs)
+// Configuração por instância: fica fora de CONFIG_TABLES para o restore de um
+// backup do par não trocar os papéis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedundancyConfig {
+    pub enabled: bool,
+    pub role: String,                // "primary" ou "standby"
+    pub peer_address: String,        // "host:porta" do HMI par
+    pub listen_port: u16,            // Porta onde este HMI atende o heartbeat do par
+    pub peer_timeout_s: u32,         // Sem heartbeat por este tempo = par perdido
+    pub updated_at: i64,
+}
+
+impl Default for RedundancyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            role: "primary".to_string(),
+            peer_address: String::new(),
+            listen_port: 8510,
+            peer_timeout_s: 15,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 QUARENTENA DE PLCs COM ERROS DE PARSE (ver parse_quarantine.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseQuarantineConfig {
+    pub enabled: bool,
+    pub error_threshold: u32,        // Erros dentro da janela para entrar em quarentena
+    pub window_s: u32,
+    pub updated_at: i64,
+}
+
+impl Default for ParseQuarantineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            error_threshold: 20,
+            window_s: 60,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 ESTADO DE EXECUÇÃO DOS SERVIDORES (retomado no setup, ver autostart.rs)
+// *_running e a última configuração são gravados ao iniciar/parar pelos
+// comandos; *_autostart é a opção do usuário por componente.
+// Fora de CONFIG_TABLES: cada instância redundante tem o seu.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeState {
+    pub tcp_autostart: bool,
+    pub tcp_running: bool,
+    pub tcp_port: u16,
+    pub websocket_autostart: bool,
+    pub websocket_running: bool,
+    pub websocket_config: Option<WebSocketConfig>,  // Última configuração usada no start
+    pub updated_at: i64,
+}
+
+impl Default for RuntimeState {
+    fn default() -> Self {
+        Self {
+            tcp_autostart: true,
+            tcp_running: false,
+            tcp_port: 8502,
+            websocket_autostart: true,
+            websocket_running: false,
+            websocket_config: None,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 VERSÕES DA UNIDADE DOS TAGS (histórico interpretado com a unidade da época)
+// Cada alteração de unit/display_unit grava uma versão vigente a partir de
+// effective_from_ms; a primeira versão de cada tag vale desde 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagUnitVersion {
+    pub id: i64,
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub unit: Option<String>,
+    pub display_unit: Option<String>,
+    pub effective_from_ms: i64,
+}
+
+impl TagUnitVersion {
+    /// Unidade em que os valores foram publicados/gravados nesta versão
+    pub fn published_unit(&self) -> Option<&str> {
+        self.display_unit.as_deref().or(self.unit.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicStreamKey {
+    pub key: String,                   // Credencial do cliente no lugar do token ("public_key")
+    pub name: String,                  // Ex: "Painel turismo"
+    pub rules: Vec<MaskingRule>,
+    pub enabled: bool,
+    pub updated_at: i64,
+}
+
+// 🆕 DESTINOS DO HISTORIAN EM ORDEM DE PRIORIDADE (failover, ver historian_failover.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorianTarget {
+    pub name: String,              // Ex: "primario", "secundario", "local"
+    pub kind: String,              // "postgres" ou "sqlite"
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub port: u16,
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub database: String,          // PostgreSQL: nome do banco / SQLite: caminho do arquivo ("" = padrão)
+    pub enabled: bool,
+}
+
+/// Faixa de amostras gravada em um destino secundário enquanto o primário estava fora
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorianCatchupWindow {
+    pub target: String,
+    pub from_ms: i64,
+    pub to_ms: i64,
+}
+
+// 🆕 GRAVAÇÃO CONTÍNUA NO HISTORIAN (ver historian_writer.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorianWriterConfig {
+    pub enabled: bool,
+    pub flush_interval_ms: u64,    // Grava o buffer pelo menos a cada N ms
+    pub batch_size: usize,         // ... ou quando acumular N amostras
+    pub max_buffered: usize,       // Limite do buffer com todos os destinos fora (descarta as mais antigas)
+    pub updated_at: i64,
+}
+
+impl Default for HistorianWriterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            flush_interval_ms: 5_000,
+            batch_size: 1_000,
+            max_buffered: 100_000,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// 🆕 CAPTURA DE INCIDENTES (logs + frames brutos, ver incident_capture.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentRecord {
+    pub id: i64,
+    pub kind: String,                  // "connection_lost", "critical_alarm"
+    pub plc_ip: Option<String>,
+    pub source_event: String,          // Evento que disparou a captura
+    pub reason: String,
+    pub notification_id: Option<i64>,
+    pub log_lines: usize,
+    pub frame_count: usize,
+    pub files_dir: String,             // Pasta com log.txt e frames.txt
+    pub created_at: i64,
+}
+
+// 🆕 MOTOR DE ALARMES (ver alarm_engine.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmDefinition {
+    #[serde(default)]
+    pub id: i64,                       // 0 = novo
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub name: String,
+    pub comparison: String,            // ">", ">=", "<", "<=", "==", "!="
+    pub threshold: f64,
+    pub severity: String,              // "info", "warning", "critical"
+    #[serde(default)]
+    pub deadband: f64,                 // Histerese para sair do alarme (> e <)
+    #[serde(default)]
+    pub on_delay_ms: u64,              // Condição precisa durar N ms para disparar
+    #[serde(default)]
+    pub off_delay_ms: u64,             // ... e N ms fora para normalizar
+    #[serde(default)]
+    pub message: Option<String>,       // Texto para o operador (padrão: condição)
+    pub enabled: bool,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+// 🆕 OCORRÊNCIA DE ALARME (estado persistido: ativo até cleared_at, pendente até acked_at)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmOccurrence {
+    pub id: i64,
+    pub alarm_id: i64,
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub name: String,
+    pub severity: String,
+    pub message: String,
+    pub raised_value: String,
+    pub raised_at_ms: i64,
+    pub cleared_value: Option<String>,
+    pub cleared_at_ms: Option<i64>,
+    pub acked_at_ms: Option<i64>,
+    pub acked_by: Option<String>,
+}
+
+// 🆕 EVENTOS DE CONEXÃO E DISPONIBILIDADE DIÁRIA POR PLC (ver availability.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlcConnectionEvent {
+    pub id: i64,
+    pub plc_ip: Option<String>,   // None = evento do servidor TCP
+    pub event: String,            // "connected", "disconnected", "server_started", "server_stopped"
+    pub reason: Option<String>,
+    pub ts_ms: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlcAvailabilityDay {
+    pub plc_ip: String,
+    pub day: String,              // "AAAA-MM-DD" (horário local)
+    pub up_ms: i64,
+    pub down_ms: i64,             // Servidor rodando e PLC desconectado
+    pub disconnections: u32,
+    pub repairs: u32,             // Reconexões depois de uma queda
+    pub updated_at: i64,
+}
+
+// 🆕 TAXA DE PACOTES ESPERADA POR PLC (ver packet_rate.rs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlcRateExpectation {
+    pub plc_ip: String,
+    pub expected_interval_ms: u64,    // Intervalo de envio configurado no PLC (ex: 500)
+    pub warning_deviation_pct: f64,   // Desvio (%) que gera aviso
+    pub alarm_deviation_pct: f64,     // Desvio (%) que gera alarme
+    pub enabled: bool,
+    pub updated_at: i64,
+}
+
 // ✅ DATABASE COM CONNECTION POOLING OTIMIZADO
 pub struct Database {
     read_conn: Arc<Mutex<Connection>>,   // ✅ Conexão para leitura
     write_conn: Arc<Mutex<Connection>>,  // ✅ Conexão para escrita
+    db_path: std::path::PathBuf,
 }
 
+/// Tabelas de configuração (incluídas no restore de backups)
+/// Banco de configuração (a versão do layout fica ao lado, ver data_version.rs)
+pub const DB_PATH: &str = "D:\\Banco_SQLITE\\plc_hmi.db";
+
+/// Colunas lidas por `tag_mapping_from_row` (mesma ordem)
+const TAG_MAPPING_COLUMNS: &str = "id, plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, \
+    area, category, min_resend_ms, debounce_ms, display_unit, COALESCE(critical, 0), raw_min, raw_max, eng_min, eng_max, scale_offset, deadband_pct, display_decimals";
+
+pub const CONFIG_TABLES: &[&str] = &["postgres_config", "plc_structures", "tag_mappings", "websocket_config", "csv_logger_config", "tag_group_priorities", "health_config", "plc_rate_expectations", "ws_public_keys", "historian_targets", "historian_writer_config", "historian_tags", "alarm_definitions", "ws_tokens", "tag_unit_versions", "ws_session_config", "remote_tunnel_config", "flatline_config", "parse_quarantine_config", "plc_aliases", "backup_config"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostgresConfig {
     pub host: String,
@@ -110,7 +733,7 @@ impl Database {
         }
     pub fn new(app_handle: &AppHandle) -> Result<Self> {
         // SEMPRE usar o banco configurado primeiro
-        let db_path = std::path::PathBuf::from("D:\\Banco_SQLITE\\plc_hmi.db");
+        let db_path = std::path::PathBuf::from(DB_PATH);
         // Criar diretório se não existir
         if let Some(parent) = db_path.parent() {
             if let Err(e) = std::fs::create_dir_all(parent) {
@@ -179,6 +802,31 @@ impl Database {
             }));
             return Err(e);
         }
+        // Migração: perfis de frame alternativos por PLC
+        {
+            let mut stmt = write_conn_ref.prepare("PRAGMA table_info(plc_structures)")?;
+            let columns: Vec<String> = stmt.query_map([], |row| row.get(1))?.filter_map(Result::ok).collect();
+            if !columns.iter().any(|c| c == "profiles_json") {
+                match write_conn_ref.execute("ALTER TABLE plc_structures ADD COLUMN profiles_json TEXT NOT NULL DEFAULT '[]'", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'profiles_json' adicionada à tabela plc_structures."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'profiles_json': {}", e),
+                }
+            }
+            // 🆕 Ordem dos bytes da estrutura
+            if !columns.iter().any(|c| c == "byte_order") {
+                match write_conn_ref.execute("ALTER TABLE plc_structures ADD COLUMN byte_order TEXT NOT NULL DEFAULT 'big'", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'byte_order' adicionada à tabela plc_structures."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'byte_order': {}", e),
+                }
+            }
+            // 🆕 Modo de separação dos frames no TCP
+            if !columns.iter().any(|c| c == "framing") {
+                match write_conn_ref.execute("ALTER TABLE plc_structures ADD COLUMN framing TEXT NOT NULL DEFAULT 'fixed'", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'framing' adicionada à tabela plc_structures."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'framing': {}", e),
+                }
+            }
+        }
         if let Err(e) = write_conn_ref.execute(
             "CREATE TABLE IF NOT EXISTS tag_mappings (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -193,6 +841,17 @@ impl Database {
                 collect_interval_s INTEGER,
                 area TEXT,
                 category TEXT,
+                min_resend_ms INTEGER,
+                debounce_ms INTEGER,
+                display_unit TEXT,
+                critical INTEGER NOT NULL DEFAULT 0,
+                raw_min REAL,
+                raw_max REAL,
+                eng_min REAL,
+                eng_max REAL,
+                scale_offset REAL,
+                deadband_pct REAL,
+                display_decimals INTEGER,
                 UNIQUE(plc_ip, variable_path),
                 FOREIGN KEY(plc_ip) REFERENCES plc_structures(plc_ip)
             )",
@@ -243,6 +902,54 @@ impl Database {
                 }
             }
             
+            // 🆕 Migração: min_resend_ms / debounce_ms (coalescência de mudanças)
+            if !columns.iter().any(|c| c == "min_resend_ms") {
+                match write_conn_ref.execute("ALTER TABLE tag_mappings ADD COLUMN min_resend_ms INTEGER", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'min_resend_ms' adicionada à tabela tag_mappings."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'min_resend_ms': {}", e),
+                }
+            }
+            if !columns.iter().any(|c| c == "debounce_ms") {
+                match write_conn_ref.execute("ALTER TABLE tag_mappings ADD COLUMN debounce_ms INTEGER", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'debounce_ms' adicionada à tabela tag_mappings."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'debounce_ms': {}", e),
+                }
+            }
+            
+            // 🆕 Migração: display_unit (conversão de unidade por tag)
+            if !columns.iter().any(|c| c == "display_unit") {
+                match write_conn_ref.execute("ALTER TABLE tag_mappings ADD COLUMN display_unit TEXT", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'display_unit' adicionada à tabela tag_mappings."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'display_unit': {}", e),
+                }
+            }
+            
+            // 🆕 Migração: critical (caminho de alta prioridade no WebSocket)
+            if !columns.iter().any(|c| c == "critical") {
+                match write_conn_ref.execute("ALTER TABLE tag_mappings ADD COLUMN critical INTEGER NOT NULL DEFAULT 0", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'critical' adicionada à tabela tag_mappings."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'critical': {}", e),
+                }
+            }
+            
+            // 🆕 Migração: escalonamento e banda morta
+            for column in ["raw_min", "raw_max", "eng_min", "eng_max", "scale_offset", "deadband_pct"] {
+                if !columns.iter().any(|c| c == column) {
+                    match write_conn_ref.execute(&format!("ALTER TABLE tag_mappings ADD COLUMN {} REAL", column), []) {
+                        Ok(_) => println!("[MIGRATION] ✅ Coluna '{}' adicionada à tabela tag_mappings.", column),
+                        Err(e) => println!("[MIGRATION][AVISO] Coluna '{}': {}", column, e),
+                    }
+                }
+            }
+            
+            // 🆕 Migração: casas decimais publicadas por tag
+            if !columns.iter().any(|c| c == "display_decimals") {
+                match write_conn_ref.execute("ALTER TABLE tag_mappings ADD COLUMN display_decimals INTEGER", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'display_decimals' adicionada à tabela tag_mappings."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'display_decimals': {}", e),
+                }
+            }
+            
             println!("[MIGRATION] ✅ Verificação de colunas concluída.");
         }
         
@@ -271,28 +978,493 @@ impl Database {
             "ALTER TABLE websocket_config ADD COLUMN bind_interfaces_json TEXT NOT NULL DEFAULT '[\"0.0.0.0\"]'",
             [],
         );
-        // ✅ CRIAR ÍNDICES PARA PERFORMANCE
-        let indexes = [
-            "CREATE INDEX IF NOT EXISTS idx_plc_structures_last_updated ON plc_structures(last_updated DESC)",
-            "CREATE INDEX IF NOT EXISTS idx_tag_mappings_plc_ip ON tag_mappings(plc_ip)",
-            "CREATE INDEX IF NOT EXISTS idx_tag_mappings_enabled ON tag_mappings(enabled)",
-            "CREATE INDEX IF NOT EXISTS idx_tag_mappings_plc_enabled ON tag_mappings(plc_ip, enabled)",
-        ];
-        
-        for index_sql in &indexes {
-            if let Err(e) = write_conn_ref.execute(index_sql, []) {
-                println!("⚠️ Aviso: Falha ao criar índice: {} - {}", index_sql, e);
-            }
-        }
-        
-        println!("✅ Banco de dados SQLite OTIMIZADO inicializado com dual connections");
-        
-        Ok(Database {
-            read_conn: Arc::new(Mutex::new(read_conn)),
-            write_conn: Arc::new(Mutex::new(write_conn)),
-        })
-    }
-    
+        // 🆕 TABELA DE CONFIGURAÇÃO DO LOGGER CSV
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS csv_logger_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 0,
+                output_dir TEXT NOT NULL DEFAULT '',
+                file_name_template TEXT NOT NULL,
+                interval_ms INTEGER NOT NULL DEFAULT 1000,
+                rotate_max_mb INTEGER NOT NULL DEFAULT 0,
+                flush_every_rows INTEGER NOT NULL DEFAULT 1,
+                delimiter TEXT NOT NULL DEFAULT ';',
+                tags_json TEXT NOT NULL DEFAULT '[]',
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_csv_logger_config",
+                "message": format!("Erro ao criar tabela csv_logger_config: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE CONFIGURAÇÃO DO HEALTH CHECK
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS health_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 0,
+                bind_host TEXT NOT NULL DEFAULT '0.0.0.0',
+                port INTEGER NOT NULL DEFAULT 8090,
+                max_data_age_ms INTEGER NOT NULL DEFAULT 10000,
+                require_tcp_server INTEGER NOT NULL DEFAULT 1,
+                require_websocket INTEGER NOT NULL DEFAULT 1,
+                require_plc_data INTEGER NOT NULL DEFAULT 0,
+                check_postgres INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_health_config",
+                "message": format!("Erro ao criar tabela health_config: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE PRIORIDADES DOS GRUPOS DE TAGS
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS tag_group_priorities (
+                group_type TEXT NOT NULL,
+                group_name TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (group_type, group_name)
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_tag_group_priorities",
+                "message": format!("Erro ao criar tabela tag_group_priorities: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELAS DE PAINÉIS REMOTOS (heartbeat + logs encaminhados)
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS panels (
+                panel_id TEXT PRIMARY KEY,
+                hostname TEXT NOT NULL DEFAULT '',
+                address TEXT NOT NULL DEFAULT '',
+                app_version TEXT NOT NULL DEFAULT '',
+                plc_connected INTEGER NOT NULL DEFAULT 0,
+                last_data_age_s INTEGER,
+                uptime_s INTEGER NOT NULL DEFAULT 0,
+                last_heartbeat INTEGER NOT NULL,
+                first_seen INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS panel_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                panel_id TEXT NOT NULL,
+                remote_id INTEGER NOT NULL,
+                timestamp TEXT NOT NULL,
+                level TEXT NOT NULL,
+                category TEXT NOT NULL,
+                message TEXT NOT NULL,
+                details TEXT NOT NULL DEFAULT '',
+                received_at INTEGER NOT NULL,
+                UNIQUE(panel_id, remote_id)
+            );"
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_panels",
+                "message": format!("Erro ao criar tabelas de painéis: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE NOTIFICAÇÕES
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS notifications (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                severity TEXT NOT NULL,
+                title TEXT NOT NULL,
+                body TEXT NOT NULL,
+                source_event TEXT,
+                read INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_notifications",
+                "message": format!("Erro ao criar tabela notifications: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 Migração: read_at (instante do reconhecimento, para o tempo médio de reconhecimento)
+        {
+            let mut stmt = write_conn_ref.prepare("PRAGMA table_info(notifications)")?;
+            let columns: Vec<String> = stmt.query_map([], |row| row.get(1))?.filter_map(Result::ok).collect();
+            if !columns.iter().any(|c| c == "read_at") {
+                match write_conn_ref.execute("ALTER TABLE notifications ADD COLUMN read_at INTEGER", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'read_at' adicionada à tabela notifications."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'read_at': {}", e),
+                }
+            }
+        }
+        // 🆕 TABELA DE AUDITORIA
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL,
+                target TEXT NOT NULL,
+                result TEXT NOT NULL,
+                details TEXT NOT NULL DEFAULT '',
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_audit_log",
+                "message": format!("Erro ao criar tabela audit_log: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE TAXA ESPERADA POR PLC
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS plc_rate_expectations (
+                plc_ip TEXT PRIMARY KEY,
+                expected_interval_ms INTEGER NOT NULL,
+                warning_deviation_pct REAL NOT NULL DEFAULT 25,
+                alarm_deviation_pct REAL NOT NULL DEFAULT 50,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_plc_rate_expectations",
+                "message": format!("Erro ao criar tabela plc_rate_expectations: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE CHAVES PÚBLICAS DO WEBSOCKET (mascaramento)
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS ws_public_keys (
+                key TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                rules_json TEXT NOT NULL DEFAULT '[]',
+                enabled INTEGER NOT NULL DEFAULT 1,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_ws_public_keys",
+                "message": format!("Erro ao criar tabela ws_public_keys: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE TOKENS DE API DO WEBSOCKET
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS ws_tokens (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                prefix TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                last_used_at INTEGER,
+                revoked_at INTEGER
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_ws_tokens",
+                "message": format!("Erro ao criar tabela ws_tokens: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 Migração: remote_support (token de suporte remoto, escrita só com aprovação)
+        {
+            let mut stmt = write_conn_ref.prepare("PRAGMA table_info(ws_tokens)")?;
+            let columns: Vec<String> = stmt.query_map([], |row| row.get(1))?.filter_map(Result::ok).collect();
+            if !columns.iter().any(|c| c == "remote_support") {
+                match write_conn_ref.execute("ALTER TABLE ws_tokens ADD COLUMN remote_support INTEGER NOT NULL DEFAULT 0", []) {
+                    Ok(_) => println!("[MIGRATION] ✅ Coluna 'remote_support' adicionada à tabela ws_tokens."),
+                    Err(e) => println!("[MIGRATION][AVISO] Coluna 'remote_support': {}", e),
+                }
+            }
+        }
+        // 🆕 TABELA DE VERSÕES DA UNIDADE DOS TAGS
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tag_unit_versions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                plc_ip TEXT NOT NULL,
+                tag_name TEXT NOT NULL,
+                unit TEXT,
+                display_unit TEXT,
+                effective_from_ms INTEGER NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_tag_unit_versions_tag ON tag_unit_versions (plc_ip, tag_name, effective_from_ms);",
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_tag_unit_versions",
+                "message": format!("Erro ao criar tabela tag_unit_versions: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELAS DE SESSÕES DE CLIENTES WEBSOCKET (+ configuração de retenção)
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS ws_client_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                client_id INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                token_id INTEGER,
+                token_name TEXT,
+                public_key TEXT,
+                connected_at_ms INTEGER NOT NULL,
+                last_seen_ms INTEGER NOT NULL,
+                disconnected_at_ms INTEGER,
+                disconnect_reason TEXT,
+                bytes_sent INTEGER NOT NULL DEFAULT 0,
+                messages_sent INTEGER NOT NULL DEFAULT 0,
+                messages_received INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS idx_ws_client_sessions_time ON ws_client_sessions (connected_at_ms, disconnected_at_ms);
+            CREATE TABLE IF NOT EXISTS ws_session_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                retention_days INTEGER NOT NULL DEFAULT 30,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS remote_tunnel_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 0,
+                approval_timeout_s INTEGER NOT NULL DEFAULT 120,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS flatline_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                flatline_after_s INTEGER NOT NULL DEFAULT 900,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS backup_config (
+                id INTEGER PRIMARY KEY,
+                retention INTEGER NOT NULL DEFAULT 7,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS redundancy_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 0,
+                role TEXT NOT NULL DEFAULT 'primary',
+                peer_address TEXT NOT NULL DEFAULT '',
+                listen_port INTEGER NOT NULL DEFAULT 8510,
+                peer_timeout_s INTEGER NOT NULL DEFAULT 15,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS plc_aliases (
+                plc_ip TEXT PRIMARY KEY,
+                alias TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS parse_quarantine_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                error_threshold INTEGER NOT NULL DEFAULT 20,
+                window_s INTEGER NOT NULL DEFAULT 60,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS runtime_state (
+                id INTEGER PRIMARY KEY,
+                tcp_autostart INTEGER NOT NULL DEFAULT 1,
+                tcp_running INTEGER NOT NULL DEFAULT 0,
+                tcp_port INTEGER NOT NULL DEFAULT 8502,
+                websocket_autostart INTEGER NOT NULL DEFAULT 1,
+                websocket_running INTEGER NOT NULL DEFAULT 0,
+                websocket_config TEXT,
+                updated_at INTEGER NOT NULL
+            );",
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_ws_client_sessions",
+                "message": format!("Erro ao criar tabela ws_client_sessions: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELAS DE FAILOVER DO HISTORIAN (destinos + faixas pendentes de replicação)
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS historian_targets (
+                position INTEGER PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                kind TEXT NOT NULL,
+                host TEXT NOT NULL DEFAULT '',
+                port INTEGER NOT NULL DEFAULT 0,
+                user TEXT NOT NULL DEFAULT '',
+                password TEXT NOT NULL DEFAULT '',
+                database TEXT NOT NULL DEFAULT '',
+                enabled INTEGER NOT NULL DEFAULT 1,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS historian_catchup_windows (
+                target TEXT PRIMARY KEY,
+                from_ms INTEGER NOT NULL,
+                to_ms INTEGER NOT NULL
+            );",
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_historian_targets",
+                "message": format!("Erro ao criar tabelas de failover do historian: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELAS DA GRAVAÇÃO CONTÍNUA NO HISTORIAN (configuração + tags gravados)
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS historian_writer_config (
+                id INTEGER PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 0,
+                flush_interval_ms INTEGER NOT NULL DEFAULT 5000,
+                batch_size INTEGER NOT NULL DEFAULT 1000,
+                max_buffered INTEGER NOT NULL DEFAULT 100000,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS historian_tags (
+                plc_ip TEXT NOT NULL,
+                tag_name TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (plc_ip, tag_name)
+            );",
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_historian_writer",
+                "message": format!("Erro ao criar tabelas da gravação do historian: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELAS DO MOTOR DE ALARMES (definições + ocorrências)
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS alarm_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                plc_ip TEXT NOT NULL,
+                tag_name TEXT NOT NULL,
+                name TEXT NOT NULL,
+                comparison TEXT NOT NULL,
+                threshold REAL NOT NULL,
+                severity TEXT NOT NULL DEFAULT 'warning',
+                deadband REAL NOT NULL DEFAULT 0,
+                on_delay_ms INTEGER NOT NULL DEFAULT 0,
+                off_delay_ms INTEGER NOT NULL DEFAULT 0,
+                message TEXT,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS alarm_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                alarm_id INTEGER NOT NULL,
+                plc_ip TEXT NOT NULL,
+                tag_name TEXT NOT NULL,
+                name TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                message TEXT NOT NULL,
+                raised_value TEXT NOT NULL,
+                raised_at_ms INTEGER NOT NULL,
+                cleared_value TEXT,
+                cleared_at_ms INTEGER,
+                acked_at_ms INTEGER,
+                acked_by TEXT
+            );",
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_alarms",
+                "message": format!("Erro ao criar tabelas de alarmes: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 TABELA DE INCIDENTES CAPTURADOS (arquivos em <pasta do banco>/incidents)
+        if let Err(e) = write_conn_ref.execute(
+            "CREATE TABLE IF NOT EXISTS incidents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                plc_ip TEXT,
+                source_event TEXT NOT NULL,
+                reason TEXT NOT NULL DEFAULT '',
+                notification_id INTEGER,
+                log_lines INTEGER NOT NULL DEFAULT 0,
+                frame_count INTEGER NOT NULL DEFAULT 0,
+                files_dir TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_incidents",
+                "message": format!("Erro ao criar tabela incidents: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // 🆕 EVENTOS DE CONEXÃO DOS PLCs + DISPONIBILIDADE CONSOLIDADA POR DIA
+        if let Err(e) = write_conn_ref.execute_batch(
+            "CREATE TABLE IF NOT EXISTS plc_connection_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                plc_ip TEXT,
+                event TEXT NOT NULL,
+                reason TEXT,
+                ts_ms INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS plc_availability_daily (
+                plc_ip TEXT NOT NULL,
+                day TEXT NOT NULL,
+                up_ms INTEGER NOT NULL DEFAULT 0,
+                down_ms INTEGER NOT NULL DEFAULT 0,
+                disconnections INTEGER NOT NULL DEFAULT 0,
+                repairs INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (plc_ip, day)
+            );"
+        ) {
+            let _ = app_handle.emit("sqlite-error", serde_json::json!({
+                "operation": "create_table_plc_availability",
+                "message": format!("Erro ao criar tabelas de disponibilidade: {}", e),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return Err(e);
+        }
+        // ✅ CRIAR ÍNDICES PARA PERFORMANCE
+        let indexes = [
+            "CREATE INDEX IF NOT EXISTS idx_plc_structures_last_updated ON plc_structures(last_updated DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_tag_mappings_plc_ip ON tag_mappings(plc_ip)",
+            "CREATE INDEX IF NOT EXISTS idx_tag_mappings_enabled ON tag_mappings(enabled)",
+            "CREATE INDEX IF NOT EXISTS idx_tag_mappings_plc_enabled ON tag_mappings(plc_ip, enabled)",
+            "CREATE INDEX IF NOT EXISTS idx_notifications_read_created ON notifications(read, created_at DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_panel_logs_panel_level ON panel_logs(panel_id, level, id DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_incidents_created ON incidents(created_at DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_alarm_definitions_plc_tag ON alarm_definitions(plc_ip, tag_name)",
+            "CREATE INDEX IF NOT EXISTS idx_alarm_history_raised ON alarm_history(raised_at_ms DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_alarm_history_open ON alarm_history(cleared_at_ms, acked_at_ms)",
+            "CREATE INDEX IF NOT EXISTS idx_plc_connection_events_ts ON plc_connection_events(ts_ms)",
+        ];
+        
+        for index_sql in &indexes {
+            if let Err(e) = write_conn_ref.execute(index_sql, []) {
+                println!("⚠️ Aviso: Falha ao criar índice: {} - {}", index_sql, e);
+            }
+        }
+        
+        println!("✅ Banco de dados SQLite OTIMIZADO inicializado com dual connections");
+        
+        Ok(Database {
+            read_conn: Arc::new(Mutex::new(read_conn)),
+            write_conn: Arc::new(Mutex::new(write_conn)),
+            db_path,
+        })
+    }
+    
     /// Salva a configuração de estrutura de um PLC
     pub fn save_plc_structure(&self, config: &PlcStructureConfig) -> Result<()> {
         let conn = self.write_conn.lock().unwrap();
@@ -303,29 +1475,30 @@ impl Database {
                 return Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e)));
             }
         };
+        let profiles_json = serde_json::to_string(&config.profiles)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
         if let Err(e) = conn.execute(
-            "INSERT OR REPLACE INTO plc_structures (plc_ip, config_json, total_size, last_updated)
-             VALUES (?1, ?2, ?3, ?4)",
+            "INSERT OR REPLACE INTO plc_structures (plc_ip, config_json, total_size, last_updated, profiles_json, byte_order, framing)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             (
                 &config.plc_ip,
                 &config_json,
                 config.total_size as i64,
                 config.last_updated,
+                &profiles_json,
+                config.byte_order.as_str(),
+                config.framing.as_str(),
             ),
         ) {
             // Não temos app_handle aqui, então não emitimos
             return Err(e);
         }
-        println!("💾 Configuração salva para PLC {}: {} bytes, {} blocos", 
-                 config.plc_ip, config.total_size, config.blocks.len());
+        println!("💾 Configuração salva para PLC {}: {} bytes, {} blocos, {} perfis, ordem {}, framing {}", 
+                 config.plc_ip, config.total_size, config.blocks.len(), config.profiles.len(), config.byte_order.as_str(), config.framing.as_str());
         // 🔍 DEBUG AUTOMÁTICO: Mostrar o que foi salvo
         println!("🔍 DEBUG - Estrutura salva:");
         for (i, block) in config.blocks.iter().enumerate() {
-            let size_per_element = match block.data_type.as_str() {
-                "WORD" | "INT" => 2,
-                "DWORD" | "REAL" => 4,
-                _ => 1
-            };
+            let size_per_element = crate::plc_parser::data_type_size(&block.data_type).unwrap_or(1) as u32;
             println!("  {}. {} [{}]: {} × {} = {} bytes", 
                 i + 1, block.name, block.data_type, 
                 block.count, size_per_element, 
@@ -340,22 +1513,30 @@ impl Database {
         let conn = self.read_conn.lock().unwrap();
         
         let mut stmt = conn.prepare(
-            "SELECT config_json, total_size, last_updated FROM plc_structures WHERE plc_ip = ?1"
+            "SELECT config_json, total_size, last_updated, profiles_json, COALESCE(byte_order, 'big'), COALESCE(framing, 'fixed') FROM plc_structures WHERE plc_ip = ?1"
         )?;
         
         let result = stmt.query_row([plc_ip], |row| {
             let config_json: String = row.get(0)?;
             let total_size: i64 = row.get(1)?;
             let last_updated: i64 = row.get(2)?;
+            let profiles_json: String = row.get(3).unwrap_or_else(|_| "[]".to_string());
             
             let blocks: Vec<DataBlockConfig> = serde_json::from_str(&config_json)
                 .map_err(|e| rusqlite::Error::InvalidQuery)?;
+            let profiles: Vec<FrameProfile> = serde_json::from_str(&profiles_json)
+                .unwrap_or_default();
+            let byte_order = ByteOrder::parse(&row.get::<_, String>(4)?).unwrap_or_default();
+            let framing = FrameMode::parse(&row.get::<_, String>(5)?).unwrap_or_default();
             
             Ok(PlcStructureConfig {
                 plc_ip: plc_ip.to_string(),
                 blocks,
                 total_size: total_size as usize,
                 last_updated,
+                profiles,
+                byte_order,
+                framing,
             })
         });
         
@@ -381,6 +1562,49 @@ impl Database {
         Ok(plcs)
     }
     
+    /// Lista os PLCs que possuem tag mappings (com ou sem estrutura salva)
+    pub fn list_plcs_with_tags(&self) -> Result<Vec<String>> {
+        let conn = self.read_conn.lock().unwrap();
+        
+        let mut stmt = conn.prepare("SELECT DISTINCT plc_ip FROM tag_mappings ORDER BY plc_ip")?;
+        
+        let plcs = stmt.query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        
+        Ok(plcs)
+    }
+    
+    /// Apelidos definidos (PLCs sem apelido usam o derivado do IP)
+    pub fn list_plc_aliases(&self) -> Result<Vec<PlcAlias>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT plc_ip, alias, updated_at FROM plc_aliases ORDER BY alias")?;
+        let aliases = stmt.query_map([], |row| {
+            Ok(PlcAlias {
+                plc_ip: row.get(0)?,
+                alias: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })?.collect::<Result<Vec<PlcAlias>>>()?;
+        Ok(aliases)
+    }
+    
+    /// Grava o apelido do PLC; apelido já usado por outro PLC falha (UNIQUE)
+    pub fn save_plc_alias(&self, alias: &PlcAlias) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO plc_aliases (plc_ip, alias, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(plc_ip) DO UPDATE SET alias = excluded.alias, updated_at = excluded.updated_at",
+            (&alias.plc_ip, &alias.alias, alias.updated_at),
+        )?;
+        println!("🏷️ PLC {} agora é '{}'", alias.plc_ip, alias.alias);
+        Ok(())
+    }
+    
+    pub fn delete_plc_alias(&self, plc_ip: &str) -> Result<bool> {
+        let conn = self.write_conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM plc_aliases WHERE plc_ip = ?1", [plc_ip])? > 0)
+    }
+    
     /// Remove a configuração de um PLC
     pub fn delete_plc_structure(&self, plc_ip: &str) -> Result<()> {
         let conn = self.write_conn.lock().unwrap();
@@ -456,11 +1680,13 @@ impl Database {
     pub fn save_tag_mapping(&self, tag: &TagMapping) -> Result<i64> {
         let conn = self.write_conn.lock().unwrap();
         
+        Self::record_tag_unit_version(&conn, &tag.plc_ip, &tag.tag_name, tag.unit.as_deref(), tag.display_unit.as_deref())?;
         let _result = conn.execute(
             "INSERT OR REPLACE INTO tag_mappings 
-             (plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-            (
+             (plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category, min_resend_ms, debounce_ms, display_unit, critical,
+              raw_min, raw_max, eng_min, eng_max, scale_offset, deadband_pct, display_decimals)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+            rusqlite::params![
                 &tag.plc_ip,
                 &tag.variable_path,
                 &tag.tag_name,
@@ -472,7 +1698,18 @@ impl Database {
                 &tag.collect_interval_s,
                 &tag.area,
                 &tag.category,
-            ),
+                &tag.min_resend_ms,
+                &tag.debounce_ms,
+                &tag.display_unit,
+                tag.critical as i32,
+                &tag.raw_min,
+                &tag.raw_max,
+                &tag.eng_min,
+                &tag.eng_max,
+                &tag.scale_offset,
+                &tag.deadband_pct,
+                &tag.display_decimals,
+            ],
         )?;
         
         let tag_id = conn.last_insert_rowid();
@@ -481,31 +1718,43 @@ impl Database {
         Ok(tag_id)
     }
     
+    fn tag_mapping_from_row(row: &rusqlite::Row) -> Result<TagMapping> {
+        Ok(TagMapping {
+            id: Some(row.get(0)?),
+            plc_ip: row.get(1)?,
+            variable_path: row.get(2)?,
+            tag_name: row.get(3)?,
+            description: row.get(4)?,
+            unit: row.get(5)?,
+            enabled: row.get::<usize, i32>(6)? == 1,
+            created_at: row.get(7)?,
+            collect_mode: row.get(8).ok(),
+            collect_interval_s: row.get(9).ok(),
+            area: row.get(10).ok(),
+            category: row.get(11).ok(),
+            min_resend_ms: row.get(12).ok(),
+            debounce_ms: row.get(13).ok(),
+            display_unit: row.get(14).ok(),
+            critical: row.get::<usize, i32>(15).unwrap_or(0) == 1,
+            raw_min: row.get(16).unwrap_or(None),
+            raw_max: row.get(17).unwrap_or(None),
+            eng_min: row.get(18).unwrap_or(None),
+            eng_max: row.get(19).unwrap_or(None),
+            scale_offset: row.get(20).unwrap_or(None),
+            deadband_pct: row.get(21).unwrap_or(None),
+            display_decimals: row.get(22).unwrap_or(None),
+        })
+    }
+    
     /// Carrega todos os tags de um PLC
     pub fn load_tag_mappings(&self, plc_ip: &str) -> Result<Vec<TagMapping>> {
         let conn = self.read_conn.lock().unwrap();
         
-        let mut stmt = conn.prepare(
-            "SELECT id, plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category 
-             FROM tag_mappings WHERE plc_ip = ?1 ORDER BY variable_path"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tag_mappings WHERE plc_ip = ?1 ORDER BY variable_path", TAG_MAPPING_COLUMNS
+        ))?;
 
-        let tag_iter = stmt.query_map([plc_ip], |row| {
-            Ok(TagMapping {
-                id: Some(row.get(0)?),
-                plc_ip: row.get(1)?,
-                variable_path: row.get(2)?,
-                tag_name: row.get(3)?,
-                description: row.get(4)?,
-                unit: row.get(5)?,
-                enabled: row.get::<usize, i32>(6)? == 1,
-                created_at: row.get(7)?,
-                collect_mode: row.get(8).ok(),
-                collect_interval_s: row.get(9).ok(),
-                area: row.get(10).ok(),
-                category: row.get(11).ok(),
-            })
-        })?;
+        let tag_iter = stmt.query_map([plc_ip], Self::tag_mapping_from_row)?;
         
         let tags: Result<Vec<TagMapping>> = tag_iter.collect();
         let tags = tags?;
@@ -531,29 +1780,34 @@ impl Database {
         Ok(())
     }
 
-    /// Salva múltiplos tags de uma vez (Bulk Save) - OTIMIZADO para evitar travamento do cache
-    pub fn save_tag_mappings_bulk(&self, tags: &[TagMapping]) -> Result<Vec<i64>> {
+    /// Salva múltiplos tags em uma única transação (tudo ou nada).
+    /// `pending` são os resultados já calculados pelo chamador (ex: validação);
+    /// se algum item falhar, nada é gravado e os demais ficam como "rolled_back".
+    pub fn save_tag_mappings_bulk(&self, tags: &[(usize, TagMapping)], mut pending: Vec<TagItemResult>) -> Result<TagBatchResult> {
         let mut conn = self.write_conn.lock().unwrap();
         
-        if tags.is_empty() {
-            return Ok(vec![]);
+        if tags.is_empty() || pending.iter().any(|r| r.status == "failed") {
+            pending.extend(tags.iter().map(|(index, tag)| TagItemResult::for_tag(*index, tag, "rolled_back", None, None)));
+            return Ok(TagBatchResult::finish(false, pending));
         }
         
-        let mut tag_ids = Vec::new();
-        let mut successful_count = 0;
-        
-        // Usar transação para performance e atomicidade
         let tx = conn.transaction()?;
         
         {
             let mut stmt = tx.prepare(
                 "INSERT OR REPLACE INTO tag_mappings 
-                 (plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+                 (plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category, min_resend_ms, debounce_ms, display_unit, critical,
+                  raw_min, raw_max, eng_min, eng_max, scale_offset, deadband_pct, display_decimals)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)"
             )?;
             
-            for tag in tags {
-                match stmt.execute((
+            for (index, tag) in tags {
+                if let Err(e) = Self::record_tag_unit_version(&tx, &tag.plc_ip, &tag.tag_name, tag.unit.as_deref(), tag.display_unit.as_deref()) {
+                    println!("⚠️ Erro ao versionar unidade do tag '{}': {}", tag.tag_name, e);
+                    pending.push(TagItemResult::for_tag(*index, tag, "failed", None, Some(e.to_string())));
+                    continue;
+                }
+                match stmt.execute(rusqlite::params![
                     &tag.plc_ip,
                     &tag.variable_path,
                     &tag.tag_name,
@@ -565,69 +1819,345 @@ impl Database {
                     &tag.collect_interval_s,
                     &tag.area,
                     &tag.category,
-                )) {
-                    Ok(_) => {
-                        let tag_id = tx.last_insert_rowid();
-                        tag_ids.push(tag_id);
-                        successful_count += 1;
-                    }
+                    &tag.min_resend_ms,
+                    &tag.debounce_ms,
+                    &tag.display_unit,
+                    tag.critical as i32,
+                    &tag.raw_min,
+                    &tag.raw_max,
+                    &tag.eng_min,
+                    &tag.eng_max,
+                    &tag.scale_offset,
+                    &tag.deadband_pct,
+                    &tag.display_decimals,
+                ]) {
+                    Ok(_) => pending.push(TagItemResult::for_tag(*index, tag, "saved", Some(tx.last_insert_rowid()), None)),
                     Err(e) => {
                         println!("⚠️ Erro ao salvar tag '{}': {}", tag.tag_name, e);
-                        tag_ids.push(-1); // Indica erro
+                        pending.push(TagItemResult::for_tag(*index, tag, "failed", None, Some(e.to_string())));
                     }
                 }
             }
         }
         
-        tx.commit()?;
-        
-        println!("💾 Bulk Save: {}/{} tags salvos com sucesso", successful_count, tags.len());
+        let committed = !pending.iter().any(|r| r.status == "failed");
+        if committed {
+            tx.commit()?;
+        } else {
+            tx.rollback()?;
+        }
         
-        Ok(tag_ids)
+        let result = TagBatchResult::finish(committed, pending);
+        println!("💾 Bulk Save: {} tags {}", tags.len(), if committed { "gravados" } else { "revertidos (lote com erro)" });
+        Ok(result)
     }
 
-    /// Remove múltiplos tags de uma vez (Bulk Delete)
-    pub fn delete_tag_mappings_bulk(&self, ids: Vec<i64>) -> Result<()> {
+    /// Remove múltiplos tags em uma única transação (tudo ou nada)
+    pub fn delete_tag_mappings_bulk(&self, ids: Vec<i64>) -> Result<TagBatchResult> {
         let mut conn = self.write_conn.lock().unwrap();
         let tx = conn.transaction()?;
+        let mut results = Vec::with_capacity(ids.len());
         
         {
             let mut stmt = tx.prepare("DELETE FROM tag_mappings WHERE id = ?")?;
-            for id in &ids {
-                stmt.execute([id])?;
+            for (index, id) in ids.iter().enumerate() {
+                let (status, error) = match stmt.execute([id]) {
+                    Ok(0) => ("failed", Some(format!("Tag ID {} não encontrado", id))),
+                    Ok(_) => ("deleted", None),
+                    Err(e) => ("failed", Some(e.to_string())),
+                };
+                results.push(TagItemResult {
+                    index,
+                    tag_name: None,
+                    variable_path: None,
+                    id: Some(*id),
+                    status: status.to_string(),
+                    error,
+                });
             }
         }
         
-        tx.commit()?;
-        println!("🗑️ Bulk Delete: {} tags removidos com sucesso.", ids.len());
-        Ok(())
+        let committed = !results.iter().any(|r| r.status == "failed");
+        if committed {
+            tx.commit()?;
+            println!("🗑️ Bulk Delete: {} tags removidos com sucesso.", ids.len());
+        } else {
+            tx.rollback()?;
+            println!("⚠️ Bulk Delete revertido: lote com IDs inválidos");
+        }
+        Ok(TagBatchResult::finish(committed, results))
     }
     
-    /// Lista todos os tags ativos (enabled=true) de um PLC para o WebSocket
-    pub fn get_active_tags(&self, plc_ip: &str) -> Result<Vec<TagMapping>> {
+    /// Grava nova versão da unidade do tag se unit/display_unit mudou em relação à
+    /// última versão. Tag sem versão (novo) começa em 0. Retorna se gravou.
+    fn record_tag_unit_version(conn: &Connection, plc_ip: &str, tag_name: &str, unit: Option<&str>, display_unit: Option<&str>) -> Result<bool> {
+        let unit = unit.map(str::trim).filter(|u| !u.is_empty());
+        let display_unit = display_unit.map(str::trim).filter(|u| !u.is_empty());
+        let latest: Option<(Option<String>, Option<String>)> = match conn.query_row(
+            "SELECT unit, display_unit FROM tag_unit_versions WHERE plc_ip = ?1 AND tag_name = ?2
+             ORDER BY effective_from_ms DESC, id DESC LIMIT 1",
+            [plc_ip, tag_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(latest) => Some(latest),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+        if let Some((last_unit, last_display)) = &latest {
+            if last_unit.as_deref() == unit && last_display.as_deref() == display_unit {
+                return Ok(false);
+            }
+        }
+
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let effective_from_ms = if latest.is_some() { now_ms } else { 0 };
+        conn.execute(
+            "INSERT INTO tag_unit_versions (plc_ip, tag_name, unit, display_unit, effective_from_ms, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (plc_ip, tag_name, unit, display_unit, effective_from_ms, now_ms / 1000),
+        )?;
+        if latest.is_some() {
+            println!("📐 Unidade de {}:{} alterada para {:?} → {:?} (histórico anterior mantém a versão antiga)",
+                plc_ip, tag_name, unit, display_unit);
+        }
+        Ok(true)
+    }
+    
+    /// Versões da unidade (mais antigas primeiro), filtradas por PLC e tag
+    pub fn list_tag_unit_versions(&self, plc_ip: Option<&str>, tag_name: Option<&str>) -> Result<Vec<TagUnitVersion>> {
         let conn = self.read_conn.lock().unwrap();
-        
         let mut stmt = conn.prepare(
-            "SELECT id, plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category 
-             FROM tag_mappings WHERE plc_ip = ?1 AND enabled = 1 ORDER BY tag_name"
+            "SELECT id, plc_ip, tag_name, unit, display_unit, effective_from_ms FROM tag_unit_versions
+             WHERE (?1 IS NULL OR plc_ip = ?1) AND (?2 IS NULL OR tag_name = ?2)
+             ORDER BY plc_ip, tag_name, effective_from_ms, id"
         )?;
-
-        let tag_iter = stmt.query_map([plc_ip], |row| {
-            Ok(TagMapping {
-                id: Some(row.get(0)?),
+        let versions = stmt.query_map((plc_ip, tag_name), |row| {
+            Ok(TagUnitVersion {
+                id: row.get(0)?,
                 plc_ip: row.get(1)?,
-                variable_path: row.get(2)?,
-                tag_name: row.get(3)?,
-                description: row.get(4)?,
-                unit: row.get(5)?,
-                enabled: true,
-                created_at: row.get(7)?,
-                collect_mode: row.get(8).ok(),
-                collect_interval_s: row.get(9).ok(),
-                area: row.get(10).ok(),
-                category: row.get(11).ok(),
+                tag_name: row.get(2)?,
+                unit: row.get(3)?,
+                display_unit: row.get(4)?,
+                effective_from_ms: row.get(5)?,
             })
-        })?;
+        })?.collect::<Result<Vec<TagUnitVersion>>>()?;
+        Ok(versions)
+    }
+    
+    /// Copia estrutura (com perfis) e tag mappings de `source_ip` para `target_ip`
+    /// em uma transação. Com `overwrite`, a configuração existente do destino é
+    /// substituída. Retorna o número de tags copiados.
+    pub fn clone_plc_config(&self, source_ip: &str, target_ip: &str, overwrite: bool) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now().timestamp();
+        
+        if overwrite {
+            tx.execute("DELETE FROM plc_structures WHERE plc_ip = ?1", [target_ip])?;
+            tx.execute("DELETE FROM tag_mappings WHERE plc_ip = ?1", [target_ip])?;
+            tx.execute("DELETE FROM alarm_definitions WHERE plc_ip = ?1", [target_ip])?;
+        }
+        
+        let structures = tx.execute(
+            "INSERT INTO plc_structures (plc_ip, config_json, total_size, last_updated, profiles_json, byte_order, framing)
+             SELECT ?1, config_json, total_size, ?2, profiles_json, byte_order, framing FROM plc_structures WHERE plc_ip = ?3",
+            (target_ip, now, source_ip),
+        )?;
+        if structures == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        
+        let tags = tx.execute(
+            "INSERT INTO tag_mappings 
+             (plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category, min_resend_ms, debounce_ms, display_unit, critical,
+              raw_min, raw_max, eng_min, eng_max, scale_offset, deadband_pct, display_decimals)
+             SELECT ?1, variable_path, tag_name, description, unit, enabled, ?2, collect_mode, collect_interval_s, area, category, min_resend_ms, debounce_ms, display_unit, critical,
+                    raw_min, raw_max, eng_min, eng_max, scale_offset, deadband_pct, display_decimals
+             FROM tag_mappings WHERE plc_ip = ?3",
+            (target_ip, now, source_ip),
+        )?;
+        
+        // 🆕 Unidade dos tags copiados: nova versão no destino quando diferente
+        let copied: Vec<(String, Option<String>, Option<String>)> = {
+            let mut stmt = tx.prepare("SELECT tag_name, unit, display_unit FROM tag_mappings WHERE plc_ip = ?1")?;
+            let rows = stmt.query_map([target_ip], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect::<Result<Vec<_>>>()?
+        };
+        for (tag_name, unit, display_unit) in &copied {
+            Self::record_tag_unit_version(&tx, target_ip, tag_name, unit.as_deref(), display_unit.as_deref())?;
+        }
+        
+        // 🆕 Alarmes seguem os tags
+        let alarms = tx.execute(
+            "INSERT INTO alarm_definitions
+             (plc_ip, tag_name, name, comparison, threshold, severity, deadband, on_delay_ms, off_delay_ms, message, enabled, updated_at)
+             SELECT ?1, tag_name, name, comparison, threshold, severity, deadband, on_delay_ms, off_delay_ms, message, enabled, ?2
+             FROM alarm_definitions WHERE plc_ip = ?3",
+            (target_ip, now, source_ip),
+        )?;
+        
+        tx.commit()?;
+        println!("📋 Configuração de {} clonada para {}: estrutura + {} tags + {} alarmes", source_ip, target_ip, tags, alarms);
+        Ok(tags)
+    }
+    
+    /// Move tudo que está sob `old_ip` para `new_ip` em uma transação: estrutura,
+    /// tags, versões de unidade, historian, alarmes (definições e ocorrências),
+    /// taxa esperada, incidentes e colunas do logger CSV. Se o novo IP já tiver
+    /// configuração com a mesma chave, a do IP antigo prevalece. Com `dry_run`
+    /// a transação é revertida e só as contagens são devolvidas.
+    pub fn migrate_plc_identity(&self, old_ip: &str, new_ip: &str, dry_run: bool) -> Result<PlcIdentityMigration> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut report = PlcIdentityMigration {
+            old_ip: old_ip.to_string(),
+            new_ip: new_ip.to_string(),
+            dry_run,
+            ..Default::default()
+        };
+        
+        report.replaced_on_new = tx.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM plc_structures n WHERE n.plc_ip = ?2
+                    AND EXISTS (SELECT 1 FROM plc_structures o WHERE o.plc_ip = ?1))
+              + (SELECT COUNT(*) FROM tag_mappings n WHERE n.plc_ip = ?2
+                    AND EXISTS (SELECT 1 FROM tag_mappings o WHERE o.plc_ip = ?1 AND o.variable_path = n.variable_path))
+              + (SELECT COUNT(*) FROM historian_tags n WHERE n.plc_ip = ?2
+                    AND EXISTS (SELECT 1 FROM historian_tags o WHERE o.plc_ip = ?1 AND o.tag_name = n.tag_name))
+              + (SELECT COUNT(*) FROM plc_rate_expectations n WHERE n.plc_ip = ?2
+                    AND EXISTS (SELECT 1 FROM plc_rate_expectations o WHERE o.plc_ip = ?1))",
+            [old_ip, new_ip],
+            |row| row.get::<usize, i64>(0),
+        )? as usize;
+        
+        // Tabelas com chave única por PLC: UPDATE OR REPLACE (o antigo prevalece)
+        report.structures = tx.execute("UPDATE OR REPLACE plc_structures SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.tag_mappings = tx.execute("UPDATE OR REPLACE tag_mappings SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.historian_tags = tx.execute("UPDATE OR REPLACE historian_tags SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.rate_expectations = tx.execute("UPDATE OR REPLACE plc_rate_expectations SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        // Apelido acompanha o PLC (nomes qualificados dos tags não mudam)
+        tx.execute("UPDATE OR REPLACE plc_aliases SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        
+        // Versões de unidade: para tags presentes nos dois, valem as do antigo
+        tx.execute(
+            "DELETE FROM tag_unit_versions WHERE plc_ip = ?2 AND tag_name IN (SELECT tag_name FROM tag_unit_versions WHERE plc_ip = ?1)",
+            [old_ip, new_ip],
+        )?;
+        report.tag_unit_versions = tx.execute("UPDATE tag_unit_versions SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.alarm_definitions = tx.execute("UPDATE alarm_definitions SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.alarm_history = tx.execute("UPDATE alarm_history SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.incidents = tx.execute("UPDATE incidents SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        report.availability = tx.execute("UPDATE plc_connection_events SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?
+            + tx.execute("UPDATE OR REPLACE plc_availability_daily SET plc_ip = ?2 WHERE plc_ip = ?1", [old_ip, new_ip])?;
+        
+        // Colunas do logger CSV ("plc_ip:tag_name")
+        let tags_json: Option<String> = tx.query_row(
+            "SELECT tags_json FROM csv_logger_config WHERE id = 1", [], |row| row.get(0),
+        ).ok();
+        if let Some(tags_json) = tags_json {
+            let old_prefix = format!("{}:", old_ip);
+            let mut tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+            for tag in tags.iter_mut().filter(|t| t.starts_with(&old_prefix)) {
+                *tag = format!("{}:{}", new_ip, &tag[old_prefix.len()..]);
+                report.csv_logger_columns += 1;
+            }
+            if report.csv_logger_columns > 0 {
+                tx.execute(
+                    "UPDATE csv_logger_config SET tags_json = ?1 WHERE id = 1",
+                    [serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string())],
+                )?;
+            }
+        }
+        
+        if dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+            println!("🔀 Identidade do PLC migrada: {} → {} ({} linhas locais, {} substituídas no destino)",
+                old_ip, new_ip, report.local_rows(), report.replaced_on_new);
+        }
+        Ok(report)
+    }
+    
+    /// Renomeia um tag e todas as referências locais em uma transação:
+    /// tag_mappings, edge tags (RISE/FALL), historian, alarmes e colunas do logger CSV.
+    /// Retorna o número de referências atualizadas (além do próprio tag).
+    pub fn rename_tag(&self, plc_ip: &str, old_name: &str, new_name: &str) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        
+        let renamed = tx.execute(
+            "UPDATE tag_mappings SET tag_name = ?1 WHERE plc_ip = ?2 AND tag_name = ?3",
+            [new_name, plc_ip, old_name],
+        )?;
+        if renamed == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        
+        let mut references = 0;
+        for edge in ["RISE", "FALL"] {
+            references += tx.execute(
+                "UPDATE tag_mappings SET variable_path = ?1 WHERE plc_ip = ?2 AND variable_path = ?3",
+                [
+                    format!("{}({})", edge, new_name),
+                    plc_ip.to_string(),
+                    format!("{}({})", edge, old_name),
+                ],
+            )?;
+        }
+        
+        // 🆕 Gravação no historian segue o tag
+        references += tx.execute(
+            "UPDATE historian_tags SET tag_name = ?1 WHERE plc_ip = ?2 AND tag_name = ?3",
+            [new_name, plc_ip, old_name],
+        )?;
+        
+        // 🆕 Versões da unidade seguem o tag (o histórico também é renomeado)
+        tx.execute(
+            "UPDATE tag_unit_versions SET tag_name = ?1 WHERE plc_ip = ?2 AND tag_name = ?3",
+            [new_name, plc_ip, old_name],
+        )?;
+        
+        // 🆕 Definições de alarme seguem o tag (ocorrências antigas mantêm o nome da época)
+        references += tx.execute(
+            "UPDATE alarm_definitions SET tag_name = ?1 WHERE plc_ip = ?2 AND tag_name = ?3",
+            [new_name, plc_ip, old_name],
+        )?;
+        
+        let tags_json: Option<String> = tx.query_row(
+            "SELECT tags_json FROM csv_logger_config WHERE id = 1", [], |row| row.get(0),
+        ).ok();
+        if let Some(tags_json) = tags_json {
+            let old_key = format!("{}:{}", plc_ip, old_name);
+            let mut tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+            let mut changed = false;
+            for tag in tags.iter_mut().filter(|t| **t == old_key) {
+                *tag = format!("{}:{}", plc_ip, new_name);
+                changed = true;
+                references += 1;
+            }
+            if changed {
+                tx.execute(
+                    "UPDATE csv_logger_config SET tags_json = ?1 WHERE id = 1",
+                    [serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string())],
+                )?;
+            }
+        }
+        
+        tx.commit()?;
+        println!("✏️ Tag renomeado: {} '{}' → '{}' ({} referências atualizadas)", plc_ip, old_name, new_name, references);
+        Ok(references)
+    }
+    
+    /// Lista todos os tags ativos (enabled=true) de um PLC para o WebSocket
+    pub fn get_active_tags(&self, plc_ip: &str) -> Result<Vec<TagMapping>> {
+        let conn = self.read_conn.lock().unwrap();
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tag_mappings WHERE plc_ip = ?1 AND enabled = 1 ORDER BY tag_name", TAG_MAPPING_COLUMNS
+        ))?;
+
+        let tag_iter = stmt.query_map([plc_ip], Self::tag_mapping_from_row)?;
         
         let tags: Result<Vec<TagMapping>> = tag_iter.collect();
         tags
@@ -638,9 +2168,8 @@ impl Database {
         let conn = self.read_conn.lock().unwrap();
         
         // Construir query dinâmica baseada nos filtros
-        let mut sql = String::from(
-            "SELECT id, plc_ip, variable_path, tag_name, description, unit, enabled, created_at, collect_mode, collect_interval_s, area, category 
-             FROM tag_mappings WHERE plc_ip = ?1 AND enabled = 1"
+        let mut sql = format!(
+            "SELECT {} FROM tag_mappings WHERE plc_ip = ?1 AND enabled = 1", TAG_MAPPING_COLUMNS
         );
         
         let has_area_filter = areas.as_ref().map(|a| !a.is_empty()).unwrap_or(false);
@@ -680,22 +2209,7 @@ impl Database {
         
         let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
         
-        let tag_iter = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(TagMapping {
-                id: Some(row.get(0)?),
-                plc_ip: row.get(1)?,
-                variable_path: row.get(2)?,
-                tag_name: row.get(3)?,
-                description: row.get(4)?,
-                unit: row.get(5)?,
-                enabled: true,
-                created_at: row.get(7)?,
-                collect_mode: row.get(8).ok(),
-                collect_interval_s: row.get(9).ok(),
-                area: row.get(10).ok(),
-                category: row.get(11).ok(),
-            })
-        })?;
+        let tag_iter = stmt.query_map(params_refs.as_slice(), Self::tag_mapping_from_row)?;
         
         let tags: Result<Vec<TagMapping>> = tag_iter.collect();
         let result = tags?;
@@ -704,6 +2218,95 @@ impl Database {
         Ok(result)
     }
     
+    // ============================================================================
+    // CHECKSUM DA CONFIGURAÇÃO (DETECÇÃO DE DIVERGÊNCIA ENTRE HMIs)
+    // ============================================================================
+    
+    /// Calcula um hash determinístico (FNV-1a 64) da configuração ativa:
+    /// estruturas de PLC e tag mappings, sempre na mesma ordem.
+    pub fn compute_config_checksum(&self) -> Result<String> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut canonical = String::new();
+        
+        {
+            let mut stmt = conn.prepare(
+                "SELECT plc_ip, config_json, total_size, COALESCE(profiles_json, '[]'), COALESCE(byte_order, 'big'), COALESCE(framing, 'fixed') FROM plc_structures ORDER BY plc_ip"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(format!("S|{}|{}|{}|{}|{}|{}\n",
+                    row.get::<usize, String>(0)?,
+                    row.get::<usize, String>(1)?,
+                    row.get::<usize, i64>(2)?,
+                    row.get::<usize, String>(3)?,
+                    row.get::<usize, String>(4)?,
+                    row.get::<usize, String>(5)?))
+            })?;
+            for row in rows {
+                canonical.push_str(&row?);
+            }
+        }
+        
+        {
+            let mut stmt = conn.prepare(
+                "SELECT plc_ip, variable_path, tag_name, COALESCE(unit, ''), enabled, COALESCE(collect_mode, ''),
+                        COALESCE(collect_interval_s, 0), COALESCE(area, ''), COALESCE(category, ''),
+                        COALESCE(min_resend_ms, 0), COALESCE(debounce_ms, 0), COALESCE(display_unit, ''), COALESCE(critical, 0),
+                        COALESCE(raw_min, '') || '|' || COALESCE(raw_max, '') || '|' || COALESCE(eng_min, '') || '|' ||
+                        COALESCE(eng_max, '') || '|' || COALESCE(scale_offset, '') || '|' || COALESCE(deadband_pct, '') || '|' ||
+                        COALESCE(display_decimals, '')
+                 FROM tag_mappings ORDER BY plc_ip, variable_path"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(format!("T|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}\n",
+                    row.get::<usize, String>(0)?,
+                    row.get::<usize, String>(1)?,
+                    row.get::<usize, String>(2)?,
+                    row.get::<usize, String>(3)?,
+                    row.get::<usize, i64>(4)?,
+                    row.get::<usize, String>(5)?,
+                    row.get::<usize, i64>(6)?,
+                    row.get::<usize, String>(7)?,
+                    row.get::<usize, String>(8)?,
+                    row.get::<usize, i64>(9)?,
+                    row.get::<usize, i64>(10)?,
+                    row.get::<usize, String>(11)?,
+                    row.get::<usize, i64>(12)?,
+                    row.get::<usize, String>(13)?))
+            })?;
+            for row in rows {
+                canonical.push_str(&row?);
+            }
+        }
+        
+        // 🆕 Definições de alarme (sem id/updated_at: iguais em HMIs diferentes)
+        {
+            let mut stmt = conn.prepare(
+                "SELECT plc_ip, tag_name, name, comparison, threshold, severity, deadband, on_delay_ms, off_delay_ms,
+                        COALESCE(message, ''), enabled
+                 FROM alarm_definitions ORDER BY plc_ip, tag_name, name"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(format!("A|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}\n",
+                    row.get::<usize, String>(0)?,
+                    row.get::<usize, String>(1)?,
+                    row.get::<usize, String>(2)?,
+                    row.get::<usize, String>(3)?,
+                    row.get::<usize, f64>(4)?,
+                    row.get::<usize, String>(5)?,
+                    row.get::<usize, f64>(6)?,
+                    row.get::<usize, i64>(7)?,
+                    row.get::<usize, i64>(8)?,
+                    row.get::<usize, String>(9)?,
+                    row.get::<usize, i64>(10)?))
+            })?;
+            for row in rows {
+                canonical.push_str(&row?);
+            }
+        }
+        
+        Ok(format!("{:016x}", fnv1a_64(canonical.as_bytes())))
+    }
+    
     // ============================================================================
     // MÉTODOS PARA CONFIGURAÇÕES WEBSOCKET
     // ============================================================================
@@ -786,4 +2389,1561 @@ impl Database {
             Err(e) => Err(e),
         }
     }
+    
+    // ============================================================================
+    // MÉTODOS PARA HEALTH CHECK
+    // ============================================================================
+    
+    /// Verifica se o SQLite responde (usado pelo /healthz)
+    pub fn ping(&self) -> Result<()> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.query_row("SELECT 1", [], |row| row.get::<usize, i64>(0))?;
+        Ok(())
+    }
+    
+    pub fn save_health_config(&self, config: &HealthConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO health_config 
+             (id, enabled, bind_host, port, max_data_age_ms, require_tcp_server, require_websocket, require_plc_data, check_postgres, updated_at)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                config.enabled as i32,
+                &config.bind_host,
+                config.port as i64,
+                config.max_data_age_ms as i64,
+                config.require_tcp_server as i32,
+                config.require_websocket as i32,
+                config.require_plc_data as i32,
+                config.check_postgres as i32,
+                config.updated_at,
+            ),
+        )?;
+        println!("💾 Configuração do health check salva: {}:{} (enabled: {})", config.bind_host, config.port, config.enabled);
+        Ok(())
+    }
+    
+    pub fn load_health_config(&self) -> Result<HealthConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, bind_host, port, max_data_age_ms, require_tcp_server, require_websocket, require_plc_data, check_postgres, updated_at
+             FROM health_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(HealthConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    bind_host: row.get(1)?,
+                    port: row.get::<usize, i64>(2)? as u16,
+                    max_data_age_ms: row.get::<usize, i64>(3)? as u64,
+                    require_tcp_server: row.get::<usize, i32>(4)? == 1,
+                    require_websocket: row.get::<usize, i32>(5)? == 1,
+                    require_plc_data: row.get::<usize, i32>(6)? == 1,
+                    check_postgres: row.get::<usize, i32>(7)? == 1,
+                    updated_at: row.get(8)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(HealthConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA PRIORIDADE DE GRUPOS DE TAGS
+    // ============================================================================
+    
+    pub fn list_tag_group_priorities(&self) -> Result<Vec<TagGroupPriority>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT group_type, group_name, priority FROM tag_group_priorities ORDER BY group_type, group_name"
+        )?;
+        let priorities = stmt.query_map([], |row| {
+            Ok(TagGroupPriority {
+                group_type: row.get(0)?,
+                group_name: row.get(1)?,
+                priority: row.get::<usize, i64>(2)?.clamp(0, u8::MAX as i64) as u8,
+            })
+        })?;
+        priorities.collect()
+    }
+    
+    pub fn save_tag_group_priority(&self, priority: &TagGroupPriority) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO tag_group_priorities (group_type, group_name, priority, updated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            (&priority.group_type, &priority.group_name, priority.priority as i64, chrono::Utc::now().timestamp()),
+        )?;
+        println!("💾 Prioridade do grupo {}:{} = {}", priority.group_type, priority.group_name, priority.priority);
+        Ok(())
+    }
+    
+    pub fn delete_tag_group_priority(&self, group_type: &str, group_name: &str) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM tag_group_priorities WHERE group_type = ?1 AND group_name = ?2",
+            [group_type, group_name],
+        )?;
+        Ok(())
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA CONFIGURAÇÃO DO LOGGER CSV
+    // ============================================================================
+    
+    /// Salva configuração do logger CSV
+    pub fn save_csv_logger_config(&self, config: &CsvLoggerConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        
+        let tags_json = serde_json::to_string(&config.tags).unwrap_or_else(|_| "[]".to_string());
+        
+        conn.execute(
+            "INSERT OR REPLACE INTO csv_logger_config 
+             (id, enabled, output_dir, file_name_template, interval_ms, rotate_max_mb, flush_every_rows, delimiter, tags_json, updated_at)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                config.enabled as i32,
+                &config.output_dir,
+                &config.file_name_template,
+                config.interval_ms as i64,
+                config.rotate_max_mb as i64,
+                config.flush_every_rows as i64,
+                &config.delimiter,
+                &tags_json,
+                config.updated_at,
+            ),
+        )?;
+        
+        println!("💾 Configuração do logger CSV salva: {} ({} tags)", config.output_dir, config.tags.len());
+        Ok(())
+    }
+    
+    /// Carrega configuração do logger CSV (padrão se ainda não existir)
+    pub fn load_csv_logger_config(&self) -> Result<CsvLoggerConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        
+        let result = conn.query_row(
+            "SELECT enabled, output_dir, file_name_template, interval_ms, rotate_max_mb, flush_every_rows, delimiter, tags_json, updated_at
+             FROM csv_logger_config WHERE id = 1",
+            [],
+            |row| {
+                let tags_json: String = row.get(7).unwrap_or_else(|_| "[]".to_string());
+                
+                Ok(CsvLoggerConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    output_dir: row.get(1)?,
+                    file_name_template: row.get(2)?,
+                    interval_ms: row.get::<usize, i64>(3)? as u64,
+                    rotate_max_mb: row.get::<usize, i64>(4)? as u64,
+                    flush_every_rows: row.get::<usize, i64>(5)? as u32,
+                    delimiter: row.get(6)?,
+                    tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+                    updated_at: row.get(8)?,
+                })
+            },
+        );
+        
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(CsvLoggerConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    // ============================================================================
+    // BACKUP / RESTORE DO BANCO DE CONFIGURAÇÃO
+    // ============================================================================
+    
+    /// Caminho do arquivo SQLite em uso
+    // ============================================================================
+    // MÉTODOS PARA PAINÉIS REMOTOS (plc-app)
+    // ============================================================================
+    
+    pub fn upsert_panel_heartbeat(&self, heartbeat: &PanelHeartbeat, address: &str) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        conn.execute(
+            "INSERT INTO panels (panel_id, hostname, address, app_version, plc_connected, last_data_age_s, uptime_s, last_heartbeat, first_seen)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+             ON CONFLICT(panel_id) DO UPDATE SET
+                hostname = excluded.hostname,
+                address = excluded.address,
+                app_version = excluded.app_version,
+                plc_connected = excluded.plc_connected,
+                last_data_age_s = excluded.last_data_age_s,
+                uptime_s = excluded.uptime_s,
+                last_heartbeat = excluded.last_heartbeat",
+            (
+                &heartbeat.panel_id,
+                &heartbeat.hostname,
+                address,
+                &heartbeat.app_version,
+                heartbeat.plc_connected as i32,
+                heartbeat.last_data_age_s.map(|a| a as i64),
+                heartbeat.uptime_s as i64,
+                now,
+            ),
+        )?;
+        Ok(())
+    }
+    
+    /// Grava um lote de logs do painel (reenvios são ignorados pelo UNIQUE)
+    pub fn insert_panel_logs(&self, panel_id: &str, logs: &[PanelLogEntry]) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut inserted = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO panel_logs (panel_id, remote_id, timestamp, level, category, message, details, received_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            )?;
+            for log in logs {
+                inserted += stmt.execute((panel_id, log.id, &log.timestamp, &log.level, &log.category, &log.message, &log.details, now))?;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+    
+    pub fn list_panels(&self, offline_after_ms: i64) -> Result<Vec<PanelStatus>> {
+        let conn = self.read_conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut stmt = conn.prepare(
+            "SELECT p.panel_id, p.hostname, p.address, p.app_version, p.plc_connected, p.last_data_age_s, p.uptime_s,
+                    p.last_heartbeat, p.first_seen,
+                    COALESCE((SELECT MAX(remote_id) FROM panel_logs l WHERE l.panel_id = p.panel_id), 0)
+             FROM panels p ORDER BY p.panel_id"
+        )?;
+        let panels = stmt.query_map([], |row| {
+            let last_heartbeat: i64 = row.get(7)?;
+            Ok(PanelStatus {
+                panel_id: row.get(0)?,
+                hostname: row.get(1)?,
+                address: row.get(2)?,
+                app_version: row.get(3)?,
+                plc_connected: row.get::<usize, i32>(4)? == 1,
+                last_data_age_s: row.get::<usize, Option<i64>>(5)?.map(|a| a.max(0) as u64),
+                uptime_s: row.get::<usize, i64>(6)?.max(0) as u64,
+                last_heartbeat,
+                first_seen: row.get(8)?,
+                online: now - last_heartbeat <= offline_after_ms,
+                last_log_id: row.get(9)?,
+            })
+        })?;
+        panels.collect()
+    }
+    
+    pub fn panel_last_log_id(&self, panel_id: &str) -> Result<i64> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.query_row(
+            "SELECT COALESCE(MAX(remote_id), 0) FROM panel_logs WHERE panel_id = ?1",
+            [panel_id],
+            |row| row.get(0),
+        )
+    }
+    
+    /// Logs encaminhados (mais recentes primeiro), com filtros opcionais
+    pub fn get_panel_logs(&self, panel_id: Option<&str>, level: Option<&str>, limit: u32) -> Result<Vec<PanelLog>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, panel_id, remote_id, timestamp, level, category, message, details, received_at
+             FROM panel_logs
+             WHERE (?1 IS NULL OR panel_id = ?1) AND (?2 IS NULL OR level = ?2)
+             ORDER BY id DESC LIMIT ?3"
+        )?;
+        let logs = stmt.query_map((panel_id, level, limit), |row| {
+            Ok(PanelLog {
+                id: row.get(0)?,
+                panel_id: row.get(1)?,
+                remote_id: row.get(2)?,
+                timestamp: row.get(3)?,
+                level: row.get(4)?,
+                category: row.get(5)?,
+                message: row.get(6)?,
+                details: row.get(7)?,
+                received_at: row.get(8)?,
+            })
+        })?;
+        logs.collect()
+    }
+    
+    /// Remove o painel e seus logs
+    pub fn delete_panel(&self, panel_id: &str) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM panel_logs WHERE panel_id = ?1", [panel_id])?;
+        let deleted = tx.execute("DELETE FROM panels WHERE panel_id = ?1", [panel_id])?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+    
+    pub fn prune_panel_logs(&self, older_than_ms: i64) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute("DELETE FROM panel_logs WHERE received_at < ?1", [older_than_ms])
+    }
+    
+    /// Passos de migração versionados + manifesto (após as tabelas existirem)
+    pub fn apply_data_migrations(&self, from_version: u32) -> std::result::Result<(), String> {
+        let mut conn = self.write_conn.lock().unwrap();
+        crate::data_version::apply_migrations(&mut conn, &self.db_path, from_version)
+    }
+    
+    pub fn db_path(&self) -> &std::path::Path {
+        &self.db_path
+    }
+    
+    /// Gera uma cópia consistente do banco em `dest` (VACUUM INTO, sem parar a escrita)
+    pub fn backup_into(&self, dest: &std::path::Path) -> Result<()> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", [dest.to_string_lossy().to_string()])?;
+        Ok(())
+    }
+    
+    /// Substitui as tabelas de configuração pelo conteúdo de um backup.
+    /// Somente colunas presentes nos dois bancos são copiadas (backups antigos continuam válidos).
+    pub fn restore_config_from(&self, src: &std::path::Path) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        conn.execute("ATTACH DATABASE ?1 AS bkp", [src.to_string_lossy().to_string()])?;
+        
+        let result: Result<usize> = (|| {
+            let tx = conn.transaction()?;
+            let mut restored_rows = 0;
+            
+            for table in CONFIG_TABLES {
+                let column_names = |schema: &str| -> Result<Vec<String>> {
+                    let mut stmt = tx.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+                    let columns = stmt.query_map([], |row| row.get::<usize, String>(1))?
+                        .collect::<Result<Vec<String>>>()?;
+                    Ok(columns)
+                };
+                let backup_columns = column_names("bkp")?;
+                if backup_columns.is_empty() {
+                    println!("⚠️ Restore: tabela '{}' não existe no backup, mantida como está", table);
+                    continue;
+                }
+                let common: Vec<String> = column_names("main")?
+                    .into_iter()
+                    .filter(|c| backup_columns.contains(c))
+                    .collect();
+                let column_list = common.join(", ");
+                
+                tx.execute(&format!("DELETE FROM main.{}", table), [])?;
+                restored_rows += tx.execute(
+                    &format!("INSERT INTO main.{0} ({1}) SELECT {1} FROM bkp.{0}", table, column_list),
+                    [],
+                )?;
+            }
+            
+            tx.commit()?;
+            Ok(restored_rows)
+        })();
+        
+        conn.execute("DETACH DATABASE bkp", [])?;
+        result
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA CENTRAL DE NOTIFICAÇÕES
+    // ============================================================================
+    
+    /// Registra uma notificação e retorna o ID
+    pub fn add_notification(&self, severity: &str, title: &str, body: &str, source_event: Option<&str>) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        
+        conn.execute(
+            "INSERT INTO notifications (severity, title, body, source_event, read, created_at)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+            (severity, title, body, source_event, chrono::Utc::now().timestamp()),
+        )?;
+        
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Lista notificações (mais recentes primeiro)
+    pub fn list_notifications(&self, unread_only: bool, limit: u32) -> Result<Vec<Notification>> {
+        let conn = self.read_conn.lock().unwrap();
+        
+        let mut stmt = conn.prepare(
+            "SELECT id, severity, title, body, source_event, read, created_at
+             FROM notifications WHERE (?1 = 0 OR read = 0)
+             ORDER BY created_at DESC, id DESC LIMIT ?2"
+        )?;
+        
+        let notifications = stmt.query_map((unread_only as i32, limit), |row| {
+            Ok(Notification {
+                id: row.get(0)?,
+                severity: row.get(1)?,
+                title: row.get(2)?,
+                body: row.get(3)?,
+                source_event: row.get(4)?,
+                read: row.get::<usize, i32>(5)? == 1,
+                created_at: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<Notification>>>()?;
+        
+        Ok(notifications)
+    }
+    
+    pub fn count_unread_notifications(&self) -> Result<i64> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM notifications WHERE read = 0", [], |row| row.get(0))
+    }
+    
+    /// Marca notificações como lidas (None = todas)
+    pub fn mark_notifications_read(&self, ids: Option<Vec<i64>>) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        
+        match ids {
+            None => conn.execute("UPDATE notifications SET read = 1, read_at = ?1 WHERE read = 0", [chrono::Utc::now().timestamp()]),
+            Some(ids) => {
+                let tx = conn.transaction()?;
+                let mut updated = 0;
+                {
+                    let now = chrono::Utc::now().timestamp();
+                    let mut stmt = tx.prepare("UPDATE notifications SET read = 1, read_at = COALESCE(read_at, ?2) WHERE id = ?1")?;
+                    for id in &ids {
+                        updated += stmt.execute((id, now))?;
+                    }
+                }
+                tx.commit()?;
+                Ok(updated)
+            }
+        }
+    }
+    
+    /// Ocorrências no período [from_s, to_s) para os KPIs de alarme:
+    /// (severity, title, source_event, created_at, read_at)
+    pub fn list_notification_occurrences(&self, from_s: i64, to_s: i64) -> Result<Vec<(String, String, Option<String>, i64, Option<i64>)>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT severity, title, source_event, created_at, read_at
+             FROM notifications WHERE created_at >= ?1 AND created_at < ?2
+             ORDER BY created_at"
+        )?;
+        let rows = stmt.query_map((from_s, to_s), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    /// Notificações ainda não reconhecidas criadas até `before_s` (mais antigas primeiro)
+    pub fn list_standing_notifications(&self, before_s: i64, limit: u32) -> Result<Vec<Notification>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, severity, title, body, source_event, read, created_at
+             FROM notifications WHERE read = 0 AND created_at <= ?1
+             ORDER BY created_at, id LIMIT ?2"
+        )?;
+        let notifications = stmt.query_map((before_s, limit), |row| {
+            Ok(Notification {
+                id: row.get(0)?,
+                severity: row.get(1)?,
+                title: row.get(2)?,
+                body: row.get(3)?,
+                source_event: row.get(4)?,
+                read: row.get::<usize, i32>(5)? == 1,
+                created_at: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<Notification>>>()?;
+        Ok(notifications)
+    }
+
+    /// Remove notificações (somente lidas ou todas)
+    pub fn clear_notifications(&self, only_read: bool) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        if only_read {
+            conn.execute("DELETE FROM notifications WHERE read = 1", [])
+        } else {
+            conn.execute("DELETE FROM notifications", [])
+        }
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA AUDITORIA
+    // ============================================================================
+    
+    pub fn add_audit_entry(&self, action: &str, target: &str, result: &str, details: &str) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        
+        conn.execute(
+            "INSERT INTO audit_log (action, target, result, details, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (action, target, result, details, chrono::Utc::now().timestamp()),
+        )?;
+        
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Lista a auditoria (mais recentes primeiro)
+    pub fn list_audit_log(&self, limit: u32) -> Result<Vec<AuditEntry>> {
+        let conn = self.read_conn.lock().unwrap();
+        
+        let mut stmt = conn.prepare(
+            "SELECT id, action, target, result, details, created_at
+             FROM audit_log ORDER BY created_at DESC, id DESC LIMIT ?1"
+        )?;
+        
+        let entries = stmt.query_map([limit], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                action: row.get(1)?,
+                target: row.get(2)?,
+                result: row.get(3)?,
+                details: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<AuditEntry>>>()?;
+        
+        Ok(entries)
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA TAXA ESPERADA POR PLC
+    // ============================================================================
+    
+    pub fn save_plc_rate_expectation(&self, expectation: &PlcRateExpectation) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO plc_rate_expectations
+             (plc_ip, expected_interval_ms, warning_deviation_pct, alarm_deviation_pct, enabled, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                &expectation.plc_ip,
+                expectation.expected_interval_ms as i64,
+                expectation.warning_deviation_pct,
+                expectation.alarm_deviation_pct,
+                expectation.enabled as i32,
+                expectation.updated_at,
+            ),
+        )?;
+        println!("💾 Taxa esperada salva para {}: {} ms", expectation.plc_ip, expectation.expected_interval_ms);
+        Ok(())
+    }
+    
+    pub fn load_plc_rate_expectations(&self) -> Result<Vec<PlcRateExpectation>> {
+        let conn = self.read_conn.lock().unwrap();
+        
+        let mut stmt = conn.prepare(
+            "SELECT plc_ip, expected_interval_ms, warning_deviation_pct, alarm_deviation_pct, enabled, updated_at
+             FROM plc_rate_expectations ORDER BY plc_ip"
+        )?;
+        
+        let expectations = stmt.query_map([], |row| {
+            Ok(PlcRateExpectation {
+                plc_ip: row.get(0)?,
+                expected_interval_ms: row.get::<usize, i64>(1)? as u64,
+                warning_deviation_pct: row.get(2)?,
+                alarm_deviation_pct: row.get(3)?,
+                enabled: row.get::<usize, i32>(4)? == 1,
+                updated_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<PlcRateExpectation>>>()?;
+        
+        Ok(expectations)
+    }
+    
+    pub fn delete_plc_rate_expectation(&self, plc_ip: &str) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute("DELETE FROM plc_rate_expectations WHERE plc_ip = ?1", [plc_ip])
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA FAILOVER DO HISTORIAN
+    // ============================================================================
+    
+    /// Substitui a lista de destinos (a ordem da lista é a prioridade)
+    pub fn save_historian_targets(&self, targets: &[HistorianTarget]) -> Result<()> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM historian_targets", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO historian_targets (position, name, kind, host, port, user, password, database, enabled, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+            )?;
+            let now = chrono::Utc::now().timestamp();
+            for (position, target) in targets.iter().enumerate() {
+                stmt.execute(rusqlite::params![
+                    position as i64, target.name, target.kind, target.host, target.port as i64,
+                    target.user, target.password, target.database, target.enabled as i32, now
+                ])?;
+            }
+        }
+        tx.commit()?;
+        println!("💾 {} destino(s) do historian salvos", targets.len());
+        Ok(())
+    }
+    
+    pub fn load_historian_targets(&self) -> Result<Vec<HistorianTarget>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT name, kind, host, port, user, password, database, enabled FROM historian_targets ORDER BY position"
+        )?;
+        let targets = stmt.query_map([], |row| {
+            Ok(HistorianTarget {
+                name: row.get(0)?,
+                kind: row.get(1)?,
+                host: row.get(2)?,
+                port: row.get::<usize, i64>(3)?.clamp(0, u16::MAX as i64) as u16,
+                user: row.get(4)?,
+                password: row.get(5)?,
+                database: row.get(6)?,
+                enabled: row.get::<usize, i32>(7)? == 1,
+            })
+        })?.collect::<Result<Vec<HistorianTarget>>>()?;
+        Ok(targets)
+    }
+    
+    /// Amplia a faixa pendente de replicação do destino (uma faixa por destino)
+    pub fn extend_historian_catchup_window(&self, target: &str, from_ms: i64, to_ms: i64) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO historian_catchup_windows (target, from_ms, to_ms) VALUES (?1, ?2, ?3)
+             ON CONFLICT(target) DO UPDATE SET from_ms = MIN(from_ms, excluded.from_ms), to_ms = MAX(to_ms, excluded.to_ms)",
+            (target, from_ms, to_ms),
+        )?;
+        Ok(())
+    }
+    
+    pub fn load_historian_catchup_windows(&self) -> Result<Vec<HistorianCatchupWindow>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT target, from_ms, to_ms FROM historian_catchup_windows ORDER BY target")?;
+        let windows = stmt.query_map([], |row| {
+            Ok(HistorianCatchupWindow { target: row.get(0)?, from_ms: row.get(1)?, to_ms: row.get(2)? })
+        })?.collect::<Result<Vec<HistorianCatchupWindow>>>()?;
+        Ok(windows)
+    }
+    
+    /// Remove a faixa replicada - só se não cresceu durante a replicação
+    pub fn clear_historian_catchup_window(&self, window: &HistorianCatchupWindow) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM historian_catchup_windows WHERE target = ?1 AND from_ms = ?2 AND to_ms = ?3",
+            (&window.target, window.from_ms, window.to_ms),
+        )
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA GRAVAÇÃO CONTÍNUA NO HISTORIAN
+    // ============================================================================
+    
+    pub fn save_historian_writer_config(&self, config: &HistorianWriterConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO historian_writer_config (id, enabled, flush_interval_ms, batch_size, max_buffered, updated_at)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+            (
+                config.enabled as i32,
+                config.flush_interval_ms as i64,
+                config.batch_size as i64,
+                config.max_buffered as i64,
+                config.updated_at,
+            ),
+        )?;
+        Ok(())
+    }
+    
+    /// Configuração da gravação contínua (padrão se ainda não existir)
+    pub fn load_historian_writer_config(&self) -> Result<HistorianWriterConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, flush_interval_ms, batch_size, max_buffered, updated_at FROM historian_writer_config WHERE id = 1",
+            [],
+            |row| Ok(HistorianWriterConfig {
+                enabled: row.get::<usize, i32>(0)? == 1,
+                flush_interval_ms: row.get::<usize, i64>(1)? as u64,
+                batch_size: row.get::<usize, i64>(2)? as usize,
+                max_buffered: row.get::<usize, i64>(3)? as usize,
+                updated_at: row.get(4)?,
+            }),
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(HistorianWriterConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Tags gravados no historian: (plc_ip, tag_name)
+    pub fn load_historian_tags(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT plc_ip, tag_name FROM historian_tags ORDER BY plc_ip, tag_name")?;
+        let tags = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, String)>>>()?;
+        Ok(tags)
+    }
+    
+    /// Liga/desliga a gravação de vários tags de um PLC em uma transação
+    pub fn set_historian_tags(&self, plc_ip: &str, tag_names: &[String], enabled: bool) -> Result<usize> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+        {
+            let now = chrono::Utc::now().timestamp();
+            let mut insert = tx.prepare("INSERT OR IGNORE INTO historian_tags (plc_ip, tag_name, updated_at) VALUES (?1, ?2, ?3)")?;
+            let mut delete = tx.prepare("DELETE FROM historian_tags WHERE plc_ip = ?1 AND tag_name = ?2")?;
+            for tag_name in tag_names {
+                changed += if enabled {
+                    insert.execute((plc_ip, tag_name, now))?
+                } else {
+                    delete.execute((plc_ip, tag_name))?
+                };
+            }
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA O MOTOR DE ALARMES
+    // ============================================================================
+    
+    fn alarm_definition_from_row(row: &rusqlite::Row) -> Result<AlarmDefinition> {
+        Ok(AlarmDefinition {
+            id: row.get(0)?,
+            plc_ip: row.get(1)?,
+            tag_name: row.get(2)?,
+            name: row.get(3)?,
+            comparison: row.get(4)?,
+            threshold: row.get(5)?,
+            severity: row.get(6)?,
+            deadband: row.get(7)?,
+            on_delay_ms: row.get::<usize, i64>(8)?.max(0) as u64,
+            off_delay_ms: row.get::<usize, i64>(9)?.max(0) as u64,
+            message: row.get(10)?,
+            enabled: row.get::<usize, i32>(11)? == 1,
+            updated_at: row.get(12)?,
+        })
+    }
+    
+    /// Definições de alarme (todas ou de um PLC)
+    pub fn list_alarm_definitions(&self, plc_ip: Option<&str>) -> Result<Vec<AlarmDefinition>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, plc_ip, tag_name, name, comparison, threshold, severity, deadband, on_delay_ms, off_delay_ms, message, enabled, updated_at
+             FROM alarm_definitions WHERE (?1 IS NULL OR plc_ip = ?1) ORDER BY plc_ip, tag_name, name"
+        )?;
+        let definitions = stmt.query_map([plc_ip], Self::alarm_definition_from_row)?
+            .collect::<Result<Vec<AlarmDefinition>>>()?;
+        Ok(definitions)
+    }
+    
+    /// Insere (id = 0) ou atualiza uma definição; retorna o id
+    pub fn save_alarm_definition(&self, definition: &AlarmDefinition) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        // id = 0 não existe: o INSERT OR REPLACE vira inserção com id novo
+        let id = (definition.id != 0).then_some(definition.id);
+        if let Some(id) = id {
+            let exists: i64 = conn.query_row("SELECT COUNT(*) FROM alarm_definitions WHERE id = ?1", [id], |row| row.get(0))?;
+            if exists == 0 {
+                return Err(rusqlite::Error::QueryReturnedNoRows);
+            }
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO alarm_definitions
+             (id, plc_ip, tag_name, name, comparison, threshold, severity, deadband, on_delay_ms, off_delay_ms, message, enabled, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            (
+                id,
+                &definition.plc_ip,
+                &definition.tag_name,
+                &definition.name,
+                &definition.comparison,
+                definition.threshold,
+                &definition.severity,
+                definition.deadband,
+                definition.on_delay_ms as i64,
+                definition.off_delay_ms as i64,
+                &definition.message,
+                definition.enabled as i32,
+                chrono::Utc::now().timestamp(),
+            ),
+        )?;
+        Ok(id.unwrap_or_else(|| conn.last_insert_rowid()))
+    }
+    
+    pub fn delete_alarm_definition(&self, id: i64) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute("DELETE FROM alarm_definitions WHERE id = ?1", [id])
+    }
+    
+    fn alarm_occurrence_from_row(row: &rusqlite::Row) -> Result<AlarmOccurrence> {
+        Ok(AlarmOccurrence {
+            id: row.get(0)?,
+            alarm_id: row.get(1)?,
+            plc_ip: row.get(2)?,
+            tag_name: row.get(3)?,
+            name: row.get(4)?,
+            severity: row.get(5)?,
+            message: row.get(6)?,
+            raised_value: row.get(7)?,
+            raised_at_ms: row.get(8)?,
+            cleared_value: row.get(9)?,
+            cleared_at_ms: row.get(10)?,
+            acked_at_ms: row.get(11)?,
+            acked_by: row.get(12)?,
+        })
+    }
+    
+    const ALARM_OCCURRENCE_COLUMNS: &'static str = "id, alarm_id, plc_ip, tag_name, name, severity, message, raised_value, raised_at_ms,
+         cleared_value, cleared_at_ms, acked_at_ms, acked_by";
+    
+    /// Registra um alarme que disparou; retorna o id da ocorrência
+    pub fn insert_alarm_occurrence(&self, occurrence: &AlarmOccurrence) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO alarm_history (alarm_id, plc_ip, tag_name, name, severity, message, raised_value, raised_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                occurrence.alarm_id,
+                &occurrence.plc_ip,
+                &occurrence.tag_name,
+                &occurrence.name,
+                &occurrence.severity,
+                &occurrence.message,
+                &occurrence.raised_value,
+                occurrence.raised_at_ms,
+            ),
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Marca a ocorrência como normalizada
+    pub fn clear_alarm_occurrence(&self, id: i64, value: Option<&str>, cleared_at_ms: i64) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "UPDATE alarm_history SET cleared_value = ?1, cleared_at_ms = ?2 WHERE id = ?3 AND cleared_at_ms IS NULL",
+            (value, cleared_at_ms, id),
+        )
+    }
+    
+    /// Reconhece a ocorrência; None se não existe ou já estava reconhecida
+    pub fn ack_alarm_occurrence(&self, id: i64, acked_by: Option<&str>) -> Result<Option<AlarmOccurrence>> {
+        let conn = self.write_conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE alarm_history SET acked_at_ms = ?1, acked_by = ?2 WHERE id = ?3 AND acked_at_ms IS NULL",
+            (chrono::Utc::now().timestamp_millis(), acked_by, id),
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        conn.query_row(
+            &format!("SELECT {} FROM alarm_history WHERE id = ?1", Self::ALARM_OCCURRENCE_COLUMNS),
+            [id],
+            Self::alarm_occurrence_from_row,
+        ).map(Some)
+    }
+    
+    /// Alarmes em aberto: ativos ou normalizados ainda não reconhecidos
+    pub fn list_active_alarms(&self) -> Result<Vec<AlarmOccurrence>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM alarm_history WHERE cleared_at_ms IS NULL OR acked_at_ms IS NULL ORDER BY raised_at_ms DESC",
+            Self::ALARM_OCCURRENCE_COLUMNS
+        ))?;
+        let alarms = stmt.query_map([], Self::alarm_occurrence_from_row)?
+            .collect::<Result<Vec<AlarmOccurrence>>>()?;
+        Ok(alarms)
+    }
+    
+    /// Ocorrências ainda ativas (cleared_at_ms nulo) - estado retomado pelo motor ao iniciar
+    pub fn list_open_alarm_occurrences(&self) -> Result<Vec<AlarmOccurrence>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM alarm_history WHERE cleared_at_ms IS NULL ORDER BY raised_at_ms",
+            Self::ALARM_OCCURRENCE_COLUMNS
+        ))?;
+        let alarms = stmt.query_map([], Self::alarm_occurrence_from_row)?
+            .collect::<Result<Vec<AlarmOccurrence>>>()?;
+        Ok(alarms)
+    }
+    
+    /// Histórico de ocorrências disparadas no intervalo (mais recentes primeiro)
+    pub fn list_alarm_history(&self, from_ms: i64, to_ms: i64, plc_ip: Option<&str>, limit: u32) -> Result<Vec<AlarmOccurrence>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM alarm_history
+             WHERE raised_at_ms >= ?1 AND raised_at_ms < ?2 AND (?3 IS NULL OR plc_ip = ?3)
+             ORDER BY raised_at_ms DESC, id DESC LIMIT ?4",
+            Self::ALARM_OCCURRENCE_COLUMNS
+        ))?;
+        let alarms = stmt.query_map((from_ms, to_ms, plc_ip, limit), Self::alarm_occurrence_from_row)?
+            .collect::<Result<Vec<AlarmOccurrence>>>()?;
+        Ok(alarms)
+    }
+    
+    /// Não reconhecidos disparados antes de `raised_before_ms` (KPI de alarmes em aberto)
+    pub fn list_standing_alarm_occurrences(&self, raised_before_ms: i64, limit: u32) -> Result<Vec<AlarmOccurrence>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM alarm_history WHERE acked_at_ms IS NULL AND raised_at_ms < ?1
+             ORDER BY raised_at_ms LIMIT ?2",
+            Self::ALARM_OCCURRENCE_COLUMNS
+        ))?;
+        let alarms = stmt.query_map((raised_before_ms, limit), Self::alarm_occurrence_from_row)?
+            .collect::<Result<Vec<AlarmOccurrence>>>()?;
+        Ok(alarms)
+    }
+    
+    // ============================================================================
+    // 🆕 MÉTODOS PARA EVENTOS DE CONEXÃO E DISPONIBILIDADE
+    // ============================================================================
+    
+    pub fn add_connection_event(&self, plc_ip: Option<&str>, event: &str, reason: Option<&str>, ts_ms: i64) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO plc_connection_events (plc_ip, event, reason, ts_ms) VALUES (?1, ?2, ?3, ?4)",
+            (plc_ip, event, reason, ts_ms),
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+    
+    fn connection_event_from_row(row: &rusqlite::Row) -> Result<PlcConnectionEvent> {
+        Ok(PlcConnectionEvent {
+            id: row.get(0)?,
+            plc_ip: row.get(1)?,
+            event: row.get(2)?,
+            reason: row.get(3)?,
+            ts_ms: row.get(4)?,
+        })
+    }
+    
+    /// Eventos com `from_ms <= ts_ms < to_ms` em ordem cronológica (filtro opcional por PLC,
+    /// eventos do servidor sempre incluídos)
+    pub fn list_connection_events(&self, plc_ip: Option<&str>, from_ms: i64, to_ms: i64) -> Result<Vec<PlcConnectionEvent>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, plc_ip, event, reason, ts_ms FROM plc_connection_events
+             WHERE ts_ms >= ?1 AND ts_ms < ?2 AND (?3 IS NULL OR plc_ip IS NULL OR plc_ip = ?3)
+             ORDER BY ts_ms, id"
+        )?;
+        let events = stmt.query_map((from_ms, to_ms, plc_ip), Self::connection_event_from_row)?
+            .collect::<Result<Vec<PlcConnectionEvent>>>()?;
+        Ok(events)
+    }
+    
+    /// Último evento antes de `ts_ms` de cada PLC e do servidor (estado no início de uma janela)
+    pub fn last_connection_events_before(&self, ts_ms: i64) -> Result<Vec<PlcConnectionEvent>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, plc_ip, event, reason, ts_ms FROM plc_connection_events
+             WHERE id IN (SELECT MAX(id) FROM plc_connection_events WHERE ts_ms < ?1 GROUP BY plc_ip)
+             ORDER BY ts_ms, id"
+        )?;
+        let events = stmt.query_map([ts_ms], Self::connection_event_from_row)?
+            .collect::<Result<Vec<PlcConnectionEvent>>>()?;
+        Ok(events)
+    }
+    
+    /// Instante do evento mais antigo e do mais recente (None = tabela vazia)
+    pub fn connection_events_range(&self) -> Result<Option<(i64, i64)>> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.query_row(
+            "SELECT MIN(ts_ms), MAX(ts_ms) FROM plc_connection_events",
+            [],
+            |row| Ok(row.get::<usize, Option<i64>>(0)?.zip(row.get::<usize, Option<i64>>(1)?)),
+        )
+    }
+    
+    pub fn upsert_availability_days(&self, days: &[PlcAvailabilityDay]) -> Result<()> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for day in days {
+            tx.execute(
+                "INSERT OR REPLACE INTO plc_availability_daily (plc_ip, day, up_ms, down_ms, disconnections, repairs, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (&day.plc_ip, &day.day, day.up_ms, day.down_ms, day.disconnections, day.repairs, day.updated_at),
+            )?;
+        }
+        tx.commit()
+    }
+    
+    /// Dias consolidados entre `from_day` e `to_day` ("AAAA-MM-DD", inclusive)
+    pub fn list_availability_days(&self, plc_ip: Option<&str>, from_day: &str, to_day: &str) -> Result<Vec<PlcAvailabilityDay>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT plc_ip, day, up_ms, down_ms, disconnections, repairs, updated_at FROM plc_availability_daily
+             WHERE day >= ?1 AND day <= ?2 AND (?3 IS NULL OR plc_ip = ?3)
+             ORDER BY plc_ip, day"
+        )?;
+        let days = stmt.query_map((from_day, to_day, plc_ip), |row| {
+            Ok(PlcAvailabilityDay {
+                plc_ip: row.get(0)?,
+                day: row.get(1)?,
+                up_ms: row.get(2)?,
+                down_ms: row.get(3)?,
+                disconnections: row.get(4)?,
+                repairs: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<PlcAvailabilityDay>>>()?;
+        Ok(days)
+    }
+    
+    /// Último dia consolidado e quando foi consolidado (None = nunca consolidado)
+    pub fn last_availability_day(&self) -> Result<Option<(String, i64)>> {
+        let conn = self.read_conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT day, MAX(updated_at) FROM plc_availability_daily GROUP BY day ORDER BY day DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(last) => Ok(Some(last)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA INCIDENTES CAPTURADOS
+    // ============================================================================
+    
+    pub fn add_incident(&self, incident: &IncidentRecord) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO incidents (kind, plc_ip, source_event, reason, notification_id, log_lines, frame_count, files_dir, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                &incident.kind,
+                &incident.plc_ip,
+                &incident.source_event,
+                &incident.reason,
+                incident.notification_id,
+                incident.log_lines as i64,
+                incident.frame_count as i64,
+                &incident.files_dir,
+                incident.created_at,
+            ),
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+    
+    fn incident_from_row(row: &rusqlite::Row) -> Result<IncidentRecord> {
+        Ok(IncidentRecord {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            plc_ip: row.get(2)?,
+            source_event: row.get(3)?,
+            reason: row.get(4)?,
+            notification_id: row.get(5)?,
+            log_lines: row.get::<usize, i64>(6)? as usize,
+            frame_count: row.get::<usize, i64>(7)? as usize,
+            files_dir: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+    
+    /// Lista incidentes (mais recentes primeiro)
+    pub fn list_incidents(&self, limit: u32) -> Result<Vec<IncidentRecord>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, plc_ip, source_event, reason, notification_id, log_lines, frame_count, files_dir, created_at
+             FROM incidents ORDER BY created_at DESC, id DESC LIMIT ?1"
+        )?;
+        let incidents = stmt.query_map([limit], Self::incident_from_row)?
+            .collect::<Result<Vec<IncidentRecord>>>()?;
+        Ok(incidents)
+    }
+    
+    pub fn get_incident(&self, id: i64) -> Result<Option<IncidentRecord>> {
+        let conn = self.read_conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT id, kind, plc_ip, source_event, reason, notification_id, log_lines, frame_count, files_dir, created_at
+             FROM incidents WHERE id = ?1",
+            [id],
+            Self::incident_from_row,
+        ) {
+            Ok(incident) => Ok(Some(incident)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Remove os incidentes além dos `retention` mais recentes; devolve as pastas a apagar
+    pub fn prune_incidents(&self, retention: usize) -> Result<Vec<String>> {
+        let mut conn = self.write_conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let dirs = {
+            let mut stmt = tx.prepare("SELECT files_dir FROM incidents ORDER BY created_at DESC, id DESC LIMIT -1 OFFSET ?1")?;
+            let dirs = stmt.query_map([retention as i64], |row| row.get::<usize, String>(0))?
+                .collect::<Result<Vec<String>>>()?;
+            dirs
+        };
+        tx.execute(
+            "DELETE FROM incidents WHERE id NOT IN (SELECT id FROM incidents ORDER BY created_at DESC, id DESC LIMIT ?1)",
+            [retention as i64],
+        )?;
+        tx.commit()?;
+        Ok(dirs)
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA CHAVES PÚBLICAS DO WEBSOCKET
+    // ============================================================================
+    
+    pub fn save_public_stream_key(&self, key: &PublicStreamKey) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        let rules_json = serde_json::to_string(&key.rules).unwrap_or_else(|_| "[]".to_string());
+        conn.execute(
+            "INSERT OR REPLACE INTO ws_public_keys (key, name, rules_json, enabled, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (&key.key, &key.name, rules_json, key.enabled as i32, chrono::Utc::now().timestamp()),
+        )?;
+        println!("💾 Chave pública '{}' salva ({} regras)", key.name, key.rules.len());
+        Ok(())
+    }
+    
+    pub fn load_public_stream_keys(&self) -> Result<Vec<PublicStreamKey>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT key, name, rules_json, enabled, updated_at FROM ws_public_keys ORDER BY name"
+        )?;
+        let keys = stmt.query_map([], |row| {
+            let rules_json: String = row.get(2)?;
+            Ok(PublicStreamKey {
+                key: row.get(0)?,
+                name: row.get(1)?,
+                rules: serde_json::from_str(&rules_json).unwrap_or_default(),
+                enabled: row.get::<usize, i32>(3)? == 1,
+                updated_at: row.get(4)?,
+            })
+        })?.collect::<Result<Vec<PublicStreamKey>>>()?;
+        Ok(keys)
+    }
+    
+    /// Chave habilitada correspondente ao valor enviado pelo cliente
+    pub fn find_public_stream_key(&self, key: &str) -> Result<Option<PublicStreamKey>> {
+        Ok(self.load_public_stream_keys()?.into_iter().find(|k| k.enabled && k.key == key))
+    }
+    
+    /// Há alguma chave pública habilitada? (com ela o WebSocket deixa de ser aberto)
+    pub fn has_enabled_public_stream_keys(&self) -> Result<bool> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.query_row("SELECT EXISTS(SELECT 1 FROM ws_public_keys WHERE enabled = 1)", [], |row| row.get(0))
+    }
+    
+    pub fn delete_public_stream_key(&self, key: &str) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute("DELETE FROM ws_public_keys WHERE key = ?1", [key])
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA TOKENS DE API DO WEBSOCKET
+    // ============================================================================
+    
+    pub fn create_ws_token(&self, name: &str, token_hash: &str, prefix: &str, remote_support: bool) -> Result<WsToken> {
+        let conn = self.write_conn.lock().unwrap();
+        let created_at = chrono::Utc::now().timestamp();
+        conn.execute(
+            "INSERT INTO ws_tokens (name, token_hash, prefix, created_at, remote_support) VALUES (?1, ?2, ?3, ?4, ?5)",
+            (name, token_hash, prefix, created_at, remote_support as i32),
+        )?;
+        println!("🔑 Token WebSocket '{}' criado ({}…){}", name, prefix, if remote_support { " - suporte remoto" } else { "" });
+        Ok(WsToken {
+            id: conn.last_insert_rowid(),
+            name: name.to_string(),
+            prefix: prefix.to_string(),
+            created_at,
+            last_used_at: None,
+            revoked_at: None,
+            remote_support,
+        })
+    }
+    
+    fn ws_token_from_row(row: &rusqlite::Row) -> Result<WsToken> {
+        Ok(WsToken {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            prefix: row.get(2)?,
+            created_at: row.get(3)?,
+            last_used_at: row.get(4)?,
+            revoked_at: row.get(5)?,
+            remote_support: row.get::<_, i64>(6)? != 0,
+        })
+    }
+    
+    pub fn list_ws_tokens(&self) -> Result<Vec<WsToken>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, prefix, created_at, last_used_at, revoked_at, remote_support FROM ws_tokens ORDER BY revoked_at IS NOT NULL, name"
+        )?;
+        let tokens = stmt.query_map([], Self::ws_token_from_row)?.collect::<Result<Vec<WsToken>>>()?;
+        Ok(tokens)
+    }
+    
+    /// Token ativo (não revogado) com este hash
+    pub fn find_active_ws_token(&self, token_hash: &str) -> Result<Option<WsToken>> {
+        let conn = self.read_conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT id, name, prefix, created_at, last_used_at, revoked_at, remote_support FROM ws_tokens
+             WHERE token_hash = ?1 AND revoked_at IS NULL",
+            [token_hash],
+            Self::ws_token_from_row,
+        ) {
+            Ok(token) => Ok(Some(token)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Há algum token ativo? (sem nenhum, o WebSocket continua aberto)
+    pub fn has_active_ws_tokens(&self) -> Result<bool> {
+        let conn = self.read_conn.lock().unwrap();
+        conn.query_row("SELECT EXISTS(SELECT 1 FROM ws_tokens WHERE revoked_at IS NULL)", [], |row| row.get(0))
+    }
+    
+    pub fn touch_ws_token(&self, id: i64) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute("UPDATE ws_tokens SET last_used_at = ?1 WHERE id = ?2", (chrono::Utc::now().timestamp(), id))?;
+        Ok(())
+    }
+    
+    /// Revoga o token; retorna o token revogado (None se não existe ou já estava revogado)
+    pub fn revoke_ws_token(&self, id: i64) -> Result<Option<WsToken>> {
+        let conn = self.write_conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE ws_tokens SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            (chrono::Utc::now().timestamp(), id),
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        conn.query_row(
+            "SELECT id, name, prefix, created_at, last_used_at, revoked_at, remote_support FROM ws_tokens WHERE id = ?1",
+            [id],
+            Self::ws_token_from_row,
+        ).map(Some)
+    }
+    
+    // ============================================================================
+    // MÉTODOS PARA SESSÕES DE CLIENTES WEBSOCKET
+    // ============================================================================
+    
+    pub fn save_ws_session_config(&self, config: &WsSessionConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO ws_session_config (id, enabled, retention_days, updated_at) VALUES (1, ?1, ?2, ?3)",
+            (config.enabled as i32, config.retention_days as i64, config.updated_at),
+        )?;
+        println!("💾 Registro de sessões WebSocket: enabled={} retenção={} dias", config.enabled, config.retention_days);
+        Ok(())
+    }
+    
+    pub fn load_ws_session_config(&self) -> Result<WsSessionConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, retention_days, updated_at FROM ws_session_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(WsSessionConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    retention_days: row.get::<usize, i64>(1)?.max(1) as u32,
+                    updated_at: row.get(2)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(WsSessionConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save_remote_tunnel_config(&self, config: &RemoteTunnelConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO remote_tunnel_config (id, enabled, approval_timeout_s, updated_at) VALUES (1, ?1, ?2, ?3)",
+            (config.enabled as i32, config.approval_timeout_s as i64, config.updated_at),
+        )?;
+        println!("💾 Túnel de comandos remotos: enabled={} aprovação em até {}s", config.enabled, config.approval_timeout_s);
+        Ok(())
+    }
+    
+    pub fn load_remote_tunnel_config(&self) -> Result<RemoteTunnelConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, approval_timeout_s, updated_at FROM remote_tunnel_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(RemoteTunnelConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    approval_timeout_s: row.get::<usize, i64>(1)?.max(1) as u32,
+                    updated_at: row.get(2)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(RemoteTunnelConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save_flatline_config(&self, config: &FlatlineConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO flatline_config (id, enabled, flatline_after_s, updated_at) VALUES (1, ?1, ?2, ?3)",
+            (config.enabled as i32, config.flatline_after_s as i64, config.updated_at),
+        )?;
+        println!("💾 Detecção de sensor congelado: enabled={} após {}s sem variação", config.enabled, config.flatline_after_s);
+        Ok(())
+    }
+    
+    pub fn load_flatline_config(&self) -> Result<FlatlineConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, flatline_after_s, updated_at FROM flatline_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(FlatlineConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    flatline_after_s: row.get::<usize, i64>(1)?.max(1) as u32,
+                    updated_at: row.get(2)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(FlatlineConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save_backup_config(&self, config: &BackupConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO backup_config (id, retention, updated_at) VALUES (1, ?1, ?2)",
+            (config.retention as i64, config.updated_at),
+        )?;
+        println!("💾 Backup da configuração: manter {} backups", config.retention);
+        Ok(())
+    }
+    
+    pub fn load_backup_config(&self) -> Result<BackupConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT retention, updated_at FROM backup_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(BackupConfig {
+                    retention: row.get::<usize, i64>(0)?.max(1) as u32,
+                    updated_at: row.get(1)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(BackupConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save_redundancy_config(&self, config: &RedundancyConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO redundancy_config (id, enabled, role, peer_address, listen_port, peer_timeout_s, updated_at) VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6)",
+            (config.enabled as i32, &config.role, &config.peer_address, config.listen_port as i64, config.peer_timeout_s as i64, config.updated_at),
+        )?;
+        println!("💾 Redundância: enabled={} papel={} par={} porta={} timeout={}s",
+                 config.enabled, config.role, config.peer_address, config.listen_port, config.peer_timeout_s);
+        Ok(())
+    }
+    
+    pub fn load_redundancy_config(&self) -> Result<RedundancyConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, role, peer_address, listen_port, peer_timeout_s, updated_at FROM redundancy_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(RedundancyConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    role: row.get(1)?,
+                    peer_address: row.get(2)?,
+                    listen_port: row.get::<usize, i64>(3)?.clamp(1, u16::MAX as i64) as u16,
+                    peer_timeout_s: row.get::<usize, i64>(4)?.max(1) as u32,
+                    updated_at: row.get(5)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(RedundancyConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save_parse_quarantine_config(&self, config: &ParseQuarantineConfig) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO parse_quarantine_config (id, enabled, error_threshold, window_s, updated_at) VALUES (1, ?1, ?2, ?3, ?4)",
+            (config.enabled as i32, config.error_threshold as i64, config.window_s as i64, config.updated_at),
+        )?;
+        println!("💾 Quarentena de parse: enabled={} {} erros em {}s", config.enabled, config.error_threshold, config.window_s);
+        Ok(())
+    }
+    
+    pub fn load_parse_quarantine_config(&self) -> Result<ParseQuarantineConfig> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT enabled, error_threshold, window_s, updated_at FROM parse_quarantine_config WHERE id = 1",
+            [],
+            |row| {
+                Ok(ParseQuarantineConfig {
+                    enabled: row.get::<usize, i32>(0)? == 1,
+                    error_threshold: row.get::<usize, i64>(1)?.max(1) as u32,
+                    window_s: row.get::<usize, i64>(2)?.max(1) as u32,
+                    updated_at: row.get(3)?,
+                })
+            },
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(ParseQuarantineConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save_runtime_state(&self, state: &RuntimeState) -> Result<()> {
+        let websocket_config = state.websocket_config.as_ref()
+            .map(|c| serde_json::to_string(c).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))))
+            .transpose()?;
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO runtime_state (id, tcp_autostart, tcp_running, tcp_port, websocket_autostart, websocket_running, websocket_config, updated_at)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (state.tcp_autostart as i32, state.tcp_running as i32, state.tcp_port as i64,
+             state.websocket_autostart as i32, state.websocket_running as i32, websocket_config, state.updated_at),
+        )?;
+        Ok(())
+    }
+    
+    pub fn load_runtime_state(&self) -> Result<RuntimeState> {
+        let conn = self.read_conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT tcp_autostart, tcp_running, tcp_port, websocket_autostart, websocket_running, websocket_config, updated_at FROM runtime_state WHERE id = 1",
+            [],
+            |row| {
+                // JSON ilegível: sem configuração, o WebSocket não é retomado
+                let websocket_config = row.get::<usize, Option<String>>(5)?
+                    .and_then(|json| serde_json::from_str::<WebSocketConfig>(&json).ok());
+                Ok(RuntimeState {
+                    tcp_autostart: row.get::<usize, i32>(0)? == 1,
+                    tcp_running: row.get::<usize, i32>(1)? == 1,
+                    tcp_port: row.get::<usize, i64>(2)? as u16,
+                    websocket_autostart: row.get::<usize, i32>(3)? == 1,
+                    websocket_running: row.get::<usize, i32>(4)? == 1,
+                    websocket_config,
+                    updated_at: row.get(6)?,
+                })
+            },
+        );
+        match result {
+            Ok(state) => Ok(state),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(RuntimeState::default()),
+            Err(e) => Err(e),
+        }
+    }
+    
+    fn ws_session_from_row(row: &rusqlite::Row) -> Result<WsClientSession> {
+        Ok(WsClientSession {
+            id: row.get(0)?,
+            client_id: row.get(1)?,
+            address: row.get(2)?,
+            token_id: row.get(3)?,
+            token_name: row.get(4)?,
+            public_key: row.get(5)?,
+            connected_at_ms: row.get(6)?,
+            last_seen_ms: row.get(7)?,
+            disconnected_at_ms: row.get(8)?,
+            disconnect_reason: row.get(9)?,
+            bytes_sent: row.get(10)?,
+            messages_sent: row.get(11)?,
+            messages_received: row.get(12)?,
+        })
+    }
+    
+    /// Abre a sessão de um cliente; retorna o id
+    pub fn open_ws_session(&self, session: &WsClientSession) -> Result<i64> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO ws_client_sessions (client_id, address, token_id, token_name, public_key, connected_at_ms, last_seen_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            (session.client_id, &session.address, session.token_id, &session.token_name, &session.public_key, session.connected_at_ms),
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Atualiza contadores/identificação de uma sessão ainda aberta; com
+    /// `disconnected_at_ms` preenchido a sessão é encerrada
+    pub fn update_ws_session(&self, session: &WsClientSession) -> Result<()> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "UPDATE ws_client_sessions SET token_id = ?2, token_name = ?3, public_key = ?4, last_seen_ms = ?5,
+                disconnected_at_ms = ?6, disconnect_reason = ?7, bytes_sent = ?8, messages_sent = ?9, messages_received = ?10
+             WHERE id = ?1 AND disconnected_at_ms IS NULL",
+            rusqlite::params![
+                session.id,
+                session.token_id,
+                &session.token_name,
+                &session.public_key,
+                session.last_seen_ms,
+                session.disconnected_at_ms,
+                &session.disconnect_reason,
+                session.bytes_sent,
+                session.messages_sent,
+                session.messages_received,
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Fecha sessões que ficaram abertas (HMI caiu) no último instante visto
+    pub fn close_interrupted_ws_sessions(&self) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        conn.execute(
+            "UPDATE ws_client_sessions SET disconnected_at_ms = last_seen_ms, disconnect_reason = 'interrupted'
+             WHERE disconnected_at_ms IS NULL",
+            [],
+        )
+    }
+    
+    /// Remove sessões encerradas há mais de `retention_days`
+    pub fn prune_ws_sessions(&self, retention_days: u32) -> Result<usize> {
+        let conn = self.write_conn.lock().unwrap();
+        let cutoff_ms = chrono::Utc::now().timestamp_millis() - retention_days as i64 * 86_400_000;
+        conn.execute(
+            "DELETE FROM ws_client_sessions WHERE disconnected_at_ms IS NOT NULL AND disconnected_at_ms < ?1",
+            [cutoff_ms],
+        )
+    }
+    
+    /// Sessões que estiveram conectadas em algum momento de [from_ms, to_ms]
+    pub fn query_ws_sessions(&self, from_ms: i64, to_ms: i64, address: Option<&str>, token_name: Option<&str>, limit: i64) -> Result<Vec<WsClientSession>> {
+        let conn = self.read_conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, client_id, address, token_id, token_name, public_key, connected_at_ms, last_seen_ms,
+                    disconnected_at_ms, disconnect_reason, bytes_sent, messages_sent, messages_received
+             FROM ws_client_sessions
+             WHERE connected_at_ms <= ?2 AND COALESCE(disconnected_at_ms, ?2) >= ?1
+               AND (?3 IS NULL OR address = ?3) AND (?4 IS NULL OR token_name = ?4)
+             ORDER BY connected_at_ms DESC LIMIT ?5"
+        )?;
+        let sessions = stmt.query_map((from_ms, to_ms, address, token_name, limit), Self::ws_session_from_row)?
+            .collect::<Result<Vec<WsClientSession>>>()?;
+        Ok(sessions)
+    }
+}
+
+/// Hash FNV-1a de 64 bits - estável entre versões e plataformas (ao contrário do DefaultHasher)
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
 }
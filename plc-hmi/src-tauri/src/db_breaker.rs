@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// CIRCUIT BREAKER DO SQLITE NO CAMINHO QUENTE (CACHE DO WEBSOCKET)
+// ============================================================================
+//
+// Com o disco disputado (backup, antivírus, outra HMI no mesmo volume) uma
+// consulta ao SQLite pode levar segundos, e o processador do cache para junto.
+// Depois de TRIP_AFTER chamadas lentas (ou com erro) seguidas o circuito abre:
+// o cache passa a servir só o que já tem (modo degradado) e o banco não é
+// consultado por OPEN_COOLDOWN. Vencido o prazo, uma chamada de teste decide:
+// rápida fecha o circuito, lenta reabre. Cada troca emite "websocket-db-degraded".
+
+pub const SLOW_CALL: Duration = Duration::from_millis(250);
+const TRIP_AFTER: u32 = 3;
+const OPEN_COOLDOWN: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbBreakerStatus {
+    pub degraded: bool,
+    pub consecutive_slow: u32,
+    pub trips: u64,
+    pub skipped_calls: u64,        // Consultas não feitas com o circuito aberto
+    pub last_call_ms: u64,
+    pub slow_threshold_ms: u64,
+}
+
+#[derive(Debug)]
+pub struct DbCircuitBreaker {
+    app_handle: AppHandle,
+    open: AtomicBool,
+    retry_at_ms: AtomicI64,        // Circuito aberto: próxima chamada de teste
+    consecutive_slow: AtomicU32,
+    trips: AtomicU64,
+    skipped_calls: AtomicU64,
+    last_call_ms: AtomicU64,
+}
+
+impl DbCircuitBreaker {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            open: AtomicBool::new(false),
+            retry_at_ms: AtomicI64::new(0),
+            consecutive_slow: AtomicU32::new(0),
+            trips: AtomicU64::new(0),
+            skipped_calls: AtomicU64::new(0),
+            last_call_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Executa `operation` se o circuito permitir. None = circuito aberto (usar o cache).
+    /// Erros contam como chamada lenta: o banco não está respondendo direito.
+    pub fn call<T, E: std::fmt::Display>(&self, label: &str, operation: impl FnOnce() -> Result<T, E>) -> Option<Result<T, E>> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        if self.open.load(Ordering::SeqCst) {
+            // Só uma chamada de teste por janela de espera
+            let retry_at = self.retry_at_ms.load(Ordering::SeqCst);
+            if now_ms < retry_at || self.retry_at_ms
+                .compare_exchange(retry_at, now_ms + OPEN_COOLDOWN.as_millis() as i64, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+            {
+                self.skipped_calls.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        }
+
+        let started = Instant::now();
+        let result = operation();
+        let elapsed = started.elapsed();
+        self.last_call_ms.store(elapsed.as_millis() as u64, Ordering::Relaxed);
+
+        match &result {
+            Ok(_) if elapsed < SLOW_CALL => self.record_fast(label),
+            Ok(_) => self.record_slow(label, format!("{} levou {} ms", label, elapsed.as_millis())),
+            Err(e) => self.record_slow(label, format!("{} falhou: {}", label, e)),
+        }
+        Some(result)
+    }
+
+    fn record_fast(&self, label: &str) {
+        self.consecutive_slow.store(0, Ordering::SeqCst);
+        if self.open.swap(false, Ordering::SeqCst) {
+            println!("✅ SQLite respondendo de novo ({}): cache do WebSocket volta a consultar o banco", label);
+            self.emit(false, None);
+        }
+    }
+
+    fn record_slow(&self, label: &str, reason: String) {
+        let slow = self.consecutive_slow.fetch_add(1, Ordering::SeqCst) + 1;
+        println!("🐢 SQLite lento no caminho quente ({}/{}): {}", slow, TRIP_AFTER, reason);
+        if self.open.load(Ordering::SeqCst) {
+            // Chamada de teste falhou: continua aberto até o próximo teste
+            self.retry_at_ms.store(chrono::Utc::now().timestamp_millis() + OPEN_COOLDOWN.as_millis() as i64, Ordering::SeqCst);
+            return;
+        }
+        if slow >= TRIP_AFTER {
+            self.retry_at_ms.store(chrono::Utc::now().timestamp_millis() + OPEN_COOLDOWN.as_millis() as i64, Ordering::SeqCst);
+            self.open.store(true, Ordering::SeqCst);
+            self.trips.fetch_add(1, Ordering::Relaxed);
+            println!("⚡ Circuit breaker do SQLite ABERTO ({}): servindo só do cache por {}s", label, OPEN_COOLDOWN.as_secs());
+            self.emit(true, Some(reason));
+        }
+    }
+
+    fn emit(&self, degraded: bool, reason: Option<String>) {
+        let _ = self.app_handle.emit("websocket-db-degraded", serde_json::json!({
+            "degraded": degraded,
+            "reason": reason,
+            "status": self.status(),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.open.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> DbBreakerStatus {
+        DbBreakerStatus {
+            degraded: self.is_degraded(),
+            consecutive_slow: self.consecutive_slow.load(Ordering::SeqCst),
+            trips: self.trips.load(Ordering::Relaxed),
+            skipped_calls: self.skipped_calls.load(Ordering::Relaxed),
+            last_call_ms: self.last_call_ms.load(Ordering::Relaxed),
+            slow_threshold_ms: SLOW_CALL.as_millis() as u64,
+        }
+    }
+}
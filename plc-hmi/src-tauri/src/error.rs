@@ -0,0 +1,153 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+// ============================================================================
+// ERROS TIPADOS DOS COMANDOS
+// ============================================================================
+//
+// Os comandos devolviam só texto, e o frontend tinha que procurar palavras na
+// mensagem para saber o que fazer. AppError chega ao frontend como
+// {"code": "NOT_RUNNING", "message": "..."}: a UI decide pelo código e mostra a
+// mensagem (src/utils/appError.ts). Todos os comandos de commands.rs devolvem
+// AppError e classificam o erro na origem; funções auxiliares em String entram
+// pelo `?` como OTHER (String -> AppError::Other) e AppError -> String vale no
+// sentido inverso.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    NotRunning(String),        // Serviço parado (servidor TCP, WebSocket, ...)
+    AlreadyRunning(String),
+    DbNotInitialized(String),  // Banco/historian não configurado ou inacessível
+    PlcNotFound(String),       // PLC sem conexão, sem estrutura ou desconhecido
+    NotFound(String),          // 🆕 Alarme, tag, token, painel... inexistente
+    Timeout(String),
+    ConfigInvalid(String),     // Parâmetro ou configuração recusada
+    Io(String),                // Porta, arquivo, socket
+    Database(String),          // 🆕 Falha de leitura/gravação no banco (SQLite/PostgreSQL)
+    Other(String),             // Erros ainda não classificados
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotRunning(_) => "NOT_RUNNING",
+            AppError::AlreadyRunning(_) => "ALREADY_RUNNING",
+            AppError::DbNotInitialized(_) => "DB_NOT_INITIALIZED",
+            AppError::PlcNotFound(_) => "PLC_NOT_FOUND",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Timeout(_) => "TIMEOUT",
+            AppError::ConfigInvalid(_) => "CONFIG_INVALID",
+            AppError::Io(_) => "IO",
+            AppError::Database(_) => "DATABASE",
+            AppError::Other(_) => "OTHER",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotRunning(message)
+            | AppError::AlreadyRunning(message)
+            | AppError::DbNotInitialized(message)
+            | AppError::PlcNotFound(message)
+            | AppError::NotFound(message)
+            | AppError::Timeout(message)
+            | AppError::ConfigInvalid(message)
+            | AppError::Io(message)
+            | AppError::Database(message)
+            | AppError::Other(message) => message,
+        }
+    }
+
+    /// "Servidor TCP não está rodando"
+    pub fn tcp_not_running() -> Self {
+        AppError::NotRunning("Servidor TCP não está rodando".to_string())
+    }
+
+    pub fn websocket_not_running() -> Self {
+        AppError::NotRunning("WebSocket server não está rodando".to_string())
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound("Registro não encontrado".to_string()),
+            rusqlite::Error::SqliteFailure(failure, _) => match failure.code {
+                // Arquivo do banco ausente, travado ou sem permissão
+                ErrorCode::CannotOpen
+                | ErrorCode::NotADatabase
+                | ErrorCode::DatabaseCorrupt
+                | ErrorCode::DatabaseBusy
+                | ErrorCode::DatabaseLocked
+                | ErrorCode::PermissionDenied
+                | ErrorCode::ReadOnly => AppError::DbNotInitialized(format!("Banco de dados inacessível: {}", e)),
+                ErrorCode::ConstraintViolation => AppError::ConfigInvalid(format!("Registro recusado pelo banco: {}", e)),
+                _ => AppError::Database(format!("Erro no banco de dados: {}", e)),
+            },
+            _ => AppError::Database(format!("Erro no banco de dados: {}", e)),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::RowNotFound => AppError::NotFound("Registro não encontrado".to_string()),
+            sqlx::Error::PoolTimedOut => AppError::Timeout(format!("PostgreSQL não respondeu: {}", e)),
+            sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolClosed => {
+                AppError::DbNotInitialized(format!("PostgreSQL inacessível: {}", e))
+            }
+            _ => AppError::Database(format!("Erro no PostgreSQL: {}", e)),
+        }
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for AppError {
+    fn from(_: tokio::time::error::Elapsed) -> Self {
+        AppError::Timeout("Tempo esgotado aguardando a operação".to_string())
+    }
+}
+
+/// Funções que ainda devolvem String podem usar `?` em chamadas com AppError
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.message().to_string()
+    }
+}
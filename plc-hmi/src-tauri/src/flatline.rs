@@ -0,0 +1,199 @@
+use crate::commands::{TcpServerState, WebSocketServerState};
+use crate::database::{Database, FlatlineConfig};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// SENSOR CONGELADO (FLATLINE) - VALOR ANALÓGICO SEM NENHUMA VARIAÇÃO
+// ============================================================================
+//
+// Transmissor de nível com defeito costuma travar no último valor: o PLC
+// continua mandando frames, só que o número não muda nem na última casa.
+// A cada CHECK_INTERVAL compara o valor de cada tag analógico do SmartCache
+// com o anterior; parado por `flatline_after_s` vira "flatlined" (evento
+// "tag-flatlined", aviso na central de notificações). Qualquer variação
+// normaliza ("tag-flatline-cleared").
+// Diferente de comunicação parada: só conta enquanto o PLC está conectado e o
+// tag continua sendo atualizado (timestamp recente). Sem comunicação a contagem
+// recomeça e a marcação existente fica como está até o valor voltar a mudar.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const RELOAD_CONFIG_INTERVAL: Duration = Duration::from_secs(30);
+const STALE_AFTER_NS: u128 = 10_000_000_000; // Tag sem atualização há 10s = comunicação parada
+
+pub const MIN_FLATLINE_AFTER_S: u32 = 60;
+pub const MAX_FLATLINE_AFTER_S: u32 = 7 * 86_400;
+
+/// Tipos analógicos: bits, WORDs de status e textos ficam parados legitimamente
+const ANALOG_TYPES: &[&str] = &["REAL", "LREAL", "INT", "DINT", "LINT"];
+
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+static FLATLINED: Mutex<Option<HashMap<String, FlatlinedTag>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlatlinedTag {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub value: String,
+    pub data_type: String,
+    pub unchanged_since_ms: i64,  // Última variação vista (com comunicação ativa)
+    pub flagged_at_ms: i64,
+}
+
+pub fn validate_config(config: &FlatlineConfig) -> Result<(), String> {
+    if !(MIN_FLATLINE_AFTER_S..=MAX_FLATLINE_AFTER_S).contains(&config.flatline_after_s) {
+        return Err(format!("Tempo sem variação deve estar entre {}s e {}s", MIN_FLATLINE_AFTER_S, MAX_FLATLINE_AFTER_S));
+    }
+    Ok(())
+}
+
+/// Aplica a configuração na próxima verificação (após salvar)
+pub fn request_reload() {
+    RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Tags marcados como congelados, mais antigos primeiro
+pub fn list_flatlined() -> Vec<FlatlinedTag> {
+    let mut tags: Vec<_> = FLATLINED.lock().unwrap().as_ref()
+        .map(|f| f.values().cloned().collect())
+        .unwrap_or_default();
+    tags.sort_by_key(|t| t.flagged_at_ms);
+    tags
+}
+
+/// Valor atual e desde quando está igual
+struct Tracked {
+    value: String,
+    since: Instant,
+    since_ms: i64,
+}
+
+fn is_analog(data_type: &str, value: &str) -> bool {
+    ANALOG_TYPES.contains(&data_type) && value.trim().parse::<f64>().is_ok_and(|v| v.is_finite())
+}
+
+pub fn start_flatline_monitor(app_handle: AppHandle, database: Arc<Database>, websocket_state: WebSocketServerState, tcp_state: TcpServerState) {
+    tauri::async_runtime::spawn(async move {
+        let mut config = FlatlineConfig::default();
+        let mut loaded_at: Option<Instant> = None;
+        let mut tracked: HashMap<String, Tracked> = HashMap::new();
+
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            if RELOAD_REQUESTED.swap(false, Ordering::SeqCst) || loaded_at.map_or(true, |at| at.elapsed() >= RELOAD_CONFIG_INTERVAL) {
+                match database.load_flatline_config() {
+                    Ok(loaded) => config = loaded,
+                    Err(e) => println!("⚠️ Flatline: erro ao carregar configuração: {}", e),
+                }
+                loaded_at = Some(Instant::now());
+            }
+            if !config.enabled {
+                tracked.clear();
+                clear_all(&app_handle, "detecção desabilitada");
+                continue;
+            }
+
+            let Some(smart_cache) = websocket_state.read().await.as_ref().map(|s| s.smart_cache()) else {
+                continue; // Sem WebSocket rodando não há valores ao vivo
+            };
+            let connected: HashSet<String> = match tcp_state.read().await.as_ref() {
+                Some(server) => server.get_connected_clients().await.into_iter().collect(),
+                None => HashSet::new(),
+            };
+
+            let now = Instant::now();
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            let now_ns = now_ms as u128 * 1_000_000;
+            let flatline_after = Duration::from_secs(config.flatline_after_s as u64);
+            let mut seen = HashSet::new();
+
+            for cached in smart_cache.snapshot(None) {
+                if cached.area.as_deref() == Some(crate::self_monitor::SELF_AREA) || !is_analog(&cached.data_type, &cached.value) {
+                    continue;
+                }
+                let key = format!("{}:{}", cached.plc_ip, cached.tag_name);
+                seen.insert(key.clone());
+
+                // Comunicação parada não é sensor congelado: recomeça a contagem
+                let communicating = connected.contains(&cached.plc_ip) && now_ns.saturating_sub(cached.timestamp_ns) <= STALE_AFTER_NS;
+                let entry = tracked.entry(key.clone()).or_insert_with(|| Tracked { value: cached.value.clone(), since: now, since_ms: now_ms });
+                if entry.value != cached.value {
+                    entry.value = cached.value.clone();
+                    entry.since = now;
+                    entry.since_ms = now_ms;
+                    clear(&app_handle, &key, Some(&cached.value), "valor voltou a variar");
+                    continue;
+                }
+                if !communicating {
+                    entry.since = now;
+                    continue;
+                }
+                if now.duration_since(entry.since) < flatline_after || is_flagged(&key) {
+                    continue;
+                }
+                flag(&app_handle, key, FlatlinedTag {
+                    plc_ip: cached.plc_ip,
+                    tag_name: cached.tag_name,
+                    value: cached.value,
+                    data_type: cached.data_type,
+                    unchanged_since_ms: entry.since_ms,
+                    flagged_at_ms: now_ms,
+                });
+            }
+
+            // Tag removido do mapeamento (ou PLC fora do cache): esquecer
+            tracked.retain(|key, _| seen.contains(key));
+            let gone: Vec<String> = FLATLINED.lock().unwrap().as_ref()
+                .map(|f| f.keys().filter(|k| !seen.contains(*k)).cloned().collect())
+                .unwrap_or_default();
+            for key in gone {
+                clear(&app_handle, &key, None, "tag fora do cache");
+            }
+        }
+    });
+}
+
+fn is_flagged(key: &str) -> bool {
+    FLATLINED.lock().unwrap().as_ref().is_some_and(|f| f.contains_key(key))
+}
+
+fn flag(app_handle: &AppHandle, key: String, tag: FlatlinedTag) {
+    let duration_s = (tag.flagged_at_ms - tag.unchanged_since_ms).max(0) / 1000;
+    println!("🧊 FLATLINE: PLC {} tag {} parado em {} há {}s", tag.plc_ip, tag.tag_name, tag.value, duration_s);
+    let _ = app_handle.emit("tag-flatlined", serde_json::json!({
+        "plc_ip": tag.plc_ip,
+        "tag_name": tag.tag_name,
+        "value": tag.value,
+        "data_type": tag.data_type,
+        "unchanged_since_ms": tag.unchanged_since_ms,
+        "duration_s": duration_s,
+    }));
+    FLATLINED.lock().unwrap().get_or_insert_with(HashMap::new).insert(key, tag);
+}
+
+fn clear(app_handle: &AppHandle, key: &str, value: Option<&str>, reason: &str) {
+    let Some(tag) = FLATLINED.lock().unwrap().as_mut().and_then(|f| f.remove(key)) else { return };
+    println!("✅ FLATLINE: PLC {} tag {} normalizado ({})", tag.plc_ip, tag.tag_name, reason);
+    let _ = app_handle.emit("tag-flatline-cleared", serde_json::json!({
+        "plc_ip": tag.plc_ip,
+        "tag_name": tag.tag_name,
+        "value": value,
+        "reason": reason,
+        "cleared_at_ms": chrono::Utc::now().timestamp_millis(),
+    }));
+}
+
+fn clear_all(app_handle: &AppHandle, reason: &str) {
+    let keys: Vec<String> = FLATLINED.lock().unwrap().as_ref()
+        .map(|f| f.keys().cloned().collect())
+        .unwrap_or_default();
+    for key in keys {
+        clear(app_handle, &key, None, reason);
+    }
+}
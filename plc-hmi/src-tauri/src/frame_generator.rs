@@ -0,0 +1,312 @@
+use crate::database::{ByteOrder, DataBlockConfig, PlcStructureConfig};
+use crate::plc_parser::{data_type_size, format_duration_ms, format_time_of_day, parse_with_config, s7_date, select_frame_layout, text_type, time_type, to_big_endian, TextType, TimeType, WAVEFORM_TYPE_SUFFIX};
+use crate::tcp_server::PlcVariable;
+use serde::Serialize;
+
+// ============================================================================
+// GERADOR DE FRAMES SINTÉTICOS A PARTIR DE UMA ESTRUTURA
+// ============================================================================
+//
+// Para cada layout da estrutura (principal + perfis) gera um frame válido com
+// valores determinísticos (mesma semente = mesmos bytes) e um conjunto de frames
+// mutados que o parser deveria recusar (truncado, byte extra, vazio, byte de tipo
+// errado...). Cada frame passa pelo mesmo select_frame_layout/parse_with_config
+// usados ao vivo, então estruturas ambíguas aparecem antes de existir o
+// programa do PLC. Usado pelo simulador e pelos testes de estrutura.
+
+const DEFAULT_SEED: u64 = 0x504C_4348_4D49; // "PLCHMI"
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestFrame {
+    pub name: String,              // Ex: "default:valid", "fast:truncated"
+    pub layout: String,            // Layout a partir do qual o frame foi gerado
+    pub description: String,
+    pub expect_accepted: bool,     // Se o parser deveria aceitar este frame neste layout
+    pub accepted_as: Option<String>, // Layout escolhido pelo parser (None = recusado)
+    pub ok: bool,                  // Resultado confere com o esperado
+    pub size: usize,
+    pub hex: String,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestFrameSet {
+    pub plc_ip: String,
+    pub seed: u64,
+    pub frames: Vec<TestFrame>,
+    pub expected_variables: Vec<PlcVariable>, // Valores do frame válido da estrutura principal
+    pub issues: Vec<String>,                  // Ambiguidades/divergências encontradas
+}
+
+/// xorshift64: determinístico e sem dependências
+struct FrameRng(u64);
+
+impl FrameRng {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+/// Bytes big-endian e texto esperado do parser para um valor aleatório do tipo
+fn random_value(data_type: &str, rng: &mut FrameRng) -> Option<(Vec<u8>, String)> {
+    let r = rng.next();
+    let encoded = match data_type {
+        "BYTE" => (vec![r as u8], format!("{}", r as u8)),
+        "WORD" => ((r as u16).to_be_bytes().to_vec(), format!("{}", r as u16)),
+        "INT" => ((r as i16).to_be_bytes().to_vec(), format!("{}", r as i16)),
+        "DWORD" => ((r as u32).to_be_bytes().to_vec(), format!("{}", r as u32)),
+        "DINT" => ((r as i32).to_be_bytes().to_vec(), format!("{}", r as i32)),
+        "LWORD" => (r.to_be_bytes().to_vec(), format!("{}", r)),
+        "LINT" => ((r as i64).to_be_bytes().to_vec(), format!("{}", r as i64)),
+        // Reais em faixa de processo (-1000.00 a 1000.00), sempre finitos
+        "REAL" => {
+            let value = (r % 200_001) as f32 / 100.0 - 1000.0;
+            (value.to_be_bytes().to_vec(), format!("{:.6}", value))
+        }
+        "LREAL" => {
+            let value = (r % 200_001) as f64 / 100.0 - 1000.0;
+            (value.to_be_bytes().to_vec(), format!("{:.6}", value))
+        }
+        // 🆕 Data/hora: valores válidos (DT em BCD entre 1990 e 2089)
+        _ if time_type(data_type).is_some() => return random_time(time_type(data_type)?, rng),
+        // 🆕 Texto: caracteres ASCII imprimíveis, tamanho atual aleatório
+        _ => match text_type(data_type)? {
+            TextType::String(max) => {
+                let text = random_text(max, rng);
+                let mut bytes = vec![max as u8, text.len() as u8];
+                bytes.extend_from_slice(text.as_bytes());
+                bytes.resize(max + 2, 0);
+                (bytes, text)
+            }
+            TextType::WString(max) => {
+                let text = random_text(max, rng);
+                let mut bytes = (max as u16).to_be_bytes().to_vec();
+                bytes.extend_from_slice(&(text.len() as u16).to_be_bytes());
+                bytes.extend(text.bytes().flat_map(|b| (b as u16).to_be_bytes()));
+                bytes.resize(max * 2 + 4, 0);
+                (bytes, text)
+            }
+            TextType::Char(len) => {
+                // Sem NUL no final: o parser corta NULs finais
+                let text: String = (0..len).map(|_| (b'A' + (rng.next() % 26) as u8) as char).collect();
+                (text.clone().into_bytes(), text)
+            }
+        },
+    };
+    Some(encoded)
+}
+
+fn random_time(time: TimeType, rng: &mut FrameRng) -> Option<(Vec<u8>, String)> {
+    let r = rng.next();
+    let encoded = match time {
+        // Até ±24h
+        TimeType::Time => {
+            let ms = (r % 172_800_001) as i32 - 86_400_000;
+            (ms.to_be_bytes().to_vec(), format_duration_ms(ms))
+        }
+        TimeType::Date => {
+            let days = (r % 40_000) as u16;
+            (days.to_be_bytes().to_vec(), s7_date(days)?.format("%Y-%m-%d").to_string())
+        }
+        TimeType::TimeOfDay => {
+            let ms = (r % 86_400_000) as u32;
+            (ms.to_be_bytes().to_vec(), format_time_of_day(ms)?)
+        }
+        TimeType::DateAndTime => {
+            let to_bcd = |value: u64| (((value / 10) << 4) | (value % 10)) as u8;
+            let year = r % 100;
+            let (month, day) = (1 + rng.next() % 12, 1 + rng.next() % 28);
+            let (hour, minute, second) = (rng.next() % 24, rng.next() % 60, rng.next() % 60);
+            let millis = rng.next() % 1000;
+            let weekday = 1 + rng.next() % 7;
+            let bytes = vec![
+                to_bcd(year), to_bcd(month), to_bcd(day), to_bcd(hour), to_bcd(minute), to_bcd(second),
+                to_bcd(millis / 10), (((millis % 10) << 4) | weekday) as u8,
+            ];
+            let full_year = if year >= 90 { 1900 + year } else { 2000 + year };
+            let text = format!("{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}", full_year, month, day, hour, minute, second, millis);
+            (bytes, text)
+        }
+    };
+    Some(encoded)
+}
+
+/// Texto ASCII imprimível (0..=max caracteres)
+fn random_text(max: usize, rng: &mut FrameRng) -> String {
+    let len = (rng.next() % (max as u64 + 1)) as usize;
+    (0..len).map(|_| (b' ' + (rng.next() % 95) as u8) as char).collect()
+}
+
+/// Frame válido para os blocos + variáveis esperadas com o intervalo de bytes de cada uma
+fn build_frame(blocks: &[DataBlockConfig], byte_order: ByteOrder, rng: &mut FrameRng) -> (Vec<u8>, Vec<(PlcVariable, std::ops::Range<usize>)>) {
+    let mut frame = Vec::new();
+    let mut variables = Vec::new();
+    for block in blocks {
+        let block_start = frame.len();
+        let mut waveform_points = Vec::new();
+        let order = block.byte_order.unwrap_or(byte_order);
+        for i in 0..block.count {
+            let Some((mut bytes, value)) = random_value(&block.data_type, rng) else { break };
+            // Texto e DT (BCD) não têm ordem de bytes; números vão na ordem do PLC
+            let time = time_type(&block.data_type);
+            if text_type(&block.data_type).is_none() && time.map(|time| time.uses_byte_order()).unwrap_or(true) {
+                to_big_endian(order, &mut bytes);
+            }
+            let range = frame.len()..frame.len() + bytes.len();
+            frame.extend_from_slice(&bytes);
+            // Como no parser: texto e data/hora ignoram a forma de onda
+            if block.waveform && text_type(&block.data_type).is_none() && time.is_none() {
+                waveform_points.push(value);
+                continue;
+            }
+            variables.push((PlcVariable {
+                name: format!("{}[{}]", block.name, i),
+                value,
+                data_type: block.data_type.clone(),
+                unit: None,
+            }, range));
+        }
+        // Forma de onda: uma variável com o bloco inteiro
+        if !waveform_points.is_empty() {
+            variables.push((PlcVariable {
+                name: block.name.clone(),
+                value: format!("[{}]", waveform_points.join(",")),
+                data_type: format!("{}{}", block.data_type, WAVEFORM_TYPE_SUFFIX),
+                unit: None,
+            }, block_start..frame.len()));
+        }
+    }
+    (frame, variables)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ")
+}
+
+fn check_frame(config: &PlcStructureConfig, layout: &str, name: &str, description: String, bytes: Vec<u8>, expect_accepted: bool) -> TestFrame {
+    let accepted_as = select_frame_layout(config, &bytes).map(|(profile, _)| profile.to_string());
+    let ok = match (&accepted_as, expect_accepted) {
+        (Some(selected), true) => selected == layout,
+        (Some(_), false) => false,
+        (None, accepted) => !accepted,
+    };
+    TestFrame {
+        name: format!("{}:{}", layout, name),
+        layout: layout.to_string(),
+        description,
+        expect_accepted,
+        accepted_as,
+        ok,
+        size: bytes.len(),
+        hex: to_hex(&bytes),
+        bytes,
+    }
+}
+
+/// Frame válido com valores determinísticos (base do simulador para texto e data/hora)
+pub fn valid_frame(blocks: &[DataBlockConfig], byte_order: ByteOrder, seed: Option<u64>) -> Vec<u8> {
+    let mut rng = FrameRng(seed.filter(|s| *s != 0).unwrap_or(DEFAULT_SEED));
+    build_frame(blocks, byte_order, &mut rng).0
+}
+
+/// Gera o frame válido e os frames mutados de cada layout da estrutura
+pub fn generate_test_frames(config: &PlcStructureConfig, seed: Option<u64>) -> Result<TestFrameSet, String> {
+    let seed = seed.filter(|s| *s != 0).unwrap_or(DEFAULT_SEED); // xorshift não aceita semente 0
+    let mut rng = FrameRng(seed);
+    let mut frames = Vec::new();
+    let mut issues = Vec::new();
+    let mut expected_variables = Vec::new();
+
+    let layouts: Vec<(&str, &[DataBlockConfig], Option<(usize, u8)>)> = std::iter::once(("default", config.blocks.as_slice(), None))
+        .chain(config.profiles.iter().map(|p| {
+            (p.name.as_str(), p.blocks.as_slice(), p.type_byte_offset.zip(p.type_byte_value))
+        }))
+        .filter(|(_, blocks, _)| !blocks.is_empty())
+        .collect();
+    if layouts.is_empty() {
+        return Err("Estrutura sem blocos: nada a gerar".to_string());
+    }
+
+    for (layout, blocks, type_byte) in layouts {
+        if let Some(block) = blocks.iter().find(|b| data_type_size(&b.data_type).is_none()) {
+            return Err(format!("Layout '{}': tipo inválido {} no bloco {}", layout, block.data_type, block.name));
+        }
+        let (mut valid, mut variables) = build_frame(blocks, config.byte_order, &mut rng);
+        if valid.is_empty() {
+            issues.push(format!("Layout '{}': todos os blocos têm quantidade 0", layout));
+            continue;
+        }
+
+        // Byte de tipo sobrescreve parte dos dados: essas variáveis não entram na comparação
+        if let Some((offset, value)) = type_byte {
+            if offset >= valid.len() {
+                issues.push(format!("Layout '{}': byte de tipo na posição {} fora do frame de {} bytes", layout, offset, valid.len()));
+            } else {
+                valid[offset] = value;
+                variables.retain(|(_, range)| !range.contains(&offset));
+            }
+        }
+
+        // Frame válido: precisa ser aceito neste layout e devolver os mesmos valores
+        let parsed = select_frame_layout(config, &valid)
+            .filter(|(profile, _)| *profile == layout)
+            .map(|(_, selected_blocks)| parse_with_config(&valid, selected_blocks, config.byte_order));
+        let mut frame = check_frame(config, layout, "valid", format!("Frame válido de {} bytes (semente {})", valid.len(), seed), valid.clone(), true);
+        if let Some(parsed) = parsed {
+            let mismatches: Vec<String> = variables.iter()
+                .filter(|(expected, _)| !parsed.iter().any(|p| p.name == expected.name && p.value == expected.value))
+                .map(|(expected, _)| expected.name.clone())
+                .collect();
+            if !mismatches.is_empty() {
+                frame.ok = false;
+                issues.push(format!("Layout '{}': valores divergentes após o parse em {}", layout, mismatches.join(", ")));
+            }
+        }
+        if !frame.ok {
+            issues.push(format!("Layout '{}': frame válido foi interpretado como {:?}", layout, frame.accepted_as));
+        }
+        frames.push(frame);
+        if layout == "default" {
+            expected_variables = variables.into_iter().map(|(variable, _)| variable).collect();
+        }
+
+        // Frames mutados: todos deveriam ser recusados para este layout
+        let mut mutations: Vec<(&str, String, Vec<u8>)> = vec![
+            ("truncated", "Último byte removido".to_string(), valid[..valid.len() - 1].to_vec()),
+            ("extra_byte", "Um byte 0x00 a mais no fim".to_string(), [valid.as_slice(), &[0x00]].concat()),
+            ("half", format!("Metade do frame ({} bytes)", valid.len() / 2), valid[..valid.len() / 2].to_vec()),
+            ("empty", "Frame vazio".to_string(), Vec::new()),
+        ];
+        if let Some((offset, value)) = type_byte.filter(|(offset, _)| *offset < valid.len()) {
+            let mut wrong_type = valid.clone();
+            wrong_type[offset] = value.wrapping_add(1);
+            mutations.push(("wrong_type_byte", format!("Byte de tipo {} em vez de {}", value.wrapping_add(1), value), wrong_type));
+        }
+
+        for (name, description, bytes) in mutations {
+            let mut frame = check_frame(config, layout, name, description, bytes, false);
+            // Aceito por OUTRO layout com o mesmo tamanho/tipo não é erro deste layout, mas é ambiguidade
+            if let Some(other) = frame.accepted_as.clone().filter(|other| other != layout) {
+                issues.push(format!("Layout '{}': frame '{}' ({} bytes) é aceito como '{}'", layout, name, frame.size, other));
+                frame.ok = true;
+            } else if !frame.ok {
+                issues.push(format!("Layout '{}': frame '{}' ({} bytes) não foi recusado", layout, name, frame.size));
+            }
+            frames.push(frame);
+        }
+    }
+
+    println!("🧪 Frames de teste gerados para {}: {} frames, {} problema(s)", config.plc_ip, frames.len(), issues.len());
+    Ok(TestFrameSet {
+        plc_ip: config.plc_ip.clone(),
+        seed,
+        frames,
+        expected_variables,
+        issues,
+    })
+}
@@ -0,0 +1,540 @@
+// ============================================================================
+// API GRAPHQL OPCIONAL (feature "graphql")
+// ============================================================================
+//
+// Alternativa ao WebSocket para integradores: consultas de PLCs, tags, valores
+// ao vivo (SmartCache), histórico (PostgreSQL) e alarmes, com subscriptions de valores.
+//   POST /graphql  - queries
+//   GET  /graphql  - GraphiQL
+//   WS   /ws       - subscriptions (graphql-ws)
+// Autenticação com as mesmas credenciais do WebSocket (ws_auth.rs), exigida
+// enquanto existir token ativo ou chave pública habilitada: token em
+// "Authorization: Bearer wst_..." / "?token=wst_..." ou chave pública em
+// "X-Public-Key" / "?public_key=". Com chave pública os valores sempre seguem as
+// regras de mascaramento da chave (ws_masking.rs), mesmo com token junto.
+
+use crate::commands::WebSocketServerState;
+use crate::database::Database;
+use std::sync::Arc;
+
+pub const DEFAULT_GRAPHQL_PORT: u16 = 4000;
+
+/// Dependências compartilhadas com os resolvers
+#[derive(Clone)]
+#[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+pub struct GraphqlContext {
+    pub database: Arc<Database>,
+    pub websocket_state: WebSocketServerState,
+}
+
+#[cfg(feature = "graphql")]
+mod server {
+    use super::GraphqlContext;
+    use crate::database::{Database, PublicStreamKey};
+    use crate::historian;
+    use crate::postgres::PgDatabase;
+    use crate::websocket_server::{CachedTagValue, SmartCache};
+    use crate::ws_auth;
+    use crate::ws_masking::{self, StreamMask, TagGroups};
+    use async_graphql::http::GraphiQLSource;
+    use async_graphql::{Context, Data, EmptyMutation, Executor, Object, Schema, SimpleObject, Subscription};
+    use async_graphql_axum::{GraphQL, GraphQLSubscription};
+    use axum::extract::{Request, State};
+    use axum::http::{header, HeaderValue, Method, StatusCode};
+    use axum::middleware::Next;
+    use axum::response::{Html, IntoResponse, Response};
+    use axum::routing::get;
+    use axum::{Extension, Json, Router};
+    use futures_util::stream::BoxStream;
+    use futures_util::Stream;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use tokio::sync::OnceCell;
+    use tower_service::Service;
+
+    const SUBSCRIPTION_POLL_MS: u64 = 250;
+
+    pub type PlcSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
+
+    #[derive(SimpleObject)]
+    pub struct GqlPlc {
+        pub ip: String,
+        pub total_size: i32,
+        pub block_count: i32,
+        pub last_updated: i64,
+        pub tag_count: i32,
+    }
+
+    #[derive(SimpleObject)]
+    pub struct GqlTag {
+        pub plc_ip: String,
+        pub tag_name: String,
+        pub variable_path: String,
+        pub description: Option<String>,
+        pub unit: Option<String>,
+        pub display_unit: Option<String>,
+        pub enabled: bool,
+        pub collect_mode: Option<String>,
+        pub area: Option<String>,
+        pub category: Option<String>,
+        /// Valor atual no SmartCache (null se o WebSocket não estiver rodando)
+        pub value: Option<String>,
+    }
+
+    #[derive(SimpleObject, Clone)]
+    pub struct GqlTagValue {
+        pub plc_ip: String,
+        pub tag_name: String,
+        pub value: String,
+        pub unit: Option<String>,
+        pub timestamp_ms: i64,
+    }
+
+    #[derive(SimpleObject)]
+    pub struct GqlHistorySample {
+        pub ts_ms: i64,
+        pub value: String,
+        pub value_num: Option<f64>,
+        pub unit: Option<String>,
+    }
+
+    #[derive(SimpleObject)]
+    pub struct GqlAlarm {
+        pub id: i64,
+        pub alarm_id: i64,
+        pub plc_ip: String,
+        pub tag_name: String,
+        pub name: String,
+        pub severity: String,
+        pub message: String,
+        pub raised_value: String,
+        pub raised_at_ms: i64,
+        pub cleared_value: Option<String>,
+        pub cleared_at_ms: Option<i64>,
+        pub acked_at_ms: Option<i64>,
+        pub acked_by: Option<String>,
+    }
+
+    /// Estado interno do schema: contexto + pool do historian criado sob demanda
+    pub struct SchemaState {
+        pub context: GraphqlContext,
+        pub historian_pool: OnceCell<PgDatabase>,
+    }
+
+    impl SchemaState {
+        async fn smart_cache(&self) -> Option<Arc<SmartCache>> {
+            self.context.websocket_state.read().await.as_ref().map(|s| s.smart_cache())
+        }
+
+        async fn historian(&self) -> async_graphql::Result<&PgDatabase> {
+            self.historian_pool.get_or_try_init(|| async {
+                let config = self.context.database.load_postgres_config()
+                    .map_err(|e| format!("Erro ao carregar configuração PostgreSQL: {}", e))?
+                    .ok_or_else(|| "PostgreSQL não configurado".to_string())?;
+                PgDatabase::connect(&historian::postgres_url(&config)).await
+                    .map_err(|e| format!("Erro ao conectar no historian: {}", e))
+            }).await.map_err(async_graphql::Error::new)
+        }
+    }
+
+    /// Quem faz a consulta: com chave pública os valores passam pelas regras da chave
+    #[derive(Clone, Default)]
+    pub struct ClientAccess {
+        pub public_key: Option<Arc<PublicStreamKey>>,
+    }
+
+    impl ClientAccess {
+        fn mask(&self) -> Option<StreamMask> {
+            self.public_key.as_deref().map(StreamMask::new)
+        }
+    }
+
+    /// Schema que anexa o acesso do cliente a cada requisição
+    #[derive(Clone)]
+    struct ClientExecutor {
+        schema: PlcSchema,
+        access: ClientAccess,
+    }
+
+    impl Executor for ClientExecutor {
+        async fn execute(&self, request: async_graphql::Request) -> async_graphql::Response {
+            self.schema.execute(request.data(self.access.clone())).await
+        }
+
+        fn execute_stream(&self, request: async_graphql::Request, session_data: Option<Arc<Data>>) -> BoxStream<'static, async_graphql::Response> {
+            self.schema.execute_stream_with_session_data(request.data(self.access.clone()), session_data.unwrap_or_default())
+        }
+    }
+
+    fn now_ms() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+    }
+
+    fn to_gql_value(cached: &CachedTagValue) -> GqlTagValue {
+        GqlTagValue {
+            plc_ip: cached.plc_ip.clone(),
+            tag_name: cached.tag_name.clone(),
+            value: cached.value.clone(),
+            unit: cached.unit.clone(),
+            timestamp_ms: (cached.timestamp_ns / 1_000_000) as i64,
+        }
+    }
+
+    pub struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        /// PLCs com estrutura configurada
+        async fn plcs(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<GqlPlc>> {
+            let state = ctx.data::<Arc<SchemaState>>()?;
+            let db = &state.context.database;
+            let mut result = Vec::new();
+            for ip in db.list_configured_plcs()? {
+                let Some(structure) = db.load_plc_structure(&ip)? else { continue };
+                let tag_count = db.load_tag_mappings(&ip)?.len() as i32;
+                result.push(GqlPlc {
+                    ip,
+                    total_size: structure.total_size as i32,
+                    block_count: structure.blocks.len() as i32,
+                    last_updated: structure.last_updated,
+                    tag_count,
+                });
+            }
+            Ok(result)
+        }
+
+        /// Tags de um PLC com o valor atual do cache
+        async fn tags(&self, ctx: &Context<'_>, plc_ip: String, enabled_only: Option<bool>) -> async_graphql::Result<Vec<GqlTag>> {
+            let state = ctx.data::<Arc<SchemaState>>()?;
+            let mask = ctx.data::<ClientAccess>()?.mask();
+            let mappings = state.context.database.load_tag_mappings(&plc_ip)?;
+            let values: HashMap<String, String> = match state.smart_cache().await {
+                Some(cache) => cache.snapshot(Some(&plc_ip)).into_iter().map(|c| (c.tag_name, c.value)).collect(),
+                None => HashMap::new(),
+            };
+
+            Ok(mappings.into_iter()
+                .filter(|t| !enabled_only.unwrap_or(false) || t.enabled)
+                .filter(|t| !mask.as_ref().is_some_and(|m| m.is_hidden(&t.tag_name, t.area.as_deref(), t.category.as_deref())))
+                .map(|t| GqlTag {
+                    value: values.get(&t.tag_name).and_then(|value| match &mask {
+                        Some(mask) => mask.snapshot_value(&t.tag_name, t.area.as_deref(), t.category.as_deref(), value),
+                        None => Some(value.clone()),
+                    }),
+                    plc_ip: t.plc_ip,
+                    tag_name: t.tag_name,
+                    variable_path: t.variable_path,
+                    description: t.description,
+                    unit: t.unit,
+                    display_unit: t.display_unit,
+                    enabled: t.enabled,
+                    collect_mode: t.collect_mode,
+                    area: t.area,
+                    category: t.category,
+                })
+                .collect())
+        }
+
+        /// Valores ao vivo do SmartCache (opcionalmente filtrados por PLC/tags).
+        /// Chave pública: tags atrasados só saem pela subscription
+        async fn live_values(&self, ctx: &Context<'_>, plc_ip: Option<String>, tag_names: Option<Vec<String>>) -> async_graphql::Result<Vec<GqlTagValue>> {
+            let state = ctx.data::<Arc<SchemaState>>()?;
+            let mask = ctx.data::<ClientAccess>()?.mask();
+            let cache = state.smart_cache().await
+                .ok_or_else(|| async_graphql::Error::new("WebSocket server não está rodando"))?;
+            Ok(cache.snapshot(plc_ip.as_deref()).iter()
+                .filter(|c| tag_names.as_ref().is_none_or_contains(&c.tag_name))
+                .filter_map(|c| {
+                    let mut value = to_gql_value(c);
+                    if let Some(mask) = &mask {
+                        value.value = mask.snapshot_value(&c.tag_name, c.area.as_deref(), c.category.as_deref(), &c.value)?;
+                    }
+                    Some(value)
+                })
+                .collect())
+        }
+
+        /// Histórico de um tag (PostgreSQL)
+        async fn history(&self, ctx: &Context<'_>, plc_ip: String, tag_name: String, from_ms: i64, to_ms: i64, limit: Option<i64>) -> async_graphql::Result<Vec<GqlHistorySample>> {
+            let state = ctx.data::<Arc<SchemaState>>()?;
+            // Chave pública: tag oculto não tem histórico; com atraso, nada mais novo que agora - atraso
+            let (round_decimals, to_ms) = match ctx.data::<ClientAccess>()?.mask() {
+                Some(mask) => {
+                    let mapping = state.context.database.load_tag_mappings(&plc_ip)?
+                        .into_iter()
+                        .find(|m| m.tag_name == tag_name);
+                    let (area, category) = mapping.map(|m| (m.area, m.category)).unwrap_or_default();
+                    let (round_decimals, delay_s) = mask.history_rule(&tag_name, area.as_deref(), category.as_deref())
+                        .ok_or_else(|| async_graphql::Error::new("Tag não disponível para esta chave"))?;
+                    (round_decimals, to_ms.min(now_ms() as i64 - (delay_s * 1000) as i64))
+                }
+                None => (None, to_ms),
+            };
+            let pg = state.historian().await?;
+            let mut samples = historian::fetch_tag_history(&pg.pool, &plc_ip, &tag_name, from_ms, to_ms, limit.unwrap_or(10_000)).await?;
+            let versions = state.context.database.list_tag_unit_versions(Some(&plc_ip), Some(&tag_name))?;
+            crate::units::apply_unit_versions(&mut samples, &versions);
+            Ok(samples.into_iter()
+                .map(|s| match round_decimals {
+                    Some(decimals) => {
+                        let value = ws_masking::round_value(&s.value, decimals);
+                        GqlHistorySample { ts_ms: s.ts_ms, value_num: s.value_num.and(value.parse().ok()), value, unit: s.unit }
+                    }
+                    None => GqlHistorySample { ts_ms: s.ts_ms, value: s.value, value_num: s.value_num, unit: s.unit },
+                })
+                .collect())
+        }
+
+        /// Ocorrências do motor de alarmes: em aberto (padrão) ou histórico das últimas 24h.
+        /// Não disponível para chaves públicas (valores sem atraso nem arredondamento)
+        async fn alarms(&self, ctx: &Context<'_>, active_only: Option<bool>, plc_ip: Option<String>, limit: Option<i32>) -> async_graphql::Result<Vec<GqlAlarm>> {
+            let state = ctx.data::<Arc<SchemaState>>()?;
+            if ctx.data::<ClientAccess>()?.public_key.is_some() {
+                return Err(async_graphql::Error::new("Alarmes não disponíveis para chaves públicas"));
+            }
+            let db = &state.context.database;
+            let occurrences = if active_only.unwrap_or(true) {
+                db.list_active_alarms()?.into_iter()
+                    .filter(|a| plc_ip.as_ref().map_or(true, |ip| &a.plc_ip == ip))
+                    .collect()
+            } else {
+                let now_ms = chrono::Utc::now().timestamp_millis();
+                db.list_alarm_history(now_ms - 86_400_000, now_ms, plc_ip.as_deref(), limit.unwrap_or(1_000).max(0) as u32)?
+            };
+            Ok(occurrences.into_iter()
+                .map(|a| GqlAlarm {
+                    id: a.id,
+                    alarm_id: a.alarm_id,
+                    plc_ip: a.plc_ip,
+                    tag_name: a.tag_name,
+                    name: a.name,
+                    severity: a.severity,
+                    message: a.message,
+                    raised_value: a.raised_value,
+                    raised_at_ms: a.raised_at_ms,
+                    cleared_value: a.cleared_value,
+                    cleared_at_ms: a.cleared_at_ms,
+                    acked_at_ms: a.acked_at_ms,
+                    acked_by: a.acked_by,
+                })
+                .collect())
+        }
+    }
+
+    /// Helper para filtros opcionais de lista
+    trait OptionalFilter {
+        fn is_none_or_contains(&self, item: &String) -> bool;
+    }
+
+    impl OptionalFilter for Option<&Vec<String>> {
+        fn is_none_or_contains(&self, item: &String) -> bool {
+            self.map(|list| list.contains(item)).unwrap_or(true)
+        }
+    }
+
+    /// Subscription com chave pública: uma StreamMask por PLC (a fila de atraso
+    /// da máscara é por nome de tag)
+    struct PublicStream {
+        key: Arc<PublicStreamKey>,
+        masks: HashMap<String, StreamMask>,
+        units: HashMap<(String, String), Option<String>>,
+    }
+
+    impl PublicStream {
+        fn new(key: Arc<PublicStreamKey>) -> Self {
+            Self { key, masks: HashMap::new(), units: HashMap::new() }
+        }
+
+        /// Mudanças deste ciclo pelas regras da chave + atrasados que venceram
+        fn apply(&mut self, changed: Vec<&CachedTagValue>, now_ms: u64) -> Vec<GqlTagValue> {
+            let mut batches: HashMap<String, (HashMap<String, String>, HashMap<String, TagGroups>)> = self.masks.keys()
+                .map(|plc_ip| (plc_ip.clone(), Default::default()))
+                .collect();
+            for cached in changed {
+                self.units.insert((cached.plc_ip.clone(), cached.tag_name.clone()), cached.unit.clone());
+                let (tags, groups) = batches.entry(cached.plc_ip.clone()).or_default();
+                tags.insert(cached.tag_name.clone(), cached.value.clone());
+                groups.insert(cached.tag_name.clone(), TagGroups {
+                    area: cached.area.clone(),
+                    category: cached.category.clone(),
+                    ts_ms: (cached.timestamp_ns / 1_000_000) as u64,
+                });
+            }
+
+            let mut values = Vec::new();
+            for (plc_ip, (tags, groups)) in batches {
+                let mask = self.masks.entry(plc_ip.clone()).or_insert_with(|| StreamMask::new(&self.key));
+                let (output, original_ts) = mask.apply(tags, &groups, now_ms);
+                for (tag_name, value) in output {
+                    let ts_ms = original_ts.get(&tag_name).copied()
+                        .or_else(|| groups.get(&tag_name).map(|g| g.ts_ms))
+                        .unwrap_or(now_ms);
+                    values.push(GqlTagValue {
+                        unit: self.units.get(&(plc_ip.clone(), tag_name.clone())).cloned().flatten(),
+                        plc_ip: plc_ip.clone(),
+                        tag_name,
+                        value,
+                        timestamp_ms: ts_ms as i64,
+                    });
+                }
+            }
+            values
+        }
+    }
+
+    pub struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        /// Emite os tags cujo valor mudou desde o último envio (verificação a cada 250ms)
+        async fn tag_values(&self, ctx: &Context<'_>, plc_ip: Option<String>, tag_names: Option<Vec<String>>) -> async_graphql::Result<impl Stream<Item = Vec<GqlTagValue>>> {
+            let state = ctx.data::<Arc<SchemaState>>()?.clone();
+            let public = ctx.data::<ClientAccess>()?.public_key.clone().map(PublicStream::new);
+            let last_values: HashMap<String, String> = HashMap::new();
+
+            Ok(futures_util::stream::unfold((state, last_values, public), move |(state, mut last_values, mut public)| {
+                let plc_ip = plc_ip.clone();
+                let tag_names = tag_names.clone();
+                async move {
+                    loop {
+                        tokio::time::sleep(Duration::from_millis(SUBSCRIPTION_POLL_MS)).await;
+                        let Some(cache) = state.smart_cache().await else { continue };
+
+                        let snapshot = cache.snapshot(plc_ip.as_deref());
+                        let changed: Vec<&CachedTagValue> = snapshot.iter()
+                            .filter(|c| tag_names.as_ref().is_none_or_contains(&c.tag_name))
+                            .filter(|c| {
+                                let key = format!("{}:{}", c.plc_ip, c.tag_name);
+                                let is_new = last_values.get(&key) != Some(&c.value);
+                                if is_new {
+                                    last_values.insert(key, c.value.clone());
+                                }
+                                is_new
+                            })
+                            .collect();
+                        let changed: Vec<GqlTagValue> = match public.as_mut() {
+                            Some(public) => public.apply(changed, now_ms()),
+                            None => changed.into_iter().map(to_gql_value).collect(),
+                        };
+
+                        if !changed.is_empty() {
+                            return Some((changed, (state, last_values, public)));
+                        }
+                    }
+                }
+            }))
+        }
+    }
+
+    async fn graphiql() -> impl IntoResponse {
+        Html(GraphiQLSource::build().endpoint("/graphql").subscription_endpoint("/ws").finish())
+    }
+
+    /// Cabeçalhos X-Instance-* em toda resposta HTTP (ver config.rs)
+    async fn instance_headers(mut response: Response) -> Response {
+        for (name, value) in crate::config::current_instance().http_headers() {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+        response
+    }
+
+    fn unauthorized(status: StatusCode, message: String) -> Response {
+        (status, Json(serde_json::json!({ "errors": [{ "message": message }] }))).into_response()
+    }
+
+    /// Credencial exigida só quando existe token ativo ou chave pública habilitada
+    /// (mesma regra do WebSocket e da API REST). A chave pública é credencial
+    /// própria e vai anexada à requisição como ClientAccess (valores mascarados).
+    async fn authorize(State(database): State<Arc<Database>>, mut request: Request, next: Next) -> Response {
+        let query = request.uri().query();
+        // A página do GraphiQL é estática; as consultas feitas por ela passam por aqui
+        let is_graphiql = request.method() == Method::GET && request.uri().path() == "/graphql";
+        let public_key = ws_auth::public_key_from_request(
+            request.headers().get("x-public-key").and_then(|value| value.to_str().ok()), query);
+
+        let access = match public_key {
+            Some(key) => match ws_auth::authenticate_public_key(&database, &key) {
+                Ok(key) => ClientAccess { public_key: Some(Arc::new(key)) },
+                Err(e) => return unauthorized(StatusCode::UNAUTHORIZED, e),
+            },
+            None => {
+                if !is_graphiql && ws_auth::auth_required(&database) {
+                    let authorization = request.headers().get(header::AUTHORIZATION).and_then(|value| value.to_str().ok());
+                    let authenticated = ws_auth::token_from_request(authorization, query)
+                        .ok_or_else(|| "Token de API ou chave pública obrigatório".to_string())
+                        .and_then(|token| ws_auth::authenticate(&database, &token));
+                    if let Err(e) = authenticated {
+                        return unauthorized(StatusCode::UNAUTHORIZED, e);
+                    }
+                }
+                ClientAccess::default()
+            }
+        };
+        request.extensions_mut().insert(access);
+        next.run(request).await
+    }
+
+    async fn graphql_query(State(schema): State<PlcSchema>, Extension(access): Extension<ClientAccess>, request: Request) -> Response {
+        let response = GraphQL::new(ClientExecutor { schema, access }).call(request).await;
+        response.unwrap_or_else(|never| match never {}).into_response()
+    }
+
+    async fn graphql_subscription(State(schema): State<PlcSchema>, Extension(access): Extension<ClientAccess>, request: Request) -> Response {
+        let response = GraphQLSubscription::new(ClientExecutor { schema, access }).call(request).await;
+        response.unwrap_or_else(|never| match never {}).into_response()
+    }
+
+    pub fn build_router(context: GraphqlContext) -> Router {
+        let database = context.database.clone();
+        let state = Arc::new(SchemaState { context, historian_pool: OnceCell::new() });
+        let schema: PlcSchema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+            .data(state)
+            .finish();
+
+        Router::new()
+            .route("/graphql", get(graphiql).post(graphql_query))
+            .route("/ws", get(graphql_subscription))
+            .layer(axum::middleware::from_fn_with_state(database, authorize))
+            .layer(axum::middleware::map_response(instance_headers))
+            .with_state(schema)
+    }
+}
+
+/// Servidor GraphQL em execução
+#[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+pub struct GraphqlServer {
+    pub address: String,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl GraphqlServer {
+    #[cfg(feature = "graphql")]
+    pub async fn start(context: GraphqlContext, host: &str, port: u16) -> Result<Self, String> {
+        let address = format!("{}:{}", host, port);
+        let listener = tokio::net::TcpListener::bind(&address).await
+            .map_err(|e| format!("Erro ao abrir porta GraphQL {}: {}", address, e))?;
+        let router = server::build_router(context);
+
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                println!("❌ Servidor GraphQL finalizado com erro: {}", e);
+            }
+        });
+
+        println!("🚀 GraphQL disponível em http://{}/graphql", address);
+        Ok(Self { address, handle })
+    }
+
+    #[cfg(not(feature = "graphql"))]
+    pub async fn start(_context: GraphqlContext, _host: &str, _port: u16) -> Result<Self, String> {
+        Err("Aplicação compilada sem suporte a GraphQL (habilite a feature \"graphql\")".to_string())
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+        println!("🛑 Servidor GraphQL parado ({})", self.address);
+    }
+}
@@ -0,0 +1,264 @@
+// ============================================================================
+// HEALTH CHECK - /healthz PARA WATCHDOGS E BALANCEADORES
+// ============================================================================
+//
+// Servidor HTTP mínimo (sem framework): só responde GET/HEAD /healthz.
+//   200 = tudo dentro dos limites configurados
+//   503 = alguma verificação falhou (detalhes em "failures")
+// O mesmo relatório é exposto ao frontend pelo comando `get_health_status`.
+// Toda resposta leva os cabeçalhos X-Instance-Id/-Name/-Site (config.rs).
+
+use crate::commands::{TcpServerState, WebSocketServerState};
+use crate::config::InstanceIdentity;
+use crate::database::{Database, HealthConfig};
+use serde::Serialize;
+use sqlx::Connection;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const POSTGRES_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlcDataAge {
+    pub ip: String,
+    pub last_data_age_ms: u64,
+    pub packet_count: u64,
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TcpHealth {
+    pub running: bool,
+    pub active_connections: u64,
+    pub plcs: Vec<PlcDataAge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebSocketHealth {
+    pub running: bool,
+    pub active_connections: u64,
+    pub uptime_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseHealth {
+    pub sqlite_ok: bool,
+    pub sqlite_error: Option<String>,
+    pub postgres_ok: Option<bool>, // None = não verificado
+    pub postgres_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub status: String, // "ok" | "unhealthy"
+    pub timestamp: String,
+    pub max_data_age_ms: u64,
+    pub tcp_server: TcpHealth,
+    pub websocket_server: WebSocketHealth,
+    pub database: DatabaseHealth,
+    pub failures: Vec<String>,
+    pub instance: InstanceIdentity, // 🆕 Servidor que gerou o relatório
+}
+
+/// Dependências do health check (clonáveis para a task do servidor HTTP)
+#[derive(Clone)]
+pub struct HealthContext {
+    pub database: Arc<Database>,
+    pub tcp_state: TcpServerState,
+    pub websocket_state: WebSocketServerState,
+}
+
+/// Coleta o estado atual e aplica os limites da configuração
+pub async fn collect_health(context: &HealthContext, config: &HealthConfig) -> HealthReport {
+    let mut failures = Vec::new();
+
+    // TCP: servidor + idade do último dado de cada PLC conectado
+    let tcp_server = match context.tcp_state.read().await.as_ref() {
+        Some(server) => {
+            let plcs: Vec<PlcDataAge> = server.get_connection_health().await.into_iter()
+                .filter(|h| h.is_alive)
+                .map(|h| {
+                    let age_ms = h.last_data_received.elapsed().as_millis() as u64;
+                    PlcDataAge {
+                        ip: h.ip,
+                        last_data_age_ms: age_ms,
+                        packet_count: h.packet_count,
+                        stale: age_ms > config.max_data_age_ms,
+                    }
+                })
+                .collect();
+            TcpHealth {
+                running: server.is_running(),
+                active_connections: server.get_connection_stats().await.active_connections,
+                plcs,
+            }
+        }
+        None => TcpHealth { running: false, active_connections: 0, plcs: Vec::new() },
+    };
+    if config.require_tcp_server && !tcp_server.running {
+        failures.push("Servidor TCP parado".to_string());
+    }
+    if config.require_plc_data && tcp_server.plcs.is_empty() {
+        failures.push("Nenhum PLC conectado".to_string());
+    }
+    for plc in tcp_server.plcs.iter().filter(|p| p.stale) {
+        failures.push(format!("PLC {} sem dados há {} ms", plc.ip, plc.last_data_age_ms));
+    }
+
+    // WebSocket
+    let websocket_server = match context.websocket_state.read().await.as_ref() {
+        Some(server) => {
+            let stats = server.get_stats();
+            WebSocketHealth {
+                running: server.is_running(),
+                active_connections: stats.active_connections,
+                uptime_seconds: stats.uptime_seconds,
+            }
+        }
+        None => WebSocketHealth { running: false, active_connections: 0, uptime_seconds: 0 },
+    };
+    if config.require_websocket && !websocket_server.running {
+        failures.push("Servidor WebSocket parado".to_string());
+    }
+
+    // Banco de dados
+    let sqlite_error = context.database.ping().err().map(|e| e.to_string());
+    if let Some(e) = &sqlite_error {
+        failures.push(format!("SQLite indisponível: {}", e));
+    }
+    let (postgres_ok, postgres_error) = if config.check_postgres {
+        match check_postgres(&context.database).await {
+            Ok(()) => (Some(true), None),
+            Err(e) => {
+                failures.push(format!("PostgreSQL indisponível: {}", e));
+                (Some(false), Some(e))
+            }
+        }
+    } else {
+        (None, None)
+    };
+
+    let healthy = failures.is_empty();
+    HealthReport {
+        healthy,
+        status: if healthy { "ok" } else { "unhealthy" }.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        max_data_age_ms: config.max_data_age_ms,
+        tcp_server,
+        websocket_server,
+        database: DatabaseHealth {
+            sqlite_ok: sqlite_error.is_none(),
+            sqlite_error,
+            postgres_ok,
+            postgres_error,
+        },
+        failures,
+        instance: crate::config::current_instance(),
+    }
+}
+
+/// Conexão avulsa (sem pool) para não manter conexões abertas entre checagens
+async fn check_postgres(database: &Database) -> Result<(), String> {
+    let config = database.load_postgres_config()
+        .map_err(|e| format!("Erro ao carregar configuração: {}", e))?
+        .ok_or_else(|| "PostgreSQL não configurado".to_string())?;
+    let url = crate::historian::postgres_url(&config);
+
+    let check = async {
+        let mut conn = sqlx::postgres::PgConnection::connect(&url).await?;
+        sqlx::query("SELECT 1").execute(&mut conn).await?;
+        conn.close().await
+    };
+    match tokio::time::timeout(POSTGRES_CHECK_TIMEOUT, check).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("Timeout após {} s", POSTGRES_CHECK_TIMEOUT.as_secs())),
+    }
+}
+
+// ============================================================================
+// SERVIDOR HTTP
+// ============================================================================
+
+pub struct HealthServer {
+    pub address: String,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl HealthServer {
+    pub async fn start(context: HealthContext, config: HealthConfig) -> Result<Self, String> {
+        let address = format!("{}:{}", config.bind_host, config.port);
+        let listener = tokio::net::TcpListener::bind(&address).await
+            .map_err(|e| format!("Erro ao abrir porta do health check {}: {}", address, e))?;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let context = context.clone();
+                        let config = config.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_request(stream, &context, &config).await {
+                                println!("⚠️ Health check: erro na requisição: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        println!("❌ Health check: erro ao aceitar conexão: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        });
+
+        println!("🚀 Health check disponível em http://{}/healthz", address);
+        Ok(Self { address, handle })
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+        println!("🛑 Health check parado ({})", self.address);
+    }
+}
+
+async fn handle_request(mut stream: TcpStream, context: &HealthContext, config: &HealthConfig) -> std::io::Result<()> {
+    // Só a linha de requisição importa: "GET /healthz HTTP/1.1"
+    let mut buffer = [0u8; 2048];
+    let n = match tokio::time::timeout(REQUEST_READ_TIMEOUT, stream.read(&mut buffer)).await {
+        Ok(result) => result?,
+        Err(_) => return Ok(()),
+    };
+    let request = String::from_utf8_lossy(&buffer[..n]);
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("");
+
+    let (status_line, body) = match (method, path) {
+        ("GET" | "HEAD", "/healthz") => {
+            let report = collect_health(context, config).await;
+            let status_line = if report.healthy { "200 OK" } else { "503 Service Unavailable" };
+            (status_line, serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string()))
+        }
+        ("GET" | "HEAD", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+    };
+
+    let instance_headers: String = crate::config::current_instance().http_headers().into_iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\n{}Connection: close\r\n\r\n",
+        status_line,
+        body.len(),
+        instance_headers
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,702 @@
+use crate::database::PostgresConfig;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres, Row};
+use std::collections::{BTreeMap, HashMap};
+
+// ============================================================================
+// HISTORIAN - LEITURA DE VALORES HISTÓRICOS DE TAGS (PostgreSQL)
+// ============================================================================
+//
+// Tabela esperada no PostgreSQL:
+//   tag_history (plc_ip TEXT, tag_name TEXT, value TEXT, value_num DOUBLE PRECISION, ts_ms BIGINT)
+// `ts_ms` é o timestamp Unix em milissegundos da amostra. Criada pelo historian
+// (historian_writer.rs) particionada por mês: tag_history_yAAAAmMM + tag_history_default.
+
+/// Monta a URL de conexão a partir da configuração salva no SQLite
+pub fn postgres_url(config: &PostgresConfig) -> String {
+    format!(
+        "postgresql://{}:{}@{}:{}/{}",
+        config.user, config.password, config.host, config.port, config.database
+    )
+}
+
+/// Valor de um tag em um instante (última amostra <= instante)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotValue {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub value: String,
+    pub value_num: Option<f64>,
+    pub ts_ms: i64,
+    // 🆕 Unidade da amostra, preenchida nas consultas que aplicam as versões da unidade do tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSnapshotDiff {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub value_t1: Option<String>,
+    pub value_t2: Option<String>,
+    pub delta: Option<f64>,       // Somente para valores numéricos
+    pub status: String,           // "changed", "unchanged", "added", "removed"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotComparison {
+    pub t1: i64,
+    pub t2: i64,
+    pub total_tags: usize,
+    pub changed_count: usize,
+    pub changes: Vec<TagSnapshotDiff>,
+}
+
+/// Busca o último valor conhecido de cada tag até o instante `ts_ms`
+pub async fn fetch_snapshot(pool: &Pool<Postgres>, ts_ms: i64) -> Result<Vec<SnapshotValue>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT ON (plc_ip, tag_name) plc_ip, tag_name, value, value_num, ts_ms
+         FROM tag_history
+         WHERE ts_ms <= $1
+         ORDER BY plc_ip, tag_name, ts_ms DESC"
+    )
+    .bind(ts_ms)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| SnapshotValue {
+        plc_ip: row.get("plc_ip"),
+        tag_name: row.get("tag_name"),
+        value: row.get("value"),
+        value_num: row.get("value_num"),
+        ts_ms: row.get("ts_ms"),
+        unit: None,
+    }).collect())
+}
+
+/// Busca as amostras com `after_ms < ts_ms <= until_ms` em ordem cronológica (playback)
+pub async fn fetch_range(
+    pool: &Pool<Postgres>,
+    plc_ip: Option<&str>,
+    after_ms: i64,
+    until_ms: i64,
+) -> Result<Vec<SnapshotValue>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT plc_ip, tag_name, value, value_num, ts_ms
+         FROM tag_history
+         WHERE ts_ms > $1 AND ts_ms <= $2 AND ($3::TEXT IS NULL OR plc_ip = $3)
+         ORDER BY ts_ms"
+    )
+    .bind(after_ms)
+    .bind(until_ms)
+    .bind(plc_ip)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| SnapshotValue {
+        plc_ip: row.get("plc_ip"),
+        tag_name: row.get("tag_name"),
+        value: row.get("value"),
+        value_num: row.get("value_num"),
+        ts_ms: row.get("ts_ms"),
+        unit: None,
+    }).collect())
+}
+
+/// Página de amostras com `from_ms <= ts_ms <= until_ms` depois do cursor
+/// (ts_ms, plc_ip, tag_name), na mesma ordem; o cursor da próxima página é a
+/// última amostra devolvida. Replica faixas grandes sem carregar tudo na memória.
+pub async fn fetch_range_page(
+    pool: &Pool<Postgres>,
+    from_ms: i64,
+    until_ms: i64,
+    after: Option<(i64, &str, &str)>,
+    limit: usize,
+) -> Result<Vec<SnapshotValue>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT plc_ip, tag_name, value, value_num, ts_ms
+         FROM tag_history
+         WHERE ts_ms >= $1 AND ts_ms <= $2
+           AND ($3::BIGINT IS NULL OR (ts_ms, plc_ip, tag_name) > ($3, $4::TEXT, $5::TEXT))
+         ORDER BY ts_ms, plc_ip, tag_name
+         LIMIT $6"
+    )
+    .bind(from_ms)
+    .bind(until_ms)
+    .bind(after.map(|a| a.0))
+    .bind(after.map(|a| a.1))
+    .bind(after.map(|a| a.2))
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| SnapshotValue {
+        plc_ip: row.get("plc_ip"),
+        tag_name: row.get("tag_name"),
+        value: row.get("value"),
+        value_num: row.get("value_num"),
+        ts_ms: row.get("ts_ms"),
+        unit: None,
+    }).collect())
+}
+
+/// Amostras de um único tag em uma janela de tempo (mais antigas primeiro)
+pub async fn fetch_tag_history(
+    pool: &Pool<Postgres>,
+    plc_ip: &str,
+    tag_name: &str,
+    from_ms: i64,
+    to_ms: i64,
+    limit: i64,
+) -> Result<Vec<SnapshotValue>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT plc_ip, tag_name, value, value_num, ts_ms
+         FROM tag_history
+         WHERE plc_ip = $1 AND tag_name = $2 AND ts_ms >= $3 AND ts_ms <= $4
+         ORDER BY ts_ms
+         LIMIT $5"
+    )
+    .bind(plc_ip)
+    .bind(tag_name)
+    .bind(from_ms)
+    .bind(to_ms)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| SnapshotValue {
+        plc_ip: row.get("plc_ip"),
+        tag_name: row.get("tag_name"),
+        value: row.get("value"),
+        value_num: row.get("value_num"),
+        ts_ms: row.get("ts_ms"),
+        unit: None,
+    }).collect())
+}
+
+// 🆕 LEITURA EM UM INSTANTE ARBITRÁRIO (relatórios alinhados a horários exatos)
+// O historian grava por mudança/intervalo, então o instante pedido quase nunca
+// tem amostra: o valor vem das amostras vizinhas. "step" repete a última
+// amostra <= instante (o valor que estava no PLC); "linear" interpola entre
+// ela e a próxima, só para valores numéricos (texto/bits caem para step).
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    Step,
+    Linear,
+}
+
+impl Interpolation {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "step" | "previous" => Some(Interpolation::Step),
+            "linear" => Some(Interpolation::Linear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterpolatedValue {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub ts_ms: i64,
+    pub value: Option<String>,       // None = nenhuma amostra até o instante
+    pub value_num: Option<f64>,
+    pub unit: Option<String>,
+    pub quality: String,             // "exact", "interpolated", "step", "no_data"
+    pub before_ts_ms: Option<i64>,   // Amostras usadas
+    pub after_ts_ms: Option<i64>,
+}
+
+/// Última amostra <= `ts_ms` e primeira amostra > `ts_ms` de um tag
+pub async fn fetch_neighbors(
+    pool: &Pool<Postgres>,
+    plc_ip: &str,
+    tag_name: &str,
+    ts_ms: i64,
+) -> Result<(Option<SnapshotValue>, Option<SnapshotValue>), sqlx::Error> {
+    let to_sample = |row: sqlx::postgres::PgRow| SnapshotValue {
+        plc_ip: row.get("plc_ip"),
+        tag_name: row.get("tag_name"),
+        value: row.get("value"),
+        value_num: row.get("value_num"),
+        ts_ms: row.get("ts_ms"),
+        unit: None,
+    };
+    let before = sqlx::query(
+        "SELECT plc_ip, tag_name, value, value_num, ts_ms
+         FROM tag_history
+         WHERE plc_ip = $1 AND tag_name = $2 AND ts_ms <= $3
+         ORDER BY ts_ms DESC
+         LIMIT 1"
+    )
+    .bind(plc_ip)
+    .bind(tag_name)
+    .bind(ts_ms)
+    .fetch_optional(pool)
+    .await?
+    .map(to_sample);
+    let after = sqlx::query(
+        "SELECT plc_ip, tag_name, value, value_num, ts_ms
+         FROM tag_history
+         WHERE plc_ip = $1 AND tag_name = $2 AND ts_ms > $3
+         ORDER BY ts_ms
+         LIMIT 1"
+    )
+    .bind(plc_ip)
+    .bind(tag_name)
+    .bind(ts_ms)
+    .fetch_optional(pool)
+    .await?
+    .map(to_sample);
+    Ok((before, after))
+}
+
+/// Valor no instante `ts_ms` a partir das amostras vizinhas
+pub fn interpolate(
+    plc_ip: &str,
+    tag_name: &str,
+    ts_ms: i64,
+    before: Option<&SnapshotValue>,
+    after: Option<&SnapshotValue>,
+    mode: Interpolation,
+) -> InterpolatedValue {
+    let mut result = InterpolatedValue {
+        plc_ip: plc_ip.to_string(),
+        tag_name: tag_name.to_string(),
+        ts_ms,
+        value: None,
+        value_num: None,
+        unit: before.or(after).and_then(|s| s.unit.clone()),
+        quality: "no_data".to_string(),
+        before_ts_ms: before.map(|s| s.ts_ms),
+        after_ts_ms: None,
+    };
+    // Antes da primeira amostra não há valor: o tag ainda não existia no historian
+    let Some(before) = before else { return result };
+    result.value = Some(before.value.clone());
+    result.value_num = before.value_num;
+    result.quality = if before.ts_ms == ts_ms { "exact" } else { "step" }.to_string();
+
+    if mode == Interpolation::Linear && before.ts_ms < ts_ms {
+        if let Some(after) = after {
+            if let (Some(v0), Some(v1)) = (before.value_num, after.value_num) {
+                let fraction = (ts_ms - before.ts_ms) as f64 / (after.ts_ms - before.ts_ms) as f64;
+                let value = v0 + (v1 - v0) * fraction;
+                result.value = Some(crate::units::format_value(value));
+                result.value_num = Some(value);
+                result.after_ts_ms = Some(after.ts_ms);
+                result.quality = "interpolated".to_string();
+            }
+        }
+    }
+    result
+}
+
+/// Renomeia o tag no histórico dentro de uma transação aberta pelo chamador.
+/// Tabela ainda inexistente (historian não inicializado) conta como 0 linhas.
+pub async fn rename_tag_history(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    plc_ip: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("UPDATE tag_history SET tag_name = $1 WHERE plc_ip = $2 AND tag_name = $3")
+        .bind(new_name)
+        .bind(plc_ip)
+        .bind(old_name)
+        .execute(&mut **tx)
+        .await;
+
+    match result {
+        Ok(done) => Ok(done.rows_affected()),
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+/// Move o histórico de um PLC para o novo IP dentro de uma transação aberta pelo
+/// chamador. Retorna (amostras, formas de onda); tabelas inexistentes contam como 0.
+pub async fn migrate_plc_history(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    old_ip: &str,
+    new_ip: &str,
+) -> Result<(u64, u64), sqlx::Error> {
+    let mut moved = [0u64; 2];
+    for (index, table) in ["tag_history", "tag_waveforms"].iter().enumerate() {
+        let query = format!("UPDATE {} SET plc_ip = $1 WHERE plc_ip = $2", table);
+        moved[index] = match sqlx::query(&query).bind(new_ip).bind(old_ip).execute(&mut **tx).await {
+            Ok(done) => done.rows_affected(),
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => 0,
+            Err(e) => return Err(e),
+        };
+    }
+    Ok((moved[0], moved[1]))
+}
+
+/// Prévia de `migrate_plc_history`: linhas de (amostras, formas de onda) do PLC
+pub async fn count_plc_history(pool: &Pool<Postgres>, plc_ip: &str) -> Result<(u64, u64), sqlx::Error> {
+    let mut counts = [0u64; 2];
+    for (index, table) in ["tag_history", "tag_waveforms"].iter().enumerate() {
+        let query = format!("SELECT COUNT(*) AS total FROM {} WHERE plc_ip = $1", table);
+        counts[index] = match sqlx::query(&query).bind(plc_ip).fetch_one(pool).await {
+            Ok(row) => row.get::<i64, _>("total").max(0) as u64,
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => 0,
+            Err(e) => return Err(e),
+        };
+    }
+    Ok((counts[0], counts[1]))
+}
+
+/// Linhas de histórico por tag (amostras + formas de onda) para a análise de impacto.
+/// Tabelas ainda inexistentes contam como 0.
+pub async fn count_tag_history(
+    pool: &Pool<Postgres>,
+    plc_ip: &str,
+    tag_names: &[String],
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let mut counts = HashMap::new();
+    for table in ["tag_history", "tag_waveforms"] {
+        let query = format!(
+            "SELECT tag_name, COUNT(*) AS total FROM {} WHERE plc_ip = $1 AND tag_name = ANY($2) GROUP BY tag_name",
+            table
+        );
+        let rows = match sqlx::query(&query).bind(plc_ip).bind(tag_names).fetch_all(pool).await {
+            Ok(rows) => rows,
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => continue,
+            Err(e) => return Err(e),
+        };
+        for row in rows {
+            *counts.entry(row.get::<String, _>("tag_name")).or_insert(0) += row.get::<i64, _>("total");
+        }
+    }
+    Ok(counts)
+}
+
+// ============================================================================
+// LACUNAS E BACKFILL
+// ============================================================================
+
+/// Intervalo sem nenhuma amostra de um PLC no histórico
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryGap {
+    pub plc_ip: String,
+    pub from_ms: i64,      // Última amostra antes da lacuna
+    pub to_ms: i64,        // Primeira amostra depois da lacuna
+    pub duration_ms: i64,
+}
+
+/// Cria a tabela do historian se ainda não existir (primeira escrita).
+/// Instalações novas ganham a tabela particionada por mês (ts_ms) com uma
+/// partição padrão; tabelas antigas sem partição continuam funcionando.
+pub async fn ensure_history_table(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS tag_history (
+            plc_ip TEXT NOT NULL,
+            tag_name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            value_num DOUBLE PRECISION,
+            ts_ms BIGINT NOT NULL
+        ) PARTITION BY RANGE (ts_ms)"
+    )
+    .execute(pool)
+    .await?;
+    if is_partitioned(pool).await? {
+        sqlx::query("CREATE TABLE IF NOT EXISTS tag_history_default PARTITION OF tag_history DEFAULT")
+            .execute(pool)
+            .await?;
+    }
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tag_history_plc_tag_ts ON tag_history (plc_ip, tag_name, ts_ms)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn is_partitioned(pool: &Pool<Postgres>) -> Result<bool, sqlx::Error> {
+    let kind = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT relkind::TEXT FROM pg_class WHERE oid = to_regclass('tag_history')"
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+    Ok(kind.as_deref() == Some("p"))
+}
+
+/// Nome da partição mensal e a faixa [início, fim) em ms (UTC) que contém `ts_ms`
+pub fn month_partition(ts_ms: i64) -> (String, i64, i64) {
+    use chrono::{Datelike, TimeZone, Utc};
+    let date = Utc.timestamp_millis_opt(ts_ms).single().unwrap_or_else(Utc::now);
+    let start = Utc.with_ymd_and_hms(date.year(), date.month(), 1, 0, 0, 0).unwrap();
+    let (next_year, next_month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+    let end = Utc.with_ymd_and_hms(next_year, next_month, 1, 0, 0, 0).unwrap();
+    (format!("tag_history_y{}m{:02}", date.year(), date.month()), start.timestamp_millis(), end.timestamp_millis())
+}
+
+/// Cria a partição mensal de `ts_ms`. Sem particionamento não faz nada. Se a
+/// partição padrão já tiver linhas do mês, elas ficam lá (retorna Ok(false)).
+pub async fn ensure_month_partition(pool: &Pool<Postgres>, ts_ms: i64) -> Result<bool, sqlx::Error> {
+    if !is_partitioned(pool).await? {
+        return Ok(false);
+    }
+    let (name, from_ms, to_ms) = month_partition(ts_ms);
+    let query = format!(
+        "CREATE TABLE IF NOT EXISTS {} PARTITION OF tag_history FOR VALUES FROM ({}) TO ({})",
+        name, from_ms, to_ms
+    );
+    match sqlx::query(&query).execute(pool).await {
+        Ok(_) => Ok(true),
+        // 23514: linhas do mês já gravadas na partição padrão
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23514") => {
+            println!("⚠️ Historian: partição {} não criada (mês já presente na partição padrão)", name);
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Insere amostras com o timestamp original. Amostras já existentes
+/// (mesmo PLC, tag e ts_ms) são ignoradas, então reenviar o mesmo backfill é seguro.
+pub async fn insert_samples(pool: &Pool<Postgres>, samples: &[SnapshotValue]) -> Result<u64, sqlx::Error> {
+    if samples.is_empty() {
+        return Ok(0);
+    }
+    let plc_ips: Vec<&str> = samples.iter().map(|s| s.plc_ip.as_str()).collect();
+    let tag_names: Vec<&str> = samples.iter().map(|s| s.tag_name.as_str()).collect();
+    let values: Vec<&str> = samples.iter().map(|s| s.value.as_str()).collect();
+    let value_nums: Vec<Option<f64>> = samples.iter().map(|s| s.value_num).collect();
+    let timestamps: Vec<i64> = samples.iter().map(|s| s.ts_ms).collect();
+
+    let result = sqlx::query(
+        "INSERT INTO tag_history (plc_ip, tag_name, value, value_num, ts_ms)
+         SELECT s.plc_ip, s.tag_name, s.value, s.value_num, s.ts_ms
+         FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::DOUBLE PRECISION[], $5::BIGINT[])
+              AS s(plc_ip, tag_name, value, value_num, ts_ms)
+         WHERE NOT EXISTS (
+             SELECT 1 FROM tag_history h
+             WHERE h.plc_ip = s.plc_ip AND h.tag_name = s.tag_name AND h.ts_ms = s.ts_ms
+         )"
+    )
+    .bind(plc_ips)
+    .bind(tag_names)
+    .bind(values)
+    .bind(value_nums)
+    .bind(timestamps)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Lacunas de pelo menos `min_gap_ms` entre amostras consecutivas de cada PLC na janela
+pub async fn detect_gaps(
+    pool: &Pool<Postgres>,
+    plc_ip: Option<&str>,
+    from_ms: i64,
+    to_ms: i64,
+    min_gap_ms: i64,
+) -> Result<Vec<HistoryGap>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT plc_ip, prev_ts, ts_ms
+         FROM (
+             SELECT plc_ip, ts_ms, LAG(ts_ms) OVER (PARTITION BY plc_ip ORDER BY ts_ms) AS prev_ts
+             FROM (
+                 SELECT DISTINCT plc_ip, ts_ms FROM tag_history
+                 WHERE ts_ms >= $1 AND ts_ms <= $2 AND ($3::TEXT IS NULL OR plc_ip = $3)
+             ) samples
+         ) steps
+         WHERE prev_ts IS NOT NULL AND ts_ms - prev_ts >= $4
+         ORDER BY plc_ip, prev_ts"
+    )
+    .bind(from_ms)
+    .bind(to_ms)
+    .bind(plc_ip)
+    .bind(min_gap_ms)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| {
+        let from_ms: i64 = row.get("prev_ts");
+        let to_ms: i64 = row.get("ts_ms");
+        HistoryGap {
+            plc_ip: row.get("plc_ip"),
+            from_ms,
+            to_ms,
+            duration_ms: to_ms - from_ms,
+        }
+    }).collect())
+}
+
+// ============================================================================
+// 🆕 FORMAS DE ONDA (BLOBS)
+// ============================================================================
+//
+// Tags array (blocos `waveform`) não cabem em tag_history: cada amostra vai para
+// tag_waveforms com os pontos em um BYTEA (f64 big-endian, NaN = ponto sem valor)
+// e os metadados ao lado (tipo do elemento, unidade, quantidade de pontos).
+
+pub const WAVEFORM_ENCODING: &str = "f64be";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveformSample {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub data_type: String,     // Tipo de cada ponto no PLC (ex: "REAL")
+    pub unit: Option<String>,
+    pub ts_ms: i64,
+    pub point_count: i32,
+    pub points: Vec<f64>,      // Vazio quando consultado sem os pontos
+}
+
+pub fn encode_waveform(points: &[f64]) -> Vec<u8> {
+    points.iter().flat_map(|p| p.to_be_bytes()).collect()
+}
+
+pub fn decode_waveform(data: &[u8]) -> Vec<f64> {
+    data.chunks_exact(8)
+        .map(|chunk| f64::from_be_bytes(chunk.try_into().unwrap_or([0; 8])))
+        .collect()
+}
+
+pub async fn ensure_waveform_table(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS tag_waveforms (
+            plc_ip TEXT NOT NULL,
+            tag_name TEXT NOT NULL,
+            ts_ms BIGINT NOT NULL,
+            data_type TEXT NOT NULL,
+            unit TEXT,
+            point_count INTEGER NOT NULL,
+            encoding TEXT NOT NULL,
+            data BYTEA NOT NULL
+        )"
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tag_waveforms_plc_tag_ts ON tag_waveforms (plc_ip, tag_name, ts_ms)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Insere formas de onda (mesmo PLC, tag e ts_ms já gravados são ignorados)
+pub async fn insert_waveforms(pool: &Pool<Postgres>, waveforms: &[WaveformSample]) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+    for waveform in waveforms {
+        let result = sqlx::query(
+            "INSERT INTO tag_waveforms (plc_ip, tag_name, ts_ms, data_type, unit, point_count, encoding, data)
+             SELECT $1, $2, $3, $4, $5, $6, $7, $8
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM tag_waveforms WHERE plc_ip = $1 AND tag_name = $2 AND ts_ms = $3
+             )"
+        )
+        .bind(&waveform.plc_ip)
+        .bind(&waveform.tag_name)
+        .bind(waveform.ts_ms)
+        .bind(&waveform.data_type)
+        .bind(&waveform.unit)
+        .bind(waveform.points.len() as i32)
+        .bind(WAVEFORM_ENCODING)
+        .bind(encode_waveform(&waveform.points))
+        .execute(&mut *tx)
+        .await?;
+        inserted += result.rows_affected();
+    }
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Formas de onda de um tag na janela (mais antigas primeiro); sem os pontos se `with_points` = false
+pub async fn fetch_waveforms(
+    pool: &Pool<Postgres>,
+    plc_ip: &str,
+    tag_name: &str,
+    from_ms: i64,
+    to_ms: i64,
+    limit: i64,
+    with_points: bool,
+) -> Result<Vec<WaveformSample>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT plc_ip, tag_name, ts_ms, data_type, unit, point_count,
+                CASE WHEN $6 THEN data ELSE NULL END AS data
+         FROM tag_waveforms
+         WHERE plc_ip = $1 AND tag_name = $2 AND ts_ms >= $3 AND ts_ms <= $4
+         ORDER BY ts_ms
+         LIMIT $5"
+    )
+    .bind(plc_ip)
+    .bind(tag_name)
+    .bind(from_ms)
+    .bind(to_ms)
+    .bind(limit)
+    .bind(with_points)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| {
+        let data: Option<Vec<u8>> = row.get("data");
+        WaveformSample {
+            plc_ip: row.get("plc_ip"),
+            tag_name: row.get("tag_name"),
+            data_type: row.get("data_type"),
+            unit: row.get("unit"),
+            ts_ms: row.get("ts_ms"),
+            point_count: row.get("point_count"),
+            points: data.map(|d| decode_waveform(&d)).unwrap_or_default(),
+        }
+    }).collect())
+}
+
+/// Compara dois snapshots e retorna somente os tags que mudaram (ou surgiram/sumiram)
+pub fn diff_snapshots(t1: i64, t2: i64, before: Vec<SnapshotValue>, after: Vec<SnapshotValue>) -> SnapshotComparison {
+    let mut merged: BTreeMap<(String, String), (Option<SnapshotValue>, Option<SnapshotValue>)> = BTreeMap::new();
+
+    for v in before {
+        let key = (v.plc_ip.clone(), v.tag_name.clone());
+        merged.entry(key).or_default().0 = Some(v);
+    }
+    for v in after {
+        let key = (v.plc_ip.clone(), v.tag_name.clone());
+        merged.entry(key).or_default().1 = Some(v);
+    }
+
+    let total_tags = merged.len();
+    let mut changes = Vec::new();
+
+    for ((plc_ip, tag_name), (a, b)) in merged {
+        let status = match (&a, &b) {
+            (Some(a), Some(b)) if a.value == b.value => "unchanged",
+            (Some(_), Some(_)) => "changed",
+            (None, Some(_)) => "added",
+            (Some(_), None) => "removed",
+            (None, None) => continue,
+        };
+
+        if status == "unchanged" {
+            continue;
+        }
+
+        let delta = match (a.as_ref().and_then(|v| v.value_num), b.as_ref().and_then(|v| v.value_num)) {
+            (Some(x), Some(y)) => Some(y - x),
+            _ => None,
+        };
+
+        changes.push(TagSnapshotDiff {
+            plc_ip,
+            tag_name,
+            value_t1: a.map(|v| v.value),
+            value_t2: b.map(|v| v.value),
+            delta,
+            status: status.to_string(),
+        });
+    }
+
+    SnapshotComparison {
+        t1,
+        t2,
+        total_tags,
+        changed_count: changes.len(),
+        changes,
+    }
+}
@@ -0,0 +1,379 @@
+use crate::database::{fnv1a_64, Database};
+use crate::historian::{self, SnapshotValue};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::TryStreamExt;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres, Row};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// ARQUIVO MORTO DO HISTORIAN (MESES ANTIGOS FORA DO POSTGRESQL)
+// ============================================================================
+//
+// Cada mês mais antigo que N meses sai do tag_history para um SQLite próprio,
+// compactado com VACUUM INTO e gzip:
+//   <pasta do banco>/historian_archive/tag_history_AAAA-MM.db.gz (+ .json com contagem e checksum)
+// As linhas só são removidas do PostgreSQL (DROP da partição mensal + DELETE
+// do que estiver na partição padrão) depois que o arquivo confere a contagem.
+// Para consultar, o arquivo é "anexado": descompactado em historian_archive/attached
+// e aberto somente leitura. Formas de onda (tag_waveforms) não são arquivadas.
+
+const ARCHIVE_DIR: &str = "historian_archive";
+const ATTACHED_DIR: &str = "attached";
+const ARCHIVE_PREFIX: &str = "tag_history_";
+const INSERT_CHUNK: usize = 10_000;
+pub const MIN_ARCHIVE_AGE_MONTHS: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveInfo {
+    pub month: String,        // "AAAA-MM"
+    pub file_name: String,
+    pub rows: u64,
+    pub from_ms: i64,         // Faixa [from_ms, to_ms) do mês (UTC)
+    pub to_ms: i64,
+    pub size_bytes: u64,
+    pub checksum: String,     // FNV-1a do .db.gz
+    pub archived_at: i64,
+    #[serde(default)]
+    pub attached: bool,       // Descompactado e disponível para consulta
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ArchiveReport {
+    pub archived: Vec<ArchiveInfo>,
+    pub skipped: Vec<String>, // Meses já arquivados ou sem linhas
+    pub rows_removed: u64,
+}
+
+fn archive_dir(db: &Database) -> PathBuf {
+    db.db_path()
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default()
+        .join(ARCHIVE_DIR)
+}
+
+fn archive_path(db: &Database, month: &str) -> PathBuf {
+    archive_dir(db).join(format!("{}{}.db.gz", ARCHIVE_PREFIX, month))
+}
+
+fn info_path(archive: &Path) -> PathBuf {
+    archive.with_extension("json")
+}
+
+fn attached_path(db: &Database, month: &str) -> PathBuf {
+    archive_dir(db).join(ATTACHED_DIR).join(format!("{}{}.db", ARCHIVE_PREFIX, month))
+}
+
+fn month_label(ts_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ts_ms)
+        .map(|d| d.format("%Y-%m").to_string())
+        .unwrap_or_default()
+}
+
+fn validate_month(month: &str) -> Result<(), String> {
+    chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| format!("Mês inválido: {} (use AAAA-MM)", month))
+}
+
+/// Início (ms UTC) do mês que está `months` meses antes do mês atual
+fn cutoff_ms(months: u32) -> i64 {
+    use chrono::{Datelike, TimeZone, Utc};
+    let now = Utc::now();
+    let total = now.year() * 12 + now.month0() as i32 - months as i32;
+    Utc.with_ymd_and_hms(total.div_euclid(12), total.rem_euclid(12) as u32 + 1, 1, 0, 0, 0)
+        .unwrap()
+        .timestamp_millis()
+}
+
+fn load_info(archive: &Path) -> Option<ArchiveInfo> {
+    let text = fs::read_to_string(info_path(archive)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Arquivos do historian (mais antigos primeiro)
+pub fn list_archives(db: &Database) -> Vec<ArchiveInfo> {
+    let Ok(entries) = fs::read_dir(archive_dir(db)) else {
+        return Vec::new();
+    };
+    let mut archives: Vec<ArchiveInfo> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.starts_with(ARCHIVE_PREFIX) && name.ends_with(".db.gz")
+        })
+        .filter_map(|p| load_info(&p))
+        .map(|mut info| {
+            info.attached = attached_path(db, &info.month).exists();
+            info
+        })
+        .collect();
+    archives.sort_by(|a, b| a.month.cmp(&b.month));
+    archives
+}
+
+fn create_archive_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE tag_history (
+            plc_ip TEXT NOT NULL,
+            tag_name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            value_num REAL,
+            ts_ms INTEGER NOT NULL
+        );
+        CREATE TABLE archive_info (month TEXT NOT NULL, rows INTEGER NOT NULL, from_ms INTEGER NOT NULL, to_ms INTEGER NOT NULL);"
+    )
+}
+
+fn write_chunk(conn: &mut Connection, rows: &[SnapshotValue]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare_cached("INSERT INTO tag_history (plc_ip, tag_name, value, value_num, ts_ms) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for row in rows {
+            stmt.execute(params![row.plc_ip, row.tag_name, row.value, row.value_num, row.ts_ms])?;
+        }
+    }
+    tx.commit()
+}
+
+/// VACUUM INTO (arquivo compacto) + gzip; retorna o checksum do .db.gz
+fn compress_archive(staging: &Path, target: &Path) -> Result<String, String> {
+    let compact = staging.with_extension("compact");
+    let _ = fs::remove_file(&compact);
+    let conn = Connection::open(staging).map_err(|e| format!("Erro ao abrir {:?}: {}", staging, e))?;
+    conn.execute("VACUUM INTO ?1", params![compact.to_string_lossy()])
+        .map_err(|e| format!("Erro no VACUUM INTO: {}", e))?;
+    drop(conn);
+
+    let gz_tmp = target.with_extension("gz.tmp");
+    let mut input = fs::File::open(&compact).map_err(|e| format!("Erro ao ler {:?}: {}", compact, e))?;
+    let output = fs::File::create(&gz_tmp).map_err(|e| format!("Erro ao criar {:?}: {}", gz_tmp, e))?;
+    let mut encoder = GzEncoder::new(output, Compression::best());
+    std::io::copy(&mut input, &mut encoder).map_err(|e| format!("Erro ao compactar arquivo: {}", e))?;
+    encoder.finish().map_err(|e| format!("Erro ao compactar arquivo: {}", e))?;
+    fs::rename(&gz_tmp, target).map_err(|e| format!("Erro ao gravar {:?}: {}", target, e))?;
+
+    let _ = fs::remove_file(&compact);
+    let _ = fs::remove_file(staging);
+    let bytes = fs::read(target).map_err(|e| format!("Erro ao ler {:?}: {}", target, e))?;
+    Ok(format!("{:016x}", fnv1a_64(&bytes)))
+}
+
+/// Copia um mês do PostgreSQL para o arquivo e remove as linhas do banco vivo
+async fn archive_month(db: &Database, pool: &Pool<Postgres>, from_ms: i64, to_ms: i64) -> Result<Option<ArchiveInfo>, String> {
+    let month = month_label(from_ms);
+    let expected: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tag_history WHERE ts_ms >= $1 AND ts_ms < $2")
+        .bind(from_ms)
+        .bind(to_ms)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Erro ao contar linhas de {}: {}", month, e))?;
+    if expected == 0 {
+        return Ok(None);
+    }
+
+    let target = archive_path(db, &month);
+    if target.exists() {
+        return Err(format!("Arquivo de {} já existe com linhas ainda no PostgreSQL - verifique antes de arquivar de novo", month));
+    }
+    let staging = target.with_extension("staging");
+    let _ = fs::remove_file(&staging);
+    let mut conn = Connection::open(&staging).map_err(|e| format!("Erro ao criar {:?}: {}", staging, e))?;
+    create_archive_schema(&conn).map_err(|e| format!("Erro ao preparar arquivo de {}: {}", month, e))?;
+
+    let mut written = 0u64;
+    let mut chunk = Vec::with_capacity(INSERT_CHUNK);
+    let mut rows = sqlx::query(
+        "SELECT plc_ip, tag_name, value, value_num, ts_ms FROM tag_history WHERE ts_ms >= $1 AND ts_ms < $2 ORDER BY ts_ms"
+    )
+    .bind(from_ms)
+    .bind(to_ms)
+    .fetch(pool);
+    while let Some(row) = rows.try_next().await.map_err(|e| format!("Erro ao ler histórico de {}: {}", month, e))? {
+        chunk.push(SnapshotValue {
+            plc_ip: row.get("plc_ip"),
+            tag_name: row.get("tag_name"),
+            value: row.get("value"),
+            value_num: row.get("value_num"),
+            ts_ms: row.get("ts_ms"),
+            unit: None,
+        });
+        if chunk.len() >= INSERT_CHUNK {
+            write_chunk(&mut conn, &chunk).map_err(|e| format!("Erro ao gravar arquivo de {}: {}", month, e))?;
+            written += chunk.len() as u64;
+            chunk.clear();
+        }
+    }
+    drop(rows);
+    write_chunk(&mut conn, &chunk).map_err(|e| format!("Erro ao gravar arquivo de {}: {}", month, e))?;
+    written += chunk.len() as u64;
+
+    if written != expected as u64 {
+        drop(conn);
+        let _ = fs::remove_file(&staging);
+        return Err(format!("Contagem de {} não confere ({} no PostgreSQL, {} no arquivo) - nada removido", month, expected, written));
+    }
+    conn.execute_batch("CREATE INDEX idx_archive_plc_tag_ts ON tag_history (plc_ip, tag_name, ts_ms)")
+        .and_then(|_| conn.execute("INSERT INTO archive_info (month, rows, from_ms, to_ms) VALUES (?1, ?2, ?3, ?4)",
+            params![month, written as i64, from_ms, to_ms]))
+        .map_err(|e| format!("Erro ao finalizar arquivo de {}: {}", month, e))?;
+    drop(conn);
+
+    let (staging_path, target_path) = (staging.clone(), target.clone());
+    let checksum = tokio::task::spawn_blocking(move || compress_archive(&staging_path, &target_path)).await
+        .unwrap_or_else(|e| Err(format!("Task de compactação falhou: {}", e)))?;
+    let info = ArchiveInfo {
+        file_name: target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        size_bytes: fs::metadata(&target).map(|m| m.len()).unwrap_or(0),
+        month: month.clone(),
+        rows: written,
+        from_ms,
+        to_ms,
+        checksum,
+        archived_at: chrono::Utc::now().timestamp(),
+        attached: false,
+    };
+    fs::write(info_path(&target), serde_json::to_string_pretty(&info).unwrap_or_default())
+        .map_err(|e| format!("Erro ao gravar informações do arquivo de {}: {}", month, e))?;
+
+    // Só agora sai do banco vivo: partição do mês inteira + o que caiu na partição padrão
+    let mut tx = pool.begin().await.map_err(|e| format!("Erro ao iniciar transação no historian: {}", e))?;
+    // Backfill/failover podem ter gravado no mês durante a cópia: travar escritas
+    // (leituras continuam) e recontar antes de apagar qualquer linha
+    sqlx::query("LOCK TABLE tag_history IN EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Erro ao travar tag_history para arquivar {}: {}", month, e))?;
+    let current: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tag_history WHERE ts_ms >= $1 AND ts_ms < $2")
+        .bind(from_ms)
+        .bind(to_ms)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Erro ao recontar linhas de {}: {}", month, e))?;
+    if current != written as i64 {
+        drop(tx); // Rollback: nada removido
+        let _ = fs::remove_file(info_path(&target));
+        let _ = fs::remove_file(&target);
+        return Err(format!(
+            "{} recebeu linhas durante o arquivamento ({} no PostgreSQL, {} no arquivo) - nada removido, arquive de novo",
+            month, current, written
+        ));
+    }
+    let (partition, _, _) = historian::month_partition(from_ms);
+    sqlx::query(&format!("DROP TABLE IF EXISTS {}", partition))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Erro ao remover partição {}: {}", partition, e))?;
+    sqlx::query("DELETE FROM tag_history WHERE ts_ms >= $1 AND ts_ms < $2")
+        .bind(from_ms)
+        .bind(to_ms)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Erro ao remover linhas de {}: {}", month, e))?;
+    tx.commit().await.map_err(|e| format!("Erro ao confirmar remoção de {}: {}", month, e))?;
+
+    println!("📦 Historian: {} arquivado ({} linhas, {} bytes)", month, info.rows, info.size_bytes);
+    Ok(Some(info))
+}
+
+/// Arquiva todos os meses anteriores a `older_than_months` meses atrás
+pub async fn archive_older_than(db: &Database, pool: &Pool<Postgres>, older_than_months: u32) -> Result<ArchiveReport, String> {
+    if older_than_months < MIN_ARCHIVE_AGE_MONTHS {
+        return Err(format!("Arquive apenas meses com pelo menos {} mês(es) de idade", MIN_ARCHIVE_AGE_MONTHS));
+    }
+    fs::create_dir_all(archive_dir(db)).map_err(|e| format!("Erro ao criar pasta do arquivo: {}", e))?;
+
+    let cutoff = cutoff_ms(older_than_months);
+    let oldest: Option<i64> = sqlx::query_scalar("SELECT MIN(ts_ms) FROM tag_history WHERE ts_ms < $1")
+        .bind(cutoff)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Erro ao consultar historian: {}", e))?;
+
+    let mut report = ArchiveReport::default();
+    let mut month_start = oldest.unwrap_or(cutoff);
+    while month_start < cutoff {
+        let (_, from_ms, to_ms) = historian::month_partition(month_start);
+        match archive_month(db, pool, from_ms, to_ms).await? {
+            Some(info) => {
+                report.rows_removed += info.rows;
+                report.archived.push(info);
+            }
+            None => report.skipped.push(month_label(from_ms)),
+        }
+        month_start = to_ms;
+    }
+    Ok(report)
+}
+
+/// Descompacta o arquivo do mês para consulta somente leitura
+pub fn attach_archive(db: &Database, month: &str) -> Result<ArchiveInfo, String> {
+    validate_month(month)?;
+    let archive = archive_path(db, month);
+    let mut info = load_info(&archive).ok_or_else(|| format!("Arquivo do historian de {} não encontrado", month))?;
+    let bytes = fs::read(&archive).map_err(|e| format!("Erro ao ler {:?}: {}", archive, e))?;
+    if format!("{:016x}", fnv1a_64(&bytes)) != info.checksum {
+        return Err(format!("Checksum do arquivo de {} não confere - arquivo corrompido ou alterado", month));
+    }
+
+    let target = attached_path(db, month);
+    if !target.exists() {
+        let dir = target.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar pasta {:?}: {}", dir, e))?;
+        let tmp = target.with_extension("tmp");
+        let mut output = fs::File::create(&tmp).map_err(|e| format!("Erro ao criar {:?}: {}", tmp, e))?;
+        std::io::copy(&mut GzDecoder::new(bytes.as_slice()), &mut output)
+            .map_err(|e| format!("Erro ao descompactar arquivo de {}: {}", month, e))?;
+        fs::rename(&tmp, &target).map_err(|e| format!("Erro ao gravar {:?}: {}", target, e))?;
+    }
+    println!("📂 Historian: arquivo de {} anexado (somente leitura)", month);
+    info.attached = true;
+    Ok(info)
+}
+
+pub fn detach_archive(db: &Database, month: &str) -> Result<(), String> {
+    validate_month(month)?;
+    let target = attached_path(db, month);
+    if !target.exists() {
+        return Err(format!("Arquivo de {} não está anexado", month));
+    }
+    fs::remove_file(&target).map_err(|e| format!("Erro ao remover {:?}: {}", target, e))?;
+    println!("📂 Historian: arquivo de {} desanexado", month);
+    Ok(())
+}
+
+/// Amostras de um tag nos arquivos anexados que cobrem a janela (mais antigas primeiro)
+pub fn query_attached(db: &Database, plc_ip: &str, tag_name: &str, from_ms: i64, to_ms: i64, limit: usize) -> Result<Vec<SnapshotValue>, String> {
+    let mut samples = Vec::new();
+    for info in list_archives(db).into_iter().filter(|a| a.attached && a.from_ms <= to_ms && a.to_ms > from_ms) {
+        let path = attached_path(db, &info.month);
+        let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .map_err(|e| format!("Erro ao abrir arquivo de {}: {}", info.month, e))?;
+        let mut stmt = conn.prepare(
+            "SELECT plc_ip, tag_name, value, value_num, ts_ms FROM tag_history
+             WHERE plc_ip = ?1 AND tag_name = ?2 AND ts_ms >= ?3 AND ts_ms <= ?4
+             ORDER BY ts_ms LIMIT ?5"
+        ).map_err(|e| format!("Erro ao consultar arquivo de {}: {}", info.month, e))?;
+        let rows = stmt.query_map(params![plc_ip, tag_name, from_ms, to_ms, (limit - samples.len()) as i64], |row| Ok(SnapshotValue {
+            plc_ip: row.get(0)?,
+            tag_name: row.get(1)?,
+            value: row.get(2)?,
+            value_num: row.get(3)?,
+            ts_ms: row.get(4)?,
+            unit: None,
+        })).map_err(|e| format!("Erro ao consultar arquivo de {}: {}", info.month, e))?;
+        for row in rows {
+            samples.push(row.map_err(|e| format!("Erro ao ler arquivo de {}: {}", info.month, e))?);
+        }
+        if samples.len() >= limit {
+            break;
+        }
+    }
+    Ok(samples)
+}
@@ -0,0 +1,532 @@
+use crate::database::{Database, HistorianCatchupWindow, HistorianTarget, PostgresConfig};
+use crate::historian::{self, SnapshotValue, WaveformSample};
+use serde::Serialize;
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex as TokioMutex;
+
+// ============================================================================
+// FAILOVER DO HISTORIAN (DESTINOS EM ORDEM + REPLICAÇÃO DE RECUPERAÇÃO)
+// ============================================================================
+//
+// As amostras vão para o primeiro destino habilitado que aceitar a escrita
+// (ex: PostgreSQL primário → PostgreSQL secundário → SQLite local). O que foi
+// gravado fora do primário é replicado de volta quando ele volta:
+//   - PostgreSQL secundário: faixa de ts_ms gravada lá (historian_catchup_windows)
+//     é relida e inserida no primário;
+//   - SQLite local: as linhas do arquivo são a fila; apagadas após replicar.
+// insert_samples ignora duplicatas, então replicar duas vezes é seguro. Faixas
+// são lidas em páginas de CATCHUP_BATCH; uma faixa com erro (ex: secundário
+// fora) fica para a próxima rodada sem travar as demais nem a fila local.
+// Sem destinos configurados, usa a configuração PostgreSQL única (postgres_config).
+
+pub const TARGET_POSTGRES: &str = "postgres";
+pub const TARGET_SQLITE: &str = "sqlite";
+const DEFAULT_SQLITE_PATH: &str = "D:\\Banco_SQLITE\\plc_hmi_historian_fallback.db";
+const CONNECT_TIMEOUT_S: u64 = 5;               // Failover não pode esperar os 30 s do pool padrão
+const RETRY_DOWN_TARGET_S: u64 = 30;            // Destino fora: pular por este tempo antes de tentar de novo
+const CATCHUP_INTERVAL_S: u64 = 30;
+const CATCHUP_BATCH: usize = 5_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetStatus {
+    pub name: String,
+    pub kind: String,
+    pub healthy: bool,
+    pub last_error: Option<String>,
+    pub last_write_ms: Option<i64>,
+    pub rows_written: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailoverStatus {
+    pub active_target: Option<String>,
+    pub targets: Vec<TargetStatus>,
+    pub pending_windows: Vec<HistorianCatchupWindow>,
+    pub pending_local_rows: u64,
+    pub last_catchup_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatchupReport {
+    pub primary: String,
+    pub replicated_rows: u64,
+    pub windows_done: usize,
+    pub windows_skipped: usize, // Faixas com erro, tentadas de novo na próxima rodada
+    pub local_rows_done: u64,
+}
+
+fn validate_target(target: &HistorianTarget) -> Result<(), String> {
+    if target.name.trim().is_empty() {
+        return Err("Destino do historian sem nome".to_string());
+    }
+    match target.kind.as_str() {
+        TARGET_POSTGRES if target.host.trim().is_empty() || target.database.trim().is_empty() => {
+            Err(format!("Destino '{}': informe host e banco do PostgreSQL", target.name))
+        }
+        TARGET_POSTGRES | TARGET_SQLITE => Ok(()),
+        other => Err(format!("Destino '{}': tipo inválido {} (use postgres ou sqlite)", target.name, other)),
+    }
+}
+
+/// Valida a lista antes de salvar (nomes únicos, tipos conhecidos)
+pub fn validate_targets(targets: &[HistorianTarget]) -> Result<(), String> {
+    for (index, target) in targets.iter().enumerate() {
+        validate_target(target)?;
+        if targets[..index].iter().any(|t| t.name == target.name) {
+            return Err(format!("Destino '{}' repetido", target.name));
+        }
+    }
+    Ok(())
+}
+
+fn sqlite_path(target: &HistorianTarget) -> String {
+    if target.database.trim().is_empty() { DEFAULT_SQLITE_PATH.to_string() } else { target.database.clone() }
+}
+
+fn open_sqlite(path: &str) -> rusqlite::Result<rusqlite::Connection> {
+    let conn = rusqlite::Connection::open(path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tag_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plc_ip TEXT NOT NULL,
+            tag_name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            value_num REAL,
+            ts_ms INTEGER NOT NULL
+        )"
+    )?;
+    Ok(conn)
+}
+
+fn write_sqlite(path: String, samples: Vec<SnapshotValue>) -> rusqlite::Result<u64> {
+    let mut conn = open_sqlite(&path)?;
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("INSERT INTO tag_history (plc_ip, tag_name, value, value_num, ts_ms) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for s in &samples {
+            stmt.execute((&s.plc_ip, &s.tag_name, &s.value, s.value_num, s.ts_ms))?;
+        }
+    }
+    tx.commit()?;
+    Ok(samples.len() as u64)
+}
+
+/// Próximo lote da fila local: (ids, amostras)
+fn read_sqlite_batch(path: &str) -> rusqlite::Result<(Vec<i64>, Vec<SnapshotValue>)> {
+    let conn = open_sqlite(path)?;
+    let mut stmt = conn.prepare("SELECT id, plc_ip, tag_name, value, value_num, ts_ms FROM tag_history ORDER BY id LIMIT ?1")?;
+    let rows = stmt.query_map([CATCHUP_BATCH as i64], |row| {
+        Ok((row.get::<usize, i64>(0)?, SnapshotValue {
+            plc_ip: row.get(1)?,
+            tag_name: row.get(2)?,
+            value: row.get(3)?,
+            value_num: row.get(4)?,
+            ts_ms: row.get(5)?,
+            unit: None,
+        }))
+    })?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows.into_iter().unzip())
+}
+
+fn delete_sqlite_rows(path: &str, last_id: i64) -> rusqlite::Result<usize> {
+    let conn = open_sqlite(path)?;
+    conn.execute("DELETE FROM tag_history WHERE id <= ?1", [last_id])
+}
+
+fn count_sqlite_rows(path: &str) -> u64 {
+    if !std::path::Path::new(path).exists() {
+        return 0;
+    }
+    open_sqlite(path)
+        .and_then(|conn| conn.query_row("SELECT COUNT(*) FROM tag_history", [], |row| row.get::<usize, i64>(0)))
+        .map(|count| count as u64)
+        .unwrap_or(0)
+}
+
+pub struct HistorianFailover {
+    app_handle: AppHandle,
+    database: Arc<Database>,
+    pools: TokioMutex<HashMap<String, Pool<Postgres>>>,
+    partitions: Mutex<HashSet<(String, String)>>, // (destino, partição mensal) já verificadas
+    status: Mutex<HashMap<String, TargetStatus>>,
+    down_since: Mutex<HashMap<String, Instant>>,
+    active: Mutex<Option<String>>,
+    last_catchup_ms: Mutex<Option<i64>>,
+    catchup_running: TokioMutex<()>,
+}
+
+impl HistorianFailover {
+    pub fn new(app_handle: AppHandle, database: Arc<Database>) -> Self {
+        Self {
+            app_handle,
+            database,
+            pools: TokioMutex::new(HashMap::new()),
+            partitions: Mutex::new(HashSet::new()),
+            status: Mutex::new(HashMap::new()),
+            down_since: Mutex::new(HashMap::new()),
+            active: Mutex::new(None),
+            last_catchup_ms: Mutex::new(None),
+            catchup_running: TokioMutex::new(()),
+        }
+    }
+
+    /// Destinos habilitados em ordem; sem lista configurada, o PostgreSQL único
+    pub fn targets(&self) -> Result<Vec<HistorianTarget>, String> {
+        let targets = self.database.load_historian_targets()
+            .map_err(|e| format!("Erro ao carregar destinos do historian: {}", e))?;
+        if !targets.is_empty() {
+            return Ok(targets.into_iter().filter(|t| t.enabled).collect());
+        }
+        let legacy = self.database.load_postgres_config()
+            .map_err(|e| format!("Erro ao carregar configuração PostgreSQL: {}", e))?;
+        Ok(legacy.into_iter().map(|c| HistorianTarget {
+            name: "primario".to_string(),
+            kind: TARGET_POSTGRES.to_string(),
+            host: c.host,
+            port: c.port,
+            user: c.user,
+            password: c.password,
+            database: c.database,
+            enabled: true,
+        }).collect())
+    }
+
+    /// Descarta conexões em cache (ex: lista de destinos alterada)
+    pub async fn reset(&self) {
+        self.pools.lock().await.clear();
+        self.partitions.lock().unwrap().clear();
+        self.down_since.lock().unwrap().clear();
+        self.status.lock().unwrap().clear();
+    }
+
+    async fn pool_for(&self, target: &HistorianTarget) -> Result<Pool<Postgres>, String> {
+        if let Some(pool) = self.pools.lock().await.get(&target.name) {
+            return Ok(pool.clone());
+        }
+        let url = historian::postgres_url(&PostgresConfig {
+            host: target.host.clone(),
+            port: target.port,
+            user: target.user.clone(),
+            password: target.password.clone(),
+            database: target.database.clone(),
+            updated_at: 0,
+        });
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(CONNECT_TIMEOUT_S))
+            .connect(&url)
+            .await
+            .map_err(|e| format!("Erro ao conectar em '{}': {}", target.name, e))?;
+        historian::ensure_history_table(&pool).await
+            .map_err(|e| format!("Erro ao preparar tabela em '{}': {}", target.name, e))?;
+        historian::ensure_waveform_table(&pool).await
+            .map_err(|e| format!("Erro ao preparar tabela de formas de onda em '{}': {}", target.name, e))?;
+        self.pools.lock().await.insert(target.name.clone(), pool.clone());
+        Ok(pool)
+    }
+
+    /// 🆕 Pool do primeiro destino PostgreSQL (o mesmo da gravação) para consultas
+    pub async fn primary_pool(&self) -> Result<Pool<Postgres>, String> {
+        let targets = self.targets()?;
+        let primary = targets.iter()
+            .find(|t| t.kind == TARGET_POSTGRES)
+            .ok_or_else(|| "Nenhum destino PostgreSQL do historian configurado".to_string())?;
+        self.pool_for(primary).await
+    }
+
+    /// Partições mensais dos meses presentes no lote (uma verificação por mês e destino)
+    async fn ensure_partitions(&self, target: &HistorianTarget, pool: &Pool<Postgres>, samples: &[SnapshotValue]) -> Result<(), String> {
+        let mut months: HashMap<String, i64> = HashMap::new();
+        for sample in samples {
+            months.entry(historian::month_partition(sample.ts_ms).0).or_insert(sample.ts_ms);
+        }
+        for (name, ts_ms) in months {
+            let key = (target.name.clone(), name);
+            if self.partitions.lock().unwrap().contains(&key) {
+                continue;
+            }
+            // Tabela criada antes da partição para o mês não cair na partição padrão
+            historian::ensure_history_table(pool).await
+                .map_err(|e| format!("Erro ao criar tabela do historian em '{}': {}", target.name, e))?;
+            historian::ensure_month_partition(pool, ts_ms).await
+                .map_err(|e| format!("Erro ao criar partição em '{}': {}", target.name, e))?;
+            self.partitions.lock().unwrap().insert(key);
+        }
+        Ok(())
+    }
+
+    async fn write_to(&self, target: &HistorianTarget, samples: &[SnapshotValue]) -> Result<u64, String> {
+        match target.kind.as_str() {
+            TARGET_POSTGRES => {
+                let pool = self.pool_for(target).await?;
+                self.ensure_partitions(target, &pool, samples).await?;
+                match historian::insert_samples(&pool, samples).await {
+                    Ok(rows) => Ok(rows),
+                    Err(e) => {
+                        self.pools.lock().await.remove(&target.name); // Reconectar na próxima tentativa
+                        Err(format!("Erro ao gravar em '{}': {}", target.name, e))
+                    }
+                }
+            }
+            TARGET_SQLITE => {
+                let (path, samples) = (sqlite_path(target), samples.to_vec());
+                tokio::task::spawn_blocking(move || write_sqlite(path, samples))
+                    .await
+                    .map_err(|e| format!("Erro na gravação local: {}", e))?
+                    .map_err(|e| format!("Erro ao gravar em '{}': {}", target.name, e))
+            }
+            other => Err(format!("Tipo de destino inválido: {}", other)),
+        }
+    }
+
+    fn mark(&self, target: &HistorianTarget, result: &Result<u64, String>) {
+        let mut status = self.status.lock().unwrap();
+        let entry = status.entry(target.name.clone()).or_insert_with(|| TargetStatus {
+            name: target.name.clone(),
+            kind: target.kind.clone(),
+            healthy: true,
+            last_error: None,
+            last_write_ms: None,
+            rows_written: 0,
+        });
+        let mut down_since = self.down_since.lock().unwrap();
+        match result {
+            Ok(rows) => {
+                entry.healthy = true;
+                entry.last_error = None;
+                entry.last_write_ms = Some(chrono::Utc::now().timestamp_millis());
+                entry.rows_written += rows;
+                down_since.remove(&target.name);
+            }
+            Err(e) => {
+                entry.healthy = false;
+                entry.last_error = Some(e.clone());
+                down_since.entry(target.name.clone()).or_insert_with(Instant::now);
+            }
+        }
+    }
+
+    fn skip_down_target(&self, target: &HistorianTarget) -> bool {
+        let mut down_since = self.down_since.lock().unwrap();
+        match down_since.get(&target.name) {
+            Some(since) if since.elapsed() < Duration::from_secs(RETRY_DOWN_TARGET_S) => true,
+            Some(_) => {
+                down_since.remove(&target.name); // Hora de tentar de novo
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn set_active(&self, name: &str, reason: Option<&str>) {
+        let mut active = self.active.lock().unwrap();
+        if active.as_deref() == Some(name) {
+            return;
+        }
+        let previous = active.replace(name.to_string());
+        println!("🔀 Historian gravando em '{}' (antes: {:?})", name, previous);
+        let _ = self.app_handle.emit("historian-failover", serde_json::json!({
+            "from": previous,
+            "to": name,
+            "reason": reason,
+        }));
+    }
+
+    /// Grava as amostras no primeiro destino disponível. Retorna o destino usado.
+    pub async fn write(&self, samples: &[SnapshotValue]) -> Result<String, String> {
+        let targets = self.targets()?;
+        if targets.is_empty() {
+            return Err("Nenhum destino do historian configurado".to_string());
+        }
+        let mut last_error: Option<String> = None;
+        for (index, target) in targets.iter().enumerate() {
+            // O último destino sempre é tentado (não há para onde cair)
+            if index + 1 < targets.len() && self.skip_down_target(target) {
+                continue;
+            }
+            let result = self.write_to(target, samples).await;
+            self.mark(target, &result);
+            match result {
+                Ok(_) => {
+                    // Gravado fora do primário: registrar faixa para replicação (SQLite local já é a fila)
+                    if index > 0 && target.kind == TARGET_POSTGRES {
+                        let from_ms = samples.iter().map(|s| s.ts_ms).min();
+                        let to_ms = samples.iter().map(|s| s.ts_ms).max();
+                        if let (Some(from_ms), Some(to_ms)) = (from_ms, to_ms) {
+                            if let Err(e) = self.database.extend_historian_catchup_window(&target.name, from_ms, to_ms) {
+                                println!("⚠️ Falha ao registrar faixa de replicação de '{}': {}", target.name, e);
+                            }
+                        }
+                    }
+                    self.set_active(&target.name, last_error.as_deref());
+                    return Ok(target.name.clone());
+                }
+                Err(e) => {
+                    println!("⚠️ Historian: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| "Nenhum destino do historian disponível".to_string()))
+    }
+
+    /// Grava formas de onda (blobs) no primeiro PostgreSQL disponível. O SQLite
+    /// local não guarda blobs e o que cair no secundário não entra na replicação.
+    pub async fn write_waveforms(&self, waveforms: &[WaveformSample]) -> Result<String, String> {
+        let targets: Vec<HistorianTarget> = self.targets()?.into_iter().filter(|t| t.kind == TARGET_POSTGRES).collect();
+        let mut last_error: Option<String> = None;
+        for (index, target) in targets.iter().enumerate() {
+            if index + 1 < targets.len() && self.skip_down_target(target) {
+                continue;
+            }
+            let result = match self.pool_for(target).await {
+                Ok(pool) => historian::insert_waveforms(&pool, waveforms).await
+                    .map_err(|e| format!("Erro ao gravar formas de onda em '{}': {}", target.name, e)),
+                Err(e) => Err(e),
+            };
+            self.mark(target, &result);
+            match result {
+                Ok(_) => return Ok(target.name.clone()),
+                Err(e) => {
+                    println!("⚠️ Historian: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| "Nenhum destino PostgreSQL para formas de onda".to_string()))
+    }
+
+    /// Copia uma faixa do secundário para o primário em páginas de CATCHUP_BATCH
+    async fn replicate_window(
+        &self,
+        window: &HistorianCatchupWindow,
+        source: &HistorianTarget,
+        primary: &HistorianTarget,
+        primary_pool: &Pool<Postgres>,
+        replicated_rows: &mut u64,
+    ) -> Result<(), String> {
+        let source_pool = self.pool_for(source).await?;
+        let mut cursor: Option<SnapshotValue> = None;
+        loop {
+            let after = cursor.as_ref().map(|s| (s.ts_ms, s.plc_ip.as_str(), s.tag_name.as_str()));
+            let page = historian::fetch_range_page(&source_pool, window.from_ms, window.to_ms, after, CATCHUP_BATCH).await
+                .map_err(|e| format!("Erro ao ler '{}': {}", source.name, e))?;
+            *replicated_rows += historian::insert_samples(primary_pool, &page).await
+                .map_err(|e| format!("Erro ao replicar para '{}': {}", primary.name, e))?;
+            if page.len() < CATCHUP_BATCH {
+                return Ok(());
+            }
+            cursor = page.into_iter().last();
+        }
+    }
+
+    /// Replica para o primário o que foi gravado nos destinos de reserva
+    pub async fn catch_up(&self) -> Result<CatchupReport, String> {
+        let _running = self.catchup_running.lock().await;
+        let targets = self.targets()?;
+        let primary = targets.first()
+            .filter(|t| t.kind == TARGET_POSTGRES)
+            .ok_or_else(|| "O primeiro destino precisa ser PostgreSQL para receber a replicação".to_string())?;
+        let primary_pool = self.pool_for(primary).await?;
+        let mut report = CatchupReport { primary: primary.name.clone(), replicated_rows: 0, windows_done: 0, windows_skipped: 0, local_rows_done: 0 };
+
+        // 1. Faixas gravadas em PostgreSQL secundários
+        let windows = self.database.load_historian_catchup_windows()
+            .map_err(|e| format!("Erro ao ler faixas pendentes: {}", e))?;
+        for window in windows {
+            let Some(source) = targets.iter().find(|t| t.name == window.target && t.kind == TARGET_POSTGRES) else {
+                continue; // Destino removido/desabilitado: faixa fica até voltar
+            };
+            if let Err(e) = self.replicate_window(&window, source, primary, &primary_pool, &mut report.replicated_rows).await {
+                println!("⚠️ Historian: faixa {}..{} de '{}' fica para a próxima rodada: {}", window.from_ms, window.to_ms, source.name, e);
+                report.windows_skipped += 1;
+                continue;
+            }
+            if self.database.clear_historian_catchup_window(&window).unwrap_or(0) > 0 {
+                report.windows_done += 1;
+            }
+        }
+
+        // 2. Fila local (SQLite)
+        for target in targets.iter().filter(|t| t.kind == TARGET_SQLITE) {
+            let path = sqlite_path(target);
+            if !std::path::Path::new(&path).exists() {
+                continue;
+            }
+            loop {
+                let batch_path = path.clone();
+                let (ids, samples) = tokio::task::spawn_blocking(move || read_sqlite_batch(&batch_path))
+                    .await
+                    .map_err(|e| format!("Erro na leitura local: {}", e))?
+                    .map_err(|e| format!("Erro ao ler '{}': {}", target.name, e))?;
+                let Some(last_id) = ids.last().copied() else { break };
+                report.replicated_rows += historian::insert_samples(&primary_pool, &samples).await
+                    .map_err(|e| format!("Erro ao replicar para '{}': {}", primary.name, e))?;
+                let delete_path = path.clone();
+                tokio::task::spawn_blocking(move || delete_sqlite_rows(&delete_path, last_id))
+                    .await
+                    .map_err(|e| format!("Erro na limpeza local: {}", e))?
+                    .map_err(|e| format!("Erro ao limpar '{}': {}", target.name, e))?;
+                report.local_rows_done += ids.len() as u64;
+            }
+        }
+
+        *self.last_catchup_ms.lock().unwrap() = Some(chrono::Utc::now().timestamp_millis());
+        Ok(report)
+    }
+
+    pub fn status(&self) -> FailoverStatus {
+        let targets = self.targets().unwrap_or_default();
+        let known = self.status.lock().unwrap().clone();
+        FailoverStatus {
+            active_target: self.active.lock().unwrap().clone(),
+            targets: targets.iter().map(|t| known.get(&t.name).cloned().unwrap_or_else(|| TargetStatus {
+                name: t.name.clone(),
+                kind: t.kind.clone(),
+                healthy: true,
+                last_error: None,
+                last_write_ms: None,
+                rows_written: 0,
+            })).collect(),
+            pending_windows: self.database.load_historian_catchup_windows().unwrap_or_default(),
+            pending_local_rows: targets.iter()
+                .filter(|t| t.kind == TARGET_SQLITE)
+                .map(|t| count_sqlite_rows(&sqlite_path(t)))
+                .sum(),
+            last_catchup_ms: *self.last_catchup_ms.lock().unwrap(),
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        let targets = self.targets().unwrap_or_default();
+        !self.database.load_historian_catchup_windows().unwrap_or_default().is_empty()
+            || targets.iter()
+                .filter(|t| t.kind == TARGET_SQLITE)
+                .any(|t| count_sqlite_rows(&sqlite_path(t)) > 0)
+    }
+}
+
+/// Task de fundo: replica as pendências assim que o primário responder
+pub fn start_catchup_task(failover: Arc<HistorianFailover>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(CATCHUP_INTERVAL_S));
+        loop {
+            interval.tick().await;
+            if !failover.has_pending() {
+                continue;
+            }
+            match failover.catch_up().await {
+                Ok(report) if report.replicated_rows > 0 || report.windows_done > 0 || report.local_rows_done > 0 => {
+                    println!("🔁 Historian: {} linha(s) replicadas para '{}'", report.replicated_rows, report.primary);
+                    let _ = failover.app_handle.emit("historian-catchup", &report);
+                }
+                Ok(_) => {}
+                Err(e) => println!("⏳ Replicação do historian aguardando: {}", e),
+            }
+        }
+    });
+}
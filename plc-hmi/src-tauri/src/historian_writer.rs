@@ -0,0 +1,286 @@
+use crate::commands::WebSocketServerState;
+use crate::database::{Database, HistorianWriterConfig};
+use crate::historian::{SnapshotValue, WaveformSample};
+use crate::historian_failover::HistorianFailover;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+// ============================================================================
+// HISTORIAN - GRAVAÇÃO CONTÍNUA DOS VALORES DOS TAGS (PostgreSQL)
+// ============================================================================
+//
+// Lê o SmartCache a cada SAMPLE_INTERVAL_MS e acumula amostras dos tags com
+// gravação ligada (tabela historian_tags). O modo de coleta do tag decide
+// quando gerar amostra: "change"/"on_change" = a cada valor novo; "interval" =
+// a cada collect_interval_s. O buffer é gravado a cada `flush_interval_ms` ou
+// ao juntar `batch_size` amostras, sempre pelo failover (primário → reserva →
+// SQLite local). Formas de onda vão para tag_waveforms.
+
+const SAMPLE_INTERVAL_MS: u64 = 250;
+const RELOAD_TAGS_INTERVAL: Duration = Duration::from_secs(10);
+pub const MIN_FLUSH_INTERVAL_MS: u64 = 500;
+const MAX_BATCH_SIZE: usize = 50_000;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistorianWriterStats {
+    pub running: bool,
+    pub tags_logged: usize,
+    pub buffered: usize,
+    pub samples_written: u64,
+    pub waveforms_written: u64,
+    pub batches_written: u64,
+    pub write_errors: u64,
+    pub samples_dropped: u64,          // Descartadas por estouro do buffer
+    pub last_flush_ms: Option<i64>,
+    pub last_flush_duration_ms: Option<u64>,
+    pub last_target: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Valida a configuração antes de salvar/iniciar
+pub fn validate_config(config: &HistorianWriterConfig) -> Result<(), String> {
+    if config.flush_interval_ms < MIN_FLUSH_INTERVAL_MS {
+        return Err(format!("Intervalo mínimo de gravação do historian é {}ms", MIN_FLUSH_INTERVAL_MS));
+    }
+    if config.batch_size == 0 || config.batch_size > MAX_BATCH_SIZE {
+        return Err(format!("Tamanho do lote deve estar entre 1 e {}", MAX_BATCH_SIZE));
+    }
+    if config.max_buffered < config.batch_size {
+        return Err("O buffer máximo deve ser maior ou igual ao tamanho do lote".to_string());
+    }
+    Ok(())
+}
+
+/// Última amostra gravada de cada tag (decide a próxima no modo change/interval)
+struct LastLogged {
+    value: String,
+    at: Instant,
+}
+
+pub struct HistorianWriter {
+    stats: Arc<Mutex<HistorianWriterStats>>,
+    stop_tx: oneshot::Sender<()>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl HistorianWriter {
+    pub fn start(
+        database: Arc<Database>,
+        websocket_state: WebSocketServerState,
+        failover: Arc<HistorianFailover>,
+        config: HistorianWriterConfig,
+    ) -> Result<Self, String> {
+        validate_config(&config)?;
+        let stats = Arc::new(Mutex::new(HistorianWriterStats { running: true, ..Default::default() }));
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        println!("🚀 Historian gravando: lote de {} amostras ou a cada {}ms", config.batch_size, config.flush_interval_ms);
+        let handle = tokio::spawn(run_writer(database, websocket_state, failover, config, stats.clone(), stop_rx));
+        Ok(Self { stats, stop_tx, handle })
+    }
+
+    pub fn stats(&self) -> HistorianWriterStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Para a gravação, gravando o que estiver no buffer
+    pub async fn stop(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.handle.await;
+        println!("🛑 Historian: gravação contínua parada");
+    }
+}
+
+struct Buffer {
+    samples: VecDeque<SnapshotValue>,
+    waveforms: Vec<WaveformSample>,
+}
+
+async fn run_writer(
+    database: Arc<Database>,
+    websocket_state: WebSocketServerState,
+    failover: Arc<HistorianFailover>,
+    config: HistorianWriterConfig,
+    stats: Arc<Mutex<HistorianWriterStats>>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let mut sample_tick = tokio::time::interval(Duration::from_millis(SAMPLE_INTERVAL_MS));
+    let mut flush_tick = tokio::time::interval(Duration::from_millis(config.flush_interval_ms));
+    let mut buffer = Buffer { samples: VecDeque::new(), waveforms: Vec::new() };
+    let mut last_logged: HashMap<String, LastLogged> = HashMap::new();
+    let mut tags: HashSet<String> = HashSet::new();
+    let mut tags_loaded_at: Option<Instant> = None;
+
+    loop {
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            _ = sample_tick.tick() => {
+                if tags_loaded_at.map_or(true, |at| at.elapsed() >= RELOAD_TAGS_INTERVAL) {
+                    match database.load_historian_tags() {
+                        Ok(list) => {
+                            tags = list.into_iter().map(|(ip, name)| format!("{}:{}", ip, name)).collect();
+                            last_logged.retain(|key, _| tags.contains(key));
+                            stats.lock().unwrap().tags_logged = tags.len();
+                        }
+                        Err(e) => println!("⚠️ Historian: erro ao carregar tags gravados: {}", e),
+                    }
+                    tags_loaded_at = Some(Instant::now());
+                }
+                collect(&websocket_state, &tags, &mut last_logged, &mut buffer).await;
+                if buffer.samples.len() >= config.batch_size {
+                    flush(&failover, &config, &mut buffer, &stats).await;
+                }
+                trim(&config, &mut buffer, &stats);
+            }
+            _ = flush_tick.tick() => flush(&failover, &config, &mut buffer, &stats).await,
+        }
+    }
+
+    flush(&failover, &config, &mut buffer, &stats).await;
+    let mut stats = stats.lock().unwrap();
+    stats.running = false;
+    stats.buffered = buffer.samples.len();
+}
+
+/// Gera as amostras devidas a partir do cache atual
+async fn collect(
+    websocket_state: &WebSocketServerState,
+    tags: &HashSet<String>,
+    last_logged: &mut HashMap<String, LastLogged>,
+    buffer: &mut Buffer,
+) {
+    if tags.is_empty() {
+        return;
+    }
+    let Some(smart_cache) = websocket_state.read().await.as_ref().map(|s| s.smart_cache()) else {
+        return; // Sem WebSocket rodando não há valores ao vivo
+    };
+
+    let now = Instant::now();
+    for cached in smart_cache.snapshot(None) {
+        let key = format!("{}:{}", cached.plc_ip, cached.tag_name);
+        if !tags.contains(&key) {
+            continue;
+        }
+        let due = match last_logged.get(&key) {
+            None => true,
+            Some(last) => match cached.collect_mode.as_str() {
+                "interval" => last.at.elapsed() >= Duration::from_secs(cached.interval_s.max(1)),
+                _ => last.value != cached.value,
+            },
+        };
+        if !due {
+            continue;
+        }
+
+        let ts_ms = (cached.timestamp_ns / 1_000_000) as i64;
+        if crate::plc_parser::is_waveform_type(&cached.data_type) {
+            if let Some(points) = crate::plc_parser::waveform_points(&cached.value) {
+                buffer.waveforms.push(WaveformSample {
+                    plc_ip: cached.plc_ip.clone(),
+                    tag_name: cached.tag_name.clone(),
+                    data_type: cached.data_type.trim_end_matches(crate::plc_parser::WAVEFORM_TYPE_SUFFIX).to_string(),
+                    unit: cached.unit.clone(),
+                    ts_ms,
+                    point_count: points.len() as i32,
+                    points,
+                });
+            }
+        } else {
+            buffer.samples.push_back(SnapshotValue {
+                plc_ip: cached.plc_ip.clone(),
+                tag_name: cached.tag_name.clone(),
+                value_num: cached.value.parse::<f64>().ok(),
+                value: cached.value.clone(),
+                // "interval" grava o instante da amostragem; "change", o da chegada do valor
+                ts_ms: if cached.collect_mode == "interval" { chrono::Utc::now().timestamp_millis() } else { ts_ms },
+                unit: None,
+            });
+        }
+        last_logged.insert(key, LastLogged { value: cached.value, at: now });
+    }
+}
+
+/// Grava o buffer em lotes; o que falhar volta para o buffer
+async fn flush(failover: &HistorianFailover, config: &HistorianWriterConfig, buffer: &mut Buffer, stats: &Mutex<HistorianWriterStats>) {
+    let started = Instant::now();
+    let mut written = 0u64;
+    let mut batches = 0u64;
+    let mut target = None;
+    let mut error = None;
+
+    while !buffer.samples.is_empty() {
+        let count = buffer.samples.len().min(config.batch_size);
+        let batch: Vec<SnapshotValue> = buffer.samples.iter().take(count).cloned().collect();
+        match failover.write(&batch).await {
+            Ok(name) => {
+                buffer.samples.drain(..count);
+                written += count as u64;
+                batches += 1;
+                target = Some(name);
+            }
+            Err(e) => {
+                error = Some(e);
+                break; // Todos os destinos fora: tentar de novo no próximo ciclo
+            }
+        }
+    }
+
+    let mut waveforms_written = 0u64;
+    if !buffer.waveforms.is_empty() && error.is_none() {
+        match failover.write_waveforms(&buffer.waveforms).await {
+            Ok(_) => {
+                waveforms_written = buffer.waveforms.len() as u64;
+                buffer.waveforms.clear();
+            }
+            Err(e) => error = Some(e),
+        }
+    }
+
+    if written == 0 && waveforms_written == 0 && error.is_none() {
+        return;
+    }
+    let mut stats = stats.lock().unwrap();
+    stats.samples_written += written;
+    stats.waveforms_written += waveforms_written;
+    stats.batches_written += batches;
+    stats.buffered = buffer.samples.len();
+    stats.last_flush_ms = Some(chrono::Utc::now().timestamp_millis());
+    stats.last_flush_duration_ms = Some(started.elapsed().as_millis() as u64);
+    if target.is_some() {
+        stats.last_target = target;
+    }
+    match error {
+        Some(e) => {
+            if stats.last_error.is_none() {
+                println!("❌ Historian: gravação falhou ({} amostras no buffer): {}", buffer.samples.len(), e);
+            }
+            stats.write_errors += 1;
+            stats.last_error = Some(e);
+        }
+        None => {
+            if stats.last_error.take().is_some() {
+                println!("✅ Historian: gravação retomada");
+            }
+        }
+    }
+}
+
+/// Com os destinos fora por muito tempo, descarta as amostras mais antigas
+fn trim(config: &HistorianWriterConfig, buffer: &mut Buffer, stats: &Mutex<HistorianWriterStats>) {
+    let excess = buffer.samples.len().saturating_sub(config.max_buffered);
+    // Formas de onda são grandes: no máximo um lote pendente
+    let waveform_excess = buffer.waveforms.len().saturating_sub(config.batch_size);
+    if waveform_excess > 0 {
+        buffer.waveforms.drain(..waveform_excess);
+    }
+    let mut stats = stats.lock().unwrap();
+    if excess > 0 {
+        buffer.samples.drain(..excess);
+        stats.samples_dropped += excess as u64;
+    }
+    stats.buffered = buffer.samples.len();
+}
@@ -0,0 +1,138 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+// ============================================================================
+// LOCALIZAÇÃO DAS MENSAGENS DO BACKEND
+// ============================================================================
+//
+// Catálogo chave → texto por idioma (pt/en). Os textos usam parâmetros
+// nomeados ("{ip}"), preenchidos por `t`/`msg`. Eventos e comandos convertidos
+// devolvem a chave + parâmetros junto com o texto, para a interface poder
+// traduzir por conta própria. Chave sem tradução cai no português e, sem
+// português, na própria chave. O idioma fica no AppConfig (`language`).
+
+pub const DEFAULT_LANGUAGE: &str = "pt";
+pub const LANGUAGES: &[&str] = &["pt", "en"];
+
+static LANGUAGE: RwLock<&'static str> = RwLock::new(DEFAULT_LANGUAGE);
+
+/// (chave, português, inglês)
+const CATALOG: &[(&str, &str, &str)] = &[
+    // Genéricos
+    ("common.raw", "{message}", "{message}"),
+    ("common.unknown_event", "{event}", "{event}"),
+    ("language.changed", "Idioma do backend: {language}", "Backend language: {language}"),
+    ("language.unsupported", "Idioma não suportado: {language} (use {supported})", "Unsupported language: {language} (use {supported})"),
+    // Comandos
+    ("config.saved", "Configuração salva com sucesso!", "Configuration saved successfully!"),
+    ("plc.structure_saved", "Configuração salva para PLC {ip}: {bytes} bytes", "Configuration saved for PLC {ip}: {bytes} bytes"),
+    ("plc.structure_deleted", "Configuração removida para PLC {ip}", "Configuration removed for PLC {ip}"),
+    ("tag.saved", "Tag '{tag}' salvo com ID {id} - {state}", "Tag '{tag}' saved with ID {id} - {state}"),
+    ("tag.state_enabled", "Ativado para WebSocket", "Enabled for WebSocket"),
+    ("tag.state_disabled", "Inativo", "Inactive"),
+    ("tag.deleted", "Tag {path} removido", "Tag {path} removed"),
+    ("tag.renamed", "Tag '{old}' renomeado para '{new}' ({rows} amostras de histórico, {references} referências)",
+        "Tag '{old}' renamed to '{new}' ({rows} history samples, {references} references)"),
+    ("alarm.saved", "Alarme '{name}' salvo com ID {id}", "Alarm '{name}' saved with ID {id}"),
+    ("alarm.deleted", "Alarme #{id} removido", "Alarm #{id} removed"),
+    ("alarm.acked", "Alarme '{name}' reconhecido", "Alarm '{name}' acknowledged"),
+    // Alarmes (texto padrão da ocorrência)
+    ("alarm.condition", "{tag} {comparison} {threshold} (valor {value})", "{tag} {comparison} {threshold} (value {value})"),
+    // Notificações (título / corpo)
+    ("notification.tcp_connection_dead.title", "PLC {ip} sem resposta", "PLC {ip} not responding"),
+    ("notification.tcp_connection_dead.body", "Conexão encerrada pelo watchdog após {seconds}s sem dados",
+        "Connection closed by the watchdog after {seconds}s without data"),
+    ("notification.tcp_connection_timeout.title", "Timeout na conexão com PLC {ip}", "Connection timeout with PLC {ip}"),
+    ("notification.tcp_connection_error.title", "Erro na conexão com PLC {ip}", "Connection error with PLC {ip}"),
+    ("notification.plc_disconnected.title", "PLC {ip} desconectado", "PLC {ip} disconnected"),
+    ("notification.plc_disconnected.body", "A conexão TCP com o PLC foi encerrada", "The TCP connection with the PLC was closed"),
+    ("notification.sqlite_error.title", "Erro no banco de dados local", "Local database error"),
+    ("notification.config_drift.title", "Configuração divergente do HMI {peer}", "Configuration drift on HMI {peer}"),
+    ("notification.config_drift.body", "Checksum local {local} ≠ par {remote}", "Local checksum {local} ≠ peer {remote}"),
+    ("notification.config_backup_failed.title", "Backup automático da configuração falhou", "Automatic configuration backup failed"),
+    ("notification.csv_logger_error.title", "Logger CSV sem acesso ao arquivo", "CSV logger cannot access the file"),
+    ("notification.opc_bridge_stopped.title", "Ponte OPC DA {prog_id} parou", "OPC DA bridge {prog_id} stopped"),
+    ("notification.config_tampered.title", "Arquivo de configuração possivelmente adulterado", "Configuration file possibly tampered with"),
+    ("notification.backend_restarted.title", "HMI reiniciado pelo supervisor (#{count})", "HMI restarted by the supervisor (#{count})"),
+    ("notification.panel_offline.title", "Painel {panel} sem comunicação", "Panel {panel} offline"),
+    ("notification.panel_offline.body", "Nenhum heartbeat recebido de {hostname} ({address})", "No heartbeat received from {hostname} ({address})"),
+    ("notification.panel_error.title", "Painel {panel} reportou {count} erro(s)", "Panel {panel} reported {count} error(s)"),
+    ("notification.plc_rate_deviation.title", "PLC {ip} fora da taxa esperada ({level})", "PLC {ip} outside the expected rate ({level})"),
+    ("notification.plc_rate_deviation.body", "Intervalo real {actual} ms, esperado {expected} ms ({deviation}%)",
+        "Actual interval {actual} ms, expected {expected} ms ({deviation}%)"),
+    ("notification.plc_rate_stopped.title", "PLC {ip} parou de enviar", "PLC {ip} stopped sending"),
+    ("notification.plc_rate_stopped.body", "Nenhum pacote nos últimos {window}s (esperado a cada {expected} ms)",
+        "No packets in the last {window}s (expected every {expected} ms)"),
+    ("notification.historian_backfill_error.title", "Backfill do PLC {ip} não gravado", "Backfill from PLC {ip} not stored"),
+    ("notification.security_anomaly.title", "Taxa anormal de comandos '{category}' na sessão {session}",
+        "Abnormal '{category}' command rate in session {session}"),
+    ("notification.security_anomaly.body", "{count} operações em {window}s (limite {limit})", "{count} operations in {window}s (limit {limit})"),
+    ("notification.historian_failover.title", "Historian gravando em '{to}'", "Historian writing to '{to}'"),
+    ("notification.historian_failover.body", "Destino anterior: {from}", "Previous target: {from}"),
+    ("notification.historian_failover.body_reason", "Destino anterior: {from} - {reason}", "Previous target: {from} - {reason}"),
+    ("notification.tag_flatlined.title", "Possível sensor congelado: {tag} (PLC {ip})", "Possible frozen sensor: {tag} (PLC {ip})"),
+    ("notification.tag_flatlined.body", "Valor parado em {value} há {seconds}s com o PLC comunicando",
+        "Value stuck at {value} for {seconds}s while the PLC is communicating"),
+    ("notification.plc_quarantined.title", "PLC {ip} em quarentena: frames não estão sendo parseados",
+        "PLC {ip} quarantined: frames are not being parsed"),
+    ("notification.redundancy_peer_lost.title", "HMI par {peer} sem heartbeat", "Peer HMI {peer} missing heartbeat"),
+    ("notification.redundancy_peer_lost.body", "Nenhum heartbeat há {seconds}s", "No heartbeat for {seconds}s"),
+];
+
+/// Mensagem com chave, parâmetros e o texto no idioma atual
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalizedMessage {
+    pub key: String,
+    pub text: String,
+    pub args: BTreeMap<String, String>,
+}
+
+pub fn current_language() -> &'static str {
+    *LANGUAGE.read().unwrap()
+}
+
+pub fn set_language(language: &str) -> Result<&'static str, String> {
+    let Some(language) = LANGUAGES.iter().copied().find(|l| l.eq_ignore_ascii_case(language.trim())) else {
+        return Err(translate(current_language(), "language.unsupported", &[
+            ("language", language.to_string()),
+            ("supported", LANGUAGES.join(", ")),
+        ]));
+    };
+    *LANGUAGE.write().unwrap() = language;
+    Ok(language)
+}
+
+fn template(language: &str, key: &str) -> Option<&'static str> {
+    let (_, pt, en) = CATALOG.iter().find(|(k, _, _)| *k == key)?;
+    Some(if language == "en" && !en.is_empty() { *en } else { *pt })
+}
+
+/// Texto da chave em um idioma, com os parâmetros substituídos
+pub fn translate(language: &str, key: &str, args: &[(&str, String)]) -> String {
+    let mut text = template(language, key).unwrap_or(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Texto da chave no idioma atual
+pub fn t(key: &str, args: &[(&str, String)]) -> String {
+    translate(current_language(), key, args)
+}
+
+pub fn msg(key: &str, args: &[(&str, String)]) -> LocalizedMessage {
+    LocalizedMessage {
+        key: key.to_string(),
+        text: t(key, args),
+        args: args.iter().map(|(name, value)| (name.to_string(), value.clone())).collect(),
+    }
+}
+
+/// Catálogo completo de um idioma (para a interface traduzir as chaves)
+pub fn catalog(language: &str) -> BTreeMap<&'static str, &'static str> {
+    CATALOG.iter()
+        .map(|(key, _, _)| (*key, template(language, key).unwrap_or(*key)))
+        .collect()
+}
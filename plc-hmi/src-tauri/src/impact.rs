@@ -0,0 +1,278 @@
+use crate::database::{Database, TagMapping};
+use crate::plc_parser::{split_array_index, split_variable_path};
+use crate::websocket_server::parse_edge_path;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+// ============================================================================
+// GRAFO DE DEPENDÊNCIAS E ANÁLISE DE IMPACTO
+// ============================================================================
+//
+// Antes de excluir um tag ou um bloco da estrutura, a interface pergunta o que
+// depende dele. O grafo liga:
+//   bloco → tag (variable_path aponta para o bloco)
+//   tag → tag derivado (RISE(tag)/FALL(tag))
+//   tag → logger CSV, gravação no historian, regra de chave pública (grupo "tag"), caminho crítico
+//   tag → definição de alarme
+//   tag → prioridade de área/categoria (quando é o último tag do grupo)
+// Referências no historian (PostgreSQL) são contadas à parte pelo comando,
+// porque exigem conexão.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyNode {
+    pub id: String,    // Ex: "block:192.168.1.10:Word", "tag:192.168.1.10:nivel"
+    pub kind: String,  // "block", "tag", "csv_logger", "historian_logging", "public_key", "group_priority", "critical_path", "alarm"
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: String, // "maps", "derives", "logged_by", "masked_by", "prioritized_by", "critical", "alarmed_by"
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+}
+
+/// Algo que deixa de funcionar (ou fica órfão) com a exclusão
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactItem {
+    pub kind: String,
+    pub id: String,
+    pub label: String,
+    pub relation: String,
+    pub via: String,    // Nó de onde vem a dependência
+    pub depth: usize,   // 1 = depende diretamente do alvo
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactReport {
+    pub plc_ip: String,
+    pub target_type: String,            // "tag" ou "block"
+    pub target: String,
+    pub affected_tags: Vec<String>,     // Tags removidos/quebrados (inclui derivados)
+    pub dependents: Vec<ImpactItem>,
+    pub history_rows: Option<i64>,      // None = historian não consultado
+    pub history_by_tag: BTreeMap<String, i64>,
+    pub history_error: Option<String>,
+    pub safe_to_delete: bool,           // Nada depende do alvo
+}
+
+fn block_id(plc_ip: &str, block: &str) -> String {
+    format!("block:{}:{}", plc_ip, block)
+}
+
+fn tag_id(plc_ip: &str, tag: &str) -> String {
+    format!("tag:{}:{}", plc_ip, tag)
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    graph: DependencyGraph,
+    seen: HashSet<String>,
+}
+
+impl GraphBuilder {
+    fn node(&mut self, id: String, kind: &str, label: String) -> String {
+        if self.seen.insert(id.clone()) {
+            self.graph.nodes.push(DependencyNode { id: id.clone(), kind: kind.to_string(), label });
+        }
+        id
+    }
+
+    fn edge(&mut self, from: &str, to: &str, relation: &str) {
+        self.graph.edges.push(DependencyEdge { from: from.to_string(), to: to.to_string(), relation: relation.to_string() });
+    }
+}
+
+/// Bloco referenciado por um variable_path ("Word[5].3" → "Word"; forma de onda = nome do bloco)
+fn block_of(tag: &TagMapping, block_names: &HashSet<String>) -> Option<String> {
+    if block_names.contains(&tag.variable_path) {
+        return Some(tag.variable_path.clone());
+    }
+    let (variable, _) = split_variable_path(&tag.variable_path).ok()?;
+    split_array_index(variable).map(|(block, _)| block.to_string())
+}
+
+/// Monta o grafo de toda a configuração (todos os PLCs)
+pub fn build_graph(db: &Database) -> Result<DependencyGraph, String> {
+    let mut plcs: Vec<String> = db.list_configured_plcs().map_err(|e| format!("Erro ao listar PLCs: {}", e))?;
+    for plc_ip in db.list_plcs_with_tags().map_err(|e| format!("Erro ao listar PLCs com tags: {}", e))? {
+        if !plcs.contains(&plc_ip) {
+            plcs.push(plc_ip);
+        }
+    }
+
+    let mut all_tags: Vec<TagMapping> = Vec::new();
+    let mut builder = GraphBuilder::default();
+
+    for plc_ip in &plcs {
+        let structure = db.load_plc_structure(plc_ip)
+            .map_err(|e| format!("Erro ao carregar estrutura de {}: {}", plc_ip, e))?;
+        let mut block_names: HashSet<String> = HashSet::new();
+        if let Some(structure) = &structure {
+            for block in structure.profiles.iter().flat_map(|p| p.blocks.iter()).chain(structure.blocks.iter()) {
+                block_names.insert(block.name.clone());
+                builder.node(block_id(plc_ip, &block.name), "block", format!("{} ({})", block.name, plc_ip));
+            }
+        }
+
+        let tags = db.load_tag_mappings(plc_ip)
+            .map_err(|e| format!("Erro ao carregar tags de {}: {}", plc_ip, e))?;
+        for tag in &tags {
+            let id = builder.node(tag_id(plc_ip, &tag.tag_name), "tag", format!("{} ({})", tag.tag_name, tag.variable_path));
+            match parse_edge_path(&tag.variable_path) {
+                Some((_, source)) => {
+                    let source_id = builder.node(tag_id(plc_ip, source), "tag", source.to_string());
+                    builder.edge(&source_id, &id, "derives");
+                }
+                None => {
+                    if let Some(block) = block_of(tag, &block_names) {
+                        let from = builder.node(block_id(plc_ip, &block), "block", format!("{} ({})", block, plc_ip));
+                        builder.edge(&from, &id, "maps");
+                    }
+                }
+            }
+            if tag.critical {
+                let critical = builder.node("critical_path".to_string(), "critical_path", "Caminho crítico do WebSocket".to_string());
+                builder.edge(&id, &critical, "critical");
+            }
+        }
+        all_tags.extend(tags);
+    }
+
+    // Logger CSV: colunas "plc_ip:tag"
+    let csv = db.load_csv_logger_config().map_err(|e| format!("Erro ao carregar logger CSV: {}", e))?;
+    for column in &csv.tags {
+        if let Some((plc_ip, tag)) = column.split_once(':') {
+            let csv_id = builder.node("csv_logger".to_string(), "csv_logger", "Logger CSV".to_string());
+            builder.edge(&tag_id(plc_ip, tag), &csv_id, "logged_by");
+        }
+    }
+
+    // Gravação contínua no historian (tabela historian_tags)
+    for (plc_ip, tag) in db.load_historian_tags().map_err(|e| format!("Erro ao carregar tags do historian: {}", e))? {
+        let historian_id = builder.node("historian_logging".to_string(), "historian_logging", "Gravação no historian".to_string());
+        builder.edge(&tag_id(&plc_ip, &tag), &historian_id, "logged_by");
+    }
+
+    // Definições de alarme
+    for alarm in db.list_alarm_definitions(None).map_err(|e| format!("Erro ao carregar alarmes: {}", e))? {
+        let alarm_id = builder.node(
+            format!("alarm:{}", alarm.id),
+            "alarm",
+            format!("{} ({} {} {})", alarm.name, alarm.tag_name, alarm.comparison, alarm.threshold),
+        );
+        builder.edge(&tag_id(&alarm.plc_ip, &alarm.tag_name), &alarm_id, "alarmed_by");
+    }
+
+    // Regras de chaves públicas por tag (valem para o nome em qualquer PLC)
+    for key in db.load_public_stream_keys().map_err(|e| format!("Erro ao carregar chaves públicas: {}", e))? {
+        for rule in key.rules.iter().filter(|r| r.group_type == "tag") {
+            let key_id = builder.node(format!("public_key:{}", key.name), "public_key", key.name.clone());
+            for tag in all_tags.iter().filter(|t| t.tag_name == rule.group_name) {
+                builder.edge(&tag_id(&tag.plc_ip, &tag.tag_name), &key_id, "masked_by");
+            }
+        }
+    }
+
+    // Prioridade de grupo só depende do tag se ele for o último membro do grupo
+    let mut members: HashMap<(&str, &str), Vec<&TagMapping>> = HashMap::new();
+    for tag in &all_tags {
+        if let Some(area) = tag.area.as_deref() {
+            members.entry(("area", area)).or_default().push(tag);
+        }
+        if let Some(category) = tag.category.as_deref() {
+            members.entry(("category", category)).or_default().push(tag);
+        }
+    }
+    for priority in db.list_tag_group_priorities().map_err(|e| format!("Erro ao carregar prioridades: {}", e))? {
+        let Some(tags) = members.get(&(priority.group_type.as_str(), priority.group_name.as_str())) else { continue };
+        if let [only] = tags.as_slice() {
+            let group_id = builder.node(
+                format!("group_priority:{}:{}", priority.group_type, priority.group_name),
+                "group_priority",
+                format!("{} {} (prioridade {})", priority.group_type, priority.group_name, priority.priority),
+            );
+            builder.edge(&tag_id(&only.plc_ip, &only.tag_name), &group_id, "prioritized_by");
+        }
+    }
+
+    Ok(builder.graph)
+}
+
+/// Percorre o grafo a partir do alvo e lista tudo que depende dele
+fn analyze(graph: &DependencyGraph, plc_ip: &str, target_type: &str, target: &str, root: String) -> Result<ImpactReport, String> {
+    if !graph.nodes.iter().any(|n| n.id == root) {
+        return Err(match target_type {
+            "block" => format!("Bloco '{}' não encontrado em {}", target, plc_ip),
+            _ => format!("Tag '{}' não encontrado em {}", target, plc_ip),
+        });
+    }
+    let labels: HashMap<&str, &DependencyNode> = graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let mut outgoing: HashMap<&str, Vec<&DependencyEdge>> = HashMap::new();
+    for edge in &graph.edges {
+        outgoing.entry(edge.from.as_str()).or_default().push(edge);
+    }
+
+    let mut dependents = Vec::new();
+    let mut visited: HashSet<&str> = HashSet::from([root.as_str()]);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(root.as_str(), 0)]);
+    while let Some((node, depth)) = queue.pop_front() {
+        for edge in outgoing.get(node).map(Vec::as_slice).unwrap_or_default() {
+            if !visited.insert(edge.to.as_str()) {
+                continue;
+            }
+            let Some(target_node) = labels.get(edge.to.as_str()) else { continue };
+            dependents.push(ImpactItem {
+                kind: target_node.kind.clone(),
+                id: target_node.id.clone(),
+                label: target_node.label.clone(),
+                relation: edge.relation.clone(),
+                via: node.to_string(),
+                depth: depth + 1,
+            });
+            // Só tags propagam: logger, chaves, prioridades e alarmes são folhas
+            if target_node.kind == "tag" {
+                queue.push_back((edge.to.as_str(), depth + 1));
+            }
+        }
+    }
+
+    let tag_prefix = format!("tag:{}:", plc_ip);
+    let mut affected_tags: Vec<String> = dependents.iter()
+        .filter(|d| d.kind == "tag")
+        .filter_map(|d| d.id.strip_prefix(&tag_prefix).map(str::to_string))
+        .collect();
+    if target_type == "tag" {
+        affected_tags.insert(0, target.to_string());
+    }
+
+    Ok(ImpactReport {
+        plc_ip: plc_ip.to_string(),
+        target_type: target_type.to_string(),
+        target: target.to_string(),
+        affected_tags,
+        safe_to_delete: dependents.is_empty(),
+        dependents,
+        history_rows: None,
+        history_by_tag: BTreeMap::new(),
+        history_error: None,
+    })
+}
+
+/// Impacto de excluir um tag
+pub fn analyze_tag(db: &Database, plc_ip: &str, tag_name: &str) -> Result<ImpactReport, String> {
+    let graph = build_graph(db)?;
+    analyze(&graph, plc_ip, "tag", tag_name, tag_id(plc_ip, tag_name))
+}
+
+/// Impacto de excluir um bloco da estrutura (todos os tags mapeados nele e seus dependentes)
+pub fn analyze_block(db: &Database, plc_ip: &str, block_name: &str) -> Result<ImpactReport, String> {
+    let graph = build_graph(db)?;
+    analyze(&graph, plc_ip, "block", block_name, block_id(plc_ip, block_name))
+}
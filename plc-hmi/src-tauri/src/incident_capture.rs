@@ -0,0 +1,236 @@
+use crate::database::{Database, IncidentRecord};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// CAPTURA AUTOMÁTICA DE LOGS EM INCIDENTES
+// ============================================================================
+//
+// Falhas de campo costumam ser transitórias: quando alguém olha, a conexão já
+// voltou. Por isso o backend mantém em memória os logs de depuração dos
+// últimos LOG_WINDOW_SECS e os últimos frames brutos de cada PLC. Quando uma
+// conexão cai ou um alarme crítico dispara, esse conteúdo é congelado em um
+// incidente: linha na tabela `incidents` + arquivos em
+// `<pasta do banco>/incidents/incident_<ms>/` (log.txt e frames.txt).
+
+const LOG_WINDOW_SECS: u64 = 60;
+const MAX_LOG_LINES: usize = 20_000;
+const MAX_FRAMES_PER_PLC: usize = 10;
+const MAX_FRAME_BYTES: usize = 64 * 1024;
+/// Mesmo gatilho (tipo + PLC) não gera outro incidente dentro deste intervalo
+const CAPTURE_COOLDOWN: Duration = Duration::from_secs(30);
+pub const INCIDENT_RETENTION: usize = 200;
+
+/// Eventos de queda de conexão (os de severidade "critical" são alarmes críticos)
+const CONNECTION_LOST_EVENTS: &[&str] = &["tcp-connection-dead", "tcp-connection-timeout", "tcp-connection-error"];
+
+struct LogLine {
+    at: Instant,
+    ts_ms: i64,
+    level: &'static str,
+    source: String,
+    message: String,
+}
+
+struct RawFrame {
+    ts_ms: i64,
+    total_len: usize,
+    bytes: Vec<u8>,
+}
+
+static LOG_RING: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+static FRAME_RING: Mutex<Option<HashMap<String, VecDeque<RawFrame>>>> = Mutex::new(None);
+static LAST_CAPTURE: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+
+fn push_log(level: &'static str, source: &str, message: String) {
+    let now = Instant::now();
+    let mut ring = LOG_RING.lock().unwrap();
+    while ring.front().is_some_and(|l| now.duration_since(l.at).as_secs() > LOG_WINDOW_SECS) || ring.len() >= MAX_LOG_LINES {
+        ring.pop_front();
+    }
+    ring.push_back(LogLine { at: now, ts_ms: chrono::Utc::now().timestamp_millis(), level, source: source.to_string(), message });
+}
+
+/// Log de depuração: não vai para o console, só entra nos incidentes
+pub fn debug(source: &str, message: impl Into<String>) {
+    push_log("DEBUG", source, message.into());
+}
+
+/// Guarda um frame bruto recebido do PLC (só os últimos MAX_FRAMES_PER_PLC)
+pub fn record_frame(plc_ip: &str, frame: &[u8]) {
+    let mut rings = FRAME_RING.lock().unwrap();
+    let ring = rings.get_or_insert_with(HashMap::new).entry(plc_ip.to_string()).or_default();
+    if ring.len() >= MAX_FRAMES_PER_PLC {
+        ring.pop_front();
+    }
+    ring.push_back(RawFrame {
+        ts_ms: chrono::Utc::now().timestamp_millis(),
+        total_len: frame.len(),
+        bytes: frame[..frame.len().min(MAX_FRAME_BYTES)].to_vec(),
+    });
+}
+
+fn format_ts(ts_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ts_ms)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| ts_ms.to_string())
+}
+
+/// Copia os logs da janela atual
+fn snapshot_logs() -> (usize, String) {
+    let now = Instant::now();
+    let ring = LOG_RING.lock().unwrap();
+    let mut text = String::new();
+    let mut count = 0;
+    for line in ring.iter().filter(|l| now.duration_since(l.at).as_secs() <= LOG_WINDOW_SECS) {
+        let _ = writeln!(text, "{} [{}] {}: {}", format_ts(line.ts_ms), line.level, line.source, line.message);
+        count += 1;
+    }
+    (count, text)
+}
+
+/// Dump hex dos frames do PLC (ou de todos, sem PLC)
+fn snapshot_frames(plc_ip: Option<&str>) -> (usize, String) {
+    let rings = FRAME_RING.lock().unwrap();
+    let mut text = String::new();
+    let mut count = 0;
+    for (ip, frames) in rings.iter().flatten() {
+        if plc_ip.is_some_and(|wanted| wanted != ip) {
+            continue;
+        }
+        for frame in frames {
+            count += 1;
+            let truncated = if frame.total_len > frame.bytes.len() { " (truncado)" } else { "" };
+            let _ = writeln!(text, "# {} | PLC {} | {} bytes{}", format_ts(frame.ts_ms), ip, frame.total_len, truncated);
+            for (row, chunk) in frame.bytes.chunks(16).enumerate() {
+                let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+                let _ = writeln!(text, "{:08X}  {}", row * 16, hex.join(" "));
+            }
+            text.push('\n');
+        }
+    }
+    (count, text)
+}
+
+fn incidents_dir(db: &Database) -> PathBuf {
+    db.db_path()
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default()
+        .join("incidents")
+}
+
+/// Congela logs e frames em um incidente (None se o gatilho ainda está em cooldown)
+pub fn capture(
+    database: &Database,
+    kind: &str,
+    plc_ip: Option<&str>,
+    source_event: &str,
+    reason: &str,
+    notification_id: Option<i64>,
+) -> Result<Option<IncidentRecord>, String> {
+    let cooldown_key = format!("{}:{}", kind, plc_ip.unwrap_or("*"));
+    {
+        let mut last = LAST_CAPTURE.lock().unwrap();
+        let last = last.get_or_insert_with(HashMap::new);
+        if last.get(&cooldown_key).is_some_and(|at| at.elapsed() < CAPTURE_COOLDOWN) {
+            return Ok(None);
+        }
+        last.insert(cooldown_key, Instant::now());
+    }
+
+    let created_at_ms = chrono::Utc::now().timestamp_millis();
+    let (log_lines, logs) = snapshot_logs();
+    let (frame_count, frames) = snapshot_frames(plc_ip);
+    let dir = incidents_dir(database).join(format!("incident_{}", created_at_ms));
+    fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar pasta do incidente: {}", e))?;
+
+    let header = format!(
+        "Incidente: {} ({})\nPLC: {}\nMotivo: {}\nCapturado em: {}\n\n",
+        kind, source_event, plc_ip.unwrap_or("-"), reason, format_ts(created_at_ms)
+    );
+    fs::write(dir.join("log.txt"), format!("{}{}", header, logs))
+        .map_err(|e| format!("Erro ao gravar log do incidente: {}", e))?;
+    fs::write(dir.join("frames.txt"), format!("{}{}", header, frames))
+        .map_err(|e| format!("Erro ao gravar frames do incidente: {}", e))?;
+
+    let mut incident = IncidentRecord {
+        id: 0,
+        kind: kind.to_string(),
+        plc_ip: plc_ip.map(str::to_string),
+        source_event: source_event.to_string(),
+        reason: reason.to_string(),
+        notification_id,
+        log_lines,
+        frame_count,
+        files_dir: dir.to_string_lossy().to_string(),
+        created_at: created_at_ms / 1000,
+    };
+    incident.id = database.add_incident(&incident).map_err(|e| format!("Erro ao registrar incidente: {}", e))?;
+
+    match database.prune_incidents(INCIDENT_RETENTION) {
+        Ok(old_dirs) => {
+            for old in old_dirs {
+                let _ = fs::remove_dir_all(old);
+            }
+        }
+        Err(e) => println!("⚠️ Incidentes: erro ao remover antigos: {}", e),
+    }
+
+    println!("🧾 Incidente #{} capturado ({}, PLC {}): {} linhas de log, {} frames", incident.id, kind, plc_ip.unwrap_or("-"), log_lines, frame_count);
+    Ok(Some(incident))
+}
+
+/// Captura em segundo plano a partir de um evento já persistido como notificação
+pub fn capture_for_event(app_handle: &AppHandle, database: std::sync::Arc<Database>, event_name: &str, severity: &str, payload: &serde_json::Value, notification_id: Option<i64>) {
+    let kind = if CONNECTION_LOST_EVENTS.contains(&event_name) {
+        "connection_lost"
+    } else if severity == "critical" {
+        "critical_alarm"
+    } else {
+        return;
+    };
+    let plc_ip = ["ip", "plc_ip"].iter()
+        .find_map(|key| payload.get(*key).and_then(|v| v.as_str()))
+        .map(str::to_string);
+    let reason = ["reason", "error", "message"].iter()
+        .find_map(|key| payload.get(*key).and_then(|v| v.as_str()))
+        .unwrap_or_default()
+        .to_string();
+    let event_name = event_name.to_string();
+    let emitter = app_handle.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        match capture(&database, kind, plc_ip.as_deref(), &event_name, &reason, notification_id) {
+            Ok(Some(incident)) => {
+                let _ = emitter.emit("incident-captured", &incident);
+            }
+            Ok(None) => {}
+            Err(e) => println!("❌ Incidentes: falha ao capturar '{}': {}", event_name, e),
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentDetail {
+    pub incident: IncidentRecord,
+    pub log: String,
+    pub frames: String,
+}
+
+/// Incidente com o conteúdo dos arquivos
+pub fn load_incident(database: &Database, id: i64) -> Result<IncidentDetail, String> {
+    let incident = database.get_incident(id)
+        .map_err(|e| format!("Erro ao carregar incidente: {}", e))?
+        .ok_or_else(|| format!("Incidente #{} não encontrado", id))?;
+    let dir = PathBuf::from(&incident.files_dir);
+    let read = |name: &str| fs::read_to_string(dir.join(name))
+        .map_err(|e| format!("Erro ao ler {} do incidente #{}: {}", name, id, e));
+    Ok(IncidentDetail { log: read("log.txt")?, frames: read("frames.txt")?, incident })
+}
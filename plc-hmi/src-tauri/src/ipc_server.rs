@@ -0,0 +1,557 @@
+use crate::commands::WebSocketServerState;
+use crate::database::Database;
+use crate::websocket_server::{CachedTagValue, SmartCache};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinSet;
+
+// ============================================================================
+// API LOCAL (IPC) PARA O PLC-APP CONSUMIR OS TAGS DO HMI
+// ============================================================================
+//
+// Um painel plc-app na mesma máquina assina tags direto do SmartCache, sem
+// passar pelo WebSocket/TCP. Transporte: named pipe no Windows, socket Unix
+// nos demais. Protocolo compacto, uma mensagem JSON por linha:
+//
+// plc-app → HMI:
+//   {"op":"auth","token":"wst_..."}     // 1ª mensagem, se houver token de API ativo
+//   {"op":"sub","tags":["nivel","192.168.1.10:pressao","192.168.1.11:*"]}
+//   {"op":"unsub","tags":["nivel"]}
+//   {"op":"list"}                       // Tags disponíveis
+//   {"op":"ping"}
+// HMI → plc-app:
+//   {"t":"hello","v":1,"auth":true}     // auth = exige {"op":"auth"} antes de tudo
+//   {"t":"auth","ok":true}
+//   {"t":"v","ts":1700000000000,"d":{"nivel":"12.5"}}   // Só valores alterados
+//   {"t":"tags","d":[{"k":"192.168.1.10:nivel","type":"REAL","unit":"m"}]}
+//   {"t":"q","q":"bad"}                 // Sem dados ao vivo (WebSocket parado)
+//   {"t":"pong"} | {"t":"err","m":"..."}
+//
+// A chave em "d" é a assinatura usada: "tag" (qualquer PLC), "ip:tag" ou, para
+// "ip:*", o nome do tag. Ao assinar, os valores atuais são enviados na hora.
+// Depois disso o cliente acompanha o canal de cópias do SmartCache (a mesma
+// cópia de 100ms das instâncias do cluster), sem montar uma cópia própria.
+//
+// Acesso: o socket Unix padrão fica numa pasta só do usuário ($XDG_RUNTIME_DIR
+// ou <tmp>/plc-hmi-<uid>, criada com 0700) e é criado já com 0600 (umask 077
+// no bind); o named pipe tem DACL só para SYSTEM e o usuário do HMI. Um socket
+// antigo só é removido se for socket do mesmo usuário. Com token de API ativo
+// (ws_auth.rs) vale a mesma regra do WebSocket: sem token válido a conexão
+// fecha sem dados.
+
+pub const PROTOCOL_VERSION: u32 = 1;
+#[cfg(windows)]
+pub const DEFAULT_ENDPOINT: &str = r"\\.\pipe\plc-hmi-tags";
+#[cfg(not(windows))]
+const SOCKET_NAME: &str = "plc-hmi-tags.sock";
+
+const FEED_CHECK_INTERVAL: Duration = Duration::from_secs(1); // Servidor parado/trocado
+const MAX_SUBSCRIPTIONS: usize = 5_000;
+const MAX_LINE_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpcServerStatus {
+    pub running: bool,
+    pub endpoint: String,
+    pub clients: usize,
+    pub updates_sent: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IpcRequest {
+    op: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Assinatura de um cliente: "tag", "ip:tag" ou "ip:*"
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Subscription {
+    Tag(String),
+    PlcTag(String, String),
+    Plc(String),
+}
+
+impl Subscription {
+    fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        match raw.rsplit_once(':') {
+            Some((ip, "*")) if !ip.is_empty() => Some(Subscription::Plc(ip.to_string())),
+            Some((ip, tag)) if !ip.is_empty() && !tag.is_empty() => Some(Subscription::PlcTag(ip.to_string(), tag.to_string())),
+            None if !raw.is_empty() => Some(Subscription::Tag(raw.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Chave publicada para o valor, se a assinatura o cobre
+    fn key_for(&self, plc_ip: &str, tag_name: &str) -> Option<String> {
+        match self {
+            Subscription::Tag(tag) if tag == tag_name => Some(tag.clone()),
+            Subscription::PlcTag(ip, tag) if ip == plc_ip && tag == tag_name => Some(format!("{}:{}", ip, tag)),
+            Subscription::Plc(ip) if ip == plc_ip => Some(tag_name.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub struct IpcServer {
+    status: Arc<RwLock<IpcServerStatus>>,
+    handle: tokio::task::JoinHandle<()>,
+    endpoint: String,
+}
+
+#[cfg(windows)]
+type Listener = tokio::net::windows::named_pipe::NamedPipeServer;
+#[cfg(not(windows))]
+type Listener = tokio::net::UnixListener;
+
+/// DACL protegida do pipe: só SYSTEM e o usuário dono do processo
+#[cfg(windows)]
+const PIPE_SDDL: &str = "D:P(A;;GA;;;SY)(A;;GA;;;OW)";
+
+/// Nova instância do pipe com a DACL restrita (a padrão deixa outros usuários conectarem)
+#[cfg(windows)]
+fn create_pipe(endpoint: &str, first_instance: bool) -> std::io::Result<Listener> {
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Authorization::{ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1};
+    use windows_sys::Win32::Security::{PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES};
+
+    let sddl: Vec<u16> = PIPE_SDDL.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+    // SAFETY: `sddl` termina em 0 e `descriptor` recebe memória liberada com LocalFree abaixo
+    let converted = unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(sddl.as_ptr(), SDDL_REVISION_1, &mut descriptor, std::ptr::null_mut())
+    };
+    if converted == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let mut attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: descriptor,
+        bInheritHandle: 0,
+    };
+    // SAFETY: `attributes` e o descritor valem até o fim da chamada
+    let pipe = unsafe {
+        tokio::net::windows::named_pipe::ServerOptions::new()
+            .first_pipe_instance(first_instance) // Falha se outro HMI já publica neste pipe
+            .reject_remote_clients(true)
+            .create_with_security_attributes_raw(endpoint, &mut attributes as *mut SECURITY_ATTRIBUTES as *mut std::ffi::c_void)
+    };
+    // SAFETY: descritor alocado por ConvertStringSecurityDescriptorToSecurityDescriptorW
+    unsafe { LocalFree(descriptor) };
+    pipe
+}
+
+#[cfg(windows)]
+fn bind(endpoint: &str) -> std::io::Result<Listener> {
+    create_pipe(endpoint, true)
+}
+
+/// Endpoint padrão (o plc-app calcula o mesmo em hmi_ipc.rs)
+#[cfg(windows)]
+pub fn default_endpoint() -> String {
+    DEFAULT_ENDPOINT.to_string()
+}
+
+/// Endpoint padrão: socket na pasta do usuário (o plc-app calcula o mesmo em hmi_ipc.rs)
+#[cfg(not(windows))]
+pub fn default_endpoint() -> String {
+    runtime_dir().join(SOCKET_NAME).to_string_lossy().to_string()
+}
+
+#[cfg(not(windows))]
+fn runtime_dir() -> std::path::PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => dir.into(),
+        // SAFETY: getuid não falha nem tem efeitos colaterais
+        None => std::env::temp_dir().join(format!("plc-hmi-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// Pasta do socket padrão: criada com 0700; se já existe, precisa ser do usuário e fechada
+#[cfg(not(windows))]
+fn ensure_runtime_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => return Err(e),
+        Err(_) => {}
+    }
+    let metadata = std::fs::symlink_metadata(dir)?;
+    // SAFETY: getuid não falha nem tem efeitos colaterais
+    let uid = unsafe { libc::getuid() };
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied,
+            format!("Pasta {:?} não é do usuário do HMI ou está aberta a outros usuários", dir)));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn bind(endpoint: &str) -> std::io::Result<Listener> {
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    if endpoint == default_endpoint() {
+        ensure_runtime_dir(&runtime_dir())?;
+    }
+
+    // Socket de uma execução anterior que não foi encerrada corretamente:
+    // só removido se for um socket do mesmo usuário e ninguém estiver ouvindo
+    if let Ok(metadata) = std::fs::symlink_metadata(endpoint) {
+        // SAFETY: getuid não falha nem tem efeitos colaterais
+        let ours = metadata.file_type().is_socket() && metadata.uid() == unsafe { libc::getuid() };
+        if !ours {
+            return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists,
+                format!("{} já existe e não é um socket do usuário do HMI", endpoint)));
+        }
+        if std::os::unix::net::UnixStream::connect(endpoint).is_err() {
+            std::fs::remove_file(endpoint)?;
+        }
+    }
+
+    // Só o usuário do HMI conecta (o plc-app roda com o mesmo usuário): o socket
+    // já nasce 0600, sem janela entre o bind e um chmod
+    // SAFETY: umask só troca a máscara do processo; a anterior é restaurada logo após o bind
+    let previous = unsafe { libc::umask(0o077) };
+    let listener = tokio::net::UnixListener::bind(endpoint);
+    // SAFETY: idem
+    unsafe { libc::umask(previous) };
+    listener
+}
+
+impl IpcServer {
+    pub async fn start(websocket_state: WebSocketServerState, database: Arc<Database>, endpoint: Option<String>) -> Result<Self, String> {
+        let endpoint = endpoint.filter(|e| !e.trim().is_empty()).unwrap_or_else(default_endpoint);
+        let listener = bind(&endpoint)
+            .map_err(|e| format!("Erro ao abrir endpoint IPC {}: {}", endpoint, e))?;
+
+        let status = Arc::new(RwLock::new(IpcServerStatus {
+            running: true,
+            endpoint: endpoint.clone(),
+            ..Default::default()
+        }));
+
+        println!("🚀 API IPC para o plc-app em {}", endpoint);
+        let handle = tokio::spawn(run_server(listener, endpoint.clone(), websocket_state, database, status.clone()));
+        Ok(Self { status, handle, endpoint })
+    }
+
+    pub async fn status(&self) -> IpcServerStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Encerra o endpoint e derruba os clientes conectados
+    pub fn stop(self) {
+        self.handle.abort();
+        #[cfg(not(windows))]
+        let _ = std::fs::remove_file(&self.endpoint);
+        println!("🛑 API IPC parada ({})", self.endpoint);
+    }
+}
+
+async fn run_server(listener: Listener, endpoint: String, websocket_state: WebSocketServerState, database: Arc<Database>, status: Arc<RwLock<IpcServerStatus>>) {
+    // Clientes no JoinSet: abortar esta task (stop) encerra todos juntos
+    let mut clients = JoinSet::new();
+    #[cfg(windows)]
+    let mut listener = listener;
+
+    let error = loop {
+        #[cfg(windows)]
+        let accepted = tokio::select! {
+            result = listener.connect() => result,
+            Some(_) = clients.join_next(), if !clients.is_empty() => continue,
+        }
+        .and_then(|_| {
+            // Instância conectada vai para o cliente; nova instância aguarda o próximo
+            let next = create_pipe(&endpoint, false)?;
+            Ok(std::mem::replace(&mut listener, next))
+        });
+        #[cfg(not(windows))]
+        let accepted = tokio::select! {
+            result = listener.accept() => result.map(|(stream, _)| stream),
+            Some(_) = clients.join_next(), if !clients.is_empty() => continue,
+        };
+
+        match accepted {
+            Ok(stream) => {
+                clients.spawn(serve_client(stream, websocket_state.clone(), database.clone(), status.clone()));
+            }
+            Err(e) => break format!("Erro ao aceitar cliente IPC em {}: {}", endpoint, e),
+        }
+    };
+
+    println!("❌ API IPC: {}", error);
+    let mut status = status.write().await;
+    status.running = false;
+    status.clients = 0;
+    status.last_error = Some(error);
+}
+
+async fn send_line<W: AsyncWrite + Unpin>(writer: &mut W, message: &serde_json::Value) -> std::io::Result<()> {
+    writer.write_all(format!("{}\n", message).as_bytes()).await?;
+    writer.flush().await
+}
+
+async fn serve_client<S>(stream: S, websocket_state: WebSocketServerState, database: Arc<Database>, status: Arc<RwLock<IpcServerStatus>>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    status.write().await.clients += 1;
+    println!("🔗 Cliente IPC conectado");
+    if let Err(e) = client_loop(stream, &websocket_state, &database, &status).await {
+        println!("⚠️ Cliente IPC: {}", e);
+    }
+    let mut status = status.write().await;
+    status.clients = status.clients.saturating_sub(1);
+    println!("🔌 Cliente IPC desconectado");
+}
+
+/// Leitura de linhas que nunca acumula mais que MAX_LINE_BYTES por cliente
+struct LineReader<R> {
+    reader: BufReader<R>,
+    buffer: Vec<u8>, // Linha parcial: sobrevive ao cancelamento no select!
+}
+
+impl<R: AsyncRead + Unpin> LineReader<R> {
+    fn new(reader: R) -> Self {
+        Self { reader: BufReader::new(reader), buffer: Vec::new() }
+    }
+
+    /// Ok(None) = conexão encerrada; Err(InvalidData) = linha acima do limite
+    async fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        loop {
+            let remaining = (MAX_LINE_BYTES + 1).saturating_sub(self.buffer.len()) as u64;
+            let read = (&mut self.reader).take(remaining).read_until(b'\n', &mut self.buffer).await?;
+            if self.buffer.last() == Some(&b'\n') {
+                let mut line = std::mem::take(&mut self.buffer);
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                return String::from_utf8(line)
+                    .map(Some)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+            }
+            if self.buffer.len() > MAX_LINE_BYTES {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Mensagem acima de {} bytes", MAX_LINE_BYTES),
+                ));
+            }
+            if read == 0 {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Com token de API ativo, a 1ª mensagem precisa ser {"op":"auth","token":"wst_..."}
+async fn authenticate<R, W>(lines: &mut LineReader<R>, writer: &mut W, database: &Database) -> std::io::Result<bool>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let rejected = match tokio::time::timeout(crate::ws_auth::AUTH_TIMEOUT, lines.next_line()).await {
+        Err(_) => "Token não enviado a tempo".to_string(),
+        Ok(line) => {
+            let Some(line) = line? else { return Ok(false) };
+            match serde_json::from_str::<IpcRequest>(&line) {
+                Ok(IpcRequest { op, token: Some(token), .. }) if op == "auth" => {
+                    match crate::ws_auth::authenticate(database, &token) {
+                        Ok(token) => {
+                            println!("🔐 Cliente IPC autenticado com o token '{}'", token.name);
+                            send_line(writer, &serde_json::json!({"t": "auth", "ok": true})).await?;
+                            return Ok(true);
+                        }
+                        Err(e) => e,
+                    }
+                }
+                _ => "Autenticação obrigatória: envie {\"op\":\"auth\",\"token\":\"wst_...\"}".to_string(),
+            }
+        }
+    };
+    println!("⛔ Cliente IPC recusado: {}", rejected);
+    send_line(writer, &serde_json::json!({"t": "err", "m": rejected})).await?;
+    Ok(false)
+}
+
+async fn client_loop<S>(stream: S, websocket_state: &WebSocketServerState, database: &Database, status: &Arc<RwLock<IpcServerStatus>>) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = LineReader::new(reader);
+    let mut feed_check = tokio::time::interval(FEED_CHECK_INTERVAL);
+    let mut feed: Option<SnapshotFeed> = None; // Só existe com assinaturas ativas
+    let mut subscriptions: BTreeSet<Subscription> = BTreeSet::new();
+    let mut last_values: HashMap<String, String> = HashMap::new();
+    let mut live = true;
+
+    // Mesma regra do WebSocket: sem token ativo o endpoint local fica aberto
+    // (chaves públicas não valem aqui: o IPC não mascara valores)
+    let auth_required = crate::ws_auth::token_required(database);
+    send_line(&mut writer, &serde_json::json!({"t": "hello", "v": PROTOCOL_VERSION, "auth": auth_required})).await?;
+    if auth_required && !authenticate(&mut lines, &mut writer, database).await? {
+        return Ok(());
+    }
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    Ok(None) => return Ok(()),
+                    Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                        // Sem como achar o fim da linha sem ler tudo: encerrar o cliente
+                        send_line(&mut writer, &serde_json::json!({"t": "err", "m": e.to_string()})).await?;
+                        return Err(e);
+                    }
+                    Err(e) => return Err(e),
+                };
+                let request = match serde_json::from_str::<IpcRequest>(&line) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        send_line(&mut writer, &serde_json::json!({"t": "err", "m": format!("JSON inválido: {}", e)})).await?;
+                        continue;
+                    }
+                };
+                match request.op.as_str() {
+                    "sub" => {
+                        let parsed: Vec<Subscription> = request.tags.iter().filter_map(|t| Subscription::parse(t)).collect();
+                        if subscriptions.len() + parsed.len() > MAX_SUBSCRIPTIONS {
+                            send_line(&mut writer, &serde_json::json!({"t": "err", "m": format!("Máximo de {} assinaturas", MAX_SUBSCRIPTIONS)})).await?;
+                            continue;
+                        }
+                        subscriptions.extend(parsed);
+                        last_values.clear(); // Reenviar tudo: o cliente recebe os valores atuais das novas assinaturas
+                        feed = None;
+                        feed_check.reset_immediately();
+                    }
+                    "unsub" => {
+                        for tag in &request.tags {
+                            if let Some(subscription) = Subscription::parse(tag) {
+                                subscriptions.remove(&subscription);
+                            }
+                        }
+                        last_values.retain(|key, _| subscriptions.iter().any(|s| match s {
+                            Subscription::Tag(tag) => tag == key,
+                            Subscription::PlcTag(ip, tag) => key == &format!("{}:{}", ip, tag),
+                            Subscription::Plc(_) => true,
+                        }));
+                        if subscriptions.is_empty() {
+                            feed = None; // Sem receptor o SmartCache deixa de montar a cópia
+                        }
+                    }
+                    "list" => {
+                        let smart_cache = websocket_state.read().await.as_ref().map(|s| s.smart_cache());
+                        let mut tags: Vec<serde_json::Value> = smart_cache.map(|cache| cache.snapshot(None)).unwrap_or_default()
+                            .into_iter()
+                            .map(|c| serde_json::json!({"k": format!("{}:{}", c.plc_ip, c.tag_name), "type": c.data_type, "unit": c.unit}))
+                            .collect();
+                        tags.sort_by(|a, b| a["k"].as_str().cmp(&b["k"].as_str()));
+                        send_line(&mut writer, &serde_json::json!({"t": "tags", "d": tags})).await?;
+                    }
+                    "ping" => send_line(&mut writer, &serde_json::json!({"t": "pong"})).await?,
+                    "auth" => send_line(&mut writer, &serde_json::json!({"t": "auth", "ok": true})).await?,
+                    other => send_line(&mut writer, &serde_json::json!({"t": "err", "m": format!("Operação desconhecida: {}", other)})).await?,
+                }
+            }
+            _ = feed_check.tick() => {
+                if subscriptions.is_empty() {
+                    feed = None;
+                    continue;
+                }
+                let smart_cache = websocket_state.read().await.as_ref().map(|s| s.smart_cache());
+                match smart_cache {
+                    Some(cache) => {
+                        if feed.as_ref().is_some_and(|f| Arc::ptr_eq(&f.cache, &cache)) {
+                            continue;
+                        }
+                        // Novo receptor do canal + valores atuais de uma vez
+                        let snapshots = cache.subscribe_snapshots();
+                        let cached = cache.snapshot(None);
+                        feed = Some(SnapshotFeed { cache, snapshots });
+                        live = true;
+                        send_changes(&mut writer, &subscriptions, &mut last_values, &cached, status).await?;
+                    }
+                    None => {
+                        // Sem dados ao vivo: sinalizar qualidade ruim uma vez
+                        feed = None;
+                        if live {
+                            live = false;
+                            last_values.clear();
+                            send_line(&mut writer, &serde_json::json!({"t": "q", "q": "bad"})).await?;
+                        }
+                    }
+                }
+            }
+            snapshot = next_snapshot(&mut feed) => {
+                match snapshot {
+                    Some(cached) => {
+                        send_changes(&mut writer, &subscriptions, &mut last_values, &cached, status).await?;
+                    }
+                    None => {
+                        // Servidor WebSocket parado/reiniciado: verificar agora
+                        feed = None;
+                        feed_check.reset_immediately();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Receptor do canal de cópias do SmartCache em uso por um cliente
+struct SnapshotFeed {
+    cache: Arc<SmartCache>,
+    snapshots: watch::Receiver<Arc<Vec<CachedTagValue>>>,
+}
+
+/// Próxima cópia publicada (None = canal fechado); sem receptor nunca completa
+async fn next_snapshot(feed: &mut Option<SnapshotFeed>) -> Option<Arc<Vec<CachedTagValue>>> {
+    let Some(feed) = feed.as_mut() else {
+        return std::future::pending().await;
+    };
+    feed.snapshots.changed().await.ok()?;
+    Some(feed.snapshots.borrow_and_update().clone())
+}
+
+/// Envia só os valores assinados que mudaram desde o último envio a este cliente
+async fn send_changes<W>(
+    writer: &mut W,
+    subscriptions: &BTreeSet<Subscription>,
+    last_values: &mut HashMap<String, String>,
+    cached: &[CachedTagValue],
+    status: &Arc<RwLock<IpcServerStatus>>,
+) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut changed = serde_json::Map::new();
+    for c in cached {
+        for key in subscriptions.iter().filter_map(|s| s.key_for(&c.plc_ip, &c.tag_name)) {
+            if last_values.get(&key) != Some(&c.value) {
+                last_values.insert(key.clone(), c.value.clone());
+                changed.insert(key, serde_json::Value::String(c.value.clone()));
+            }
+        }
+    }
+    if changed.is_empty() {
+        return Ok(());
+    }
+    send_line(writer, &serde_json::json!({
+        "t": "v",
+        "ts": chrono::Utc::now().timestamp_millis(),
+        "d": changed
+    })).await?;
+    status.write().await.updates_sent += 1;
+    Ok(())
+}
@@ -3,12 +3,56 @@ use tauri::Emitter;
 mod tcp_server;
 mod commands;
 mod plc_parser;
+mod plc_write;
 mod database;
 mod websocket_server;
 mod config;
 mod postgres;
+mod historian;
+mod redundancy;
+mod playback;
+mod validation;
+mod units;
+mod notifications;
+mod availability;
+mod backup;
+mod graphql;
+mod rest_api;
+mod csv_logger;
+mod opc_bridge;
+mod health;
+mod data_version;
+mod panels;
+mod packet_rate;
+mod ws_protocol;
+mod frame_generator;
+mod alarm_kpis;
+mod command_audit;
+mod ws_masking;
+mod ws_auth;
+mod historian_failover;
+mod historian_writer;
+mod historian_archive;
+mod simulator;
+mod packet_recorder;
+mod remote_commands;
+mod ipc_server;
+mod impact;
+mod i18n;
+mod self_monitor;
+mod incident_capture;
+mod alarm_engine;
+mod server_status;
+mod mqtt_status;
+mod db_breaker;
+mod parse_quarantine;
+mod autostart;
+mod flatline;
+mod error;
+mod tag_registry;
+pub mod supervisor;
 
-use commands::{TcpServerState, WebSocketServerState};
+use commands::{TcpServerState, WebSocketServerState, WebSocketWorkersState, PlaybackState, GraphqlServerState, RestApiServerState, MqttStatusState, CsvLoggerState, OpcBridgeState, HealthServerState, IpcServerState, HistorianWriterState, SimulatorState, PacketReplayState};
 use database::Database;
 use std::sync::Arc;
 use tauri::Manager;
@@ -31,21 +75,213 @@ pub fn run() {
         )?;
       }
       
+      // Versão do diretório de dados: recusar dados de um HMI mais novo (downgrade)
+      let data_version = data_version::check_before_open(std::path::Path::new(database::DB_PATH))
+        .map_err(|e| {
+          println!("❌ {}", e);
+          e
+        })?;
+      
       // Inicializar banco de dados
-      let db = Database::new(&app.handle())
-        .expect("Falha ao inicializar banco de dados");
-      app.manage(Arc::new(db));
+      let db = Arc::new(Database::new(&app.handle())
+        .expect("Falha ao inicializar banco de dados"));
+      db.apply_data_migrations(data_version)
+        .map_err(|e| {
+          println!("❌ {}", e);
+          e
+        })?;
+      app.manage(db.clone());
+      
+      // 🆕 Início do backend (uptime do SERVER_STATUS / status MQTT)
+      server_status::init(app.state::<TcpServerState>().inner().clone());
+      
+      // Central de notificações (persistir eventos críticos)
+      notifications::start_notification_recorder(app.handle().clone(), db.clone());
+      
+      // 🆕 Eventos de conexão dos PLCs → disponibilidade (uptime, MTBF/MTTR)
+      availability::start_availability_tracker(app.handle().clone(), db.clone());
+      
+      // Reiniciado pelo supervisor? Registrar o motivo
+      if let Ok(reason) = std::env::var(supervisor::RESTART_REASON_ENV) {
+        println!("🔄 HMI reiniciado pelo supervisor: {}", reason);
+        let _ = app.emit("backend-restarted", serde_json::json!({
+          "reason": reason,
+          "restart_count": std::env::var(supervisor::RESTART_COUNT_ENV).ok().and_then(|c| c.parse::<u32>().ok()).unwrap_or(0),
+          "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+      }
+      
+      // Verificar integridade do arquivo de configuração (criptografia em repouso)
+      match config::ConfigManager::new(app.handle()) {
+        Ok(config_manager) => {
+          let security = config_manager.check_security();
+          if !security.integrity_ok {
+            println!("🚨 {}", security.message);
+            let _ = app.emit("config-tampered", &security);
+          }
+          // Identidade da instância + banner de startup
+          match config_manager.load_instance() {
+            Ok(app_config) => {
+              if let Some(language) = app_config.language.as_deref() {
+                if let Err(e) = i18n::set_language(language) {
+                  println!("⚠️ {}", e);
+                }
+              }
+              println!("{}", config::render_banner(app_config.startup_banner.as_deref(), &app_config.instance));
+            }
+            Err(e) => println!("⚠️ Identidade da instância não carregada: {}", e),
+          }
+        }
+        Err(e) => println!("⚠️ Não foi possível verificar a configuração: {}", e),
+      }
+      
+      // Historian com failover entre destinos + replicação de recuperação
+      let historian_failover = Arc::new(historian_failover::HistorianFailover::new(app.handle().clone(), db.clone()));
+      app.manage(historian_failover.clone());
+      historian_failover::start_catchup_task(historian_failover.clone());
+      
+      // Gravação contínua no historian: retomar se estava ativa
+      match db.load_historian_writer_config() {
+        Ok(config) if config.enabled => {
+          let websocket_state = app.state::<WebSocketServerState>().inner().clone();
+          let writer_state = app.state::<HistorianWriterState>().inner().clone();
+          let database = db.clone();
+          tauri::async_runtime::spawn(async move {
+            match historian_writer::HistorianWriter::start(database, websocket_state, historian_failover, config) {
+              Ok(writer) => *writer_state.write().await = Some(writer),
+              Err(e) => println!("⚠️ Gravação do historian não retomada: {}", e),
+            }
+          });
+        }
+        Ok(_) => {}
+        Err(e) => println!("⚠️ Erro ao carregar configuração do historian: {}", e),
+      }
+      
+      // Motor de alarmes: avalia as definições sobre o SmartCache (estado retomado do banco)
+      alarm_engine::start_alarm_engine(
+        app.handle().clone(),
+        db.clone(),
+        app.state::<WebSocketServerState>().inner().clone(),
+      );
+      
+      // 🆕 Sensor congelado: tags analógicos sem variação com o PLC comunicando
+      flatline::start_flatline_monitor(
+        app.handle().clone(),
+        db.clone(),
+        app.state::<WebSocketServerState>().inner().clone(),
+        app.state::<TcpServerState>().inner().clone(),
+      );
+      
+      // 🆕 Limite de erros de parse para quarentena dos PLCs
+      match db.load_parse_quarantine_config() {
+        Ok(config) => parse_quarantine::set_config(config),
+        Err(e) => println!("⚠️ Erro ao carregar configuração da quarentena: {}", e),
+      }
+      
+      // 🆕 Servidores TCP/WebSocket que estavam rodando no último encerramento
+      // (o standby de um par redundante fica parado: quem recebe os PLCs é o primário)
+      match db.load_redundancy_config() {
+        Ok(config) if config.enabled && config.role == redundancy::ROLE_STANDBY => {
+          println!("⏸️ Standby de redundância: servidores TCP/WebSocket não restaurados");
+        }
+        _ => autostart::restore_servers(
+          app.handle().clone(),
+          db.clone(),
+          app.state::<TcpServerState>().inner().clone(),
+          app.state::<WebSocketServerState>().inner().clone(),
+        ),
+      }
+      
+      // Backup automático diário do banco de configuração
+      backup::start_daily_backup(app.handle().clone(), db.clone());
+      
+      // Logger CSV: retomar se estava ativo no último encerramento
+      match db.load_csv_logger_config() {
+        Ok(config) if config.enabled => {
+          let websocket_state = app.state::<WebSocketServerState>().inner().clone();
+          let csv_logger_state = app.state::<CsvLoggerState>().inner().clone();
+          let app_handle = app.handle().clone();
+          tauri::async_runtime::spawn(async move {
+            match csv_logger::CsvLogger::start(app_handle, websocket_state, config) {
+              Ok(logger) => *csv_logger_state.write().await = Some(logger),
+              Err(e) => println!("⚠️ Logger CSV não retomado: {}", e),
+            }
+          });
+        }
+        Ok(_) => {}
+        Err(e) => println!("⚠️ Erro ao carregar configuração do logger CSV: {}", e),
+      }
+      
+      // Health check (/healthz): subir o endpoint se estiver habilitado
+      match db.load_health_config() {
+        Ok(config) if config.enabled => {
+          let context = health::HealthContext {
+            database: db.clone(),
+            tcp_state: app.state::<TcpServerState>().inner().clone(),
+            websocket_state: app.state::<WebSocketServerState>().inner().clone(),
+          };
+          let health_state = app.state::<HealthServerState>().inner().clone();
+          tauri::async_runtime::spawn(async move {
+            match health::HealthServer::start(context, config).await {
+              Ok(server) => *health_state.write().await = Some(server),
+              Err(e) => println!("⚠️ Health check não iniciado: {}", e),
+            }
+          });
+        }
+        Ok(_) => {}
+        Err(e) => println!("⚠️ Erro ao carregar configuração do health check: {}", e),
+      }
+      
+      // Automonitoramento: CPU, memória, disco e tarefas como tags internos
+      self_monitor::start_self_monitor(
+        app.state::<TcpServerState>().inner().clone(),
+        app.state::<WebSocketServerState>().inner().clone(),
+      );
+      
+      // Painéis remotos (plc-app): detectar painéis sem heartbeat
+      panels::start_panel_monitor(app.handle().clone(), db.clone());
+      
+      // Heartbeat de redundância com o par (checksum da configuração + divergência)
+      redundancy::start_heartbeat(
+        app.handle().clone(),
+        db,
+        app.state::<redundancy::RedundancyState>().inner().clone(),
+      );
       
       Ok(())
     })
     .manage(TcpServerState::default())
     .manage(WebSocketServerState::default())
-    .invoke_handler(tauri::generate_handler![
+    .manage(WebSocketWorkersState::default())
+    .manage(PlaybackState::default())
+    .manage(GraphqlServerState::default())
+    .manage(RestApiServerState::default())
+    .manage(MqttStatusState::default())
+    .manage(CsvLoggerState::default())
+    .manage(HistorianWriterState::default())
+    .manage(SimulatorState::default())
+    .manage(PacketReplayState::default())
+    .manage(OpcBridgeState::default())
+    .manage(IpcServerState::default())
+    .manage(HealthServerState::default())
+    .manage(redundancy::RedundancyState::default())
+    .manage(Arc::new(command_audit::CommandRateAuditor::new()))
+    .invoke_handler(command_audit::audited(tauri::generate_handler![
       commands::start_tcp_server,
       commands::stop_tcp_server,
       commands::connect_to_plc,
       commands::disconnect_plc,
+      commands::write_plc_variable,
       commands::allow_plc_reconnect,
+      commands::run_connection_batch,
+      commands::list_audit_log,
+      commands::get_command_audit,
+      commands::get_plc_rate_expectations,
+      commands::save_plc_rate_expectation,
+      commands::delete_plc_rate_expectation,
+      commands::get_plc_rate_status,
+      commands::export_websocket_protocol,
+      commands::generate_test_frames,
       commands::get_connection_stats,
       commands::get_connected_clients,
       commands::get_all_known_plcs,
@@ -57,27 +293,115 @@ pub fn run() {
       commands::test_plc_connection,
       commands::get_latest_plc_data,
       commands::get_plc_variable,
+      commands::get_latest_raw_frame,
+      commands::subscribe_plc_data_channel,
+      commands::unsubscribe_plc_data_channel,
       commands::save_plc_structure,
       commands::load_plc_structure,
       commands::list_configured_plcs,
       commands::delete_plc_structure,
       commands::debug_show_plc_structure,
+      commands::save_plc_frame_profiles,
       commands::save_tag_mapping,
       commands::save_tag_mappings_bulk,
       commands::load_tag_mappings,
       commands::delete_tag_mapping,
       commands::delete_tag_mappings_bulk,
+      commands::rename_tag,
+      commands::clone_plc_config,
+      commands::migrate_plc_identity,
+      commands::list_tag_group_priorities,
+      commands::save_tag_group_priority,
+      commands::delete_tag_group_priority,
+      commands::list_group_interval_overrides,
+      commands::set_group_interval_override,
+      commands::clear_group_interval_override,
+      commands::list_ws_tokens,
+      commands::create_ws_token,
+      commands::revoke_ws_token,
+      commands::list_public_stream_keys,
+      commands::save_public_stream_key,
+      commands::delete_public_stream_key,
+      commands::get_historian_targets,
+      commands::save_historian_targets,
+      commands::get_historian_failover_status,
+      commands::run_historian_catchup,
+      commands::get_historian_writer_config,
+      commands::save_historian_writer_config,
+      commands::start_historian_writer,
+      commands::stop_historian_writer,
+      commands::get_historian_writer_stats,
+      commands::start_plc_simulator,
+      commands::stop_plc_simulator,
+      commands::get_plc_simulator_status,
+      commands::start_packet_recording,
+      commands::stop_packet_recording,
+      commands::list_packet_recordings,
+      commands::replay_packet_file,
+      commands::stop_packet_replay,
+      commands::get_packet_replay_status,
+      commands::set_historian_tag_logging,
+      commands::list_historian_tags,
+      commands::archive_historian_months,
+      commands::list_historian_archives,
+      commands::attach_historian_archive,
+      commands::detach_historian_archive,
+      commands::query_archived_tag_history,
+      commands::query_tag_history,
+      commands::read_tag_at,
+      commands::list_tag_unit_versions,
+      commands::get_health_status,
+      commands::get_health_config,
+      commands::save_health_config,
+      commands::list_panels,
+      commands::get_panel_logs,
+      commands::delete_panel,
       commands::get_active_tags,
       commands::get_plc_variables_for_mapping,
       commands::start_websocket_server,
       commands::stop_websocket_server,
       commands::get_websocket_stats,
+      commands::start_websocket_workers,
+      commands::stop_websocket_workers,
+      commands::get_websocket_workers,
+      commands::get_websocket_suppressed_events,
+      commands::get_websocket_db_breaker_status,
+      commands::get_ws_session_config,
+      commands::save_ws_session_config,
+      commands::get_remote_tunnel_config,
+      commands::save_remote_tunnel_config,
+      commands::list_all_tags,
+      commands::find_duplicate_tag_names,
+      commands::resolve_tag,
+      commands::list_plc_aliases,
+      commands::save_plc_alias,
+      commands::delete_plc_alias,
+      commands::get_parse_quarantine_config,
+      commands::save_parse_quarantine_config,
+      commands::get_plc_parse_stats,
+      commands::release_plc_quarantine,
+      commands::get_runtime_state,
+      commands::save_autostart_config,
+      commands::get_flatline_config,
+      commands::save_flatline_config,
+      commands::get_flatlined_tags,
+      commands::list_pending_remote_commands,
+      commands::approve_remote_command,
+      commands::deny_remote_command,
+      commands::query_ws_client_sessions,
       commands::get_websocket_clients,
       commands::update_websocket_config,
       commands::get_websocket_config,
       commands::check_first_run,
       commands::save_initial_config,
       commands::get_app_config,
+      commands::get_instance_identity,
+      commands::set_instance_identity,
+      commands::get_backend_language,
+      commands::set_backend_language,
+      commands::get_message_catalog,
+      commands::get_config_security_status,
+      commands::set_config_encryption,
       commands::get_default_db_path,
       commands::validate_db_path,
       commands::get_network_interfaces,
@@ -95,12 +419,78 @@ pub fn run() {
       commands::get_scl_tags,
       commands::get_system_memory_stats,
       commands::get_memory_health_report,
+      commands::get_resource_usage,
       commands::force_memory_cleanup,
       commands::subscribe_client_to_plcs,
       commands::get_available_plcs,
       commands::write_file,
       commands::read_file,
-    ])
+      commands::validate_configuration,
+      commands::get_mapping_coverage,
+      commands::get_tag_dependency_graph,
+      commands::analyze_tag_delete_impact,
+      commands::analyze_block_delete_impact,
+      commands::list_unit_conversions,
+      commands::get_formatted_tag,
+      commands::compare_snapshots,
+      commands::detect_history_gaps,
+      commands::get_waveform_history,
+      commands::get_live_waveform,
+      commands::start_playback,
+      commands::playback_play,
+      commands::playback_pause,
+      commands::playback_seek,
+      commands::playback_set_speed,
+      commands::get_playback_status,
+      commands::stop_playback,
+      commands::list_notifications,
+      commands::count_unread_notifications,
+      commands::mark_notifications_read,
+      commands::clear_notifications,
+      commands::get_alarm_kpis,
+      commands::get_plc_availability,
+      commands::list_plc_connection_events,
+      commands::list_alarm_definitions,
+      commands::save_alarm_definition,
+      commands::delete_alarm_definition,
+      commands::get_active_alarms,
+      commands::ack_alarm,
+      commands::get_alarm_history,
+      commands::list_incidents,
+      commands::get_incident,
+      commands::list_config_backups,
+      commands::create_config_backup,
+      commands::verify_config_backup,
+      commands::get_backup_config,
+      commands::save_backup_config,
+      commands::restore_config_backup,
+      commands::get_config_checksum,
+      commands::report_peer_heartbeat,
+      commands::get_redundancy_config,
+      commands::save_redundancy_config,
+      commands::get_redundancy_status,
+      commands::start_graphql_server,
+      commands::stop_graphql_server,
+      commands::get_graphql_status,
+      commands::start_rest_api,
+      commands::stop_rest_api,
+      commands::get_rest_api_status,
+      commands::get_server_status,
+      commands::start_mqtt_status,
+      commands::stop_mqtt_status,
+      commands::get_mqtt_status_info,
+      commands::get_csv_logger_config,
+      commands::save_csv_logger_config,
+      commands::start_csv_logger,
+      commands::stop_csv_logger,
+      commands::get_csv_logger_status,
+      commands::start_opc_bridge,
+      commands::stop_opc_bridge,
+      commands::get_opc_bridge_status,
+      commands::start_ipc_server,
+      commands::stop_ipc_server,
+      commands::get_ipc_server_status,
+    ]))
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+  // Modo supervisor: processo pai que reinicia o HMI (ver supervisor.rs)
+  if std::env::args().any(|arg| arg == "--supervise") {
+    std::process::exit(app_lib::supervisor::run());
+  }
   app_lib::run();
 }
@@ -0,0 +1,196 @@
+// ============================================================================
+// STATUS DO SERVIDOR NO MQTT (feature "mqtt")
+// ============================================================================
+//
+// Publica o status do servidor (server_status.rs) em um tópico retido:
+//   - "online" ao conectar no broker e a cada STATUS_INTERVAL_SECS;
+//   - "offline" ao parar o publicador (desligamento limpo);
+//   - last will "offline" registrado no CONNECT: o broker publica sozinho se o
+//     HMI cair sem desconectar.
+// Assim um assinante diferencia "sem dados porque o processo está parado" de
+// "HMI fora do ar" lendo o último valor retido.
+// 🆕 Uplink de suporte remoto (remote_commands = true): assina
+// "<tópico>/remote/request" e cada pedido (RemoteCommandRequest + "request_id")
+// passa pela aprovação do operador local (remote_commands.rs) antes de
+// executar. O desfecho sai em "<tópico>/remote/result" (não retido).
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+pub const DEFAULT_STATUS_TOPIC: &str = "plc-hmi/status";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+pub struct MqttStatusConfig {
+    pub broker_host: String,
+    #[serde(default = "default_port")]
+    pub broker_port: u16,
+    #[serde(default = "default_topic")]
+    pub topic: String,
+    #[serde(default)]
+    pub client_id: Option<String>, // Padrão: plc-hmi-<uuid da instância>
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub remote_commands: bool,     // 🆕 Recebe comandos do suporte remoto (com aprovação local)
+}
+
+/// Contexto para executar comandos remotos recebidos pelo broker
+#[derive(Clone)]
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+pub struct RemoteCommandContext {
+    pub app_handle: tauri::AppHandle,
+    pub database: std::sync::Arc<crate::database::Database>,
+    pub tcp_state: crate::commands::TcpServerState,
+}
+
+/// Pedido recebido em "<tópico>/remote/request"
+#[derive(Debug, Deserialize)]
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+struct RemoteRequestMessage {
+    #[serde(default)]
+    request_id: Option<String>,    // Devolvido no resultado para o remoto correlacionar
+    #[serde(flatten)]
+    request: crate::remote_commands::RemoteCommandRequest,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_MQTT_PORT
+}
+
+fn default_topic() -> String {
+    DEFAULT_STATUS_TOPIC.to_string()
+}
+
+/// Publicador em execução
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+pub struct MqttStatusPublisher {
+    pub broker: String,
+    pub topic: String,
+    #[cfg(feature = "mqtt")]
+    client: rumqttc::AsyncClient,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl MqttStatusPublisher {
+    #[cfg(feature = "mqtt")]
+    pub async fn start(config: MqttStatusConfig, remote: RemoteCommandContext) -> Result<Self, String> {
+        use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+        use std::time::Duration;
+
+        if config.broker_host.trim().is_empty() {
+            return Err("Broker MQTT não informado".to_string());
+        }
+        if config.topic.trim().is_empty() || config.topic.contains(['#', '+']) {
+            return Err(format!("Tópico MQTT inválido: '{}'", config.topic));
+        }
+
+        let instance = crate::config::current_instance();
+        let client_id = config.client_id.clone()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| format!("plc-hmi-{}", if instance.uuid.is_empty() { "local" } else { instance.uuid.as_str() }));
+        let mut options = MqttOptions::new(client_id, config.broker_host.clone(), config.broker_port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let will = serde_json::to_vec(&crate::server_status::offline().await)
+            .map_err(|e| format!("Erro ao serializar last will: {}", e))?;
+        options.set_last_will(LastWill::new(config.topic.clone(), will, QoS::AtLeastOnce, true));
+        if let Some(username) = config.username.clone().filter(|u| !u.is_empty()) {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options, 10);
+        let broker = format!("{}:{}", config.broker_host, config.broker_port);
+        let topic = config.topic.clone();
+
+        let publisher = client.clone();
+        let task_topic = topic.clone();
+        let task_broker = broker.clone();
+        let request_topic = format!("{}/remote/request", topic);
+        let remote_commands = config.remote_commands;
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(crate::server_status::STATUS_INTERVAL_SECS));
+            loop {
+                tokio::select! {
+                    event = eventloop.poll() => match event {
+                        // (Re)conectado: o last will foi registrado, publicar "online"
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            println!("📡 MQTT: conectado em {} (status em '{}')", task_broker, task_topic);
+                            publish_status(&publisher, &task_topic, &crate::server_status::current().await).await;
+                            if remote_commands {
+                                if let Err(e) = publisher.subscribe(request_topic.as_str(), QoS::AtLeastOnce).await {
+                                    println!("⚠️ MQTT: erro ao assinar '{}': {}", request_topic, e);
+                                }
+                            }
+                        }
+                        // 🛰️ Pedido do suporte remoto: espera a aprovação fora do event loop
+                        Ok(Event::Incoming(Packet::Publish(message))) if remote_commands && message.topic == request_topic => {
+                            tokio::spawn(handle_remote_request(publisher.clone(), task_topic.clone(), remote.clone(), message.payload.to_vec()));
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            // poll() reconecta na próxima chamada
+                            println!("⚠️ MQTT: conexão com {} falhou: {}", task_broker, e);
+                            tokio::time::sleep(Duration::from_secs(5)).await;
+                        }
+                    },
+                    _ = interval.tick() => {
+                        publish_status(&publisher, &task_topic, &crate::server_status::current().await).await;
+                    }
+                }
+            }
+        });
+
+        println!("📡 Status MQTT iniciado: {} -> '{}'", broker, topic);
+        Ok(Self { broker, topic, client, handle })
+    }
+
+    #[cfg(not(feature = "mqtt"))]
+    pub async fn start(_config: MqttStatusConfig, _remote: RemoteCommandContext) -> Result<Self, String> {
+        Err("Aplicação compilada sem suporte a MQTT (habilite a feature \"mqtt\")".to_string())
+    }
+
+    /// Publica "offline" retido e desconecta (o broker não dispara o last will)
+    pub async fn stop(self) {
+        #[cfg(feature = "mqtt")]
+        {
+            publish_status(&self.client, &self.topic, &crate::server_status::offline().await).await;
+            let _ = self.client.disconnect().await;
+            // Dar tempo ao event loop de enviar PUBLISH + DISCONNECT
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        self.handle.abort();
+        println!("🛑 Status MQTT parado ({})", self.broker);
+    }
+}
+
+#[cfg(feature = "mqtt")]
+async fn publish_status(client: &rumqttc::AsyncClient, topic: &str, status: &crate::server_status::ServerStatus) {
+    let Ok(payload) = serde_json::to_vec(status) else { return };
+    if let Err(e) = client.publish(topic, rumqttc::QoS::AtLeastOnce, true, payload).await {
+        println!("⚠️ MQTT: erro ao publicar status: {}", e);
+    }
+}
+
+/// Executa um pedido de "<tópico>/remote/request" e publica o desfecho em "<tópico>/remote/result"
+#[cfg(feature = "mqtt")]
+async fn handle_remote_request(client: rumqttc::AsyncClient, topic: String, remote: RemoteCommandContext, payload: Vec<u8>) {
+    let result = match serde_json::from_slice::<RemoteRequestMessage>(&payload) {
+        Ok(message) => {
+            let outcome = crate::remote_commands::execute_write(&remote.app_handle, &remote.database, &remote.tcp_state, "mqtt", "mqtt", message.request).await;
+            match outcome {
+                Ok(write) => serde_json::json!({ "request_id": message.request_id, "status": "executed", "result": write }),
+                Err(e) => serde_json::json!({ "request_id": message.request_id, "status": "error", "message": e }),
+            }
+        }
+        Err(e) => {
+            println!("⚠️ MQTT: pedido remoto inválido: {}", e);
+            serde_json::json!({ "request_id": null, "status": "error", "message": format!("JSON inválido: {}", e) })
+        }
+    };
+    let Ok(payload) = serde_json::to_vec(&result) else { return };
+    if let Err(e) = client.publish(format!("{}/remote/result", topic), rumqttc::QoS::AtLeastOnce, false, payload).await {
+        println!("⚠️ MQTT: erro ao publicar resultado do comando remoto: {}", e);
+    }
+}
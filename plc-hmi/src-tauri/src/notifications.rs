@@ -0,0 +1,200 @@
+use crate::database::Database;
+use crate::i18n::{msg, LocalizedMessage};
+use serde_json::Value;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Listener};
+
+// ============================================================================
+// CENTRAL DE NOTIFICAÇÕES - PERSISTE EVENTOS CRÍTICOS DO BACKEND
+// ============================================================================
+//
+// Os eventos do backend são fire-and-forget: se a UI estiver fechada o operador
+// nunca fica sabendo. Aqui cada evento relevante vira uma linha na tabela
+// `notifications`, e a UI é avisada com `notification-added`.
+
+/// Eventos persistidos e a severidade de cada um
+const RECORDED_EVENTS: &[(&str, &str)] = &[
+    ("tcp-connection-dead", "critical"),
+    ("tcp-connection-timeout", "warning"),
+    ("tcp-connection-error", "warning"),
+    ("plc-disconnected", "warning"),
+    ("sqlite-error", "critical"),
+    ("config-drift-detected", "critical"),
+    ("config-backup-failed", "warning"),
+    ("csv-logger-error", "warning"),
+    ("opc-bridge-stopped", "warning"),
+    ("config-tampered", "critical"),
+    ("backend-restarted", "warning"),
+    ("panel-offline", "warning"),
+    ("panel-error", "warning"),
+    ("plc-rate-deviation", "warning"),
+    ("historian-backfill-error", "warning"),
+    ("security-anomaly", "critical"),
+    ("historian-failover", "warning"),
+    ("tag-flatlined", "info"),
+    ("plc-quarantined", "critical"),
+    ("redundancy-peer-lost", "critical"),
+];
+
+fn str_field<'a>(payload: &'a Value, key: &str) -> &'a str {
+    payload.get(key).and_then(|v| v.as_str()).unwrap_or("?")
+}
+
+fn u64_field(payload: &Value, key: &str) -> String {
+    payload.get(key).and_then(|v| v.as_u64()).unwrap_or(0).to_string()
+}
+
+/// Texto livre vindo do payload (mensagens de erro do sistema não são traduzidas)
+fn raw(text: &str) -> LocalizedMessage {
+    msg("common.raw", &[("message", text.to_string())])
+}
+
+/// Monta título e corpo (chave + texto no idioma atual) a partir do payload do evento
+fn describe_event(event: &str, payload: &Value) -> (LocalizedMessage, LocalizedMessage) {
+    let field = |key: &str| str_field(payload, key).to_string();
+    match event {
+        "tcp-connection-dead" => (
+            msg("notification.tcp_connection_dead.title", &[("ip", field("ip"))]),
+            msg("notification.tcp_connection_dead.body", &[("seconds", u64_field(payload, "seconds_since_data"))]),
+        ),
+        "tcp-connection-timeout" => (
+            msg("notification.tcp_connection_timeout.title", &[("ip", field("ip"))]),
+            raw(str_field(payload, "reason")),
+        ),
+        "tcp-connection-error" => (
+            msg("notification.tcp_connection_error.title", &[("ip", field("ip"))]),
+            raw(str_field(payload, "error")),
+        ),
+        "plc-disconnected" => (
+            msg("notification.plc_disconnected.title", &[("ip", field("ip"))]),
+            msg("notification.plc_disconnected.body", &[]),
+        ),
+        "sqlite-error" => (
+            msg("notification.sqlite_error.title", &[]),
+            raw(str_field(payload, "message")),
+        ),
+        "config-drift-detected" => (
+            msg("notification.config_drift.title", &[("peer", field("peer_id"))]),
+            msg("notification.config_drift.body", &[("local", field("local_checksum")), ("remote", field("peer_checksum"))]),
+        ),
+        "config-backup-failed" => (
+            msg("notification.config_backup_failed.title", &[]),
+            raw(str_field(payload, "message")),
+        ),
+        "csv-logger-error" => (
+            msg("notification.csv_logger_error.title", &[]),
+            raw(str_field(payload, "message")),
+        ),
+        "opc-bridge-stopped" => (
+            msg("notification.opc_bridge_stopped.title", &[("prog_id", field("prog_id"))]),
+            raw(str_field(payload, "message")),
+        ),
+        "config-tampered" => (
+            msg("notification.config_tampered.title", &[]),
+            raw(str_field(payload, "message")),
+        ),
+        "backend-restarted" => (
+            msg("notification.backend_restarted.title", &[("count", u64_field(payload, "restart_count"))]),
+            raw(str_field(payload, "reason")),
+        ),
+        "panel-offline" => (
+            msg("notification.panel_offline.title", &[("panel", field("panel_id"))]),
+            msg("notification.panel_offline.body", &[("hostname", field("hostname")), ("address", field("address"))]),
+        ),
+        "panel-error" => (
+            msg("notification.panel_error.title", &[("panel", field("panel_id")), ("count", u64_field(payload, "error_count"))]),
+            raw(str_field(payload, "message")),
+        ),
+        "plc-rate-deviation" => {
+            let expected = u64_field(payload, "expected_interval_ms");
+            match payload.get("actual_interval_ms").and_then(|v| v.as_f64()) {
+                Some(actual) => (
+                    msg("notification.plc_rate_deviation.title", &[("ip", field("plc_ip")), ("level", field("level"))]),
+                    msg("notification.plc_rate_deviation.body", &[
+                        ("actual", format!("{:.0}", actual)),
+                        ("expected", expected),
+                        ("deviation", format!("{:+.0}", payload.get("deviation_pct").and_then(|v| v.as_f64()).unwrap_or(0.0))),
+                    ]),
+                ),
+                None => (
+                    msg("notification.plc_rate_stopped.title", &[("ip", field("plc_ip"))]),
+                    msg("notification.plc_rate_stopped.body", &[("window", u64_field(payload, "window_s")), ("expected", expected)]),
+                ),
+            }
+        }
+        "historian-backfill-error" => (
+            msg("notification.historian_backfill_error.title", &[("ip", field("ip"))]),
+            raw(str_field(payload, "message")),
+        ),
+        "security-anomaly" => (
+            msg("notification.security_anomaly.title", &[("category", field("category")), ("session", field("session"))]),
+            msg("notification.security_anomaly.body", &[
+                ("count", u64_field(payload, "count")),
+                ("window", u64_field(payload, "window_s")),
+                ("limit", u64_field(payload, "limit")),
+            ]),
+        ),
+        "historian-failover" => (
+            msg("notification.historian_failover.title", &[("to", field("to"))]),
+            match payload.get("reason").and_then(|v| v.as_str()) {
+                Some(reason) => msg("notification.historian_failover.body_reason", &[("from", field("from")), ("reason", reason.to_string())]),
+                None => msg("notification.historian_failover.body", &[("from", field("from"))]),
+            },
+        ),
+        "tag-flatlined" => (
+            msg("notification.tag_flatlined.title", &[("tag", field("tag_name")), ("ip", field("plc_ip"))]),
+            msg("notification.tag_flatlined.body", &[("value", field("value")), ("seconds", u64_field(payload, "duration_s"))]),
+        ),
+        "plc-quarantined" => (
+            msg("notification.plc_quarantined.title", &[("ip", field("plc_ip"))]),
+            raw(str_field(payload, "reason")),
+        ),
+        "redundancy-peer-lost" => (
+            msg("notification.redundancy_peer_lost.title", &[("peer", field("peer_address"))]),
+            msg("notification.redundancy_peer_lost.body", &[("seconds", u64_field(payload, "seconds"))]),
+        ),
+        _ => (msg("common.unknown_event", &[("event", event.to_string())]), raw(&payload.to_string())),
+    }
+}
+
+/// Registra listeners para os eventos críticos e grava cada ocorrência no SQLite
+pub fn start_notification_recorder(app_handle: AppHandle, database: Arc<Database>) {
+    for (event_name, severity) in RECORDED_EVENTS {
+        let database = database.clone();
+        let emitter = app_handle.clone();
+
+        app_handle.listen(*event_name, move |event| {
+            let payload: Value = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+            let (title, body) = describe_event(event_name, &payload);
+
+            // Gravado no idioma atual; o evento leva as chaves para a UI traduzir
+            let notification_id = match database.add_notification(severity, &title.text, &body.text, Some(event_name)) {
+                Ok(id) => {
+                    let _ = emitter.emit("notification-added", serde_json::json!({
+                        "id": id,
+                        "severity": severity,
+                        "title": title.text,
+                        "body": body.text,
+                        "title_key": title.key,
+                        "body_key": body.key,
+                        "title_args": title.args,
+                        "body_args": body.args,
+                        "source_event": event_name,
+                        "created_at": chrono::Utc::now().timestamp()
+                    }));
+                    Some(id)
+                }
+                // Não emitir sqlite-error aqui para não gerar loop de notificações
+                Err(e) => {
+                    println!("⚠️ Notificações: erro ao gravar '{}': {}", title.text, e);
+                    None
+                }
+            };
+            
+            // 🆕 Queda de conexão / alarme crítico: congelar logs e frames recentes
+            crate::incident_capture::capture_for_event(&emitter, database.clone(), event_name, severity, &payload, notification_id);
+        });
+    }
+
+    println!("🔔 Central de notificações ativa ({} eventos monitorados)", RECORDED_EVENTS.len());
+}
@@ -0,0 +1,205 @@
+use crate::commands::WebSocketServerState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::RwLock;
+
+// ============================================================================
+// PONTE OPC DA (WINDOWS) - EXPÕE OS TAGS PARA HISTORIANS LEGADOS
+// ============================================================================
+//
+// OPC DA é COM/DCOM: o servidor precisa ser um executável registrado no Windows,
+// construído com um SDK OPC (ou um shim COM). Este módulo não implementa COM:
+// ele inicia esse executável e o alimenta pelo stdin com JSON por linha.
+//
+// Protocolo (uma mensagem JSON por linha, HMI → ponte):
+//   {"type":"define","prog_id":"PlcHmi.OpcDa.1","items":[{"item_id":"PLC_192_168_1_10.nivel","data_type":"REAL"}]}
+//   {"type":"update","timestamp_ms":1700000000000,"values":[{"item_id":"...","value":"12.5","quality":"good"}]}
+//   {"type":"quality","quality":"bad"}   // Dados ao vivo indisponíveis (WebSocket parado)
+// Cada linha escrita pela ponte no stdout é repassada ao log do HMI.
+
+const DEFAULT_UPDATE_INTERVAL_MS: u64 = 500;
+const MIN_UPDATE_INTERVAL_MS: u64 = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpcBridgeConfig {
+    pub executable_path: String,    // Executável da ponte (SDK OPC / shim COM)
+    pub prog_id: String,            // ProgID registrado, ex: "PlcHmi.OpcDa.1"
+    pub update_interval_ms: Option<u64>,
+    pub plc_ips: Option<Vec<String>>, // PLCs expostos (None = todos)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpcBridgeStatus {
+    pub running: bool,
+    pub prog_id: String,
+    pub item_count: usize,
+    pub updates_sent: u64,
+    pub last_error: Option<String>,
+}
+
+/// ItemID OPC: pontos do IP viram "_" para não criar níveis extras no browse
+pub fn opc_item_id(plc_ip: &str, tag_name: &str) -> String {
+    format!("PLC_{}.{}", plc_ip.replace('.', "_"), tag_name)
+}
+
+pub struct OpcBridge {
+    status: Arc<RwLock<OpcBridgeStatus>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl OpcBridge {
+    pub async fn start(app_handle: AppHandle, websocket_state: WebSocketServerState, config: OpcBridgeConfig) -> Result<Self, String> {
+        if !cfg!(windows) {
+            return Err("Ponte OPC DA só está disponível no Windows".to_string());
+        }
+        if config.prog_id.trim().is_empty() {
+            return Err("ProgID da ponte OPC DA não informado".to_string());
+        }
+        let interval_ms = config.update_interval_ms.unwrap_or(DEFAULT_UPDATE_INTERVAL_MS).max(MIN_UPDATE_INTERVAL_MS);
+
+        let mut child = Command::new(&config.executable_path)
+            .arg("--prog-id")
+            .arg(&config.prog_id)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Erro ao iniciar ponte OPC DA '{}': {}", config.executable_path, e))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| "stdin da ponte OPC DA indisponível".to_string())?;
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stdout).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    println!("🔌 [OPC DA] {}", line);
+                }
+            });
+        }
+
+        let status = Arc::new(RwLock::new(OpcBridgeStatus {
+            running: true,
+            prog_id: config.prog_id.clone(),
+            ..Default::default()
+        }));
+
+        println!("🚀 Ponte OPC DA iniciada: {} ({})", config.prog_id, config.executable_path);
+        let handle = tokio::spawn(run_bridge(app_handle, websocket_state, config, interval_ms, child, stdin, status.clone()));
+        Ok(Self { status, handle })
+    }
+
+    pub async fn status(&self) -> OpcBridgeStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Encerra a ponte (o processo filho é finalizado junto com a task)
+    pub fn stop(self) {
+        self.handle.abort();
+        println!("🛑 Ponte OPC DA parada");
+    }
+}
+
+async fn send_line(stdin: &mut ChildStdin, message: &serde_json::Value) -> std::io::Result<()> {
+    stdin.write_all(format!("{}\n", message).as_bytes()).await?;
+    stdin.flush().await
+}
+
+async fn run_bridge(
+    app_handle: AppHandle,
+    websocket_state: WebSocketServerState,
+    config: OpcBridgeConfig,
+    interval_ms: u64,
+    mut child: Child,
+    mut stdin: ChildStdin,
+    status: Arc<RwLock<OpcBridgeStatus>>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+    let mut defined_items: HashMap<String, String> = HashMap::new(); // item_id → data_type
+    let mut last_values: HashMap<String, String> = HashMap::new();
+    let mut live = false;
+
+    let error = loop {
+        tokio::select! {
+            exit = child.wait() => {
+                break format!("Processo da ponte encerrou: {:?}", exit);
+            }
+            _ = interval.tick() => {}
+        }
+
+        let smart_cache = websocket_state.read().await.as_ref().map(|s| s.smart_cache());
+        let cached: Vec<_> = match &smart_cache {
+            Some(cache) => cache.snapshot(None).into_iter()
+                .filter(|c| config.plc_ips.as_ref().map(|ips| ips.contains(&c.plc_ip)).unwrap_or(true))
+                .collect(),
+            None => {
+                // Sem dados ao vivo: sinalizar qualidade ruim uma vez
+                if live {
+                    live = false;
+                    last_values.clear();
+                    if let Err(e) = send_line(&mut stdin, &serde_json::json!({"type": "quality", "quality": "bad"})).await {
+                        break format!("Erro ao escrever na ponte: {}", e);
+                    }
+                }
+                continue;
+            }
+        };
+        live = true;
+
+        // Novos itens: (re)enviar a definição completa do address space
+        let new_items = cached.iter().any(|c| !defined_items.contains_key(&opc_item_id(&c.plc_ip, &c.tag_name)));
+        if new_items {
+            for c in &cached {
+                defined_items.insert(opc_item_id(&c.plc_ip, &c.tag_name), c.data_type.clone());
+            }
+            let items: Vec<serde_json::Value> = defined_items.iter()
+                .map(|(item_id, data_type)| serde_json::json!({"item_id": item_id, "data_type": data_type}))
+                .collect();
+            let define = serde_json::json!({"type": "define", "prog_id": config.prog_id, "items": items});
+            if let Err(e) = send_line(&mut stdin, &define).await {
+                break format!("Erro ao escrever na ponte: {}", e);
+            }
+            status.write().await.item_count = defined_items.len();
+        }
+
+        let changed: Vec<serde_json::Value> = cached.iter()
+            .filter_map(|c| {
+                let item_id = opc_item_id(&c.plc_ip, &c.tag_name);
+                if last_values.get(&item_id) == Some(&c.value) {
+                    return None;
+                }
+                last_values.insert(item_id.clone(), c.value.clone());
+                Some(serde_json::json!({"item_id": item_id, "value": c.value, "quality": "good"}))
+            })
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+
+        let update = serde_json::json!({
+            "type": "update",
+            "timestamp_ms": chrono::Utc::now().timestamp_millis(),
+            "values": changed
+        });
+        if let Err(e) = send_line(&mut stdin, &update).await {
+            break format!("Erro ao escrever na ponte: {}", e);
+        }
+        status.write().await.updates_sent += 1;
+    };
+
+    println!("❌ Ponte OPC DA: {}", error);
+    {
+        let mut status = status.write().await;
+        status.running = false;
+        status.last_error = Some(error.clone());
+    }
+    let _ = app_handle.emit("opc-bridge-stopped", serde_json::json!({
+        "prog_id": config.prog_id,
+        "message": error,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+}
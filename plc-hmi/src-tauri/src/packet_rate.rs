@@ -0,0 +1,128 @@
+use crate::database::PlcRateExpectation;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// TAXA DE PACOTES ESPERADA POR PLC
+// ============================================================================
+//
+// O watchdog só percebe um PLC depois de INACTIVITY_TIMEOUT_SECS sem dados.
+// Com um intervalo esperado declarado (ex: 500 ms), a taxa real é medida numa
+// janela deslizante e um desvio vira aviso/alarme antes disso, separando
+// "PLC lento" (ainda envia, mas fora do ritmo) de "PLC parado" (nada na janela).
+
+const MIN_WINDOW: Duration = Duration::from_secs(10);
+const WINDOW_INTERVALS: u32 = 5; // Janela cobre pelo menos 5 intervalos esperados
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLevel {
+    Ok,
+    Warning, // Desvio acima de warning_deviation_pct
+    Alarm,   // Desvio acima de alarm_deviation_pct
+    Stalled, // Nenhum pacote na janela: provável PLC parado
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlcRateStatus {
+    pub plc_ip: String,
+    pub expected_interval_ms: u64,
+    pub actual_interval_ms: Option<f64>, // None = sem pacotes na janela
+    pub deviation_pct: Option<f64>,      // Positivo = mais lento que o esperado
+    pub level: RateLevel,
+    pub window_s: u64,
+}
+
+struct RateSamples {
+    samples: VecDeque<(Instant, u64)>, // (instante, packet_count acumulado da conexão)
+    level: RateLevel,
+}
+
+#[derive(Default)]
+pub struct PacketRateMonitor {
+    expectations: DashMap<String, PlcRateExpectation>,
+    samples: DashMap<String, RateSamples>,
+    status: DashMap<String, PlcRateStatus>,
+}
+
+impl PacketRateMonitor {
+    /// Substitui as expectativas (somente as habilitadas) e descarta medições de PLCs removidos
+    pub fn set_expectations(&self, expectations: Vec<PlcRateExpectation>) {
+        self.expectations.clear();
+        for expectation in expectations.into_iter().filter(|e| e.enabled && e.expected_interval_ms > 0) {
+            self.expectations.insert(expectation.plc_ip.clone(), expectation);
+        }
+        self.samples.retain(|ip, _| self.expectations.contains_key(ip));
+        self.status.retain(|ip, _| self.expectations.contains_key(ip));
+        println!("⏱️ Taxa esperada configurada para {} PLC(s)", self.expectations.len());
+    }
+
+    /// Registra o contador de pacotes da conexão; retorna o status quando o nível muda
+    pub fn observe(&self, ip: &str, packet_count: u64, now: Instant) -> Option<PlcRateStatus> {
+        let expectation = self.expectations.get(ip)?.clone();
+        let expected = Duration::from_millis(expectation.expected_interval_ms);
+        let window = (expected * WINDOW_INTERVALS).max(MIN_WINDOW);
+
+        let mut entry = self.samples.entry(ip.to_string()).or_insert_with(|| RateSamples {
+            samples: VecDeque::new(),
+            level: RateLevel::Ok,
+        });
+        // Contador menor = reconexão (packet_count é por conexão)
+        if entry.samples.back().is_some_and(|(_, count)| *count > packet_count) {
+            entry.samples.clear();
+        }
+        entry.samples.push_back((now, packet_count));
+        while entry.samples.len() > 2 && entry.samples.get(1).is_some_and(|(t, _)| now.duration_since(*t) >= window) {
+            entry.samples.pop_front();
+        }
+
+        // Só avalia com a janela completa (evita alarme logo após conectar)
+        let (first_time, first_count) = *entry.samples.front()?;
+        let elapsed = now.duration_since(first_time);
+        if elapsed < window {
+            return None;
+        }
+
+        let packets = packet_count - first_count;
+        let actual_interval_ms = (packets > 0).then(|| elapsed.as_secs_f64() * 1000.0 / packets as f64);
+        let deviation_pct = actual_interval_ms
+            .map(|actual| (actual - expectation.expected_interval_ms as f64) / expectation.expected_interval_ms as f64 * 100.0);
+        let level = match deviation_pct {
+            None => RateLevel::Stalled,
+            Some(d) if d.abs() >= expectation.alarm_deviation_pct => RateLevel::Alarm,
+            Some(d) if d.abs() >= expectation.warning_deviation_pct => RateLevel::Warning,
+            Some(_) => RateLevel::Ok,
+        };
+
+        let status = PlcRateStatus {
+            plc_ip: ip.to_string(),
+            expected_interval_ms: expectation.expected_interval_ms,
+            actual_interval_ms,
+            deviation_pct,
+            level,
+            window_s: window.as_secs(),
+        };
+        self.status.insert(ip.to_string(), status.clone());
+
+        if level != entry.level {
+            entry.level = level;
+            Some(status)
+        } else {
+            None
+        }
+    }
+
+    /// Descarta medições de conexões encerradas: a medição recomeça na próxima conexão
+    pub fn retain_connected(&self, is_connected: impl Fn(&str) -> bool) {
+        self.samples.retain(|ip, _| is_connected(ip));
+        self.status.retain(|ip, _| is_connected(ip));
+    }
+
+    pub fn get_status(&self) -> Vec<PlcRateStatus> {
+        let mut status: Vec<PlcRateStatus> = self.status.iter().map(|e| e.value().clone()).collect();
+        status.sort_by(|a, b| a.plc_ip.cmp(&b.plc_ip));
+        status
+    }
+}
@@ -0,0 +1,293 @@
+use crate::commands::TcpServerState;
+use crate::database::{Database, PlcStructureConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+// ============================================================================
+// GRAVAÇÃO E REPRODUÇÃO DE PACOTES BRUTOS DO PLC
+// ============================================================================
+//
+// Para depurar em bancada um problema visto em campo: a gravação guarda cada
+// frame completo do PLC (o mesmo que vai para o parser) com o instante de
+// chegada; a reprodução injeta os frames de volta no pipeline normal
+// (TcpServer::inject_frame), respeitando os intervalos originais divididos por
+// `speed`. Formato do arquivo (.plcrec):
+//   "PLCREC1\n" + cabeçalho JSON em uma linha (PLC, início e estrutura vigente)
+//   + registros [ts_us: i64 BE][tamanho: u32 BE][bytes]
+// Nomes relativos ficam em `<pasta do banco>/recordings/`.
+
+const FILE_MAGIC: &[u8] = b"PLCREC1\n";
+const RECORDINGS_DIR: &str = "recordings";
+const MAX_RECORD_BYTES: usize = 16 * 1024 * 1024; // Frames de backfill podem ser grandes
+pub const MIN_SPEED: f64 = 0.1;
+pub const MAX_SPEED: f64 = 100.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub plc_ip: String,
+    pub started_at_ms: i64,
+    pub structure: Option<PlcStructureConfig>, // Estrutura vigente na gravação (reprodução offline)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub plc_ip: String,
+    pub path: String,
+    pub started_at_ms: i64,
+    pub frames: u64,
+    pub bytes: u64,
+    pub last_error: Option<String>,
+}
+
+struct Recording {
+    writer: BufWriter<fs::File>,
+    info: RecordingInfo,
+}
+
+static RECORDINGS: Mutex<Option<HashMap<String, Recording>>> = Mutex::new(None);
+
+/// Caminho absoluto como veio; nome simples vai para a pasta de gravações
+pub fn resolve_path(db: &Database, file: &str) -> PathBuf {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    db.db_path()
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default()
+        .join(RECORDINGS_DIR)
+        .join(path)
+}
+
+pub fn start_recording(path: &Path, plc_ip: &str, structure: Option<PlcStructureConfig>) -> Result<RecordingInfo, String> {
+    let mut recordings = RECORDINGS.lock().unwrap();
+    let recordings = recordings.get_or_insert_with(HashMap::new);
+    if recordings.contains_key(plc_ip) {
+        return Err(format!("PLC {} já está sendo gravado", plc_ip));
+    }
+    if path.exists() {
+        return Err(format!("Arquivo {:?} já existe", path));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Erro ao criar pasta {:?}: {}", dir, e))?;
+    }
+
+    let header = RecordingHeader {
+        plc_ip: plc_ip.to_string(),
+        started_at_ms: chrono::Utc::now().timestamp_millis(),
+        structure,
+    };
+    let file = fs::File::create(path).map_err(|e| format!("Erro ao criar {:?}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    let header_json = serde_json::to_string(&header).map_err(|e| format!("Erro ao gerar cabeçalho: {}", e))?;
+    writer.write_all(FILE_MAGIC)
+        .and_then(|_| writer.write_all(header_json.as_bytes()))
+        .and_then(|_| writer.write_all(b"\n"))
+        .map_err(|e| format!("Erro ao gravar cabeçalho em {:?}: {}", path, e))?;
+
+    let info = RecordingInfo {
+        plc_ip: plc_ip.to_string(),
+        path: path.to_string_lossy().to_string(),
+        started_at_ms: header.started_at_ms,
+        frames: 0,
+        bytes: 0,
+        last_error: None,
+    };
+    recordings.insert(plc_ip.to_string(), Recording { writer, info: info.clone() });
+    println!("⏺️ Gravação de pacotes do PLC {} em {:?}", plc_ip, path);
+    Ok(info)
+}
+
+pub fn stop_recording(plc_ip: &str) -> Result<RecordingInfo, String> {
+    let mut recording = RECORDINGS.lock().unwrap().as_mut()
+        .and_then(|r| r.remove(plc_ip))
+        .ok_or_else(|| format!("PLC {} não está sendo gravado", plc_ip))?;
+    recording.writer.flush().map_err(|e| format!("Erro ao finalizar {}: {}", recording.info.path, e))?;
+    println!("⏹️ Gravação do PLC {} encerrada: {} frames, {} bytes", plc_ip, recording.info.frames, recording.info.bytes);
+    Ok(recording.info)
+}
+
+pub fn active_recordings() -> Vec<RecordingInfo> {
+    RECORDINGS.lock().unwrap().iter().flatten().map(|(_, r)| r.info.clone()).collect()
+}
+
+/// Chamado para cada frame completo recebido do PLC (sem gravação ativa não faz nada)
+pub fn record_frame(plc_ip: &str, frame: &[u8]) {
+    let mut recordings = RECORDINGS.lock().unwrap();
+    let Some(recording) = recordings.as_mut().and_then(|r| r.get_mut(plc_ip)) else { return };
+    let ts_us = chrono::Utc::now().timestamp_micros();
+    let result = recording.writer.write_all(&ts_us.to_be_bytes())
+        .and_then(|_| recording.writer.write_all(&(frame.len() as u32).to_be_bytes()))
+        .and_then(|_| recording.writer.write_all(frame));
+    match result {
+        Ok(()) => {
+            recording.info.frames += 1;
+            recording.info.bytes += frame.len() as u64;
+        }
+        Err(e) => {
+            if recording.info.last_error.is_none() {
+                println!("❌ Gravação do PLC {}: {}", plc_ip, e);
+            }
+            recording.info.last_error = Some(e.to_string());
+        }
+    }
+}
+
+struct RecordedFrame {
+    ts_us: i64,
+    bytes: Vec<u8>,
+}
+
+/// Lê o arquivo inteiro (cabeçalho + frames)
+fn read_recording(path: &Path) -> Result<(RecordingHeader, Vec<RecordedFrame>), String> {
+    let file = fs::File::open(path).map_err(|e| format!("Erro ao abrir {:?}: {}", path, e))?;
+    let mut reader = BufReader::new(file);
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic).map_err(|e| format!("Erro ao ler {:?}: {}", path, e))?;
+    if magic != FILE_MAGIC {
+        return Err(format!("{:?} não é uma gravação de pacotes (.plcrec)", path));
+    }
+    let mut header_line = String::new();
+    reader.read_line(&mut header_line).map_err(|e| format!("Erro ao ler cabeçalho: {}", e))?;
+    let header: RecordingHeader = serde_json::from_str(header_line.trim())
+        .map_err(|e| format!("Cabeçalho inválido: {}", e))?;
+
+    let mut frames = Vec::new();
+    let mut prefix = [0u8; 12];
+    loop {
+        match reader.read_exact(&mut prefix) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("Erro ao ler frame {}: {}", frames.len() + 1, e)),
+        }
+        let ts_us = i64::from_be_bytes(prefix[..8].try_into().unwrap());
+        let len = u32::from_be_bytes(prefix[8..].try_into().unwrap()) as usize;
+        if len > MAX_RECORD_BYTES {
+            return Err(format!("Frame {} com tamanho inválido ({} bytes)", frames.len() + 1, len));
+        }
+        let mut bytes = vec![0u8; len];
+        // Gravação interrompida (app fechado): aproveita os frames completos
+        if reader.read_exact(&mut bytes).is_err() {
+            println!("⚠️ Gravação {:?} truncada após {} frames", path, frames.len());
+            break;
+        }
+        frames.push(RecordedFrame { ts_us, bytes });
+    }
+    Ok((header, frames))
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayStats {
+    pub running: bool,
+    pub path: String,
+    pub plc_ip: String,
+    pub speed: f64,
+    pub total_frames: usize,
+    pub frames_sent: usize,
+    pub recorded_duration_ms: i64,
+    pub started_at_ms: i64,
+    pub last_error: Option<String>,
+}
+
+pub struct PacketReplay {
+    stats: Arc<Mutex<ReplayStats>>,
+    stop_tx: Option<oneshot::Sender<()>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl PacketReplay {
+    /// Lê a gravação e começa a injetar os frames como o PLC `plc_ip` (padrão: o gravado)
+    pub async fn start(tcp_state: TcpServerState, path: &Path, speed: f64, plc_ip: Option<String>, saved_structure: Option<PlcStructureConfig>) -> Result<Self, String> {
+        if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+            return Err(format!("Velocidade deve estar entre {} e {}", MIN_SPEED, MAX_SPEED));
+        }
+        let read_path = path.to_path_buf();
+        let (header, frames) = tokio::task::spawn_blocking(move || read_recording(&read_path)).await
+            .map_err(|e| format!("Task de leitura falhou: {}", e))??;
+        if frames.is_empty() {
+            return Err(format!("Gravação {:?} sem frames", path));
+        }
+        let plc_ip = plc_ip.unwrap_or_else(|| header.plc_ip.clone());
+        // Estrutura da gravação tem prioridade: o parser vê o mesmo layout do campo
+        let structure = header.structure.clone().or(saved_structure)
+            .ok_or_else(|| format!("Sem estrutura para {}: gravação sem cabeçalho de estrutura e PLC não configurado", plc_ip))?;
+
+        match tcp_state.read().await.as_ref() {
+            Some(server) => server.register_simulated_plc(&plc_ip, structure).await?,
+            None => return Err("Servidor TCP não está rodando".to_string()),
+        }
+
+        let stats = Arc::new(Mutex::new(ReplayStats {
+            running: true,
+            path: path.to_string_lossy().to_string(),
+            plc_ip: plc_ip.clone(),
+            speed,
+            total_frames: frames.len(),
+            recorded_duration_ms: (frames.last().unwrap().ts_us - frames[0].ts_us) / 1000,
+            started_at_ms: chrono::Utc::now().timestamp_millis(),
+            ..Default::default()
+        }));
+        let (stop_tx, stop_rx) = oneshot::channel();
+        println!("▶️ Reprodução de {:?}: {} frames como PLC {} ({}x)", path, frames.len(), plc_ip, speed);
+        let handle = tokio::spawn(run_replay(tcp_state, plc_ip, frames, speed, stats.clone(), stop_rx));
+        Ok(Self { stats, stop_tx: Some(stop_tx), handle })
+    }
+
+    pub fn stats(&self) -> ReplayStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Interrompe a reprodução e devolve as estatísticas finais
+    pub async fn stop(mut self) -> ReplayStats {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        let _ = (&mut self.handle).await;
+        self.stats()
+    }
+}
+
+async fn run_replay(
+    tcp_state: TcpServerState,
+    plc_ip: String,
+    frames: Vec<RecordedFrame>,
+    speed: f64,
+    stats: Arc<Mutex<ReplayStats>>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let first_us = frames[0].ts_us;
+    let started = Instant::now();
+    for (index, frame) in frames.iter().enumerate() {
+        // Instante do frame na linha do tempo da reprodução
+        let offset_us = ((frame.ts_us - first_us).max(0) as f64 / speed) as u64;
+        let due = started + Duration::from_micros(offset_us);
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            _ = tokio::time::sleep_until(due.into()) => {}
+        }
+        let result = match tcp_state.read().await.as_ref() {
+            Some(server) => server.inject_frame(&plc_ip, &frame.bytes).await.map_err(String::from),
+            None => Err("Servidor TCP não está rodando".to_string()),
+        };
+        let mut stats = stats.lock().unwrap();
+        stats.frames_sent = index + 1;
+        if let Err(e) = result {
+            stats.last_error = Some(e);
+            break;
+        }
+    }
+
+    if let Some(server) = tcp_state.read().await.as_ref() {
+        server.unregister_simulated_plc(&plc_ip).await;
+    }
+    let mut stats = stats.lock().unwrap();
+    stats.running = false;
+    println!("⏹️ Reprodução do PLC {} encerrada: {}/{} frames", plc_ip, stats.frames_sent, stats.total_frames);
+}
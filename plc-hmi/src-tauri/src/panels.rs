@@ -0,0 +1,160 @@
+use crate::database::{Database, PanelHeartbeat, PanelLogEntry};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// PAINÉIS REMOTOS (plc-app) - HEARTBEAT E LOGS ENCAMINHADOS PELO WEBSOCKET
+// ============================================================================
+//
+// Cada painel abre uma conexão WebSocket comum e envia:
+//   {"type":"PANEL_HEARTBEAT","panel":{panel_id, hostname, app_version, plc_connected, last_data_age_s, uptime_s}}
+//   {"type":"PANEL_LOGS","panel_id":"...","logs":[{id, timestamp, level, category, message, details}]}
+// As respostas trazem `last_log_id` (maior id já gravado aqui) para o painel
+// continuar o envio de onde parou, mesmo após reinício de qualquer lado.
+
+pub const PANEL_OFFLINE_AFTER_MS: i64 = 60_000;
+const MONITOR_INTERVAL_SECS: u64 = 15;
+const PANEL_LOG_RETENTION_DAYS: i64 = 30;
+const MAX_LOGS_PER_BATCH: usize = 500;
+
+fn last_log_id(database: &Database, panel_id: &str) -> i64 {
+    database.panel_last_log_id(panel_id).unwrap_or(0)
+}
+
+/// Trata os comandos PANEL_* recebidos pelo servidor WebSocket; retorna a resposta
+pub fn handle_panel_message(
+    app_handle: &AppHandle,
+    database: &Database,
+    cmd_type: &str,
+    cmd: &serde_json::Value,
+    address: &str,
+) -> serde_json::Value {
+    match cmd_type {
+        "PANEL_HEARTBEAT" => {
+            let heartbeat: PanelHeartbeat = match cmd.get("panel").cloned().map(serde_json::from_value) {
+                Some(Ok(heartbeat)) => heartbeat,
+                Some(Err(e)) => return error_response("PANEL_ACK", &format!("Heartbeat inválido: {}", e)),
+                None => return error_response("PANEL_ACK", "Campo 'panel' ausente"),
+            };
+            if heartbeat.panel_id.trim().is_empty() {
+                return error_response("PANEL_ACK", "panel_id vazio");
+            }
+            if let Err(e) = database.upsert_panel_heartbeat(&heartbeat, address) {
+                println!("❌ Erro ao registrar heartbeat do painel {}: {}", heartbeat.panel_id, e);
+                return error_response("PANEL_ACK", &format!("Erro ao registrar heartbeat: {}", e));
+            }
+            let _ = app_handle.emit("panel-heartbeat", serde_json::json!({
+                "panel_id": heartbeat.panel_id,
+                "plc_connected": heartbeat.plc_connected,
+                "address": address
+            }));
+            serde_json::json!({
+                "type": "PANEL_ACK",
+                "success": true,
+                "last_log_id": last_log_id(database, &heartbeat.panel_id)
+            })
+        }
+        "PANEL_LOGS" => {
+            let panel_id = cmd.get("panel_id").and_then(|p| p.as_str()).unwrap_or("").to_string();
+            if panel_id.trim().is_empty() {
+                return error_response("PANEL_LOGS_ACK", "panel_id vazio");
+            }
+            let mut logs: Vec<PanelLogEntry> = match cmd.get("logs").cloned().map(serde_json::from_value) {
+                Some(Ok(logs)) => logs,
+                Some(Err(e)) => return error_response("PANEL_LOGS_ACK", &format!("Logs inválidos: {}", e)),
+                None => Vec::new(),
+            };
+            logs.truncate(MAX_LOGS_PER_BATCH);
+
+            match database.insert_panel_logs(&panel_id, &logs) {
+                Ok(inserted) => {
+                    let errors: Vec<&PanelLogEntry> = logs.iter()
+                        .filter(|l| l.level == "error" || l.level == "critical")
+                        .collect();
+                    if inserted > 0 {
+                        let _ = app_handle.emit("panel-logs-received", serde_json::json!({
+                            "panel_id": panel_id,
+                            "count": inserted
+                        }));
+                    }
+                    if let Some(last_error) = errors.last().filter(|_| inserted > 0) {
+                        let _ = app_handle.emit("panel-error", serde_json::json!({
+                            "panel_id": panel_id,
+                            "error_count": errors.len(),
+                            "message": last_error.message,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        }));
+                    }
+                    serde_json::json!({
+                        "type": "PANEL_LOGS_ACK",
+                        "success": true,
+                        "inserted": inserted,
+                        "last_log_id": last_log_id(database, &panel_id)
+                    })
+                }
+                Err(e) => {
+                    println!("❌ Erro ao gravar logs do painel {}: {}", panel_id, e);
+                    error_response("PANEL_LOGS_ACK", &format!("Erro ao gravar logs: {}", e))
+                }
+            }
+        }
+        _ => error_response("ERROR", &format!("Comando de painel desconhecido: {}", cmd_type)),
+    }
+}
+
+fn error_response(response_type: &str, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": response_type,
+        "success": false,
+        "message": message
+    })
+}
+
+/// Detecta painéis que pararam de enviar heartbeat e limpa logs antigos
+pub fn start_panel_monitor(app_handle: AppHandle, database: Arc<Database>) {
+    tauri::async_runtime::spawn(async move {
+        let mut online: HashSet<String> = HashSet::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(MONITOR_INTERVAL_SECS));
+        let mut last_prune = std::time::Instant::now();
+
+        loop {
+            interval.tick().await;
+
+            let panels = match database.list_panels(PANEL_OFFLINE_AFTER_MS) {
+                Ok(panels) => panels,
+                Err(e) => {
+                    println!("⚠️ Monitor de painéis: {}", e);
+                    continue;
+                }
+            };
+            for panel in panels {
+                if panel.online {
+                    if online.insert(panel.panel_id.clone()) {
+                        println!("🖥️ Painel {} online ({})", panel.panel_id, panel.address);
+                    }
+                } else if online.remove(&panel.panel_id) {
+                    println!("⚠️ Painel {} sem heartbeat", panel.panel_id);
+                    let _ = app_handle.emit("panel-offline", serde_json::json!({
+                        "panel_id": panel.panel_id,
+                        "hostname": panel.hostname,
+                        "address": panel.address,
+                        "last_heartbeat": panel.last_heartbeat,
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }));
+                }
+            }
+
+            if last_prune.elapsed() >= Duration::from_secs(3600) {
+                last_prune = std::time::Instant::now();
+                let cutoff = chrono::Utc::now().timestamp_millis() - PANEL_LOG_RETENTION_DAYS * 24 * 3600 * 1000;
+                match database.prune_panel_logs(cutoff) {
+                    Ok(0) => {}
+                    Ok(n) => println!("🗑️ {} logs de painéis com mais de {} dias removidos", n, PANEL_LOG_RETENTION_DAYS),
+                    Err(e) => println!("⚠️ Erro ao limpar logs de painéis: {}", e),
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,178 @@
+use crate::database::{Database, ParseQuarantineConfig};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// ERROS DE PARSE POR PLC E QUARENTENA
+// ============================================================================
+//
+// Estrutura errada (bloco a mais, tipo trocado, PLC reprogramado) faz o frame
+// chegar com outro tamanho, o parser cai na detecção automática e a UI, o
+// WebSocket e o historian passam a receber lixo com cara de valor. Aqui cada
+// PLC tem contadores de falha de framing (cabeçalho inválido, acumulador
+// estourado) e de frames fora dos tamanhos da estrutura. Passando de
+// `error_threshold` erros em `window_s`, o PLC entra em quarentena:
+//   - os frames continuam chegando e indo para a captura bruta (incidentes e
+//     gravação de pacotes), mas não são mais parseados nem publicados;
+//   - "plc-quarantined" vira notificação crítica e congela um incidente com os
+//     últimos frames;
+// até alguém corrigir a estrutura e liberar (release_plc_quarantine).
+// O protocolo não tem CRC: erros de checksum não existem para contar.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Framing,       // Cabeçalho impossível / acumulador estourado
+    SizeMismatch,  // Frame fora dos tamanhos conhecidos da estrutura
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlcParseStats {
+    pub plc_ip: String,
+    pub frames_ok: u64,
+    pub framing_errors: u64,
+    pub size_mismatches: u64,
+    pub errors_in_window: usize,
+    pub last_error: Option<String>,
+    pub last_error_at_ms: Option<i64>,
+    pub quarantined: bool,
+    pub quarantined_at_ms: Option<i64>,
+    pub quarantine_reason: Option<String>,
+    pub frames_held: u64,          // Frames recebidos (e não parseados) em quarentena
+}
+
+#[derive(Default)]
+struct PlcErrors {
+    stats: PlcParseStats,
+    recent: VecDeque<Instant>,     // Erros dentro da janela
+}
+
+static STATE: Mutex<Option<HashMap<String, PlcErrors>>> = Mutex::new(None);
+static CONFIG: Mutex<Option<ParseQuarantineConfig>> = Mutex::new(None);
+
+pub fn validate_config(config: &ParseQuarantineConfig) -> Result<(), String> {
+    if !(1..=10_000).contains(&config.error_threshold) {
+        return Err("Limite de erros deve estar entre 1 e 10000".to_string());
+    }
+    if !(1..=86_400).contains(&config.window_s) {
+        return Err("Janela deve estar entre 1s e 86400s".to_string());
+    }
+    Ok(())
+}
+
+/// Configuração em uso (carregada no setup e a cada save)
+pub fn set_config(config: ParseQuarantineConfig) {
+    *CONFIG.lock().unwrap() = Some(config);
+}
+
+fn with_plc<T>(ip: &str, f: impl FnOnce(&mut PlcErrors) -> T) -> T {
+    let mut state = STATE.lock().unwrap();
+    let entry = state.get_or_insert_with(HashMap::new).entry(ip.to_string()).or_insert_with(|| PlcErrors {
+        stats: PlcParseStats { plc_ip: ip.to_string(), ..Default::default() },
+        recent: VecDeque::new(),
+    });
+    f(entry)
+}
+
+/// Frame dentro dos tamanhos esperados
+pub fn record_ok(ip: &str) {
+    with_plc(ip, |plc| plc.stats.frames_ok += 1);
+}
+
+/// Conta o erro e coloca o PLC em quarentena ao passar do limite da janela
+pub fn record_error(app_handle: &AppHandle, database: Option<&Database>, ip: &str, kind: ParseErrorKind, detail: String) {
+    let config = CONFIG.lock().unwrap().clone().unwrap_or_default();
+    let now = Instant::now();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let window = Duration::from_secs(config.window_s as u64);
+
+    let quarantined = with_plc(ip, |plc| {
+        match kind {
+            ParseErrorKind::Framing => plc.stats.framing_errors += 1,
+            ParseErrorKind::SizeMismatch => plc.stats.size_mismatches += 1,
+        }
+        plc.stats.last_error = Some(detail.clone());
+        plc.stats.last_error_at_ms = Some(now_ms);
+        while plc.recent.front().is_some_and(|at| now.duration_since(*at) > window) {
+            plc.recent.pop_front();
+        }
+        plc.recent.push_back(now);
+        plc.stats.errors_in_window = plc.recent.len();
+
+        if !config.enabled || plc.stats.quarantined || plc.recent.len() < config.error_threshold as usize {
+            return None;
+        }
+        let reason = format!("{} erros de parse em {}s (último: {})", plc.recent.len(), config.window_s, detail);
+        plc.stats.quarantined = true;
+        plc.stats.quarantined_at_ms = Some(now_ms);
+        plc.stats.quarantine_reason = Some(reason);
+        plc.stats.frames_held = 0;
+        Some(plc.stats.clone())
+    });
+
+    let Some(stats) = quarantined else { return };
+    let reason = stats.quarantine_reason.clone().unwrap_or_default();
+    println!("☣️ PLC {} em QUARENTENA: {}", ip, reason);
+    if let Some(db) = database {
+        if let Err(e) = db.add_audit_entry("plc_quarantined", ip, "ok", &reason) {
+            println!("⚠️ Erro ao registrar quarentena na auditoria: {}", e);
+        }
+    }
+    let _ = app_handle.emit("plc-quarantined", serde_json::json!({
+        "plc_ip": ip,
+        "reason": reason,
+        "framing_errors": stats.framing_errors,
+        "size_mismatches": stats.size_mismatches,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+}
+
+/// true = PLC em quarentena: o frame fica só na captura bruta
+pub fn hold_frame(ip: &str) -> bool {
+    let mut state = STATE.lock().unwrap();
+    match state.as_mut().and_then(|s| s.get_mut(ip)) {
+        Some(plc) if plc.stats.quarantined => {
+            plc.stats.frames_held += 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn stats() -> Vec<PlcParseStats> {
+    let mut stats: Vec<_> = STATE.lock().unwrap().as_ref()
+        .map(|s| s.values().map(|plc| PlcParseStats { errors_in_window: plc.recent.len(), ..plc.stats.clone() }).collect())
+        .unwrap_or_default();
+    stats.sort_by(|a, b| a.plc_ip.cmp(&b.plc_ip));
+    stats
+}
+
+/// Libera o PLC (após corrigir a estrutura) e zera a janela de erros
+pub fn release(app_handle: &AppHandle, database: &Database, ip: &str) -> Result<PlcParseStats, String> {
+    let stats = {
+        let mut state = STATE.lock().unwrap();
+        let plc = state.as_mut().and_then(|s| s.get_mut(ip))
+            .filter(|plc| plc.stats.quarantined)
+            .ok_or_else(|| format!("PLC {} não está em quarentena", ip))?;
+        let held = plc.stats.frames_held;
+        plc.recent.clear();
+        plc.stats.quarantined = false;
+        plc.stats.quarantined_at_ms = None;
+        plc.stats.quarantine_reason = None;
+        plc.stats.errors_in_window = 0;
+        plc.stats.frames_held = 0;
+        PlcParseStats { frames_held: held, ..plc.stats.clone() }
+    };
+    println!("✅ PLC {} liberado da quarentena ({} frames retidos)", ip, stats.frames_held);
+    if let Err(e) = database.add_audit_entry("plc_quarantine_released", ip, "ok", &format!("{} frames retidos", stats.frames_held)) {
+        println!("⚠️ Erro ao registrar liberação da quarentena na auditoria: {}", e);
+    }
+    let _ = app_handle.emit("plc-quarantine-released", serde_json::json!({
+        "plc_ip": ip,
+        "frames_held": stats.frames_held,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    Ok(stats)
+}
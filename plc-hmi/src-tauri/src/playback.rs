@@ -0,0 +1,239 @@
+use crate::historian::{self, SnapshotValue};
+use crate::websocket_server::{CachedTagValue, SmartCache};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{mpsc, RwLock};
+
+// ============================================================================
+// PLAYBACK HISTÓRICO - REPRODUZ O HISTORIAN PELOS TÓPICOS NORMAIS DO WEBSOCKET
+// ============================================================================
+//
+// Os valores do `tag_history` são reproduzidos na velocidade escolhida num estado
+// próprio, publicado ao lado do SmartCache (nunca dentro dele): o cache ao vivo,
+// historian, alarmes e os demais clientes seguem com os dados TCP. Só a conexão
+// que envia {"type":"PLAYBACK","enabled":true} recebe o replay, no mesmo TAG_DATA
+// de sempre marcado com "playback": true.
+
+const PLAYBACK_TICK_MS: u64 = 100;
+const FETCH_WINDOW_MS: i64 = 60_000; // Busca 1 minuto de histórico por consulta
+pub const MAX_PLAYBACK_SPEED: f64 = 60.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackStatus {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub position_ms: i64,
+    pub speed: f64,
+    pub playing: bool,
+    pub finished: bool,
+    pub plc_ip: Option<String>,
+}
+
+/// Estado reproduzido na posição atual (último valor de cada tag)
+#[derive(Debug, Clone)]
+pub struct PlaybackFrame {
+    pub position_ms: i64,
+    pub values: Vec<CachedTagValue>,
+}
+
+#[derive(Debug)]
+enum PlaybackCommand {
+    Play,
+    Pause,
+    Seek(i64),
+    SetSpeed(f64),
+}
+
+pub struct PlaybackController {
+    status: Arc<RwLock<PlaybackStatus>>,
+    commands: mpsc::UnboundedSender<PlaybackCommand>,
+    handle: tokio::task::JoinHandle<()>,
+    smart_cache: Arc<SmartCache>,
+}
+
+impl PlaybackController {
+    /// Inicia o playback pausado na posição `from_ms`
+    pub fn start(
+        app_handle: AppHandle,
+        pool: Pool<Postgres>,
+        smart_cache: Arc<SmartCache>,
+        from_ms: i64,
+        to_ms: i64,
+        speed: f64,
+        plc_ip: Option<String>,
+    ) -> Self {
+        let status = Arc::new(RwLock::new(PlaybackStatus {
+            from_ms,
+            to_ms,
+            position_ms: from_ms,
+            speed,
+            playing: false,
+            finished: false,
+            plc_ip,
+        }));
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let handle = tokio::spawn(run_playback(app_handle, pool, smart_cache.clone(), status.clone(), rx));
+
+        println!("⏪ Playback iniciado: {} → {} ({}×)", from_ms, to_ms, speed);
+        Self { status, commands: tx, handle, smart_cache }
+    }
+
+    pub fn play(&self) -> Result<(), String> {
+        self.send(PlaybackCommand::Play)
+    }
+
+    pub fn pause(&self) -> Result<(), String> {
+        self.send(PlaybackCommand::Pause)
+    }
+
+    pub fn seek(&self, position_ms: i64) -> Result<(), String> {
+        self.send(PlaybackCommand::Seek(position_ms))
+    }
+
+    pub fn set_speed(&self, speed: f64) -> Result<(), String> {
+        self.send(PlaybackCommand::SetSpeed(speed))
+    }
+
+    pub async fn status(&self) -> PlaybackStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Encerra o playback; os clientes que estavam nele voltam aos dados ao vivo
+    pub async fn stop(self) {
+        self.handle.abort();
+        self.smart_cache.publish_playback(None);
+        println!("⏹️ Playback encerrado - clientes devolvidos aos dados ao vivo");
+    }
+
+    fn send(&self, command: PlaybackCommand) -> Result<(), String> {
+        self.commands.send(command)
+            .map_err(|_| "Playback não está ativo".to_string())
+    }
+}
+
+/// Valores reproduzidos por "plc_ip:tag_name"
+type PlaybackValues = HashMap<String, CachedTagValue>;
+
+fn apply_value(cache: &SmartCache, values: &mut PlaybackValues, v: &SnapshotValue) {
+    values.insert(format!("{}:{}", v.plc_ip, v.tag_name), cache.playback_value(&v.plc_ip, &v.tag_name, &v.value, v.ts_ms));
+}
+
+/// Troca o estado pelo último valor de cada tag até `position_ms` (usado no início e no seek)
+async fn apply_snapshot(pool: &Pool<Postgres>, cache: &SmartCache, values: &mut PlaybackValues, plc_ip: Option<&str>, position_ms: i64) {
+    match historian::fetch_snapshot(pool, position_ms).await {
+        Ok(snapshot) => {
+            values.clear();
+            for v in snapshot.iter().filter(|v| plc_ip.is_none() || plc_ip == Some(v.plc_ip.as_str())) {
+                apply_value(cache, values, v);
+            }
+        }
+        Err(e) => println!("⚠️ Playback: erro ao carregar snapshot em {}: {}", position_ms, e),
+    }
+}
+
+fn publish(cache: &SmartCache, values: &PlaybackValues, position_ms: i64) {
+    cache.publish_playback(Some(PlaybackFrame {
+        position_ms,
+        values: values.values().cloned().collect(),
+    }));
+}
+
+async fn run_playback(
+    app_handle: AppHandle,
+    pool: Pool<Postgres>,
+    cache: Arc<SmartCache>,
+    status: Arc<RwLock<PlaybackStatus>>,
+    mut commands: mpsc::UnboundedReceiver<PlaybackCommand>,
+) {
+    let mut state = status.read().await.clone();
+    let mut buffer: VecDeque<SnapshotValue> = VecDeque::new();
+    let mut fetched_until = state.position_ms;
+    let mut ticks: u64 = 0;
+    let mut values: PlaybackValues = HashMap::new();
+
+    apply_snapshot(&pool, &cache, &mut values, state.plc_ip.as_deref(), state.position_ms).await;
+    publish(&cache, &values, state.position_ms);
+    let _ = app_handle.emit("playback-status", &state);
+
+    let mut interval = tokio::time::interval(Duration::from_millis(PLAYBACK_TICK_MS));
+    loop {
+        interval.tick().await;
+        ticks += 1;
+
+        // Processar comandos pendentes
+        let mut status_changed = false;
+        let mut values_changed = false;
+        loop {
+            match commands.try_recv() {
+                Ok(PlaybackCommand::Play) => {
+                    if state.finished {
+                        continue;
+                    }
+                    state.playing = true;
+                }
+                Ok(PlaybackCommand::Pause) => state.playing = false,
+                Ok(PlaybackCommand::SetSpeed(speed)) => state.speed = speed,
+                Ok(PlaybackCommand::Seek(position_ms)) => {
+                    state.position_ms = position_ms.clamp(state.from_ms, state.to_ms);
+                    state.finished = false;
+                    buffer.clear();
+                    fetched_until = state.position_ms;
+                    apply_snapshot(&pool, &cache, &mut values, state.plc_ip.as_deref(), state.position_ms).await;
+                    values_changed = true;
+                }
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => return,
+            }
+            status_changed = true;
+        }
+
+        if state.playing {
+            let step = (PLAYBACK_TICK_MS as f64 * state.speed) as i64;
+            state.position_ms = (state.position_ms + step).min(state.to_ms);
+
+            // Buscar o histórico em janelas até cobrir a posição atual
+            while fetched_until < state.position_ms {
+                let until = (fetched_until + FETCH_WINDOW_MS).min(state.to_ms);
+                match historian::fetch_range(&pool, state.plc_ip.as_deref(), fetched_until, until).await {
+                    Ok(rows) => buffer.extend(rows),
+                    Err(e) => {
+                        println!("❌ Playback: erro ao ler historian: {}", e);
+                        state.playing = false;
+                        status_changed = true;
+                        break;
+                    }
+                }
+                fetched_until = until;
+            }
+
+            while buffer.front().is_some_and(|v| v.ts_ms <= state.position_ms) {
+                if let Some(v) = buffer.pop_front() {
+                    apply_value(&cache, &mut values, &v);
+                    values_changed = true;
+                }
+            }
+
+            if state.position_ms >= state.to_ms {
+                state.playing = false;
+                state.finished = true;
+                status_changed = true;
+                println!("✅ Playback chegou ao fim da janela");
+            }
+        }
+
+        *status.write().await = state.clone();
+        if values_changed {
+            publish(&cache, &values, state.position_ms);
+        }
+
+        // Emitir status quando mudar e 1×/s durante a reprodução
+        if status_changed || (state.playing && ticks % 10 == 0) {
+            let _ = app_handle.emit("playback-status", &state);
+        }
+    }
+}
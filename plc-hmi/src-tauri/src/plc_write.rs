@@ -0,0 +1,288 @@
+use crate::database::{ByteOrder, DataBlockConfig, PlcStructureConfig};
+use crate::plc_parser::{data_type_size, is_text_type, is_time_type, select_frame_layout, split_array_index, split_variable_path, to_big_endian, PathSelector};
+use crate::error::AppError;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+// ============================================================================
+// ESCRITA NO PLC PELA CONEXÃO TCP JÁ ACEITA (WRITE-BACK)
+// ============================================================================
+//
+// O HMI envia um frame de escrita "WRTE" pelo mesmo socket em que recebe os
+// dados e o PLC confirma com "WACK" entre frames (formato no fio em
+// plc_core::write_protocol, o mesmo do plc-app). O valor vai no tipo e na
+// ordem de bytes do bloco; o offset é a posição da variável no frame de dados
+// (estrutura principal ou perfil que contém o bloco), então o PLC aplica o
+// valor na mesma área que envia.
+//
+// 🆕 Depois do WACK o valor é lido de volta: os próximos frames do layout do
+// alvo são comparados com os bytes enviados até `WRITE_READBACK_TIMEOUT_MS`.
+// O resultado sai em `verified` (escrita confirmada, mas o valor pode não ter
+// aparecido - ex: lógica do PLC sobrescreve a variável).
+
+/// Tempo máximo aguardando a confirmação do PLC
+pub const WRITE_ACK_TIMEOUT_MS: u64 = 3000;
+/// 🆕 Tempo máximo, depois da confirmação, aguardando o valor nos frames recebidos
+pub const WRITE_READBACK_TIMEOUT_MS: u64 = 3000;
+
+/// Posição da variável no frame do PLC
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteTarget {
+    pub block: String,
+    pub data_type: String,
+    pub byte_offset: u32,
+    pub bit: Option<u8>,
+    pub byte_order: ByteOrder, // 🆕 Ordem em que o PLC espera o valor
+    pub layout: String,        // 🆕 "default" ou perfil cujos frames trazem a variável
+}
+
+impl WriteTarget {
+    /// Indica se o frame (do layout do alvo) já traz os bytes escritos
+    fn matches(&self, config: &PlcStructureConfig, frame: &[u8], data: &[u8]) -> bool {
+        if select_frame_layout(config, frame).map(|(name, _)| name) != Some(self.layout.as_str()) {
+            return false;
+        }
+        let start = self.byte_offset as usize;
+        match self.bit {
+            None => frame.get(start..start + data.len()) == Some(data),
+            Some(bit) => {
+                let size = data_type_size(&self.data_type).unwrap_or(0);
+                let Some(bytes) = frame.get(start..start + size) else { return false };
+                let mut value = bytes.to_vec();
+                to_big_endian(self.byte_order, &mut value);
+                let byte = value[size - 1 - bit as usize / 8];
+                (byte >> (bit % 8)) & 1 == data.first().copied().unwrap_or(0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlcWriteResult {
+    pub plc_ip: String,
+    pub variable_path: String,
+    pub value: String,
+    pub seq: u16,
+    pub target: WriteTarget,
+    pub status: String,              // "confirmed", "rejected", "timeout"
+    pub error_code: Option<u8>,      // Status devolvido pelo PLC quando rejeitada
+    pub elapsed_ms: u64,
+    pub verified: Option<bool>,      // 🆕 Valor lido de volta nos frames (None se não confirmada)
+}
+
+impl PlcWriteResult {
+    /// Resumo para a auditoria: "confirmado em 12ms, verificado"
+    pub fn audit_summary(&self) -> String {
+        let verification = match self.verified {
+            Some(true) => "verificado",
+            Some(false) => "não verificado",
+            None => "sem verificação",
+        };
+        format!("confirmado em {}ms, {}", self.elapsed_ms, verification)
+    }
+}
+
+fn find_in_blocks(blocks: &[DataBlockConfig], name: &str, index: u32, byte_order: ByteOrder) -> Result<Option<(u32, String, ByteOrder)>, String> {
+    let mut offset = 0usize;
+    for block in blocks {
+        let size = data_type_size(&block.data_type)
+            .ok_or_else(|| format!("Tipo inválido: {}", block.data_type))?;
+        if block.name == name {
+            if block.waveform {
+                return Err(format!("Bloco '{}' é uma forma de onda e não aceita escrita", name));
+            }
+            // 🆕 Texto (STRING/WSTRING/CHAR) e data/hora (TIME/DATE/TOD/DT) são só leitura
+            if is_text_type(&block.data_type) || is_time_type(&block.data_type) {
+                return Err(format!("Bloco '{}' é do tipo {} e não aceita escrita", name, block.data_type));
+            }
+            if index >= block.count {
+                return Err(format!("Índice {} fora do bloco '{}' ({} elementos)", index, name, block.count));
+            }
+            return Ok(Some(((offset + size * index as usize) as u32, block.data_type.clone(), block.byte_order.unwrap_or(byte_order))));
+        }
+        offset += size * block.count as usize;
+    }
+    Ok(None)
+}
+
+/// Resolve "Bloco[i]" ou "Bloco[i].bit" para a posição no frame (estrutura principal, depois perfis)
+pub fn resolve_write_target(config: &PlcStructureConfig, variable_path: &str) -> Result<WriteTarget, String> {
+    let (variable, selector) = split_variable_path(variable_path.trim())?;
+    // Seletores de leitura (faixa de bits, byte, REAL_SWAP) não têm escrita equivalente
+    let bit = match selector {
+        None => None,
+        Some(PathSelector::Bit(bit)) => Some(bit),
+        Some(_) => return Err(format!(
+            "Seletor de '{}' é só leitura: escreva na variável '{}' ou em um bit (Bloco[i].bit)", variable_path, variable)),
+    };
+    let (block, index) = split_array_index(variable)
+        .ok_or_else(|| format!("Caminho inválido: '{}' (use Bloco[i] ou Bloco[i].bit)", variable_path))?;
+
+    let layouts = std::iter::once(("default", config.blocks.as_slice()))
+        .chain(config.profiles.iter().map(|p| (p.name.as_str(), p.blocks.as_slice())));
+    for (layout, blocks) in layouts {
+        if let Some((byte_offset, data_type, byte_order)) = find_in_blocks(blocks, block, index, config.byte_order)? {
+            let bit = match bit {
+                Some(bit) => {
+                    if matches!(data_type.as_str(), "REAL" | "LREAL") {
+                        return Err(format!("Escrita de bit não suportada em {}", data_type));
+                    }
+                    let bits = data_type_size(&data_type).unwrap_or(0) * 8;
+                    if bit as usize >= bits {
+                        return Err(format!("Bit {} fora do tipo {} (0-{})", bit, data_type, bits - 1));
+                    }
+                    Some(bit)
+                }
+                None => None,
+            };
+            return Ok(WriteTarget { block: block.to_string(), data_type, byte_offset, bit, byte_order, layout: layout.to_string() });
+        }
+    }
+    Err(format!("Bloco '{}' não existe na estrutura do PLC {}", block, config.plc_ip))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "TRUE" | "1" | "ON" => Ok(true),
+        "FALSE" | "0" | "OFF" => Ok(false),
+        _ => Err(format!("Valor de bit inválido: '{}' (use TRUE/FALSE ou 1/0)", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, data_type: &str) -> Result<T, String> {
+    value.trim().parse::<T>()
+        .map_err(|_| format!("Valor '{}' inválido ou fora da faixa de {}", value, data_type))
+}
+
+/// Bytes do valor no formato do PLC (ordem de bytes do bloco). Bit: 1 byte (0/1).
+pub fn encode_value(target: &WriteTarget, value: &str) -> Result<Vec<u8>, String> {
+    if target.bit.is_some() {
+        return Ok(vec![parse_bool(value)? as u8]);
+    }
+    let data_type = target.data_type.as_str();
+    let mut bytes = match data_type {
+        "BYTE" => vec![parse_number::<u8>(value, data_type)?],
+        "WORD" => parse_number::<u16>(value, data_type)?.to_be_bytes().to_vec(),
+        "INT" => parse_number::<i16>(value, data_type)?.to_be_bytes().to_vec(),
+        "DWORD" => parse_number::<u32>(value, data_type)?.to_be_bytes().to_vec(),
+        "DINT" => parse_number::<i32>(value, data_type)?.to_be_bytes().to_vec(),
+        "LWORD" => parse_number::<u64>(value, data_type)?.to_be_bytes().to_vec(),
+        "LINT" => parse_number::<i64>(value, data_type)?.to_be_bytes().to_vec(),
+        "REAL" => {
+            let number = parse_number::<f32>(value, data_type)?;
+            if !number.is_finite() {
+                return Err(format!("Valor '{}' inválido para REAL", value));
+            }
+            number.to_be_bytes().to_vec()
+        }
+        "LREAL" => {
+            let number = parse_number::<f64>(value, data_type)?;
+            if !number.is_finite() {
+                return Err(format!("Valor '{}' inválido para LREAL", value));
+            }
+            number.to_be_bytes().to_vec()
+        }
+        other => return Err(format!("Tipo inválido: {}", other)),
+    };
+    to_big_endian(target.byte_order, &mut bytes);
+    Ok(bytes)
+}
+
+pub fn encode_write_frame(seq: u16, target: &WriteTarget, data: &[u8]) -> Vec<u8> {
+    plc_core::write_protocol::encode_write_frame(seq, target.byte_offset, target.bit, data)
+}
+
+/// Escritas aguardando confirmação: (IP, seq) → canal da resposta
+pub type PendingWrites = DashMap<(String, u16), oneshot::Sender<u8>>;
+
+/// 🆕 Valor escrito aguardando aparecer num frame do PLC
+pub struct Readback {
+    pub target: WriteTarget,
+    pub data: Vec<u8>,               // Bytes enviados (ordem do PLC; bit = 0/1)
+    pub reply: oneshot::Sender<()>,
+}
+
+/// Leituras de volta pendentes: (IP, seq) → valor esperado
+pub type PendingReadbacks = DashMap<(String, u16), Readback>;
+
+/// Confere um frame recebido do PLC contra as leituras de volta pendentes dele
+pub fn check_readbacks(readbacks: &PendingReadbacks, ip: &str, config: &PlcStructureConfig, frame: &[u8]) {
+    let matched: Vec<(String, u16)> = readbacks.iter()
+        .filter(|entry| entry.key().0 == ip && entry.value().target.matches(config, frame, &entry.value().data))
+        .map(|entry| entry.key().clone())
+        .collect();
+    for key in matched {
+        if let Some((_, readback)) = readbacks.remove(&key) {
+            let _ = readback.reply.send(());
+        }
+    }
+}
+
+/// Escrita já enviada ao socket do PLC, aguardando o WACK
+pub struct PendingWrite {
+    pub plc_ip: String,
+    pub variable_path: String,
+    pub value: String,
+    pub seq: u16,
+    pub target: WriteTarget,
+    pub started: Instant,
+    pub reply: oneshot::Receiver<u8>,
+    pub pending: Arc<PendingWrites>,
+    pub readback: oneshot::Receiver<()>,
+    pub readbacks: Arc<PendingReadbacks>,
+}
+
+impl PendingWrite {
+    /// Aguarda a confirmação e a leitura de volta; emite "plc-write-confirmed" ou "plc-write-failed"
+    pub async fn wait(self, app_handle: &AppHandle) -> Result<PlcWriteResult, AppError> {
+        let outcome = tokio::time::timeout(Duration::from_millis(WRITE_ACK_TIMEOUT_MS), self.reply).await;
+        let (status, error_code) = match outcome {
+            Ok(Ok(0)) => ("confirmed", None),
+            Ok(Ok(code)) => ("rejected", Some(code)),
+            // Sem resposta (ou conexão encerrada antes do WACK)
+            _ => {
+                self.pending.remove(&(self.plc_ip.clone(), self.seq));
+                ("timeout", None)
+            }
+        };
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        let verified = match status {
+            "confirmed" => Some(matches!(
+                tokio::time::timeout(Duration::from_millis(WRITE_READBACK_TIMEOUT_MS), self.readback).await,
+                Ok(Ok(()))
+            )),
+            _ => None,
+        };
+        self.readbacks.remove(&(self.plc_ip.clone(), self.seq));
+        let result = PlcWriteResult {
+            plc_ip: self.plc_ip,
+            variable_path: self.variable_path,
+            value: self.value,
+            seq: self.seq,
+            target: self.target,
+            status: status.to_string(),
+            error_code,
+            elapsed_ms,
+            verified,
+        };
+
+        if status == "confirmed" {
+            println!("✍️ PLC {}: {} = {} confirmado (#{}, {}ms)", result.plc_ip, result.variable_path, result.value, result.seq, result.elapsed_ms);
+            if verified == Some(false) {
+                println!("⚠️ PLC {}: {} = {} não apareceu nos frames em {}ms (não verificado)",
+                         result.plc_ip, result.variable_path, result.value, WRITE_READBACK_TIMEOUT_MS);
+            }
+            let _ = app_handle.emit("plc-write-confirmed", &result);
+            return Ok(result);
+        }
+        let _ = app_handle.emit("plc-write-failed", &result);
+        Err(match error_code {
+            Some(code) => AppError::Other(format!("PLC {} rejeitou a escrita de {} (código {})", result.plc_ip, result.variable_path, code)),
+            None => AppError::Timeout(format!("PLC {} não confirmou a escrita de {} em {}ms", result.plc_ip, result.variable_path, WRITE_ACK_TIMEOUT_MS)),
+        })
+    }
+}
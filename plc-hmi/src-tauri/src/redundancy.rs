@@ -0,0 +1,346 @@
+use crate::database::{Database, RedundancyConfig};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+// ============================================================================
+// REDUNDÂNCIA - HEARTBEAT COM CHECKSUM DA CONFIGURAÇÃO
+// ============================================================================
+//
+// Os dois HMIs do par trocam, a cada 5s, uma linha JSON por TCP com o checksum
+// da configuração ativa: cada um conecta em `peer_address`, envia o seu
+// heartbeat e recebe o do par como resposta; `listen_port` atende o lado
+// contrário (só aceita o endereço do par). Checksums diferentes disparam
+// `config-drift-detected`. Sem heartbeat por `peer_timeout_s` o par é dado como
+// perdido (`redundancy-peer-lost`); a volta é sinalizada em
+// `redundancy-peer-restored`. Trocar de papel é decisão do operador.
+
+pub const ROLE_PRIMARY: &str = "primary";
+pub const ROLE_STANDBY: &str = "standby";
+const HEARTBEAT_INTERVAL_SECS: u64 = 5;
+const MIN_PEER_TIMEOUT_S: u32 = 10;
+const MAX_PEER_TIMEOUT_S: u32 = 600;
+const PEER_IO_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_HEARTBEAT_BYTES: u64 = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDriftReport {
+    pub peer_id: String,
+    pub local_checksum: String,
+    pub peer_checksum: String,
+    pub in_sync: bool,
+    pub checked_at: i64,
+}
+
+/// Linha JSON trocada entre os HMIs do par
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Heartbeat {
+    peer_id: String,
+    role: String,
+    config_checksum: String,
+    timestamp_ms: i64,
+}
+
+/// Estado do par visto por este HMI (get_redundancy_status)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RedundancyStatus {
+    pub enabled: bool,
+    pub role: String,
+    pub peer_id: Option<String>,
+    pub peer_role: Option<String>,
+    pub peer_alive: bool,
+    pub peer_lost: bool,                     // Timeout já sinalizado
+    pub last_peer_heartbeat_ms: Option<i64>,
+    pub last_error: Option<String>,          // Última falha ao falar com o par
+    pub last_report: Option<ConfigDriftReport>,
+}
+
+pub type RedundancyState = Arc<RwLock<RedundancyStatus>>;
+
+pub fn validate_config(config: &RedundancyConfig) -> Result<(), String> {
+    if config.role != ROLE_PRIMARY && config.role != ROLE_STANDBY {
+        return Err(format!("Papel inválido: {} (use '{}' ou '{}')", config.role, ROLE_PRIMARY, ROLE_STANDBY));
+    }
+    if !(MIN_PEER_TIMEOUT_S..=MAX_PEER_TIMEOUT_S).contains(&config.peer_timeout_s) {
+        return Err(format!("Timeout do par deve estar entre {}s e {}s", MIN_PEER_TIMEOUT_S, MAX_PEER_TIMEOUT_S));
+    }
+    if config.listen_port == 0 {
+        return Err("Porta do heartbeat inválida".to_string());
+    }
+    if config.enabled {
+        let valid_address = config.peer_address.trim().rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok_and(|p| p > 0));
+        if !valid_address {
+            return Err(format!("Endereço do par inválido: '{}' (use host:porta)", config.peer_address));
+        }
+    }
+    Ok(())
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn local_id() -> String {
+    let identity = crate::config::current_instance();
+    if identity.name.is_empty() { identity.uuid } else { identity.name }
+}
+
+/// Compara o checksum do par com o local (sem emitir evento)
+fn drift_report(database: &Database, peer_id: &str, peer_checksum: &str) -> Result<ConfigDriftReport, String> {
+    let local_checksum = database.compute_config_checksum()
+        .map_err(|e| format!("Erro ao calcular checksum: {}", e))?;
+
+    Ok(ConfigDriftReport {
+        peer_id: peer_id.to_string(),
+        in_sync: local_checksum == peer_checksum,
+        local_checksum,
+        peer_checksum: peer_checksum.to_string(),
+        checked_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Compara o checksum recebido do par com o local e sinaliza divergência
+pub fn check_peer(app_handle: &AppHandle, database: &Database, peer_id: &str, peer_checksum: &str) -> Result<ConfigDriftReport, String> {
+    let report = drift_report(database, peer_id, peer_checksum)?;
+
+    if !report.in_sync {
+        println!("❌ Redundância: configuração DIVERGENTE com {} (local {}, par {})",
+                 peer_id, report.local_checksum, report.peer_checksum);
+        let _ = app_handle.emit("config-drift-detected", &report);
+    }
+
+    Ok(report)
+}
+
+async fn write_heartbeat<W: AsyncWrite + Unpin>(writer: &mut W, heartbeat: &Heartbeat) -> Result<(), String> {
+    let mut line = serde_json::to_vec(heartbeat).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    writer.write_all(&line).await.map_err(|e| format!("Erro ao enviar heartbeat: {}", e))
+}
+
+/// Uma linha de no máximo MAX_HEARTBEAT_BYTES
+async fn read_heartbeat<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Heartbeat, String> {
+    let mut line = Vec::new();
+    BufReader::new(reader.take(MAX_HEARTBEAT_BYTES)).read_until(b'\n', &mut line).await
+        .map_err(|e| format!("Erro ao ler heartbeat: {}", e))?;
+    serde_json::from_slice(&line).map_err(|e| format!("Heartbeat inválido: {}", e))
+}
+
+/// Envia o heartbeat local e devolve o do par
+async fn exchange(peer_address: &str, own: &Heartbeat) -> Result<Heartbeat, String> {
+    tokio::time::timeout(PEER_IO_TIMEOUT, async {
+        let mut stream = TcpStream::connect(peer_address).await
+            .map_err(|e| format!("Par {} inacessível: {}", peer_address, e))?;
+        write_heartbeat(&mut stream, own).await?;
+        read_heartbeat(&mut stream).await
+    })
+    .await
+    .map_err(|_| format!("Par {} não respondeu em {}s", peer_address, PEER_IO_TIMEOUT.as_secs()))?
+}
+
+struct PeerLink {
+    app_handle: AppHandle,
+    database: Arc<Database>,
+    status: RedundancyState,
+}
+
+impl PeerLink {
+    fn own_heartbeat(&self, role: &str) -> Result<Heartbeat, String> {
+        Ok(Heartbeat {
+            peer_id: local_id(),
+            role: role.to_string(),
+            config_checksum: self.database.compute_config_checksum()
+                .map_err(|e| format!("Erro ao calcular checksum: {}", e))?,
+            timestamp_ms: now_ms(),
+        })
+    }
+
+    async fn on_peer_heartbeat(&self, heartbeat: Heartbeat) {
+        let report = match drift_report(&self.database, &heartbeat.peer_id, &heartbeat.config_checksum) {
+            Ok(report) => Some(report),
+            Err(e) => {
+                println!("⚠️ Redundância: {}", e);
+                None
+            }
+        };
+
+        let mut status = self.status.write().await;
+        if !status.peer_alive {
+            println!("✅ Redundância: heartbeat do par {} ({}) recebido", heartbeat.peer_id, heartbeat.role);
+            if status.peer_lost {
+                let _ = self.app_handle.emit("redundancy-peer-restored", serde_json::json!({
+                    "peer_id": heartbeat.peer_id
+                }));
+            }
+        }
+        // Divergência sinalizada uma vez por transição, não a cada heartbeat
+        if let Some(report) = &report {
+            let was_in_sync = status.last_report.as_ref().map(|r| r.in_sync);
+            if !report.in_sync && was_in_sync != Some(false) {
+                println!("❌ Redundância: configuração DIVERGENTE com {} (local {}, par {})",
+                         report.peer_id, report.local_checksum, report.peer_checksum);
+                let _ = self.app_handle.emit("config-drift-detected", report);
+            }
+        }
+        status.peer_id = Some(heartbeat.peer_id);
+        status.peer_role = Some(heartbeat.role);
+        status.peer_alive = true;
+        status.peer_lost = false;
+        status.last_peer_heartbeat_ms = Some(now_ms());
+        status.last_error = None;
+        if report.is_some() {
+            status.last_report = report;
+        }
+    }
+
+    /// Par sem heartbeat além do timeout: só sinaliza (uma vez por perda)
+    async fn check_peer_timeout(&self, config: &RedundancyConfig, waiting_since_ms: i64) {
+        let mut status = self.status.write().await;
+        let last_seen = status.last_peer_heartbeat_ms.unwrap_or(waiting_since_ms).max(waiting_since_ms);
+        let silent_s = (now_ms() - last_seen) / 1000;
+        if silent_s < config.peer_timeout_s as i64 {
+            return;
+        }
+
+        status.peer_alive = false;
+        if !status.peer_lost {
+            status.peer_lost = true;
+            println!("🚨 Redundância: sem heartbeat do par {} há {}s", config.peer_address, silent_s);
+            let _ = self.app_handle.emit("redundancy-peer-lost", serde_json::json!({
+                "peer_address": config.peer_address,
+                "peer_id": status.peer_id,
+                "seconds": silent_s,
+                "error": status.last_error
+            }));
+        }
+    }
+}
+
+/// Atende o heartbeat do par (só do endereço configurado) e responde com o local
+async fn accept_loop(link: Arc<PeerLink>, listener: TcpListener) {
+    loop {
+        let (mut stream, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                println!("⚠️ Redundância: erro ao aceitar conexão: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        let link = link.clone();
+        tokio::spawn(async move {
+            let config = link.database.load_redundancy_config().unwrap_or_default();
+            let from_peer = tokio::net::lookup_host(config.peer_address.trim()).await
+                .map(|mut addrs| addrs.any(|peer| peer.ip() == addr.ip()))
+                .unwrap_or(false);
+            if !from_peer {
+                println!("⚠️ Redundância: heartbeat recusado de {} (par configurado: {})", addr, config.peer_address);
+                return;
+            }
+
+            let result = tokio::time::timeout(PEER_IO_TIMEOUT, async {
+                let heartbeat = read_heartbeat(&mut stream).await?;
+                write_heartbeat(&mut stream, &link.own_heartbeat(&config.role)?).await?;
+                Ok::<_, String>(heartbeat)
+            }).await;
+            match result {
+                Ok(Ok(heartbeat)) => link.on_peer_heartbeat(heartbeat).await,
+                Ok(Err(e)) => println!("⚠️ Redundância: heartbeat de {}: {}", addr, e),
+                Err(_) => println!("⚠️ Redundância: heartbeat de {} incompleto em {}s", addr, PEER_IO_TIMEOUT.as_secs()),
+            }
+        });
+    }
+}
+
+/// A cada 5s: emite `redundancy-heartbeat` localmente, troca heartbeats com o
+/// par configurado e verifica o timeout. A configuração é relida a cada ciclo.
+pub fn start_heartbeat(
+    app_handle: AppHandle,
+    database: Arc<Database>,
+    status: RedundancyState,
+) {
+    let link = Arc::new(PeerLink { app_handle, database, status });
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+        let mut last_checksum = String::new();
+        // Porta em escuta (None no handle = bind falhou; tenta de novo se a porta mudar)
+        let mut listener: Option<(u16, Option<JoinHandle<()>>)> = None;
+        // Início do prazo do timeout (ativação ou mudança de par)
+        let mut waiting_since_ms = now_ms();
+        let mut watched_peer: Option<String> = None;
+
+        loop {
+            interval.tick().await;
+
+            let config = link.database.load_redundancy_config().unwrap_or_else(|e| {
+                println!("⚠️ Redundância: erro ao carregar configuração: {}", e);
+                RedundancyConfig::default()
+            });
+            let own = match link.own_heartbeat(&config.role) {
+                Ok(own) => own,
+                Err(e) => {
+                    println!("⚠️ Redundância: {}", e);
+                    continue;
+                }
+            };
+
+            if own.config_checksum != last_checksum {
+                if !last_checksum.is_empty() {
+                    println!("🔄 Redundância: configuração alterada ({} → {})", last_checksum, own.config_checksum);
+                }
+                last_checksum = own.config_checksum.clone();
+            }
+
+            let _ = link.app_handle.emit("redundancy-heartbeat", serde_json::json!({
+                "config_checksum": own.config_checksum,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+
+            let wanted_port = config.enabled.then_some(config.listen_port);
+            if listener.as_ref().map(|(port, _)| *port) != wanted_port {
+                if let Some((_, Some(handle))) = listener.take() {
+                    handle.abort();
+                }
+                listener = wanted_port.map(|port| (port, None));
+                if let Some(port) = wanted_port {
+                    match TcpListener::bind(("0.0.0.0", port)).await {
+                        Ok(bound) => {
+                            println!("🔁 Redundância: heartbeat do par na porta {}", port);
+                            listener = Some((port, Some(tokio::spawn(accept_loop(link.clone(), bound)))));
+                        }
+                        Err(e) => println!("❌ Redundância: porta {} indisponível para o heartbeat: {}", port, e),
+                    }
+                }
+            }
+
+            {
+                let mut status = link.status.write().await;
+                status.enabled = config.enabled;
+                status.role = config.role.clone();
+            }
+
+            let peer = config.enabled.then(|| config.peer_address.trim().to_string()).filter(|p| !p.is_empty());
+            if peer != watched_peer {
+                watched_peer = peer.clone();
+                waiting_since_ms = now_ms();
+                let mut status = link.status.write().await;
+                status.peer_alive = false;
+                status.peer_lost = false;
+                status.last_error = None;
+            }
+            let Some(peer_address) = peer else { continue };
+
+            match exchange(&peer_address, &own).await {
+                Ok(heartbeat) => link.on_peer_heartbeat(heartbeat).await,
+                Err(e) => link.status.write().await.last_error = Some(e),
+            }
+            link.check_peer_timeout(&config, waiting_since_ms).await;
+        }
+    });
+}
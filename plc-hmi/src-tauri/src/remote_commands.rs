@@ -0,0 +1,248 @@
+use crate::database::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+// ============================================================================
+// TÚNEL DE COMANDOS REMOTOS - APROVAÇÃO ÚNICA PELO OPERADOR LOCAL
+// ============================================================================
+//
+// Suporte remoto: comandos que chegam de fora da máquina (POST /api/remote/write
+// da API REST, tópico de comandos do uplink MQTT) não executam direto. Cada um entra na fila de pendentes, o
+// frontend recebe "remote-command-pending" (toast) e o operador aprova ou nega
+// pelos comandos approve_remote_command / deny_remote_command. A aprovação vale
+// para aquele comando só: é consumida na decisão e não pode ser reaproveitada.
+// Sem decisão no prazo da configuração o comando é negado ("expired"); decisão
+// que chega depois disso também é registrada como "expired", nunca como aprovada.
+// Cada credencial (token REST, broker MQTT) tem no máximo MAX_PENDING_PER_CREDENTIAL
+// pendentes, para um token de suporte não inundar a fila do operador.
+// Tudo vai para a auditoria: pedido, aprovação, negação, expiração e resultado.
+// Desligado por padrão (remote_tunnel_config.enabled); desligar nega os pendentes.
+
+pub const MIN_APPROVAL_TIMEOUT_S: u32 = 10;
+pub const MAX_APPROVAL_TIMEOUT_S: u32 = 3600;
+pub const MAX_PENDING_PER_CREDENTIAL: usize = 5;
+
+/// Comando pedido pelo suporte remoto (hoje só escrita em variável do PLC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteCommandRequest {
+    pub requested_by: String,       // Quem pediu (técnico / nome do token)
+    #[serde(default)]
+    pub reason: Option<String>,     // Motivo mostrado ao operador
+    pub plc_ip: String,
+    pub variable_path: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingRemoteCommand {
+    pub id: String,
+    pub source: String,             // Canal de entrada: "rest" ou "mqtt"
+    pub credential: String,         // Credencial que pediu (limite de pendentes)
+    pub request: RemoteCommandRequest,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+enum Decision {
+    Approved,
+    Denied(String),
+}
+
+static PENDING: Mutex<Option<HashMap<String, (PendingRemoteCommand, oneshot::Sender<Decision>)>>> = Mutex::new(None);
+
+pub fn validate_timeout(approval_timeout_s: u32) -> Result<(), String> {
+    if !(MIN_APPROVAL_TIMEOUT_S..=MAX_APPROVAL_TIMEOUT_S).contains(&approval_timeout_s) {
+        return Err(format!("Prazo de aprovação deve estar entre {}s e {}s", MIN_APPROVAL_TIMEOUT_S, MAX_APPROVAL_TIMEOUT_S));
+    }
+    Ok(())
+}
+
+fn audit(db: &Database, action: &str, command: &PendingRemoteCommand, result: &str, details: &str) {
+    let target = format!("{} {}", command.request.plc_ip, command.request.variable_path);
+    let details = format!("{} [{}] {} = {} por {}: {}", command.source, command.id, command.request.variable_path,
+        command.request.value, command.request.requested_by, details);
+    if let Err(e) = db.add_audit_entry(action, &target, result, &details) {
+        println!("⚠️ Erro ao registrar comando remoto na auditoria: {}", e);
+    }
+}
+
+fn resolved(app_handle: &AppHandle, command: &PendingRemoteCommand, status: &str, reason: Option<&str>) {
+    let _ = app_handle.emit("remote-command-resolved", serde_json::json!({
+        "id": command.id,
+        "status": status,
+        "reason": reason,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+}
+
+/// Enfileira o comando e espera a decisão do operador. Ok = aprovado, pode executar.
+#[cfg_attr(not(any(feature = "rest", feature = "mqtt")), allow(dead_code))]
+pub async fn request_approval(
+    app_handle: &AppHandle,
+    db: &Database,
+    source: &str,
+    credential: &str,
+    request: RemoteCommandRequest,
+) -> Result<PendingRemoteCommand, String> {
+    let config = db.load_remote_tunnel_config()
+        .map_err(|e| format!("Erro ao carregar configuração do túnel remoto: {}", e))?;
+    if !config.enabled {
+        return Err("Túnel de comandos remotos desabilitado nesta máquina".to_string());
+    }
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let command = PendingRemoteCommand {
+        id: uuid::Uuid::new_v4().to_string(),
+        source: source.to_string(),
+        credential: credential.to_string(),
+        request,
+        created_at_ms: now_ms,
+        expires_at_ms: now_ms + config.approval_timeout_s as i64 * 1000,
+    };
+    let (tx, mut rx) = oneshot::channel();
+    {
+        // Contagem e inserção sob o mesmo lock: pedidos simultâneos não passam do limite
+        let mut guard = PENDING.lock().unwrap();
+        let pending = guard.get_or_insert_with(HashMap::new);
+        if pending.values().filter(|(c, _)| c.credential == command.credential).count() >= MAX_PENDING_PER_CREDENTIAL {
+            drop(guard);
+            let message = format!("Muitos comandos remotos pendentes para '{}' (máx {})", command.credential, MAX_PENDING_PER_CREDENTIAL);
+            println!("⚠️ {}", message);
+            audit(db, "remote_command_rejected", &command, "denied", &message);
+            return Err(message);
+        }
+        pending.insert(command.id.clone(), (command.clone(), tx));
+    }
+
+    println!("🛰️ Comando remoto aguardando aprovação: {} {} = {} ({})",
+        command.request.plc_ip, command.request.variable_path, command.request.value, command.request.requested_by);
+    audit(db, "remote_command_requested", &command, "pending", command.request.reason.as_deref().unwrap_or("sem motivo informado"));
+    let _ = app_handle.emit("remote-command-pending", &command);
+
+    // `&mut rx`: o receptor sobrevive ao prazo, para a decisão já tomada ainda chegar
+    let decision = match tokio::time::timeout(Duration::from_secs(config.approval_timeout_s as u64), &mut rx).await {
+        Ok(decision) => decision,
+        Err(_) => {
+            // Ninguém decidiu: sai da fila para a aprovação não chegar depois
+            if PENDING.lock().unwrap().as_mut().and_then(|p| p.remove(&command.id)).is_some() {
+                println!("⏱️ Comando remoto {} expirou sem decisão", command.id);
+                audit(db, "remote_command_expired", &command, "denied", &format!("sem decisão em {}s", config.approval_timeout_s));
+                resolved(app_handle, &command, "expired", None);
+                return Err(format!("Sem aprovação do operador local em {}s", config.approval_timeout_s));
+            }
+            // O operador decidiu no limite do prazo: a decisão está a caminho
+            rx.await
+        }
+    };
+    match decision {
+        Ok(Decision::Approved) => Ok(command),
+        Ok(Decision::Denied(reason)) => Err(format!("Comando negado pelo operador local: {}", reason)),
+        Err(_) => Err("Comando remoto descartado".to_string()),
+    }
+}
+
+/// Aprova ou nega um pendente. A entrada sai da fila aqui: cada aprovação vale uma vez.
+pub fn decide(app_handle: &AppHandle, db: &Database, id: &str, approve: bool, reason: Option<String>) -> Result<PendingRemoteCommand, String> {
+    let (command, tx) = PENDING.lock().unwrap().as_mut()
+        .and_then(|p| p.remove(id))
+        .ok_or_else(|| format!("Comando remoto {} não está pendente (já decidido ou expirado)", id))?;
+    if tx.is_closed() {
+        // O lado remoto desistiu (conexão HTTP encerrada): nada a executar
+        audit(db, "remote_command_cancelled", &command, "denied", "requisição remota encerrada antes da decisão");
+        resolved(app_handle, &command, "cancelled", None);
+        return Err(format!("Comando remoto {} foi cancelado pelo solicitante", id));
+    }
+
+    let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let denied_reason = (!approve).then(|| reason.clone().unwrap_or_else(|| "negado pelo operador local".to_string()));
+    let decision = match &denied_reason {
+        None => Decision::Approved,
+        Some(reason) => Decision::Denied(reason.clone()),
+    };
+    // Só audita a decisão que o solicitante recebeu: se ele já saiu (prazo), o comando não executa
+    if tx.send(decision).is_err() {
+        println!("⏱️ Comando remoto {} expirou antes da decisão", id);
+        audit(db, "remote_command_expired", &command, "denied", "decisão do operador chegou depois do prazo");
+        resolved(app_handle, &command, "expired", None);
+        return Err(format!("Comando remoto {} expirou antes da decisão", id));
+    }
+
+    match denied_reason {
+        None => {
+            println!("✅ Comando remoto {} aprovado", id);
+            audit(db, "remote_command_approved", &command, "ok", reason.as_deref().unwrap_or("aprovado pelo operador local"));
+            resolved(app_handle, &command, "approved", reason.as_deref());
+        }
+        Some(reason) => {
+            println!("⛔ Comando remoto {} negado: {}", id, reason);
+            audit(db, "remote_command_denied", &command, "denied", &reason);
+            resolved(app_handle, &command, "denied", Some(&reason));
+        }
+    }
+    Ok(command)
+}
+
+/// Nega todos os pendentes (túnel desligado)
+pub fn deny_all(app_handle: &AppHandle, db: &Database, reason: &str) -> usize {
+    let drained: Vec<_> = PENDING.lock().unwrap().as_mut()
+        .map(|p| p.drain().map(|(_, entry)| entry).collect())
+        .unwrap_or_default();
+    for (command, _) in &drained {
+        audit(db, "remote_command_denied", command, "denied", reason);
+        resolved(app_handle, command, "denied", Some(reason));
+    }
+    let count = drained.len();
+    for (_, tx) in drained {
+        let _ = tx.send(Decision::Denied(reason.to_string()));
+    }
+    count
+}
+
+/// Pendentes, mais antigos primeiro (para a UI recompor os toasts)
+pub fn list_pending() -> Vec<PendingRemoteCommand> {
+    let mut pending: Vec<_> = PENDING.lock().unwrap().as_ref()
+        .map(|p| p.values().map(|(command, _)| command.clone()).collect())
+        .unwrap_or_default();
+    pending.sort_by_key(|c| c.created_at_ms);
+    pending
+}
+
+/// Registra o resultado da execução de um comando aprovado
+#[cfg_attr(not(any(feature = "rest", feature = "mqtt")), allow(dead_code))]
+pub fn audit_result(db: &Database, command: &PendingRemoteCommand, result: &Result<String, String>) {
+    match result {
+        Ok(details) => audit(db, "remote_command_executed", command, "ok", details),
+        Err(e) => audit(db, "remote_command_executed", command, "error", e),
+    }
+}
+
+/// Aprovação + escrita + auditoria do resultado (canais sem resposta HTTP, ex: MQTT)
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+pub async fn execute_write(
+    app_handle: &AppHandle,
+    db: &Database,
+    tcp_state: &crate::commands::TcpServerState,
+    source: &str,
+    credential: &str,
+    request: RemoteCommandRequest,
+) -> Result<crate::plc_write::PlcWriteResult, String> {
+    let command = request_approval(app_handle, db, source, credential, request).await?;
+    let pending = match tcp_state.read().await.as_ref() {
+        Some(server) => server.send_write(&command.request.plc_ip, &command.request.variable_path, &command.request.value)
+            .map_err(String::from),
+        None => Err("Servidor TCP não está rodando".to_string()),
+    };
+    let outcome = match pending {
+        Ok(pending) => pending.wait(app_handle).await.map_err(String::from),
+        Err(e) => Err(e),
+    };
+    let audited = outcome.as_ref()
+        .map(|result| result.audit_summary())
+        .map_err(Clone::clone);
+    audit_result(db, &command, &audited);
+    outcome
+}
@@ -0,0 +1,396 @@
+// ============================================================================
+// API REST OPCIONAL (feature "rest")
+// ============================================================================
+//
+// Para integrações que não falam WebSocket (Grafana, scripts, MES). Lê os mesmos
+// dados que o WebSocket entrega: valores do SmartCache e configuração do banco.
+//   GET  /api/plcs                 - PLCs configurados
+//   GET  /api/tags/{plc_ip}        - tags do PLC com o valor atual
+//   GET  /api/tags/{plc_ip}/{tag}  - um tag
+//   POST /api/write                - {"plc_ip","tag" ou "variable_path","value"}
+//   POST /api/remote/write         - mesma escrita + "requested_by"/"reason", só executa
+//                                    após aprovação do operador local (remote_commands.rs)
+// Autenticação com as mesmas credenciais do WebSocket (ws_auth.rs): token em
+// "Authorization: Bearer wst_..." / "?token=wst_..." ou chave pública em
+// "X-Public-Key" / "?public_key=". Leitura fica aberta enquanto não existir
+// token ativo nem chave pública habilitada; com chave pública os valores seguem
+// as regras de mascaramento da chave (tags ocultos somem, atrasados vêm sem
+// valor). Escrita exige token sempre e é recusada com chave pública. Tokens de
+// suporte remoto (remote_support) são recusados em /api/write.
+
+use crate::commands::{TcpServerState, WebSocketServerState};
+use crate::database::Database;
+use std::sync::Arc;
+use tauri::AppHandle;
+
+pub const DEFAULT_REST_PORT: u16 = 8080;
+
+/// Dependências compartilhadas com os handlers
+#[derive(Clone)]
+#[cfg_attr(not(feature = "rest"), allow(dead_code))]
+pub struct RestApiContext {
+    pub database: Arc<Database>,
+    pub websocket_state: WebSocketServerState,
+    pub tcp_state: TcpServerState,
+    pub app_handle: AppHandle,
+}
+
+#[cfg(feature = "rest")]
+mod server {
+    use super::RestApiContext;
+    use crate::database::{TagMapping, WsToken};
+    use crate::websocket_server::{CachedTagValue, SmartCache};
+    use crate::remote_commands::{self, RemoteCommandRequest};
+    use crate::ws_auth;
+    use crate::ws_masking::StreamMask;
+    use axum::extract::{Extension, Path, Request, State};
+    use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+    use axum::middleware::Next;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// Erro devolvido como {"error": "..."} com o status HTTP
+    struct ApiError(StatusCode, String);
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+        }
+    }
+
+    fn internal(e: impl std::fmt::Display) -> ApiError {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+
+    type ApiResult<T> = Result<Json<T>, ApiError>;
+
+    #[derive(Serialize)]
+    struct ApiPlc {
+        ip: String,
+        total_size: usize,
+        block_count: usize,
+        last_updated: i64,
+        tag_count: usize,
+        connected: bool,
+    }
+
+    #[derive(Serialize)]
+    struct ApiTag {
+        plc_ip: String,
+        tag_name: String,
+        variable_path: String,
+        description: Option<String>,
+        unit: Option<String>,
+        enabled: bool,
+        area: Option<String>,
+        category: Option<String>,
+        /// Valor atual no SmartCache (null se o WebSocket não estiver rodando ou sem leitura)
+        value: Option<String>,
+        data_type: Option<String>,
+        timestamp_ms: Option<i64>,
+    }
+
+    impl ApiTag {
+        fn new(mapping: TagMapping, cached: Option<&CachedTagValue>) -> Self {
+            ApiTag {
+                value: cached.map(|c| c.value.clone()),
+                data_type: cached.map(|c| c.data_type.clone()),
+                timestamp_ms: cached.map(|c| (c.timestamp_ns / 1_000_000) as i64),
+                unit: cached.and_then(|c| c.unit.clone()).or(mapping.unit),
+                plc_ip: mapping.plc_ip,
+                tag_name: mapping.tag_name,
+                variable_path: mapping.variable_path,
+                description: mapping.description,
+                enabled: mapping.enabled,
+                area: mapping.area,
+                category: mapping.category,
+            }
+        }
+    }
+
+    /// Quem faz a requisição: com chave pública os valores passam pelas regras da chave
+    #[derive(Clone, Default)]
+    struct RestAccess {
+        mask: Option<Arc<StreamMask>>,
+    }
+
+    impl RestAccess {
+        /// Tag como o cliente pode vê-lo; None = oculto pela chave pública
+        fn tag(&self, mapping: TagMapping, cached: Option<&CachedTagValue>) -> Option<ApiTag> {
+            let Some(mask) = &self.mask else { return Some(ApiTag::new(mapping, cached)) };
+            if mask.is_hidden(&mapping.tag_name, mapping.area.as_deref(), mapping.category.as_deref()) {
+                return None;
+            }
+            let mut tag = ApiTag::new(mapping, cached);
+            tag.value = tag.value.take()
+                .and_then(|value| mask.snapshot_value(&tag.tag_name, tag.area.as_deref(), tag.category.as_deref(), &value));
+            if tag.value.is_none() {
+                tag.timestamp_ms = None; // Atrasado: a consulta pontual não tem o valor liberado
+            }
+            Some(tag)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct WriteRequest {
+        plc_ip: String,
+        tag: Option<String>,            // Nome do tag (resolvido para o variable_path)
+        variable_path: Option<String>,  // Ou o endereço direto, ex: "Word[5]"
+        value: String,
+    }
+
+    /// Escrita pedida pelo suporte remoto (passa pela aprovação do operador)
+    #[derive(Deserialize)]
+    struct RemoteWriteRequest {
+        #[serde(flatten)]
+        write: WriteRequest,
+        requested_by: Option<String>,   // Nome do técnico (padrão: nome do token)
+        reason: Option<String>,
+    }
+
+    async fn smart_cache(context: &RestApiContext) -> Option<Arc<SmartCache>> {
+        context.websocket_state.read().await.as_ref().map(|s| s.smart_cache())
+    }
+
+    async fn cached_values(context: &RestApiContext, plc_ip: &str) -> HashMap<String, CachedTagValue> {
+        match smart_cache(context).await {
+            Some(cache) => cache.snapshot(Some(plc_ip)).into_iter().map(|c| (c.tag_name.clone(), c)).collect(),
+            None => HashMap::new(),
+        }
+    }
+
+    async fn list_plcs(State(context): State<RestApiContext>) -> ApiResult<Vec<ApiPlc>> {
+        let db = &context.database;
+        let connected: Vec<String> = match context.tcp_state.read().await.as_ref() {
+            Some(server) => server.get_connected_clients().await,
+            None => Vec::new(),
+        };
+
+        let mut result = Vec::new();
+        for ip in db.list_configured_plcs().map_err(internal)? {
+            let Some(structure) = db.load_plc_structure(&ip).map_err(internal)? else { continue };
+            let tag_count = db.load_tag_mappings(&ip).map_err(internal)?.len();
+            result.push(ApiPlc {
+                connected: connected.contains(&ip),
+                total_size: structure.total_size,
+                block_count: structure.blocks.len(),
+                last_updated: structure.last_updated,
+                tag_count,
+                ip,
+            });
+        }
+        Ok(Json(result))
+    }
+
+    async fn list_tags(State(context): State<RestApiContext>, Extension(access): Extension<RestAccess>, Path(plc_ip): Path<String>) -> ApiResult<Vec<ApiTag>> {
+        let mappings = context.database.load_tag_mappings(&plc_ip).map_err(internal)?;
+        if mappings.is_empty() && context.database.load_plc_structure(&plc_ip).map_err(internal)?.is_none() {
+            return Err(ApiError(StatusCode::NOT_FOUND, format!("PLC {} não configurado", plc_ip)));
+        }
+        let values = cached_values(&context, &plc_ip).await;
+        Ok(Json(mappings.into_iter()
+            .filter_map(|m| {
+                let cached = values.get(&m.tag_name);
+                access.tag(m, cached)
+            })
+            .collect()))
+    }
+
+    async fn get_tag(State(context): State<RestApiContext>, Extension(access): Extension<RestAccess>, Path((plc_ip, tag)): Path<(String, String)>) -> ApiResult<ApiTag> {
+        let mapping = context.database.load_tag_mappings(&plc_ip).map_err(internal)?
+            .into_iter()
+            .find(|m| m.tag_name == tag)
+            .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Tag {} não encontrado no PLC {}", tag, plc_ip)))?;
+        let values = cached_values(&context, &plc_ip).await;
+        let cached = values.get(&mapping.tag_name);
+        access.tag(mapping, cached)
+            .map(Json)
+            .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Tag {} não encontrado no PLC {}", tag, plc_ip)))
+    }
+
+    /// Token obrigatório (escrita nunca fica aberta, nem com chave pública) + corpo JSON
+    async fn authenticated_body<T: serde::de::DeserializeOwned>(context: &RestApiContext, request: Request) -> Result<(WsToken, T), ApiError> {
+        if request.extensions().get::<RestAccess>().is_some_and(|access| access.mask.is_some()) {
+            return Err(ApiError(StatusCode::FORBIDDEN, "Chave pública não permite escrita".to_string()));
+        }
+        let token = request_token(request.headers(), request.uri().query())
+            .ok_or_else(|| ApiError(StatusCode::UNAUTHORIZED, "Escrita exige token de API (Authorization: Bearer wst_...)".to_string()))?;
+        let token = ws_auth::authenticate(&context.database, &token)
+            .map_err(|e| ApiError(StatusCode::UNAUTHORIZED, e))?;
+
+        let body = axum::body::to_bytes(request.into_body(), 64 * 1024).await
+            .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("Corpo inválido: {}", e)))?;
+        let body = serde_json::from_slice(&body)
+            .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("JSON inválido: {}", e)))?;
+        Ok((token, body))
+    }
+
+    fn resolve_variable_path(context: &RestApiContext, request: &WriteRequest) -> Result<String, ApiError> {
+        match (&request.variable_path, &request.tag) {
+            (Some(path), _) => Ok(path.clone()),
+            (None, Some(tag)) => context.database.load_tag_mappings(&request.plc_ip).map_err(internal)?
+                .into_iter()
+                .find(|m| &m.tag_name == tag)
+                .map(|m| m.variable_path)
+                .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Tag {} não encontrado no PLC {}", tag, request.plc_ip))),
+            (None, None) => Err(ApiError(StatusCode::BAD_REQUEST, "Informe \"tag\" ou \"variable_path\"".to_string())),
+        }
+    }
+
+    async fn send_write(context: &RestApiContext, plc_ip: &str, variable_path: &str, value: &str) -> Result<crate::plc_write::PlcWriteResult, ApiError> {
+        let pending = {
+            let server_guard = context.tcp_state.read().await;
+            let server = server_guard.as_ref()
+                .ok_or_else(|| ApiError(StatusCode::SERVICE_UNAVAILABLE, "Servidor TCP não está rodando".to_string()))?;
+            server.send_write(plc_ip, variable_path, value)
+                .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?
+        };
+        pending.wait(&context.app_handle).await
+            .map_err(|e| ApiError(StatusCode::BAD_GATEWAY, e.to_string()))
+    }
+
+    async fn write(State(context): State<RestApiContext>, request: Request) -> Result<Response, ApiError> {
+        let (token, request): (WsToken, WriteRequest) = authenticated_body(&context, request).await?;
+        // Token de suporte remoto nunca escreve direto: só pela aprovação do operador
+        if token.remote_support {
+            return Err(ApiError(StatusCode::FORBIDDEN,
+                format!("Token '{}' é de suporte remoto: use POST /api/remote/write (aprovação do operador local)", token.name)));
+        }
+        let variable_path = resolve_variable_path(&context, &request)?;
+        let outcome = send_write(&context, &request.plc_ip, &variable_path, &request.value).await;
+
+        let target = format!("{} {}", request.plc_ip, variable_path);
+        let (status, details) = match &outcome {
+            Ok(result) => ("ok", format!("REST ({}): {} = {} {}", token.name, variable_path, result.value, result.audit_summary())),
+            Err(e) => ("error", format!("REST ({}): {}", token.name, e.1)),
+        };
+        if let Err(e) = context.database.add_audit_entry("rest_write", &target, status, &details) {
+            println!("⚠️ Erro ao registrar escrita REST na auditoria: {}", e);
+        }
+
+        outcome.map(|result| Json(result).into_response())
+    }
+
+    /// 🆕 Escrita do suporte remoto: espera a aprovação única do operador local
+    async fn remote_write(State(context): State<RestApiContext>, request: Request) -> Result<Response, ApiError> {
+        let (token, request): (WsToken, RemoteWriteRequest) = authenticated_body(&context, request).await?;
+        let variable_path = resolve_variable_path(&context, &request.write)?;
+        let requested_by = request.requested_by.as_deref().map(str::trim).filter(|r| !r.is_empty())
+            .map(|r| format!("{} (token {})", r, token.name))
+            .unwrap_or_else(|| token.name.clone());
+
+        let command = remote_commands::request_approval(&context.app_handle, &context.database, "rest", &format!("token {}", token.name), RemoteCommandRequest {
+            requested_by,
+            reason: request.reason,
+            plc_ip: request.write.plc_ip.clone(),
+            variable_path: variable_path.clone(),
+            value: request.write.value.clone(),
+        }).await.map_err(|e| ApiError(StatusCode::FORBIDDEN, e))?;
+
+        let outcome = send_write(&context, &request.write.plc_ip, &variable_path, &request.write.value).await;
+        let audited = outcome.as_ref()
+            .map(|result| result.audit_summary())
+            .map_err(|e| e.1.clone());
+        remote_commands::audit_result(&context.database, &command, &audited);
+
+        outcome.map(|result| Json(result).into_response())
+    }
+
+    /// Token do cabeçalho Authorization (Bearer) ou do query param `token`
+    fn request_token(headers: &HeaderMap, query: Option<&str>) -> Option<String> {
+        let authorization = headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok());
+        ws_auth::token_from_request(authorization, query)
+    }
+
+    /// Leituras: credencial exigida só quando existe token ativo ou chave pública
+    /// habilitada (mesma regra do WebSocket). A chave pública é credencial própria
+    /// e vai anexada à requisição como RestAccess (valores mascarados).
+    async fn require_credential(State(context): State<RestApiContext>, mut request: Request, next: Next) -> Response {
+        let query = request.uri().query();
+        let public_key = ws_auth::public_key_from_request(
+            request.headers().get("x-public-key").and_then(|value| value.to_str().ok()), query);
+
+        let access = match public_key {
+            Some(key) => match ws_auth::authenticate_public_key(&context.database, &key) {
+                Ok(key) => RestAccess { mask: Some(Arc::new(StreamMask::new(&key))) },
+                Err(e) => return ApiError(StatusCode::UNAUTHORIZED, e).into_response(),
+            },
+            None => {
+                let is_write = matches!(request.uri().path(), "/api/write" | "/api/remote/write");
+                if !is_write && ws_auth::auth_required(&context.database) {
+                    let authenticated: Result<WsToken, String> = request_token(request.headers(), query)
+                        .ok_or_else(|| "Token de API ou chave pública obrigatório".to_string())
+                        .and_then(|token| ws_auth::authenticate(&context.database, &token));
+                    if let Err(e) = authenticated {
+                        return ApiError(StatusCode::UNAUTHORIZED, e).into_response();
+                    }
+                }
+                RestAccess::default()
+            }
+        };
+        request.extensions_mut().insert(access);
+        next.run(request).await
+    }
+
+    /// Cabeçalhos X-Instance-* em toda resposta HTTP (ver config.rs)
+    async fn instance_headers(mut response: Response) -> Response {
+        for (name, value) in crate::config::current_instance().http_headers() {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+        response
+    }
+
+    pub fn build_router(context: RestApiContext) -> Router {
+        Router::new()
+            .route("/api/plcs", get(list_plcs))
+            .route("/api/tags/:plc_ip", get(list_tags))
+            .route("/api/tags/:plc_ip/:tag", get(get_tag))
+            .route("/api/write", post(write))
+            .route("/api/remote/write", post(remote_write))
+            .layer(axum::middleware::from_fn_with_state(context.clone(), require_credential))
+            .layer(axum::middleware::map_response(instance_headers))
+            .with_state(context)
+    }
+}
+
+/// Servidor REST em execução
+#[cfg_attr(not(feature = "rest"), allow(dead_code))]
+pub struct RestApiServer {
+    pub address: String,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl RestApiServer {
+    #[cfg(feature = "rest")]
+    pub async fn start(context: RestApiContext, host: &str, port: u16) -> Result<Self, String> {
+        let address = format!("{}:{}", host, port);
+        let listener = tokio::net::TcpListener::bind(&address).await
+            .map_err(|e| format!("Erro ao abrir porta REST {}: {}", address, e))?;
+        let router = server::build_router(context);
+
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                println!("❌ Servidor REST finalizado com erro: {}", e);
+            }
+        });
+
+        println!("🚀 API REST disponível em http://{}/api", address);
+        Ok(Self { address, handle })
+    }
+
+    #[cfg(not(feature = "rest"))]
+    pub async fn start(_context: RestApiContext, _host: &str, _port: u16) -> Result<Self, String> {
+        Err("Aplicação compilada sem suporte à API REST (habilite a feature \"rest\")".to_string())
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+        println!("🛑 Servidor REST parado ({})", self.address);
+    }
+}
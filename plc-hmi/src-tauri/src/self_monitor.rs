@@ -0,0 +1,261 @@
+use crate::commands::{TcpServerState, WebSocketServerState};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// AUTOMONITORAMENTO DO SERVIDOR (TAGS INTERNOS DE DIAGNÓSTICO)
+// ============================================================================
+//
+// A cada SAMPLE_INTERVAL_SECS mede CPU e memória do processo, memória do
+// sistema, espaço livre na partição do banco (DB_PATH) e contagens de tarefas,
+// e publica tudo no SmartCache como tags do PLC virtual SELF_PLC_IP (área
+// "HMI", categoria "DIAG"). Assim os valores seguem o mesmo caminho dos tags
+// do processo: broadcast do WebSocket, GraphQL, logger CSV e historian.
+
+pub const SELF_PLC_IP: &str = "HMI_SELF";
+pub const SELF_AREA: &str = "HMI";
+pub const SELF_CATEGORY: &str = "DIAG";
+const SAMPLE_INTERVAL_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResourceUsage {
+    pub ts_ms: i64,
+    pub cpu_pct: Option<f64>,              // CPU do processo (100% = todos os núcleos)
+    pub memory_rss_mb: Option<f64>,        // Memória residente do processo
+    pub system_memory_used_pct: Option<f64>,
+    pub disk_free_gb: Option<f64>,         // Partição do banco de dados
+    pub disk_free_pct: Option<f64>,
+    pub os_threads: Option<u64>,
+    pub runtime_tasks: u64,                // Tarefas tokio vivas
+    pub runtime_workers: u64,
+    pub tcp_connections: u64,
+    pub ws_clients: u64,
+    pub uptime_s: u64,
+}
+
+static LATEST: RwLock<Option<ResourceUsage>> = RwLock::new(None);
+
+/// Última medição (None antes da primeira amostra)
+pub fn latest() -> Option<ResourceUsage> {
+    LATEST.read().unwrap().clone()
+}
+
+/// (tag, tipo, unidade) de cada tag interno, na ordem publicada
+pub const SELF_TAGS: &[(&str, &str, &str)] = &[
+    ("HMI_CPU_PCT", "REAL", "%"),
+    ("HMI_MEM_RSS_MB", "REAL", "MB"),
+    ("HMI_SYS_MEM_USED_PCT", "REAL", "%"),
+    ("HMI_DISK_FREE_GB", "REAL", "GB"),
+    ("HMI_DISK_FREE_PCT", "REAL", "%"),
+    ("HMI_OS_THREADS", "DINT", ""),
+    ("HMI_RUNTIME_TASKS", "DINT", ""),
+    ("HMI_TCP_CONNECTIONS", "DINT", ""),
+    ("HMI_WS_CLIENTS", "DINT", ""),
+    ("HMI_UPTIME_S", "DINT", "s"),
+];
+
+impl ResourceUsage {
+    /// Valores na ordem de SELF_TAGS (medição indisponível = tag não publicado)
+    fn tag_values(&self) -> [Option<String>; 10] {
+        let real = |v: Option<f64>| v.map(|v| format!("{:.1}", v));
+        [
+            real(self.cpu_pct),
+            real(self.memory_rss_mb),
+            real(self.system_memory_used_pct),
+            real(self.disk_free_gb.map(|gb| (gb * 10.0).round() / 10.0)),
+            real(self.disk_free_pct),
+            self.os_threads.map(|n| n.to_string()),
+            Some(self.runtime_tasks.to_string()),
+            Some(self.tcp_connections.to_string()),
+            Some(self.ws_clients.to_string()),
+            Some(self.uptime_s.to_string()),
+        ]
+    }
+}
+
+/// Tempo de CPU do processo (user + kernel) em segundos
+#[derive(Default)]
+struct CpuReading {
+    cpu_s: Option<f64>,
+    at: Option<Instant>,
+}
+
+pub fn start_self_monitor(tcp_state: TcpServerState, websocket_state: WebSocketServerState) {
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let data_dir = Path::new(crate::database::DB_PATH).parent()
+            .filter(|dir| dir.exists())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+        let mut previous = CpuReading::default();
+        let mut interval = tokio::time::interval(Duration::from_secs(SAMPLE_INTERVAL_SECS));
+        println!("🩺 Automonitoramento: tags {} a cada {}s (disco: {})", SELF_PLC_IP, SAMPLE_INTERVAL_SECS, data_dir.display());
+
+        loop {
+            interval.tick().await;
+
+            let now = Instant::now();
+            let cpu_s = platform::process_cpu_seconds();
+            let cpu_pct = match (cpu_s, previous.cpu_s, previous.at) {
+                (Some(cpu), Some(prev_cpu), Some(prev_at)) => {
+                    let wall = now.duration_since(prev_at).as_secs_f64();
+                    (wall > 0.0).then(|| ((cpu - prev_cpu) / wall / cores * 100.0).clamp(0.0, 100.0))
+                }
+                _ => None, // Primeira amostra: sem intervalo para comparar
+            };
+            previous = CpuReading { cpu_s, at: Some(now) };
+
+            let disk = platform::disk_space(&data_dir);
+            let metrics = tokio::runtime::Handle::current().metrics();
+            let tcp_connections = tcp_state.read().await.as_ref().map(|s| s.get_connected_clients_count() as u64).unwrap_or(0);
+            let ws_clients = websocket_state.read().await.as_ref().map(|s| s.get_stats().active_connections).unwrap_or(0);
+            let usage = ResourceUsage {
+                ts_ms: chrono::Utc::now().timestamp_millis(),
+                cpu_pct,
+                memory_rss_mb: platform::process_rss_bytes().map(|b| b as f64 / 1_048_576.0),
+                system_memory_used_pct: platform::system_memory().map(|(total, available)| {
+                    if total == 0 { 0.0 } else { (total - available.min(total)) as f64 / total as f64 * 100.0 }
+                }),
+                disk_free_gb: disk.map(|(_, free)| free as f64 / 1_073_741_824.0),
+                disk_free_pct: disk.map(|(total, free)| if total == 0 { 0.0 } else { free as f64 / total as f64 * 100.0 }),
+                os_threads: platform::os_threads(),
+                runtime_tasks: metrics.num_alive_tasks() as u64,
+                runtime_workers: metrics.num_workers() as u64,
+                tcp_connections,
+                ws_clients,
+                uptime_s: started.elapsed().as_secs(),
+            };
+
+            if let Some(smart_cache) = websocket_state.read().await.as_ref().map(|s| s.smart_cache()) {
+                for ((tag_name, data_type, unit), value) in SELF_TAGS.iter().zip(usage.tag_values()) {
+                    if let Some(value) = value {
+                        let unit = (!unit.is_empty()).then_some(*unit);
+                        smart_cache.apply_internal_value(SELF_PLC_IP, tag_name, &value, data_type, unit);
+                    }
+                }
+            }
+            *LATEST.write().unwrap() = Some(usage);
+        }
+    });
+}
+
+// ============================================================================
+// MEDIÇÕES POR SISTEMA OPERACIONAL
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::Path;
+
+    fn status_field(name: &str) -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with(name))?;
+        line.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    pub fn process_cpu_seconds() -> Option<f64> {
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        // Campos após o nome do processo "(comm)": utime = 14º, stime = 15º
+        let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+        let utime: f64 = fields.get(11)?.parse().ok()?;
+        let stime: f64 = fields.get(12)?.parse().ok()?;
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        (ticks > 0).then(|| (utime + stime) / ticks as f64)
+    }
+
+    pub fn process_rss_bytes() -> Option<u64> {
+        status_field("VmRSS:").map(|kb| kb * 1024)
+    }
+
+    pub fn os_threads() -> Option<u64> {
+        status_field("Threads:")
+    }
+
+    /// (total, disponível) em bytes
+    pub fn system_memory() -> Option<(u64, u64)> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let field = |name: &str| -> Option<u64> {
+            meminfo.lines().find(|l| l.starts_with(name))?.split_whitespace().nth(1)?.parse::<u64>().ok().map(|kb| kb * 1024)
+        };
+        Some((field("MemTotal:")?, field("MemAvailable:")?))
+    }
+
+    /// (total, livre para o usuário) em bytes
+    pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+        use std::os::unix::ffi::OsStrExt;
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        let block = stat.f_frsize as u64;
+        Some((stat.f_blocks as u64 * block, stat.f_bavail as u64 * block))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use winapi::shared::minwindef::FILETIME;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+    use winapi::um::processthreadsapi::{GetCurrentProcess, GetProcessTimes};
+    use winapi::um::psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    fn filetime_seconds(ft: &FILETIME) -> f64 {
+        // FILETIME em unidades de 100 ns
+        (((ft.dwHighDateTime as u64) << 32) | ft.dwLowDateTime as u64) as f64 / 10_000_000.0
+    }
+
+    pub fn process_cpu_seconds() -> Option<f64> {
+        let mut creation: FILETIME = unsafe { std::mem::zeroed() };
+        let mut exit: FILETIME = unsafe { std::mem::zeroed() };
+        let mut kernel: FILETIME = unsafe { std::mem::zeroed() };
+        let mut user: FILETIME = unsafe { std::mem::zeroed() };
+        let ok = unsafe { GetProcessTimes(GetCurrentProcess(), &mut creation, &mut exit, &mut kernel, &mut user) };
+        (ok != 0).then(|| filetime_seconds(&kernel) + filetime_seconds(&user))
+    }
+
+    pub fn process_rss_bytes() -> Option<u64> {
+        let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+        (ok != 0).then_some(counters.WorkingSetSize as u64)
+    }
+
+    pub fn os_threads() -> Option<u64> {
+        None // Exigiria um snapshot do Toolhelp a cada amostra
+    }
+
+    /// (total, disponível) em bytes
+    pub fn system_memory() -> Option<(u64, u64)> {
+        let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+        status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+        let ok = unsafe { GlobalMemoryStatusEx(&mut status) };
+        (ok != 0).then_some((status.ullTotalPhys, status.ullAvailPhys))
+    }
+
+    /// (total, livre para o usuário) em bytes
+    pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        let mut available: winapi::um::winnt::ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+        let mut total: winapi::um::winnt::ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+        let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, std::ptr::null_mut()) };
+        (ok != 0).then(|| unsafe { (*total.QuadPart(), *available.QuadPart()) })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod platform {
+    use std::path::Path;
+
+    pub fn process_cpu_seconds() -> Option<f64> { None }
+    pub fn process_rss_bytes() -> Option<u64> { None }
+    pub fn os_threads() -> Option<u64> { None }
+    pub fn system_memory() -> Option<(u64, u64)> { None }
+    pub fn disk_space(_path: &Path) -> Option<(u64, u64)> { None }
+}
@@ -0,0 +1,56 @@
+use crate::commands::TcpServerState;
+use plc_hmi_client::protocol::ServerMessage;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+// ============================================================================
+// STATUS DO SERVIDOR (ONLINE/OFFLINE) PARA SISTEMAS A JUSANTE
+// ============================================================================
+//
+// Sem dado novo um consumidor não sabe se o processo está parado ou se o HMI
+// caiu. O mesmo payload é publicado:
+//   - no WebSocket: SERVER_STATUS logo após o WELCOME, a cada STATUS_INTERVAL_SECS
+//     e com state "offline" quando o servidor é parado;
+//   - no MQTT (feature "mqtt", mqtt_status.rs): tópico retido, com last will
+//     "offline" para quedas sem desligamento limpo.
+// O tipo ServerStatus é o do crate plc-hmi-client (mesmo formato nos clientes).
+
+pub use plc_hmi_client::protocol::ServerStatus;
+
+pub const STATUS_INTERVAL_SECS: u64 = 30;
+
+static STARTED: OnceLock<(Instant, TcpServerState)> = OnceLock::new();
+
+/// Marca o início do backend (uptime) e guarda o estado TCP para contar os PLCs
+pub fn init(tcp_state: TcpServerState) {
+    let _ = STARTED.set((Instant::now(), tcp_state));
+}
+
+/// Status atual (state "online")
+pub async fn current() -> ServerStatus {
+    let (uptime_s, connected_plcs) = match STARTED.get() {
+        Some((started, tcp_state)) => (
+            started.elapsed().as_secs(),
+            tcp_state.read().await.as_ref().map(|s| s.get_connected_clients_count() as u64).unwrap_or(0),
+        ),
+        None => (0, 0),
+    };
+    ServerStatus {
+        state: "online".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_s,
+        connected_plcs,
+        instance: crate::config::current_instance().into(),
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+    }
+}
+
+/// Status de desligamento (mesmos campos, state "offline")
+pub async fn offline() -> ServerStatus {
+    ServerStatus { state: "offline".to_string(), ..current().await }
+}
+
+/// Mensagem SERVER_STATUS do WebSocket
+pub fn ws_message(status: &ServerStatus) -> serde_json::Value {
+    ServerMessage::ServerStatus { status: status.clone() }.to_json()
+}
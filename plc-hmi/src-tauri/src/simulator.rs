@@ -0,0 +1,267 @@
+use crate::commands::TcpServerState;
+use crate::database::{ByteOrder, PlcStructureConfig};
+use crate::plc_parser::{data_type_size, to_big_endian};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+// ============================================================================
+// SIMULADOR DE PLC - TRÁFEGO SINTÉTICO PARA DESENVOLVIMENTO
+// ============================================================================
+//
+// Gera frames a partir da estrutura salva de um PLC e injeta no mesmo caminho
+// dos frames recebidos pelo socket (TcpServer::inject_frame): parser, Channels
+// do frontend, SmartCache/WebSocket e historian enxergam um PLC normal.
+// Sinais por tipo de bloco:
+//   REAL/LREAL             - senoides (período e fase diferentes por elemento)
+//   INT/DINT/LINT          - rampas dente de serra
+//   BYTE/WORD/DWORD/LWORD  - bits alternando ao acaso (~1 troca a cada 5s por elemento)
+//   forma de onda          - senoide ao longo dos pontos, andando no tempo
+//   texto e data/hora      - valor fixo do gerador de frames
+// Escritas não são atendidas: o PLC simulado não tem socket.
+
+pub const MIN_INTERVAL_MS: u64 = 10;
+pub const MAX_INTERVAL_MS: u64 = 60_000;
+const BIT_TOGGLE_MEAN_MS: f64 = 5_000.0;
+const WAVEFORM_CYCLES: f64 = 4.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatorConfig {
+    pub plc_ip: String,
+    pub interval_ms: u64,
+    #[serde(default)]
+    pub seed: Option<u64>,    // Mesma semente = mesma sequência de bits
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimulatorStats {
+    pub running: bool,
+    pub plc_ip: String,
+    pub interval_ms: u64,
+    pub frame_size: usize,
+    pub frames_sent: u64,
+    pub started_at_ms: i64,
+    pub last_error: Option<String>,
+}
+
+pub fn validate_config(config: &SimulatorConfig) -> Result<(), String> {
+    if config.plc_ip.trim().is_empty() {
+        return Err("IP do PLC simulado é obrigatório".to_string());
+    }
+    if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&config.interval_ms) {
+        return Err(format!("Intervalo do simulador deve estar entre {}ms e {}ms", MIN_INTERVAL_MS, MAX_INTERVAL_MS));
+    }
+    Ok(())
+}
+
+enum Signal {
+    Sine { period_s: f64, phase: f64, center: f64, amplitude: f64 },
+    Ramp { period_s: f64, max: f64 },
+    Bits { state: u64, bits: u32 },
+    Waveform { point: usize, points: usize, center: f64, amplitude: f64 },
+    Fixed,
+}
+
+/// Um elemento do frame e o sinal que o alimenta
+struct Element {
+    offset: usize,
+    data_type: String,
+    order: ByteOrder,
+    signal: Signal,
+}
+
+/// xorshift64 (as trocas de bits não precisam de mais que isso)
+struct SimRng(u64);
+
+impl SimRng {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        (self.next() % 1_000_000) as f64 / 1_000_000.0 < probability
+    }
+}
+
+/// Faixa dos sinais analógicos: tipos sem sinal ficam positivos
+fn analog_range(data_type: &str) -> (f64, f64) {
+    match data_type {
+        "REAL" | "LREAL" => (50.0, 50.0),
+        "INT" | "DINT" | "LINT" => (0.0, 1000.0),
+        "BYTE" => (128.0, 120.0),
+        _ => (1000.0, 1000.0),
+    }
+}
+
+fn build_elements(structure: &PlcStructureConfig) -> Vec<Element> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+    let mut n = 0usize; // Índice global: espalha períodos e fases entre os elementos
+    for block in &structure.blocks {
+        let Some(size) = data_type_size(&block.data_type) else { break };
+        let order = block.byte_order.unwrap_or(structure.byte_order);
+        let numeric = matches!(block.data_type.as_str(), "BYTE" | "WORD" | "INT" | "DWORD" | "DINT" | "REAL" | "LWORD" | "LINT" | "LREAL");
+        for i in 0..block.count as usize {
+            let (center, amplitude) = analog_range(&block.data_type);
+            let signal = match block.data_type.as_str() {
+                _ if !numeric => Signal::Fixed,
+                _ if block.waveform => Signal::Waveform { point: i, points: block.count as usize, center, amplitude },
+                "REAL" | "LREAL" => Signal::Sine { period_s: 10.0 + (n % 7) as f64 * 5.0, phase: n as f64 * 0.7, center, amplitude },
+                "INT" | "DINT" | "LINT" => Signal::Ramp { period_s: 20.0 + (n % 5) as f64 * 10.0, max: amplitude },
+                _ => Signal::Bits { state: 0, bits: size as u32 * 8 },
+            };
+            elements.push(Element { offset, data_type: block.data_type.clone(), order, signal });
+            offset += size;
+            n += 1;
+        }
+    }
+    elements
+}
+
+/// Bytes big-endian de um valor numérico no tipo do elemento
+fn encode(data_type: &str, value: f64) -> Option<Vec<u8>> {
+    Some(match data_type {
+        "BYTE" => vec![value as u8],
+        "WORD" => (value as u16).to_be_bytes().to_vec(),
+        "INT" => (value as i16).to_be_bytes().to_vec(),
+        "DWORD" => (value as u32).to_be_bytes().to_vec(),
+        "DINT" => (value as i32).to_be_bytes().to_vec(),
+        "LWORD" => (value as u64).to_be_bytes().to_vec(),
+        "LINT" => (value as i64).to_be_bytes().to_vec(),
+        "REAL" => (value as f32).to_be_bytes().to_vec(),
+        "LREAL" => value.to_be_bytes().to_vec(),
+        _ => return None,
+    })
+}
+
+/// Atualiza os elementos no frame para o instante `t` (segundos desde o início)
+fn update_frame(frame: &mut [u8], elements: &mut [Element], t: f64, toggle_probability: f64, rng: &mut SimRng) {
+    use std::f64::consts::TAU;
+    for element in elements.iter_mut() {
+        let mut bytes = match &mut element.signal {
+            Signal::Fixed => continue,
+            Signal::Sine { period_s, phase, center, amplitude } => {
+                encode(&element.data_type, *center + *amplitude * (TAU * t / *period_s + *phase).sin())
+            }
+            Signal::Ramp { period_s, max } => encode(&element.data_type, (t % *period_s) / *period_s * *max),
+            Signal::Waveform { point, points, center, amplitude } => {
+                let x = *point as f64 / (*points).max(1) as f64;
+                encode(&element.data_type, *center + *amplitude * (TAU * (x * WAVEFORM_CYCLES + t / 2.0)).sin())
+            }
+            Signal::Bits { state, bits } => {
+                if rng.chance(toggle_probability) {
+                    *state ^= 1 << (rng.next() % *bits as u64);
+                }
+                let size = (*bits / 8) as usize;
+                Some(state.to_be_bytes()[8 - size..].to_vec())
+            }
+        };
+        let Some(bytes) = bytes.as_mut() else { continue };
+        to_big_endian(element.order, bytes);
+        if let Some(target) = frame.get_mut(element.offset..element.offset + bytes.len()) {
+            target.copy_from_slice(bytes);
+        }
+    }
+}
+
+pub struct Simulator {
+    plc_ip: String,
+    stats: Arc<Mutex<SimulatorStats>>,
+    stop_tx: oneshot::Sender<()>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl Simulator {
+    /// Registra o PLC simulado no servidor TCP e começa a gerar frames
+    pub async fn start(tcp_state: TcpServerState, config: SimulatorConfig, structure: PlcStructureConfig) -> Result<Self, String> {
+        validate_config(&config)?;
+        if structure.blocks.is_empty() {
+            return Err(format!("Estrutura de {} sem blocos: nada a simular", config.plc_ip));
+        }
+        let frame = crate::frame_generator::valid_frame(&structure.blocks, structure.byte_order, config.seed);
+        let elements = build_elements(&structure);
+
+        match tcp_state.read().await.as_ref() {
+            Some(server) => server.register_simulated_plc(&config.plc_ip, structure).await?,
+            None => return Err("Servidor TCP não está rodando".to_string()),
+        }
+
+        let stats = Arc::new(Mutex::new(SimulatorStats {
+            running: true,
+            plc_ip: config.plc_ip.clone(),
+            interval_ms: config.interval_ms,
+            frame_size: frame.len(),
+            started_at_ms: chrono::Utc::now().timestamp_millis(),
+            ..Default::default()
+        }));
+        let (stop_tx, stop_rx) = oneshot::channel();
+        println!("🧪 Simulador: PLC {} com frame de {} bytes a cada {}ms", config.plc_ip, frame.len(), config.interval_ms);
+        let plc_ip = config.plc_ip.clone();
+        let handle = tokio::spawn(run_simulator(tcp_state, config, frame, elements, stats.clone(), stop_rx));
+        Ok(Self { plc_ip, stats, stop_tx, handle })
+    }
+
+    pub fn stats(&self) -> SimulatorStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Para a geração e tira o PLC simulado da lista de conectados
+    pub async fn stop(self, tcp_state: &TcpServerState) {
+        let _ = self.stop_tx.send(());
+        let _ = self.handle.await;
+        if let Some(server) = tcp_state.read().await.as_ref() {
+            server.unregister_simulated_plc(&self.plc_ip).await;
+        }
+        println!("🛑 Simulador: PLC {} parado", self.plc_ip);
+    }
+}
+
+async fn run_simulator(
+    tcp_state: TcpServerState,
+    config: SimulatorConfig,
+    mut frame: Vec<u8>,
+    mut elements: Vec<Element>,
+    stats: Arc<Mutex<SimulatorStats>>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let seed = config.seed.filter(|s| *s != 0)
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or(1) as u64 | 1);
+    let mut rng = SimRng(seed);
+    let toggle_probability = (config.interval_ms as f64 / BIT_TOGGLE_MEAN_MS).min(1.0);
+    let started = Instant::now();
+    let mut tick = tokio::time::interval(Duration::from_millis(config.interval_ms));
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            _ = tick.tick() => {
+                update_frame(&mut frame, &mut elements, started.elapsed().as_secs_f64(), toggle_probability, &mut rng);
+                let result = match tcp_state.read().await.as_ref() {
+                    Some(server) => server.inject_frame(&config.plc_ip, &frame).await.map_err(String::from),
+                    None => Err("Servidor TCP não está rodando".to_string()),
+                };
+                let mut stats = stats.lock().unwrap();
+                match result {
+                    Ok(()) => {
+                        stats.frames_sent += 1;
+                        stats.last_error = None;
+                    }
+                    Err(e) => {
+                        if stats.last_error.is_none() {
+                            println!("⚠️ Simulador: frame de {} não injetado: {}", config.plc_ip, e);
+                        }
+                        stats.last_error = Some(e);
+                    }
+                }
+            }
+        }
+    }
+    stats.lock().unwrap().running = false;
+}
@@ -0,0 +1,210 @@
+// ============================================================================
+// MODO SUPERVISOR - REINICIA O HMI SE O PROCESSO CAIR OU TRAVAR
+// ============================================================================
+//
+// Uso: plc-hmi --supervise [--health-url URL] [--max-failures N]
+//                          [--check-interval-s S] [--startup-grace-s S] [--log ARQUIVO]
+//
+// O processo pai não abre janela nem banco: só inicia o HMI como filho e o
+// reinicia quando
+//   - o filho cai (crash, kill, exit != 0), ou
+//   - o /healthz (ver health.rs) falha N vezes seguidas.
+// Exit 0 é encerramento normal (ex: operador fechou a janela): o supervisor
+// registra a parada e também termina.
+// Cada reinício é registrado no log e o motivo é repassado ao novo processo
+// pela variável PLC_HMI_RESTART_REASON (vira notificação "backend-restarted").
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+pub const RESTART_REASON_ENV: &str = "PLC_HMI_RESTART_REASON";
+pub const RESTART_COUNT_ENV: &str = "PLC_HMI_RESTART_COUNT";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_UPTIME_FOR_RESET: Duration = Duration::from_secs(60);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+struct SupervisorOptions {
+    health_url: Option<String>,
+    max_failures: u32,
+    check_interval: Duration,
+    startup_grace: Duration,
+    log_path: std::path::PathBuf,
+    child_args: Vec<String>,
+}
+
+impl SupervisorOptions {
+    fn from_args() -> Self {
+        let mut options = Self {
+            health_url: None,
+            max_failures: 3,
+            check_interval: Duration::from_secs(10),
+            startup_grace: Duration::from_secs(30),
+            log_path: std::env::temp_dir().join("plc-hmi-supervisor.log"),
+            child_args: Vec::new(),
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--supervise" => {}
+                "--health-url" => options.health_url = args.next(),
+                "--max-failures" => {
+                    options.max_failures = args.next().and_then(|v| v.parse().ok()).unwrap_or(3).max(1)
+                }
+                "--check-interval-s" => {
+                    options.check_interval = Duration::from_secs(args.next().and_then(|v| v.parse().ok()).unwrap_or(10).max(1))
+                }
+                "--startup-grace-s" => {
+                    options.startup_grace = Duration::from_secs(args.next().and_then(|v| v.parse().ok()).unwrap_or(30))
+                }
+                "--log" => {
+                    if let Some(path) = args.next() {
+                        options.log_path = path.into();
+                    }
+                }
+                // Qualquer outro argumento é repassado ao HMI
+                _ => options.child_args.push(arg),
+            }
+        }
+        options
+    }
+}
+
+fn log_line(options: &SupervisorOptions, message: &str) {
+    let line = format!("[{}] {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+    println!("🛡️ {}", line);
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&options.log_path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// GET simples no /healthz; Ok(()) somente para HTTP 200
+fn probe_health(url: &str) -> Result<(), String> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| "Só URLs http:// são suportadas".to_string())?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/healthz"),
+    };
+    let address = authority.to_socket_addrs()
+        .map_err(|e| format!("Endereço inválido '{}': {}", authority, e))?
+        .next()
+        .ok_or_else(|| format!("Endereço não resolvido: {}", authority))?;
+
+    let mut stream = TcpStream::connect_timeout(&address, HTTP_TIMEOUT).map_err(|e| format!("Conexão recusada: {}", e))?;
+    stream.set_read_timeout(Some(HTTP_TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(HTTP_TIMEOUT)).map_err(|e| e.to_string())?;
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, authority)
+        .map_err(|e| format!("Erro ao enviar requisição: {}", e))?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(|e| format!("Sem resposta: {}", e))?;
+    let status = response.split_whitespace().nth(1).unwrap_or("");
+    if status == "200" {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", if status.is_empty() { "?" } else { status }))
+    }
+}
+
+fn spawn_child(options: &SupervisorOptions, restart_reason: Option<&str>, restart_count: u32) -> std::io::Result<Child> {
+    let exe = std::env::current_exe()?;
+    let mut command = Command::new(exe);
+    command.args(&options.child_args);
+    if let Some(reason) = restart_reason {
+        command.env(RESTART_REASON_ENV, reason);
+        command.env(RESTART_COUNT_ENV, restart_count.to_string());
+    }
+    command.spawn()
+}
+
+/// Como o filho terminou
+enum ChildExit {
+    Stopped,          // Exit 0: encerramento pedido, não reiniciar
+    Restart(String),  // Motivo do reinício
+}
+
+/// Aguarda o filho encerrar ou o health check estourar o limite de falhas
+fn watch_child(options: &SupervisorOptions, child: &mut Child) -> ChildExit {
+    let started = Instant::now();
+    let mut consecutive_failures = 0u32;
+    let poll = Duration::from_millis(500);
+    let mut next_check = started + options.startup_grace;
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return ChildExit::Stopped,
+            Ok(Some(status)) => return ChildExit::Restart(format!("Processo encerrou ({})", status)),
+            Ok(None) => {}
+            Err(e) => return ChildExit::Restart(format!("Erro ao consultar processo: {}", e)),
+        }
+
+        if let Some(url) = &options.health_url {
+            if Instant::now() >= next_check {
+                next_check = Instant::now() + options.check_interval;
+                match probe_health(url) {
+                    Ok(()) => consecutive_failures = 0,
+                    Err(e) => {
+                        consecutive_failures += 1;
+                        log_line(options, &format!("Health check falhou ({}/{}): {}", consecutive_failures, options.max_failures, e));
+                        if consecutive_failures >= options.max_failures {
+                            let _ = child.kill();
+                            let _ = child.wait();
+                            return ChildExit::Restart(format!("Health check falhou {} vezes seguidas (último erro: {})", consecutive_failures, e));
+                        }
+                    }
+                }
+            }
+        }
+
+        std::thread::sleep(poll);
+    }
+}
+
+/// Loop do supervisor (retorna quando o HMI encerra normalmente ou não pode ser reiniciado)
+pub fn run() -> i32 {
+    let options = SupervisorOptions::from_args();
+    log_line(&options, &format!(
+        "Supervisor iniciado (health: {}, falhas máx: {}, log: {})",
+        options.health_url.as_deref().unwrap_or("desativado"),
+        options.max_failures,
+        options.log_path.display()
+    ));
+
+    let mut restart_reason: Option<String> = None;
+    let mut restart_count = 0u32;
+    let mut backoff = Duration::from_secs(1);
+
+    loop {
+        let started = Instant::now();
+        let mut child = match spawn_child(&options, restart_reason.as_deref(), restart_count) {
+            Ok(child) => child,
+            Err(e) => {
+                log_line(&options, &format!("❌ Não foi possível iniciar o HMI: {}", e));
+                return 1;
+            }
+        };
+        log_line(&options, &format!("HMI iniciado (pid {})", child.id()));
+
+        let reason = match watch_child(&options, &mut child) {
+            ChildExit::Restart(reason) => reason,
+            ChildExit::Stopped => {
+                log_line(&options, "HMI encerrado normalmente (exit 0): supervisor finalizado");
+                return 0;
+            }
+        };
+        restart_count += 1;
+        log_line(&options, &format!("🔄 Reiniciando HMI (#{}): {}", restart_count, reason));
+
+        // Quedas logo após iniciar: espera crescente para não entrar em loop
+        if started.elapsed() >= MIN_UPTIME_FOR_RESET {
+            backoff = Duration::from_secs(1);
+        } else {
+            std::thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+        restart_reason = Some(reason);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::database::{Database, PlcAlias};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// ============================================================================
+// REGISTRO GLOBAL DE TAGS - NOMES QUALIFICADOS ENTRE PLCs
+// ============================================================================
+//
+// Os comandos de tag são todos por `plc_ip`; um dashboard com tags de vários
+// PLCs precisava carregar o IP junto de cada nome. Aqui cada tag ganha um nome
+// qualificado único `apelido.tag` (ex: "forno1.temperatura"). O apelido vem da
+// tabela `plc_aliases`; sem apelido, é derivado do IP ("plc_192_168_1_10").
+// O registro é montado na hora a partir de tag_mappings + estruturas (tipo do
+// dado), então não há cache para invalidar. Nomes repetidos são reportados:
+//   - "fqn": o mesmo tag_name duas vezes no mesmo PLC (nome qualificado ambíguo);
+//   - "tag_name": o mesmo tag_name em PLCs diferentes (ambíguo sem o apelido).
+
+pub const MAX_ALIAS_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredTag {
+    pub fqn: String,                   // "apelido.tag_name"
+    pub plc_ip: String,
+    pub plc_alias: String,
+    pub tag_name: String,
+    pub variable_path: String,
+    pub data_type: Option<String>,     // Tipo na estrutura (BOOL para bit, EDGE para RISE/FALL)
+    pub unit: Option<String>,
+    pub description: Option<String>,
+    pub area: Option<String>,
+    pub category: Option<String>,
+    pub enabled: bool,
+    pub duplicate: bool,               // Nome qualificado repetido: resolve_tag recusa
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateTagName {
+    pub kind: String,                  // "fqn" | "tag_name"
+    pub name: String,
+    pub plc_ips: Vec<String>,
+    pub count: usize,
+}
+
+/// Filtros do navegador de tags (todos opcionais)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TagFilter {
+    #[serde(default)]
+    pub plc: Option<String>,           // IP ou apelido
+    #[serde(default)]
+    pub data_type: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub search: Option<String>,        // Trecho do nome qualificado ou da descrição
+}
+
+/// Apelido derivado do IP para PLCs sem apelido definido
+pub fn default_alias(plc_ip: &str) -> String {
+    let mut alias = String::from("plc_");
+    alias.extend(plc_ip.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }));
+    alias
+}
+
+pub fn validate_alias(alias: &str) -> Result<(), String> {
+    if alias.is_empty() || alias.len() > MAX_ALIAS_LEN {
+        return Err(format!("Apelido deve ter de 1 a {} caracteres", MAX_ALIAS_LEN));
+    }
+    if !alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("Apelido '{}' inválido: use letras, números, '_' ou '-' (o '.' separa apelido e tag)", alias));
+    }
+    Ok(())
+}
+
+/// Apelido de cada PLC: o definido ou o derivado do IP
+fn alias_map(db: &Database, plc_ips: &[String]) -> Result<HashMap<String, String>, String> {
+    let defined: HashMap<String, String> = db.list_plc_aliases()
+        .map_err(|e| format!("Erro ao carregar apelidos dos PLCs: {}", e))?
+        .into_iter()
+        .map(|a| (a.plc_ip, a.alias))
+        .collect();
+    Ok(plc_ips.iter()
+        .map(|ip| (ip.clone(), defined.get(ip).cloned().unwrap_or_else(|| default_alias(ip))))
+        .collect())
+}
+
+/// Confere se o apelido já está em uso (definido ou derivado) por outro PLC
+pub fn check_alias_available(db: &Database, plc_ip: &str, alias: &str) -> Result<(), String> {
+    validate_alias(alias)?;
+    match list_aliases(db)?.into_iter().find(|a| a.plc_ip != plc_ip && a.alias.eq_ignore_ascii_case(alias)) {
+        Some(other) => Err(format!("Apelido '{}' já é usado pelo PLC {}", alias, other.plc_ip)),
+        None => Ok(()),
+    }
+}
+
+fn data_type_of(structure: Option<&crate::database::PlcStructureConfig>, variable_path: &str) -> Option<String> {
+    if crate::websocket_server::parse_edge_path(variable_path).is_some() {
+        return Some("EDGE".to_string());
+    }
+    let target = crate::plc_write::resolve_write_target(structure?, variable_path).ok()?;
+    Some(if target.bit.is_some() { "BOOL".to_string() } else { target.data_type })
+}
+
+/// Todos os tags de todos os PLCs, com nomes qualificados (ordem: apelido, tag)
+pub fn build(db: &Database) -> Result<(Vec<RegisteredTag>, Vec<DuplicateTagName>), String> {
+    let plc_ips = db.list_plcs_with_tags().map_err(|e| format!("Erro ao listar PLCs: {}", e))?;
+    let aliases = alias_map(db, &plc_ips)?;
+
+    let mut tags = Vec::new();
+    for plc_ip in &plc_ips {
+        let structure = db.load_plc_structure(plc_ip).ok().flatten();
+        let mappings = db.load_tag_mappings(plc_ip)
+            .map_err(|e| format!("Erro ao carregar tags do PLC {}: {}", plc_ip, e))?;
+        let alias = &aliases[plc_ip];
+        for mapping in mappings {
+            tags.push(RegisteredTag {
+                fqn: format!("{}.{}", alias, mapping.tag_name),
+                plc_ip: plc_ip.clone(),
+                plc_alias: alias.clone(),
+                data_type: data_type_of(structure.as_ref(), &mapping.variable_path),
+                tag_name: mapping.tag_name,
+                variable_path: mapping.variable_path,
+                unit: mapping.display_unit.or(mapping.unit),
+                description: mapping.description,
+                area: mapping.area,
+                category: mapping.category,
+                enabled: mapping.enabled,
+                duplicate: false,
+            });
+        }
+    }
+    tags.sort_by(|a, b| a.fqn.to_lowercase().cmp(&b.fqn.to_lowercase()));
+
+    // Repetições: nome qualificado (mesmo PLC) e tag_name entre PLCs
+    let mut by_fqn: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut by_name: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, tag) in tags.iter().enumerate() {
+        by_fqn.entry(tag.fqn.to_lowercase()).or_default().push(i);
+        by_name.entry(tag.tag_name.to_lowercase()).or_default().push(i);
+    }
+    let mut duplicates = Vec::new();
+    for indices in by_fqn.values().filter(|i| i.len() > 1) {
+        for &i in indices {
+            tags[i].duplicate = true;
+        }
+        duplicates.push(DuplicateTagName {
+            kind: "fqn".to_string(),
+            name: tags[indices[0]].fqn.clone(),
+            plc_ips: vec![tags[indices[0]].plc_ip.clone()],
+            count: indices.len(),
+        });
+    }
+    for indices in by_name.values() {
+        let mut plc_ips: Vec<String> = indices.iter().map(|&i| tags[i].plc_ip.clone()).collect();
+        plc_ips.sort();
+        plc_ips.dedup();
+        if plc_ips.len() > 1 {
+            duplicates.push(DuplicateTagName {
+                kind: "tag_name".to_string(),
+                name: tags[indices[0]].tag_name.clone(),
+                plc_ips,
+                count: indices.len(),
+            });
+        }
+    }
+    Ok((tags, duplicates))
+}
+
+pub fn list(db: &Database, filter: &TagFilter) -> Result<Vec<RegisteredTag>, String> {
+    let (tags, _) = build(db)?;
+    let plc = filter.plc.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    let data_type = filter.data_type.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let search = filter.search.as_deref().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+    Ok(tags.into_iter()
+        .filter(|t| plc.map_or(true, |p| t.plc_ip == p || t.plc_alias.eq_ignore_ascii_case(p)))
+        .filter(|t| data_type.map_or(true, |d| t.data_type.as_deref().is_some_and(|dt| dt.eq_ignore_ascii_case(d))))
+        .filter(|t| filter.enabled.map_or(true, |e| t.enabled == e))
+        .filter(|t| search.as_ref().map_or(true, |s| {
+            t.fqn.to_lowercase().contains(s) || t.description.as_deref().is_some_and(|d| d.to_lowercase().contains(s))
+        }))
+        .collect())
+}
+
+/// Nome qualificado → tag (PLC + nome local). Nomes repetidos não resolvem.
+pub fn resolve(db: &Database, fqn: &str) -> Result<RegisteredTag, String> {
+    let (alias, tag_name) = fqn.trim().split_once('.')
+        .ok_or_else(|| format!("Nome '{}' sem apelido do PLC (use apelido.tag)", fqn))?;
+    let (tags, _) = build(db)?;
+    let mut matches = tags.into_iter()
+        .filter(|t| t.plc_alias.eq_ignore_ascii_case(alias) && t.tag_name.eq_ignore_ascii_case(tag_name));
+    let tag = matches.next().ok_or_else(|| format!("Tag '{}' não encontrado", fqn))?;
+    if tag.duplicate || matches.next().is_some() {
+        return Err(format!("Nome '{}' é ambíguo: o tag aparece mais de uma vez no PLC {}", fqn, tag.plc_ip));
+    }
+    Ok(tag)
+}
+
+/// Apelidos de todos os PLCs conhecidos (definidos e derivados)
+pub fn list_aliases(db: &Database) -> Result<Vec<PlcAlias>, String> {
+    let defined = db.list_plc_aliases().map_err(|e| format!("Erro ao carregar apelidos dos PLCs: {}", e))?;
+    let mut plc_ips = db.list_plcs_with_tags().map_err(|e| format!("Erro ao listar PLCs: {}", e))?;
+    plc_ips.extend(db.list_configured_plcs().map_err(|e| format!("Erro ao listar PLCs: {}", e))?);
+    plc_ips.sort();
+    plc_ips.dedup();
+    let mut aliases = defined.clone();
+    for ip in plc_ips.into_iter().filter(|ip| !defined.iter().any(|a| &a.plc_ip == ip)) {
+        aliases.push(PlcAlias { alias: default_alias(&ip), plc_ip: ip, updated_at: 0 });
+    }
+    aliases.sort_by(|a, b| a.alias.to_lowercase().cmp(&b.alias.to_lowercase()));
+    Ok(aliases)
+}
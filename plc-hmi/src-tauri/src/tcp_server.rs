@@ -4,16 +4,22 @@
 // ============================================================================
 
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{RwLock, Mutex, mpsc};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri::ipc::Channel;
 use crate::database::Database;
 use crate::database::PlcStructureConfig;
+use plc_core::connection::{read_or_write, FrameEvent, FrameReader, SocketEvent};
+use crate::packet_rate::{PacketRateMonitor, PlcRateStatus, RateLevel};
+use crate::plc_write::{PendingReadbacks, PendingWrite, PendingWrites, Readback};
+use crate::incident_capture;
+use crate::parse_quarantine::{self, ParseErrorKind};
+use crate::error::AppError;
 
 // ============================================================================
 // CONSTANTES DE CONFIGURAÇÃO - OTIMIZADAS PARA PLC SIEMENS 2Hz
@@ -33,6 +39,8 @@ const FRAGMENT_CLEAR_SECS: u64 = 5;
 const WATCHDOG_CHECK_INTERVAL_MS: u64 = 2000;
 // ✅ OTIMIZAÇÃO: Capacidade reduzida para evitar acúmulo de eventos
 const EVENT_CHANNEL_CAPACITY: usize = 500; // Reduzido de 1000 para 500
+// Frames de escrita aguardando envio por conexão
+const WRITE_CHANNEL_CAPACITY: usize = 32;
 
 // ============================================================================
 // BUFFER POOL
@@ -161,22 +169,8 @@ pub struct PlcData {
     pub variables: HashMap<String, f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlcVariable {
-    pub name: String,
-    pub value: String,
-    pub data_type: String,
-    pub unit: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlcDataPacket {
-    pub ip: String,
-    pub timestamp: u64,
-    pub raw_data: Vec<u8>,
-    pub size: usize,
-    pub variables: Vec<PlcVariable>,
-}
+// 🆕 Variáveis e pacote parseado (definidos no plc-core, junto com o parser)
+pub use plc_core::parser::{PlcDataPacket, PlcVariable};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionStats {
@@ -187,6 +181,21 @@ pub struct ConnectionStats {
     pub plc_status: String,
 }
 
+// 🆕 RESULTADO DE OPERAÇÕES EM LOTE NAS CONEXÕES (paleta de comandos)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionBatchFailure {
+    pub ip: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionBatchResult {
+    pub operation: String,
+    pub affected: Vec<String>,              // IPs em que a operação foi aplicada
+    pub failed: Vec<ConnectionBatchFailure>,
+    pub summary: String,
+}
+
 enum ConnectionResult {
     Normal(u64),
     Timeout(String),
@@ -202,6 +211,31 @@ enum TcpEvent {
     PlcDataStats(serde_json::Value),
 }
 
+// ============================================================================
+// CANAIS DE DADOS DE ALTA FREQUÊNCIA (Tauri Channels)
+// ============================================================================
+
+/// Assinatura do frontend para receber pacotes via `Channel` em vez de eventos globais
+struct DataChannelSubscription {
+    plc_ips: Option<HashSet<String>>, // None = todos os PLCs
+    decimation: u32,                  // Entregar 1 a cada N pacotes (por PLC)
+    counters: HashMap<String, u64>,
+    channel: Channel<serde_json::Value>,
+}
+
+impl DataChannelSubscription {
+    fn accepts(&mut self, ip: &str) -> bool {
+        if let Some(ips) = &self.plc_ips {
+            if !ips.contains(ip) {
+                return false;
+            }
+        }
+        let counter = self.counters.entry(ip.to_string()).or_insert(0);
+        *counter += 1;
+        (*counter - 1) % self.decimation.max(1) as u64 == 0
+    }
+}
+
 // ============================================================================
 // TCP SERVER
 // ============================================================================
@@ -217,7 +251,7 @@ pub struct TcpServer {
     connected_clients: Arc<RwLock<Vec<String>>>,
     connection_handles: Arc<RwLock<HashMap<String, tokio::task::AbortHandle>>>,
     unique_plcs: Arc<RwLock<HashSet<String>>>,
-    blacklisted_ips: Arc<RwLock<HashSet<String>>>,
+    blacklisted_ips: Arc<RwLock<HashMap<String, Option<std::time::Instant>>>>, // IP -> fim do bloqueio (None = até desbloqueio manual)
     ip_to_id: Arc<RwLock<HashMap<String, u64>>>,
     bytes_received: Arc<RwLock<HashMap<String, u64>>>,
     latest_data: Arc<DashMap<String, PlcDataPacket>>,
@@ -226,10 +260,23 @@ pub struct TcpServer {
     plc_configs_cache: Arc<DashMap<String, PlcStructureConfig>>,
     connection_health: Arc<DashMap<String, ConnectionHealth>>,
     event_sender: Option<mpsc::Sender<TcpEvent>>,
+    data_channels: Arc<DashMap<u32, DataChannelSubscription>>,
+    next_channel_id: Arc<AtomicU32>,
+    packet_rate: Arc<PacketRateMonitor>,
+    // 🆕 ESCRITA NO PLC: frames a enviar por conexão + confirmações e leituras de volta pendentes
+    write_channels: Arc<DashMap<String, mpsc::Sender<Vec<u8>>>>,
+    pending_writes: Arc<PendingWrites>,
+    pending_readbacks: Arc<PendingReadbacks>,
+    next_write_seq: Arc<AtomicU16>,
 }
 
 impl TcpServer {
     pub fn new(port: u16, app_handle: AppHandle, database: Option<Arc<Database>>) -> Self {
+        let packet_rate = Arc::new(PacketRateMonitor::default());
+        if let Some(expectations) = database.as_ref().and_then(|db| db.load_plc_rate_expectations().ok()) {
+            packet_rate.set_expectations(expectations);
+        }
+        
         Self {
             port,
             is_running: Arc::new(AtomicBool::new(false)),
@@ -241,7 +288,7 @@ impl TcpServer {
             connected_clients: Arc::new(RwLock::new(Vec::new())),
             connection_handles: Arc::new(RwLock::new(HashMap::new())),
             unique_plcs: Arc::new(RwLock::new(HashSet::new())),
-            blacklisted_ips: Arc::new(RwLock::new(HashSet::new())),
+            blacklisted_ips: Arc::new(RwLock::new(HashMap::new())),
             ip_to_id: Arc::new(RwLock::new(HashMap::new())),
             bytes_received: Arc::new(RwLock::new(HashMap::new())),
             latest_data: Arc::new(DashMap::new()),
@@ -250,6 +297,13 @@ impl TcpServer {
             plc_configs_cache: Arc::new(DashMap::new()),
             connection_health: Arc::new(DashMap::new()),
             event_sender: None,
+            data_channels: Arc::new(DashMap::new()),
+            next_channel_id: Arc::new(AtomicU32::new(1)),
+            packet_rate,
+            write_channels: Arc::new(DashMap::new()),
+            pending_writes: Arc::new(DashMap::new()),
+            pending_readbacks: Arc::new(DashMap::new()),
+            next_write_seq: Arc::new(AtomicU16::new(1)),
         }
     }
 
@@ -258,12 +312,24 @@ impl TcpServer {
         self.event_sender = Some(tx);
         
         let app_handle = self.app_handle.clone();
+        let data_channels = self.data_channels.clone();
         
         let handle = tokio::spawn(async move {
             while let Some(event) = rx.recv().await {
                 match event {
                     TcpEvent::PlcDataReceived(data) => {
-                        let _ = app_handle.emit("plc-data-received", data);
+                        // 🚀 Alta frequência: somente via Channels assinados pelo frontend
+                        let ip = data.get("ip").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        let mut closed = Vec::new();
+                        for mut entry in data_channels.iter_mut() {
+                            if entry.value_mut().accepts(&ip) && entry.value().channel.send(data.clone()).is_err() {
+                                closed.push(*entry.key());
+                            }
+                        }
+                        for id in closed {
+                            data_channels.remove(&id);
+                            println!("📪 Canal de dados {} removido (frontend fechado)", id);
+                        }
                     }
                     TcpEvent::WebSocketCacheUpdate(data) => {
                         let _ = app_handle.emit("websocket-cache-update", data);
@@ -281,14 +347,14 @@ impl TcpServer {
         self.event_emitter_handle = Some(handle);
     }
 
-    pub async fn start_server(&mut self) -> Result<String, String> {
+    pub async fn start_server(&mut self) -> Result<String, AppError> {
         if self.is_running.load(Ordering::SeqCst) {
-            return Err("Servidor já está rodando".to_string());
+            return Err(AppError::AlreadyRunning("Servidor já está rodando".to_string()));
         }
 
         let listener = match TcpListener::bind(format!("0.0.0.0:{}", self.port)).await {
             Ok(l) => l,
-            Err(e) => return Err(format!("Erro ao fazer bind na porta {}: {}", self.port, e)),
+            Err(e) => return Err(AppError::Io(format!("Erro ao fazer bind na porta {}: {}", self.port, e))),
         };
 
         self.is_running.store(true, Ordering::SeqCst);
@@ -311,6 +377,9 @@ impl TcpServer {
         let plc_configs_cache = self.plc_configs_cache.clone();
         let connection_health = self.connection_health.clone();
         let event_sender = self.event_sender.clone();
+        let write_channels = self.write_channels.clone();
+        let pending_writes = self.pending_writes.clone();
+        let pending_readbacks = self.pending_readbacks.clone();
         let port = self.port;
 
         let handle = tokio::spawn(async move {
@@ -318,7 +387,7 @@ impl TcpServer {
             println!("🚀 SERVIDOR TCP INICIADO NA PORTA {}", port);
             println!("═══════════════════════════════════════════════════════════");
             println!("⚡ Otimizado para PLC Siemens S7-1500 (TSEND_C @ 2Hz)");
-            println!("📡 Modo: SOMENTE RECEPÇÃO (sem ACK)");
+            println!("📡 Modo: RECEPÇÃO (sem ACK) + escrita sob demanda (WRTE/WACK)");
             println!("⏱️  Timeout leitura: {}s | Inatividade: {}s", READ_TIMEOUT_SECS, INACTIVITY_TIMEOUT_SECS);
             println!("═══════════════════════════════════════════════════════════");
             
@@ -334,7 +403,8 @@ impl TcpServer {
                     Ok(Ok((socket, addr))) => {
                         let ip = addr.ip().to_string();
                         
-                        if blacklisted_ips.read().await.contains(&ip) {
+                        expire_blocks(&blacklisted_ips, &app_handle).await;
+                        if blacklisted_ips.read().await.contains_key(&ip) {
                             println!("🚫 CONEXÃO RECUSADA: {} (bloqueado)", ip);
                             drop(socket);
                             continue;
@@ -380,6 +450,7 @@ impl TcpServer {
                         let total_unique = unique_plcs.read().await.len() as u64;
                         
                         println!("✅ PLC CONECTADO: {} (ID: {}) | Ativos: {}", ip, conn_id, current_active);
+                        incident_capture::debug(&ip, format!("conexão #{} aceita ({} ativas)", conn_id, current_active));
                         
                         let _ = app_handle.emit("plc-connected", serde_json::json!({
                             "id": conn_id,
@@ -408,6 +479,11 @@ impl TcpServer {
                         let event_sender_clone = event_sender.clone();
                         let ip_clone = ip.clone();
                         let is_running_clone = is_running.clone();
+                        let (write_tx, write_rx) = mpsc::channel::<Vec<u8>>(WRITE_CHANNEL_CAPACITY);
+                        write_channels.insert(ip.clone(), write_tx.clone());
+                        let write_channels_clone = write_channels.clone();
+                        let pending_writes_clone = pending_writes.clone();
+                        let pending_readbacks_clone = pending_readbacks.clone();
 
                         let connection_handle = tokio::spawn(async move {
                             let result = handle_client_connection(
@@ -416,7 +492,10 @@ impl TcpServer {
                                 app_handle_clone.clone(), database_clone.clone(),
                                 buffer_pool_clone.clone(), plc_configs_cache_clone.clone(),
                                 connection_health_clone.clone(), event_sender_clone,
+                                write_rx, pending_writes_clone, pending_readbacks_clone,
                             ).await;
+                            // Reconexão já pode ter registrado o canal da nova conexão
+                            write_channels_clone.remove_if(&ip_clone, |_, tx| tx.same_channel(&write_tx));
                             
                             let should_cleanup = {
                                 if let Some(mut health) = connection_health_clone.get_mut(&ip_clone) {
@@ -434,12 +513,14 @@ impl TcpServer {
                                     }
                                     ConnectionResult::Timeout(reason) => {
                                         println!("⏰ PLC {} timeout: {}", ip_clone, reason);
+                                        incident_capture::debug(&ip_clone, format!("conexão #{} encerrada por timeout: {}", conn_id, reason));
                                         let _ = app_handle_clone.emit("tcp-connection-timeout", serde_json::json!({
                                             "ip": ip_clone, "id": conn_id, "reason": reason
                                         }));
                                     }
                                     ConnectionResult::Error(error) => {
                                         println!("❌ PLC {} erro: {}", ip_clone, error);
+                                        incident_capture::debug(&ip_clone, format!("conexão #{} encerrada por erro: {}", conn_id, error));
                                         let _ = app_handle_clone.emit("tcp-connection-error", serde_json::json!({
                                             "ip": ip_clone, "id": conn_id, "error": error
                                         }));
@@ -496,6 +577,8 @@ impl TcpServer {
         let connected_clients = self.connected_clients.clone();
         let active_connections = self.active_connections.clone();
         let app_handle = self.app_handle.clone();
+        let blacklisted_ips = self.blacklisted_ips.clone();
+        let packet_rate = self.packet_rate.clone();
         
         let watchdog = tokio::spawn(async move {
             println!("🐕 WATCHDOG INICIADO");
@@ -507,6 +590,8 @@ impl TcpServer {
             while is_running.load(Ordering::SeqCst) {
                 interval.tick().await;
                 
+                expire_blocks(&blacklisted_ips, &app_handle).await;
+                
                 let now = std::time::Instant::now();
                 let mut dead_connections: Vec<String> = Vec::new();
                 
@@ -518,6 +603,7 @@ impl TcpServer {
                     
                     if seconds_since_data > INACTIVITY_TIMEOUT_SECS {
                         println!("🚨 WATCHDOG: {} MORTA! Sem dados há {}s", health.ip, seconds_since_data);
+                        incident_capture::debug(&health.ip, format!("watchdog: sem dados há {}s ({} pacotes, {} bytes)", seconds_since_data, health.packet_count, health.total_bytes));
                         dead_connections.push(health.ip.clone());
                         
                         let _ = app_handle.emit("tcp-connection-dead", serde_json::json!({
@@ -530,13 +616,27 @@ impl TcpServer {
                         }));
                     } else if seconds_since_data > INACTIVITY_TIMEOUT_SECS / 2 {
                         println!("⚠️ WATCHDOG: {} LENTA! Sem dados há {}s", health.ip, seconds_since_data);
+                        incident_capture::debug(&health.ip, format!("watchdog: conexão lenta, sem dados há {}s", seconds_since_data));
                         let _ = app_handle.emit("tcp-connection-slow", serde_json::json!({
                             "ip": health.ip,
                             "id": health.conn_id,
                             "seconds_since_data": seconds_since_data
                         }));
                     }
+                    
+                    // 🆕 Taxa real x taxa esperada (só emite quando o nível muda)
+                    if let Some(status) = packet_rate.observe(&health.ip, health.packet_count, now) {
+                        if status.level == RateLevel::Ok {
+                            println!("✅ TAXA: {} voltou ao normal ({} ms esperado)", status.plc_ip, status.expected_interval_ms);
+                            let _ = app_handle.emit("plc-rate-normal", &status);
+                        } else {
+                            println!("📉 TAXA: {} {:?} - intervalo real {:?} ms, esperado {} ms",
+                                status.plc_ip, status.level, status.actual_interval_ms.map(|v| v.round()), status.expected_interval_ms);
+                            let _ = app_handle.emit("plc-rate-deviation", &status);
+                        }
+                    }
                 }
+                packet_rate.retain_connected(|ip| connection_health.contains_key(ip));
                 
                 for ip in dead_connections {
                     let should_remove = {
@@ -566,9 +666,9 @@ impl TcpServer {
         self.watchdog_handle = Some(watchdog);
     }
 
-    pub async fn stop_server(&mut self) -> Result<String, String> {
+    pub async fn stop_server(&mut self) -> Result<String, AppError> {
         if !self.is_running.load(Ordering::SeqCst) {
-            return Err("Servidor não está rodando".to_string());
+            return Err(AppError::tcp_not_running());
         }
 
         println!("🛑 PARANDO SERVIDOR TCP...");
@@ -585,6 +685,7 @@ impl TcpServer {
         }
         
         self.connection_health.clear();
+        self.write_channels.clear();
         if let Some(handle) = self.server_handle.take() { handle.abort(); }
         
         self.active_connections.store(0, Ordering::SeqCst);
@@ -597,21 +698,25 @@ impl TcpServer {
         Ok("Servidor TCP parado".to_string())
     }
 
-    pub async fn disconnect_client(&self, client_ip: String) -> Result<String, String> {
+    /// Desconecta e bloqueia o IP; com `block_duration` o bloqueio expira sozinho
+    pub async fn disconnect_client(&self, client_ip: String, block_duration: Option<std::time::Duration>) -> Result<String, AppError> {
         println!("🔌 DESCONECTANDO: {}", client_ip);
-        self.blacklisted_ips.write().await.insert(client_ip.clone());
+        let expires_at = block_duration.map(|d| std::time::Instant::now() + d);
+        self.blacklisted_ips.write().await.insert(client_ip.clone(), expires_at);
         
         let mut handles = self.connection_handles.write().await;
         if let Some(handle) = handles.remove(&client_ip) {
             handle.abort();
             self.connection_health.remove(&client_ip);
+            self.write_channels.remove(&client_ip);
             self.connected_clients.write().await.retain(|ip| ip != &client_ip);
             
             let remaining = self.active_connections.fetch_sub(1, Ordering::SeqCst).saturating_sub(1);
             let total_unique = self.unique_plcs.read().await.len() as u64;
             
             let _ = self.app_handle.emit("plc-force-disconnected", serde_json::json!({
-                "ip": client_ip.clone(), "blocked": true
+                "ip": client_ip.clone(), "blocked": true,
+                "block_seconds": block_duration.map(|d| d.as_secs())
             }));
             
             let _ = self.app_handle.emit("tcp-stats", serde_json::json!({
@@ -621,21 +726,93 @@ impl TcpServer {
                 "plc_status": if remaining > 0 { "Conectado" } else { "Desconectado" }
             }));
             
-            Ok(format!("PLC {} desconectado e bloqueado", client_ip))
+            match block_duration {
+                Some(d) => Ok(format!("PLC {} desconectado e bloqueado por {} min", client_ip, d.as_secs().div_ceil(60))),
+                None => Ok(format!("PLC {} desconectado e bloqueado", client_ip)),
+            }
         } else {
-            Err(format!("PLC {} não encontrado", client_ip))
+            Err(AppError::PlcNotFound(format!("PLC {} não encontrado", client_ip)))
         }
     }
     
-    pub async fn allow_reconnect(&self, client_ip: String) -> Result<String, String> {
-        if self.blacklisted_ips.write().await.remove(&client_ip) {
+    pub async fn allow_reconnect(&self, client_ip: String) -> Result<String, AppError> {
+        if self.blacklisted_ips.write().await.remove(&client_ip).is_some() {
             println!("✅ {} desbloqueado", client_ip);
             Ok(format!("PLC {} pode reconectar", client_ip))
         } else {
-            Err(format!("PLC {} não estava bloqueado", client_ip))
+            Err(AppError::PlcNotFound(format!("PLC {} não estava bloqueado", client_ip)))
         }
     }
 
+    /// Desconecta (e bloqueia) todos os PLCs conectados
+    pub async fn disconnect_all_clients(&self, block_duration: Option<std::time::Duration>) -> ConnectionBatchResult {
+        let ips: Vec<String> = self.connection_handles.read().await.keys().cloned().collect();
+        let mut affected = Vec::new();
+        let mut failed = Vec::new();
+        for ip in ips {
+            match self.disconnect_client(ip.clone(), block_duration).await {
+                Ok(_) => affected.push(ip),
+                Err(error) => failed.push(ConnectionBatchFailure { ip, error: error.to_string() }),
+            }
+        }
+        let summary = format!("{} PLC(s) desconectado(s) e bloqueado(s), {} falha(s)", affected.len(), failed.len());
+        println!("🔌 {}", summary);
+        ConnectionBatchResult { operation: "disconnect_all".to_string(), affected, failed, summary }
+    }
+
+    /// Remove todos os bloqueios de reconexão
+    pub async fn allow_reconnect_all(&self) -> ConnectionBatchResult {
+        let mut affected: Vec<String> = self.blacklisted_ips.write().await.drain().map(|(ip, _)| ip).collect();
+        affected.sort();
+        let summary = format!("{} PLC(s) desbloqueado(s)", affected.len());
+        println!("✅ {}", summary);
+        ConnectionBatchResult { operation: "allow_reconnect_all".to_string(), affected, failed: Vec::new(), summary }
+    }
+
+    /// Descarta o health de conexões que não existem mais e limpa o último erro das ativas
+    pub async fn clear_health(&self) -> ConnectionBatchResult {
+        let connected = self.connection_handles.read().await;
+        let mut affected = Vec::new();
+        // Conexões ainda abertas mantêm o registro: o watchdog depende dele
+        self.connection_health.retain(|ip, health| {
+            let keep = connected.contains_key(ip);
+            if !keep || health.last_error.is_some() {
+                affected.push(ip.clone());
+            }
+            keep
+        });
+        for mut health in self.connection_health.iter_mut() {
+            health.last_error = None;
+        }
+        affected.sort();
+        let summary = format!("Health limpo para {} conexão(ões)", affected.len());
+        println!("🧹 {}", summary);
+        ConnectionBatchResult { operation: "clear_health".to_string(), affected, failed: Vec::new(), summary }
+    }
+
+    /// Zera bytes recebidos e o histórico de PLCs únicos (mantém os conectados agora)
+    pub async fn reset_statistics(&self) -> ConnectionBatchResult {
+        let mut affected: Vec<String> = self.bytes_received.write().await.drain().map(|(ip, _)| ip).collect();
+        let connected = self.connected_clients.read().await.clone();
+        {
+            let mut unique_plcs = self.unique_plcs.write().await;
+            for ip in unique_plcs.iter() {
+                if !affected.contains(ip) {
+                    affected.push(ip.clone());
+                }
+            }
+            *unique_plcs = connected.into_iter().collect();
+        }
+        affected.sort();
+        
+        let stats = self.get_connection_stats().await;
+        let _ = self.app_handle.emit("tcp-stats", &stats);
+        
+        let summary = format!("Estatísticas zeradas para {} PLC(s)", affected.len());
+        println!("📊 {}", summary);
+        ConnectionBatchResult { operation: "reset_statistics".to_string(), affected, failed: Vec::new(), summary }
+    }
+
     pub async fn get_connection_stats(&self) -> ConnectionStats {
         let active = self.active_connections.load(Ordering::SeqCst);
         let total_unique = self.unique_plcs.read().await.len() as u64;
@@ -651,20 +828,37 @@ impl TcpServer {
         }
     }
 
+    /// Recarrega as taxas esperadas do banco (após salvar/remover pela interface)
+    pub fn reload_rate_expectations(&self) -> Result<(), String> {
+        let db = self.database.as_ref().ok_or("Banco de dados não disponível")?;
+        let expectations = db.load_plc_rate_expectations().map_err(|e| e.to_string())?;
+        self.packet_rate.set_expectations(expectations);
+        Ok(())
+    }
+
+    pub fn get_rate_status(&self) -> Vec<PlcRateStatus> {
+        self.packet_rate.get_status()
+    }
+
     pub async fn get_connected_clients(&self) -> Vec<String> {
         self.connected_clients.read().await.clone()
     }
 
-    pub async fn get_all_known_plcs(&self) -> Vec<(String, String)> {
+    /// (ip, status, segundos restantes de bloqueio - None se não bloqueado ou bloqueio sem prazo)
+    pub async fn get_all_known_plcs(&self) -> Vec<(String, String, Option<u64>)> {
+        expire_blocks(&self.blacklisted_ips, &self.app_handle).await;
         let connected = self.connected_clients.read().await;
         let blacklisted = self.blacklisted_ips.read().await;
         let unique_plcs = self.unique_plcs.read().await;
+        let now = std::time::Instant::now();
         
         unique_plcs.iter().map(|ip| {
-            let status = if blacklisted.contains(ip) { "blocked" }
+            let block = blacklisted.get(ip);
+            let status = if block.is_some() { "blocked" }
                 else if connected.contains(ip) { "connected" }
                 else { "disconnected" };
-            (ip.clone(), status.to_string())
+            let remaining = block.copied().flatten().map(|until| until.saturating_duration_since(now).as_secs());
+            (ip.clone(), status.to_string(), remaining)
         }).collect()
     }
 
@@ -680,10 +874,130 @@ impl TcpServer {
         self.latest_data.get(ip).map(|e| e.value().clone())
     }
 
+    /// Último frame bruto recebido de um PLC (para visualização hex sob demanda)
+    pub fn get_latest_raw_frame(&self, ip: &str) -> Option<(u64, Vec<u8>)> {
+        self.latest_data.get(ip).map(|e| (e.value().timestamp, e.value().raw_data.clone()))
+    }
+
     pub async fn get_all_plc_data(&self) -> HashMap<String, PlcDataPacket> {
         self.latest_data.iter().map(|e| (e.key().clone(), e.value().clone())).collect()
     }
     
+    /// Registra um Channel do frontend para receber pacotes (com filtro de PLC e decimação)
+    pub fn subscribe_data_channel(&self, plc_ips: Option<Vec<String>>, decimation: u32, channel: Channel<serde_json::Value>) -> u32 {
+        let id = self.next_channel_id.fetch_add(1, Ordering::SeqCst);
+        self.data_channels.insert(id, DataChannelSubscription {
+            plc_ips: plc_ips.map(|ips| ips.into_iter().collect()),
+            decimation: decimation.max(1),
+            counters: HashMap::new(),
+            channel,
+        });
+        println!("📬 Canal de dados {} registrado (decimação 1/{})", id, decimation.max(1));
+        id
+    }
+
+    pub fn unsubscribe_data_channel(&self, id: u32) -> bool {
+        self.data_channels.remove(&id).is_some()
+    }
+
+    /// 🆕 SIMULADOR: registra o PLC simulado como conectado (sem socket nem watchdog)
+    pub async fn register_simulated_plc(&self, ip: &str, config: PlcStructureConfig) -> Result<(), AppError> {
+        if !self.is_running() {
+            return Err(AppError::tcp_not_running());
+        }
+        if self.connection_handles.read().await.contains_key(ip) {
+            return Err(AppError::ConfigInvalid(format!("PLC {} está conectado de verdade - simulação recusada", ip)));
+        }
+        self.plc_configs_cache.insert(ip.to_string(), config);
+        let mut connected = self.connected_clients.write().await;
+        if !connected.iter().any(|c| c == ip) {
+            connected.push(ip.to_string());
+        }
+        self.unique_plcs.write().await.insert(ip.to_string());
+        let _ = self.app_handle.emit("plc-connected", serde_json::json!({
+            "id": 0,
+            "address": "simulador",
+            "ip": ip,
+            "simulated": true
+        }));
+        Ok(())
+    }
+
+    pub async fn unregister_simulated_plc(&self, ip: &str) {
+        self.connected_clients.write().await.retain(|c| c != ip);
+        self.reload_plc_config(ip);
+        let _ = self.app_handle.emit("plc-disconnected", serde_json::json!({
+            "id": 0, "ip": ip
+        }));
+    }
+
+    /// 🆕 SIMULADOR: publica um frame como se tivesse chegado do PLC `ip`
+    pub async fn inject_frame(&self, ip: &str, frame: &[u8]) -> Result<(), AppError> {
+        if !self.is_running() {
+            return Err(AppError::tcp_not_running());
+        }
+        *self.bytes_received.write().await.entry(ip.to_string()).or_insert(0) += frame.len() as u64;
+        publish_frame(ip, frame, &self.plc_configs_cache, &self.latest_data, self.event_sender.as_ref());
+        Ok(())
+    }
+
+    /// Recarrega do banco a estrutura cacheada de um PLC (após edição da configuração)
+    pub fn reload_plc_config(&self, ip: &str) {
+        let loaded = self.database.as_ref().and_then(|db| db.load_plc_structure(ip).ok().flatten());
+        match loaded {
+            Some(config) => {
+                println!("🔄 PLC {}: Config recarregada no cache - tamanhos {:?}", ip, crate::plc_parser::known_frame_sizes(&config));
+                self.plc_configs_cache.insert(ip.to_string(), config);
+            }
+            None => {
+                self.plc_configs_cache.remove(ip);
+            }
+        }
+    }
+
+    /// 🆕 Envia um frame de escrita pela conexão do PLC; a confirmação é aguardada
+    /// com `PendingWrite::wait` (sem segurar o lock do servidor)
+    pub fn send_write(&self, plc_ip: &str, variable_path: &str, value: &str) -> Result<PendingWrite, AppError> {
+        let config = match self.plc_configs_cache.get(plc_ip) {
+            Some(config) => config.clone(),
+            None => self.database.as_ref()
+                .and_then(|db| db.load_plc_structure(plc_ip).ok().flatten())
+                .ok_or_else(|| AppError::PlcNotFound(format!("PLC {} sem estrutura configurada", plc_ip)))?,
+        };
+        let target = crate::plc_write::resolve_write_target(&config, variable_path).map_err(AppError::ConfigInvalid)?;
+        let data = crate::plc_write::encode_value(&target, value).map_err(AppError::ConfigInvalid)?;
+        let sender = self.write_channels.get(plc_ip).map(|s| s.clone())
+            .ok_or_else(|| AppError::PlcNotFound(format!("PLC {} não está conectado", plc_ip)))?;
+
+        let seq = self.next_write_seq.fetch_add(1, Ordering::SeqCst);
+        let (reply_tx, reply) = tokio::sync::oneshot::channel();
+        let (readback_tx, readback) = tokio::sync::oneshot::channel();
+        self.pending_writes.insert((plc_ip.to_string(), seq), reply_tx);
+        self.pending_readbacks.insert((plc_ip.to_string(), seq), Readback { target: target.clone(), data: data.clone(), reply: readback_tx });
+        if let Err(e) = sender.try_send(crate::plc_write::encode_write_frame(seq, &target, &data)) {
+            self.pending_writes.remove(&(plc_ip.to_string(), seq));
+            self.pending_readbacks.remove(&(plc_ip.to_string(), seq));
+            return Err(match e {
+                mpsc::error::TrySendError::Full(_) => AppError::Other(format!("Fila de escrita do PLC {} cheia", plc_ip)),
+                mpsc::error::TrySendError::Closed(_) => AppError::PlcNotFound(format!("PLC {} não está conectado", plc_ip)),
+            });
+        }
+        println!("✍️ PLC {}: escrita #{} {} = {} (offset {}, bit {:?})", plc_ip, seq, variable_path, value, target.byte_offset, target.bit);
+
+        Ok(PendingWrite {
+            plc_ip: plc_ip.to_string(),
+            variable_path: variable_path.to_string(),
+            value: value.to_string(),
+            seq,
+            target,
+            started: std::time::Instant::now(),
+            reply,
+            pending: self.pending_writes.clone(),
+            readback,
+            readbacks: self.pending_readbacks.clone(),
+        })
+    }
+
     pub async fn get_connection_health(&self) -> Vec<ConnectionHealth> {
         self.connection_health.iter().map(|e| e.value().clone()).collect()
     }
@@ -695,6 +1009,10 @@ impl TcpServer {
         (buffer_stats.3, cache_size) // (total_buffers_active, cache_entries)
     }
 
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
+
     pub fn get_connected_clients_count(&self) -> usize {
         // Contar conexões ativas baseado no health
         self.connection_health.iter()
@@ -703,10 +1021,161 @@ impl TcpServer {
     }
 }
 
+/// Remove bloqueios temporários vencidos e avisa a UI ("plc-unblocked")
+async fn expire_blocks(blacklisted_ips: &RwLock<HashMap<String, Option<std::time::Instant>>>, app_handle: &AppHandle) {
+    let now = std::time::Instant::now();
+    let expired: Vec<String> = blacklisted_ips.read().await.iter()
+        .filter(|(_, until)| until.is_some_and(|until| until <= now))
+        .map(|(ip, _)| ip.clone())
+        .collect();
+    if expired.is_empty() {
+        return;
+    }
+    let mut blacklisted = blacklisted_ips.write().await;
+    for ip in expired {
+        if blacklisted.remove(&ip).is_some() {
+            println!("⏰ Bloqueio temporário expirado: {} pode reconectar", ip);
+            let _ = app_handle.emit("plc-unblocked", serde_json::json!({ "ip": ip, "automatic": true }));
+        }
+    }
+}
+
+/// Grava no historian as amostras de um frame de backfill com os timestamps
+/// originais. Não mexe no cache de valores atuais nem no WebSocket.
+async fn store_backfill(ip: String, frame: Vec<u8>, config: PlcStructureConfig, database: Arc<Database>, app_handle: AppHandle) {
+    let result: Result<serde_json::Value, String> = async {
+        let (records, rejected) = crate::plc_parser::parse_backfill_frame(&frame, &config)?;
+
+        // Só tags mapeados e habilitados vão para o histórico, com o nome do tag
+        let tag_names: HashMap<String, String> = database.load_tag_mappings(&ip)
+            .map_err(|e| format!("Erro ao carregar tags: {}", e))?
+            .into_iter()
+            .filter(|m| m.enabled)
+            .map(|m| (m.variable_path, m.tag_name))
+            .collect();
+        let samples: Vec<crate::historian::SnapshotValue> = records.iter()
+            .flat_map(|record| record.variables.iter()
+                .filter(|variable| !crate::plc_parser::is_waveform_type(&variable.data_type))
+                .filter_map(|variable| {
+                    tag_names.get(&variable.name).map(|tag_name| crate::historian::SnapshotValue {
+                        plc_ip: ip.clone(),
+                        tag_name: tag_name.clone(),
+                        value: variable.value.clone(),
+                        value_num: variable.value.parse::<f64>().ok(),
+                        ts_ms: record.ts_ms,
+                        unit: None,
+                    })
+                }))
+            .collect();
+        // 🆕 Formas de onda vão como blob para tag_waveforms
+        let waveforms: Vec<crate::historian::WaveformSample> = records.iter()
+            .flat_map(|record| record.variables.iter()
+                .filter(|variable| crate::plc_parser::is_waveform_type(&variable.data_type))
+                .filter_map(|variable| {
+                    let tag_name = tag_names.get(&variable.name)?;
+                    let points = crate::plc_parser::waveform_points(&variable.value)?;
+                    Some(crate::historian::WaveformSample {
+                        plc_ip: ip.clone(),
+                        tag_name: tag_name.clone(),
+                        data_type: variable.data_type.trim_end_matches(crate::plc_parser::WAVEFORM_TYPE_SUFFIX).to_string(),
+                        unit: variable.unit.clone(),
+                        ts_ms: record.ts_ms,
+                        point_count: points.len() as i32,
+                        points,
+                    })
+                }))
+            .collect();
+
+        // Mesmo caminho de failover do historian (primário → secundário → SQLite local)
+        let failover = app_handle.try_state::<Arc<crate::historian_failover::HistorianFailover>>()
+            .ok_or_else(|| "Historian não inicializado".to_string())?;
+        let target = failover.write(&samples).await
+            .map_err(|e| format!("Erro ao gravar backfill: {}", e))?;
+        if !waveforms.is_empty() {
+            failover.write_waveforms(&waveforms).await
+                .map_err(|e| format!("Erro ao gravar formas de onda do backfill: {}", e))?;
+        }
+
+        Ok(serde_json::json!({
+            "ip": ip,
+            "records": records.len(),
+            "rejected_records": rejected,
+            "samples": samples.len(),
+            "waveforms": waveforms.len(),
+            "target": target,
+            "from_ms": records.iter().map(|r| r.ts_ms).min(),
+            "to_ms": records.iter().map(|r| r.ts_ms).max(),
+        }))
+    }.await;
+
+    match result {
+        Ok(summary) => {
+            println!("📥 PLC {}: backfill gravado no historian - {}", ip, summary);
+            let _ = app_handle.emit("historian-backfill", summary);
+        }
+        Err(e) => {
+            println!("❌ PLC {}: backfill falhou - {}", ip, e);
+            let _ = app_handle.emit("historian-backfill-error", serde_json::json!({ "ip": ip, "message": e }));
+        }
+    }
+}
+
+/// Parse de um frame completo + publicação (latest_data, Channels e cache do WebSocket).
+/// Retorna o tempo de processamento em µs.
+fn publish_frame(
+    ip: &str,
+    frame: &[u8],
+    plc_configs_cache: &DashMap<String, PlcStructureConfig>,
+    latest_data: &DashMap<String, PlcDataPacket>,
+    event_sender: Option<&mpsc::Sender<TcpEvent>>,
+) -> u128 {
+    let tcp_received_ns = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    
+    let cached_config = plc_configs_cache.get(ip).map(|e| e.clone());
+    let parsed = crate::plc_parser::parse_plc_data_cached(frame, ip, cached_config);
+    
+    let backend_processed_ns = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    
+    latest_data.insert(ip.to_string(), parsed.clone());
+    
+    let processing_time_us = (backend_processed_ns - tcp_received_ns) / 1000;
+    
+    if let Some(sender) = event_sender {
+        let _ = sender.try_send(TcpEvent::PlcDataReceived(serde_json::json!({
+            "ip": parsed.ip,
+            "timestamp": parsed.timestamp,
+            "size": parsed.size,
+            "variables": parsed.variables,
+            "tcp_received_ns": tcp_received_ns.to_string(),
+            "backend_processed_ns": backend_processed_ns.to_string(),
+            "processing_time_us": processing_time_us
+        })));
+        
+        let _ = sender.try_send(TcpEvent::WebSocketCacheUpdate(serde_json::json!({
+            "plc_ip": parsed.ip,
+            "variables": parsed.variables,
+            "timestamp": parsed.timestamp,
+            "tcp_received_ns": tcp_received_ns.to_string()
+        })));
+    }
+    processing_time_us
+}
+
 // ============================================================================
-// HANDLER DE CONEXÃO - SEM ACK
+// HANDLER DE CONEXÃO - SEM ACK (SÓ ESCRITAS E SUAS CONFIRMAÇÕES)
 // ============================================================================
 
+/// Só com escrita aguardando confirmação o início de um frame pode ser um WACK
+fn has_pending_write(pending_writes: &PendingWrites, ip: &str) -> bool {
+    pending_writes.iter().any(|entry| entry.key().0 == ip)
+}
+
 async fn handle_client_connection(
     mut socket: TcpStream, 
     conn_id: u64, 
@@ -720,17 +1189,20 @@ async fn handle_client_connection(
     plc_configs_cache: Arc<DashMap<String, PlcStructureConfig>>,
     connection_health: Arc<DashMap<String, ConnectionHealth>>,
     event_sender: Option<mpsc::Sender<TcpEvent>>,
+    mut write_rx: mpsc::Receiver<Vec<u8>>,
+    pending_writes: Arc<PendingWrites>,
+    pending_readbacks: Arc<PendingReadbacks>,
 ) -> ConnectionResult {
     
     let mut expected_size: Option<usize> = None;
     
     if let Some(cached_config) = plc_configs_cache.get(&ip) {
-        expected_size = Some(cached_config.total_size);
+        expected_size = crate::plc_parser::known_frame_sizes(&cached_config).last().copied();
         println!("⚡ PLC {}: Config CACHE - {} bytes", ip, cached_config.total_size);
     } else if let Some(db) = database.as_ref() {
         match db.load_plc_structure(&ip) {
             Ok(Some(structure)) => {
-                expected_size = Some(structure.total_size);
+                expected_size = crate::plc_parser::known_frame_sizes(&structure).last().copied();
                 plc_configs_cache.insert(ip.clone(), structure.clone());
                 println!("💾 PLC {}: Config carregada - {} bytes", ip, structure.total_size);
             }
@@ -741,7 +1213,7 @@ async fn handle_client_connection(
     
     let buffer_size = expected_size.unwrap_or(1024).max(1024).min(MAX_ACCUMULATOR_SIZE);
     let mut buffer = vec![0u8; buffer_size];
-    let mut accumulator = buffer_pool.get_buffer(BUFFER_CAPACITY).await;
+    let mut reader = FrameReader::new(buffer_pool.get_buffer(BUFFER_CAPACITY).await, MAX_ACCUMULATOR_SIZE, Some(MAX_PACKET_SIZE));
     
     let mut total_bytes = 0u64;
     let mut packet_count = 0u64;
@@ -754,33 +1226,47 @@ async fn handle_client_connection(
     
     loop {
         if !is_running.load(Ordering::SeqCst) {
-            buffer_pool.return_buffer(accumulator).await;
+            buffer_pool.return_buffer(reader.into_buffer()).await;
             return ConnectionResult::ServerStopped;
         }
         
         if last_valid_packet.elapsed().as_secs() > INACTIVITY_TIMEOUT_SECS {
-            buffer_pool.return_buffer(accumulator).await;
+            buffer_pool.return_buffer(reader.into_buffer()).await;
             return ConnectionResult::Timeout(format!("Sem dados há {}s", last_valid_packet.elapsed().as_secs()));
         }
         
-        if !accumulator.is_empty() && last_fragment_time.elapsed().as_secs() > FRAGMENT_WARN_SECS {
+        if !reader.is_empty() && last_fragment_time.elapsed().as_secs() > FRAGMENT_WARN_SECS {
             if last_fragment_time.elapsed().as_secs() > FRAGMENT_CLEAR_SECS {
-                accumulator.clear();
+                reader.clear();
                 last_fragment_time = std::time::Instant::now();
             }
         }
         
-        match tokio::time::timeout(
-            tokio::time::Duration::from_secs(READ_TIMEOUT_SECS),
-            socket.read(&mut buffer)
-        ).await {
-            Ok(Ok(0)) => {
-                buffer_pool.return_buffer(accumulator).await;
+        // ✍️ Escritas pendentes saem pelo mesmo socket, entre as leituras (plc-core)
+        let event = match read_or_write(&mut socket, &mut buffer, &mut write_rx, tokio::time::Duration::from_secs(READ_TIMEOUT_SECS)).await {
+            Ok(event) => event,
+            Err(e) => {
+                if let Some(mut health) = connection_health.get_mut(&ip) {
+                    health.is_alive = false;
+                    health.last_error = Some(e.to_string());
+                }
+                buffer_pool.return_buffer(reader.into_buffer()).await;
+                return ConnectionResult::Error(e.to_string());
+            }
+        };
+        
+        match event {
+            SocketEvent::WriteSent(len) => {
+                incident_capture::debug(&ip, format!("frame de escrita enviado ({} bytes)", len));
+            }
+            SocketEvent::Closed => {
+                buffer_pool.return_buffer(reader.into_buffer()).await;
                 return ConnectionResult::Normal(total_bytes);
             }
-            Ok(Ok(n)) => {
+            SocketEvent::Read(n) => {
                 consecutive_timeouts = 0;
                 total_bytes += n as u64;
+                incident_capture::debug(&ip, format!("leitura de {} bytes (acumulador {} bytes)", n, reader.len()));
                 bytes_since_last_emit += n as u64;
                 
                 {
@@ -796,20 +1282,55 @@ async fn handle_client_connection(
                     health.is_alive = true;
                 }
                 
-                if accumulator.len() + n > MAX_ACCUMULATOR_SIZE {
-                    accumulator.clear();
+                // Frames de backfill podem ser bem maiores que os frames normais
+                if let Err(reason) = reader.push(&buffer[0..n]) {
+                    incident_capture::debug(&ip, format!("{} - descartado", reason));
+                    parse_quarantine::record_error(&app_handle, database.as_deref(), &ip, ParseErrorKind::Framing, reason);
                     continue;
                 }
                 
-                accumulator.extend_from_slice(&buffer[0..n]);
-                
-                let should_parse = if let Some(expected) = expected_size {
-                    accumulator.len() >= expected
-                } else {
-                    true
-                };
+                let frame_sizes = plc_configs_cache.get(&ip)
+                    .map(|c| crate::plc_parser::known_frame_sizes(&c))
+                    .unwrap_or_default();
+                let mut frames: Vec<Vec<u8>> = Vec::new();
+                {
+                    let config = plc_configs_cache.get(&ip);
+                    let framing = config.as_ref().map(|c| c.framing).unwrap_or_default();
+                    // 🧩 Vários itens podem chegar na mesma leitura; em cada fronteira de frame:
+                    // WACK (só com escrita pendente), backfill ou frame do framing da conexão
+                    while let Some(event) = reader.next_event(config.as_deref(), has_pending_write(&pending_writes, &ip)) {
+                        match event {
+                            // ✍️ Confirmações de escrita (WACK) chegam entre os frames de dados
+                            FrameEvent::WriteAck { seq, status } => {
+                                incident_capture::debug(&ip, format!("confirmação de escrita #{} com status {}", seq, status));
+                                last_valid_packet = std::time::Instant::now();
+                                match pending_writes.remove(&(ip.clone(), seq)) {
+                                    Some((_, reply)) => { let _ = reply.send(status); }
+                                    None => println!("⚠️ PLC {}: confirmação de escrita #{} sem pedido pendente", ip, seq),
+                                }
+                            }
+                            // 📥 Backfill: amostras buferizadas pelo PLC vão para o historian com o
+                            // timestamp original, nunca como valor atual
+                            FrameEvent::Backfill(frame) => {
+                                last_valid_packet = std::time::Instant::now();
+                                incident_capture::debug(&ip, format!("frame de backfill com {} bytes", frame.len()));
+                                match (config.as_deref().cloned(), database.clone()) {
+                                    (Some(config), Some(db)) => {
+                                        tokio::spawn(store_backfill(ip.clone(), frame, config, db, app_handle.clone()));
+                                    }
+                                    _ => println!("⚠️ PLC {}: backfill de {} bytes ignorado - sem estrutura configurada", ip, frame.len()),
+                                }
+                            }
+                            FrameEvent::Frame(frame) => frames.push(frame),
+                            FrameEvent::Invalid(reason) => {
+                                incident_capture::debug(&ip, format!("{} (framing {}) - acumulador descartado", reason, framing.as_str()));
+                                parse_quarantine::record_error(&app_handle, database.as_deref(), &ip, ParseErrorKind::Framing, reason);
+                            }
+                        }
+                    }
+                }
                 
-                if should_parse {
+                for frame in frames {
                     last_valid_packet = std::time::Instant::now();
                     packet_count += 1;
                     
@@ -817,45 +1338,30 @@ async fn handle_client_connection(
                         health.packet_count = packet_count;
                     }
                     
-                    let tcp_received_ns = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_nanos();
-                    
-                    let data_to_parse = if accumulator.is_empty() { &buffer[0..n] } else { &accumulator[..] };
-                    
-                    let cached_config = plc_configs_cache.get(&ip).map(|e| e.clone());
-                    let parsed = crate::plc_parser::parse_plc_data_cached(data_to_parse, &ip, cached_config);
-                    
-                    let backend_processed_ns = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_nanos();
-                    
-                    latest_data.insert(ip.clone(), parsed.clone());
-                    
-                    let processing_time_us = (backend_processed_ns - tcp_received_ns) / 1000;
-                    
-                    if let Some(sender) = &event_sender {
-                        let _ = sender.try_send(TcpEvent::PlcDataReceived(serde_json::json!({
-                            "ip": parsed.ip,
-                            "timestamp": parsed.timestamp,
-                            "raw_data": parsed.raw_data,
-                            "size": parsed.size,
-                            "variables": parsed.variables,
-                            "tcp_received_ns": tcp_received_ns.to_string(),
-                            "backend_processed_ns": backend_processed_ns.to_string(),
-                            "processing_time_us": processing_time_us
-                        })));
-                        
-                        let _ = sender.try_send(TcpEvent::WebSocketCacheUpdate(serde_json::json!({
-                            "plc_ip": parsed.ip,
-                            "variables": parsed.variables,
-                            "timestamp": parsed.timestamp
-                        })));
+                    let data_to_parse = &frame[..];
+                    incident_capture::record_frame(&ip, data_to_parse);
+                    crate::packet_recorder::record_frame(&ip, data_to_parse);
+                    if !frame_sizes.is_empty() && !frame_sizes.contains(&data_to_parse.len()) {
+                        incident_capture::debug(&ip, format!("frame de {} bytes fora dos tamanhos conhecidos {:?}", data_to_parse.len(), frame_sizes));
+                        parse_quarantine::record_error(&app_handle, database.as_deref(), &ip, ParseErrorKind::SizeMismatch,
+                            format!("frame de {} bytes, esperado {:?}", data_to_parse.len(), frame_sizes));
+                    } else {
+                        parse_quarantine::record_ok(&ip);
                     }
                     
-                    accumulator.clear();
+                    // ☣️ Em quarentena o frame fica só na captura bruta: nada de valores lixo
+                    let quarantined = parse_quarantine::hold_frame(&ip);
+                    // ✍️ Escritas confirmadas: o valor enviado já aparece neste frame?
+                    if !quarantined && !pending_readbacks.is_empty() {
+                        if let Some(config) = plc_configs_cache.get(&ip) {
+                            crate::plc_write::check_readbacks(&pending_readbacks, &ip, &config, data_to_parse);
+                        }
+                    }
+                    let processing_time_us = if quarantined {
+                        0
+                    } else {
+                        publish_frame(&ip, data_to_parse, &plc_configs_cache, &latest_data, event_sender.as_ref())
+                    };
                     
                     // Estatísticas a cada 1 segundo
                     let elapsed = last_emit_time.elapsed();
@@ -882,10 +1388,11 @@ async fn handle_client_connection(
                                 "packets": packet_count,
                                 "totalBytes": total_bytes,
                                 "transferRate": format!("{:.2} KB/s", bytes_per_second as f64 / 1024.0),
+                                "processing_time_us": processing_time_us,
                                 "industrialMetrics": {
                                     "packetFrequency": packets_per_second,
                                     "avgPacketSize": avg_packet_size,
-                                    "dataIntegrity": "OK"
+                                    "dataIntegrity": if quarantined { "QUARANTINED" } else { "OK" }
                                 }
                             })));
                         }
@@ -897,23 +1404,16 @@ async fn handle_client_connection(
                 
                 // 🚫 SEM ACK - PLC NÃO LÊ!
             }
-            Ok(Err(e)) => {
-                if let Some(mut health) = connection_health.get_mut(&ip) {
-                    health.is_alive = false;
-                    health.last_error = Some(e.to_string());
-                }
-                buffer_pool.return_buffer(accumulator).await;
-                return ConnectionResult::Error(e.to_string());
-            }
-            Err(_) => {
+            SocketEvent::ReadTimeout => {
                 consecutive_timeouts += 1;
+                incident_capture::debug(&ip, format!("timeout de leitura {} de 3 ({}s)", consecutive_timeouts, READ_TIMEOUT_SECS));
                 if consecutive_timeouts >= 3 {
                     let reason = format!("{} timeouts de {}s", consecutive_timeouts, READ_TIMEOUT_SECS);
                     if let Some(mut health) = connection_health.get_mut(&ip) {
                         health.is_alive = false;
                         health.last_error = Some(reason.clone());
                     }
-                    buffer_pool.return_buffer(accumulator).await;
+                    buffer_pool.return_buffer(reader.into_buffer()).await;
                     return ConnectionResult::Timeout(reason);
                 }
             }
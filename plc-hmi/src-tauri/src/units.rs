@@ -0,0 +1,254 @@
+use crate::database::{TagMapping, TagUnitVersion};
+use crate::historian::SnapshotValue;
+use std::collections::HashMap;
+
+// ============================================================================
+// CONVERSÃO DE UNIDADES POR TAG
+// ============================================================================
+//
+// Cada tag pode ter `display_unit`: o valor lido (na unidade `unit`) é convertido
+// antes de ir para o cache/WebSocket, e a unidade publicada passa a ser a nova.
+// O historian grava o valor publicado; cada mudança de unit/display_unit vira
+// uma versão (tag_unit_versions) para que amostras antigas sejam lidas com a
+// unidade da época e convertidas para a atual (apply_unit_versions).
+// `display_decimals` fixa as casas decimais do valor publicado (após a conversão).
+
+/// Unidades lineares: (nome, dimensão, fator para a unidade base da dimensão)
+const LINEAR_UNITS: &[(&str, &str, f64)] = &[
+    // Pressão (base: Pa)
+    ("Pa", "pressure", 1.0),
+    ("kPa", "pressure", 1_000.0),
+    ("mbar", "pressure", 100.0),
+    ("bar", "pressure", 100_000.0),
+    ("psi", "pressure", 6_894.757),
+    // Vazão (base: l/s)
+    ("l/s", "flow", 1.0),
+    ("l/min", "flow", 1.0 / 60.0),
+    ("m³/h", "flow", 1_000.0 / 3_600.0),
+    ("m³/s", "flow", 1_000.0),
+    // 🆕 Comprimento/nível (base: m)
+    ("mm", "length", 0.001),
+    ("cm", "length", 0.01),
+    ("m", "length", 1.0),
+    ("km", "length", 1_000.0),
+];
+
+pub const MAX_DISPLAY_DECIMALS: u32 = 6;
+
+const TEMPERATURE_UNITS: &[&str] = &["°C", "°F", "K"];
+
+/// Normaliza grafias comuns ("m3/h", "ºC", "C") para o nome canônico
+fn normalize_unit(unit: &str) -> &str {
+    match unit.trim() {
+        "m3/h" => "m³/h",
+        "m3/s" => "m³/s",
+        "ºC" | "C" | "degC" => "°C",
+        "ºF" | "F" | "degF" => "°F",
+        "kpa" | "KPa" => "kPa",
+        "BAR" => "bar",
+        other => other,
+    }
+}
+
+fn to_celsius(value: f64, unit: &str) -> f64 {
+    match unit {
+        "°F" => (value - 32.0) * 5.0 / 9.0,
+        "K" => value - 273.15,
+        _ => value,
+    }
+}
+
+fn from_celsius(value: f64, unit: &str) -> f64 {
+    match unit {
+        "°F" => value * 9.0 / 5.0 + 32.0,
+        "K" => value + 273.15,
+        _ => value,
+    }
+}
+
+/// Converte `value` de `from` para `to`; None se as unidades não forem compatíveis
+pub fn convert(value: f64, from: &str, to: &str) -> Option<f64> {
+    let (from, to) = (normalize_unit(from), normalize_unit(to));
+    if from == to {
+        return Some(value);
+    }
+
+    if TEMPERATURE_UNITS.contains(&from) && TEMPERATURE_UNITS.contains(&to) {
+        return Some(from_celsius(to_celsius(value, from), to));
+    }
+
+    let (_, from_dim, from_factor) = LINEAR_UNITS.iter().find(|(name, _, _)| *name == from)?;
+    let (_, to_dim, to_factor) = LINEAR_UNITS.iter().find(|(name, _, _)| *name == to)?;
+    if from_dim != to_dim {
+        return None;
+    }
+    Some(value * from_factor / to_factor)
+}
+
+/// Unidades para as quais `unit` pode ser convertida (para o seletor da UI)
+pub fn compatible_units(unit: &str) -> Vec<String> {
+    let unit = normalize_unit(unit);
+    if TEMPERATURE_UNITS.contains(&unit) {
+        return TEMPERATURE_UNITS.iter().filter(|u| **u != unit).map(|u| u.to_string()).collect();
+    }
+    match LINEAR_UNITS.iter().find(|(name, _, _)| *name == unit) {
+        Some((_, dimension, _)) => LINEAR_UNITS.iter()
+            .filter(|(name, dim, _)| dim == dimension && *name != unit)
+            .map(|(name, _, _)| name.to_string())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Formata sem zeros à direita desnecessários (ex: 1.500000 → 1.5)
+pub(crate) fn format_value(value: f64) -> String {
+    let formatted = format!("{:.6}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Aplica a conversão e as casas decimais configuradas no tag, retornando
+/// (valor, unidade publicada). Valores não numéricos (ex: bits TRUE/FALSE) passam sem alteração.
+pub fn apply_tag_conversion(tag: &TagMapping, value: &str) -> (String, Option<String>) {
+    let (value, unit) = match (tag.unit.as_deref(), tag.display_unit.as_deref()) {
+        (Some(from), Some(to)) => match value.parse::<f64>().ok().and_then(|v| convert(v, from, to)) {
+            Some(converted) => (format_value(converted), Some(normalize_unit(to).to_string())),
+            None => (value.to_string(), tag.unit.clone()),
+        },
+        _ => (value.to_string(), tag.unit.clone()),
+    };
+    (apply_tag_precision(tag, &value), unit)
+}
+
+/// Arredonda para `display_decimals` casas (ex: 2 → "3.10"); sem configuração o valor fica como está
+pub fn apply_tag_precision(tag: &TagMapping, value: &str) -> String {
+    match (tag.display_decimals, value.trim().parse::<f64>()) {
+        (Some(decimals), Ok(number)) if number.is_finite() => format!("{:.*}", decimals.min(MAX_DISPLAY_DECIMALS) as usize, number),
+        _ => value.to_string(),
+    }
+}
+
+/// Valor publicado com a unidade, para exibição (ex: "3.10 bar", "TRUE")
+pub fn format_with_unit(value: &str, unit: Option<&str>) -> String {
+    match unit.map(str::trim).filter(|u| !u.is_empty()) {
+        Some(unit) if value.trim().parse::<f64>().is_ok() => format!("{} {}", value, unit),
+        _ => value.to_string(),
+    }
+}
+
+/// Interpreta cada amostra com a versão da unidade vigente no seu instante e a
+/// converte para a unidade publicada hoje (última versão). Amostras de tags sem
+/// versão ficam como estão; sem conversão possível (ex: °C → bar) o valor fica
+/// na unidade da época, indicada em `unit`.
+pub fn apply_unit_versions(samples: &mut [SnapshotValue], versions: &[TagUnitVersion]) {
+    let mut by_tag: HashMap<(&str, &str), Vec<&TagUnitVersion>> = HashMap::new();
+    for version in versions {
+        by_tag.entry((version.plc_ip.as_str(), version.tag_name.as_str())).or_default().push(version);
+    }
+    for tag_versions in by_tag.values_mut() {
+        tag_versions.sort_by_key(|v| (v.effective_from_ms, v.id));
+    }
+
+    for sample in samples.iter_mut() {
+        let Some(tag_versions) = by_tag.get(&(sample.plc_ip.as_str(), sample.tag_name.as_str())) else { continue };
+        let current = tag_versions.last().and_then(|v| v.published_unit());
+        let recorded = tag_versions.iter().rev()
+            .find(|v| v.effective_from_ms <= sample.ts_ms)
+            .or(tag_versions.first())
+            .and_then(|v| v.published_unit());
+
+        let converted = match (recorded, current, sample.value_num) {
+            (Some(from), Some(to), Some(value)) if normalize_unit(from) != normalize_unit(to) => convert(value, from, to),
+            _ => None,
+        };
+        match converted {
+            Some(value) => {
+                sample.value = format_value(value);
+                sample.value_num = Some(value);
+                sample.unit = current.map(|u| normalize_unit(u).to_string());
+            }
+            None if recorded.map(normalize_unit) == current.map(normalize_unit) => {
+                sample.unit = current.map(str::to_string);
+            }
+            None => sample.unit = recorded.map(str::to_string),
+        }
+    }
+}
+
+// ============================================================================
+// 🆕 ESCALONAMENTO LINEAR E BANDA MORTA POR TAG
+// ============================================================================
+//
+// O valor bruto do PLC (ex: 0..27648 de uma entrada analógica) é levado para a
+// faixa de engenharia (eng_min..eng_max, na unidade `unit`) e somado ao
+// scale_offset ANTES da conversão de unidade. A banda morta é uma porcentagem
+// da faixa de engenharia: variações menores não contam como mudança.
+
+/// (raw_min, raw_max, eng_min, eng_max) quando a faixa está completa e válida
+fn scaling_range(tag: &TagMapping) -> Option<(f64, f64, f64, f64)> {
+    match (tag.raw_min, tag.raw_max, tag.eng_min, tag.eng_max) {
+        (Some(raw_min), Some(raw_max), Some(eng_min), Some(eng_max)) if raw_max != raw_min => {
+            Some((raw_min, raw_max, eng_min, eng_max))
+        }
+        _ => None,
+    }
+}
+
+/// Aplica escalonamento e offset do tag. Sem faixa completa só o offset vale;
+/// valores não numéricos passam sem alteração.
+pub fn apply_tag_scaling(tag: &TagMapping, value: &str) -> String {
+    let range = scaling_range(tag);
+    if range.is_none() && tag.scale_offset.is_none() {
+        return value.to_string();
+    }
+    let Ok(raw) = value.trim().parse::<f64>() else {
+        return value.to_string();
+    };
+
+    let scaled = match range {
+        Some((raw_min, raw_max, eng_min, eng_max)) => eng_min + (raw - raw_min) * (eng_max - eng_min) / (raw_max - raw_min),
+        None => raw,
+    };
+    format_value(scaled + tag.scale_offset.unwrap_or(0.0))
+}
+
+/// Valida a configuração de escalonamento/banda morta (mensagem para a UI)
+pub fn validate_tag_scaling(tag: &TagMapping) -> Result<(), String> {
+    let fields = [tag.raw_min, tag.raw_max, tag.eng_min, tag.eng_max];
+    if fields.iter().any(Option::is_some) && fields.iter().any(Option::is_none) {
+        return Err(format!("Escalonamento do tag '{}' incompleto: informe raw_min, raw_max, eng_min e eng_max", tag.tag_name));
+    }
+    if fields.iter().all(Option::is_some) && scaling_range(tag).is_none() {
+        return Err(format!("Escalonamento do tag '{}' inválido: raw_min e raw_max iguais", tag.tag_name));
+    }
+    if let Some(pct) = tag.deadband_pct {
+        if !(0.0..=100.0).contains(&pct) {
+            return Err(format!("Banda morta do tag '{}' deve estar entre 0 e 100%", tag.tag_name));
+        }
+    }
+    Ok(())
+}
+
+/// true se `current` saiu da banda morta em torno de `last` (valores já publicados,
+/// isto é, escalonados e convertidos). Sem faixa de engenharia a porcentagem vale
+/// sobre o último valor. Valores não numéricos ou banda 0 sempre contam como mudança.
+pub fn outside_deadband(tag: &TagMapping, last: &str, current: &str) -> bool {
+    let Some(pct) = tag.deadband_pct.filter(|p| *p > 0.0) else {
+        return true;
+    };
+    let (Ok(last), Ok(current)) = (last.trim().parse::<f64>(), current.trim().parse::<f64>()) else {
+        return true;
+    };
+
+    let span = match scaling_range(tag) {
+        Some((_, _, eng_min, eng_max)) => {
+            // Faixa levada para a unidade publicada (conversões com offset, ex: °C → °F)
+            let published = |v: f64| match (tag.unit.as_deref(), tag.display_unit.as_deref()) {
+                (Some(from), Some(to)) => convert(v, from, to).unwrap_or(v),
+                _ => v,
+            };
+            (published(eng_max) - published(eng_min)).abs()
+        }
+        None => last.abs(),
+    };
+    (current - last).abs() > span * pct / 100.0
+}
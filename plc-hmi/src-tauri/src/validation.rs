@@ -0,0 +1,342 @@
+use crate::database::{DataBlockConfig, Database, TagMapping};
+use crate::plc_parser::{data_type_size, is_text_type, is_time_type, split_array_index, split_variable_path, PathSelector};
+use crate::websocket_server::parse_edge_path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+// ============================================================================
+// VALIDAÇÃO DA CONFIGURAÇÃO - CONSISTÊNCIA ENTRE TAGS E ESTRUTURA DO PLC
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub severity: String,          // "error", "warning"
+    pub code: String,              // Ex: "UNKNOWN_BLOCK", "INDEX_OUT_OF_RANGE"
+    pub plc_ip: String,
+    pub tag_name: Option<String>,
+    pub variable_path: Option<String>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(severity: &str, code: &str, plc_ip: &str, tag: Option<&TagMapping>, message: String) -> Self {
+        Self {
+            severity: severity.to_string(),
+            code: code.to_string(),
+            plc_ip: plc_ip.to_string(),
+            tag_name: tag.map(|t| t.tag_name.clone()),
+            variable_path: tag.map(|t| t.variable_path.clone()),
+            message,
+        }
+    }
+}
+
+/// Verifica um tag contra os blocos conhecidos do PLC (estrutura principal + perfis)
+fn check_tag_against_blocks(plc_ip: &str, tag: &TagMapping, blocks: &HashMap<String, DataBlockConfig>) -> Option<ConfigIssue> {
+    // 🆕 Forma de onda: o tag aponta para o bloco inteiro, sem índice
+    if blocks.get(&tag.variable_path).is_some_and(|b| b.waveform) {
+        return None;
+    }
+    let (variable, selector) = match split_variable_path(&tag.variable_path) {
+        Ok(split) => split,
+        Err(e) => return Some(ConfigIssue::new("error", "INVALID_SELECTOR", plc_ip, Some(tag),
+            format!("{} em '{}'", e, tag.variable_path))),
+    };
+    let Some((block_name, index)) = split_array_index(variable) else {
+        return Some(ConfigIssue::new("warning", "UNRECOGNIZED_PATH", plc_ip, Some(tag),
+            format!("Formato de variable_path não reconhecido: '{}'", tag.variable_path)));
+    };
+
+    let Some(block) = blocks.get(block_name) else {
+        return Some(ConfigIssue::new("error", "UNKNOWN_BLOCK", plc_ip, Some(tag),
+            format!("Bloco '{}' não existe na estrutura do PLC", block_name)));
+    };
+
+    if block.waveform {
+        return Some(ConfigIssue::new("error", "INDEX_ON_WAVEFORM", plc_ip, Some(tag),
+            format!("Bloco '{}' é forma de onda: use '{}' sem índice", block_name, block_name)));
+    }
+
+    if index >= block.count {
+        return Some(ConfigIssue::new("error", "INDEX_OUT_OF_RANGE", plc_ip, Some(tag),
+            format!("Índice {} fora do intervalo do bloco '{}' (0..{})", index, block_name, block.count)));
+    }
+
+    let size = data_type_size(&block.data_type).unwrap_or(2);
+    match selector {
+        // 🆕 REAL_SWAP reinterpreta a variável inteira: precisa de 4 bytes numéricos
+        Some(PathSelector::SwappedReal) => {
+            if size != 4 || is_text_type(&block.data_type) || is_time_type(&block.data_type) {
+                return Some(ConfigIssue::new("error", "SWAP_ON_INVALID_TYPE", plc_ip, Some(tag),
+                    format!("REAL_SWAP exige variável de 4 bytes, '{}' é {}", variable, block.data_type)));
+            }
+        }
+        Some(selector) => {
+            if block.data_type == "REAL" {
+                return Some(ConfigIssue::new("error", "BIT_ON_REAL", plc_ip, Some(tag),
+                    format!("Extração de bit não é suportada em REAL ('{}')", tag.variable_path)));
+            }
+            // 🆕 STRING/WSTRING/CHAR: valor é texto, não há bits
+            if is_text_type(&block.data_type) {
+                return Some(ConfigIssue::new("error", "BIT_ON_TEXT", plc_ip, Some(tag),
+                    format!("Extração de bit não é suportada em {} ('{}')", block.data_type, tag.variable_path)));
+            }
+            // 🆕 TIME/DATE/TOD/DT: valor é data/hora ISO, não há bits
+            if is_time_type(&block.data_type) {
+                return Some(ConfigIssue::new("error", "BIT_ON_TIME", plc_ip, Some(tag),
+                    format!("Extração de bit não é suportada em {} ('{}')", block.data_type, tag.variable_path)));
+            }
+            let width = size as u32 * 8;
+            let out_of_range = match selector {
+                PathSelector::Bit(bit) => bit as u32 >= width,
+                PathSelector::BitRange(_, high) => high as u32 >= width,
+                PathSelector::Byte(byte) => byte as usize >= size,
+                PathSelector::SwappedReal => false,
+            };
+            if out_of_range {
+                return Some(ConfigIssue::new("error", "BIT_OUT_OF_RANGE", plc_ip, Some(tag),
+                    format!("Seletor de '{}' fora de {} ({} bits)", tag.variable_path, block.data_type, width)));
+            }
+        }
+        None => {}
+    }
+
+    None
+}
+
+/// Cruza todos os tag mappings com as estruturas salvas e retorna a lista de problemas
+pub fn validate_configuration(db: &Database) -> Result<Vec<ConfigIssue>, String> {
+    let mut plcs: BTreeSet<String> = db.list_configured_plcs()
+        .map_err(|e| format!("Erro ao listar PLCs: {}", e))?
+        .into_iter()
+        .collect();
+    plcs.extend(db.list_plcs_with_tags().map_err(|e| format!("Erro ao listar PLCs com tags: {}", e))?);
+
+    let mut issues = Vec::new();
+
+    for plc_ip in &plcs {
+        let structure = db.load_plc_structure(plc_ip)
+            .map_err(|e| format!("Erro ao carregar estrutura de {}: {}", plc_ip, e))?;
+        let tags = db.load_tag_mappings(plc_ip)
+            .map_err(|e| format!("Erro ao carregar tags de {}: {}", plc_ip, e))?;
+
+        let Some(structure) = structure else {
+            if !tags.is_empty() {
+                issues.push(ConfigIssue::new("error", "MISSING_STRUCTURE", plc_ip, None,
+                    format!("{} tags configurados, mas o PLC não possui estrutura salva", tags.len())));
+            }
+            continue;
+        };
+
+        // Blocos válidos: estrutura principal + todos os perfis de frame
+        let mut blocks: HashMap<String, DataBlockConfig> = HashMap::new();
+        for block in structure.profiles.iter().flat_map(|p| p.blocks.iter()).chain(structure.blocks.iter()) {
+            blocks.insert(block.name.clone(), block.clone());
+        }
+
+        // Nomes de tag duplicados no mesmo PLC
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for tag in &tags {
+            *name_counts.entry(tag.tag_name.as_str()).or_default() += 1;
+        }
+        let mut duplicates: Vec<(&str, usize)> = name_counts.into_iter().filter(|(_, count)| *count > 1).collect();
+        duplicates.sort();
+        for (name, count) in duplicates {
+            issues.push(ConfigIssue {
+                severity: "error".to_string(),
+                code: "DUPLICATE_TAG_NAME".to_string(),
+                plc_ip: plc_ip.clone(),
+                tag_name: Some(name.to_string()),
+                variable_path: None,
+                message: format!("Nome de tag '{}' usado {} vezes", name, count),
+            });
+        }
+
+        for tag in &tags {
+            // Edge tags referenciam outro tag, não um endereço do PLC
+            if let Some((_, source)) = parse_edge_path(&tag.variable_path) {
+                if !tags.iter().any(|t| t.tag_name == source) {
+                    issues.push(ConfigIssue::new("error", "EDGE_SOURCE_MISSING", plc_ip, Some(tag),
+                        format!("Tag de origem '{}' não existe", source)));
+                }
+                continue;
+            }
+
+            if tag.variable_path.starts_with("DB") {
+                continue; // Endereçamento absoluto, fora da estrutura de blocos
+            }
+
+            if let Some(issue) = check_tag_against_blocks(plc_ip, tag, &blocks) {
+                issues.push(issue);
+            }
+        }
+    }
+
+    // 🆕 Alarmes que apontam para tags inexistentes nunca disparam
+    let mut tags_by_plc: HashMap<String, Vec<TagMapping>> = HashMap::new();
+    for alarm in db.list_alarm_definitions(None).map_err(|e| format!("Erro ao carregar alarmes: {}", e))? {
+        if !tags_by_plc.contains_key(&alarm.plc_ip) {
+            let tags = db.load_tag_mappings(&alarm.plc_ip)
+                .map_err(|e| format!("Erro ao carregar tags de {}: {}", alarm.plc_ip, e))?;
+            tags_by_plc.insert(alarm.plc_ip.clone(), tags);
+        }
+        if !tags_by_plc[&alarm.plc_ip].iter().any(|t| t.tag_name == alarm.tag_name) {
+            issues.push(ConfigIssue {
+                severity: if alarm.enabled { "error" } else { "warning" }.to_string(),
+                code: "ORPHANED_ALARM".to_string(),
+                plc_ip: alarm.plc_ip.clone(),
+                tag_name: Some(alarm.tag_name.clone()),
+                variable_path: None,
+                message: format!("Alarme '{}' referencia o tag '{}', que não existe", alarm.name, alarm.tag_name),
+            });
+        }
+    }
+
+    println!("🔎 Validação da configuração: {} problemas encontrados em {} PLCs", issues.len(), plcs.len());
+    Ok(issues)
+}
+
+// ============================================================================
+// RELATÓRIO DE COBERTURA - QUAIS BYTES DO FRAME ESTÃO MAPEADOS EM TAGS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableCoverage {
+    pub variable: String,       // Ex: "Word[5]"
+    pub byte_offset: usize,
+    pub byte_size: usize,
+    pub tags: Vec<String>,      // Tags que referenciam a variável (inteira ou bits)
+    pub bits_used: Vec<u32>,    // Bits extraídos (vazio = variável inteira)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteRange {
+    pub start: usize,
+    pub end: usize,             // Exclusivo
+    pub variables: Vec<String>, // Ex: ["Word[10]", "Word[11]"]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingCoverageReport {
+    pub plc_ip: String,
+    pub layout: String,              // "default" ou nome do perfil
+    pub total_bytes: usize,
+    pub used_bytes: usize,
+    pub coverage_percent: f64,
+    pub used_variables: Vec<VariableCoverage>,
+    pub unused_ranges: Vec<ByteRange>,
+    pub unresolved_tags: Vec<String>, // Tags que não apontam para nenhuma variável deste layout
+    pub type_byte_offset: Option<usize>,
+}
+
+/// Cruza o layout do frame (estrutura principal ou perfil) com os tag mappings do PLC
+pub fn mapping_coverage(db: &Database, plc_ip: &str, profile: Option<&str>) -> Result<MappingCoverageReport, String> {
+    let structure = db.load_plc_structure(plc_ip)
+        .map_err(|e| format!("Erro ao carregar estrutura de {}: {}", plc_ip, e))?
+        .ok_or_else(|| format!("PLC {} não possui estrutura salva", plc_ip))?;
+    let tags = db.load_tag_mappings(plc_ip)
+        .map_err(|e| format!("Erro ao carregar tags de {}: {}", plc_ip, e))?;
+
+    let (layout, blocks, type_byte_offset) = match profile {
+        None | Some("default") => ("default".to_string(), &structure.blocks, None),
+        Some(name) => {
+            let profile = structure.profiles.iter().find(|p| p.name == name)
+                .ok_or_else(|| format!("Perfil de frame '{}' não existe em {}", name, plc_ip))?;
+            (profile.name.clone(), &profile.blocks, profile.type_byte_offset)
+        }
+    };
+
+    // Offset de cada bloco no frame, na ordem em que aparecem
+    let mut block_offsets: HashMap<&str, (usize, usize, &DataBlockConfig)> = HashMap::new();
+    let mut offset = 0;
+    for block in blocks.iter() {
+        let size = data_type_size(&block.data_type)
+            .ok_or_else(|| format!("Tipo inválido no bloco '{}': {}", block.name, block.data_type))?;
+        block_offsets.insert(block.name.as_str(), (offset, size, block));
+        offset += size * block.count as usize;
+    }
+    let total_bytes = offset;
+
+    // (bloco, índice) → (tags, bits)
+    let mut references: HashMap<(String, u32), (Vec<String>, BTreeSet<u32>)> = HashMap::new();
+    let mut unresolved_tags = Vec::new();
+    for tag in &tags {
+        // Edge tags e endereçamento absoluto não ocupam variáveis do layout
+        if parse_edge_path(&tag.variable_path).is_some() || tag.variable_path.starts_with("DB") {
+            continue;
+        }
+        // 🆕 Forma de onda: o tag cobre todos os elementos do bloco
+        if let Some((_, _, block)) = block_offsets.get(tag.variable_path.as_str()).filter(|(_, _, b)| b.waveform) {
+            for index in 0..block.count {
+                references.entry((block.name.clone(), index)).or_default().0.push(tag.tag_name.clone());
+            }
+            continue;
+        }
+        let split = split_variable_path(&tag.variable_path).ok()
+            .and_then(|(variable, selector)| split_array_index(variable).map(|(name, index)| (name, index, selector)));
+        match split {
+            Some((name, index, selector)) if block_offsets.get(name).is_some_and(|(_, _, b)| index < b.count && !b.waveform) => {
+                let size = block_offsets[name].1 as u32;
+                let entry = references.entry((name.to_string(), index)).or_default();
+                entry.0.push(tag.tag_name.clone());
+                // Bits lidos pelo seletor; REAL_SWAP ocupa a variável inteira
+                match selector {
+                    Some(PathSelector::Bit(bit)) => { entry.1.insert(bit as u32); }
+                    Some(PathSelector::BitRange(low, high)) => entry.1.extend(low as u32..=high as u32),
+                    Some(PathSelector::Byte(byte)) if (byte as u32) < size => {
+                        let low = (size - 1 - byte as u32) * 8;
+                        entry.1.extend(low..low + 8);
+                    }
+                    _ => {}
+                }
+            }
+            _ => unresolved_tags.push(tag.tag_name.clone()),
+        }
+    }
+
+    let mut used_variables = Vec::new();
+    let mut unused_ranges: Vec<ByteRange> = Vec::new();
+    for block in blocks.iter() {
+        let (block_offset, size, _) = block_offsets[block.name.as_str()];
+        for index in 0..block.count {
+            let variable = format!("{}[{}]", block.name, index);
+            let byte_offset = block_offset + index as usize * size;
+
+            match references.remove(&(block.name.clone(), index)) {
+                Some((tag_names, bits)) => used_variables.push(VariableCoverage {
+                    variable,
+                    byte_offset,
+                    byte_size: size,
+                    tags: tag_names,
+                    bits_used: bits.into_iter().collect(),
+                }),
+                None => match unused_ranges.last_mut() {
+                    // Juntar com a região livre anterior se for contígua
+                    Some(range) if range.end == byte_offset => {
+                        range.end += size;
+                        range.variables.push(variable);
+                    }
+                    _ => unused_ranges.push(ByteRange { start: byte_offset, end: byte_offset + size, variables: vec![variable] }),
+                },
+            }
+        }
+    }
+
+    let used_bytes: usize = used_variables.iter().map(|v| v.byte_size).sum();
+    let coverage_percent = if total_bytes > 0 { used_bytes as f64 * 100.0 / total_bytes as f64 } else { 0.0 };
+
+    println!("📐 Cobertura de {} ({}): {}/{} bytes mapeados ({:.1}%), {} tags sem variável",
+             plc_ip, layout, used_bytes, total_bytes, coverage_percent, unresolved_tags.len());
+
+    Ok(MappingCoverageReport {
+        plc_ip: plc_ip.to_string(),
+        layout,
+        total_bytes,
+        used_bytes,
+        coverage_percent,
+        used_variables,
+        unused_ranges,
+        unresolved_tags,
+        type_byte_offset,
+    })
+}
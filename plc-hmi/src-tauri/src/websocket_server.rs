@@ -3,19 +3,27 @@ use futures_util::{SinkExt, StreamExt};
 use tokio::sync::Mutex as TokioMutex;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, watch, RwLock};
 use tokio::time;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
 use std::collections::{HashMap, BTreeMap};
 
-use crate::database::Database;
+use crate::database::{Database, PublicStreamKey, WsClientSession};
 use crate::database::TagMapping;
 use crate::tcp_server::TcpServer;
+use crate::ws_protocol::{self, ClientFeatures};
+use crate::ws_masking::{StreamMask, TagGroups};
+use crate::ws_auth;
+use crate::error::AppError;
+use crate::db_breaker::{DbBreakerStatus, DbCircuitBreaker};
+use crate::playback::PlaybackFrame;
+use plc_hmi_client::protocol::{ServerMessage, TagInfo};
 use tokio::sync::mpsc;
 
 // ✅ Helper para base64 encode simples
@@ -96,12 +104,46 @@ fn sort_tags_naturally(tags: HashMap<String, String>) -> BTreeMap<String, String
     sorted_entries.into_iter().collect()
 }
 
+// 🆕 EDGE TAGS: variable_path "RISE(tag_origem)" ou "FALL(tag_origem)"
+// O tag vale TRUE por exatamente um broadcast quando o tag booleano de origem
+// sobe (RISE) ou desce (FALL), e volta a FALSE em seguida.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeKind {
+    Rising,
+    Falling,
+}
+
+/// Interpreta um variable_path de edge tag, retornando o tipo e o tag de origem
+pub fn parse_edge_path(variable_path: &str) -> Option<(EdgeKind, &str)> {
+    let (kind, rest) = if let Some(rest) = variable_path.strip_prefix("RISE(") {
+        (EdgeKind::Rising, rest)
+    } else if let Some(rest) = variable_path.strip_prefix("FALL(") {
+        (EdgeKind::Falling, rest)
+    } else {
+        return None;
+    };
+    let source = rest.strip_suffix(')')?.trim();
+    if source.is_empty() {
+        None
+    } else {
+        Some((kind, source))
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(value, "TRUE" | "true" | "1")
+}
+
+// Máximo de atualizações guardadas enquanto as tasks de broadcast reiniciam
+const RELOAD_BUFFER_LIMIT: usize = 500;
+
 // ✅ ESTRUTURA PARA SERIALIZAR ATUALIZAÇÕES DE CACHE
 #[derive(Debug, Clone)]
 struct CacheUpdateData {
     plc_ip: String,
     variables: Vec<crate::tcp_server::PlcVariable>,
     timestamp: u64,
+    received_ns: u128, // 🆕 Chegada do pacote TCP (base da latência dos tags críticos)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,6 +186,13 @@ pub struct WebSocketStats {
     pub uptime_seconds: u64,
     pub server_status: String,
     pub broadcast_rate_hz: f64,
+    pub suppressed_events: u64, // 🆕 Mudanças coalescidas/descartadas (min_resend_ms / debounce_ms)
+    // 🆕 CAMINHO CRÍTICO: latência pacote TCP -> envio ao cliente
+    pub critical_updates_sent: u64,
+    pub critical_latency_last_ms: f64,
+    pub critical_latency_avg_ms: f64,
+    pub critical_latency_max_ms: f64,
+    pub db_degraded: bool, // 🆕 Circuit breaker do SQLite aberto: servindo só do cache
 }
 
 // 🚀 SISTEMA DE CACHE INTELIGENTE PARA PERFORMANCE MÁXIMA
@@ -161,6 +210,106 @@ pub struct CachedTagValue {
     // 🆕 CAMPOS PARA FILTRAGEM INTELIGENTE
     pub area: Option<String>,     // ENH, ESV, PJU, PMO, SCO, EDR
     pub category: Option<String>, // PROC, FAULT, EVENT, ALARM
+    // 🆕 COALESCÊNCIA (modo "change")
+    pub min_resend_ms: u64,
+    pub suppressed: u64,          // Transições descartadas para este tag
+    pub is_edge: bool,            // 🆕 Edge tag (RISE/FALL): volta a FALSE após um envio
+    pub unit: Option<String>,     // 🆕 Unidade publicada (após conversão por tag)
+    pub priority: u8,             // 🆕 Prioridade do grupo (área/categoria) para filtro por cliente
+    pub critical: bool,           // 🆕 Tag crítico: mudanças vão pelo caminho de alta prioridade
+}
+
+// 🆕 TAGS CRÍTICOS (bits de segurança): cada mudança é enviada na hora pelo canal
+// de alta prioridade do cliente ({"type":"CRITICAL",...}), sem esperar os timers
+// de 500 ms / 2 s dos lotes. Os lotes continuam enviando o tag normalmente.
+#[derive(Debug, Clone)]
+pub struct CriticalUpdate {
+    pub plc_ip: String,
+    pub tag_name: String,
+    pub value: String,
+    pub data_type: String,
+    pub received_ns: u128, // Chegada do pacote TCP que trouxe a mudança
+}
+
+// 🆕 OVERRIDE TEMPORÁRIO DO INTERVALO DE UM GRUPO (ex: "modo rápido" em teste de comporta).
+// Vale para os tags em modo "interval" da área/categoria; tags "change" já saem a
+// cada mudança. Expirado, o intervalo configurado volta sozinho no próximo pacote.
+pub const MAX_OVERRIDE_INTERVAL_S: u64 = 10;        // Maior intervalo atendido pelos lotes (8-10s)
+pub const MAX_OVERRIDE_DURATION_S: u64 = 8 * 3600;  // Override esquecido não fica para o turno seguinte
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupIntervalOverride {
+    pub group_type: String,        // "area" ou "category"
+    pub group_name: String,
+    pub interval_s: u64,           // Intervalo efetivo enquanto ativo
+    pub set_at_ms: i64,
+    pub expires_at_ms: i64,
+    pub reason: Option<String>,
+}
+
+/// Latência do caminho crítico medida no envio (chegada TCP -> frame escrito no socket)
+#[derive(Debug, Default)]
+pub struct CriticalLatency {
+    sent: AtomicU64,
+    last_us: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl CriticalLatency {
+    fn record(&self, received_ns: u128) {
+        if received_ns == 0 {
+            return;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let latency_us = (now.saturating_sub(received_ns) / 1_000) as u64;
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        self.last_us.store(latency_us, Ordering::Relaxed);
+        self.total_us.fetch_add(latency_us, Ordering::Relaxed);
+        self.max_us.fetch_max(latency_us, Ordering::Relaxed);
+    }
+    
+    /// (enviados, última, média, máxima) em ms
+    fn snapshot(&self) -> (u64, f64, f64, f64) {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let avg_us = if sent > 0 { self.total_us.load(Ordering::Relaxed) as f64 / sent as f64 } else { 0.0 };
+        (
+            sent,
+            self.last_us.load(Ordering::Relaxed) as f64 / 1000.0,
+            avg_us / 1000.0,
+            self.max_us.load(Ordering::Relaxed) as f64 / 1000.0,
+        )
+    }
+}
+
+/// Filtros de SUBSCRIBE de um cliente (PLC, área, categoria, prioridade mínima).
+/// FAULT/ALARM passam pelos filtros de área e categoria com `include_all_faults`.
+fn passes_client_filters(
+    cached: &CachedTagValue,
+    plc_ips: &std::collections::HashSet<String>,
+    areas: &std::collections::HashSet<String>,
+    categories: &std::collections::HashSet<String>,
+    include_all_faults: bool,
+    min_priority: u8,
+) -> bool {
+    if !plc_ips.is_empty() && !plc_ips.contains(&cached.plc_ip) {
+        return false;
+    }
+    let is_fault = matches!(cached.category.as_deref(), Some("FAULT") | Some("ALARM"));
+    if !areas.is_empty() && !areas.contains(cached.area.as_deref().unwrap_or("")) && !(include_all_faults && is_fault) {
+        return false;
+    }
+    if !categories.is_empty() && !categories.contains(cached.category.as_deref().unwrap_or("")) && !(include_all_faults && is_fault) {
+        return false;
+    }
+    cached.priority >= min_priority
+}
+
+impl CachedTagValue {
+    /// Respeita o intervalo mínimo entre reenvios do mesmo tag
+    fn resend_allowed(&self, now: u128) -> bool {
+        self.min_resend_ms == 0 || now.saturating_sub(self.last_sent) / 1_000_000 >= self.min_resend_ms as u128
+    }
 }
 
 #[derive(Debug)]
@@ -180,6 +329,33 @@ pub struct SmartCache {
     cache_size_limit: usize, // Máximo de entradas no cache
     memory_pressure_threshold: AtomicUsize, // Threshold para limpeza automática
     last_cleanup: Arc<RwLock<std::time::Instant>>, // Última limpeza de memória
+    
+    // 🆕 BUFFER DURANTE RELOAD: Some(..) = tasks de broadcast reiniciando, guardar atualizações
+    reload_buffer: std::sync::Mutex<Option<Vec<CacheUpdateData>>>,
+    
+    // 🆕 DEBOUNCE: tag_key -> (valor candidato, desde quando em ns)
+    debounce_pending: Arc<DashMap<String, (String, u128)>>,
+    suppressed_events: AtomicU64,
+    
+    // 🆕 PLAYBACK HISTÓRICO: estado reproduzido (None = sem playback). Fica fora do
+    // cache ao vivo; só os clientes que pediram PLAYBACK recebem (ver dispatch_playback)
+    playback_tx: watch::Sender<Option<Arc<PlaybackFrame>>>,
+    
+    // 🆕 PRIORIDADE POR GRUPO: "area:ENH" / "category:FAULT" -> prioridade
+    group_priorities: Arc<DashMap<String, u8>>,
+    
+    // 🆕 LATÊNCIA DO CAMINHO CRÍTICO
+    critical_latency: CriticalLatency,
+    
+    // 🆕 OVERRIDES DE INTERVALO POR GRUPO: "area:ENH" / "category:PROC" -> override
+    interval_overrides: Arc<DashMap<String, GroupIntervalOverride>>,
+    
+    // 🆕 CIRCUIT BREAKER: SQLite lento não trava o processador do cache
+    db_breaker: DbCircuitBreaker,
+    
+    // 🆕 CLUSTER DE BROADCASTERS: cópia dos valores e tags críticos para as instâncias extras
+    snapshot_tx: watch::Sender<Arc<Vec<CachedTagValue>>>,
+    critical_broadcast: broadcast::Sender<Arc<Vec<CriticalUpdate>>>,
 }
 
 #[derive(Debug)]
@@ -195,8 +371,84 @@ pub struct ConnectedClient {
     pub subscribed_areas: Arc<RwLock<std::collections::HashSet<String>>>,     // ENH, ESV, PJU, PMO, SCO, EDR
     pub subscribed_categories: Arc<RwLock<std::collections::HashSet<String>>>, // PROC, FAULT, EVENT, ALARM
     pub include_all_faults: Arc<AtomicBool>, // Sempre receber TODAS as falhas (para painel de alarmes)
+    pub min_priority: Arc<AtomicU8>, // 🆕 Só receber tags com prioridade >= N (links de baixa banda)
     // 🆕 CANAL PARA ENVIO DE MENSAGENS FILTRADAS PARA ESTE CLIENTE
-    pub filtered_tx: Option<mpsc::Sender<String>>,
+    pub frame_tx: Option<mpsc::Sender<Message>>, // 🆕 Frames de saída (texto ou binário, conforme negociado)
+    pub critical_tx: Option<mpsc::Sender<(String, u128)>>, // 🆕 Canal de alta prioridade (mensagem, chegada TCP em ns)
+    pub features: Arc<ClientFeatures>,            // 🆕 Recursos negociados via HELLO
+    pub masking: Option<Arc<StreamMask>>,         // 🆕 Cliente público (chave na autenticação/HELLO): valores mascarados, nunca removido
+    pub playback: Arc<AtomicBool>,                // 🆕 Recebe o playback histórico em vez dos dados ao vivo
+    pub token_id: Option<i64>,                    // 🆕 Token de API usado na conexão (ws_auth.rs)
+    pub token_name: Option<String>,
+    pub disconnect: Arc<tokio::sync::Notify>,     // 🆕 Derrubar a conexão (ex: token revogado)
+    // 🆕 ESTATÍSTICAS POR CLIENTE (persistidas em ws_client_sessions)
+    pub bytes_sent: Arc<AtomicU64>,
+    pub messages_sent: Arc<AtomicU64>,
+    pub session_id: Option<i64>,
+}
+
+impl ConnectedClient {
+    /// 🆕 Estado atual do cliente no formato do registro de sessão
+    fn session_record(&self, session_id: i64) -> WsClientSession {
+        WsClientSession {
+            id: session_id,
+            client_id: self.id as i64,
+            address: self.address.to_string(),
+            token_id: self.token_id,
+            token_name: self.token_name.clone(),
+            public_key: self.masking.as_ref().map(|mask| mask.key_name.clone()),
+            connected_at_ms: self.connected_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64,
+            last_seen_ms: chrono::Utc::now().timestamp_millis(),
+            disconnected_at_ms: None,
+            disconnect_reason: None,
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed) as i64,
+            messages_sent: self.messages_sent.load(Ordering::Relaxed) as i64,
+            messages_received: self.messages_received.load(Ordering::SeqCst) as i64,
+        }
+    }
+}
+
+/// 🆕 Prende a chave pública à conexão; uma vez mascarada, a conexão não troca de chave
+fn bind_public_key(clients: &DashMap<u64, ConnectedClient>, client_id: u64, key: &PublicStreamKey) -> Result<(), String> {
+    let Some(mut client) = clients.get_mut(&client_id) else { return Ok(()) };
+    match &client.masking {
+        Some(mask) if mask.key_name != key.name => {
+            Err(format!("Conexão já autenticada com a chave pública '{}'", mask.key_name))
+        }
+        Some(_) => Ok(()),
+        None => {
+            println!("🌐 Cliente {} usando chave pública '{}' ({} regras)", client_id, key.name, key.rules.len());
+            client.masking = Some(Arc::new(StreamMask::new(key)));
+            Ok(())
+        }
+    }
+}
+
+// 🆕 Intervalo de atualização da sessão aberta (last_seen_ms e contadores)
+const SESSION_HEARTBEAT: Duration = Duration::from_secs(60);
+
+// 🆕 CLUSTER DE BROADCASTERS: instâncias extras (workers) em outras portas do
+// mesmo processo, lendo o SmartCache da instância principal. Os lotes da
+// principal marcam os tags como enviados, então os workers não consultam o
+// cache direto: recebem a cópia publicada a cada 100 ms (canal watch) e os tags
+// críticos (canal broadcast), e cada worker calcula o que mudou desde o último
+// envio dele. Cada worker tem seus clientes, filtros e tasks de envio.
+pub const MAX_CLUSTER_WORKERS: usize = 8;
+const CLUSTER_CRITICAL_CAPACITY: usize = 64;
+const CLUSTER_FANOUT_INTERVAL: Duration = Duration::from_millis(100);
+const CLUSTER_CLIENT_ID_STRIDE: u64 = 1_000_000; // IDs dos clientes do worker: porta * stride + n
+
+/// Tag da cópia do cache que o worker deve enviar agora (último envio: valor, instante em ns)
+fn cluster_due(cached: &CachedTagValue, last: Option<&(String, u128)>, now: u128) -> bool {
+    if crate::plc_parser::is_waveform_type(&cached.data_type) {
+        return false;
+    }
+    let Some((last_value, last_sent)) = last else { return true };
+    let elapsed = now.saturating_sub(*last_sent);
+    match cached.collect_mode.as_str() {
+        "interval" => elapsed / 1_000_000_000 >= cached.interval_s.max(1) as u128,
+        _ => *last_value != cached.value && (cached.min_resend_ms == 0 || elapsed / 1_000_000 >= cached.min_resend_ms as u128),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -217,7 +469,7 @@ pub struct WebSocketServer {
     app_handle: AppHandle,
     database: Arc<Database>,
     tcp_server: Option<Arc<RwLock<Option<TcpServer>>>>,
-    broadcast_sender: Option<broadcast::Sender<String>>,
+    broadcast_sender: Option<broadcast::Sender<Message>>, // 🆕 Frames prontos (texto/binário) para todos os clientes
     server_handle: Option<tokio::task::JoinHandle<()>>,
     broadcast_handle: Option<tokio::task::JoinHandle<()>>,
     interval_handles: Arc<TokioMutex<Vec<tokio::task::JoinHandle<()>>>>,
@@ -225,10 +477,12 @@ pub struct WebSocketServer {
     cache_updater_handle: Option<tokio::task::JoinHandle<()>>,
     // ✅ MELHORIA: Broadcasting por PLC específico
     plc_broadcast_channels: Arc<DashMap<String, broadcast::Sender<String>>>,
+    // 🆕 Worker do cluster: porta da instância extra (None = instância principal)
+    worker_port: Option<u16>,
 }
 
 impl SmartCache {
-    pub fn new() -> Self {
+    pub fn new(app_handle: AppHandle) -> Self {
         Self {
             tag_cache: Arc::new(DashMap::new()),
             interval_groups: Arc::new(RwLock::new(HashMap::new())),
@@ -241,12 +495,44 @@ impl SmartCache {
             cache_size_limit: 2000, // Máximo 2000 tags em cache (~400KB)
             memory_pressure_threshold: AtomicUsize::new(1500), // Iniciar limpeza em 1500 tags
             last_cleanup: Arc::new(RwLock::new(std::time::Instant::now())),
+            reload_buffer: std::sync::Mutex::new(None),
+            debounce_pending: Arc::new(DashMap::new()),
+            suppressed_events: AtomicU64::new(0),
+            playback_tx: watch::channel(None).0,
+            group_priorities: Arc::new(DashMap::new()),
+            critical_latency: CriticalLatency::default(),
+            interval_overrides: Arc::new(DashMap::new()),
+            db_breaker: DbCircuitBreaker::new(app_handle),
+            snapshot_tx: watch::channel(Arc::new(Vec::new())).0,
+            critical_broadcast: broadcast::channel(CLUSTER_CRITICAL_CAPACITY).0,
+        }
+    }
+    
+    // 🆕 CANAIS DO CLUSTER (só publicam quando existe instância extra inscrita)
+    pub fn subscribe_snapshots(&self) -> watch::Receiver<Arc<Vec<CachedTagValue>>> {
+        self.snapshot_tx.subscribe()
+    }
+    
+    pub fn subscribe_critical(&self) -> broadcast::Receiver<Arc<Vec<CriticalUpdate>>> {
+        self.critical_broadcast.subscribe()
+    }
+    
+    fn publish_snapshot(&self) {
+        if self.snapshot_tx.receiver_count() > 0 {
+            self.snapshot_tx.send_replace(Arc::new(self.snapshot(None)));
+        }
+    }
+    
+    fn publish_critical(&self, updates: &[CriticalUpdate]) {
+        if !updates.is_empty() && self.critical_broadcast.receiver_count() > 0 {
+            let _ = self.critical_broadcast.send(Arc::new(updates.to_vec()));
         }
     }
 
     pub async fn clear(&self) {
         self.tag_cache.clear();
         self.change_tracking.clear();
+        self.debounce_pending.clear();
         let mut lock = self.interval_groups.write().await;
         lock.clear();
         // 🆕 LIMPAR CACHE DE MAPPINGS TAMBÉM
@@ -254,10 +540,15 @@ impl SmartCache {
     }
     
     // 🆕 CARREGAR TAGS DO BANCO PARA CACHE (chamado apenas quando necessário)
+    /// Com o circuit breaker aberto o banco não é consultado e o cache atual continua valendo
     pub async fn load_tag_mappings_to_cache(&self, plc_ip: &str, database: &Database) {
-        match database.get_active_tags(plc_ip) {
+        let Some(result) = self.db_breaker.call("get_active_tags", || database.get_active_tags(plc_ip)) else {
+            return;
+        };
+        match result {
             Ok(tags) => {
                 println!("📦 Cache: Carregados {} tags ativos para PLC {}", tags.len(), plc_ip);
+                self.load_group_priorities(database);
                 self.tag_mappings_cache.insert(plc_ip.to_string(), tags);
                 *self.tag_mappings_last_update.write().await = std::time::Instant::now();
             }
@@ -267,6 +558,82 @@ impl SmartCache {
         }
     }
     
+    // 🆕 PRIORIDADES DOS GRUPOS (recarregadas junto com os mappings)
+    fn load_group_priorities(&self, database: &Database) {
+        let Some(result) = self.db_breaker.call("list_tag_group_priorities", || database.list_tag_group_priorities()) else {
+            return;
+        };
+        match result {
+            Ok(priorities) => {
+                self.group_priorities.clear();
+                for p in priorities {
+                    self.group_priorities.insert(format!("{}:{}", p.group_type, p.group_name), p.priority);
+                }
+            }
+            Err(e) => println!("⚠️ Cache: Erro ao carregar prioridades de grupos: {}", e),
+        }
+    }
+    
+    /// Prioridade efetiva de um tag: a maior entre a da área e a da categoria (padrão 0)
+    fn priority_for(&self, area: Option<&str>, category: Option<&str>) -> u8 {
+        let lookup = |group_type: &str, name: Option<&str>| {
+            name.and_then(|n| self.group_priorities.get(&format!("{}:{}", group_type, n)).map(|p| *p))
+                .unwrap_or(0)
+        };
+        lookup("area", area).max(lookup("category", category))
+    }
+    
+    // 🆕 OVERRIDES TEMPORÁRIOS DE INTERVALO
+    /// Ativa (ou substitui) o override do grupo; retorna o anterior
+    pub fn set_interval_override(&self, entry: GroupIntervalOverride) -> Option<GroupIntervalOverride> {
+        self.interval_overrides.insert(format!("{}:{}", entry.group_type, entry.group_name), entry)
+    }
+    
+    pub fn clear_interval_override(&self, group_type: &str, group_name: &str) -> Option<GroupIntervalOverride> {
+        self.interval_overrides.remove(&format!("{}:{}", group_type, group_name)).map(|(_, entry)| entry)
+    }
+    
+    /// Overrides ainda válidos
+    pub fn list_interval_overrides(&self) -> Vec<GroupIntervalOverride> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let mut list: Vec<GroupIntervalOverride> = self.interval_overrides.iter()
+            .filter(|e| e.expires_at_ms > now_ms)
+            .map(|e| e.value().clone())
+            .collect();
+        list.sort_by(|a, b| (&a.group_type, &a.group_name).cmp(&(&b.group_type, &b.group_name)));
+        list
+    }
+    
+    /// Remove e retorna os overrides vencidos (para auditar a reversão)
+    pub fn expire_interval_overrides(&self) -> Vec<GroupIntervalOverride> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let expired: Vec<String> = self.interval_overrides.iter()
+            .filter(|e| e.expires_at_ms <= now_ms)
+            .map(|e| e.key().clone())
+            .collect();
+        expired.iter()
+            .filter_map(|key| self.interval_overrides.remove(key).map(|(_, entry)| entry))
+            .collect()
+    }
+    
+    /// Intervalo efetivo de um tag "interval": o menor override ativo da área/categoria, senão o configurado
+    fn effective_interval_s(&self, area: Option<&str>, category: Option<&str>, configured: u64) -> u64 {
+        if self.interval_overrides.is_empty() {
+            return configured;
+        }
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let lookup = |group_type: &str, name: Option<&str>| {
+            name.and_then(|n| self.interval_overrides.get(&format!("{}:{}", group_type, n)))
+                .filter(|e| e.expires_at_ms > now_ms)
+                .map(|e| e.interval_s)
+        };
+        match (lookup("area", area), lookup("category", category)) {
+            (Some(a), Some(c)) => a.min(c),
+            (Some(s), None) | (None, Some(s)) => s,
+            (None, None) => configured,
+        }
+    }
+    
     // 🆕 OBTER TAGS DO CACHE (ZERO CONSULTAS AO BANCO!)
     fn get_cached_tags(&self, plc_ip: &str) -> Option<Vec<TagMapping>> {
         self.tag_mappings_cache.get(plc_ip).map(|r| r.value().clone())
@@ -280,7 +647,9 @@ impl SmartCache {
     }
     
     // ✅ ATUALIZAR CACHE COM DADOS TCP - AGORA USA CACHE DE TAGS!
-    pub async fn update_from_tcp(&self, plc_ip: &str, variables: &[crate::tcp_server::PlcVariable], database: &Database) {
+    /// Retorna as mudanças de tags críticos para envio imediato
+    pub async fn update_from_tcp(&self, plc_ip: &str, variables: &[crate::tcp_server::PlcVariable], database: &Database, received_ns: u128) -> Vec<CriticalUpdate> {
+        let mut critical_updates = Vec::new();
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|_| Duration::from_secs(0))
@@ -292,51 +661,127 @@ impl SmartCache {
             cached_tags
         } else {
             // ⚠️ CACHE MISS - Carregar do banco (acontece raramente)
+            // 🆕 Modo degradado (SQLite lento): PLC sem tags no cache fica sem dados até o banco voltar
+            if self.db_breaker.is_degraded() {
+                return critical_updates;
+            }
             println!("⚠️ Cache miss para PLC {} - carregando do banco", plc_ip);
             self.load_tag_mappings_to_cache(plc_ip, database).await;
             self.get_cached_tags(plc_ip).unwrap_or_default()
         };
         
+        // 🆕 Valores de origem ANTES desta atualização (para detectar bordas)
+        let edge_tags: Vec<(TagMapping, EdgeKind, String, Option<String>)> = tags.iter()
+            .filter_map(|tag| {
+                let (kind, source) = parse_edge_path(&tag.variable_path)?;
+                let previous = self.tag_cache.get(&format!("{}:{}", plc_ip, source)).map(|e| e.value.clone());
+                Some((tag.clone(), kind, source.to_string(), previous))
+            })
+            .collect();
+        
         for tag in tags {
-            // 🚀 LÓGICA DE EXTRAÇÃO DE BITS (Bit-Parser)
-            let (search_name, bit_index) = if tag.variable_path.contains('.') && !tag.variable_path.starts_with("DB") {
-                let parts: Vec<&str> = tag.variable_path.split('.').collect();
-                if parts.len() == 2 {
-                    if let Ok(bit) = parts[1].parse::<u8>() {
-                         (parts[0], Some(bit))
-                    } else {
-                         (tag.variable_path.as_str(), None)
-                    }
-                } else {
-                    (tag.variable_path.as_str(), None)
-                }
-            } else {
-                (tag.variable_path.as_str(), None)
-            };
+            if parse_edge_path(&tag.variable_path).is_some() {
+                continue;
+            }
+            
+            // 🚀 EXTRAÇÃO POR variable_path (bit, faixa de bits, byte, REAL com words trocadas)
+            let (search_name, selector) = crate::plc_parser::split_variable_path(&tag.variable_path)
+                .unwrap_or((tag.variable_path.as_str(), None));
+            let is_bit = matches!(selector, Some(crate::plc_parser::PathSelector::Bit(_)));
 
             // Encontrar variável correspondente
             if let Some(variable) = variables.iter().find(|v| v.name == search_name) {
                 let tag_key = format!("{}:{}", plc_ip, tag.tag_name);
                 
                 // Determinar valor final
-                let final_value = if let Some(bit) = bit_index {
-                    if let Ok(int_val) = variable.value.parse::<u64>() {
-                         let bit_val = (int_val >> bit) & 1;
-                         if bit_val == 1 { "TRUE".to_string() } else { "FALSE".to_string() }
-                    } else {
-                         variable.value.clone()
-                    }
+                let (final_value, data_type) = crate::plc_parser::extract_tag_value(selector, variable);
+                
+                // 🆕 ESCALONAMENTO + CONVERSÃO DE UNIDADE (bits não são convertidos)
+                let (final_value, unit) = if is_bit {
+                    (final_value, None)
                 } else {
-                    variable.value.clone()
+                    let scaled = crate::units::apply_tag_scaling(&tag, &final_value);
+                    crate::units::apply_tag_conversion(&tag, &scaled)
                 };
 
                 // Verificar mudança para tags em modo "change"
+                let is_change_mode = matches!(tag.collect_mode.as_deref(), Some("change") | Some("on_change"));
                 let mut value_changed = true;
-                if tag.collect_mode.as_deref() == Some("change") {
-                    if let Some(last_value) = self.change_tracking.get(&tag_key) {
-                        value_changed = last_value.value() != &final_value;
+                let mut suppressed = 0;
+                if is_change_mode {
+                    let debounce_ns = tag.debounce_ms.unwrap_or(0).max(0) as u128 * 1_000_000;
+                    let last_value = self.change_tracking.get(&tag_key).map(|v| v.value().clone());
+                    
+                    value_changed = match last_value {
+                        // Voltou ao valor confirmado: candidato pendente era bounce
+                        Some(last) if last == final_value => {
+                            if self.debounce_pending.remove(&tag_key).is_some() {
+                                suppressed += 1;
+                            }
+                            false
+                        }
+                        // 🆕 BANDA MORTA: ruído em torno do valor confirmado não é mudança
+                        Some(last) if !crate::units::outside_deadband(&tag, &last, &final_value) => {
+                            self.debounce_pending.remove(&tag_key);
+                            suppressed += 1;
+                            false
+                        }
+                        // 🆕 DEBOUNCE: o novo valor precisa ficar estável antes de contar
+                        Some(_) if debounce_ns > 0 => {
+                            let pending = self.debounce_pending.get(&tag_key).map(|p| p.value().clone());
+                            match pending {
+                                Some((pending_value, since)) if pending_value == final_value => {
+                                    now.saturating_sub(since) >= debounce_ns
+                                }
+                                other => {
+                                    if other.is_some() {
+                                        suppressed += 1;
+                                    }
+                                    self.debounce_pending.insert(tag_key.clone(), (final_value.clone(), now));
+                                    false
+                                }
+                            }
+                        }
+                        _ => true,
+                    };
+                    
+                    if value_changed {
+                        self.debounce_pending.remove(&tag_key);
+                        self.change_tracking.insert(tag_key.clone(), final_value.clone());
+                    } else if self.debounce_pending.contains_key(&tag_key) {
+                        // Candidato ainda em debounce: manter o valor confirmado no cache
+                        if suppressed > 0 {
+                            self.record_suppressed(&tag_key, suppressed);
+                        }
+                        continue;
+                    }
+                }
+                
+                // 🆕 Preservar envio pendente e last_sent (mudança ainda não enviada é coalescida)
+                let (pending_send, last_sent, previous_suppressed) = match self.tag_cache.get(&tag_key) {
+                    Some(prev) if is_change_mode => (prev.changed, prev.last_sent, prev.suppressed),
+                    Some(prev) => (false, 0, prev.suppressed),
+                    None => (false, 0, 0),
+                };
+                if value_changed && pending_send {
+                    suppressed += 1;
+                }
+                if suppressed > 0 {
+                    self.suppressed_events.fetch_add(suppressed, Ordering::Relaxed);
+                }
+                
+                // 🆕 Tag crítico mudou de valor: caminho de alta prioridade
+                if tag.critical {
+                    let previous = self.tag_cache.get(&tag_key).map(|prev| prev.value.clone());
+                    if previous.is_some_and(|prev| prev != final_value && crate::units::outside_deadband(&tag, &prev, &final_value)) {
+                        critical_updates.push(CriticalUpdate {
+                            plc_ip: plc_ip.to_string(),
+                            tag_name: tag.tag_name.clone(),
+                            value: final_value.clone(),
+                            data_type: data_type.clone(),
+                            received_ns,
+                        });
                     }
-                    self.change_tracking.insert(tag_key.clone(), final_value.clone());
                 }
                 
                 // Atualizar cache
@@ -344,20 +789,80 @@ impl SmartCache {
                     tag_name: tag.tag_name.clone(),
                     plc_ip: plc_ip.to_string(),
                     value: final_value,
-                    data_type: if bit_index.is_some() { "BOOL".to_string() } else { variable.data_type.clone() },
+                    data_type,
                     timestamp_ns: now,
                     collect_mode: tag.collect_mode.clone().unwrap_or_default(),
-                    interval_s: tag.collect_interval_s.unwrap_or(1) as u64,
-                    last_sent: 0,
-                    changed: value_changed,
+                    interval_s: self.effective_interval_s(tag.area.as_deref(), tag.category.as_deref(), tag.collect_interval_s.unwrap_or(1) as u64),
+                    last_sent,
+                    changed: value_changed || pending_send,
                     // 🆕 GUARDAR ÁREA E CATEGORIA PARA FILTRAGEM
                     area: tag.area.clone(),
                     category: tag.category.clone(),
+                    min_resend_ms: tag.min_resend_ms.unwrap_or(0).max(0) as u64,
+                    suppressed: previous_suppressed + suppressed,
+                    is_edge: false,
+                    unit,
+                    priority: self.priority_for(tag.area.as_deref(), tag.category.as_deref()),
+                    critical: tag.critical,
                 };
                 
                 self.tag_cache.insert(tag_key, cached);
             }
         }
+        
+        // 🆕 EDGE TAGS: comparar valor anterior e atual do tag de origem
+        for (edge_tag, kind, source, previous) in edge_tags {
+            let source_key = format!("{}:{}", plc_ip, source);
+            let current = match self.tag_cache.get(&source_key) {
+                Some(entry) => entry.value.clone(),
+                None => continue,
+            };
+            
+            let fired = match previous {
+                Some(prev) => match kind {
+                    EdgeKind::Rising => !is_truthy(&prev) && is_truthy(&current),
+                    EdgeKind::Falling => is_truthy(&prev) && !is_truthy(&current),
+                },
+                None => false, // Primeira amostra: sem transição
+            };
+            
+            let edge_key = format!("{}:{}", plc_ip, edge_tag.tag_name);
+            let exists = self.tag_cache.contains_key(&edge_key);
+            if !fired && exists {
+                continue;
+            }
+            if fired && edge_tag.critical {
+                critical_updates.push(CriticalUpdate {
+                    plc_ip: plc_ip.to_string(),
+                    tag_name: edge_tag.tag_name.clone(),
+                    value: "TRUE".to_string(),
+                    data_type: "BOOL".to_string(),
+                    received_ns,
+                });
+            }
+            
+            self.tag_cache.insert(edge_key, CachedTagValue {
+                tag_name: edge_tag.tag_name.clone(),
+                plc_ip: plc_ip.to_string(),
+                value: if fired { "TRUE".to_string() } else { "FALSE".to_string() },
+                data_type: "BOOL".to_string(),
+                timestamp_ns: now,
+                collect_mode: "change".to_string(),
+                interval_s: 1,
+                last_sent: 0,
+                changed: true,
+                area: edge_tag.area.clone(),
+                category: edge_tag.category.clone(),
+                min_resend_ms: 0,
+                suppressed: 0,
+                is_edge: true,
+                unit: None,
+                priority: self.priority_for(edge_tag.area.as_deref(), edge_tag.category.as_deref()),
+                critical: edge_tag.critical,
+            });
+        }
+        
+        critical_updates
     }
     
     // Obter tags que precisam ser enviados baseado no intervalo
@@ -371,6 +876,10 @@ impl SmartCache {
         
         for entry in self.tag_cache.iter() {
             let cached = entry.value();
+            // 🆕 Forma de onda: só sob demanda (GET_WAVEFORM)
+            if crate::plc_parser::is_waveform_type(&cached.data_type) {
+                continue;
+            }
             let time_since_last = if now >= cached.last_sent {
                 (now - cached.last_sent) / 1_000_000_000
             } else {
@@ -378,7 +887,7 @@ impl SmartCache {
             };
             
             let should_send = match cached.collect_mode.as_str() {
-                "change" => cached.changed && time_since_last >= interval_s as u128,
+                "change" => cached.changed && cached.resend_allowed(now) && time_since_last >= interval_s as u128,
                 "interval" => cached.interval_s == interval_s && time_since_last >= interval_s as u128,
                 _ => false,
             };
@@ -392,7 +901,11 @@ impl SmartCache {
         for key in keys_to_update {
             if let Some(mut cached_mut) = self.tag_cache.get_mut(&key) {
                 cached_mut.last_sent = now;
-                cached_mut.changed = false;
+                // 🆕 Edge tag enviado como TRUE: resetar e enviar FALSE no próximo ciclo
+                cached_mut.changed = cached_mut.is_edge && cached_mut.value == "TRUE";
+                if cached_mut.changed {
+                    cached_mut.value = "FALSE".to_string();
+                }
             }
         }
         
@@ -406,7 +919,8 @@ impl SmartCache {
         plc_ips: &std::collections::HashSet<String>,
         areas: &std::collections::HashSet<String>,
         categories: &std::collections::HashSet<String>,
-        include_all_faults: bool
+        include_all_faults: bool,
+        min_priority: u8
     ) -> HashMap<String, String> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -415,43 +929,17 @@ impl SmartCache {
         let mut result = HashMap::new();
         let mut keys_to_update = Vec::new();
         
-        let has_plc_filter = !plc_ips.is_empty();
-        let has_area_filter = !areas.is_empty();
-        let has_category_filter = !categories.is_empty();
-        
         for entry in self.tag_cache.iter() {
             let cached = entry.value();
             
-            // 1. Filtrar por PLC
-            if has_plc_filter && !plc_ips.contains(&cached.plc_ip) {
+            // 🆕 Forma de onda: só sob demanda (GET_WAVEFORM)
+            if crate::plc_parser::is_waveform_type(&cached.data_type) {
                 continue;
             }
             
-            // 2. Filtrar por área (se configurado)
-            if has_area_filter {
-                let tag_area = cached.area.as_deref().unwrap_or("");
-                let area_match = areas.contains(tag_area);
-                
-                // Exceção: se include_all_faults e é uma falha, incluir independente da área
-                let is_fault = cached.category.as_deref() == Some("FAULT") || 
-                              cached.category.as_deref() == Some("ALARM");
-                
-                if !area_match && !(include_all_faults && is_fault) {
-                    continue;
-                }
-            }
-            
-            // 3. Filtrar por categoria (se configurado)
-            if has_category_filter {
-                let tag_category = cached.category.as_deref().unwrap_or("");
-                let category_match = categories.contains(tag_category);
-                
-                // Exceção: sempre incluir FAULT e ALARM se include_all_faults
-                let is_fault = tag_category == "FAULT" || tag_category == "ALARM";
-                
-                if !category_match && !(include_all_faults && is_fault) {
-                    continue;
-                }
+            // 1-3. PLC, área, categoria e prioridade mínima do cliente
+            if !passes_client_filters(cached, plc_ips, areas, categories, include_all_faults, min_priority) {
+                continue;
             }
             
             // 4. Verificar timing
@@ -462,9 +950,9 @@ impl SmartCache {
             };
             
             let should_send = match cached.collect_mode.as_str() {
-                "change" => cached.changed && time_since_last >= interval_s as u128,
+                "change" => cached.changed && cached.resend_allowed(now) && time_since_last >= interval_s as u128,
                 "interval" => cached.interval_s == interval_s && time_since_last >= interval_s as u128,
-                "on_change" => cached.changed && time_since_last >= interval_s as u128,
+                "on_change" => cached.changed && cached.resend_allowed(now) && time_since_last >= interval_s as u128,
                 _ => time_since_last >= interval_s as u128, // Default: enviar baseado no intervalo
             };
             
@@ -477,13 +965,247 @@ impl SmartCache {
         for key in keys_to_update {
             if let Some(mut cached_mut) = self.tag_cache.get_mut(&key) {
                 cached_mut.last_sent = now;
-                cached_mut.changed = false;
+                // 🆕 Edge tag enviado como TRUE: resetar e enviar FALSE no próximo ciclo
+                cached_mut.changed = cached_mut.is_edge && cached_mut.value == "TRUE";
+                if cached_mut.changed {
+                    cached_mut.value = "FALSE".to_string();
+                }
             }
         }
         
         result
     }
     
+    // 🆕 CÓPIA DOS VALORES EM CACHE (consultas externas, ex: GraphQL, ponte OPC DA)
+    pub fn snapshot(&self, plc_ip: Option<&str>) -> Vec<CachedTagValue> {
+        self.tag_cache.iter()
+            .filter(|entry| plc_ip.is_none() || plc_ip == Some(entry.value().plc_ip.as_str()))
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+    
+    // 🆕 TIPO E TIMESTAMP (epoch ms) DOS TAGS DE UM LOTE (valores tipados / timestamps por tag)
+    pub fn tag_details(&self, tags: &BTreeMap<String, String>) -> HashMap<String, (String, u64)> {
+        self.tag_cache.iter()
+            .filter(|entry| tags.contains_key(&entry.value().tag_name))
+            .map(|entry| {
+                let cached = entry.value();
+                (cached.tag_name.clone(), (cached.data_type.clone(), (cached.timestamp_ns / 1_000_000) as u64))
+            })
+            .collect()
+    }
+    
+    // 🆕 ÁREA/CATEGORIA E TIMESTAMP DOS TAGS DE UM LOTE (mascaramento de clientes públicos)
+    pub fn tag_groups(&self, tags: &HashMap<String, String>) -> HashMap<String, TagGroups> {
+        self.tag_cache.iter()
+            .filter(|entry| tags.contains_key(&entry.value().tag_name))
+            .map(|entry| {
+                let cached = entry.value();
+                (cached.tag_name.clone(), TagGroups {
+                    area: cached.area.clone(),
+                    category: cached.category.clone(),
+                    ts_ms: (cached.timestamp_ns / 1_000_000) as u64,
+                })
+            })
+            .collect()
+    }
+    
+    // 🆕 ÚLTIMA FORMA DE ONDA DE UM TAG (PLC opcional: primeiro tag com o nome)
+    pub fn get_waveform(&self, plc_ip: Option<&str>, tag_name: &str) -> Option<CachedTagValue> {
+        self.tag_cache.iter()
+            .find(|entry| {
+                let cached = entry.value();
+                cached.tag_name == tag_name
+                    && plc_ip.map_or(true, |ip| ip == cached.plc_ip)
+                    && crate::plc_parser::is_waveform_type(&cached.data_type)
+            })
+            .map(|entry| entry.value().clone())
+    }
+    
+    // 🆕 VALOR ATUAL DE UM TAG (ex: logger CSV)
+    pub fn get_value(&self, plc_ip: &str, tag_name: &str) -> Option<String> {
+        self.tag_cache.get(&format!("{}:{}", plc_ip, tag_name)).map(|entry| entry.value.clone())
+    }
+    
+    // 🆕 METADADOS DOS TAGS EM CACHE (unidade publicada, tipo, área, categoria)
+    pub fn get_tag_metadata(&self, plc_ips: &std::collections::HashSet<String>) -> Vec<TagInfo> {
+        let mut tags: Vec<TagInfo> = self.tag_cache.iter()
+            .filter(|entry| plc_ips.is_empty() || plc_ips.contains(&entry.value().plc_ip))
+            .map(|entry| {
+                let cached = entry.value();
+                TagInfo {
+                    plc_ip: cached.plc_ip.clone(),
+                    tag_name: cached.tag_name.clone(),
+                    data_type: cached.data_type.clone(),
+                    unit: cached.unit.clone(),
+                    area: cached.area.clone(),
+                    category: cached.category.clone(),
+                }
+            })
+            .collect();
+        tags.sort_by(|a, b| a.tag_name.cmp(&b.tag_name));
+        tags
+    }
+    
+    // 🆕 PLAYBACK HISTÓRICO
+    /// Publica o estado reproduzido (None encerra e devolve os clientes ao vivo)
+    pub fn publish_playback(&self, frame: Option<PlaybackFrame>) {
+        self.playback_tx.send_replace(frame.map(Arc::new));
+    }
+    
+    pub fn playback_frame(&self) -> Option<Arc<PlaybackFrame>> {
+        self.playback_tx.borrow().clone()
+    }
+    
+    /// Valor do historian no formato do cache, sem tocar no cache ao vivo: tipo,
+    /// unidade e grupos do tag ao vivo, ou do cache de mappings se ainda não visto
+    pub fn playback_value(&self, plc_ip: &str, tag_name: &str, value: &str, ts_ms: i64) -> CachedTagValue {
+        let timestamp_ns = ts_ms.max(0) as u128 * 1_000_000;
+        if let Some(live) = self.tag_cache.get(&format!("{}:{}", plc_ip, tag_name)) {
+            return CachedTagValue {
+                value: value.to_string(),
+                timestamp_ns,
+                critical: false,
+                ..live.value().clone()
+            };
+        }
+        
+        let mapping = self.get_cached_tags(plc_ip)
+            .and_then(|tags| tags.into_iter().find(|t| t.tag_name == tag_name));
+        
+        CachedTagValue {
+            tag_name: tag_name.to_string(),
+            plc_ip: plc_ip.to_string(),
+            value: value.to_string(),
+            data_type: String::new(),
+            timestamp_ns,
+            collect_mode: "change".to_string(),
+            interval_s: 1,
+            last_sent: 0,
+            changed: true,
+            area: mapping.as_ref().and_then(|m| m.area.clone()),
+            category: mapping.as_ref().and_then(|m| m.category.clone()),
+            min_resend_ms: 0,
+            suppressed: 0,
+            is_edge: false,
+            unit: mapping.as_ref().and_then(|m| m.display_unit.clone().or_else(|| m.unit.clone())),
+            priority: mapping.as_ref()
+                .map(|m| self.priority_for(m.area.as_deref(), m.category.as_deref()))
+                .unwrap_or(0),
+            critical: false, // Playback: sem caminho crítico (dados não são ao vivo)
+        }
+    }
+    
+    // 🆕 TAG INTERNO DE DIAGNÓSTICO (automonitoramento, ver self_monitor.rs)
+    /// Publica como um tag comum em modo "change": só marca envio quando o valor muda
+    pub fn apply_internal_value(&self, plc_ip: &str, tag_name: &str, value: &str, data_type: &str, unit: Option<&str>) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_nanos();
+        let tag_key = format!("{}:{}", plc_ip, tag_name);
+        
+        if let Some(mut cached) = self.tag_cache.get_mut(&tag_key) {
+            if cached.value != value {
+                cached.value = value.to_string();
+                cached.changed = true;
+            }
+            cached.timestamp_ns = now;
+            return;
+        }
+        
+        let area = crate::self_monitor::SELF_AREA;
+        let category = crate::self_monitor::SELF_CATEGORY;
+        self.tag_cache.insert(tag_key, CachedTagValue {
+            tag_name: tag_name.to_string(),
+            plc_ip: plc_ip.to_string(),
+            value: value.to_string(),
+            data_type: data_type.to_string(),
+            timestamp_ns: now,
+            collect_mode: "change".to_string(),
+            interval_s: 1,
+            last_sent: 0,
+            changed: true,
+            area: Some(area.to_string()),
+            category: Some(category.to_string()),
+            min_resend_ms: 0,
+            suppressed: 0,
+            is_edge: false,
+            unit: unit.map(str::to_string),
+            priority: self.priority_for(Some(area), Some(category)),
+            critical: false,
+        });
+    }
+    
+    // 🆕 CONTADOR DE SUPRESSÃO (transições descartadas por debounce/coalescência)
+    fn record_suppressed(&self, tag_key: &str, count: u64) {
+        self.suppressed_events.fetch_add(count, Ordering::Relaxed);
+        if let Some(mut cached) = self.tag_cache.get_mut(tag_key) {
+            cached.suppressed += count;
+        }
+    }
+    
+    /// 🆕 Estado do circuit breaker do SQLite (modo degradado)
+    pub fn db_breaker_status(&self) -> DbBreakerStatus {
+        self.db_breaker.status()
+    }
+    
+    pub fn suppressed_events(&self) -> u64 {
+        self.suppressed_events.load(Ordering::Relaxed)
+    }
+    
+    /// Tags que já tiveram transições suprimidas, ordenados do maior para o menor
+    pub fn get_suppressed_by_tag(&self) -> Vec<serde_json::Value> {
+        let mut tags: Vec<(String, String, u64)> = self.tag_cache.iter()
+            .filter(|entry| entry.value().suppressed > 0)
+            .map(|entry| {
+                let cached = entry.value();
+                (cached.plc_ip.clone(), cached.tag_name.clone(), cached.suppressed)
+            })
+            .collect();
+        tags.sort_by(|a, b| b.2.cmp(&a.2));
+        
+        tags.into_iter()
+            .map(|(plc_ip, tag_name, suppressed)| serde_json::json!({
+                "plc_ip": plc_ip,
+                "tag_name": tag_name,
+                "suppressed": suppressed
+            }))
+            .collect()
+    }
+    
+    // 🆕 BUFFER DE RELOAD: enquanto as tasks reiniciam, atualizações TCP ficam guardadas
+    fn begin_reload_buffering(&self) {
+        *self.reload_buffer.lock().unwrap() = Some(Vec::new());
+    }
+    
+    /// Retorna a atualização de volta se não estiver em reload (deve ser aplicada normalmente)
+    fn buffer_if_reloading(&self, update: CacheUpdateData) -> Option<CacheUpdateData> {
+        let mut guard = self.reload_buffer.lock().unwrap();
+        match guard.as_mut() {
+            Some(buffer) => {
+                if buffer.len() >= RELOAD_BUFFER_LIMIT {
+                    buffer.remove(0);
+                }
+                buffer.push(update);
+                None
+            }
+            None => Some(update),
+        }
+    }
+    
+    /// Retira o próximo lote do buffer; encerra o modo buffer quando estiver vazio
+    fn drain_reload_buffer(&self) -> Vec<CacheUpdateData> {
+        let mut guard = self.reload_buffer.lock().unwrap();
+        match guard.as_mut() {
+            Some(buffer) if !buffer.is_empty() => std::mem::take(buffer),
+            _ => {
+                *guard = None;
+                Vec::new()
+            }
+        }
+    }
+    
     // 🆕 INVALIDAR CACHE DE UM PLC ESPECÍFICO (chamado quando tags mudam)
     pub fn invalidate_cache(&self, plc_ip: &str) {
         self.tag_mappings_cache.remove(plc_ip);
@@ -568,6 +1290,7 @@ impl WebSocketServer {
         database: Arc<Database>,
         tcp_server: Option<Arc<RwLock<Option<TcpServer>>>>,
     ) -> Self {
+        let smart_cache = Arc::new(SmartCache::new(app_handle.clone()));
         Self {
             config,
             is_running: Arc::new(AtomicBool::new(false)),
@@ -584,13 +1307,33 @@ impl WebSocketServer {
             server_handle: None,
             broadcast_handle: None,
             interval_handles: Arc::new(TokioMutex::new(Vec::new())),
-            smart_cache: Arc::new(SmartCache::new()),
+            smart_cache,
             cache_updater_handle: None,
             // ✅ MELHORIA: Inicializar channels por PLC
             plc_broadcast_channels: Arc::new(DashMap::new()),
+            worker_port: None,
         }
     }
 
+    /// 🆕 Instância extra do cluster: mesma configuração da principal em outra
+    /// porta, servindo do SmartCache da principal (sem pipeline de cache próprio)
+    pub fn new_worker(
+        config: WebSocketConfig,
+        app_handle: AppHandle,
+        database: Arc<Database>,
+        smart_cache: Arc<SmartCache>,
+    ) -> Self {
+        let worker_port = config.port;
+        let mut server = Self::new(config, app_handle, database, None);
+        server.smart_cache = smart_cache;
+        server.worker_port = Some(worker_port);
+        server
+    }
+
+    pub fn worker_port(&self) -> Option<u16> {
+        self.worker_port
+    }
+
     // ✅ MELHORIA: Cliente se inscreve em PLCs específicos
     pub async fn subscribe_to_plcs(&self, client_id: u64, plc_ips: Vec<String>) -> Result<(), String> {
         if let Some(mut client) = self.connected_clients.get_mut(&client_id) {
@@ -628,7 +1371,7 @@ impl WebSocketServer {
         
         // Manter backward compatibility: broadcast global também
         if let Some(tx) = &self.broadcast_sender {
-            let _ = tx.send(format!("{{\"plc_ip\":\"{}\",\"data\":{}}}", plc_ip, message));
+            let _ = tx.send(Message::Text(format!("{{\"plc_ip\":\"{}\",\"data\":{}}}", plc_ip, message)));
         }
     }
 
@@ -759,11 +1502,26 @@ impl WebSocketServer {
         }
     }
 
-    pub async fn start(&mut self) -> Result<String, String> {
+    pub async fn start(&mut self) -> Result<String, AppError> {
         println!("🟢 WebSocket start() chamado");
         
         if self.is_running.load(Ordering::SeqCst) {
-            return Err("WebSocket server já está rodando".to_string());
+            return Err(AppError::AlreadyRunning("WebSocket server já está rodando".to_string()));
+        }
+
+        // 🆕 Sessões que ficaram abertas (queda da HMI) e retenção do registro.
+        // Só na principal: um worker fecharia as sessões vivas das outras instâncias.
+        if self.worker_port.is_none() {
+            match self.database.close_interrupted_ws_sessions() {
+                Ok(0) => {}
+                Ok(closed) => println!("🔌 {} sessões WebSocket interrompidas fechadas no último instante visto", closed),
+                Err(e) => println!("⚠️ Erro ao fechar sessões interrompidas: {}", e),
+            }
+            if let Ok(session_config) = self.database.load_ws_session_config() {
+                if let Err(e) = self.database.prune_ws_sessions(session_config.retention_days) {
+                    println!("⚠️ Erro ao aplicar retenção das sessões WebSocket: {}", e);
+                }
+            }
         }
 
         println!("🟢 Preparando endereços de bind...");
@@ -799,16 +1557,17 @@ impl WebSocketServer {
         println!("🟢 Bind completo: {} de {} endereços funcionando", listeners.len(), bound_addresses.len());
 
         if listeners.is_empty() {
-            return Err("Não foi possível fazer bind em nenhum endereço configurado".to_string());
+            return Err(AppError::Io("Não foi possível fazer bind em nenhum endereço configurado".to_string()));
         }
 
         // ✅ OTIMIZAÇÃO: Capacidade reduzida para controle de memória
-        let (broadcast_tx, _) = broadcast::channel::<String>(200); // Reduzido de 1000 para 200
+        let (broadcast_tx, _) = broadcast::channel::<Message>(200); // Reduzido de 1000 para 200
         self.broadcast_sender = Some(broadcast_tx.clone());
 
         self.is_running.store(true, Ordering::SeqCst);
 
-        let _ = self.app_handle.emit("websocket-server-started", serde_json::json!({
+        let started_event = if self.worker_port.is_some() { "websocket-worker-started" } else { "websocket-server-started" };
+        let _ = self.app_handle.emit(started_event, serde_json::json!({
             "status": "started",
             "addresses": bound_addresses,
             "timestamp": chrono::Utc::now().to_rfc3339()
@@ -824,6 +1583,7 @@ impl WebSocketServer {
         let max_clients = self.config.max_clients;
         let database = self.database.clone(); // ✅ ADICIONAR DATABASE
         let smart_cache = self.smart_cache.clone(); // ✅ ADICIONAR SMART_CACHE
+        let client_id_offset = self.worker_port.map_or(0, |port| port as u64 * CLUSTER_CLIENT_ID_STRIDE);
 
         let mut server_handles = Vec::new();
         
@@ -849,7 +1609,7 @@ impl WebSocketServer {
                             continue;
                         }
 
-                        let client_id = total_connections_clone.fetch_add(1, Ordering::SeqCst) + 1;
+                        let client_id = client_id_offset + total_connections_clone.fetch_add(1, Ordering::SeqCst) + 1;
                         let client = ConnectedClient {
                             id: client_id,
                             address: addr,
@@ -862,8 +1622,20 @@ impl WebSocketServer {
                             subscribed_areas: Arc::new(RwLock::new(std::collections::HashSet::new())),
                             subscribed_categories: Arc::new(RwLock::new(std::collections::HashSet::new())),
                             include_all_faults: Arc::new(AtomicBool::new(false)),
+                            min_priority: Arc::new(AtomicU8::new(0)),
                             // 🆕 Canal será definido em handle_client
-                            filtered_tx: None,
+                            frame_tx: None,
+                            critical_tx: None,
+                            // 🆕 Protocolo v1 até o cliente enviar HELLO
+                            features: Arc::new(ClientFeatures::default()),
+                            masking: None,
+                            playback: Arc::new(AtomicBool::new(false)),
+                            token_id: None,
+                            token_name: None,
+                            disconnect: Arc::new(tokio::sync::Notify::new()),
+                            bytes_sent: Arc::new(AtomicU64::new(0)),
+                            messages_sent: Arc::new(AtomicU64::new(0)),
+                            session_id: None,
                         };
 
                         connected_clients_clone.insert(client_id, client);
@@ -916,19 +1688,24 @@ impl WebSocketServer {
             self.server_handle = Some(first_handle);
         }
 
-        // Iniciar sistema inteligente de cache + broadcasting
-        self.start_smart_broadcasting(broadcast_tx).await?;
+        // Iniciar sistema inteligente de cache + broadcasting (worker: só o envio a partir da cópia)
+        if self.worker_port.is_some() {
+            self.start_cluster_fanout(broadcast_tx).await;
+        } else {
+            self.start_cache_pipeline().await;
+            self.start_smart_broadcasting(broadcast_tx).await?;
+        }
 
         Ok(format!("WebSocket server rodando em: {}", bound_addresses.join(", ")))
     }
 
-    // 🚀 SISTEMA INTELIGENTE: Cache + Broadcasting sem bloqueios TCP
-    async fn start_smart_broadcasting(&mut self, broadcast_tx: broadcast::Sender<String>) -> Result<(), String> {
+    // 📦 PIPELINE DE CACHE: listener de eventos TCP + processador atômico
+    // Roda durante toda a vida do servidor (não é reiniciado no reload de grupos)
+    async fn start_cache_pipeline(&mut self) {
         let database = self.database.clone();
         let is_running = self.is_running.clone();
         let smart_cache = self.smart_cache.clone();
 
-        println!("🚀 SISTEMA INTELIGENTE: Cache + Broadcasting sem bloqueios!");
         println!("📦 Cache de tags habilitado - ZERO consultas ao banco por pacote!");
 
         // ✅ OTIMIZAÇÃO: Canal otimizado para atualizações de cache  
@@ -942,6 +1719,7 @@ impl WebSocketServer {
         
         // ✅ TASK 1A: PROCESSADOR ATÔMICO DE CACHE
         let _atomic_cache_processor = tokio::spawn({
+            let connected_clients_clone = self.connected_clients.clone();
             let smart_cache_clone = smart_cache_updater.clone();
             let database_clone = database_updater.clone();
             let is_running_clone = is_running_cache.clone();
@@ -954,6 +1732,12 @@ impl WebSocketServer {
                         break;
                     }
                     
+                    // 🆕 Durante reload das tasks, guardar para aplicar depois (sem perder transições)
+                    let update_data = match smart_cache_clone.buffer_if_reloading(update_data) {
+                        Some(update) => update,
+                        None => continue,
+                    };
+                    
                     packets_processed += 1;
                     
                     // 🆕 REFRESH CACHE A CADA 60 SEGUNDOS (não a cada pacote!)
@@ -970,12 +1754,19 @@ impl WebSocketServer {
                     }
                     
                     // ✅ ATUALIZAÇÃO ATÔMICA (usa cache, não banco!)
-                    smart_cache_clone.update_from_tcp(
+                    let critical_updates = smart_cache_clone.update_from_tcp(
                         &update_data.plc_ip,
                         &update_data.variables,
-                        &database_clone
+                        &database_clone,
+                        update_data.received_ns
                     ).await;
                     
+                    // 🆕 Tags críticos: envio imediato, independente dos timers de lote
+                    if !critical_updates.is_empty() {
+                        Self::dispatch_critical(&connected_clients_clone, &critical_updates).await;
+                        smart_cache_clone.publish_critical(&critical_updates);
+                    }
+                    
                     // ✅ OTIMIZAÇÃO: Log periódico com estatísticas de memória
                     if packets_processed % 100 == 0 {
                         let (cache_size, mappings_size, tracking_size, memory_pct) = smart_cache_clone.get_memory_stats();
@@ -1016,6 +1807,9 @@ impl WebSocketServer {
                             plc_ip: plc_ip.to_string(),
                             variables,
                             timestamp: data["timestamp"].as_u64().unwrap_or(0),
+                            received_ns: data["tcp_received_ns"].as_str()
+                                .and_then(|ns| ns.parse().ok())
+                                .unwrap_or(0),
                         };
                         
                         let _ = update_tx.try_send(update_data);
@@ -1031,6 +1825,14 @@ impl WebSocketServer {
         });
         
         self.cache_updater_handle = Some(cache_handle);
+    }
+
+    // 🚀 SISTEMA INTELIGENTE: Broadcasting sem bloqueios TCP (reiniciado a cada reload)
+    async fn start_smart_broadcasting(&mut self, broadcast_tx: broadcast::Sender<Message>) -> Result<(), String> {
+        let is_running = self.is_running.clone();
+        let smart_cache = self.smart_cache.clone();
+
+        println!("🚀 SISTEMA INTELIGENTE: Cache + Broadcasting sem bloqueios!");
 
         // TASK 2: BROADCASTING INTELIGENTE
         let smart_cache_broadcast = smart_cache.clone();
@@ -1060,8 +1862,9 @@ impl WebSocketServer {
                         let subscribed_areas = client.subscribed_areas.read().await;
                         let subscribed_categories = client.subscribed_categories.read().await;
                         let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+                        let min_priority = client.min_priority.load(Ordering::SeqCst);
                         
-                        let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty();
+                        let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty() || min_priority > 0;
                         
                         // Coletar dados para este cliente
                         let mut client_data: HashMap<String, String> = HashMap::new();
@@ -1074,7 +1877,8 @@ impl WebSocketServer {
                                     &subscribed_plcs,
                                     &subscribed_areas,
                                     &subscribed_categories,
-                                    include_all_faults
+                                    include_all_faults,
+                                    min_priority
                                 ).await;
                                 client_data.extend(filtered_tags);
                             }
@@ -1086,23 +1890,9 @@ impl WebSocketServer {
                             }
                         }
                         
-                        // Enviar dados filtrados para o cliente
+                        // Enviar dados filtrados para o cliente (formato conforme protocolo negociado)
                         if !client_data.is_empty() {
-                            if let Some(ref tx) = client.filtered_tx {
-                                let sorted_map = sort_tags_naturally(client_data);
-                                
-                                match rmp_serde::to_vec(&sorted_map) {
-                                    Ok(msgpack_bytes) => {
-                                        let base64_data = base64_encode(&msgpack_bytes);
-                                        let msgpack_message = format!("MSGPACK:{}", base64_data);
-                                        let _ = tx.send(msgpack_message).await;
-                                    }
-                                    Err(_) => {
-                                        let message = serde_json::to_string(&sorted_map).unwrap_or_else(|_| "{}".to_string());
-                                        let _ = tx.send(message).await;
-                                    }
-                                }
-                            }
+                            Self::send_tag_data(&smart_cache_clone, client, client_data, true).await;
                         }
                     }
                 }
@@ -1130,8 +1920,9 @@ impl WebSocketServer {
                         let subscribed_areas = client.subscribed_areas.read().await;
                         let subscribed_categories = client.subscribed_categories.read().await;
                         let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+                        let min_priority = client.min_priority.load(Ordering::SeqCst);
                         
-                        let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty();
+                        let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty() || min_priority > 0;
                         
                         // Coletar dados para este cliente
                         let mut client_data: HashMap<String, String> = HashMap::new();
@@ -1144,7 +1935,8 @@ impl WebSocketServer {
                                     &subscribed_plcs,
                                     &subscribed_areas,
                                     &subscribed_categories,
-                                    include_all_faults
+                                    include_all_faults,
+                                    min_priority
                                 ).await;
                                 client_data.extend(filtered_tags);
                             }
@@ -1156,23 +1948,9 @@ impl WebSocketServer {
                             }
                         }
                         
-                        // Enviar dados filtrados para o cliente
+                        // Enviar dados filtrados para o cliente (formato conforme protocolo negociado)
                         if !client_data.is_empty() {
-                            if let Some(ref tx) = client.filtered_tx {
-                                let sorted_map = sort_tags_naturally(client_data);
-                                
-                                match rmp_serde::to_vec(&sorted_map) {
-                                    Ok(msgpack_bytes) => {
-                                        let base64_data = base64_encode(&msgpack_bytes);
-                                        let msgpack_message = format!("MSGPACK:{}", base64_data);
-                                        let _ = tx.send(msgpack_message).await;
-                                    }
-                                    Err(_) => {
-                                        let message = serde_json::to_string(&sorted_map).unwrap_or_else(|_| "{}".to_string());
-                                        let _ = tx.send(message).await;
-                                    }
-                                }
-                            }
+                            Self::send_tag_data(&smart_cache_clone, client, client_data, true).await;
                         }
                     }
                 }
@@ -1200,8 +1978,9 @@ impl WebSocketServer {
                         let subscribed_areas = client.subscribed_areas.read().await;
                         let subscribed_categories = client.subscribed_categories.read().await;
                         let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+                        let min_priority = client.min_priority.load(Ordering::SeqCst);
                         
-                        let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty();
+                        let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty() || min_priority > 0;
                         
                         // Coletar dados para este cliente
                         let mut client_data: HashMap<String, String> = HashMap::new();
@@ -1214,7 +1993,8 @@ impl WebSocketServer {
                                     &subscribed_plcs,
                                     &subscribed_areas,
                                     &subscribed_categories,
-                                    include_all_faults
+                                    include_all_faults,
+                                    min_priority
                                 ).await;
                                 client_data.extend(filtered_tags);
                             }
@@ -1226,23 +2006,9 @@ impl WebSocketServer {
                             }
                         }
                         
-                        // Enviar dados filtrados para o cliente
+                        // Enviar dados filtrados para o cliente (formato conforme protocolo negociado)
                         if !client_data.is_empty() {
-                            if let Some(ref tx) = client.filtered_tx {
-                                let sorted_map = sort_tags_naturally(client_data);
-                                
-                                match rmp_serde::to_vec(&sorted_map) {
-                                    Ok(msgpack_bytes) => {
-                                        let base64_data = base64_encode(&msgpack_bytes);
-                                        let msgpack_message = format!("MSGPACK:{}", base64_data);
-                                        let _ = tx.send(msgpack_message).await;
-                                    }
-                                    Err(_) => {
-                                        let message = serde_json::to_string(&sorted_map).unwrap_or_else(|_| "{}".to_string());
-                                        let _ = tx.send(message).await;
-                                    }
-                                }
-                            }
+                            Self::send_tag_data(&smart_cache_clone, client, client_data, true).await;
                         }
                     }
                 }
@@ -1260,48 +2026,32 @@ impl WebSocketServer {
         
         let change_handle = tokio::spawn(async move {
             let mut interval = time::interval(Duration::from_millis(100));
+            let mut playback_sent: HashMap<u64, HashMap<String, String>> = HashMap::new();
             while is_running_change.load(Ordering::SeqCst) {
                 interval.tick().await;
-                
-                // 🆕 ITERAR SOBRE CADA CLIENTE CONECTADO E ENVIAR DADOS FILTRADOS
-                for client_entry in connected_clients_change.iter() {
-                    let client = client_entry.value();
-                    
-                    // Obter filtros do cliente
-                    let subscribed_plcs = client.subscribed_plcs.read().await;
-                    let subscribed_areas = client.subscribed_areas.read().await;
-                    let subscribed_categories = client.subscribed_categories.read().await;
-                    let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
-                    
-                    let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty();
-                    
-                    let changed_tags = if has_filters {
-                        // 🎯 CLIENTE TEM FILTROS - Usar get_tags_filtered para changes
-                        smart_cache_change.get_tags_filtered(
-                            0,
-                            &subscribed_plcs,
-                            &subscribed_areas,
-                            &subscribed_categories,
-                            include_all_faults
-                        ).await
-                    } else {
-                        // 📡 CLIENTE SEM FILTROS - Recebe tudo
-                        smart_cache_change.get_tags_for_broadcast(0).await
-                    };
-                    
-                    if !changed_tags.is_empty() {
-                        if let Some(ref tx) = client.filtered_tx {
-                            let sorted_changed_tags = sort_tags_naturally(changed_tags);
-                            let message = serde_json::to_string(&sorted_changed_tags).unwrap_or_else(|_| "{}".to_string());
-                            let _ = tx.send(message).await;
-                        }
-                    }
-                }
+                Self::dispatch_changed_tags(&smart_cache_change, &connected_clients_change).await;
+                Self::dispatch_playback(&smart_cache_change, &connected_clients_change, &mut playback_sent).await;
+                // 🆕 Instâncias extras do cluster leem a cópia (sem consumir os flags de envio)
+                smart_cache_change.publish_snapshot();
             }
         });
         
         handles.push(change_handle);
         
+        // 🆕 STATUS PERIÓDICO: sem dados não significa servidor fora do ar
+        let is_running_status = is_running.clone();
+        let status_handle = tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(crate::server_status::STATUS_INTERVAL_SECS));
+            interval.tick().await; // Conexão recebe o status junto com o WELCOME
+            while is_running_status.load(Ordering::SeqCst) {
+                interval.tick().await;
+                let status = crate::server_status::current().await;
+                let _ = broadcast_tx.send(Message::Text(crate::server_status::ws_message(&status).to_string()));
+            }
+        });
+        
+        handles.push(status_handle);
+        
         let mut guard = self.interval_handles.lock().await;
         *guard = handles;
         
@@ -1309,8 +2059,338 @@ impl WebSocketServer {
         Ok(())
     }
 
-    /// Para e reinicia as tasks de broadcast, recarregando os tags do banco
+    // 🆕 WORKER DO CLUSTER: envio a partir da cópia do cache e dos críticos publicados pela principal
+    async fn start_cluster_fanout(&mut self, broadcast_tx: broadcast::Sender<Message>) {
+        let mut handles = Vec::new();
+        
+        // Valores: diferença da cópia contra o último envio desta instância
+        handles.push(tokio::spawn({
+            let is_running = self.is_running.clone();
+            let smart_cache = self.smart_cache.clone();
+            let connected_clients = self.connected_clients.clone();
+            let mut snapshots = self.smart_cache.subscribe_snapshots();
+            async move {
+                let mut sent: HashMap<String, (String, u128)> = HashMap::new();
+                let mut playback_sent: HashMap<u64, HashMap<String, String>> = HashMap::new();
+                let mut tick = time::interval(CLUSTER_FANOUT_INTERVAL);
+                while is_running.load(Ordering::SeqCst) {
+                    tick.tick().await;
+                    Self::dispatch_playback(&smart_cache, &connected_clients, &mut playback_sent).await;
+                    let snapshot = snapshots.borrow_and_update().clone();
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+                    let due: Vec<&CachedTagValue> = snapshot.iter()
+                        .filter(|cached| cluster_due(cached, sent.get(&format!("{}/{}", cached.plc_ip, cached.tag_name)), now))
+                        .collect();
+                    if due.is_empty() {
+                        continue;
+                    }
+                    for cached in &due {
+                        sent.insert(format!("{}/{}", cached.plc_ip, cached.tag_name), (cached.value.clone(), now));
+                    }
+                    
+                    for client_entry in connected_clients.iter() {
+                        let client = client_entry.value();
+                        let subscribed_plcs = client.subscribed_plcs.read().await;
+                        let subscribed_areas = client.subscribed_areas.read().await;
+                        let subscribed_categories = client.subscribed_categories.read().await;
+                        let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+                        let min_priority = client.min_priority.load(Ordering::SeqCst);
+                        
+                        let tags: HashMap<String, String> = due.iter()
+                            .filter(|cached| passes_client_filters(cached, &subscribed_plcs, &subscribed_areas, &subscribed_categories, include_all_faults, min_priority))
+                            .map(|cached| (cached.tag_name.clone(), cached.value.clone()))
+                            .collect();
+                        if !tags.is_empty() {
+                            Self::send_tag_data(&smart_cache, client, tags, false).await;
+                        }
+                    }
+                }
+            }
+        }));
+        
+        // Tags críticos: repassados assim que a principal publica
+        handles.push(tokio::spawn({
+            let is_running = self.is_running.clone();
+            let connected_clients = self.connected_clients.clone();
+            let mut critical_rx = self.smart_cache.subscribe_critical();
+            let worker_port = self.worker_port;
+            async move {
+                while is_running.load(Ordering::SeqCst) {
+                    match critical_rx.recv().await {
+                        Ok(updates) => Self::dispatch_critical(&connected_clients, &updates).await,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            println!("⚠️ Worker WebSocket {:?}: {} lotes críticos perdidos (atraso)", worker_port, skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        }));
+        
+        // Status periódico, igual ao da principal
+        handles.push(tokio::spawn({
+            let is_running = self.is_running.clone();
+            async move {
+                let mut interval = time::interval(Duration::from_secs(crate::server_status::STATUS_INTERVAL_SECS));
+                interval.tick().await;
+                while is_running.load(Ordering::SeqCst) {
+                    interval.tick().await;
+                    let status = crate::server_status::current().await;
+                    let _ = broadcast_tx.send(Message::Text(crate::server_status::ws_message(&status).to_string()));
+                }
+            }
+        }));
+        
+        *self.interval_handles.lock().await = handles;
+        println!("🧩 Worker WebSocket {:?} lendo a cópia do cache da instância principal", self.worker_port);
+    }
+
+    /// Envia para cada cliente (respeitando filtros) os tags em modo "change" que mudaram
+    async fn dispatch_changed_tags(smart_cache: &SmartCache, connected_clients: &DashMap<u64, ConnectedClient>) {
+        // 🆕 ITERAR SOBRE CADA CLIENTE CONECTADO E ENVIAR DADOS FILTRADOS
+        for client_entry in connected_clients.iter() {
+            let client = client_entry.value();
+            
+            // Obter filtros do cliente
+            let subscribed_plcs = client.subscribed_plcs.read().await;
+            let subscribed_areas = client.subscribed_areas.read().await;
+            let subscribed_categories = client.subscribed_categories.read().await;
+            let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+            let min_priority = client.min_priority.load(Ordering::SeqCst);
+            
+            let has_filters = !subscribed_areas.is_empty() || !subscribed_categories.is_empty() || min_priority > 0;
+            
+            let changed_tags = if has_filters {
+                // 🎯 CLIENTE TEM FILTROS - Usar get_tags_filtered para changes
+                smart_cache.get_tags_filtered(
+                    0,
+                    &subscribed_plcs,
+                    &subscribed_areas,
+                    &subscribed_categories,
+                    include_all_faults,
+                    min_priority
+                ).await
+            } else {
+                // 📡 CLIENTE SEM FILTROS - Recebe tudo
+                smart_cache.get_tags_for_broadcast(0).await
+            };
+            
+            if !changed_tags.is_empty() {
+                Self::send_tag_data(smart_cache, client, changed_tags, false).await;
+            }
+        }
+    }
+
+    /// Envia as mudanças de tags críticos a cada cliente assinante do PLC.
+    /// Filtros de área/categoria/prioridade não se aplicam: bit de segurança sempre chega.
+    async fn dispatch_critical(connected_clients: &DashMap<u64, ConnectedClient>, updates: &[CriticalUpdate]) {
+        for client_entry in connected_clients.iter() {
+            let client = client_entry.value();
+            let Some(ref tx) = client.critical_tx else { continue };
+            // Cliente público: o caminho crítico furaria o atraso/ocultação da chave.
+            // Cliente em playback: não misturar valores ao vivo com os reproduzidos
+            if client.masking.is_some() || client.playback.load(Ordering::SeqCst) {
+                continue;
+            }
+            let subscribed_plcs = client.subscribed_plcs.read().await;
+            let typed_values = client.features.typed_values.load(Ordering::SeqCst);
+            
+            for update in updates {
+                if !subscribed_plcs.is_empty() && !subscribed_plcs.contains(&update.plc_ip) {
+                    continue;
+                }
+                let value = if typed_values {
+                    Self::parse_variable_value(&update.value, &update.data_type)
+                } else {
+                    serde_json::Value::String(update.value.clone())
+                };
+                let message = ServerMessage::Critical {
+                    plc_ip: update.plc_ip.clone(),
+                    tag: update.tag_name.clone(),
+                    value,
+                    ts: Some((update.received_ns / 1_000_000) as u64),
+                }.to_json();
+                // try_send: cliente lento não pode atrasar os demais
+                if tx.try_send((message.to_string(), update.received_ns)).is_err() {
+                    println!("⚠️ Canal crítico cheio/fechado para cliente {} - tag {}", client.id, update.tag_name);
+                }
+            }
+        }
+    }
+
+    /// 🆕 Clientes em playback: valores reproduzidos que mudaram desde o último envio
+    /// a cada um (`sent`: cliente -> tag -> valor). Sem playback ativo, voltam ao vivo.
+    async fn dispatch_playback(smart_cache: &SmartCache, connected_clients: &DashMap<u64, ConnectedClient>, sent: &mut HashMap<u64, HashMap<String, String>>) {
+        let frame = smart_cache.playback_frame();
+        sent.retain(|client_id, _| connected_clients.get(client_id).is_some_and(|c| c.playback.load(Ordering::SeqCst)));
+        
+        for client_entry in connected_clients.iter() {
+            let client = client_entry.value();
+            if !client.playback.load(Ordering::SeqCst) {
+                continue;
+            }
+            let Some(ref frame) = frame else {
+                client.playback.store(false, Ordering::SeqCst);
+                sent.remove(&client.id);
+                Self::send_live_snapshot(smart_cache, client).await;
+                continue;
+            };
+            
+            let subscribed_plcs = client.subscribed_plcs.read().await;
+            let subscribed_areas = client.subscribed_areas.read().await;
+            let subscribed_categories = client.subscribed_categories.read().await;
+            let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+            let min_priority = client.min_priority.load(Ordering::SeqCst);
+            
+            let client_sent = sent.entry(client.id).or_default();
+            let due: Vec<&CachedTagValue> = frame.values.iter()
+                .filter(|cached| passes_client_filters(cached, &subscribed_plcs, &subscribed_areas, &subscribed_categories, include_all_faults, min_priority))
+                .filter(|cached| client_sent.get(&format!("{}:{}", cached.plc_ip, cached.tag_name)) != Some(&cached.value))
+                .collect();
+            if due.is_empty() {
+                continue;
+            }
+            for cached in &due {
+                client_sent.insert(format!("{}:{}", cached.plc_ip, cached.tag_name), cached.value.clone());
+            }
+            Self::send_playback_data(client, &due).await;
+        }
+    }
+    
+    /// Lote do playback: sempre envelope TAG_DATA com "playback": true (também para
+    /// clientes v1), timestamps do historian e tipos do tag ao vivo
+    async fn send_playback_data(client: &ConnectedClient, values: &[&CachedTagValue]) {
+        let Some(ref tx) = client.frame_tx else { return };
+        let features = &client.features;
+        let typed_values = features.typed_values.load(Ordering::SeqCst);
+        let tag_timestamps = features.tag_timestamps.load(Ordering::SeqCst);
+        
+        let tags: HashMap<String, serde_json::Value> = values.iter()
+            .map(|cached| {
+                let mut json_value = if typed_values {
+                    Self::parse_variable_value(&cached.value, &cached.data_type)
+                } else {
+                    serde_json::Value::String(cached.value.clone())
+                };
+                if tag_timestamps {
+                    json_value = serde_json::json!({ "v": json_value, "ts": (cached.timestamp_ns / 1_000_000) as u64 });
+                }
+                (cached.tag_name.clone(), json_value)
+            })
+            .collect();
+        let envelope = ServerMessage::TagData {
+            protocol_version: ws_protocol::PROTOCOL_VERSION,
+            tags,
+            playback: true,
+        }.to_json();
+        
+        let message = match rmp_serde::to_vec_named(&envelope) {
+            Ok(bytes) if features.binary.load(Ordering::SeqCst) => Message::Binary(bytes),
+            _ => Message::Text(envelope.to_string()),
+        };
+        let _ = tx.send(message).await;
+    }
+    
+    /// Volta do playback: valores ao vivo atuais (pelos filtros do cliente) de uma vez
+    async fn send_live_snapshot(smart_cache: &SmartCache, client: &ConnectedClient) {
+        let tags: HashMap<String, String> = {
+            let subscribed_plcs = client.subscribed_plcs.read().await;
+            let subscribed_areas = client.subscribed_areas.read().await;
+            let subscribed_categories = client.subscribed_categories.read().await;
+            let include_all_faults = client.include_all_faults.load(Ordering::SeqCst);
+            let min_priority = client.min_priority.load(Ordering::SeqCst);
+            smart_cache.snapshot(None).into_iter()
+                .filter(|cached| !crate::plc_parser::is_waveform_type(&cached.data_type))
+                .filter(|cached| passes_client_filters(cached, &subscribed_plcs, &subscribed_areas, &subscribed_categories, include_all_faults, min_priority))
+                .map(|cached| (cached.tag_name, cached.value))
+                .collect()
+        };
+        if !tags.is_empty() {
+            Self::send_tag_data(smart_cache, client, tags, false).await;
+        }
+    }
+
+    /// Envia um lote de tags ao cliente. Sem HELLO (v1): mapa tag -> texto em
+    /// MessagePack binário (handshake com ?format=binary), "MSGPACK:" + base64
+    /// (`legacy_msgpack`) ou JSON. Com HELLO (v2): envelope TAG_DATA com valores
+    /// tipados/timestamps e MessagePack binário se pedidos.
+    async fn send_tag_data(smart_cache: &SmartCache, client: &ConnectedClient, tags: HashMap<String, String>, legacy_msgpack: bool) {
+        // 🆕 Cliente em playback: recebe só os valores reproduzidos (dispatch_playback)
+        if client.playback.load(Ordering::SeqCst) {
+            return;
+        }
+        // 🆕 Cliente público: aplicar regras da chave (ocultar/arredondar/atrasar)
+        let (tags, original_ts) = match client.masking {
+            Some(ref mask) => {
+                let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+                let groups = smart_cache.tag_groups(&tags);
+                mask.apply(tags, &groups, now_ms)
+            }
+            None => (tags, HashMap::new()),
+        };
+        if tags.is_empty() {
+            return;
+        }
+        let sorted_map = sort_tags_naturally(tags);
+        let features = &client.features;
+
+        let Some(ref tx) = client.frame_tx else { return };
+        let binary = features.binary.load(Ordering::SeqCst);
+
+        if !features.negotiated.load(Ordering::SeqCst) {
+            let msgpack = if legacy_msgpack || binary { rmp_serde::to_vec(&sorted_map).ok() } else { None };
+            let message = match msgpack {
+                Some(msgpack_bytes) if binary => Message::Binary(msgpack_bytes),
+                Some(msgpack_bytes) => Message::Text(format!("MSGPACK:{}", base64_encode(&msgpack_bytes))),
+                None => Message::Text(serde_json::to_string(&sorted_map).unwrap_or_else(|_| "{}".to_string())),
+            };
+            let _ = tx.send(message).await;
+            return;
+        }
+
+        let typed_values = features.typed_values.load(Ordering::SeqCst);
+        let tag_timestamps = features.tag_timestamps.load(Ordering::SeqCst);
+        let details = if typed_values || tag_timestamps {
+            smart_cache.tag_details(&sorted_map)
+        } else {
+            HashMap::new()
+        };
+
+        let tags: HashMap<String, serde_json::Value> = sorted_map.into_iter()
+            .map(|(name, value)| {
+                let detail = details.get(&name);
+                let mut json_value = match detail {
+                    Some((data_type, _)) if typed_values => Self::parse_variable_value(&value, data_type),
+                    _ => serde_json::Value::String(value),
+                };
+                if tag_timestamps {
+                    // Valor atrasado (cliente público): timestamp original, não o atual
+                    let ts = original_ts.get(&name).copied().or(detail.map(|(_, ts)| *ts));
+                    json_value = serde_json::json!({ "v": json_value, "ts": ts });
+                }
+                (name, json_value)
+            })
+            .collect();
+        let envelope = ServerMessage::TagData {
+            protocol_version: ws_protocol::PROTOCOL_VERSION,
+            tags,
+            playback: false,
+        }.to_json();
+
+        let message = match rmp_serde::to_vec_named(&envelope) {
+            Ok(bytes) if binary => Message::Binary(bytes),
+            _ => Message::Text(envelope.to_string()),
+        };
+        let _ = tx.send(message).await;
+    }
+
+    /// Para e reinicia as tasks de broadcast, recarregando os tags do banco.
+    /// Atualizações recebidas durante o reinício ficam em buffer e são reaplicadas
+    /// em ordem, despachando cada transição "change" antes da próxima.
     pub async fn reload_tag_groups(&mut self) -> Result<(), String> {
+        // 🆕 WARM STANDBY: guardar atualizações TCP enquanto as tasks reiniciam
+        self.smart_cache.begin_reload_buffering();
+        
         // 🆕 INVALIDAR TODO O CACHE PARA FORÇAR RELOAD
         self.smart_cache.invalidate_all_cache();
         
@@ -1327,11 +2407,32 @@ impl WebSocketServer {
         }
         
         // Recriar tasks com os grupos atualizados
-        if let Some(broadcast_tx) = &self.broadcast_sender {
+        let result = if let Some(broadcast_tx) = &self.broadcast_sender {
             self.start_smart_broadcasting(broadcast_tx.clone()).await
         } else {
             Err("Broadcast sender não inicializado".to_string())
+        };
+        
+        // 🆕 FLUSH: reaplicar o que chegou durante o reinício
+        let mut flushed = 0;
+        loop {
+            let pending = self.smart_cache.drain_reload_buffer();
+            if pending.is_empty() {
+                break;
+            }
+            for update in pending {
+                let critical_updates = self.smart_cache.update_from_tcp(&update.plc_ip, &update.variables, &self.database, update.received_ns).await;
+                Self::dispatch_critical(&self.connected_clients, &critical_updates).await;
+                self.smart_cache.publish_critical(&critical_updates);
+                Self::dispatch_changed_tags(&self.smart_cache, &self.connected_clients).await;
+                flushed += 1;
+            }
+        }
+        if flushed > 0 {
+            println!("📤 Reload: {} atualizações em buffer reaplicadas", flushed);
         }
+        
+        result
     }
 
     fn parse_variable_value(value: &str, data_type: &str) -> serde_json::Value {
@@ -1345,6 +2446,7 @@ impl WebSocketServer {
             "WORD" | "DWORD" | "LWORD" | "BYTE" => {
                 value.parse::<u64>().map(serde_json::Value::from).unwrap_or(serde_json::Value::Null)
             },
+            "BOOL" => serde_json::Value::Bool(is_truthy(value)),
             _ => serde_json::Value::String(value.to_string())
         }
     }
@@ -1353,7 +2455,7 @@ impl WebSocketServer {
         stream: TcpStream,
         client_id: u64,
         addr: SocketAddr,
-        mut broadcast_rx: broadcast::Receiver<String>,
+        mut broadcast_rx: broadcast::Receiver<Message>,
         connected_clients: Arc<DashMap<u64, ConnectedClient>>,
         active_connections: Arc<AtomicU64>,
         messages_sent: Arc<AtomicU64>,
@@ -1362,50 +2464,207 @@ impl WebSocketServer {
         database: Arc<Database>, // ✅ NOVO PARÂMETRO
         smart_cache: Arc<SmartCache>, // ✅ NOVO PARÂMETRO
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let websocket = accept_async(stream).await?;
-        let (ws_sender, mut ws_receiver) = websocket.split();
+        // 🆕 Credencial (?token=... ou ?public_key=...) e formato dos frames (?format=binary) na query string do handshake
+        let mut query_token = None;
+        let mut query_public_key = None;
+        let mut binary_frames = false;
+        let websocket = accept_hdr_async(stream, |request: &Request, response: Response| {
+            query_token = ws_auth::token_from_query(request.uri().query());
+            query_public_key = ws_auth::query_param(request.uri().query(), "public_key");
+            binary_frames = ws_protocol::binary_frames_requested(request.uri().query());
+            Ok(response)
+        }).await?;
+        let (mut ws_sender, mut ws_receiver) = websocket.split();
+        
+        // 🆕 AUTENTICAÇÃO POR TOKEN OU CHAVE PÚBLICA: com credenciais ativas (ou chave
+        // na query), nada é enviado antes de validar. A chave pública fica presa à
+        // conexão: o mascaramento vale desde o primeiro dado e não sai mais.
+        if query_public_key.is_some() || ws_auth::auth_required(&database) {
+            let (token, public_key) = if query_token.is_some() || query_public_key.is_some() {
+                (query_token, query_public_key)
+            } else {
+                let prompt = ServerMessage::AuthRequired { timeout_ms: ws_auth::AUTH_TIMEOUT.as_millis() as u64 }.to_json();
+                let _ = ws_sender.send(Message::Text(prompt.to_string())).await;
+                let auth = match time::timeout(ws_auth::AUTH_TIMEOUT, ws_receiver.next()).await {
+                    Ok(Some(Ok(Message::Text(text)))) => serde_json::from_str::<serde_json::Value>(&text).ok()
+                        .filter(|cmd| cmd.get("type").and_then(|t| t.as_str()) == Some("AUTH")),
+                    _ => None,
+                };
+                let field = |name: &str| auth.as_ref()
+                    .and_then(|cmd| cmd.get(name).and_then(|v| v.as_str()))
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string);
+                (field("token"), field("public_key"))
+            };
+            let result = ws_auth::authenticate_credential(&database, token.as_deref(), public_key.as_deref());
+            match result {
+                Ok(credential) => {
+                    let mut accepted_event = serde_json::json!({
+                        "client_id": client_id,
+                        "address": addr.to_string(),
+                    });
+                    if let Some(mut client) = connected_clients.get_mut(&client_id) {
+                        match &credential {
+                            ws_auth::Credential::Token(token) => {
+                                println!("🔑 Cliente {} autenticado com o token '{}'", client_id, token.name);
+                                client.token_id = Some(token.id);
+                                client.token_name = Some(token.name.clone());
+                                accepted_event["token_id"] = serde_json::json!(token.id);
+                                accepted_event["token"] = serde_json::json!(token.name);
+                            }
+                            ws_auth::Credential::PublicKey(key) => {
+                                println!("🌐 Cliente {} autenticado com a chave pública '{}' ({} regras)", client_id, key.name, key.rules.len());
+                                client.masking = Some(Arc::new(StreamMask::new(key)));
+                                accepted_event["public_key"] = serde_json::json!(key.name);
+                            }
+                        }
+                    }
+                    let accepted = ws_auth::auth_result(Ok(&credential));
+                    let _ = ws_sender.send(Message::Text(accepted.to_string())).await;
+                    let _ = app_handle.emit("websocket-auth-accepted", accepted_event);
+                }
+                Err(reason) => {
+                    println!("⛔ Cliente {} ({}) rejeitado: {}", client_id, addr, reason);
+                    let rejected = ws_auth::auth_result(Err(&reason));
+                    let _ = ws_sender.send(Message::Text(rejected.to_string())).await;
+                    let _ = ws_sender.send(Message::Close(None)).await;
+                    connected_clients.remove(&client_id);
+                    active_connections.fetch_sub(1, Ordering::SeqCst);
+                    let _ = app_handle.emit("websocket-auth-rejected", serde_json::json!({
+                        "client_id": client_id,
+                        "address": addr.to_string(),
+                        "reason": reason
+                    }));
+                    let _ = app_handle.emit("websocket-client-disconnected", serde_json::json!({
+                        "client_id": client_id,
+                        "address": addr.to_string(),
+                        "total_clients": active_connections.load(Ordering::SeqCst)
+                    }));
+                    return Ok(());
+                }
+            }
+        }
         
         // ✅ Canal para envio de respostas ao cliente
-        let (response_tx, mut response_rx) = mpsc::channel::<String>(100);
+        let (response_tx, mut response_rx) = mpsc::channel::<Message>(100);
+        let (critical_tx, mut critical_rx) = mpsc::channel::<(String, u128)>(256);
         let ws_sender = Arc::new(TokioMutex::new(ws_sender));
 
         println!("🔌 WebSocket handshake completo para cliente {}", client_id);
 
         // 🆕 ARMAZENAR O CANAL DE ENVIO NO CLIENTE PARA BROADCAST FILTRADO
+        let mut client_features = Arc::new(ClientFeatures::default());
+        let mut disconnect = Arc::new(tokio::sync::Notify::new());
+        let mut client_bytes_sent = Arc::new(AtomicU64::new(0));
+        let mut client_messages_sent = Arc::new(AtomicU64::new(0));
         if let Some(mut client) = connected_clients.get_mut(&client_id) {
-            client.filtered_tx = Some(response_tx.clone());
+            client.frame_tx = Some(response_tx.clone());
+            client.critical_tx = Some(critical_tx);
+            client_features = client.features.clone();
+            disconnect = client.disconnect.clone();
+            client_bytes_sent = client.bytes_sent.clone();
+            client_messages_sent = client.messages_sent.clone();
             println!("📡 Canal de filtro configurado para cliente {}", client_id);
         }
+        
+        // 🆕 REGISTRO DA SESSÃO (endereço, token, horários e bytes em ws_client_sessions)
+        let session_id = if database.load_ws_session_config().unwrap_or_default().enabled {
+            let record = connected_clients.get(&client_id).map(|client| client.session_record(0));
+            match record.map(|record| database.open_ws_session(&record)) {
+                Some(Ok(id)) => Some(id),
+                Some(Err(e)) => {
+                    println!("⚠️ Erro ao registrar sessão do cliente {}: {}", client_id, e);
+                    None
+                }
+                None => None,
+            }
+        } else {
+            None
+        };
+        if let (Some(id), Some(mut client)) = (session_id, connected_clients.get_mut(&client_id)) {
+            client.session_id = Some(id);
+        }
+        let session_task = tokio::spawn({
+            let connected_clients = connected_clients.clone();
+            let database = database.clone();
+            async move {
+                let Some(session_id) = session_id else { return };
+                let mut heartbeat = time::interval(SESSION_HEARTBEAT);
+                heartbeat.tick().await;
+                loop {
+                    heartbeat.tick().await;
+                    let Some(record) = connected_clients.get(&client_id).map(|client| client.session_record(session_id)) else { break };
+                    if let Err(e) = database.update_ws_session(&record) {
+                        println!("⚠️ Erro ao atualizar sessão do cliente {}: {}", client_id, e);
+                    }
+                }
+            }
+        });
+        // 🆕 Cliente v1 que pediu frames binários: MessagePack puro em vez de "MSGPACK:" + base64
+        if binary_frames {
+            client_features.binary.store(true, Ordering::SeqCst);
+            println!("📦 Cliente {} recebe dados em frames binários", client_id);
+        }
+        
+        // 🆕 PRIMEIRA MENSAGEM: versão do protocolo e recursos disponíveis para o HELLO
+        let _ = response_tx.send(Message::Text(ws_protocol::welcome_message(client_id).to_string())).await;
+        // 🆕 Status do servidor (online, versão, uptime, PLCs conectados)
+        let status = crate::server_status::current().await;
+        let _ = response_tx.send(Message::Text(crate::server_status::ws_message(&status).to_string())).await;
 
         // ✅ TASK DE ENVIO - Unificada para broadcast e respostas
         let ws_sender_clone = ws_sender.clone();
         let messages_sent_clone = messages_sent.clone();
         let bytes_sent_clone = bytes_sent.clone();
         
-        let send_task = tokio::spawn(async move {
+        let smart_cache_send = smart_cache.clone();
+        
+        let mut send_task = tokio::spawn(async move {
             loop {
                 tokio::select! {
-                    // Mensagens de broadcast
-                    Ok(message) = broadcast_rx.recv() => {
+                    // 🆕 biased: o canal crítico é sempre verificado primeiro
+                    biased;
+                    
+                    // 🆕 Tags críticos (alta prioridade) - latência medida após a escrita
+                    Some((message, received_ns)) = critical_rx.recv() => {
                         let msg_len = message.len() as u64;
                         let mut sender = ws_sender_clone.lock().await;
                         if let Err(e) = sender.send(Message::Text(message)).await {
+                            println!("❌ Erro ao enviar tag crítico para cliente {}: {}", client_id, e);
+                            break;
+                        }
+                        smart_cache_send.critical_latency.record(received_ns);
+                        messages_sent_clone.fetch_add(1, Ordering::SeqCst);
+                        bytes_sent_clone.fetch_add(msg_len, Ordering::SeqCst);
+                        client_messages_sent.fetch_add(1, Ordering::Relaxed);
+                        client_bytes_sent.fetch_add(msg_len, Ordering::Relaxed);
+                    }
+                    
+                    // Mensagens de broadcast (frames prontos, texto ou binário)
+                    Ok(message) = broadcast_rx.recv() => {
+                        let msg_len = message.len() as u64;
+                        let mut sender = ws_sender_clone.lock().await;
+                        if let Err(e) = sender.send(message).await {
                             println!("❌ Erro ao enviar broadcast para cliente {}: {}", client_id, e);
                             break;
                         }
                         messages_sent_clone.fetch_add(1, Ordering::SeqCst);
                         bytes_sent_clone.fetch_add(msg_len, Ordering::SeqCst);
+                        client_messages_sent.fetch_add(1, Ordering::Relaxed);
+                        client_bytes_sent.fetch_add(msg_len, Ordering::Relaxed);
                     }
-                    // Respostas diretas ao cliente
+                    // Respostas diretas e dados filtrados do cliente (texto ou MessagePack binário)
                     Some(response) = response_rx.recv() => {
                         let msg_len = response.len() as u64;
                         let mut sender = ws_sender_clone.lock().await;
-                        if let Err(e) = sender.send(Message::Text(response)).await {
+                        if let Err(e) = sender.send(response).await {
                             println!("❌ Erro ao enviar resposta para cliente {}: {}", client_id, e);
                             break;
                         }
                         messages_sent_clone.fetch_add(1, Ordering::SeqCst);
                         bytes_sent_clone.fetch_add(msg_len, Ordering::SeqCst);
+                        client_messages_sent.fetch_add(1, Ordering::Relaxed);
+                        client_bytes_sent.fetch_add(msg_len, Ordering::Relaxed);
                     }
                 }
             }
@@ -1416,8 +2675,9 @@ impl WebSocketServer {
         let response_tx_clone = response_tx.clone();
         let database_recv = database.clone(); // ✅ CLONE DATABASE
         let smart_cache_recv = smart_cache.clone(); // ✅ CLONE SMART_CACHE
+        let client_address = addr.ip().to_string();
         
-        let receive_task = tokio::spawn(async move {
+        let mut receive_task = tokio::spawn(async move {
             while let Some(msg) = ws_receiver.next().await {
                 match msg {
                     Ok(Message::Text(text)) => {
@@ -1430,6 +2690,51 @@ impl WebSocketServer {
                             let cmd_type = cmd.get("type").and_then(|t| t.as_str()).unwrap_or("");
                             
                             match cmd_type {
+                                // 🆕 NEGOCIAÇÃO DE VERSÃO/RECURSOS (resposta ao WELCOME)
+                                "HELLO" => {
+                                    let mut response = ws_protocol::handle_hello(&client_features, &cmd);
+                                    println!("🤝 Cliente {} HELLO: protocolo v{} recursos {:?}",
+                                        client_id, response["protocol_version"], client_features.enabled());
+                                    
+                                    // 🆕 Chave pública: dados mascarados pelas regras da chave (só acrescenta;
+                                    // a chave da autenticação continua valendo mesmo sem "public_key" aqui)
+                                    let public_key = cmd.get("public_key").and_then(|k| k.as_str()).filter(|k| !k.is_empty());
+                                    let bound = match public_key {
+                                        Some(public_key) => ws_auth::authenticate_public_key(&database_recv, public_key)
+                                            .and_then(|key| bind_public_key(&connected_clients_recv, client_id, &key)),
+                                        None => Ok(()),
+                                    };
+                                    if let Err(message) = bound {
+                                        response["success"] = serde_json::json!(false);
+                                        response["message"] = serde_json::json!(message);
+                                    }
+                                    if let Some(mask) = connected_clients_recv.get(&client_id).and_then(|c| c.masking.clone()) {
+                                        response["public"] = serde_json::json!(mask.key_name);
+                                    }
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
+                                }
+                                
+                                // 🆕 Credencial depois do handshake: sem autenticação exigida serve para
+                                // identificar o cliente; token não remove o mascaramento de uma chave pública
+                                "AUTH" => {
+                                    let field = |name: &str| cmd.get(name).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+                                    let result = ws_auth::authenticate_credential(&database_recv, field("token"), field("public_key"))
+                                        .and_then(|credential| {
+                                            match &credential {
+                                                ws_auth::Credential::Token(token) => {
+                                                    if let Some(mut client) = connected_clients_recv.get_mut(&client_id) {
+                                                        client.token_id = Some(token.id);
+                                                        client.token_name = Some(token.name.clone());
+                                                    }
+                                                }
+                                                ws_auth::Credential::PublicKey(key) => bind_public_key(&connected_clients_recv, client_id, key)?,
+                                            }
+                                            Ok(credential)
+                                        });
+                                    let response = ws_auth::auth_result(result.as_ref().map_err(String::as_str));
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
+                                }
+                                
                                 "LIST_PLCS" => {
                                     println!("📋 Cliente {} solicitou lista de PLCs", client_id);
                                     
@@ -1451,16 +2756,15 @@ impl WebSocketServer {
                                     
                                     println!("📡 Enviando lista de {} PLCs para cliente {}", plcs.len(), client_id);
                                     
-                                    let response = serde_json::json!({
-                                        "type": "PLC_LIST",
-                                        "plcs": plcs,
-                                        "timestamp": SystemTime::now()
+                                    let response = ServerMessage::PlcList {
+                                        plcs,
+                                        timestamp: SystemTime::now()
                                             .duration_since(UNIX_EPOCH)
                                             .unwrap_or_default()
-                                            .as_millis()
-                                    });
+                                            .as_millis() as u64,
+                                    }.to_json();
                                     
-                                    let _ = response_tx_clone.send(response.to_string()).await;
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
                                 }
                                 
                                 "SUBSCRIBE_PLCS" => {
@@ -1484,17 +2788,94 @@ impl WebSocketServer {
                                             client.client_type = ClientType::Filtered(plcs.clone());
                                         }
                                         
-                                        let response = serde_json::json!({
-                                            "type": "SUBSCRIBE_ACK",
-                                            "success": true,
-                                            "plcs": plcs,
-                                            "message": "Subscrição atualizada com sucesso"
-                                        });
+                                        let response = ServerMessage::SubscribeAck {
+                                            success: true,
+                                            plcs,
+                                            areas: None,
+                                            categories: None,
+                                            include_all_faults: None,
+                                            min_priority: None,
+                                            message: Some("Subscrição atualizada com sucesso".to_string()),
+                                        }.to_json();
                                         
-                                        let _ = response_tx_clone.send(response.to_string()).await;
+                                        let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
                                     }
                                 }
                                 
+                                // 🆕 METADADOS DOS TAGS (unidade já convertida, tipo, área, categoria)
+                                "LIST_TAGS" => {
+                                    let plcs: std::collections::HashSet<String> = cmd.get("plc_ips")
+                                        .and_then(|p| p.as_array())
+                                        .map(|arr| arr.iter().filter_map(|ip| ip.as_str().map(|s| s.to_string())).collect())
+                                        .unwrap_or_default();
+                                    
+                                    // 🆕 Cliente público não vê os tags ocultos pela chave
+                                    let mut tags = smart_cache_recv.get_tag_metadata(&plcs);
+                                    if let Some(mask) = connected_clients_recv.get(&client_id).and_then(|c| c.masking.clone()) {
+                                        tags.retain(|tag| !mask.is_hidden(
+                                            &tag.tag_name,
+                                            tag.area.as_deref(),
+                                            tag.category.as_deref(),
+                                        ));
+                                    }
+                                    let response = ServerMessage::TagList {
+                                        tags,
+                                        timestamp: SystemTime::now()
+                                            .duration_since(UNIX_EPOCH)
+                                            .unwrap_or_default()
+                                            .as_millis() as u64,
+                                    }.to_json();
+                                    
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
+                                }
+                                
+                                // 🆕 FORMA DE ONDA SOB DEMANDA (binário se negociado no HELLO)
+                                "GET_WAVEFORM" => {
+                                    let tag_name = cmd.get("tag").and_then(|t| t.as_str()).unwrap_or("");
+                                    let plc_ip = cmd.get("plc_ip").and_then(|p| p.as_str());
+                                    let masking = connected_clients_recv.get(&client_id).and_then(|c| c.masking.clone());
+                                    
+                                    let waveform_error = |message: &str| ServerMessage::Waveform {
+                                        success: false,
+                                        tag: tag_name.to_string(),
+                                        plc_ip: None,
+                                        data_type: None,
+                                        unit: None,
+                                        ts_ms: None,
+                                        count: 0,
+                                        points: Vec::new(),
+                                        message: Some(message.to_string()),
+                                    };
+                                    let response = match smart_cache_recv.get_waveform(plc_ip, tag_name) {
+                                        // Cliente público: só formas de onda sem regra de mascaramento
+                                        Some(cached) if masking.as_ref().is_some_and(|mask| !mask.allows_raw(&cached.tag_name, cached.area.as_deref(), cached.category.as_deref())) => {
+                                            waveform_error("Forma de onda não disponível para esta chave")
+                                        }
+                                        Some(cached) => {
+                                            let points = crate::plc_parser::waveform_points(&cached.value).unwrap_or_default();
+                                            ServerMessage::Waveform {
+                                                success: true,
+                                                plc_ip: Some(cached.plc_ip),
+                                                tag: cached.tag_name,
+                                                data_type: Some(cached.data_type),
+                                                unit: cached.unit,
+                                                ts_ms: Some((cached.timestamp_ns / 1_000_000) as u64),
+                                                count: points.len(),
+                                                points: points.into_iter().map(Some).collect(),
+                                                message: None,
+                                            }
+                                        }
+                                        None => waveform_error("Forma de onda não encontrada"),
+                                    }.to_json();
+                                    
+                                    let binary = client_features.binary.load(Ordering::SeqCst);
+                                    let message = match rmp_serde::to_vec_named(&response) {
+                                        Ok(bytes) if binary => Message::Binary(bytes),
+                                        _ => Message::Text(response.to_string()),
+                                    };
+                                    let _ = response_tx_clone.send(message).await;
+                                }
+                                
                                 // 🆕 SUBSCRIBE INTELIGENTE COM FILTROS DE ÁREA E CATEGORIA
                                 "SUBSCRIBE" => {
                                     let plcs: Vec<String> = cmd.get("plc_ips")
@@ -1516,11 +2897,18 @@ impl WebSocketServer {
                                         .and_then(|f| f.as_bool())
                                         .unwrap_or(false);
                                     
+                                    // 🆕 Prioridade mínima (0 = sem filtro)
+                                    let min_priority = cmd.get("min_priority")
+                                        .and_then(|p| p.as_u64())
+                                        .unwrap_or(0)
+                                        .min(u8::MAX as u64) as u8;
+                                    
                                     println!("📡 Cliente {} SUBSCRIBE inteligente:", client_id);
                                     println!("   PLCs: {:?}", plcs);
                                     println!("   Áreas: {:?}", areas);
                                     println!("   Categorias: {:?}", categories);
                                     println!("   Include All Faults: {}", include_all_faults);
+                                    println!("   Prioridade mínima: {}", min_priority);
                                     
                                     // Atualizar subscrições do cliente
                                     if let Some(mut client) = connected_clients_recv.get_mut(&client_id) {
@@ -1553,6 +2941,7 @@ impl WebSocketServer {
                                         
                                         // Flag para receber todas as falhas
                                         client.include_all_faults.store(include_all_faults, Ordering::SeqCst);
+                                        client.min_priority.store(min_priority, Ordering::SeqCst);
                                         
                                         // Atualizar client_type
                                         if !plcs.is_empty() {
@@ -1560,17 +2949,51 @@ impl WebSocketServer {
                                         }
                                     }
                                     
-                                    let response = serde_json::json!({
-                                        "type": "SUBSCRIBE_ACK",
-                                        "success": true,
-                                        "plcs": plcs,
-                                        "areas": areas,
-                                        "categories": categories,
-                                        "include_all_faults": include_all_faults,
-                                        "message": "Subscrição inteligente configurada com sucesso"
-                                    });
+                                    let response = ServerMessage::SubscribeAck {
+                                        success: true,
+                                        plcs,
+                                        areas: Some(areas),
+                                        categories: Some(categories),
+                                        include_all_faults: Some(include_all_faults),
+                                        min_priority: Some(min_priority),
+                                        message: Some("Subscrição inteligente configurada com sucesso".to_string()),
+                                    }.to_json();
+                                    
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
+                                }
+                                
+                                // 🆕 PLAYBACK HISTÓRICO só para esta conexão; os demais clientes seguem ao vivo
+                                "PLAYBACK" => {
+                                    let enabled = cmd.get("enabled").and_then(|e| e.as_bool()).unwrap_or(false);
+                                    let frame = smart_cache_recv.playback_frame();
+                                    let rejection = match connected_clients_recv.get(&client_id) {
+                                        Some(client) if enabled && client.masking.is_some() => Some("Playback não disponível para chaves públicas"),
+                                        Some(_) if enabled && frame.is_none() => Some("Nenhum playback ativo no servidor"),
+                                        Some(client) => {
+                                            let was_enabled = client.playback.swap(enabled, Ordering::SeqCst);
+                                            if was_enabled && !enabled {
+                                                Self::send_live_snapshot(&smart_cache_recv, &client).await;
+                                            }
+                                            None
+                                        }
+                                        None => Some("Cliente não encontrado"),
+                                    };
                                     
-                                    let _ = response_tx_clone.send(response.to_string()).await;
+                                    let response = ServerMessage::PlaybackAck {
+                                        success: rejection.is_none(),
+                                        enabled: enabled && rejection.is_none(),
+                                        position_ms: frame.as_ref().map(|f| f.position_ms),
+                                        message: rejection.map(str::to_string),
+                                    }.to_json();
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
+                                }
+                                
+                                // 🆕 PAINÉIS REMOTOS (plc-app): heartbeat e logs encaminhados
+                                "PANEL_HEARTBEAT" | "PANEL_LOGS" => {
+                                    let response = crate::panels::handle_panel_message(
+                                        &app_handle_recv, &database_recv, cmd_type, &cmd, &client_address,
+                                    );
+                                    let _ = response_tx_clone.send(Message::Text(response.to_string())).await;
                                 }
                                 
                                 _ => {
@@ -1595,9 +3018,30 @@ impl WebSocketServer {
             }
         });
 
-        tokio::select! {
-            _ = send_task => {},
-            _ = receive_task => {}
+        let disconnect_reason = tokio::select! {
+            _ = &mut send_task => "closed",
+            _ = &mut receive_task => "closed",
+            // 🆕 Desconexão forçada pelo servidor (token revogado)
+            _ = disconnect.notified() => {
+                println!("⛔ Cliente {} desconectado pelo servidor", client_id);
+                let _ = ws_sender.lock().await.send(Message::Close(None)).await;
+                "revoked"
+            }
+        };
+        send_task.abort();
+        receive_task.abort();
+        session_task.abort();
+        
+        // 🆕 Fechar o registro da sessão com os contadores finais
+        if let Some(session_id) = session_id {
+            let record = connected_clients.get(&client_id).map(|client| client.session_record(session_id));
+            if let Some(mut record) = record {
+                record.disconnected_at_ms = Some(record.last_seen_ms);
+                record.disconnect_reason = Some(disconnect_reason.to_string());
+                if let Err(e) = database.update_ws_session(&record) {
+                    println!("⚠️ Erro ao encerrar sessão do cliente {}: {}", client_id, e);
+                }
+            }
         }
 
         connected_clients.remove(&client_id);
@@ -1614,9 +3058,17 @@ impl WebSocketServer {
         Ok(())
     }
 
-    pub async fn stop(&mut self) -> Result<String, String> {
+    pub async fn stop(&mut self) -> Result<String, AppError> {
         if !self.is_running.load(Ordering::SeqCst) {
-            return Err("WebSocket server não está rodando".to_string());
+            return Err(AppError::websocket_not_running());
+        }
+
+        // 🆕 Avisar os clientes que o desligamento é intencional (melhor esforço)
+        if let Some(broadcast_tx) = &self.broadcast_sender {
+            let status = crate::server_status::offline().await;
+            if broadcast_tx.send(Message::Text(crate::server_status::ws_message(&status).to_string())).is_ok() {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
         }
 
         self.is_running.store(false, Ordering::SeqCst);
@@ -1630,12 +3082,29 @@ impl WebSocketServer {
         if let Some(handle) = self.cache_updater_handle.take() {
             handle.abort();
         }
+        // 🆕 Tasks de envio (o repasse de críticos do worker fica bloqueado no recv)
+        for handle in self.interval_handles.lock().await.drain(..) {
+            handle.abort();
+        }
+
+        // 🆕 Sessões abertas terminam aqui (clientes sem servidor)
+        for client in self.connected_clients.iter() {
+            let Some(session_id) = client.session_id else { continue };
+            let mut record = client.session_record(session_id);
+            record.disconnected_at_ms = Some(record.last_seen_ms);
+            record.disconnect_reason = Some("server_stopped".to_string());
+            if let Err(e) = self.database.update_ws_session(&record) {
+                println!("⚠️ Erro ao encerrar sessão do cliente {}: {}", client.id, e);
+            }
+        }
 
         self.connected_clients.clear();
         self.active_connections.store(0, Ordering::SeqCst);
 
-        let _ = self.app_handle.emit("websocket-server-stopped", serde_json::json!({
+        let stopped_event = if self.worker_port.is_some() { "websocket-worker-stopped" } else { "websocket-server-stopped" };
+        let _ = self.app_handle.emit(stopped_event, serde_json::json!({
             "status": "stopped",
+            "port": self.config.port,
             "timestamp": chrono::Utc::now().to_rfc3339()
         }));
 
@@ -1651,6 +3120,8 @@ impl WebSocketServer {
         } else {
             0.0
         };
+        let (critical_updates_sent, critical_latency_last_ms, critical_latency_avg_ms, critical_latency_max_ms) =
+            self.smart_cache.critical_latency.snapshot();
 
         WebSocketStats {
             active_connections: self.active_connections.load(Ordering::SeqCst),
@@ -1664,9 +3135,33 @@ impl WebSocketServer {
                 "Parado".to_string()
             },
             broadcast_rate_hz: broadcast_rate,
+            suppressed_events: self.smart_cache.suppressed_events(),
+            critical_updates_sent,
+            critical_latency_last_ms,
+            critical_latency_avg_ms,
+            critical_latency_max_ms,
+            db_degraded: self.smart_cache.db_breaker_status().degraded,
         }
     }
 
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
+
+    /// 🆕 Cache compartilhado (usado pelo playback histórico)
+    pub fn smart_cache(&self) -> Arc<SmartCache> {
+        self.smart_cache.clone()
+    }
+
+    /// 🆕 Contagem de transições suprimidas por tag (coalescência / debounce)
+    pub fn db_breaker_status(&self) -> DbBreakerStatus {
+        self.smart_cache.db_breaker_status()
+    }
+
+    pub fn get_suppressed_events(&self) -> Vec<serde_json::Value> {
+        self.smart_cache.get_suppressed_by_tag()
+    }
+
     pub fn get_connected_clients(&self) -> Vec<serde_json::Value> {
         self.connected_clients
             .iter()
@@ -1679,12 +3174,27 @@ impl WebSocketServer {
                         .duration_since(std::time::UNIX_EPOCH)
                         .unwrap_or_default()
                         .as_secs(),
-                    "messages_received": client.messages_received.load(Ordering::SeqCst)
+                    "messages_received": client.messages_received.load(Ordering::SeqCst),
+                    "messages_sent": client.messages_sent.load(Ordering::Relaxed),
+                    "bytes_sent": client.bytes_sent.load(Ordering::Relaxed),
+                    "public_key": client.masking.as_ref().map(|mask| mask.key_name.clone()),
+                    "token_id": client.token_id,
+                    "token": client.token_name
                 })
             })
             .collect()
     }
 
+    /// 🆕 Derruba os clientes conectados com o token (após revogação)
+    pub fn disconnect_token_clients(&self, token_id: i64) -> usize {
+        let mut count = 0;
+        for client in self.connected_clients.iter().filter(|c| c.token_id == Some(token_id)) {
+            client.disconnect.notify_one();
+            count += 1;
+        }
+        count
+    }
+
     pub fn update_config(&mut self, new_config: WebSocketConfig) {
         self.config = new_config;
     }
@@ -0,0 +1,137 @@
+use crate::database::{Database, PublicStreamKey, WsToken};
+use plc_hmi_client::protocol::ServerMessage;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+// ============================================================================
+// AUTENTICAÇÃO DO WEBSOCKET POR TOKEN DE API
+// ============================================================================
+//
+// Enquanto não existir nenhum token ativo nem chave pública habilitada o
+// WebSocket continua aberto (clientes já instalados não param). Com pelo menos
+// um dos dois, todo cliente precisa se autenticar no handshake:
+//   - query param:      ws://host:8765/?token=wst_...  ou  ?public_key=...
+//   - ou 1ª mensagem:   {"type":"AUTH","token":"wst_..."} / {"type":"AUTH","public_key":"..."}
+//                       (em até AUTH_TIMEOUT, após o servidor enviar AUTH_REQUIRED)
+// Sem credencial válida a conexão é fechada antes de receber qualquer dado.
+// A chave pública é uma credencial própria: a conexão (ou requisição HTTP)
+// autenticada com ela fica presa às regras de mascaramento da chave, mesmo que
+// também apresente um token.
+// Tokens são gerados aqui, mostrados uma única vez e guardados só como SHA-256.
+
+pub const TOKEN_PREFIX: &str = "wst_";
+pub const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+const VISIBLE_PREFIX_LEN: usize = 12;
+
+/// Credencial aceita na conexão
+#[derive(Debug, Clone)]
+pub enum Credential {
+    Token(WsToken),
+    PublicKey(PublicStreamKey), // Dados sempre mascarados pelas regras da chave
+}
+
+/// Token recém-criado: `secret` só existe nesta resposta
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedWsToken {
+    pub token: WsToken,
+    pub secret: String,
+}
+
+/// Novo token aleatório (2 UUIDs v4, 244 bits) + hash e prefixo visível para gravar
+pub fn generate_token() -> (String, String, String) {
+    let token = format!("{}{}{}", TOKEN_PREFIX, uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let prefix = token.chars().take(VISIBLE_PREFIX_LEN).collect();
+    (hash_token(&token), prefix, token)
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Token do parâmetro "token" da query string do handshake
+pub fn token_from_query(query: Option<&str>) -> Option<String> {
+    query_param(query, "token")
+}
+
+/// Parâmetro não vazio da query string
+pub fn query_param(query: Option<&str>, name: &str) -> Option<String> {
+    query?.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Chave pública das APIs HTTP: "X-Public-Key: ..." ou "?public_key=..."
+#[cfg_attr(not(any(feature = "rest", feature = "graphql")), allow(dead_code))]
+pub fn public_key_from_request(header: Option<&str>, query: Option<&str>) -> Option<String> {
+    header
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or_else(|| query_param(query, "public_key"))
+}
+
+/// Token das APIs HTTP (REST/GraphQL): "Authorization: Bearer wst_..." ou "?token=wst_..."
+#[cfg_attr(not(any(feature = "rest", feature = "graphql")), allow(dead_code))]
+pub fn token_from_request(authorization: Option<&str>, query: Option<&str>) -> Option<String> {
+    authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or_else(|| token_from_query(query))
+}
+
+/// Autenticação exigida? Token ativo ou chave pública habilitada
+/// (erro no banco = exigir, para não abrir por falha)
+pub fn auth_required(database: &Database) -> bool {
+    token_required(database) || database.has_enabled_public_stream_keys().unwrap_or_else(|e| {
+        println!("⚠️ WebSocket: erro ao verificar chaves públicas ({}), exigindo autenticação", e);
+        true
+    })
+}
+
+/// Token exigido? (endpoints que só aceitam token, ex: IPC local)
+pub fn token_required(database: &Database) -> bool {
+    database.has_active_ws_tokens().unwrap_or_else(|e| {
+        println!("⚠️ WebSocket: erro ao verificar tokens ({}), exigindo autenticação", e);
+        true
+    })
+}
+
+/// Mensagem AUTH_RESULT (aceito: nome do token ou da chave; rejeitado: motivo)
+pub fn auth_result(result: Result<&Credential, &str>) -> serde_json::Value {
+    match result {
+        Ok(Credential::Token(token)) => ServerMessage::AuthResult { success: true, token: Some(token.name.clone()), public: None, message: None },
+        Ok(Credential::PublicKey(key)) => ServerMessage::AuthResult { success: true, token: None, public: Some(key.name.clone()), message: None },
+        Err(reason) => ServerMessage::AuthResult { success: false, token: None, public: None, message: Some(reason.to_string()) },
+    }
+    .to_json()
+}
+
+/// Valida o token e registra o último uso
+pub fn authenticate(database: &Database, token: &str) -> Result<WsToken, String> {
+    let found = database.find_active_ws_token(&hash_token(token))
+        .map_err(|e| format!("Erro ao validar token: {}", e))?
+        .ok_or_else(|| "Token inválido ou revogado".to_string())?;
+    if let Err(e) = database.touch_ws_token(found.id) {
+        println!("⚠️ WebSocket: erro ao registrar uso do token '{}': {}", found.name, e);
+    }
+    Ok(found)
+}
+
+/// Valida a chave pública (habilitada)
+pub fn authenticate_public_key(database: &Database, key: &str) -> Result<PublicStreamKey, String> {
+    database.find_public_stream_key(key.trim())
+        .map_err(|e| format!("Erro ao validar chave pública: {}", e))?
+        .ok_or_else(|| "Chave pública inválida ou desabilitada".to_string())
+}
+
+/// Valida a credencial apresentada; com chave pública ela prevalece sobre o token
+pub fn authenticate_credential(database: &Database, token: Option<&str>, public_key: Option<&str>) -> Result<Credential, String> {
+    match (public_key, token) {
+        (Some(key), _) => authenticate_public_key(database, key).map(Credential::PublicKey),
+        (None, Some(token)) => authenticate(database, token).map(Credential::Token),
+        (None, None) => Err("Token ou chave pública não informado".to_string()),
+    }
+}
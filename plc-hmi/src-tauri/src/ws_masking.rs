@@ -0,0 +1,168 @@
+use crate::database::{MaskingRule, PublicStreamKey};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+// ============================================================================
+// MASCARAMENTO DE DADOS PARA CLIENTES PÚBLICOS DO WEBSOCKET
+// ============================================================================
+//
+// Um cliente autenticado com uma chave pública ("public_key" no handshake, no
+// AUTH ou no HELLO - ver ws_auth.rs) passa a receber os tags pelas regras da
+// chave: arredondados, atrasados N segundos ou ocultos. O mascaramento fica na
+// conexão até ela fechar. A regra mais específica vence: tag > category > area
+// > "*". Tags sem regra seguem sem alteração. Clientes públicos não recebem o
+// caminho crítico (CRITICAL), que furaria o atraso.
+// As APIs GraphQL e REST usam as mesmas chaves ("X-Public-Key" ou "?public_key=").
+
+pub const GROUP_TYPES: &[&str] = &["area", "category", "tag", "*"];
+
+/// Limite de valores aguardando liberação por cliente (evita crescer sem fim)
+const MAX_DELAYED_VALUES: usize = 50_000;
+
+/// Grupos de um tag usados para achar a regra
+#[derive(Debug, Clone, Default)]
+pub struct TagGroups {
+    pub area: Option<String>,
+    pub category: Option<String>,
+    pub ts_ms: u64,
+}
+
+pub struct StreamMask {
+    pub key_name: String,
+    rules: Vec<MaskingRule>,
+    // (liberar em ms, tag, valor, ts original ms)
+    delayed: Mutex<VecDeque<(u64, String, String, u64)>>,
+}
+
+impl std::fmt::Debug for StreamMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamMask").field("key_name", &self.key_name).field("rules", &self.rules.len()).finish()
+    }
+}
+
+/// Valida as regras antes de salvar a chave
+pub fn validate_rules(rules: &[MaskingRule]) -> Result<(), String> {
+    for rule in rules {
+        if !GROUP_TYPES.contains(&rule.group_type.as_str()) {
+            return Err(format!("Tipo de grupo inválido: {} (use {})", rule.group_type, GROUP_TYPES.join(", ")));
+        }
+        if rule.group_type != "*" && rule.group_name.trim().is_empty() {
+            return Err(format!("Regra '{}' sem nome de grupo", rule.group_type));
+        }
+        if rule.round_decimals.is_some_and(|d| d > 6) {
+            return Err("Arredondamento máximo: 6 casas decimais".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub fn round_value(value: &str, decimals: u32) -> String {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => format!("{:.*}", decimals as usize, number),
+        _ => value.to_string(), // BOOL/texto: sem arredondamento
+    }
+}
+
+impl StreamMask {
+    pub fn new(key: &PublicStreamKey) -> Self {
+        Self {
+            key_name: key.name.clone(),
+            rules: key.rules.clone(),
+            delayed: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn rule_for(&self, tag_name: &str, groups: Option<&TagGroups>) -> Option<&MaskingRule> {
+        let find = |group_type: &str, name: Option<&str>| {
+            name.and_then(|name| self.rules.iter().find(|r| r.group_type == group_type && r.group_name == name))
+        };
+        find("tag", Some(tag_name))
+            .or_else(|| find("category", groups.and_then(|g| g.category.as_deref())))
+            .or_else(|| find("area", groups.and_then(|g| g.area.as_deref())))
+            .or_else(|| self.rules.iter().find(|r| r.group_type == "*"))
+    }
+
+    /// Sem regra para o tag: pode receber dados brutos (ex: formas de onda sob demanda)
+    pub fn allows_raw(&self, tag_name: &str, area: Option<&str>, category: Option<&str>) -> bool {
+        let groups = TagGroups { area: area.map(str::to_string), category: category.map(str::to_string), ts_ms: 0 };
+        self.rule_for(tag_name, Some(&groups)).is_none()
+    }
+
+    pub fn is_hidden(&self, tag_name: &str, area: Option<&str>, category: Option<&str>) -> bool {
+        let groups = TagGroups { area: area.map(str::to_string), category: category.map(str::to_string), ts_ms: 0 };
+        self.rule_for(tag_name, Some(&groups)).is_some_and(|r| r.hide)
+    }
+
+    /// Valor para uma consulta pontual (GraphQL): None se o tag estiver oculto ou
+    /// atrasado, já que a fila de atraso só existe no envio contínuo (`apply`)
+    #[cfg_attr(not(any(feature = "graphql", feature = "rest")), allow(dead_code))]
+    pub fn snapshot_value(&self, tag_name: &str, area: Option<&str>, category: Option<&str>, value: &str) -> Option<String> {
+        let groups = TagGroups { area: area.map(str::to_string), category: category.map(str::to_string), ts_ms: 0 };
+        match self.rule_for(tag_name, Some(&groups)) {
+            None => Some(value.to_string()),
+            Some(rule) if rule.hide || rule.delay_s.is_some_and(|d| d > 0) => None,
+            Some(rule) => Some(rule.round_decimals.map_or_else(|| value.to_string(), |d| round_value(value, d))),
+        }
+    }
+
+    /// Histórico de um tag: None se oculto; senão (casas decimais, atraso em s),
+    /// para cortar a consulta em agora - atraso e arredondar as amostras
+    #[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+    pub fn history_rule(&self, tag_name: &str, area: Option<&str>, category: Option<&str>) -> Option<(Option<u32>, u64)> {
+        let groups = TagGroups { area: area.map(str::to_string), category: category.map(str::to_string), ts_ms: 0 };
+        match self.rule_for(tag_name, Some(&groups)) {
+            None => Some((None, 0)),
+            Some(rule) if rule.hide => None,
+            Some(rule) => Some((rule.round_decimals, rule.delay_s.unwrap_or(0))),
+        }
+    }
+
+    /// Aplica as regras a um lote. Retorna os valores a enviar agora e, para os
+    /// valores atrasados que venceram, o timestamp original (ms) de cada um.
+    pub fn apply(&self, tags: HashMap<String, String>, groups: &HashMap<String, TagGroups>, now_ms: u64) -> (HashMap<String, String>, HashMap<String, u64>) {
+        let mut output = HashMap::new();
+        let mut delayed = self.delayed.lock().unwrap();
+
+        for (tag_name, value) in tags {
+            let tag_groups = groups.get(&tag_name);
+            let Some(rule) = self.rule_for(&tag_name, tag_groups) else {
+                output.insert(tag_name, value);
+                continue;
+            };
+            if rule.hide {
+                continue;
+            }
+            let value = match rule.round_decimals {
+                Some(decimals) => round_value(&value, decimals),
+                None => value,
+            };
+            match rule.delay_s.filter(|d| *d > 0) {
+                Some(delay_s) => {
+                    if delayed.len() >= MAX_DELAYED_VALUES {
+                        delayed.pop_front();
+                    }
+                    let ts_ms = tag_groups.map(|g| g.ts_ms).unwrap_or(now_ms);
+                    delayed.push_back((now_ms + delay_s * 1000, tag_name, value, ts_ms));
+                }
+                None => {
+                    output.insert(tag_name, value);
+                }
+            }
+        }
+
+        // Liberar os atrasados vencidos (atrasos diferentes por regra: varrer a fila
+        // inteira; em ordem de chegada, o mais recente de cada tag vence)
+        let mut original_ts = HashMap::new();
+        let mut pending = VecDeque::with_capacity(delayed.len());
+        for (release_ms, tag_name, value, ts_ms) in delayed.drain(..) {
+            if release_ms <= now_ms {
+                original_ts.insert(tag_name.clone(), ts_ms);
+                output.insert(tag_name, value);
+            } else {
+                pending.push_back((release_ms, tag_name, value, ts_ms));
+            }
+        }
+        *delayed = pending;
+        (output, original_ts)
+    }
+}
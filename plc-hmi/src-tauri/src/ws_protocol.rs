@@ -0,0 +1,475 @@
+use crate::database::TagMapping;
+use crate::websocket_server::WebSocketConfig;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// ============================================================================
+// DOCUMENTAÇÃO DO PROTOCOLO WEBSOCKET (AsyncAPI / JSON Schema)
+// ============================================================================
+//
+// Gera, a partir da configuração ativa desta instalação, a descrição das
+// mensagens trocadas com os displays: comandos do cliente (LIST_PLCS, SUBSCRIBE...),
+// respostas do servidor e o mapa de tags publicado em cada broadcast
+// (JSON, "MSGPACK:" + base64 ou MessagePack em frame binário com ?format=binary).
+// Os tags documentados são os habilitados no banco; o tipo vem do cache do
+// servidor quando já recebido.
+
+pub const FORMAT_ASYNCAPI: &str = "asyncapi";
+pub const FORMAT_JSON_SCHEMA: &str = "json_schema";
+const JSON_SCHEMA_DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Tag publicado pelo servidor, com o que o cliente precisa saber para consumi-lo
+#[derive(Debug, Clone)]
+pub struct ProtocolTag {
+    pub mapping: TagMapping,
+    pub data_type: Option<String>, // Tipo do PLC (REAL, INT, BOOL...) se já visto no cache
+}
+
+/// Valores são publicados como texto; o tipo indica como interpretá-los
+fn value_schema(tag: &ProtocolTag) -> Value {
+    let mapping = &tag.mapping;
+    let mut description = mapping.description.clone().unwrap_or_default();
+    if let Some(unit) = mapping.display_unit.as_ref().or(mapping.unit.as_ref()).filter(|u| !u.is_empty()) {
+        description = format!("{} [{}]", description, unit).trim().to_string();
+    }
+    let format = match tag.data_type.as_deref() {
+        Some("BOOL") => json!({ "enum": ["TRUE", "FALSE"] }),
+        Some("REAL" | "LREAL") => json!({ "pattern": "^-?[0-9]+(\\.[0-9]+)?([eE][-+]?[0-9]+)?$" }),
+        Some("INT" | "DINT" | "LINT") => json!({ "pattern": "^-?[0-9]+$" }),
+        Some("WORD" | "DWORD" | "LWORD" | "BYTE") => json!({ "pattern": "^[0-9]+$" }),
+        _ => json!({}),
+    };
+
+    let mut schema = json!({
+        "type": "string",
+        "description": description,
+        "x-plc-ip": mapping.plc_ip,
+        "x-variable-path": mapping.variable_path,
+        "x-data-type": tag.data_type,
+        "x-unit": mapping.display_unit.as_ref().or(mapping.unit.as_ref()),
+        "x-area": mapping.area,
+        "x-category": mapping.category,
+        "x-collect-mode": mapping.collect_mode.as_deref().unwrap_or("interval"),
+        "x-collect-interval-s": mapping.collect_interval_s,
+        "x-critical": mapping.critical,
+    });
+    if let (Some(object), Some(format)) = (schema.as_object_mut(), format.as_object()) {
+        object.extend(format.clone());
+    }
+    schema
+}
+
+/// Schema do mapa { tag_name: valor } enviado nos broadcasts
+fn tag_data_schema(tags: &[ProtocolTag]) -> Value {
+    let properties: Map<String, Value> = tags.iter()
+        .map(|tag| (tag.mapping.tag_name.clone(), value_schema(tag)))
+        .collect();
+    json!({
+        "type": "object",
+        "description": "Protocolo v1 (clientes sem HELLO): mapa tag -> valor (texto), ordenado naturalmente. Enviado como JSON, como \"MSGPACK:\" + base64 do mesmo mapa ou, com ?format=binary na URL, como MessagePack num frame binário. Cada mensagem traz apenas os tags do ciclo/filtros do cliente.",
+        "properties": properties,
+        "additionalProperties": { "type": "string" }
+    })
+}
+
+fn string_array(description: &str) -> Value {
+    json!({ "type": "array", "items": { "type": "string" }, "description": description })
+}
+
+/// Identidade do servidor (nome, site, UUID) enviada no WELCOME e no HELLO_ACK
+fn instance_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "site": { "type": "string" },
+            "uuid": { "type": "string", "format": "uuid" }
+        }
+    })
+}
+
+fn command_schema(command: &str, properties: Value, required: &[&str]) -> Value {
+    let mut props = json!({ "type": { "const": command } });
+    if let (Some(object), Some(extra)) = (props.as_object_mut(), properties.as_object()) {
+        object.extend(extra.clone());
+    }
+    let mut required: Vec<&str> = required.to_vec();
+    required.insert(0, "type");
+    json!({ "type": "object", "properties": props, "required": required })
+}
+
+/// Mensagens enviadas pelo cliente (nome -> schema)
+fn client_messages(areas: &BTreeSet<String>, categories: &BTreeSet<String>) -> BTreeMap<&'static str, Value> {
+    let mut messages = BTreeMap::new();
+    messages.insert("HELLO", command_schema("HELLO", json!({
+        "protocol_version": { "type": "integer", "minimum": LEGACY_PROTOCOL_VERSION, "maximum": PROTOCOL_VERSION },
+        "features": { "type": "array", "items": { "type": "string", "enum": SUPPORTED_FEATURES } },
+        "public_key": { "type": "string", "description": "Chave pública: dados mascarados (arredondados/atrasados/ocultos) conforme as regras da chave" }
+    }), &["protocol_version"]));
+    messages.insert("AUTH", command_schema("AUTH", json!({
+        "token": { "type": "string", "description": "Token de API (wst_...). Primeira mensagem quando o servidor envia AUTH_REQUIRED; alternativa: ?token= na URL" }
+    }), &["token"]));
+    messages.insert("LIST_PLCS", command_schema("LIST_PLCS", json!({}), &[]));
+    messages.insert("LIST_TAGS", command_schema("LIST_TAGS", json!({
+        "plc_ips": string_array("PLCs a listar (vazio = todos)")
+    }), &[]));
+    messages.insert("GET_WAVEFORM", command_schema("GET_WAVEFORM", json!({
+        "tag": { "type": "string", "description": "Tag de forma de onda (tipo \"<TIPO>[]\" no TAG_LIST)" },
+        "plc_ip": { "type": "string", "description": "Opcional: PLC do tag quando o nome se repete" }
+    }), &["tag"]));
+    messages.insert("PLAYBACK", command_schema("PLAYBACK", json!({
+        "enabled": { "type": "boolean", "description": "true: esta conexão passa a receber o playback histórico em andamento (TAG_DATA com \"playback\": true) em vez dos dados ao vivo; false: volta ao vivo" }
+    }), &["enabled"]));
+    messages.insert("SUBSCRIBE_PLCS", command_schema("SUBSCRIBE_PLCS", json!({
+        "plc_ips": string_array("Substitui a lista de PLCs assinados")
+    }), &["plc_ips"]));
+    messages.insert("SUBSCRIBE", command_schema("SUBSCRIBE", json!({
+        "plc_ips": string_array("PLCs assinados (vazio = todos)"),
+        "areas": { "type": "array", "items": { "type": "string", "enum": areas }, "description": "Áreas (vazio = todas)" },
+        "categories": { "type": "array", "items": { "type": "string", "enum": categories }, "description": "Categorias (vazio = todas)" },
+        "include_all_faults": { "type": "boolean", "default": false, "description": "Recebe falhas de qualquer área" },
+        "min_priority": { "type": "integer", "minimum": 0, "maximum": 255, "default": 0, "description": "Prioridade mínima do grupo (0 = sem filtro)" }
+    }), &[]));
+    messages.insert("PANEL_HEARTBEAT", command_schema("PANEL_HEARTBEAT", json!({
+        "panel": {
+            "type": "object",
+            "properties": {
+                "panel_id": { "type": "string" },
+                "hostname": { "type": "string" },
+                "app_version": { "type": "string" },
+                "plc_connected": { "type": "boolean" },
+                "last_data_age_s": { "type": ["integer", "null"] },
+                "uptime_s": { "type": "integer" }
+            },
+            "required": ["panel_id"]
+        }
+    }), &["panel"]));
+    messages.insert("PANEL_LOGS", command_schema("PANEL_LOGS", json!({
+        "panel_id": { "type": "string" },
+        "logs": {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "timestamp": { "type": "string" },
+                    "level": { "type": "string" },
+                    "category": { "type": "string" },
+                    "message": { "type": "string" },
+                    "details": {}
+                }
+            }
+        }
+    }), &["panel_id", "logs"]));
+    messages
+}
+
+/// Mensagens enviadas pelo servidor (nome -> schema)
+fn server_messages(tags: &[ProtocolTag]) -> BTreeMap<&'static str, Value> {
+    let timestamp = json!({ "type": "integer", "description": "Epoch em milissegundos" });
+    let mut messages = BTreeMap::new();
+    messages.insert("TAG_DATA_V1", tag_data_schema(tags));
+    messages.insert("TAG_DATA", command_schema("TAG_DATA", json!({
+        "protocol_version": { "const": PROTOCOL_VERSION },
+        "tags": {
+            "type": "object",
+            "description": "Clientes com HELLO v2. Valor em texto, ou número/booleano com typed_values; com tag_timestamps cada valor vira {\"v\": valor, \"ts\": epoch ms}. Com binary, o envelope inteiro chega em MessagePack num frame binário.",
+            "propertyNames": { "enum": tags.iter().map(|t| t.mapping.tag_name.clone()).collect::<Vec<_>>() }
+        },
+        "playback": { "type": "boolean", "const": true, "description": "Presente só em valores reproduzidos do historian (cliente em PLAYBACK); também enviado a clientes v1" }
+    }), &["tags"]));
+    messages.insert("CRITICAL", command_schema("CRITICAL", json!({
+        "plc_ip": { "type": "string" },
+        "tag": { "type": "string", "enum": tags.iter().filter(|t| t.mapping.critical).map(|t| t.mapping.tag_name.clone()).collect::<Vec<_>>() },
+        "value": { "description": "Texto, ou número/booleano com typed_values" },
+        "ts": { "type": "integer", "description": "Chegada do pacote TCP (epoch ms)" }
+    }), &["plc_ip", "tag", "value"]));
+    messages.insert("WELCOME", command_schema("WELCOME", json!({
+        "protocol_version": { "const": PROTOCOL_VERSION },
+        "min_protocol_version": { "const": LEGACY_PROTOCOL_VERSION },
+        "features": { "type": "array", "items": { "type": "string", "enum": SUPPORTED_FEATURES } },
+        "client_id": { "type": "integer" },
+        "server_version": { "type": "string" },
+        "instance": instance_schema()
+    }), &["protocol_version", "features"]));
+    messages.insert("AUTH_REQUIRED", command_schema("AUTH_REQUIRED", json!({
+        "timeout_ms": { "type": "integer", "description": "Prazo para enviar AUTH antes de a conexão ser fechada" }
+    }), &[]));
+    messages.insert("AUTH_RESULT", command_schema("AUTH_RESULT", json!({
+        "success": { "type": "boolean" },
+        "token": { "type": "string", "description": "Nome do token aceito" },
+        "message": { "type": "string", "description": "Presente quando success = false" }
+    }), &["success"]));
+    messages.insert("SERVER_STATUS", command_schema("SERVER_STATUS", json!({
+        "status": {
+            "type": "object",
+            "description": "Enviado após o WELCOME, a cada 30 s e com state \"offline\" quando o servidor é parado",
+            "properties": {
+                "state": { "type": "string", "enum": ["online", "offline"] },
+                "version": { "type": "string" },
+                "uptime_s": { "type": "integer" },
+                "connected_plcs": { "type": "integer" },
+                "instance": instance_schema(),
+                "timestamp_ms": timestamp
+            },
+            "required": ["state"]
+        }
+    }), &["status"]));
+    messages.insert("HELLO_ACK", command_schema("HELLO_ACK", json!({
+        "success": { "type": "boolean" },
+        "protocol_version": { "type": "integer" },
+        "features": { "type": "array", "items": { "type": "string", "enum": SUPPORTED_FEATURES } },
+        "rejected_features": string_array("Recursos pedidos e não suportados"),
+        "public": { "type": "string", "description": "Nome da chave pública aceita" },
+        "instance": instance_schema(),
+        "message": { "type": "string" }
+    }), &["protocol_version", "features"]));
+    messages.insert("PLAYBACK_ACK", command_schema("PLAYBACK_ACK", json!({
+        "success": { "type": "boolean" },
+        "enabled": { "type": "boolean", "description": "Conexão em playback após o comando" },
+        "position_ms": { "type": "integer", "description": "Posição atual do playback (epoch ms), se houver um ativo" },
+        "message": { "type": "string", "description": "Presente quando success = false" }
+    }), &["success", "enabled"]));
+    messages.insert("WAVEFORM", command_schema("WAVEFORM", json!({
+        "success": { "type": "boolean" },
+        "plc_ip": { "type": "string" },
+        "tag": { "type": "string", "enum": tags.iter()
+            .filter(|t| t.data_type.as_deref().is_some_and(crate::plc_parser::is_waveform_type))
+            .map(|t| t.mapping.tag_name.clone()).collect::<Vec<_>>() },
+        "data_type": { "type": "string", "description": "Tipo do PLC com sufixo [] (ex: REAL[])" },
+        "unit": { "type": ["string", "null"] },
+        "ts_ms": timestamp,
+        "count": { "type": "integer" },
+        "points": { "type": "array", "items": { "type": ["number", "null"] }, "description": "Com binary, a resposta inteira chega em MessagePack num frame binário" },
+        "message": { "type": "string", "description": "Presente quando success = false" }
+    }), &["success", "tag"]));
+    messages.insert("PLC_LIST", command_schema("PLC_LIST", json!({
+        "plcs": string_array("PLCs configurados"),
+        "timestamp": timestamp
+    }), &["plcs"]));
+    messages.insert("TAG_LIST", command_schema("TAG_LIST", json!({
+        "tags": {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "plc_ip": { "type": "string" },
+                    "tag_name": { "type": "string" },
+                    "data_type": { "type": "string" },
+                    "unit": { "type": ["string", "null"] },
+                    "area": { "type": ["string", "null"] },
+                    "category": { "type": ["string", "null"] }
+                }
+            }
+        },
+        "timestamp": timestamp
+    }), &["tags"]));
+    messages.insert("SUBSCRIBE_ACK", command_schema("SUBSCRIBE_ACK", json!({
+        "success": { "type": "boolean" },
+        "plcs": string_array("PLCs assinados"),
+        "areas": string_array("Somente em resposta a SUBSCRIBE"),
+        "categories": string_array("Somente em resposta a SUBSCRIBE"),
+        "include_all_faults": { "type": "boolean" },
+        "min_priority": { "type": "integer" },
+        "message": { "type": "string" }
+    }), &["success"]));
+    for ack in ["PANEL_ACK", "PANEL_LOGS_ACK"] {
+        messages.insert(ack, command_schema(ack, json!({
+            "success": { "type": "boolean" },
+            "inserted": { "type": "integer" },
+            "last_log_id": { "type": "integer", "description": "Maior id de log já gravado para o painel" },
+            "message": { "type": "string", "description": "Presente quando success = false" }
+        }), &["success"]));
+    }
+    messages
+}
+
+fn distinct(tags: &[ProtocolTag], field: impl Fn(&TagMapping) -> Option<&String>) -> BTreeSet<String> {
+    tags.iter().filter_map(|t| field(&t.mapping).cloned()).filter(|v| !v.is_empty()).collect()
+}
+
+/// Lista os tags habilitados com o tipo observado no cache (chave "plc_ip:tag_name")
+pub fn collect_tags(mappings: Vec<TagMapping>, cached_types: &HashMap<String, String>) -> Vec<ProtocolTag> {
+    let mut tags: Vec<ProtocolTag> = mappings.into_iter()
+        .filter(|m| m.enabled)
+        .map(|mapping| {
+            let data_type = cached_types.get(&format!("{}:{}", mapping.plc_ip, mapping.tag_name))
+                .filter(|t| !t.is_empty())
+                .cloned();
+            ProtocolTag { mapping, data_type }
+        })
+        .collect();
+    tags.sort_by(|a, b| (&a.mapping.plc_ip, &a.mapping.tag_name).cmp(&(&b.mapping.plc_ip, &b.mapping.tag_name)));
+    tags
+}
+
+/// JSON Schema com uma definição por mensagem (cliente e servidor)
+pub fn build_json_schema(tags: &[ProtocolTag]) -> Value {
+    let areas = distinct(tags, |m| m.area.as_ref());
+    let categories = distinct(tags, |m| m.category.as_ref());
+    let mut definitions = Map::new();
+    for (name, schema) in client_messages(&areas, &categories).into_iter().chain(server_messages(tags)) {
+        definitions.insert(name.to_string(), schema);
+    }
+    let refs: Vec<Value> = definitions.keys().map(|name| json!({ "$ref": format!("#/definitions/{}", name) })).collect();
+
+    json!({
+        "$schema": JSON_SCHEMA_DRAFT,
+        "title": "PLC HMI - protocolo WebSocket",
+        "description": format!("Gerado em {} com {} tag(s) habilitado(s)", chrono::Utc::now().to_rfc3339(), tags.len()),
+        "definitions": definitions,
+        "oneOf": refs
+    })
+}
+
+/// Documento AsyncAPI 2.6 do servidor WebSocket desta instalação
+pub fn build_asyncapi(config: &WebSocketConfig, tags: &[ProtocolTag]) -> Value {
+    let areas = distinct(tags, |m| m.area.as_ref());
+    let categories = distinct(tags, |m| m.category.as_ref());
+    let client = client_messages(&areas, &categories);
+    let server = server_messages(tags);
+
+    let mut messages = Map::new();
+    for (name, payload) in client.iter().chain(server.iter()) {
+        messages.insert(name.to_string(), json!({ "name": name, "payload": payload }));
+    }
+    let message_refs = |names: Vec<&&str>| -> Value {
+        json!({ "oneOf": names.iter().map(|n| json!({ "$ref": format!("#/components/messages/{}", n) })).collect::<Vec<_>>() })
+    };
+
+    let hosts: Vec<String> = if config.bind_interfaces.is_empty() {
+        vec![config.host.clone()]
+    } else {
+        config.bind_interfaces.clone()
+    };
+    let servers: Map<String, Value> = hosts.iter().enumerate()
+        .map(|(i, host)| (format!("ws{}", i), json!({
+            "url": format!("{}:{}", host, config.port),
+            "protocol": "ws",
+            "description": format!("Máx. {} clientes", config.max_clients)
+        })))
+        .collect();
+
+    json!({
+        "asyncapi": "2.6.0",
+        "info": {
+            "title": "PLC HMI - protocolo WebSocket",
+            "version": env!("CARGO_PKG_VERSION"),
+            "x-protocol-version": PROTOCOL_VERSION,
+            "description": format!(
+                "Gerado em {}. Tags em modo intervalo são agrupados em ciclos de 500 ms (1-3 s), 2 s (4-7 s) e superiores; tags em modo \"change\" são enviados ao mudar. Áreas: {}. Categorias: {}.",
+                chrono::Utc::now().to_rfc3339(),
+                if areas.is_empty() { "-".to_string() } else { areas.iter().cloned().collect::<Vec<_>>().join(", ") },
+                if categories.is_empty() { "-".to_string() } else { categories.iter().cloned().collect::<Vec<_>>().join(", ") },
+            )
+        },
+        "servers": servers,
+        "channels": {
+            "/": {
+                "publish": {
+                    "summary": "Comandos do cliente (JSON com campo type)",
+                    "message": message_refs(client.keys().collect())
+                },
+                "subscribe": {
+                    "summary": "Dados de tags e respostas do servidor",
+                    "message": message_refs(server.keys().collect())
+                }
+            }
+        },
+        "components": { "messages": messages }
+    })
+}
+
+// ============================================================================
+// VERSÃO DO PROTOCOLO E NEGOCIAÇÃO DE RECURSOS (HELLO)
+// ============================================================================
+//
+// Ao conectar, o servidor envia WELCOME com a versão do protocolo e os recursos
+// suportados. Clientes novos respondem com HELLO escolhendo os recursos; a partir
+// daí os dados de tags chegam no envelope TAG_DATA no formato pedido. Clientes
+// que nunca enviam HELLO (dashboards já instalados) continuam no formato v1:
+// mapa tag -> texto, em JSON ou "MSGPACK:" + base64 - ou em MessagePack puro
+// num frame binário se o handshake pedir ?format=binary (~33% menos banda).
+// Versão, recursos e mensagens do handshake vêm do crate plc-hmi-client, o
+// mesmo que os clientes Rust/TypeScript usam para ler o protocolo.
+
+pub use plc_hmi_client::protocol::{
+    FEATURE_BINARY, FEATURE_TAG_TIMESTAMPS, FEATURE_TYPED_VALUES, LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION, SUPPORTED_FEATURES,
+};
+use plc_hmi_client::protocol::ServerMessage;
+
+pub const FRAME_FORMAT_PARAM: &str = "format"; // ?format=binary na URL do handshake
+
+/// Cliente pediu frames binários na query string do handshake (?format=binary)
+pub fn binary_frames_requested(query: Option<&str>) -> bool {
+    query.is_some_and(|query| query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .any(|(key, value)| key == FRAME_FORMAT_PARAM && value.eq_ignore_ascii_case(FEATURE_BINARY)))
+}
+
+/// Recursos negociados por cliente (alterados pelo HELLO, lidos pelos broadcasts)
+#[derive(Debug, Default)]
+pub struct ClientFeatures {
+    pub negotiated: AtomicBool, // HELLO v2+ recebido: usar envelope TAG_DATA
+    pub binary: AtomicBool,
+    pub typed_values: AtomicBool,
+    pub tag_timestamps: AtomicBool,
+}
+
+impl ClientFeatures {
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            (FEATURE_BINARY, &self.binary),
+            (FEATURE_TYPED_VALUES, &self.typed_values),
+            (FEATURE_TAG_TIMESTAMPS, &self.tag_timestamps),
+        ]
+        .into_iter()
+        .filter(|(_, flag)| flag.load(Ordering::SeqCst))
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
+
+/// Primeira mensagem enviada a cada cliente
+pub fn welcome_message(client_id: u64) -> Value {
+    ServerMessage::Welcome {
+        protocol_version: PROTOCOL_VERSION,
+        min_protocol_version: LEGACY_PROTOCOL_VERSION,
+        features: SUPPORTED_FEATURES.iter().map(|f| f.to_string()).collect(),
+        client_id,
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        instance: crate::config::current_instance().into(),
+    }
+    .to_json()
+}
+
+/// Aplica o HELLO do cliente e monta o HELLO_ACK com o que foi aceito
+pub fn handle_hello(features: &ClientFeatures, cmd: &Value) -> Value {
+    let requested_version = cmd.get("protocol_version").and_then(|v| v.as_u64()).unwrap_or(LEGACY_PROTOCOL_VERSION);
+    let version = requested_version.clamp(LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION);
+    let requested: Vec<String> = cmd.get("features")
+        .and_then(|f| f.as_array())
+        .map(|arr| arr.iter().filter_map(|f| f.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    let rejected: Vec<String> = requested.iter().filter(|f| !SUPPORTED_FEATURES.contains(&f.as_str())).cloned().collect();
+
+    // Protocolo v1 não tem envelope: recursos só valem a partir da v2
+    let negotiated = version >= PROTOCOL_VERSION;
+    let wants = |feature: &str| negotiated && requested.iter().any(|f| f == feature);
+    features.binary.store(wants(FEATURE_BINARY), Ordering::SeqCst);
+    features.typed_values.store(wants(FEATURE_TYPED_VALUES), Ordering::SeqCst);
+    features.tag_timestamps.store(wants(FEATURE_TAG_TIMESTAMPS), Ordering::SeqCst);
+    features.negotiated.store(negotiated, Ordering::SeqCst);
+
+    ServerMessage::HelloAck {
+        success: true,
+        protocol_version: version,
+        features: features.enabled().into_iter().map(str::to_string).collect(),
+        rejected_features: rejected,
+        public: None,
+        instance: crate::config::current_instance().into(),
+        message: None,
+    }
+    .to_json()
+}
@@ -2,6 +2,7 @@ import React, { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { Database, CheckCircle, X, Plus, Trash2, RefreshCw, List, Search, Eye, Table, Key, ChevronDown, ChevronRight, Expand, Minimize2, EyeOff } from 'lucide-react';
 import { useNotifications } from '../../hooks/useNotifications';
+import { errorMessage } from '../../utils/appError';
 
 interface PostgresConfig {
   host: string;
@@ -38,10 +39,6 @@ interface DatabaseInspection {
   total_tables: number;
 }
 
-const getErrorMessage = (error: unknown): string => {
-  return error instanceof Error ? error.message : String(error);
-};
-
 export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClose }) => {
   const [config, setConfig] = useState<PostgresConfig>({
     host: "localhost",
@@ -120,7 +117,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
         message: `Conexão bem-sucedida com ${config.host}:${config.port} usando usuário "${config.user}"`
       });
     } catch (e: unknown) {
-      setMessage(getErrorMessage(e));
+      setMessage(errorMessage(e));
       addNotification({
         type: 'error',
         title: 'Falha na Conexão PostgreSQL',
@@ -151,7 +148,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
         message: `Servidor PostgreSQL funcionando! Database padrão 'postgres' acessível em ${config.host}:${config.port}`
       });
     } catch (e: unknown) {
-      setMessage(getErrorMessage(e));
+      setMessage(errorMessage(e));
       addNotification({
         type: 'error',
         title: 'Erro na Database Padrão',
@@ -185,11 +182,11 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
         onClose();
       }, 1500);
     } catch (e: unknown) {
-      setMessage(getErrorMessage(e));
+      setMessage(errorMessage(e));
       addNotification({
         type: 'error',
         title: 'Erro ao Salvar Configuração',
-        message: `Não foi possível salvar a configuração PostgreSQL no banco local - ${getErrorMessage(e)}`
+        message: `Não foi possível salvar a configuração PostgreSQL no banco local - ${errorMessage(e)}`
       });
     }
     setLoading(false);
@@ -224,7 +221,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
       addNotification({
         type: 'error',
         title: 'Erro ao Listar Bancos',
-        message: `Não foi possível carregar a lista de bancos: ${getErrorMessage(e)}`
+        message: `Não foi possível carregar a lista de bancos: ${errorMessage(e)}`
       });
     }
     setDatabaseLoading(false);
@@ -264,7 +261,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
       addNotification({
         type: 'error',
         title: 'Erro ao Criar Banco',
-        message: `Não foi possível criar o banco '${newDatabaseName}': ${getErrorMessage(e)}`
+        message: `Não foi possível criar o banco '${newDatabaseName}': ${errorMessage(e)}`
       });
     }
     setDatabaseLoading(false);
@@ -302,7 +299,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
       addNotification({
         type: 'error',
         title: 'Erro ao Excluir Banco',
-        message: `Não foi possível excluir o banco '${databaseName}': ${getErrorMessage(e)}`
+        message: `Não foi possível excluir o banco '${databaseName}': ${errorMessage(e)}`
       });
     }
     setDatabaseLoading(false);
@@ -338,7 +335,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
       addNotification({
         type: 'error',
         title: 'Erro na Verificação',
-        message: `Não foi possível verificar os bancos: ${getErrorMessage(e)}`
+        message: `Não foi possível verificar os bancos: ${errorMessage(e)}`
       });
     }
     setDatabaseLoading(false);
@@ -375,7 +372,7 @@ export const PostgresConfigPanel: React.FC<PostgresConfigPanelProps> = ({ onClos
       addNotification({
         type: 'error',
         title: 'Erro na Inspeção',
-        message: `Não foi possível inspecionar o banco '${databaseName}': ${getErrorMessage(e)}`
+        message: `Não foi possível inspecionar o banco '${databaseName}': ${errorMessage(e)}`
       });
     }
     setInspectionLoading(false);
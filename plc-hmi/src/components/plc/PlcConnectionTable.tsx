@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react';
-import { useTcpServer } from '../../hooks/useTcpServer';
+import { useTcpServer, type ConnectionBatchOperation } from '../../hooks/useTcpServer';
 import {
   Wifi,
   WifiOff,
@@ -16,6 +16,7 @@ import {
   Settings,
   Tags,
   Database,
+  Layers,
 } from 'lucide-react';
 import PlcIcon from '../../assets/Plc.svg';
 import { PlcStructureModal } from './PlcStructureModal';
@@ -30,6 +31,7 @@ interface ConnectedPlc {
   status: 'connected' | 'disconnected' | 'blocked';
   bytesReceived: number;
   isActive: boolean; // Se está realmente conectado agora
+  blockRemainingS?: number | null; // Segundos até o desbloqueio automático (null = sem prazo)
 }
 
 interface PlcVariable {
@@ -42,7 +44,7 @@ interface PlcVariable {
 interface PlcDataPacket {
   ip: string;
   timestamp: number;
-  raw_data: number[];
+  raw_data?: number[]; // Somente via get_latest_raw_frame
   size: number;
   variables: PlcVariable[];
 }
@@ -84,8 +86,32 @@ export const PlcConnectionTable: React.FC = () => {
       connectionStats,
       disconnectFromPlc,
       allowPlcReconnect,
+      runConnectionBatch,
     } = useTcpServer();
 
+    // 🆕 Paleta de operações em lote
+    const [isBatchMenuOpen, setIsBatchMenuOpen] = useState(false);
+    const [batchRunning, setBatchRunning] = useState(false);
+    const [batchSummary, setBatchSummary] = useState<string | null>(null);
+    // 🆕 Duração do bloqueio ao desconectar (0 = até desbloqueio manual)
+    const [blockMinutes, setBlockMinutes] = useState(0);
+
+    const batchOperations: { id: ConnectionBatchOperation; label: string; confirm?: string }[] = [
+      { id: 'disconnect_all', label: 'Desconectar todos', confirm: 'Desconectar e bloquear TODOS os PLCs conectados?' },
+      { id: 'allow_reconnect_all', label: 'Permitir reconexão de todos' },
+      { id: 'clear_health', label: 'Limpar health' },
+      { id: 'reset_statistics', label: 'Zerar estatísticas', confirm: 'Zerar bytes recebidos e contagem de PLCs?' },
+    ];
+
+    const handleBatchOperation = async (operation: typeof batchOperations[number]) => {
+      setIsBatchMenuOpen(false);
+      if (operation.confirm && !window.confirm(operation.confirm)) return;
+      setBatchRunning(true);
+      const { message } = await runConnectionBatch(operation.id, operation.id === 'disconnect_all' ? blockMinutes : undefined);
+      setBatchSummary(message);
+      setBatchRunning(false);
+    };
+
     // Função para checar tags desativadas de um PLC
     const checkDisabledTags = async (plcIp: string) => {
       try {
@@ -213,7 +239,7 @@ export const PlcConnectionTable: React.FC = () => {
       try {
         // Buscar lista completa de PLCs conhecidos (conectados, desconectados, bloqueados)
         const { invoke } = await import('@tauri-apps/api/core');
-        const allKnownPlcs = await invoke<[string, string][]>('get_all_known_plcs');
+        const allKnownPlcs = await invoke<[string, string, number | null][]>('get_all_known_plcs');
         
         console.log('🔍 Todos PLCs conhecidos:', allKnownPlcs);
         
@@ -227,11 +253,12 @@ export const PlcConnectionTable: React.FC = () => {
           });
           
           // Atualizar/adicionar PLCs baseado no status do backend
-          allKnownPlcs.forEach(([ip, status]) => {
+          allKnownPlcs.forEach(([ip, status, blockRemainingS]) => {
             if (updated.has(ip)) {
               const plc = updated.get(ip)!;
               plc.status = status as 'connected' | 'disconnected' | 'blocked';
               plc.isActive = status === 'connected';
+              plc.blockRemainingS = blockRemainingS;
               if (status === 'connected') {
                 plc.lastActivity = new Date();
               }
@@ -247,6 +274,7 @@ export const PlcConnectionTable: React.FC = () => {
                 status: status as 'connected' | 'disconnected' | 'blocked',
                 bytesReceived: 0,
                 isActive: status === 'connected',
+                blockRemainingS,
               });
             }
           });
@@ -293,28 +321,35 @@ export const PlcConnectionTable: React.FC = () => {
   useEffect(() => {
     if (!isModalOpen || !selectedPlcIp) return;
 
-    const setupDataListener = async () => {
-      const { listen } = await import('@tauri-apps/api/event');
-      const unlisten = await listen<PlcDataPacket>('plc-data-received', (event) => {
-        const receivedData = event.payload;
-        // Atualizar apenas se for do PLC que está aberto no modal
+    const setupDataChannel = async () => {
+      const { Channel, invoke } = await import('@tauri-apps/api/core');
+      // Channel dedicado: recebe apenas pacotes do PLC aberto no modal
+      const channel = new Channel<PlcDataPacket>();
+      channel.onmessage = (receivedData) => {
         if (receivedData.ip === selectedPlcIp) {
           setPlcData(receivedData);
         }
+      };
+      const subscriptionId = await invoke<number>('subscribe_plc_data_channel', {
+        plcIps: [selectedPlcIp],
+        decimation: 1,
+        onData: channel,
       });
-      return unlisten;
+      return () => {
+        invoke('unsubscribe_plc_data_channel', { subscriptionId }).catch(() => {});
+      };
     };
 
-    const cleanup = setupDataListener();
+    const cleanup = setupDataChannel();
     return () => {
-      cleanup.then(unlisten => unlisten());
+      cleanup.then(unsubscribe => unsubscribe()).catch(() => {});
     };
   }, [isModalOpen, selectedPlcIp]);
 
   const handleDisconnectPlc = async (plc: ConnectedPlc) => {
     try {
       console.log('🔌 Desconectando PLC:', plc.ip);
-      const result = await disconnectFromPlc(plc.ip);
+      const result = await disconnectFromPlc(plc.ip, blockMinutes);
       console.log('✅ Resultado:', result);
       
       // Marcar como bloqueado localmente (atualização imediata)
@@ -425,6 +460,48 @@ export const PlcConnectionTable: React.FC = () => {
               {knownPlcs.size} {knownPlcs.size === 1 ? 'dispositivo' : 'dispositivos'} registrados
             </p>
           </div>
+          <div className="flex items-center gap-3">
+          <select
+            value={blockMinutes}
+            onChange={(e) => setBlockMinutes(Number(e.target.value))}
+            className="px-3 py-3 rounded-lg border border-[#BECACC] bg-[#F1F4F4] text-sm text-[#212E3E]"
+            title="Duração do bloqueio ao desconectar"
+          >
+            <option value={0}>Bloqueio: até desbloquear</option>
+            <option value={5}>Bloqueio: 5 min</option>
+            <option value={10}>Bloqueio: 10 min</option>
+            <option value={30}>Bloqueio: 30 min</option>
+            <option value={60}>Bloqueio: 1 h</option>
+          </select>
+          <div className="relative">
+            <button
+              onClick={() => setIsBatchMenuOpen(open => !open)}
+              disabled={batchRunning}
+              className="flex items-center gap-2 px-4 py-3 rounded-lg bg-[#212E3E] text-white text-sm font-semibold hover:bg-[#212E3E]/90 disabled:opacity-50 transition-all duration-200"
+              title="Operações em todas as conexões"
+            >
+              <Layers size={16} />
+              {batchRunning ? 'Executando...' : 'Ações em lote'}
+            </button>
+            {isBatchMenuOpen && (
+              <div className="absolute right-0 mt-2 w-64 bg-white rounded-lg border border-gray-200 shadow-xl z-20 py-1">
+                {batchOperations.map(operation => (
+                  <button
+                    key={operation.id}
+                    onClick={() => handleBatchOperation(operation)}
+                    className="w-full text-left px-4 py-2 text-sm text-[#212E3E] hover:bg-[#F1F4F4]"
+                  >
+                    {operation.label}
+                  </button>
+                ))}
+              </div>
+            )}
+            {batchSummary && (
+              <div className="absolute right-0 mt-2 w-64 text-xs text-gray-600 bg-[#F1F4F4] border border-[#BECACC] rounded-lg px-3 py-2 z-10" onClick={() => setBatchSummary(null)}>
+                {batchSummary}
+              </div>
+            )}
+          </div>
           {connectionStats && (
             <div className="flex gap-3">
               <div className="bg-[#F1F4F4] rounded-lg px-5 py-3 border border-[#BECACC] text-center">
@@ -437,6 +514,7 @@ export const PlcConnectionTable: React.FC = () => {
               </div>
             </div>
           )}
+          </div>
         </div>
 
         {/* Divisor */}
@@ -552,6 +630,9 @@ export const PlcConnectionTable: React.FC = () => {
                       }`}
                     >
                       {getStatusText(plc.status)}
+                      {plc.status === 'blocked' && plc.blockRemainingS != null
+                        ? ` (${Math.floor(plc.blockRemainingS / 60)}:${String(plc.blockRemainingS % 60).padStart(2, '0')})`
+                        : null}
                     </span>
                   </div>
 
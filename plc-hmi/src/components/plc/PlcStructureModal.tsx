@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Settings, Save, X, Copy, AlertCircle, CheckCircle } from 'lucide-react';
+import { errorMessage } from '../../utils/appError';
 
 interface DataBlockConfig {
   data_type: string;
@@ -8,6 +9,46 @@ interface DataBlockConfig {
   name: string;
 }
 
+// 🆕 Ordem dos bytes dos valores numéricos (espelha ByteOrder do backend)
+type ByteOrder = 'big' | 'little' | 'byte-swapped' | 'word-swapped';
+
+const BYTE_ORDER_OPTIONS: { value: ByteOrder; label: string }[] = [
+  { value: 'big', label: 'Big-endian (Siemens S7) — AA BB CC DD' },
+  { value: 'little', label: 'Little-endian (Beckhoff, PC) — DD CC BB AA' },
+  { value: 'byte-swapped', label: 'Bytes trocados — BB AA DD CC' },
+  { value: 'word-swapped', label: 'Words trocadas (Modbus) — CC DD AA BB' },
+];
+
+// 🆕 Separação dos frames no TCP (espelha FrameMode do backend)
+type FrameMode = 'fixed' | 'length-prefix-2' | 'length-prefix-4' | 'newline' | 'stx-etx';
+
+const FRAME_MODE_OPTIONS: { value: FrameMode; label: string }[] = [
+  { value: 'fixed', label: 'Tamanho fixo (tamanho da estrutura)' },
+  { value: 'length-prefix-2', label: 'Prefixo de tamanho 2 bytes + payload' },
+  { value: 'length-prefix-4', label: 'Prefixo de tamanho 4 bytes + payload' },
+  { value: 'newline', label: 'Payload terminado em \\n' },
+  { value: 'stx-etx', label: 'STX (0x02) + payload + ETX (0x03)' },
+];
+
+// 🆕 Tamanho em bytes de cada elemento (espelha data_type_size do backend)
+const NUMERIC_SIZES: Record<string, number> = {
+  'BYTE': 1, 'WORD': 2, 'INT': 2, 'DWORD': 4, 'DINT': 4,
+  'REAL': 4, 'LWORD': 8, 'LINT': 8, 'LREAL': 8,
+  // 🆕 Data/hora IEC 61131 (valor publicado em texto ISO)
+  'TIME': 4, 'DATE': 2, 'TOD': 4, 'TIME_OF_DAY': 4, 'DT': 8, 'DATE_AND_TIME': 8,
+};
+
+const dataTypeSize = (dataType: string): number => {
+  if (NUMERIC_SIZES[dataType]) return NUMERIC_SIZES[dataType];
+  // STRING[n] = n + 2, WSTRING[n] = 2n + 4, CHAR[n] = n (sem [n]: 254 / 254 / 1)
+  const text = dataType.match(/^(STRING|WSTRING|CHAR)(?:\[(\d+)\])?$/);
+  if (!text) return 0;
+  const len = text[2] ? parseInt(text[2]) : (text[1] === 'CHAR' ? 1 : 254);
+  if (text[1] === 'STRING') return len + 2;
+  if (text[1] === 'WSTRING') return len * 2 + 4;
+  return len;
+};
+
 interface PlcStructureModalProps {
   plcIp: string;
   onClose: () => void;
@@ -22,6 +63,8 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
   const [preview, setPreview] = useState<string>('');
   const [hasUnsavedChanges, setHasUnsavedChanges] = useState(false);
   const [showConfirmClose, setShowConfirmClose] = useState(false);
+  const [byteOrder, setByteOrder] = useState<ByteOrder>('big');
+  const [framing, setFraming] = useState<FrameMode>('fixed');
 
   // Carregar configuração existente ao abrir
   useEffect(() => {
@@ -39,6 +82,8 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
           .map((b: DataBlockConfig) => `${b.name}[0..${b.count - 1}]`)
           .join('\n');
         setStructureText(text);
+        setByteOrder(config.byte_order ?? 'big');
+        setFraming(config.framing ?? 'fixed');
       } else {
         // Exemplo padrão
         setStructureText('Word[0..64]\nInt[0..64]\nReal[0..64]\nReal2[0..64]');
@@ -81,7 +126,7 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
       }
       
       // Formato: Nome Array[0..64] of Type
-      match = trimmed.match(/^(\w+)\s+Array\[0\.\.(\d+)\]\s+of\s+(\w+)$/i);
+      match = trimmed.match(/^(\w+)\s+Array\[0\.\.(\d+)\]\s+of\s+(\w+(?:\[\d+\])?)$/i);
       if (match) {
         const name = match[1];
         const lastIndex = parseInt(match[2]);
@@ -97,7 +142,7 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
         const nameOrType = match[1].toUpperCase();
         const count = parseInt(match[2]);
         
-        const knownTypes = ['BYTE', 'WORD', 'INT', 'DWORD', 'DINT', 'REAL', 'LWORD', 'LINT', 'LREAL'];
+        const knownTypes = Object.keys(NUMERIC_SIZES);
         if (knownTypes.includes(nameOrType)) {
           blocks.push({ data_type: nameOrType, count, name: nameOrType });
         } else {
@@ -116,13 +161,8 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
   };
 
   const calculateTotalSize = (blocks: DataBlockConfig[]): number => {
-    const sizes: Record<string, number> = {
-      'BYTE': 1, 'WORD': 2, 'INT': 2, 'DWORD': 4, 'DINT': 4, 
-      'REAL': 4, 'LWORD': 8, 'LINT': 8, 'LREAL': 8,
-    };
-    
     return blocks.reduce((total, block) => {
-      const size = sizes[block.data_type] || 0;
+      const size = dataTypeSize(block.data_type);
       return total + (size * block.count);
     }, 0);
   };
@@ -145,11 +185,7 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
       
       const totalSize = calculateTotalSize(blocks);
       const lines = blocks.map(b => {
-        const sizes: Record<string, number> = {
-          'BYTE': 1, 'WORD': 2, 'INT': 2, 'DWORD': 4, 'DINT': 4, 
-          'REAL': 4, 'LWORD': 8, 'LINT': 8, 'LREAL': 8,
-        };
-        const size = sizes[b.data_type] || 0;
+        const size = dataTypeSize(b.data_type);
         return `  ${b.name}: ${b.data_type} × ${b.count} = ${b.count * size} bytes`;
       });
       
@@ -158,7 +194,7 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
       setHasUnsavedChanges(true);
     } catch (err) {
       setPreview('❌ Erro ao processar estrutura');
-      setError(String(err));
+      setError(errorMessage(err));
     }
   }, [structureText]);
 
@@ -176,7 +212,9 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
 
       await invoke('save_plc_structure', {
         plcIp,
-        blocks
+        blocks,
+        byteOrder,
+        framing
       });
       
       console.log(`✅ Configuração salva para ${plcIp}`);
@@ -185,7 +223,7 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
       onClose();
     } catch (err) {
       console.error('Erro ao salvar:', err);
-      setError(`Falha ao salvar: ${String(err)}`);
+      setError(`Falha ao salvar: ${errorMessage(err)}`);
     } finally {
       setSaving(false);
     }
@@ -289,6 +327,42 @@ export const PlcStructureModal: React.FC<PlcStructureModalProps> = ({ plcIp, onC
                 </p>
               </div>
 
+              <div>
+                <label className="text-sm font-bold text-[#212E3E] mb-2 block">
+                  Ordem dos bytes:
+                </label>
+                <select
+                  value={byteOrder}
+                  onChange={(e) => {
+                    setByteOrder(e.target.value as ByteOrder);
+                    setHasUnsavedChanges(true);
+                  }}
+                  className="w-full bg-white text-[#212E3E] rounded-lg p-2.5 text-sm border border-[#BECACC] focus:border-[#212E3E] focus:ring-2 focus:ring-[#212E3E]/20 focus:outline-none"
+                >
+                  {BYTE_ORDER_OPTIONS.map(option => (
+                    <option key={option.value} value={option.value}>{option.label}</option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label className="text-sm font-bold text-[#212E3E] mb-2 block">
+                  Separação dos frames (TCP):
+                </label>
+                <select
+                  value={framing}
+                  onChange={(e) => {
+                    setFraming(e.target.value as FrameMode);
+                    setHasUnsavedChanges(true);
+                  }}
+                  className="w-full bg-white text-[#212E3E] rounded-lg p-2.5 text-sm border border-[#BECACC] focus:border-[#212E3E] focus:ring-2 focus:ring-[#212E3E]/20 focus:outline-none"
+                >
+                  {FRAME_MODE_OPTIONS.map(option => (
+                    <option key={option.value} value={option.value}>{option.label}</option>
+                  ))}
+                </select>
+              </div>
+
               <button
                 onClick={() => setStructureText('Word[0..64]\nInt[0..64]\nReal[0..64]\nReal2[0..64]')}
                 className="w-full bg-white hover:bg-[#F1F4F4] text-[#212E3E] rounded-lg p-2.5 flex items-center justify-center gap-2 transition-colors text-sm font-semibold border border-[#BECACC]"
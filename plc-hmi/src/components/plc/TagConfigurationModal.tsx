@@ -2,6 +2,7 @@ import React, { useState, useEffect, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { save, open } from '@tauri-apps/plugin-dialog';
 import { Tag, X, Plus, Trash2, Eye, CheckCircle, Power, Pencil, Filter, Download, Upload, FileSpreadsheet, AlertCircle, ChevronLeft, ChevronRight, Search } from 'lucide-react';
+import { errorMessage } from '../../utils/appError';
 
 // ============================================================================
 // INTERFACES
@@ -47,6 +48,21 @@ interface TagMapping {
   // 🆕 CAMPOS PARA SUBSCRIBE INTELIGENTE
   area?: AreaType;      // ENH, ESV, PJU, PMO, SCO, EDR, GER
   category?: CategoryType; // PROC, FAULT, EVENT, ALARM, CMD
+  // 🆕 COALESCÊNCIA PARA TAGS EM MODO on_change
+  min_resend_ms?: number;
+  debounce_ms?: number;
+  // 🆕 CONVERSÃO DE UNIDADE (unit → display_unit)
+  display_unit?: string;
+  display_decimals?: number; // 🆕 Casas decimais publicadas (0-6)
+  // 🆕 TAG CRÍTICO: mudanças enviadas na hora no WebSocket (fora dos lotes)
+  critical?: boolean;
+  // 🆕 ESCALONAMENTO LINEAR (raw → engenharia) + offset e BANDA MORTA (% da faixa)
+  raw_min?: number;
+  raw_max?: number;
+  eng_min?: number;
+  eng_max?: number;
+  scale_offset?: number;
+  deadband_pct?: number;
 }
 
 interface ImportedTag {
@@ -64,6 +80,30 @@ interface ImportedTag {
   category?: CategoryType;
 }
 
+// 🆕 Resultado de operações em lote (tudo ou nada)
+interface TagItemResult {
+  index: number;
+  tag_name?: string;
+  variable_path?: string;
+  id?: number;
+  status: 'saved' | 'deleted' | 'skipped' | 'failed' | 'rolled_back';
+  error?: string;
+}
+
+interface TagBatchResult {
+  committed: boolean;
+  applied: number;
+  failed: number;
+  results: TagItemResult[];
+}
+
+const describeBatchFailure = (result: TagBatchResult): string => {
+  const failures = result.results
+    .filter(r => r.status === 'failed')
+    .map(r => `${r.tag_name ?? `ID ${r.id}`}: ${r.error}`);
+  return `Nenhuma alteração aplicada (${result.failed} com erro) - ${failures.join('; ')}`;
+};
+
 interface TagConfigurationModalProps {
   plcIp: string;
   onClose: () => void;
@@ -198,7 +238,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
       setAvailableVariables(variables);
     } catch (err) {
       console.error('Erro ao carregar dados:', err);
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -395,8 +435,8 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
         await invoke('write_file', { path: filePath, content: csvContent });
         console.log('✅ CSV exportado:', rows.length, 'linhas');
       }
-    } catch (err: any) {
-      const msg = err?.message || String(err);
+    } catch (err) {
+      const msg = errorMessage(err);
       if (msg.includes('os error 32')) {
         setError('Arquivo aberto no Excel. Feche e tente novamente.');
       } else {
@@ -521,7 +561,11 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
         category: tag.category || '',
       }));
 
-      await invoke('save_tag_mappings_bulk', { tags: tagsToSave });
+      const result = await invoke<TagBatchResult>('save_tag_mappings_bulk', { tags: tagsToSave });
+      if (!result.committed) {
+        setError(describeBatchFailure(result));
+        return;
+      }
       await loadData();
 
       window.dispatchEvent(new CustomEvent('plc-tags-updated', { detail: { plcIp } }));
@@ -532,7 +576,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
       
       console.log('✅ Importação concluída:', validTags.length, 'tags criados');
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setSaving(false);
     }
@@ -613,7 +657,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
         collect_interval_s: 1,
       });
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setSaving(false);
     }
@@ -625,7 +669,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
       await loadData();
       window.dispatchEvent(new CustomEvent('plc-tags-updated', { detail: { plcIp } }));
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
@@ -635,7 +679,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
       await loadData();
       window.dispatchEvent(new CustomEvent('plc-tags-updated', { detail: { plcIp } }));
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
@@ -652,7 +696,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
       setEditTagData(null);
       window.dispatchEvent(new CustomEvent('plc-tags-updated', { detail: { plcIp } }));
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setSaving(false);
     }
@@ -664,12 +708,16 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
 
     try {
       setSaving(true);
-      await invoke('delete_tag_mappings_bulk', { ids: Array.from(selectedTags) });
+      const result = await invoke<TagBatchResult>('delete_tag_mappings_bulk', { ids: Array.from(selectedTags) });
+      if (!result.committed) {
+        setError(describeBatchFailure(result));
+        return;
+      }
       setSelectedTags(new Set());
       await loadData();
       window.dispatchEvent(new CustomEvent('plc-tags-updated', { detail: { plcIp } }));
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setSaving(false);
     }
@@ -689,7 +737,7 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
         timestamp: new Date().toLocaleTimeString()
       });
     } catch (err) {
-      setError(`Erro ao obter preview: ${err}`);
+      setError(`Erro ao obter preview: ${errorMessage(err)}`);
     }
   };
 
@@ -1092,6 +1140,20 @@ export const TagConfigurationModal: React.FC<TagConfigurationModalProps> = ({ pl
                             }`}>
                               {tag.enabled ? 'Ativo' : 'Inativo'}
                             </span>
+                            {editingTagId === tag.id ? (
+                              <label className="mt-1 flex items-center justify-center gap-1 text-xs text-red-700" title="Envio imediato no WebSocket (bits de segurança)">
+                                <input
+                                  type="checkbox"
+                                  checked={!!editTagData?.critical}
+                                  onChange={e => setEditTagData({ ...editTagData, critical: e.target.checked })}
+                                />
+                                Crítico
+                              </label>
+                            ) : tag.critical && (
+                              <span className="ml-1 inline-flex px-1.5 py-0.5 text-xs font-semibold rounded bg-red-100 text-red-700" title="Envio imediato no WebSocket">
+                                ⚡ Crítico
+                              </span>
+                            )}
                           </td>
                           <td className="px-2 py-2">
                             <div className="flex items-center justify-end gap-0.5">
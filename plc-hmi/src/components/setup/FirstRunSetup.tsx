@@ -11,6 +11,7 @@ import {
   ArrowRight,
   Settings
 } from 'lucide-react';
+import { errorMessage } from '../../utils/appError';
 
 
 interface SetupModalProps {
@@ -54,7 +55,7 @@ export const FirstRunSetup: React.FC<SetupModalProps> = ({ onComplete }) => {
       }
     } catch (err) {
       console.error('Erro ao selecionar caminho:', err);
-      setError(`Erro ao abrir seletor: ${err}`);
+      setError(`Erro ao abrir seletor: ${errorMessage(err)}`);
     }
   };
 
@@ -79,8 +80,8 @@ export const FirstRunSetup: React.FC<SetupModalProps> = ({ onComplete }) => {
         onComplete();
       }, 2000);
 
-    } catch (err: any) {
-      setError(err || 'Erro ao salvar configuração');
+    } catch (err) {
+      setError(errorMessage(err) || 'Erro ao salvar configuração');
     } finally {
       setValidating(false);
     }
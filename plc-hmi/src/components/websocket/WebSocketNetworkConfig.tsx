@@ -1,7 +1,10 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { save } from '@tauri-apps/plugin-dialog';
+import { errorMessage } from '../../utils/appError';
 import { 
   Network, 
+  FileJson,
   Check, 
   X, 
   ChevronDown, 
@@ -119,7 +122,26 @@ export const WebSocketNetworkConfig: React.FC<WebSocketNetworkConfigProps> = ({
       onClose();
     } catch (error) {
       console.error('❌ Erro:', error);
-      alert(`Erro: ${error}`);
+      alert(`Erro: ${errorMessage(error)}`);
+    }
+  };
+
+  // 🆕 Exporta a documentação do protocolo (AsyncAPI / JSON Schema) gerada pelo backend
+  const handleExportProtocol = async (format: 'asyncapi' | 'json_schema') => {
+    try {
+      const document = await invoke<string>('export_websocket_protocol', { format });
+      const filePath = await save({
+        defaultPath: format === 'asyncapi' ? 'websocket_asyncapi.json' : 'websocket_schema.json',
+        filters: [{ name: 'JSON', extensions: ['json'] }],
+        title: 'Exportar documentação do protocolo WebSocket'
+      });
+      if (filePath) {
+        await invoke('write_file', { path: filePath, content: document });
+        console.log('✅ Documentação do protocolo exportada:', filePath);
+      }
+    } catch (error) {
+      console.error('❌ Erro ao exportar protocolo:', error);
+      alert(`Erro ao exportar protocolo: ${errorMessage(error)}`);
     }
   };
 
@@ -338,6 +360,21 @@ export const WebSocketNetworkConfig: React.FC<WebSocketNetworkConfigProps> = ({
 
         {/* Footer - Seguindo padrão PLC */}
         <div className="px-6 py-4 border-t border-[#BECACC] flex justify-end gap-3 bg-[#F1F4F4]">
+          <button
+            onClick={() => handleExportProtocol('asyncapi')}
+            title="Documentação das mensagens, tags e filtros desta instalação"
+            className="mr-auto px-4 py-2 bg-white hover:bg-[#F1F4F4] text-[#212E3E] rounded-lg font-semibold transition-colors text-sm border border-[#BECACC] flex items-center gap-2"
+          >
+            <FileJson className="w-4 h-4" />
+            AsyncAPI
+          </button>
+          <button
+            onClick={() => handleExportProtocol('json_schema')}
+            className="px-4 py-2 bg-white hover:bg-[#F1F4F4] text-[#212E3E] rounded-lg font-semibold transition-colors text-sm border border-[#BECACC] flex items-center gap-2"
+          >
+            <FileJson className="w-4 h-4" />
+            JSON Schema
+          </button>
           <button
             onClick={onClose}
             className="px-4 py-2 bg-white hover:bg-[#F1F4F4] text-[#212E3E] rounded-lg font-semibold transition-colors text-sm border border-[#BECACC]"
@@ -164,8 +164,8 @@ export const useNotifications = () => {
     const fetchPlcAndTagEvents = async () => {
       try {
         const { invoke } = await import('@tauri-apps/api/core');
-        // get_all_known_plcs retorna [ip, status][]
-        const allPlcs: [string, string][] = await invoke('get_all_known_plcs');
+        // get_all_known_plcs retorna [ip, status, segundos restantes de bloqueio][]
+        const allPlcs: [string, string, number | null][] = await invoke('get_all_known_plcs');
         for (const [plcIp, status] of allPlcs) {
           // Notificar status do PLC (bloqueado, desconectado, conectado)
           if (status === 'blocked') {
@@ -209,10 +209,50 @@ export const useNotifications = () => {
     fetchPlcAndTagEvents();
   }, [addTagDisabledNotification, addNotification]);
 
-  // ...existing code...
+  // 🆕 Carregar notificações persistidas pelo backend (eventos enquanto a UI estava fechada)
+  useEffect(() => {
+    const loadPersistedNotifications = async () => {
+      try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        const persisted = await invoke<any[]>('list_notifications', { unreadOnly: true, limit: 50 });
+        if (persisted.length === 0) return;
+        setState(prevState => {
+          const loaded: Notification[] = persisted
+            .filter(p => !prevState.notifications.some(n => n.id === `db-${p.id}`))
+            .map(p => ({
+              id: `db-${p.id}`,
+              type: p.severity === 'critical' ? 'error' : p.severity === 'warning' ? 'warning' : 'info',
+              title: p.title,
+              message: p.body,
+              timestamp: new Date(p.created_at * 1000),
+              read: false
+            }));
+          const notifications = [...prevState.notifications, ...loaded].slice(0, 50);
+          const unreadCount = notifications.filter(n => !n.read).length;
+          return { notifications, unreadCount };
+        });
+      } catch (e) {
+        // Silencioso
+      }
+    };
+    loadPersistedNotifications();
+  }, []);
+
+  // Sincronizar leitura/limpeza das notificações persistidas com o backend
+  const syncPersisted = useCallback(async (command: string, args: Record<string, unknown>) => {
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke(command, args);
+    } catch (e) {
+      // Silencioso
+    }
+  }, []);
 
   // Marcar notificação como lida
   const markAsRead = useCallback((id: string) => {
+    if (id.startsWith('db-')) {
+      syncPersisted('mark_notifications_read', { ids: [Number(id.slice(3))] });
+    }
     setState(prevState => {
       const notifications = prevState.notifications.map(n =>
         n.id === id ? { ...n, read: true } : n
@@ -221,15 +261,16 @@ export const useNotifications = () => {
       
       return { notifications, unreadCount };
     });
-  }, []);
+  }, [syncPersisted]);
 
   // Marcar todas como lidas
   const markAllAsRead = useCallback(() => {
+    syncPersisted('mark_notifications_read', {});
     setState(prevState => ({
       notifications: prevState.notifications.map(n => ({ ...n, read: true })),
       unreadCount: 0
     }));
-  }, []);
+  }, [syncPersisted]);
 
   // Remover notificação
   const removeNotification = useCallback((id: string) => {
@@ -243,11 +284,12 @@ export const useNotifications = () => {
 
   // Limpar todas as notificações
   const clearAll = useCallback(() => {
+    syncPersisted('clear_notifications', {});
     setState({
       notifications: [],
       unreadCount: 0
     });
-  }, []);
+  }, [syncPersisted]);
 
   // 📡 ESCUTAR EVENTOS REAIS DO BACKEND
   useEffect(() => {
@@ -336,8 +378,8 @@ export const useNotifications = () => {
         });
       }));
       // 📊 MONITORAMENTO DE PERFORMANCE (filtrado)
-      unsubscribes.push(await listen('plc-data-received', (event: any) => {
-        const latencyMs = Math.round(event.payload.processing_time_us / 1000);
+      unsubscribes.push(await listen('plc-data-stats', (event: any) => {
+        const latencyMs = Math.round((event.payload.processing_time_us ?? 0) / 1000);
         if (NotificationFilters.shouldNotifyPerformance('latency', latencyMs)) {
           const notification = NotificationMessages.WARNING.highLatency(event.payload.ip, latencyMs);
           addNotification({ ...notification, plcIp: event.payload.ip });
@@ -1,6 +1,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { errorCode, errorMessage } from '../utils/appError';
 
 export interface PlcData {
   timestamp: string;
@@ -21,6 +22,16 @@ export interface TcpConnection {
   is_plc: boolean;
 }
 
+// 🆕 Operações em lote nas conexões (paleta de comandos)
+export type ConnectionBatchOperation = 'disconnect_all' | 'allow_reconnect_all' | 'clear_health' | 'reset_statistics';
+
+export interface ConnectionBatchResult {
+  operation: ConnectionBatchOperation;
+  affected: string[];
+  failed: { ip: string; error: string }[];
+  summary: string;
+}
+
 // 🆕 Interface para health da conexão
 export interface ConnectionHealth {
   ip: string;
@@ -55,8 +66,10 @@ export const useTcpServer = () => {
       addLog(`✅ ${response}`);
       return { success: true, message: response };
     } catch (error) {
-      addLog(`❌ Erro ao iniciar servidor: ${error}`);
-      return { success: false, message: error as string };
+      // 🆕 Já rodando (ex: iniciado por outra tela): só sincroniza o estado
+      if (errorCode(error) === 'ALREADY_RUNNING') setIsServerRunning(true);
+      addLog(`❌ Erro ao iniciar servidor: ${errorMessage(error)}`);
+      return { success: false, message: errorMessage(error) };
     }
   }, [addLog]);
 
@@ -70,8 +83,9 @@ export const useTcpServer = () => {
       addLog(`🛑 ${response}`);
       return { success: true, message: response };
     } catch (error) {
-      addLog(`❌ Erro ao parar servidor: ${error}`);
-      return { success: false, message: error as string };
+      if (errorCode(error) === 'NOT_RUNNING') setIsServerRunning(false);
+      addLog(`❌ Erro ao parar servidor: ${errorMessage(error)}`);
+      return { success: false, message: errorMessage(error) };
     }
   }, [addLog]);
 
@@ -85,25 +99,44 @@ export const useTcpServer = () => {
       addLog(`🔄 ${response}`);
       return { success: true, message: response };
     } catch (error) {
-      addLog(`❌ Erro ao conectar PLC: ${error}`);
-      return { success: false, message: error as string };
+      addLog(`❌ Erro ao conectar PLC: ${errorMessage(error)}`);
+      return { success: false, message: errorMessage(error) };
+    }
+  }, [addLog]);
+
+  // 🆕 Executa uma operação em lote (registrada na auditoria pelo backend)
+  const runConnectionBatch = useCallback(async (operation: ConnectionBatchOperation, blockMinutes?: number) => {
+    try {
+      const result = await invoke<ConnectionBatchResult>('run_connection_batch', { operation, blockMinutes });
+      addLog(`${result.failed.length > 0 ? '⚠️' : '✅'} ${result.summary}`);
+      result.failed.forEach(f => addLog(`❌ ${f.ip}: ${f.error}`));
+      return { success: result.failed.length === 0, message: result.summary, result };
+    } catch (error) {
+      addLog(`❌ Erro na operação em lote: ${errorMessage(error)}`);
+      return { success: false, message: errorMessage(error), result: null };
     }
   }, [addLog]);
 
-  // Desconecta do PLC
-  const disconnectFromPlc = useCallback(async (clientIp?: string) => {
+  // Desconecta do PLC (sem IP = todos); blockMinutes = bloqueio temporário (0/undefined = até desbloquear)
+  const disconnectFromPlc = useCallback(async (clientIp?: string, blockMinutes?: number) => {
+    if (!clientIp) {
+      const { success, message } = await runConnectionBatch('disconnect_all', blockMinutes);
+      if (success) setIsPlcConnected(false);
+      return { success, message };
+    }
     try {
       const response = await invoke<string>('disconnect_plc', {
-        clientIp: clientIp || 'all'
+        clientIp,
+        blockMinutes
       });
       setIsPlcConnected(false);
       addLog(`🔌 ${response}`);
       return { success: true, message: response };
     } catch (error) {
-      addLog(`❌ Erro ao desconectar PLC: ${error}`);
-      return { success: false, message: error as string };
+      addLog(`❌ Erro ao desconectar PLC: ${errorMessage(error)}`);
+      return { success: false, message: errorMessage(error) };
     }
-  }, [addLog]);
+  }, [addLog, runConnectionBatch]);
 
   // Permite reconexão do PLC (remove da blacklist)
   const allowPlcReconnect = useCallback(async (clientIp: string) => {
@@ -114,8 +147,8 @@ export const useTcpServer = () => {
       addLog(`✅ ${response}`);
       return { success: true, message: response };
     } catch (error) {
-      addLog(`❌ Erro ao permitir reconexão: ${error}`);
-      return { success: false, message: error as string };
+      addLog(`❌ Erro ao permitir reconexão: ${errorMessage(error)}`);
+      return { success: false, message: errorMessage(error) };
     }
   }, [addLog]);
 
@@ -128,7 +161,7 @@ export const useTcpServer = () => {
       addLog(`📡 Encontrados ${discovered.length} PLCs potenciais: ${discovered.join(', ')}`);
       return { success: true, plcs: discovered };
     } catch (error) {
-      addLog(`❌ Erro na descoberta: ${error}`);
+      addLog(`❌ Erro na descoberta: ${errorMessage(error)}`);
       return { success: false, plcs: [] };
     }
   }, [addLog]);
@@ -151,7 +184,7 @@ export const useTcpServer = () => {
       return { success: true, plcs: discovered || [] };
     } catch (error) {
       console.error('Erro no escaneamento:', error);
-      addLog(`❌ Erro no escaneamento: ${error}`);
+      addLog(`❌ Erro no escaneamento: ${errorMessage(error)}`);
 
       // Retornar PLC padrão como fallback
       const fallbackPlcs = [{
@@ -380,6 +413,47 @@ export const useTcpServer = () => {
         });
       });
 
+      // 📉 Taxa de pacotes fora do intervalo esperado (configurado por PLC)
+      const unlistenRateDeviation = await listen<{
+        plc_ip: string,
+        expected_interval_ms: number,
+        actual_interval_ms: number | null,
+        deviation_pct: number | null,
+        level: 'warning' | 'alarm' | 'stalled'
+      }>('plc-rate-deviation', (event) => {
+        const { plc_ip, expected_interval_ms, actual_interval_ms, deviation_pct, level } = event.payload;
+        if (actual_interval_ms === null) {
+          addLog(`📉 TAXA: ${plc_ip} PARADO - nenhum pacote na janela (esperado a cada ${expected_interval_ms} ms)`);
+        } else {
+          const direction = (deviation_pct ?? 0) > 0 ? 'LENTO' : 'RÁPIDO';
+          addLog(`📉 TAXA: ${plc_ip} ${direction} (${level}) - ${Math.round(actual_interval_ms)} ms, esperado ${expected_interval_ms} ms`);
+        }
+      });
+
+      const unlistenRateNormal = await listen<{ plc_ip: string, expected_interval_ms: number }>('plc-rate-normal', (event) => {
+        addLog(`✅ TAXA: ${event.payload.plc_ip} voltou ao intervalo esperado (${event.payload.expected_interval_ms} ms)`);
+      });
+
+      // 📥 Backfill: amostras buferizadas no PLC gravadas no historian com timestamp original
+      const unlistenBackfill = await listen<{
+        ip: string,
+        records: number,
+        rejected_records: number,
+        inserted: number,
+        from_ms: number | null,
+        to_ms: number | null
+      }>('historian-backfill', (event) => {
+        const { ip, records, rejected_records, inserted, from_ms, to_ms } = event.payload;
+        const period = from_ms !== null && to_ms !== null
+          ? ` (${new Date(from_ms).toLocaleString()} → ${new Date(to_ms).toLocaleString()})`
+          : '';
+        addLog(`📥 BACKFILL: ${ip} - ${records} registros, ${inserted} amostras gravadas${rejected_records > 0 ? `, ${rejected_records} rejeitados` : ''}${period}`);
+      });
+
+      const unlistenBackfillError = await listen<{ ip: string, message: string }>('historian-backfill-error', (event) => {
+        addLog(`❌ BACKFILL: ${event.payload.ip} - ${event.payload.message}`);
+      });
+
       // ⏰ Timeout de conexão
       const unlistenConnectionTimeout = await listen<{
         ip: string,
@@ -446,6 +520,10 @@ export const useTcpServer = () => {
         // 🆕 Novos listeners
         unlistenConnectionDead();
         unlistenConnectionSlow();
+        unlistenRateDeviation();
+        unlistenRateNormal();
+        unlistenBackfill();
+        unlistenBackfillError();
         unlistenConnectionTimeout();
         unlistenReadTimeout();
         unlistenHeartbeat();
@@ -508,6 +586,7 @@ export const useTcpServer = () => {
     connectToPlc,
     disconnectFromPlc,
     allowPlcReconnect,
+    runConnectionBatch,
     discoverPlcs,
     scanNetworkForPlcs,
     testPlcConnection,
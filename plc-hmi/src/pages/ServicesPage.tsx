@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { errorCode, errorMessage } from '../utils/appError';
 import {
     Server,
     Database,
@@ -33,6 +34,21 @@ interface WebSocketStats {
     uptime_seconds: number;
     server_status: string;
     broadcast_rate_hz: number;
+    suppressed_events: number;
+    critical_updates_sent: number;
+    critical_latency_last_ms: number;
+    critical_latency_avg_ms: number;
+    critical_latency_max_ms: number;
+    db_degraded: boolean; // 🆕 SQLite lento: cache servindo sem consultar o banco
+}
+
+// 🆕 Retomada automática dos servidores ao reiniciar o app (runtime_state)
+interface RuntimeState {
+    tcp_autostart: boolean;
+    tcp_running: boolean;
+    tcp_port: number;
+    websocket_autostart: boolean;
+    websocket_running: boolean;
 }
 
 export const ServicesPage: React.FC = () => {
@@ -43,6 +59,7 @@ export const ServicesPage: React.FC = () => {
     const [loading, setLoading] = useState(true);
     const [showNetworkConfig, setShowNetworkConfig] = useState(false);
     const [showPostgresConfig, setShowPostgresConfig] = useState(false);
+    const [autostart, setAutostart] = useState({ tcp: true, websocket: true });
 
     const loadInitialStats = useCallback(async () => {
         try {
@@ -73,6 +90,23 @@ export const ServicesPage: React.FC = () => {
         }
     }, []);
 
+    // 🆕 Opções de retomada automática
+    useEffect(() => {
+        invoke<RuntimeState>('get_runtime_state')
+            .then(state => setAutostart({ tcp: state.tcp_autostart, websocket: state.websocket_autostart }))
+            .catch(error => console.error('Erro ao carregar retomada automática:', errorMessage(error)));
+    }, []);
+
+    const handleToggleAutostart = async (service: 'tcp' | 'websocket') => {
+        const next = { ...autostart, [service]: !autostart[service] };
+        try {
+            await invoke('save_autostart_config', next);
+            setAutostart(next);
+        } catch (error) {
+            alert(`Erro ao salvar retomada automática: ${errorMessage(error)}`);
+        }
+    };
+
     // ✅ Carregar estatísticas iniciais
     useEffect(() => {
         loadInitialStats();
@@ -87,7 +121,8 @@ export const ServicesPage: React.FC = () => {
             await invoke('start_tcp_server', { port: 8502 });
             setTcpRunning(true);
         } catch (error) {
-            console.error('Erro ao iniciar TCP:', error);
+            if (errorCode(error) === 'ALREADY_RUNNING') setTcpRunning(true);
+            console.error('Erro ao iniciar TCP:', errorMessage(error));
         }
     };
 
@@ -97,7 +132,8 @@ export const ServicesPage: React.FC = () => {
             setTcpRunning(false);
             setTcpStats(null);
         } catch (error) {
-            console.error('Erro ao parar TCP:', error);
+            if (errorCode(error) === 'NOT_RUNNING') setTcpRunning(false);
+            console.error('Erro ao parar TCP:', errorMessage(error));
         }
     };
 
@@ -125,7 +161,11 @@ export const ServicesPage: React.FC = () => {
             setWsRunning(true);
         } catch (error) {
             console.error('❌ Botão Iniciar: Erro:', error);
-            alert(`Erro ao iniciar WebSocket: ${error}`);
+            if (errorCode(error) === 'ALREADY_RUNNING') {
+                setWsRunning(true);
+                return;
+            }
+            alert(`Erro ao iniciar WebSocket: ${errorMessage(error)}`);
         }
     };
 
@@ -135,7 +175,8 @@ export const ServicesPage: React.FC = () => {
             setWsRunning(false);
             setWsStats(null);
         } catch (error) {
-            console.error('Erro ao parar WebSocket:', error);
+            if (errorCode(error) === 'NOT_RUNNING') setWsRunning(false);
+            console.error('Erro ao parar WebSocket:', errorMessage(error));
         }
     };
 
@@ -172,10 +213,14 @@ export const ServicesPage: React.FC = () => {
             port: '8765',
             icon: Wifi,
             isRunning: wsRunning,
-            status: wsRunning ? 'Transmitindo' : 'Parado',
+            status: wsRunning ? (wsStats?.db_degraded ? 'Degradado (SQLite lento)' : 'Transmitindo') : 'Parado',
             metrics: wsStats && wsRunning ? [
                 { label: 'Dashboards', value: wsStats.active_connections },
-                { label: 'Taxa', value: `${wsStats.broadcast_rate_hz} Hz` }
+                { label: 'Taxa', value: `${wsStats.broadcast_rate_hz} Hz` },
+                { label: 'Suprimidos', value: wsStats.suppressed_events },
+                { label: 'Críticos (méd/máx)', value: wsStats.critical_updates_sent > 0
+                    ? `${wsStats.critical_latency_avg_ms.toFixed(1)}/${wsStats.critical_latency_max_ms.toFixed(1)} ms`
+                    : '-' }
             ] : [],
             onStart: handleStartWebSocket,
             onStop: handleStopWebSocket
@@ -369,6 +414,19 @@ export const ServicesPage: React.FC = () => {
                                                 <span className="font-mono text-xs text-edp-marine bg-gray-100 px-2 py-1 rounded">
                                                     {service.port}
                                                 </span>
+                                                {(service.id === 'tcp' || service.id === 'websocket') && (
+                                                    <label
+                                                        className="flex items-center gap-1.5 text-xs cursor-pointer"
+                                                        title="Se estiver rodando ao fechar, o serviço sobe sozinho na próxima abertura"
+                                                    >
+                                                        <input
+                                                            type="checkbox"
+                                                            checked={autostart[service.id as 'tcp' | 'websocket']}
+                                                            onChange={() => handleToggleAutostart(service.id as 'tcp' | 'websocket')}
+                                                        />
+                                                        Retomar ao reiniciar
+                                                    </label>
+                                                )}
                                             </div>
                                         </div>
 
@@ -0,0 +1,35 @@
+// Erros tipados dos comandos Tauri (src-tauri/src/error.rs)
+// Os comandos rejeitam com { code, message }; outras falhas (JS, plugins) chegam como Error/texto.
+
+export type AppErrorCode =
+  | 'NOT_RUNNING'
+  | 'ALREADY_RUNNING'
+  | 'DB_NOT_INITIALIZED'
+  | 'PLC_NOT_FOUND'
+  | 'NOT_FOUND'
+  | 'TIMEOUT'
+  | 'CONFIG_INVALID'
+  | 'IO'
+  | 'DATABASE'
+  | 'OTHER';
+
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+}
+
+export const isAppError = (error: unknown): error is AppError =>
+  typeof error === 'object' && error !== null &&
+  typeof (error as AppError).code === 'string' &&
+  typeof (error as AppError).message === 'string';
+
+/** Código do erro (texto puro ou desconhecido = 'OTHER') */
+export const errorCode = (error: unknown): AppErrorCode =>
+  isAppError(error) ? error.code : 'OTHER';
+
+/** Mensagem para exibir, qualquer que seja o formato do erro */
+export const errorMessage = (error: unknown): string => {
+  if (isAppError(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+};